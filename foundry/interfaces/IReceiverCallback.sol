@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+interface IReceiverCallback {
+    /**
+     * @notice Called by the Tycho router on the receiver of a `...WithCallback` swap, after sending it the output.
+     * @dev Lets the receiver use the output in the same transaction (e.g. stake it or provide liquidity with it).
+     * Implementations should check that the caller is the Tycho router.
+     *
+     * @param token The output token sent to the receiver, `address(0)` for native ETH.
+     * @param amount The amount of the output token sent to the receiver.
+     * @param data The callback data of the swap.
+     */
+    function receiveSwapOutput(
+        address token,
+        uint256 amount,
+        bytes calldata data
+    ) external;
+}
@@ -40,4 +40,94 @@ library LibSwap {
         executor = address(uint160(bytes20(swap[5:25])));
         protocolData = swap[25:];
     }
+
+    /**
+     * @dev Decodes a split swap of a wide swap graph, whose token indices are
+     * encoded on 2 bytes to support more than 256 tokens.
+     */
+    function decodeWideSplitSwap(bytes calldata swap)
+        internal
+        pure
+        returns (uint16 tokenInIndex, uint16 tokenOutIndex, uint24 split, address executor, bytes calldata protocolData)
+    {
+        tokenInIndex = uint16(bytes2(swap[0:2]));
+        tokenOutIndex = uint16(bytes2(swap[2:4]));
+        split = uint24(bytes3(swap[4:7]));
+        executor = address(uint160(bytes20(swap[7:27])));
+        protocolData = swap[27:];
+    }
+
+    /**
+     * @dev Decodes a split swap whose split is encoded on 4 bytes with a 1e9
+     * basis, for splits of a finer granularity than the 3-byte ones.
+     */
+    function decodePreciseSplitSwap(bytes calldata swap)
+        internal
+        pure
+        returns (uint8 tokenInIndex, uint8 tokenOutIndex, uint32 split, address executor, bytes calldata protocolData)
+    {
+        tokenInIndex = uint8(swap[0]);
+        tokenOutIndex = uint8(swap[1]);
+        split = uint32(bytes4(swap[2:6]));
+        executor = address(uint160(bytes20(swap[6:26])));
+        protocolData = swap[26:];
+    }
+
+    /**
+     * @dev Splits the swaps of an indexed solution into its executor table and
+     * its swaps. The table is a 1-byte count followed by the 20-byte address of
+     * every executor referenced by the swaps.
+     */
+    function decodeExecutorTable(bytes calldata data)
+        internal
+        pure
+        returns (bytes calldata executors, bytes calldata swaps)
+    {
+        uint256 tableEnd = 1 + uint256(uint8(data[0])) * 20;
+        executors = data[1:tableEnd];
+        swaps = data[tableEnd:];
+    }
+
+    /**
+     * @dev Returns the executor at the given index of an executor table
+     */
+    function executorAt(bytes calldata executors, uint8 index)
+        internal
+        pure
+        returns (address executor)
+    {
+        uint256 start = uint256(index) * 20;
+        executor = address(uint160(bytes20(executors[start:start + 20])));
+    }
+
+    /**
+     * @dev Returns arguments required to perform a sequential swap referring to
+     * its executor by its index in the executor table
+     */
+    function decodeIndexedSequentialSwap(
+        bytes calldata swap,
+        bytes calldata executors
+    ) internal pure returns (address executor, bytes calldata protocolData) {
+        executor = executorAt(executors, uint8(swap[0]));
+        protocolData = swap[1:];
+    }
+
+    /**
+     * @dev Returns arguments required to perform a split swap referring to its
+     * executor by its index in the executor table
+     */
+    function decodeIndexedSplitSwap(
+        bytes calldata swap,
+        bytes calldata executors
+    )
+        internal
+        pure
+        returns (uint8 tokenInIndex, uint8 tokenOutIndex, uint24 split, address executor, bytes calldata protocolData)
+    {
+        tokenInIndex = uint8(swap[0]);
+        tokenOutIndex = uint8(swap[1]);
+        split = uint24(bytes3(swap[2:5]));
+        executor = executorAt(executors, uint8(swap[5]));
+        protocolData = swap[6:];
+    }
 }
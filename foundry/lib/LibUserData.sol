@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+library LibUserData {
+    /**
+     * @dev Returns whether the protocol data of an executor has the given
+     * length, optionally followed by the user data segment of the swap:
+     * `user_data_length (uint16) | user_data`. The user data tags the swap
+     * (e.g. with a referral) and is ignored by the executor.
+     */
+    function hasValidLength(bytes calldata data, uint256 length)
+        internal
+        pure
+        returns (bool)
+    {
+        if (data.length == length) {
+            return true;
+        }
+        if (data.length < length + 2) {
+            return false;
+        }
+        uint256 userDataLength = uint16(bytes2(data[length:length + 2]));
+        return data.length == length + 2 + userDataLength;
+    }
+}
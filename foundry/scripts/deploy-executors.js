@@ -94,10 +94,24 @@ const executors_to_deploy = {
             exchange: "BebopExecutor",
             args: ["0xbbbbbBB520d69a9775E85b458C58c648259FAD5F", "0x000000000022D473030F116dDEE9F6B43aC78BA3"]
         },
-        // Args: Hashflow router, Permit2
+        // Args: Hashflow router, WETH, Permit2
         {
             exchange: "HashflowExecutor",
-            args: ["0x55084eE0fEf03f14a305cd24286359A35D735151", "0x000000000022D473030F116dDEE9F6B43aC78BA3"]
+            args: [
+                "0x55084eE0fEf03f14a305cd24286359A35D735151",
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+            ]
+        },
+        // Args: 1inch Limit Order Protocol, Permit2
+        {
+            exchange: "OneInchLOPExecutor",
+            args: ["0x111111125421cA6dc452d289314280a0f8842A65", "0x000000000022D473030F116dDEE9F6B43aC78BA3"]
+        },
+        // Args: Across spoke pool, fill deadline buffer (seconds), Permit2
+        {
+            exchange: "AcrossExecutor",
+            args: ["0x5c7BCd6E7De5423a257D81B442095A1a6ced35C5", 3600, "0x000000000022D473030F116dDEE9F6B43aC78BA3"]
         },
         // Args: liquidity, permit2
         {
@@ -112,10 +126,85 @@ const executors_to_deploy = {
                 "0x000000000022D473030F116dDEE9F6B43aC78BA3"
             ]
         },
-        // Args: deposit pool, permit2
+        // LST executors - Args: permit2, LST, deposit token (zero for ETH), mint target, mint
+        // selector, mint args, redeem target, redeem selector (zero if not redeemable), redeem args
+        // (LstCallArgs: 0 = None, 1 = Amount, 2 = Zero, 3 = TokenAmountZero)
+        // Rocketpool: deposit() on the deposit pool, burn(uint256) on rETH
         {
-            exchange: "RocketpoolExecutor", args: [
+            exchange: "LstExecutor", args: [
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3",
+                "0xae78736Cd615f374D3085123A210448E74Fc6393",
+                "0x0000000000000000000000000000000000000000",
                 "0xCE15294273CFb9D9b628F4D61636623decDF4fdC",
+                ethers.utils.id("deposit()").slice(0, 10),
+                0,
+                "0xae78736Cd615f374D3085123A210448E74Fc6393",
+                ethers.utils.id("burn(uint256)").slice(0, 10),
+                1
+            ]
+        },
+        // Lido: submit(address referral) on stETH, redeemed through the withdrawal queue instead
+        {
+            exchange: "LstExecutor", args: [
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3",
+                "0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84",
+                "0x0000000000000000000000000000000000000000",
+                "0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84",
+                ethers.utils.id("submit(address)").slice(0, 10),
+                2,
+                "0x0000000000000000000000000000000000000000",
+                "0x00000000",
+                0
+            ]
+        },
+        // Mantle mETH: stake(uint256 minMETHAmount) on the staking contract, unstaked
+        // asynchronously so not redeemable
+        {
+            exchange: "LstExecutor", args: [
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3",
+                "0xd5F7838F5C461fefF7FE49ea5ebaF7728bB0ADfa",
+                "0x0000000000000000000000000000000000000000",
+                "0xe3cBd06D7dadB3F4e6557bAb7EdD924CD1489E8f",
+                ethers.utils.id("stake(uint256)").slice(0, 10),
+                2,
+                "0x0000000000000000000000000000000000000000",
+                "0x00000000",
+                0
+            ]
+        },
+        // Args: frxETH minter, frxETH, sfrxETH, permit2
+        {
+            exchange: "FraxEtherExecutor", args: [
+                "0xbAFA44EFE7901E04E39Dad13167D089C559c1138",
+                "0x5E8422345238F34275888049021821E8E08CAa1f",
+                "0xac3E018457B222d93114458476f3E3416Abbe38F",
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+            ]
+        },
+        // Args: permit2
+        {
+            exchange: "SkyPsmExecutor", args: [
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+            ]
+        },
+        // Args: permit2
+        {
+            exchange: "AmbientExecutor", args: [
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+            ]
+        },
+        // Args: Pendle router, permit2
+        {
+            exchange: "PendleExecutor", args: [
+                "0x888888888889758F76e7103c6CbF23ABbF58F946",
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+            ]
+        },
+        // Args: Lido withdrawal queue, stETH, permit2
+        {
+            exchange: "LidoWithdrawalExecutor", args: [
+                "0x889edC2eDab5f40e902b864aD4d7AdE8E412F9B1",
+                "0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84",
                 "0x000000000022D473030F116dDEE9F6B43aC78BA3"
             ]
         },
@@ -182,6 +271,13 @@ const executors_to_deploy = {
                 "0x000000000022D473030F116dDEE9F6B43aC78BA3"
             ]
         },
+        // Aerodrome V2 - Args: Pool Factory, Permit2
+        {
+            exchange: "AerodromeV2Executor", args: [
+                "0x420DD381b31aEf6683db6B902084cB0FFECe40Da",
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+            ]
+        },
     ],
     "unichain": [
         // Args: Factory, Pool Init Code Hash, Permit2, Fee BPS
@@ -224,6 +320,13 @@ const executors_to_deploy = {
                 "0x000000000022D473030F116dDEE9F6B43aC78BA3"
             ]
         },
+        // Velodrome V2 - Args: Pool Factory, Permit2
+        {
+            exchange: "AerodromeV2Executor", args: [
+                "0x31832f2a97Fd20664D76Cc421207669b55CE4BC0",
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+            ]
+        },
     ],
 }
 
@@ -12,6 +12,7 @@ import "@openzeppelin/contracts/utils/Pausable.sol";
 import "@openzeppelin/contracts/utils/Address.sol";
 import "@permit2/src/interfaces/IAllowanceTransfer.sol";
 import "./Dispatcher.sol";
+import "@interfaces/IReceiverCallback.sol";
 import {LibSwap} from "../lib/LibSwap.sol";
 import {IPoolManager} from "@uniswap/v4-core/src/interfaces/IPoolManager.sol";
 import {RestrictTransferFrom} from "./RestrictTransferFrom.sol";
@@ -65,6 +66,22 @@ error TychoRouter__AmountOutNotFullyReceived(
 error TychoRouter__MessageValueMismatch(uint256 value, uint256 amount);
 error TychoRouter__InvalidDataLength();
 error TychoRouter__UndefinedMinAmountOut();
+error TychoRouter__ExceededMaxAmountIn(uint256 amountIn, uint256 maxAmountIn);
+error TychoRouter__InvalidFee();
+
+/// @dev How the swaps of a split swap graph encode their token indices and
+/// splits.
+/// - Compact: 1-byte token indices and 3-byte splits (0xffffff is 100%).
+/// - WideIndices: 2-byte token indices and 3-byte splits, for swap graphs with
+///   more than 256 tokens.
+/// - PreciseSplits: 1-byte token indices and 4-byte splits with a 1e9 basis
+///   (1e9 is 100%), for amounts large enough for the rounding of 3-byte splits
+///   to matter.
+enum SplitLayout {
+    Compact,
+    WideIndices,
+    PreciseSplits
+}
 
 contract TychoRouter is
     AccessControl,
@@ -88,11 +105,25 @@ contract TychoRouter is
         0x427da25fe773164f88948d3e215c94b6554e2ed5e5f203a821c9f2f6131cf75a;
     bytes32 public constant FUND_RESCUER_ROLE =
         0x912e45d663a6f4cc1d0491d8f046e06c616f40352565ea1cdb86a0e1aaefa41b;
+    /// @dev The split of 100% in the swaps of `SplitLayout.PreciseSplits`
+    uint256 private constant _PRECISE_SPLIT_BASE = 1e9;
 
     event Withdrawal(
         address indexed token, uint256 amount, address indexed receiver
     );
 
+    /**
+     * @dev An integrator fee, taken by the `...WithFee` swap functions.
+     * @param recipient The address receiving the fee.
+     * @param bps The fee in basis points of the amount it is taken on.
+     * @param onInput If true, the fee is taken on the input token before swapping. Otherwise, it is taken on the output token.
+     */
+    struct Fee {
+        address recipient;
+        uint16 bps;
+        bool onInput;
+    }
+
     constructor(address _permit2, address weth) RestrictTransferFrom(_permit2) {
         if (_permit2 == address(0) || weth == address(0)) {
             revert TychoRouter__AddressZero();
@@ -151,7 +182,9 @@ contract TychoRouter is
             unwrapEth,
             nTokens,
             receiver,
-            swaps
+            swaps[0:0],
+            swaps,
+            SplitLayout.Compact
         );
     }
 
@@ -179,117 +212,1040 @@ contract TychoRouter is
      * @param signature A valid signature authorizing the Permit2 approval. Ignored if `wrapEth` is true.
      * @param swaps Encoded swap graph data containing details of each swap.
      *
-     * @return amountOut The total amount of the output token received by the receiver.
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function splitSwapPermit2(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        IAllowanceTransfer.PermitSingle calldata permitSingle,
+        bytes calldata signature,
+        bytes calldata swaps
+    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // For native ETH, assume funds already in our router. Else, handle approval.
+        if (tokenIn != address(0)) {
+            permit2.permit(msg.sender, permitSingle, signature);
+        }
+        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+
+        return _splitSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            nTokens,
+            receiver,
+            swaps[0:0],
+            swaps,
+            SplitLayout.Compact
+        );
+    }
+
+    /**
+     * @notice Executes a swap operation based on a predefined swap graph, like `splitSwapPermit2`, authorizing the
+     *         input token with a Permit2 batch permit. A single signed batch permit can approve several tokens (or the
+     *         total amount of several swaps of the same sender) at once.
+     *
+     * @dev
+     * - The batch permit is only submitted if it has details. Swaps sharing a batch permit pass it to the first swap,
+     *   and an empty one to the next swaps, which spend the allowance granted by the first one.
+     * - The router only transfers `amountIn` of `tokenIn` from the caller, whatever the other approved tokens.
+     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     *
+     * @param amountIn The input token amount to be swapped.
+     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
+     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
+     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
+     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
+     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
+     * @param nTokens The total number of tokens involved in the swap graph (used to initialize arrays for internal calculations).
+     * @param receiver The address to receive the output tokens.
+     * @param permitBatch A Permit2 structure containing token approval details for one or more tokens. Ignored if `wrapEth` is true or if it has no details.
+     * @param signature A valid signature authorizing the Permit2 batch approval. Ignored if the batch permit is ignored.
+     * @param swaps Encoded swap graph data containing details of each swap.
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function splitSwapPermit2Batch(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        IAllowanceTransfer.PermitBatch calldata permitBatch,
+        bytes calldata signature,
+        bytes calldata swaps
+    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // For native ETH, assume funds already in our router. Else, handle approval.
+        if (tokenIn != address(0) && permitBatch.details.length > 0) {
+            permit2.permit(msg.sender, permitBatch, signature);
+        }
+        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+
+        return _splitSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            nTokens,
+            receiver,
+            swaps[0:0],
+            swaps,
+            SplitLayout.Compact
+        );
+    }
+
+    /**
+     * @notice Executes a swap operation based on a predefined swap graph, like `splitSwap`, selling several input
+     *         tokens (each with its own amount) for a single output token, e.g. to consolidate a portfolio.
+     *
+     * @dev
+     * - If `isTransferFromAllowed` is true, every input token is transferred from the caller to the router before
+     *   swapping. Otherwise, the router assumes the input tokens are already transferred to it. Either way, the
+     *   executors transfer the input tokens from the router, so no executor is allowed to transfer from the caller.
+     * - The input tokens must be at the first token indexes of the swap graph, in the order of `tokensIn`.
+     * - Native ETH is not supported as an input token.
+     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
+     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     *
+     * @param amountsIn The amount of every input token to be swapped.
+     * @param tokensIn The addresses of the input tokens.
+     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
+     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
+     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
+     * @param nTokens The total number of tokens involved in the swap graph (used to initialize arrays for internal calculations).
+     * @param receiver The address to receive the output tokens.
+     * @param isTransferFromAllowed If false, the contract will assume that the input tokens are already transferred to the contract
+     * @param swaps Encoded swap graph data containing details of each swap.
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function multiInputSplitSwap(
+        uint256[] calldata amountsIn,
+        address[] calldata tokensIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        bool isTransferFromAllowed,
+        bytes calldata swaps
+    ) public whenNotPaused nonReentrant returns (uint256 amountOut) {
+        if (tokensIn.length == 0 || tokensIn.length != amountsIn.length) {
+            revert TychoRouter__InvalidDataLength();
+        }
+        if (receiver == address(0)) {
+            revert TychoRouter__AddressZero();
+        }
+        if (minAmountOut == 0) {
+            revert TychoRouter__UndefinedMinAmountOut();
+        }
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // The input tokens are all in the router before swapping
+        _tstoreTransferFromInfo(address(0), 0, false, false);
+
+        uint256[] memory amounts = new uint256[](nTokens);
+        for (uint256 i = 0; i < tokensIn.length; i++) {
+            if (isTransferFromAllowed) {
+                // slither-disable-next-line arbitrary-send-erc20
+                IERC20(tokensIn[i]).safeTransferFrom(
+                    msg.sender, address(this), amountsIn[i]
+                );
+            }
+            amounts[i] = amountsIn[i];
+        }
+
+        amountOut = _splitSwapFromAmounts(
+            amounts, swaps[0:0], swaps, SplitLayout.Compact
+        );
+
+        if (amountOut < minAmountOut) {
+            revert TychoRouter__NegativeSlippage(amountOut, minAmountOut);
+        }
+
+        if (unwrapEth) {
+            _unwrapETH(amountOut);
+            Address.sendValue(payable(receiver), amountOut);
+        }
+
+        // The output token is never an input token, so no input amount offsets its balance
+        _verifyAmountOutWasReceived(
+            tokensIn[0],
+            tokenOut,
+            initialBalanceTokenOut,
+            amountOut,
+            receiver,
+            0
+        );
+    }
+
+    /**
+     * @notice Executes a swap operation based on a predefined swap graph with no split routes.
+     *         This function enables multi-step swaps, optional ETH wrapping/unwrapping, and validates the output amount
+     *         against a user-specified minimum.
+     *
+     * @dev
+     * - If `wrapEth` is true, the contract wraps the provided native ETH into WETH and uses it as the sell token.
+     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
+     * - Swaps are executed sequentially using the `_swap` function.
+     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     *
+     * @param amountIn The input token amount to be swapped.
+     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
+     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
+     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
+     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
+     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
+     * @param receiver The address to receive the output tokens.
+     * @param isTransferFromAllowed If false, the contract will assume that the input token is already transferred to the contract and don't allow any transferFroms
+     * @param swaps Encoded swap graph data containing details of each swap.
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function sequentialSwap(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        address receiver,
+        bool isTransferFromAllowed,
+        bytes calldata swaps
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
+
+        return _sequentialSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            receiver,
+            swaps[0:0],
+            swaps
+        );
+    }
+
+    /**
+     * @notice Executes a swap operation based on a predefined swap graph with no split routes.
+     *         This function enables multi-step swaps, optional ETH wrapping/unwrapping, and validates the output amount
+     *         against a user-specified minimum.
+     *
+     * @dev
+     * - If `wrapEth` is true, the contract wraps the provided native ETH into WETH and uses it as the sell token.
+     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
+     * - For ERC20 tokens, Permit2 is used to approve and transfer tokens from the caller to the router.
+     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     *
+     * @param amountIn The input token amount to be swapped.
+     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
+     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
+     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
+     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
+     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
+     * @param receiver The address to receive the output tokens.
+     * @param permitSingle A Permit2 structure containing token approval details for the input token. Ignored if `wrapEth` is true.
+     * @param signature A valid signature authorizing the Permit2 approval. Ignored if `wrapEth` is true.
+     * @param swaps Encoded swap graph data containing details of each swap.
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function sequentialSwapPermit2(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        address receiver,
+        IAllowanceTransfer.PermitSingle calldata permitSingle,
+        bytes calldata signature,
+        bytes calldata swaps
+    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // For native ETH, assume funds already in our router. Else, handle approval.
+        if (tokenIn != address(0)) {
+            permit2.permit(msg.sender, permitSingle, signature);
+        }
+
+        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+
+        return _sequentialSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            receiver,
+            swaps[0:0],
+            swaps
+        );
+    }
+
+    /**
+     * @notice Executes a split swap like `splitSwap()`, with the executors of the swaps encoded in a shared table.
+     *
+     * @dev
+     * - `swaps` starts with the executor table: a 1-byte count followed by the 20-byte address of every executor used.
+     * - Each swap then refers to its executor by its 1-byte index in the table, instead of repeating its address.
+     *   This reduces the calldata of solutions using the same executors for many swaps.
+     *
+     * @param swaps The executor table, followed by the encoded swap graph data referring to it.
+     *
+     * For the other parameters, see the documentation of splitSwap().
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function splitSwapIndexed(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        bool isTransferFromAllowed,
+        bytes calldata swaps
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
+        (bytes calldata executors, bytes calldata indexedSwaps) =
+            _decodeExecutorTable(swaps);
+
+        return _splitSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            nTokens,
+            receiver,
+            executors,
+            indexedSwaps,
+            SplitLayout.Compact
+        );
+    }
+
+    /**
+     * @notice Executes a split swap like `splitSwapPermit2()`, with the executors of the swaps encoded in a shared table.
+     *
+     * @dev
+     * - `swaps` starts with the executor table: a 1-byte count followed by the 20-byte address of every executor used.
+     * - Each swap then refers to its executor by its 1-byte index in the table, instead of repeating its address.
+     *
+     * @param swaps The executor table, followed by the encoded swap graph data referring to it.
+     *
+     * For the other parameters, see the documentation of splitSwapPermit2().
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function splitSwapPermit2Indexed(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        IAllowanceTransfer.PermitSingle calldata permitSingle,
+        bytes calldata signature,
+        bytes calldata swaps
+    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // For native ETH, assume funds already in our router. Else, handle approval.
+        if (tokenIn != address(0)) {
+            permit2.permit(msg.sender, permitSingle, signature);
+        }
+        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+        (bytes calldata executors, bytes calldata indexedSwaps) =
+            _decodeExecutorTable(swaps);
+
+        return _splitSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            nTokens,
+            receiver,
+            executors,
+            indexedSwaps,
+            SplitLayout.Compact
+        );
+    }
+
+    /**
+     * @notice Executes a split swap like `splitSwap()`, for swap graphs with more than 256 tokens.
+     *
+     * @dev
+     * - Each swap encodes the indices of its input and output tokens on 2 bytes instead of 1.
+     *
+     * @param swaps Encoded swap graph data containing details of each swap, with 2-byte token indices.
+     *
+     * For the other parameters, see the documentation of splitSwap().
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function splitSwapWide(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        bool isTransferFromAllowed,
+        bytes calldata swaps
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
+
+        return _splitSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            nTokens,
+            receiver,
+            swaps[0:0],
+            swaps,
+            SplitLayout.WideIndices
+        );
+    }
+
+    /**
+     * @notice Executes a split swap like `splitSwapPermit2()`, for swap graphs with more than 256 tokens.
+     *
+     * @dev
+     * - Each swap encodes the indices of its input and output tokens on 2 bytes instead of 1.
+     *
+     * @param swaps Encoded swap graph data containing details of each swap, with 2-byte token indices.
+     *
+     * For the other parameters, see the documentation of splitSwapPermit2().
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function splitSwapPermit2Wide(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        IAllowanceTransfer.PermitSingle calldata permitSingle,
+        bytes calldata signature,
+        bytes calldata swaps
+    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // For native ETH, assume funds already in our router. Else, handle approval.
+        if (tokenIn != address(0)) {
+            permit2.permit(msg.sender, permitSingle, signature);
+        }
+        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+
+        return _splitSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            nTokens,
+            receiver,
+            swaps[0:0],
+            swaps,
+            SplitLayout.WideIndices
+        );
+    }
+
+    /**
+     * @notice Executes a split swap like `splitSwap()`, with splits of a finer granularity.
+     *
+     * @dev
+     * - Each swap encodes its split on 4 bytes with a 1e9 basis (1e9 is 100%), instead of 3 bytes where 0xffffff is 100%.
+     *
+     * @param swaps Encoded swap graph data containing details of each swap, with 4-byte splits.
+     *
+     * For the other parameters, see the documentation of splitSwap().
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function splitSwapPrecise(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        bool isTransferFromAllowed,
+        bytes calldata swaps
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
+
+        return _splitSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            nTokens,
+            receiver,
+            swaps[0:0],
+            swaps,
+            SplitLayout.PreciseSplits
+        );
+    }
+
+    /**
+     * @notice Executes a split swap like `splitSwapPermit2()`, with splits of a finer granularity.
+     *
+     * @dev
+     * - Each swap encodes its split on 4 bytes with a 1e9 basis (1e9 is 100%), instead of 3 bytes where 0xffffff is 100%.
+     *
+     * @param swaps Encoded swap graph data containing details of each swap, with 4-byte splits.
+     *
+     * For the other parameters, see the documentation of splitSwapPermit2().
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function splitSwapPermit2Precise(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        uint256 nTokens,
+        address receiver,
+        IAllowanceTransfer.PermitSingle calldata permitSingle,
+        bytes calldata signature,
+        bytes calldata swaps
+    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // For native ETH, assume funds already in our router. Else, handle approval.
+        if (tokenIn != address(0)) {
+            permit2.permit(msg.sender, permitSingle, signature);
+        }
+        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+
+        return _splitSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            nTokens,
+            receiver,
+            swaps[0:0],
+            swaps,
+            SplitLayout.PreciseSplits
+        );
+    }
+
+    /**
+     * @notice Executes a sequential swap like `sequentialSwap()`, with the executors of the swaps encoded in a shared table.
+     *
+     * @dev
+     * - `swaps` starts with the executor table: a 1-byte count followed by the 20-byte address of every executor used.
+     * - Each swap then refers to its executor by its 1-byte index in the table, instead of repeating its address.
+     *
+     * @param swaps The executor table, followed by the encoded swap graph data referring to it.
+     *
+     * For the other parameters, see the documentation of sequentialSwap().
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function sequentialSwapIndexed(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        address receiver,
+        bool isTransferFromAllowed,
+        bytes calldata swaps
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
+        (bytes calldata executors, bytes calldata indexedSwaps) =
+            _decodeExecutorTable(swaps);
+
+        return _sequentialSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            receiver,
+            executors,
+            indexedSwaps
+        );
+    }
+
+    /**
+     * @notice Executes a sequential swap like `sequentialSwapPermit2()`, with the executors of the swaps encoded in a shared table.
+     *
+     * @dev
+     * - `swaps` starts with the executor table: a 1-byte count followed by the 20-byte address of every executor used.
+     * - Each swap then refers to its executor by its 1-byte index in the table, instead of repeating its address.
+     *
+     * @param swaps The executor table, followed by the encoded swap graph data referring to it.
+     *
+     * For the other parameters, see the documentation of sequentialSwapPermit2().
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function sequentialSwapPermit2Indexed(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        address receiver,
+        IAllowanceTransfer.PermitSingle calldata permitSingle,
+        bytes calldata signature,
+        bytes calldata swaps
+    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // For native ETH, assume funds already in our router. Else, handle approval.
+        if (tokenIn != address(0)) {
+            permit2.permit(msg.sender, permitSingle, signature);
+        }
+
+        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+        (bytes calldata executors, bytes calldata indexedSwaps) =
+            _decodeExecutorTable(swaps);
+
+        return _sequentialSwapChecked(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            receiver,
+            executors,
+            indexedSwaps
+        );
+    }
+
+    /**
+     * @notice Executes a single swap operation.
+     *         This function enables optional ETH wrapping/unwrapping, and validates the output amount against a user-specified minimum.
+     *
+     * @dev
+     * - If `wrapEth` is true, the contract wraps the provided native ETH into WETH and uses it as the sell token.
+     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
+     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     *
+     * @param amountIn The input token amount to be swapped.
+     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
+     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
+     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
+     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
+     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
+     * @param receiver The address to receive the output tokens.
+     * @param isTransferFromAllowed If false, the contract will assume that the input token is already transferred to the contract and don't allow any transferFroms
+     * @param swapData Encoded swap details.
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function singleSwap(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        address receiver,
+        bool isTransferFromAllowed,
+        bytes calldata swapData
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
+
+        return _singleSwap(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            receiver,
+            swapData
+        );
+    }
+
+    /**
+     * @notice Executes a single swap operation.
+     *         This function enables optional ETH wrapping/unwrapping, and validates the output amount
+     *         against a user-specified minimum.
+     *
+     * @dev
+     * - If `wrapEth` is true, the contract wraps the provided native ETH into WETH and uses it as the sell token.
+     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
+     * - For ERC20 tokens, Permit2 is used to approve and transfer tokens from the caller to the router.
+     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     *
+     * @param amountIn The input token amount to be swapped.
+     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
+     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
+     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
+     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
+     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
+     * @param receiver The address to receive the output tokens.
+     * @param permitSingle A Permit2 structure containing token approval details for the input token. Ignored if `wrapEth` is true.
+     * @param signature A valid signature authorizing the Permit2 approval. Ignored if `wrapEth` is true.
+     * @param swapData Encoded swap details.
+     *
+     * @return amountOut The total amount of the output token received by the receiver.
+     */
+    function singleSwapPermit2(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        address receiver,
+        IAllowanceTransfer.PermitSingle calldata permitSingle,
+        bytes calldata signature,
+        bytes calldata swapData
+    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        // For native ETH, assume funds already in our router. Else, handle approval.
+        if (tokenIn != address(0)) {
+            permit2.permit(msg.sender, permitSingle, signature);
+        }
+        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+
+        return _singleSwap(
+            amountIn,
+            tokenIn,
+            tokenOut,
+            minAmountOut,
+            initialBalanceTokenOut,
+            wrapEth,
+            unwrapEth,
+            receiver,
+            swapData
+        );
+    }
+
+    /**
+     * @notice Executes a single swap operation for an exact amount of the output token.
+     *         This function enables optional ETH unwrapping, and validates the input amount against a user-specified maximum.
+     *
+     * @dev
+     * - The input token is transferred from the caller with `transferFrom`, up to `maxAmountIn`. Only the amount
+     *   needed by the swap is transferred, so nothing has to be refunded.
+     * - Wrapping ETH is not supported, as the unused part of `msg.value` would be left in the router.
+     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
+     * - Reverts with `TychoRouter__ExceededMaxAmountIn` if the input amount is greater than `maxAmountIn`.
+     *
+     * @param amountOut The exact amount of the output token to receive.
+     * @param tokenIn The address of the input token.
+     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
+     * @param maxAmountIn The maximum acceptable amount of the input token. Reverts if this condition is not met.
+     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
+     * @param receiver The address to receive the output tokens.
+     * @param isTransferFromAllowed If false, the contract will assume that the input token is already transferred to the contract and don't allow any transferFroms
+     * @param swapData Encoded swap details. The executor must support exact out swaps.
+     *
+     * @return amountIn The amount of the input token spent.
+     */
+    function exactOutSingleSwap(
+        uint256 amountOut,
+        address tokenIn,
+        address tokenOut,
+        uint256 maxAmountIn,
+        bool unwrapEth,
+        address receiver,
+        bool isTransferFromAllowed,
+        bytes calldata swapData
+    ) public whenNotPaused nonReentrant returns (uint256 amountIn) {
+        if (receiver == address(0)) {
+            revert TychoRouter__AddressZero();
+        }
+        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+        _tstoreTransferFromInfo(
+            tokenIn, maxAmountIn, false, isTransferFromAllowed
+        );
+
+        (address executor, bytes calldata protocolData) =
+            swapData.decodeSingleSwap();
+
+        amountIn = _callSwapOnExecutor(executor, amountOut, protocolData);
+
+        if (amountIn > maxAmountIn) {
+            revert TychoRouter__ExceededMaxAmountIn(amountIn, maxAmountIn);
+        }
+
+        if (unwrapEth) {
+            _unwrapETH(amountOut);
+            Address.sendValue(payable(receiver), amountOut);
+        }
+
+        _verifyAmountOutWasReceived(
+            tokenIn,
+            tokenOut,
+            initialBalanceTokenOut,
+            amountOut,
+            receiver,
+            amountIn
+        );
+    }
+
+    /**
+     * @notice Executes a single swap operation like `singleSwap()`, taking an integrator fee.
+     *
+     * @dev
+     * - If the fee is taken on the input token, it is sent to its recipient before swapping the rest of `amountIn`.
+     * - Otherwise, the swap must send its output to the router, which then sends the fee to its recipient and the
+     *   rest to the receiver. `minAmountOut` applies to the amount received by the receiver, after the fee.
+     * - Reverts with `TychoRouter__InvalidFee` if the fee recipient is `address(0)` or the fee exceeds 10000 bps.
+     * - Input fees can't be combined with `wrapEth`: the fee is taken from the native ETH sent, so the swap then
+     *   reverts with `TychoRouter__MessageValueMismatch` when wrapping the rest.
+     *
+     * @param fee The integrator fee to take.
+     *
+     * For the other parameters, see the documentation of singleSwap().
+     *
+     * @return amountOut The amount of the output token received by the receiver.
+     */
+    function singleSwapWithFee(
+        uint256 amountIn,
+        address tokenIn,
+        address tokenOut,
+        uint256 minAmountOut,
+        bool wrapEth,
+        bool unwrapEth,
+        address receiver,
+        bool isTransferFromAllowed,
+        Fee calldata fee,
+        bytes calldata swapData
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
+
+        if (fee.onInput) {
+            uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+            return _singleSwap(
+                _takeInputFee(fee, tokenIn, amountIn, isTransferFromAllowed),
+                tokenIn,
+                tokenOut,
+                minAmountOut,
+                initialBalanceTokenOut,
+                wrapEth,
+                unwrapEth,
+                receiver,
+                swapData
+            );
+        }
+
+        address swapTokenOut = unwrapEth ? address(_weth) : tokenOut;
+        amountOut = _singleSwap(
+            amountIn,
+            tokenIn,
+            swapTokenOut,
+            minAmountOut,
+            _balanceOf(swapTokenOut, address(this)),
+            wrapEth,
+            false,
+            address(this),
+            swapData
+        );
+        return _takeOutputFee(
+            fee, swapTokenOut, amountOut, minAmountOut, unwrapEth, receiver
+        );
+    }
+
+    /**
+     * @notice Executes a swap operation with no split routes like `sequentialSwap()`, taking an integrator fee.
+     *
+     * @dev See the documentation of singleSwapWithFee() for how the fee is taken.
+     *
+     * @param fee The integrator fee to take.
+     *
+     * For the other parameters, see the documentation of sequentialSwap().
+     *
+     * @return amountOut The amount of the output token received by the receiver.
      */
-    function splitSwapPermit2(
+    function sequentialSwapWithFee(
         uint256 amountIn,
         address tokenIn,
         address tokenOut,
         uint256 minAmountOut,
         bool wrapEth,
         bool unwrapEth,
-        uint256 nTokens,
         address receiver,
-        IAllowanceTransfer.PermitSingle calldata permitSingle,
-        bytes calldata signature,
+        bool isTransferFromAllowed,
+        Fee calldata fee,
         bytes calldata swaps
-    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
-        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
-        // For native ETH, assume funds already in our router. Else, handle approval.
-        if (tokenIn != address(0)) {
-            permit2.permit(msg.sender, permitSingle, signature);
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
+
+        if (fee.onInput) {
+            uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+            return _sequentialSwapChecked(
+                _takeInputFee(fee, tokenIn, amountIn, isTransferFromAllowed),
+                tokenIn,
+                tokenOut,
+                minAmountOut,
+                initialBalanceTokenOut,
+                wrapEth,
+                unwrapEth,
+                receiver,
+                swaps[0:0],
+                swaps
+            );
         }
-        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
 
-        return _splitSwapChecked(
+        address swapTokenOut = unwrapEth ? address(_weth) : tokenOut;
+        amountOut = _sequentialSwapChecked(
             amountIn,
             tokenIn,
-            tokenOut,
+            swapTokenOut,
             minAmountOut,
-            initialBalanceTokenOut,
+            _balanceOf(swapTokenOut, address(this)),
             wrapEth,
-            unwrapEth,
-            nTokens,
-            receiver,
+            false,
+            address(this),
+            swaps[0:0],
             swaps
         );
+        return _takeOutputFee(
+            fee, swapTokenOut, amountOut, minAmountOut, unwrapEth, receiver
+        );
     }
 
     /**
-     * @notice Executes a swap operation based on a predefined swap graph with no split routes.
-     *         This function enables multi-step swaps, optional ETH wrapping/unwrapping, and validates the output amount
-     *         against a user-specified minimum.
+     * @notice Executes a swap operation with split routes like `splitSwap()`, taking an integrator fee.
      *
-     * @dev
-     * - If `wrapEth` is true, the contract wraps the provided native ETH into WETH and uses it as the sell token.
-     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
-     * - Swaps are executed sequentially using the `_swap` function.
-     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     * @dev See the documentation of singleSwapWithFee() for how the fee is taken.
      *
-     * @param amountIn The input token amount to be swapped.
-     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
-     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
-     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
-     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
-     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
-     * @param receiver The address to receive the output tokens.
-     * @param isTransferFromAllowed If false, the contract will assume that the input token is already transferred to the contract and don't allow any transferFroms
-     * @param swaps Encoded swap graph data containing details of each swap.
+     * @param fee The integrator fee to take.
      *
-     * @return amountOut The total amount of the output token received by the receiver.
+     * For the other parameters, see the documentation of splitSwap().
+     *
+     * @return amountOut The amount of the output token received by the receiver.
      */
-    function sequentialSwap(
+    function splitSwapWithFee(
         uint256 amountIn,
         address tokenIn,
         address tokenOut,
         uint256 minAmountOut,
         bool wrapEth,
         bool unwrapEth,
+        uint256 nTokens,
         address receiver,
         bool isTransferFromAllowed,
+        Fee calldata fee,
         bytes calldata swaps
     ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
-        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
         _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
 
-        return _sequentialSwapChecked(
+        if (fee.onInput) {
+            uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
+            return _splitSwapChecked(
+                _takeInputFee(fee, tokenIn, amountIn, isTransferFromAllowed),
+                tokenIn,
+                tokenOut,
+                minAmountOut,
+                initialBalanceTokenOut,
+                wrapEth,
+                unwrapEth,
+                nTokens,
+                receiver,
+                swaps[0:0],
+                swaps,
+                SplitLayout.Compact
+            );
+        }
+
+        address swapTokenOut = unwrapEth ? address(_weth) : tokenOut;
+        amountOut = _splitSwapChecked(
             amountIn,
             tokenIn,
-            tokenOut,
+            swapTokenOut,
             minAmountOut,
-            initialBalanceTokenOut,
+            _balanceOf(swapTokenOut, address(this)),
             wrapEth,
-            unwrapEth,
-            receiver,
-            swaps
+            false,
+            nTokens,
+            address(this),
+            swaps[0:0],
+            swaps,
+            SplitLayout.Compact
+        );
+        return _takeOutputFee(
+            fee, swapTokenOut, amountOut, minAmountOut, unwrapEth, receiver
         );
     }
 
     /**
-     * @notice Executes a swap operation based on a predefined swap graph with no split routes.
-     *         This function enables multi-step swaps, optional ETH wrapping/unwrapping, and validates the output amount
-     *         against a user-specified minimum.
+     * @notice Executes a single swap operation like `singleSwap()`, then calls back the receiver with the output.
      *
      * @dev
-     * - If `wrapEth` is true, the contract wraps the provided native ETH into WETH and uses it as the sell token.
-     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
-     * - For ERC20 tokens, Permit2 is used to approve and transfer tokens from the caller to the router.
-     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     * - The swap must send its output to the router, which sends it to the receiver (unwrapping it first if
+     *   `unwrapEth` is true) and then calls `receiveSwapOutput` on the receiver with `callbackData`. This lets the
+     *   receiver use the output in the same transaction, e.g. to stake it or to provide liquidity with it.
+     * - Reverts if the receiver doesn't implement `IReceiverCallback`, or if its callback reverts.
      *
-     * @param amountIn The input token amount to be swapped.
-     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
-     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
-     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
-     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
-     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
-     * @param receiver The address to receive the output tokens.
-     * @param permitSingle A Permit2 structure containing token approval details for the input token. Ignored if `wrapEth` is true.
-     * @param signature A valid signature authorizing the Permit2 approval. Ignored if `wrapEth` is true.
-     * @param swaps Encoded swap graph data containing details of each swap.
+     * @param callbackData The data passed to the receiver's callback.
      *
-     * @return amountOut The total amount of the output token received by the receiver.
+     * For the other parameters, see the documentation of singleSwap().
+     *
+     * @return amountOut The amount of the output token received by the receiver.
      */
-    function sequentialSwapPermit2(
+    function singleSwapWithCallback(
         uint256 amountIn,
         address tokenIn,
         address tokenOut,
@@ -297,53 +1253,42 @@ contract TychoRouter is
         bool wrapEth,
         bool unwrapEth,
         address receiver,
-        IAllowanceTransfer.PermitSingle calldata permitSingle,
-        bytes calldata signature,
-        bytes calldata swaps
-    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
-        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
-        // For native ETH, assume funds already in our router. Else, handle approval.
-        if (tokenIn != address(0)) {
-            permit2.permit(msg.sender, permitSingle, signature);
-        }
-
-        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+        bool isTransferFromAllowed,
+        bytes calldata callbackData,
+        bytes calldata swapData
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
 
-        return _sequentialSwapChecked(
+        address swapTokenOut = unwrapEth ? address(_weth) : tokenOut;
+        amountOut = _singleSwap(
             amountIn,
             tokenIn,
-            tokenOut,
+            swapTokenOut,
             minAmountOut,
-            initialBalanceTokenOut,
+            _balanceOf(swapTokenOut, address(this)),
             wrapEth,
-            unwrapEth,
-            receiver,
-            swaps
+            false,
+            address(this),
+            swapData
+        );
+        _sendOutputWithCallback(
+            swapTokenOut, amountOut, unwrapEth, receiver, callbackData
         );
     }
 
     /**
-     * @notice Executes a single swap operation.
-     *         This function enables optional ETH wrapping/unwrapping, and validates the output amount against a user-specified minimum.
+     * @notice Executes a swap operation with no split routes like `sequentialSwap()`, then calls back the receiver
+     * with the output.
      *
-     * @dev
-     * - If `wrapEth` is true, the contract wraps the provided native ETH into WETH and uses it as the sell token.
-     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
-     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     * @dev See the documentation of singleSwapWithCallback() for how the receiver is called back.
      *
-     * @param amountIn The input token amount to be swapped.
-     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
-     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
-     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
-     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
-     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
-     * @param receiver The address to receive the output tokens.
-     * @param isTransferFromAllowed If false, the contract will assume that the input token is already transferred to the contract and don't allow any transferFroms
-     * @param swapData Encoded swap details.
+     * @param callbackData The data passed to the receiver's callback.
      *
-     * @return amountOut The total amount of the output token received by the receiver.
+     * For the other parameters, see the documentation of sequentialSwap().
+     *
+     * @return amountOut The amount of the output token received by the receiver.
      */
-    function singleSwap(
+    function sequentialSwapWithCallback(
         uint256 amountIn,
         address tokenIn,
         address tokenOut,
@@ -352,77 +1297,73 @@ contract TychoRouter is
         bool unwrapEth,
         address receiver,
         bool isTransferFromAllowed,
-        bytes calldata swapData
+        bytes calldata callbackData,
+        bytes calldata swaps
     ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
-        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
         _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
 
-        return _singleSwap(
+        address swapTokenOut = unwrapEth ? address(_weth) : tokenOut;
+        amountOut = _sequentialSwapChecked(
             amountIn,
             tokenIn,
-            tokenOut,
+            swapTokenOut,
             minAmountOut,
-            initialBalanceTokenOut,
+            _balanceOf(swapTokenOut, address(this)),
             wrapEth,
-            unwrapEth,
-            receiver,
-            swapData
+            false,
+            address(this),
+            swaps[0:0],
+            swaps
+        );
+        _sendOutputWithCallback(
+            swapTokenOut, amountOut, unwrapEth, receiver, callbackData
         );
     }
 
     /**
-     * @notice Executes a single swap operation.
-     *         This function enables optional ETH wrapping/unwrapping, and validates the output amount
-     *         against a user-specified minimum.
+     * @notice Executes a swap operation with split routes like `splitSwap()`, then calls back the receiver with
+     * the output.
      *
-     * @dev
-     * - If `wrapEth` is true, the contract wraps the provided native ETH into WETH and uses it as the sell token.
-     * - If `unwrapEth` is true, the contract converts the resulting WETH back into native ETH before sending it to the receiver.
-     * - For ERC20 tokens, Permit2 is used to approve and transfer tokens from the caller to the router.
-     * - Reverts with `TychoRouter__NegativeSlippage` if the output amount is less than `minAmountOut` and `minAmountOut` is greater than 0.
+     * @dev See the documentation of singleSwapWithCallback() for how the receiver is called back.
      *
-     * @param amountIn The input token amount to be swapped.
-     * @param tokenIn The address of the input token. Use `address(0)` for native ETH
-     * @param tokenOut The address of the output token. Use `address(0)` for native ETH
-     * @param minAmountOut The minimum acceptable amount of the output token. Reverts if this condition is not met. This should always be set to avoid losing funds due to slippage.
-     * @param wrapEth If true, wraps the input token (native ETH) into WETH.
-     * @param unwrapEth If true, unwraps the resulting WETH into native ETH and sends it to the receiver.
-     * @param receiver The address to receive the output tokens.
-     * @param permitSingle A Permit2 structure containing token approval details for the input token. Ignored if `wrapEth` is true.
-     * @param signature A valid signature authorizing the Permit2 approval. Ignored if `wrapEth` is true.
-     * @param swapData Encoded swap details.
+     * @param callbackData The data passed to the receiver's callback.
      *
-     * @return amountOut The total amount of the output token received by the receiver.
+     * For the other parameters, see the documentation of splitSwap().
+     *
+     * @return amountOut The amount of the output token received by the receiver.
      */
-    function singleSwapPermit2(
+    function splitSwapWithCallback(
         uint256 amountIn,
         address tokenIn,
         address tokenOut,
         uint256 minAmountOut,
         bool wrapEth,
         bool unwrapEth,
+        uint256 nTokens,
         address receiver,
-        IAllowanceTransfer.PermitSingle calldata permitSingle,
-        bytes calldata signature,
-        bytes calldata swapData
-    ) external payable whenNotPaused nonReentrant returns (uint256 amountOut) {
-        uint256 initialBalanceTokenOut = _balanceOf(tokenOut, receiver);
-        // For native ETH, assume funds already in our router. Else, handle approval.
-        if (tokenIn != address(0)) {
-            permit2.permit(msg.sender, permitSingle, signature);
-        }
-        _tstoreTransferFromInfo(tokenIn, amountIn, true, true);
+        bool isTransferFromAllowed,
+        bytes calldata callbackData,
+        bytes calldata swaps
+    ) public payable whenNotPaused nonReentrant returns (uint256 amountOut) {
+        _tstoreTransferFromInfo(tokenIn, amountIn, false, isTransferFromAllowed);
 
-        return _singleSwap(
+        address swapTokenOut = unwrapEth ? address(_weth) : tokenOut;
+        amountOut = _splitSwapChecked(
             amountIn,
             tokenIn,
-            tokenOut,
+            swapTokenOut,
             minAmountOut,
-            initialBalanceTokenOut,
+            _balanceOf(swapTokenOut, address(this)),
             wrapEth,
-            unwrapEth,
-            receiver,
-            swapData
+            false,
+            nTokens,
+            address(this),
+            swaps[0:0],
+            swaps,
+            SplitLayout.Compact
+        );
+        _sendOutputWithCallback(
+            swapTokenOut, amountOut, unwrapEth, receiver, callbackData
         );
     }
 
@@ -444,7 +1385,9 @@ contract TychoRouter is
         bool unwrapEth,
         uint256 nTokens,
         address receiver,
-        bytes calldata swaps
+        bytes calldata executors,
+        bytes calldata swaps,
+        SplitLayout layout
     ) internal returns (uint256 amountOut) {
         if (receiver == address(0)) {
             revert TychoRouter__AddressZero();
@@ -459,7 +1402,7 @@ contract TychoRouter is
             tokenIn = address(_weth);
         }
 
-        amountOut = _splitSwap(amountIn, nTokens, swaps);
+        amountOut = _splitSwap(amountIn, nTokens, executors, swaps, layout);
 
         if (amountOut < minAmountOut) {
             revert TychoRouter__NegativeSlippage(amountOut, minAmountOut);
@@ -553,6 +1496,7 @@ contract TychoRouter is
         bool wrapEth,
         bool unwrapEth,
         address receiver,
+        bytes calldata executors,
         bytes calldata swaps
     ) internal returns (uint256 amountOut) {
         if (receiver == address(0)) {
@@ -568,7 +1512,7 @@ contract TychoRouter is
             tokenIn = address(_weth);
         }
 
-        amountOut = _sequentialSwap(amountIn, swaps);
+        amountOut = _sequentialSwap(amountIn, executors, swaps);
 
         if (amountOut < minAmountOut) {
             revert TychoRouter__NegativeSlippage(amountOut, minAmountOut);
@@ -607,14 +1551,40 @@ contract TychoRouter is
      *
      * @param amountIn The initial amount of the sell token to be swapped.
      * @param nTokens The total number of tokens involved in the swap path, used to initialize arrays for internal tracking.
+     * @param executors The executor table of indexed swaps, whose executors are referred to by their index in it. Empty if the executor addresses are encoded in the swaps.
      * @param swaps_ Encoded swap graph data containing the details of each swap operation.
+     * @param layout How the token indices and the split of every swap are encoded (see `SplitLayout`).
      *
      * @return The total amount of the buy token obtained after all swaps have been executed.
      */
     function _splitSwap(
         uint256 amountIn,
         uint256 nTokens,
-        bytes calldata swaps_
+        bytes calldata executors,
+        bytes calldata swaps_,
+        SplitLayout layout
+    ) internal returns (uint256) {
+        uint256[] memory amounts = new uint256[](nTokens);
+        amounts[0] = amountIn;
+        return _splitSwapFromAmounts(amounts, executors, swaps_, layout);
+    }
+
+    /**
+     * @dev Executes the swaps of a swap graph like `_splitSwap`, starting from the given amounts of every token.
+     * The tokens with an initial amount (the input tokens) are at the first indexes.
+     *
+     * @param amounts The initial amount of every token of the swap path, updated with the amounts swapped.
+     * @param executors The executor table of indexed swaps, whose executors are referred to by their index in it. Empty if the executor addresses are encoded in the swaps.
+     * @param swaps_ Encoded swap graph data containing the details of each swap operation.
+     * @param layout How the token indices and the split of every swap are encoded (see `SplitLayout`).
+     *
+     * @return The total amount of the buy token obtained after all swaps have been executed.
+     */
+    function _splitSwapFromAmounts(
+        uint256[] memory amounts,
+        bytes calldata executors,
+        bytes calldata swaps_,
+        SplitLayout layout
     ) internal returns (uint256) {
         if (swaps_.length == 0) {
             revert TychoRouter__EmptySwaps();
@@ -622,27 +1592,43 @@ contract TychoRouter is
 
         uint256 currentAmountIn;
         uint256 currentAmountOut;
-        uint8 tokenInIndex = 0;
-        uint8 tokenOutIndex = 0;
-        uint24 split;
+        uint256 tokenInIndex = 0;
+        uint256 tokenOutIndex = 0;
+        uint256 split;
         address executor;
         bytes calldata protocolData;
         bytes calldata swapData;
 
-        uint256[] memory remainingAmounts = new uint256[](nTokens);
-        uint256[] memory amounts = new uint256[](nTokens);
+        uint256[] memory remainingAmounts = new uint256[](amounts.length);
+        for (uint256 i = 0; i < amounts.length; i++) {
+            remainingAmounts[i] = amounts[i];
+        }
         uint256 cyclicSwapAmountOut = 0;
-        amounts[0] = amountIn;
-        remainingAmounts[0] = amountIn;
 
         while (swaps_.length > 0) {
             (swapData, swaps_) = swaps_.next();
 
-            (tokenInIndex, tokenOutIndex, split, executor, protocolData) =
-                swapData.decodeSplitSwap();
+            if (layout == SplitLayout.WideIndices) {
+                (tokenInIndex, tokenOutIndex, split, executor, protocolData) =
+                    swapData.decodeWideSplitSwap();
+            } else if (layout == SplitLayout.PreciseSplits) {
+                (tokenInIndex, tokenOutIndex, split, executor, protocolData) =
+                    swapData.decodePreciseSplitSwap();
+            } else if (executors.length == 0) {
+                (tokenInIndex, tokenOutIndex, split, executor, protocolData) =
+                    swapData.decodeSplitSwap();
+            } else {
+                (tokenInIndex, tokenOutIndex, split, executor, protocolData) =
+                    swapData.decodeIndexedSplitSwap(executors);
+            }
 
             currentAmountIn = split > 0
-                ? (amounts[tokenInIndex] * split) / 0xffffff
+                ? (amounts[tokenInIndex] * split)
+                    / (
+                        layout == SplitLayout.PreciseSplits
+                            ? _PRECISE_SPLIT_BASE
+                            : 0xffffff
+                    )
                 : remainingAmounts[tokenInIndex];
 
             currentAmountOut =
@@ -663,27 +1649,53 @@ contract TychoRouter is
      * @dev Executes sequential swaps as defined by the provided swap graph.
      *
      * @param amountIn The initial amount of the sell token to be swapped.
+     * @param executors The executor table of indexed swaps, whose executors are referred to by their index in it. Empty if the executor addresses are encoded in the swaps.
      * @param swaps_ Encoded swap graph data containing the details of each swap operation.
      *
      * @return calculatedAmount The total amount of the buy token obtained after all swaps have been executed.
      */
-    function _sequentialSwap(uint256 amountIn, bytes calldata swaps_)
-        internal
-        returns (uint256 calculatedAmount)
-    {
+    function _sequentialSwap(
+        uint256 amountIn,
+        bytes calldata executors,
+        bytes calldata swaps_
+    ) internal returns (uint256 calculatedAmount) {
         bytes calldata swap;
+        address executor;
+        bytes calldata protocolData;
         calculatedAmount = amountIn;
         while (swaps_.length > 0) {
             (swap, swaps_) = swaps_.next();
 
-            (address executor, bytes calldata protocolData) =
-                swap.decodeSingleSwap();
+            if (executors.length == 0) {
+                (executor, protocolData) = swap.decodeSingleSwap();
+            } else {
+                (executor, protocolData) =
+                    swap.decodeIndexedSequentialSwap(executors);
+            }
 
             calculatedAmount =
                 _callSwapOnExecutor(executor, calculatedAmount, protocolData);
         }
     }
 
+    /**
+     * @dev Splits the swaps of an indexed solution into its executor table and the swaps referring to it.
+     * Reverts if the table is empty, since indexed swaps can't refer to any executor then.
+     */
+    function _decodeExecutorTable(bytes calldata swaps)
+        internal
+        pure
+        returns (bytes calldata executors, bytes calldata indexedSwaps)
+    {
+        if (swaps.length == 0) {
+            revert TychoRouter__EmptySwaps();
+        }
+        (executors, indexedSwaps) = swaps.decodeExecutorTable();
+        if (executors.length == 0) {
+            revert TychoRouter__InvalidDataLength();
+        }
+    }
+
     /**
      * @dev We use the fallback function to allow flexibility on callback.
      */
@@ -816,6 +1828,101 @@ contract TychoRouter is
             : IERC20(token).balanceOf(owner);
     }
 
+    /**
+     * @dev Sends the fee on `amountIn` to its recipient, from the caller if transferFroms are allowed (and the
+     * input token isn't native ETH) or else from the router. Returns the amount left to swap.
+     */
+    function _takeInputFee(
+        Fee calldata fee,
+        address tokenIn,
+        uint256 amountIn,
+        bool isTransferFromAllowed
+    ) internal returns (uint256 amountInAfterFee) {
+        uint256 feeAmount = _feeAmount(fee, amountIn);
+        TransferType transferType = isTransferFromAllowed
+            && tokenIn != address(0)
+            ? TransferType.TransferFrom
+            : TransferType.Transfer;
+        _transfer(fee.recipient, transferType, tokenIn, feeAmount);
+        amountInAfterFee = amountIn - feeAmount;
+    }
+
+    /**
+     * @dev Sends the fee on `amountOut`, held by the router, to its recipient and the rest to the receiver,
+     * unwrapping it first if `unwrapEth` is true. Reverts with `TychoRouter__NegativeSlippage` if the amount
+     * left for the receiver is less than `minAmountOut`.
+     */
+    function _takeOutputFee(
+        Fee calldata fee,
+        address tokenOut,
+        uint256 amountOut,
+        uint256 minAmountOut,
+        bool unwrapEth,
+        address receiver
+    ) internal returns (uint256 amountOutAfterFee) {
+        uint256 feeAmount = _feeAmount(fee, amountOut);
+        amountOutAfterFee = amountOut - feeAmount;
+        if (amountOutAfterFee < minAmountOut) {
+            revert TychoRouter__NegativeSlippage(
+                amountOutAfterFee, minAmountOut
+            );
+        }
+
+        if (unwrapEth) {
+            _unwrapETH(amountOut);
+        }
+        if (unwrapEth || tokenOut == address(0)) {
+            Address.sendValue(payable(fee.recipient), feeAmount);
+            Address.sendValue(payable(receiver), amountOutAfterFee);
+        } else {
+            IERC20(tokenOut).safeTransfer(fee.recipient, feeAmount);
+            IERC20(tokenOut).safeTransfer(receiver, amountOutAfterFee);
+        }
+    }
+
+    /**
+     * @dev Sends `amountOut`, held by the router, to the receiver (unwrapping it first if `unwrapEth` is true), then
+     * calls back the receiver with the callback data.
+     */
+    function _sendOutputWithCallback(
+        address tokenOut,
+        uint256 amountOut,
+        bool unwrapEth,
+        address receiver,
+        bytes calldata callbackData
+    ) internal {
+        if (receiver == address(0)) {
+            revert TychoRouter__AddressZero();
+        }
+        if (unwrapEth) {
+            _unwrapETH(amountOut);
+            tokenOut = address(0);
+        }
+        if (tokenOut == address(0)) {
+            Address.sendValue(payable(receiver), amountOut);
+        } else {
+            IERC20(tokenOut).safeTransfer(receiver, amountOut);
+        }
+        IReceiverCallback(receiver).receiveSwapOutput(
+            tokenOut, amountOut, callbackData
+        );
+    }
+
+    /**
+     * @dev Returns the fee on `amount`. Reverts with `TychoRouter__InvalidFee` if the fee recipient is `address(0)`
+     * or the fee exceeds 10000 bps.
+     */
+    function _feeAmount(Fee calldata fee, uint256 amount)
+        internal
+        pure
+        returns (uint256)
+    {
+        if (fee.recipient == address(0) || fee.bps > 10000) {
+            revert TychoRouter__InvalidFee();
+        }
+        return amount * fee.bps / 10000;
+    }
+
     /**
      * @dev Verifies that the expected amount of output tokens was received by the receiver.
      * It also handles the case of arbitrage swaps where the input and output tokens are the same.
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "../RestrictTransferFrom.sol";
+import {
+    IERC20,
+    SafeERC20
+} from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+
+error AcrossExecutor__InvalidDataLength();
+error AcrossExecutor__ZeroAddress();
+error AcrossExecutor__InvalidRelayFee();
+
+interface IAcrossSpokePool {
+    function depositV3(
+        address depositor,
+        address recipient,
+        address inputToken,
+        address outputToken,
+        uint256 inputAmount,
+        uint256 outputAmount,
+        uint256 destinationChainId,
+        address exclusiveRelayer,
+        uint32 quoteTimestamp,
+        uint32 fillDeadline,
+        uint32 exclusivityDeadline,
+        bytes calldata message
+    ) external payable;
+}
+
+/// @title AcrossExecutor
+/// @notice Executor bridging the output of a solution to another chain through
+/// an Across V3 deposit
+/// @dev The deposit must be the last swap of the solution. Nothing is received
+/// on this chain, so the calculated amount is the amount deposited into the
+/// spoke pool, which the router checks as the receiver. The amount delivered on
+/// the destination chain is the given amount minus the relay fee, which
+/// relayers must fill in full before the fill deadline (or the deposit is
+/// refunded to the depositor).
+contract AcrossExecutor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    /// @dev 1 (transfer type) + 20 (input token) + 20 (output token) + 32
+    /// (destination chain id) + 20 (recipient) + 20 (depositor) + 2 (relay fee)
+    uint256 private constant _DATA_LENGTH = 115;
+    uint256 private constant _BPS_DENOMINATOR = 10000;
+
+    /// @notice The Across spoke pool of this chain
+    address public immutable spokePool;
+    /// @notice How long (in seconds) relayers have to fill a deposit
+    uint32 public immutable fillDeadlineBuffer;
+
+    constructor(
+        address _spokePool,
+        uint32 _fillDeadlineBuffer,
+        address _permit2
+    ) RestrictTransferFrom(_permit2) {
+        if (_spokePool == address(0)) {
+            revert AcrossExecutor__ZeroAddress();
+        }
+        spokePool = _spokePool;
+        fillDeadlineBuffer = _fillDeadlineBuffer;
+    }
+
+    /// @notice Deposits the given amount into the Across spoke pool
+    /// @param givenAmount The amount of input token to bridge
+    /// @param data Encoded deposit data
+    /// @return calculatedAmount The amount deposited into the spoke pool
+    // slither-disable-next-line locked-ether
+    function swap(uint256 givenAmount, bytes calldata data)
+        external
+        payable
+        returns (uint256 calculatedAmount)
+    {
+        (
+            TransferType transferType,
+            address inputToken,
+            address outputToken,
+            uint256 destinationChainId,
+            address recipient,
+            address depositor,
+            uint16 relayFeeBps
+        ) = _decodeData(data);
+
+        if (relayFeeBps >= _BPS_DENOMINATOR) {
+            revert AcrossExecutor__InvalidRelayFee();
+        }
+        uint256 outputAmount =
+            givenAmount * (_BPS_DENOMINATOR - relayFeeBps) / _BPS_DENOMINATOR;
+
+        _transfer(address(this), transferType, inputToken, givenAmount);
+        IERC20(inputToken).forceApprove(spokePool, givenAmount);
+
+        // slither-disable-next-line timestamp
+        IAcrossSpokePool(spokePool).depositV3(
+            depositor,
+            recipient,
+            inputToken,
+            outputToken,
+            givenAmount,
+            outputAmount,
+            destinationChainId,
+            address(0),
+            uint32(block.timestamp),
+            uint32(block.timestamp) + fillDeadlineBuffer,
+            0,
+            ""
+        );
+        calculatedAmount = givenAmount;
+    }
+
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (
+            TransferType transferType,
+            address inputToken,
+            address outputToken,
+            uint256 destinationChainId,
+            address recipient,
+            address depositor,
+            uint16 relayFeeBps
+        )
+    {
+        if (data.length != _DATA_LENGTH) {
+            revert AcrossExecutor__InvalidDataLength();
+        }
+        transferType = TransferType(uint8(data[0]));
+        inputToken = address(bytes20(data[1:21]));
+        outputToken = address(bytes20(data[21:41]));
+        destinationChainId = uint256(bytes32(data[41:73]));
+        recipient = address(bytes20(data[73:93]));
+        depositor = address(bytes20(data[93:113]));
+        relayFeeBps = uint16(bytes2(data[113:115]));
+    }
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
+
+error AerodromeV2Executor__InvalidDataLength();
+error AerodromeV2Executor__InvalidFactory();
+error AerodromeV2Executor__InvalidTarget();
+
+interface IAerodromeV2PoolFactory {
+    function getPool(address tokenA, address tokenB, bool stable)
+        external
+        view
+        returns (address);
+}
+
+interface IAerodromeV2Pool {
+    function getAmountOut(uint256 amountIn, address tokenIn)
+        external
+        view
+        returns (uint256);
+
+    function swap(
+        uint256 amount0Out,
+        uint256 amount1Out,
+        address to,
+        bytes calldata data
+    ) external;
+}
+
+/**
+ * @dev Swaps on Aerodrome V2 (and Velodrome V2) stable and volatile pools.
+ * The pools follow the Uniswap V2 interface, but their amount out depends on
+ * their curve (stable or volatile), so it is quoted by the pool itself.
+ */
+contract AerodromeV2Executor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    address public immutable factory;
+
+    constructor(address _factory, address _permit2)
+        RestrictTransferFrom(_permit2)
+    {
+        if (_factory == address(0)) {
+            revert AerodromeV2Executor__InvalidFactory();
+        }
+        factory = _factory;
+    }
+
+    // slither-disable-next-line locked-ether
+    function swap(uint256 amountIn, bytes calldata data)
+        external
+        payable
+        returns (uint256 amountOut)
+    {
+        (
+            IERC20 tokenIn,
+            address target,
+            address receiver,
+            bool zeroForOne,
+            bool stable,
+            address poolFactory,
+            TransferType transferType
+        ) = _decodeData(data);
+
+        _verifyPairAddress(
+            address(tokenIn), target, zeroForOne, stable, poolFactory
+        );
+
+        IAerodromeV2Pool pool = IAerodromeV2Pool(target);
+        amountOut = pool.getAmountOut(amountIn, address(tokenIn));
+        _transfer(target, transferType, address(tokenIn), amountIn);
+        if (zeroForOne) {
+            pool.swap(0, amountOut, receiver, "");
+        } else {
+            pool.swap(amountOut, 0, receiver, "");
+        }
+    }
+
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (
+            IERC20 inToken,
+            address target,
+            address receiver,
+            bool zeroForOne,
+            bool stable,
+            address poolFactory,
+            TransferType transferType
+        )
+    {
+        if (!LibUserData.hasValidLength(data, 83)) {
+            revert AerodromeV2Executor__InvalidDataLength();
+        }
+        inToken = IERC20(address(bytes20(data[0:20])));
+        target = address(bytes20(data[20:40]));
+        receiver = address(bytes20(data[40:60]));
+        zeroForOne = uint8(data[60]) > 0;
+        stable = uint8(data[61]) > 0;
+        poolFactory = address(bytes20(data[62:82]));
+        transferType = TransferType(uint8(data[82]));
+    }
+
+    /**
+     * @dev The pool must be the one of the factory of this executor for the
+     * tokens and curve of the swap. The token out is read from the pool, and
+     * the factory lookup then confirms the pool is genuine.
+     */
+    function _verifyPairAddress(
+        address tokenIn,
+        address target,
+        bool zeroForOne,
+        bool stable,
+        address poolFactory
+    ) internal view {
+        if (poolFactory != factory) {
+            revert AerodromeV2Executor__InvalidFactory();
+        }
+        (bool success, bytes memory result) = target.staticcall(
+            abi.encodeWithSignature(zeroForOne ? "token1()" : "token0()")
+        );
+        if (!success || result.length != 32) {
+            revert AerodromeV2Executor__InvalidTarget();
+        }
+        address tokenOut = abi.decode(result, (address));
+        if (
+            IAerodromeV2PoolFactory(factory).getPool(tokenIn, tokenOut, stable)
+                != target
+        ) {
+            revert AerodromeV2Executor__InvalidTarget();
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import "@openzeppelin/contracts/utils/Address.sol";
+import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
+
+error AmbientExecutor__InvalidDataLength();
+
+interface ICrocSwapDex {
+    function swap(
+        address base,
+        address quote,
+        uint256 poolIdx,
+        bool isBuy,
+        bool inBaseQty,
+        uint128 qty,
+        uint16 tip,
+        uint128 limitPrice,
+        uint128 minOut,
+        uint8 reserveFlags
+    ) external payable returns (int128 baseFlow, int128 quoteFlow);
+}
+
+contract AmbientExecutor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    /// @dev Price limits of the CrocSwapDex (as square root prices in Q64.64),
+    /// so that swaps are only bounded by the amount given.
+    uint128 private constant MIN_SQRT_PRICE = 65538;
+    uint128 private constant MAX_SQRT_PRICE =
+        21267430153580247136652501917186561137;
+
+    constructor(address _permit2) RestrictTransferFrom(_permit2) {}
+
+    // slither-disable-next-line locked-ether
+    function swap(uint256 givenAmount, bytes calldata data)
+        external
+        payable
+        returns (uint256 calculatedAmount)
+    {
+        (
+            address base,
+            address quote,
+            uint256 poolIdx,
+            bool isBuy,
+            address dex,
+            address receiver,
+            TransferType transferType,
+            bool approvalNeeded
+        ) = _decodeData(data);
+
+        // Buying the quote token with the base token, or the base token with
+        // the quote token
+        address tokenIn = isBuy ? base : quote;
+        address tokenOut = isBuy ? quote : base;
+
+        uint256 ethAmount = 0;
+        if (tokenIn == address(0)) {
+            ethAmount = givenAmount;
+        } else {
+            _transfer(address(this), transferType, tokenIn, givenAmount);
+            if (approvalNeeded) {
+                // The CrocSwapDex transfers the token in from this contract
+                // slither-disable-next-line unused-return
+                IERC20(tokenIn).forceApprove(dex, type(uint256).max);
+            }
+        }
+
+        // slither-disable-next-line arbitrary-send-eth
+        (int128 baseFlow, int128 quoteFlow) = ICrocSwapDex(dex).swap{
+            value: ethAmount
+        }(
+            base,
+            quote,
+            poolIdx,
+            isBuy,
+            isBuy,
+            uint128(givenAmount),
+            0,
+            isBuy ? MAX_SQRT_PRICE : MIN_SQRT_PRICE,
+            0,
+            0
+        );
+        // The flows are the amounts paid to the pool, negative for the
+        // amounts received from it
+        calculatedAmount = uint256(uint128(-(isBuy ? quoteFlow : baseFlow)));
+
+        if (receiver != address(this)) {
+            if (tokenOut == address(0)) {
+                Address.sendValue(payable(receiver), calculatedAmount);
+            } else {
+                IERC20(tokenOut).safeTransfer(receiver, calculatedAmount);
+            }
+        }
+    }
+
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (
+            address base,
+            address quote,
+            uint256 poolIdx,
+            bool isBuy,
+            address dex,
+            address receiver,
+            TransferType transferType,
+            bool approvalNeeded
+        )
+    {
+        if (!LibUserData.hasValidLength(data, 115)) {
+            revert AmbientExecutor__InvalidDataLength();
+        }
+        base = address(bytes20(data[0:20]));
+        quote = address(bytes20(data[20:40]));
+        poolIdx = uint256(bytes32(data[40:72]));
+        isBuy = data[72] != 0;
+        dex = address(bytes20(data[73:93]));
+        receiver = address(bytes20(data[93:113]));
+        transferType = TransferType(uint8(data[113]));
+        approvalNeeded = data[114] != 0;
+    }
+
+    /**
+     * @dev The CrocSwapDex sends the native token bought to this contract.
+     */
+    receive() external payable {
+        require(msg.sender.code.length != 0);
+    }
+}
@@ -11,6 +11,7 @@ import {IAsset} from "@balancer-labs/v2-interfaces/contracts/vault/IAsset.sol";
 // slither-disable-next-line solc-version
 import {IVault} from "@balancer-labs/v2-interfaces/contracts/vault/IVault.sol";
 import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
 
 error BalancerV2Executor__InvalidDataLength();
 
@@ -77,7 +78,7 @@ contract BalancerV2Executor is IExecutor, RestrictTransferFrom {
             TransferType transferType
         )
     {
-        if (data.length != 94) {
+        if (!LibUserData.hasValidLength(data, 94)) {
             revert BalancerV2Executor__InvalidDataLength();
         }
 
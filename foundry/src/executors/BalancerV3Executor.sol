@@ -8,9 +8,12 @@ import {
 } from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
 import {IVault} from "@balancer-labs/v3-interfaces/contracts/vault/IVault.sol";
 import {
+    BufferWrapOrUnwrapParams,
     SwapKind,
-    VaultSwapParams
+    VaultSwapParams,
+    WrappingDirection
 } from "@balancer-labs/v3-interfaces/contracts/vault/VaultTypes.sol";
+import {IERC4626} from "@openzeppelin/contracts/interfaces/IERC4626.sol";
 import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
 import {ICallback} from "@interfaces/ICallback.sol";
 
@@ -31,8 +34,19 @@ contract BalancerV3Executor is IExecutor, RestrictTransferFrom, ICallback {
         payable
         returns (uint256 calculatedAmount)
     {
+        // 81 bytes for pools without hooks or buffers, or 81 bytes + 2 bytes hook data length +
+        // hook data, optionally followed by the 40 bytes of the buffer tokens in and out
         if (data.length != 81) {
-            revert BalancerV3Executor__InvalidDataLength();
+            if (data.length < 83) {
+                revert BalancerV3Executor__InvalidDataLength();
+            }
+            uint256 extendedLength = 83 + uint16(bytes2(data[81:83]));
+            if (
+                data.length != extendedLength
+                    && data.length != extendedLength + 40
+            ) {
+                revert BalancerV3Executor__InvalidDataLength();
+            }
         }
         bytes memory result = VAULT.unlock(
             abi.encodeCall(
@@ -65,29 +79,77 @@ contract BalancerV3Executor is IExecutor, RestrictTransferFrom, ICallback {
             IERC20 tokenOut,
             address poolId,
             TransferType transferType,
-            address receiver
+            address receiver,
+            bytes calldata hookData,
+            address bufferTokenIn,
+            address bufferTokenOut
         ) = _decodeData(data);
 
-        uint256 amountCalculated;
-        uint256 amountIn;
+        // Boosted pools hold ERC4626 tokens: the token in is wrapped into the buffer token in and
+        // the buffer token out unwrapped into the token out, all settled within the Vault
+        uint256 amountIn = amountGiven;
+        uint256 poolAmountIn = amountGiven;
+        if (bufferTokenIn != address(0)) {
+            (, amountIn, poolAmountIn) = _wrapOrUnwrap(
+                WrappingDirection.WRAP, bufferTokenIn, amountGiven
+            );
+        }
+
         uint256 amountOut;
-        (amountCalculated, amountIn, amountOut) = VAULT.swap(
+        // slither-disable-next-line unused-return
+        (, poolAmountIn, amountOut) = VAULT.swap(
             VaultSwapParams({
                 kind: SwapKind.EXACT_IN,
                 pool: poolId,
-                tokenIn: tokenIn,
-                tokenOut: tokenOut,
-                amountGivenRaw: amountGiven,
+                tokenIn: bufferTokenIn == address(0)
+                    ? tokenIn
+                    : IERC20(bufferTokenIn),
+                tokenOut: bufferTokenOut == address(0)
+                    ? tokenOut
+                    : IERC20(bufferTokenOut),
+                amountGivenRaw: poolAmountIn,
                 limitRaw: 0,
-                userData: ""
+                userData: hookData
             })
         );
+        if (bufferTokenIn == address(0)) {
+            amountIn = poolAmountIn;
+        }
+
+        if (bufferTokenOut != address(0)) {
+            (,, amountOut) = _wrapOrUnwrap(
+                WrappingDirection.UNWRAP, bufferTokenOut, amountOut
+            );
+        }
 
         _transfer(address(VAULT), transferType, address(tokenIn), amountIn);
         // slither-disable-next-line unused-return
         VAULT.settle(tokenIn, amountIn);
         VAULT.sendTo(tokenOut, receiver, amountOut);
-        return abi.encode(amountCalculated);
+        return abi.encode(amountOut);
+    }
+
+    function _wrapOrUnwrap(
+        WrappingDirection direction,
+        address wrappedToken,
+        uint256 amountGiven
+    )
+        internal
+        returns (
+            uint256 amountCalculated,
+            uint256 amountIn,
+            uint256 amountOut
+        )
+    {
+        return VAULT.erc4626BufferWrapOrUnwrap(
+            BufferWrapOrUnwrapParams({
+                kind: SwapKind.EXACT_IN,
+                direction: direction,
+                wrappedToken: IERC4626(wrappedToken),
+                amountGivenRaw: amountGiven,
+                limitRaw: 0
+            })
+        );
     }
 
     function handleCallback(bytes calldata data)
@@ -96,7 +158,8 @@ contract BalancerV3Executor is IExecutor, RestrictTransferFrom, ICallback {
     {
         verifyCallback(data);
         // Remove the first 68 bytes 4 selector + 32 dataOffset + 32 dataLength and extra padding at the end
-        result = _swapCallback(data[68:181]);
+        uint256 dataLength = uint256(bytes32(data[36:68]));
+        result = _swapCallback(data[68:68 + dataLength]);
         // Our general callback logic returns a not ABI encoded result (see Dispatcher._callHandleCallbackOnExecutor).
         // However, the Vault expects the result to be ABI encoded. That is why we need to encode it here again.
         return abi.encode(result);
@@ -118,7 +181,10 @@ contract BalancerV3Executor is IExecutor, RestrictTransferFrom, ICallback {
             IERC20 tokenOut,
             address poolId,
             TransferType transferType,
-            address receiver
+            address receiver,
+            bytes calldata hookData,
+            address bufferTokenIn,
+            address bufferTokenOut
         )
     {
         amountGiven = uint256(bytes32(data[0:32]));
@@ -127,5 +193,28 @@ contract BalancerV3Executor is IExecutor, RestrictTransferFrom, ICallback {
         poolId = address(bytes20(data[72:92]));
         transferType = TransferType(uint8(data[92]));
         receiver = address(bytes20(data[93:113]));
+        if (data.length == 113) {
+            hookData = data[113:];
+            return (
+                amountGiven,
+                tokenIn,
+                tokenOut,
+                poolId,
+                transferType,
+                receiver,
+                hookData,
+                address(0),
+                address(0)
+            );
+        }
+        // Pools with hooks have the hook data (forwarded as userData) appended after its length
+        uint256 hookDataEnd = 115 + uint16(bytes2(data[113:115]));
+        hookData = data[115:hookDataEnd];
+        // Boosted pools have the buffer tokens in and out (zero if none) appended last
+        if (data.length > hookDataEnd) {
+            bufferTokenIn = address(bytes20(data[hookDataEnd:hookDataEnd + 20]));
+            bufferTokenOut =
+                address(bytes20(data[hookDataEnd + 20:hookDataEnd + 40]));
+        }
     }
 }
@@ -4,7 +4,9 @@ pragma solidity ^0.8.26;
 import "@interfaces/IExecutor.sol";
 import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
 import "@openzeppelin/contracts/utils/Address.sol";
+import "@openzeppelin/contracts/utils/math/Math.sol";
 import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
 
 error CurveExecutor__AddressZero();
 error CurveExecutor__InvalidDataLength();
@@ -23,6 +25,17 @@ interface StablePool {
         payable;
 }
 
+interface StableNGPool {
+    // slither-disable-next-line naming-convention
+    function exchange_received(
+        int128 i,
+        int128 j,
+        uint256 dx,
+        uint256 min_dy,
+        address receiver
+    ) external returns (uint256);
+}
+
 interface CryptoPoolETH {
     // slither-disable-start naming-convention
     function exchange(
@@ -64,7 +77,7 @@ contract CurveExecutor is IExecutor, RestrictTransferFrom {
         payable
         returns (uint256)
     {
-        if (data.length != 85) {
+        if (!LibUserData.hasValidLength(data, 86)) {
             revert CurveExecutor__InvalidDataLength();
         }
 
@@ -77,7 +90,8 @@ contract CurveExecutor is IExecutor, RestrictTransferFrom {
             int128 j,
             bool approvalNeeded,
             TransferType transferType,
-            address receiver
+            address receiver,
+            bool useEth
         ) = _decodeData(data);
 
         if (approvalNeeded && tokenIn != nativeToken) {
@@ -95,22 +109,38 @@ contract CurveExecutor is IExecutor, RestrictTransferFrom {
             ethAmount = amountIn;
         }
 
-        if (poolType == 1 || poolType == 10) {
-            // stable and stable_ng
+        if (poolType == 1) {
+            // stable
             // slither-disable-next-line arbitrary-send-eth
             StablePool(pool).exchange{value: ethAmount}(i, j, amountIn, 0);
+        } else if (poolType == 10) {
+            // stable_ng: the pool swaps the tokens sent to it (no approval)
+            IERC20(tokenIn).safeTransfer(pool, amountIn);
+            // slither-disable-next-line unused-return
+            StableNGPool(pool).exchange_received(
+                i, j, amountIn, 0, address(this)
+            );
+        } else if (poolType == 11) {
+            // stable_ng with oracle or rebasing coins (no exchange_received).
+            // Rebasing coins can arrive 1-2 wei short, so swap at most the
+            // balance held
+            uint256 dx =
+                Math.min(amountIn, IERC20(tokenIn).balanceOf(address(this)));
+            StablePool(pool).exchange(i, j, dx, 0);
         } else {
             // crypto or llamma
-            if (tokenIn == nativeToken || tokenOut == nativeToken) {
+            if (useEth) {
+                // the pool wraps the ETH sent and unwraps the ETH bought
                 // slither-disable-next-line arbitrary-send-eth
                 CryptoPoolETH(pool).exchange{value: ethAmount}(
                     uint256(int256(i)), uint256(int256(j)), amountIn, 0, true
                 );
             } else {
-                CryptoPool(pool)
-                    .exchange(
-                        uint256(int256(i)), uint256(int256(j)), amountIn, 0
-                    );
+                // pools holding ETH take it as value without the flag
+                // slither-disable-next-line arbitrary-send-eth
+                CryptoPool(pool).exchange{value: ethAmount}(
+                    uint256(int256(i)), uint256(int256(j)), amountIn, 0
+                );
             }
         }
 
@@ -150,7 +180,8 @@ contract CurveExecutor is IExecutor, RestrictTransferFrom {
             int128 j,
             bool approvalNeeded,
             TransferType transferType,
-            address receiver
+            address receiver,
+            bool useEth
         )
     {
         tokenIn = address(bytes20(data[0:20]));
@@ -162,6 +193,7 @@ contract CurveExecutor is IExecutor, RestrictTransferFrom {
         approvalNeeded = data[63] != 0;
         transferType = TransferType(uint8(data[64]));
         receiver = address(bytes20(data[65:85]));
+        useEth = data[85] != 0;
     }
 
     /**
@@ -6,6 +6,7 @@ import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
 import "@openzeppelin/contracts/utils/Address.sol";
 import "@openzeppelin/contracts/interfaces/IERC4626.sol";
 import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
 
 error ERC4626Executor__InvalidDataLength();
 error ERC4626Executor__InvalidTarget();
@@ -58,7 +59,7 @@ contract ERC4626Executor is IExecutor, RestrictTransferFrom {
             bool approvalNeeded
         )
     {
-        if (data.length != 62) {
+        if (!LibUserData.hasValidLength(data, 62)) {
             revert ERC4626Executor__InvalidDataLength();
         }
         inToken = IERC20(address(bytes20(data[0:20])));
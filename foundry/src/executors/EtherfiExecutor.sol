@@ -4,6 +4,7 @@ pragma solidity ^0.8.26;
 import "@interfaces/IExecutor.sol";
 import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
 import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
 
 error EtherfiExecutor__InvalidDataLength();
 error EtherfiExecutor__InvalidDirection();
@@ -157,7 +158,7 @@ contract EtherfiExecutor is IExecutor, RestrictTransferFrom {
             bool approvalNeeded
         )
     {
-        if (data.length != 23) {
+        if (!LibUserData.hasValidLength(data, 23)) {
             revert EtherfiExecutor__InvalidDataLength();
         }
         receiver = address(bytes20(data[0:20]));
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import {IERC4626} from "@openzeppelin/contracts/interfaces/IERC4626.sol";
+import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
+
+error FraxEtherExecutor__InvalidDataLength();
+error FraxEtherExecutor__InvalidDirection();
+
+interface IFrxETHMinter {
+    function submitAndGive(address recipient) external payable;
+}
+
+enum FraxEtherDirection {
+    EthToFrxEth,
+    FrxEthToSfrxEth,
+    SfrxEthToFrxEth
+}
+
+contract FraxEtherExecutor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    address public immutable frxEthMinterAddress;
+    address public immutable frxEthAddress;
+    address public immutable sfrxEthAddress;
+
+    constructor(
+        address _frxEthMinterAddress,
+        address _frxEthAddress,
+        address _sfrxEthAddress,
+        address _permit2
+    ) RestrictTransferFrom(_permit2) {
+        require(
+            _frxEthMinterAddress != address(0),
+            "FraxEtherExecutor: frxEthMinterAddress is zero"
+        );
+        require(
+            _frxEthAddress != address(0),
+            "FraxEtherExecutor: frxEthAddress is zero"
+        );
+        require(
+            _sfrxEthAddress != address(0),
+            "FraxEtherExecutor: sfrxEthAddress is zero"
+        );
+
+        frxEthMinterAddress = _frxEthMinterAddress;
+        frxEthAddress = _frxEthAddress;
+        sfrxEthAddress = _sfrxEthAddress;
+    }
+
+    // slither-disable-next-line locked-ether
+    function swap(uint256 givenAmount, bytes calldata data)
+        external
+        payable
+        returns (uint256 calculatedAmount)
+    {
+        address receiver;
+        TransferType transferType;
+        FraxEtherDirection direction;
+        bool approvalNeeded;
+
+        (receiver, transferType, direction, approvalNeeded) = _decodeData(data);
+
+        if (direction == FraxEtherDirection.EthToFrxEth) {
+            // ETH is sent along with the call, so there is nothing to transfer in
+            uint256 balanceBefore = IERC20(frxEthAddress).balanceOf(receiver);
+            // slither-disable-next-line arbitrary-send-eth
+            IFrxETHMinter(frxEthMinterAddress).submitAndGive{
+                value: givenAmount
+            }(receiver);
+            calculatedAmount =
+                IERC20(frxEthAddress).balanceOf(receiver) - balanceBefore;
+        } else if (direction == FraxEtherDirection.FrxEthToSfrxEth) {
+            _transfer(address(this), transferType, frxEthAddress, givenAmount);
+            if (approvalNeeded) {
+                IERC20(frxEthAddress)
+                    .forceApprove(sfrxEthAddress, type(uint256).max);
+            }
+            calculatedAmount =
+                IERC4626(sfrxEthAddress).deposit(givenAmount, receiver);
+        } else if (direction == FraxEtherDirection.SfrxEthToFrxEth) {
+            _transfer(address(this), transferType, sfrxEthAddress, givenAmount);
+            calculatedAmount = IERC4626(sfrxEthAddress)
+                .redeem(givenAmount, receiver, address(this));
+        } else {
+            revert FraxEtherExecutor__InvalidDirection();
+        }
+    }
+
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (
+            address receiver,
+            TransferType transferType,
+            FraxEtherDirection direction,
+            bool approvalNeeded
+        )
+    {
+        if (!LibUserData.hasValidLength(data, 23)) {
+            revert FraxEtherExecutor__InvalidDataLength();
+        }
+        receiver = address(bytes20(data[0:20]));
+        transferType = TransferType(uint8(data[20]));
+        direction = FraxEtherDirection(uint8(data[21]));
+        approvalNeeded = data[22] != 0;
+    }
+}
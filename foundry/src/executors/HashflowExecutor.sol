@@ -2,11 +2,14 @@
 pragma solidity ^0.8.26;
 
 import "../RestrictTransferFrom.sol";
+import "../../lib/IWETH.sol";
 import "@interfaces/IExecutor.sol";
 import "@openzeppelin/contracts/token/ERC20/IERC20.sol";
 import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import "@openzeppelin/contracts/utils/Address.sol";
 
 error HashflowExecutor__InvalidHashflowRouter();
+error HashflowExecutor__InvalidWeth();
 error HashflowExecutor__InvalidDataLength();
 
 interface IHashflowRouter {
@@ -35,18 +38,38 @@ contract HashflowExecutor is IExecutor, RestrictTransferFrom {
     address public constant NATIVE_TOKEN =
         0x0000000000000000000000000000000000000000;
 
+    /// @dev Flag of the native byte of swaps selling the native token, wrapped
+    /// by the executor before settling the quote.
+    uint8 private constant NATIVE_IN_FLAG = 1;
+    /// @dev Flag of the native byte of swaps buying the native token, unwrapped
+    /// by the executor after settling the quote.
+    uint8 private constant NATIVE_OUT_FLAG = 2;
+
     /// @notice The Hashflow router address
     address public immutable hashflowRouter;
+    /// @notice The wrapped native token the native swaps are quoted for
+    IWETH public immutable weth;
 
-    constructor(address _hashflowRouter, address _permit2)
+    constructor(address _hashflowRouter, address _weth, address _permit2)
         RestrictTransferFrom(_permit2)
     {
         if (_hashflowRouter == address(0)) {
             revert HashflowExecutor__InvalidHashflowRouter();
         }
+        if (_weth == address(0)) {
+            revert HashflowExecutor__InvalidWeth();
+        }
         hashflowRouter = _hashflowRouter;
+        weth = IWETH(_weth);
     }
 
+    /**
+     * @dev The data can end with a native byte and a receiver. With the native
+     * in flag, the given amount of native token is wrapped before settling the
+     * quote, which is for the wrapped token. With the native out flag, the
+     * quote pays the wrapped token to this contract, which unwraps it and sends
+     * the native token to the receiver.
+     */
     function swap(uint256 givenAmount, bytes calldata data)
         external
         payable
@@ -57,6 +80,7 @@ contract HashflowExecutor is IExecutor, RestrictTransferFrom {
             bool approvalNeeded,
             TransferType transferType
         ) = _decodeData(data);
+        (uint8 nativeFlags, address receiver) = _decodeNativeData(data);
 
         // Slippage checks
         if (givenAmount > quote.baseTokenAmount) {
@@ -75,13 +99,27 @@ contract HashflowExecutor is IExecutor, RestrictTransferFrom {
         if (quote.baseToken == NATIVE_TOKEN) {
             ethValue = quote.effectiveBaseTokenAmount;
         }
-        _transfer(
-            address(this), transferType, address(quote.baseToken), givenAmount
-        );
+        if (nativeFlags & NATIVE_IN_FLAG != 0) {
+            weth.deposit{value: givenAmount}();
+        } else {
+            _transfer(
+                address(this),
+                transferType,
+                address(quote.baseToken),
+                givenAmount
+            );
+        }
         uint256 balanceBefore = _balanceOf(quote.trader, quote.quoteToken);
         IHashflowRouter(hashflowRouter).tradeRFQT{value: ethValue}(quote);
         uint256 balanceAfter = _balanceOf(quote.trader, quote.quoteToken);
         calculatedAmount = balanceAfter - balanceBefore;
+
+        if (nativeFlags & NATIVE_OUT_FLAG != 0) {
+            weth.withdraw(calculatedAmount);
+            if (receiver != address(this)) {
+                Address.sendValue(payable(receiver), calculatedAmount);
+            }
+        }
     }
 
     function _decodeData(bytes calldata data)
@@ -93,7 +131,7 @@ contract HashflowExecutor is IExecutor, RestrictTransferFrom {
             TransferType transferType
         )
     {
-        if (data.length != 327) {
+        if (data.length != 327 && data.length != 348) {
             revert HashflowExecutor__InvalidDataLength();
         }
 
@@ -117,6 +155,17 @@ contract HashflowExecutor is IExecutor, RestrictTransferFrom {
         quote.signature = data[262:327];
     }
 
+    function _decodeNativeData(bytes calldata data)
+        internal
+        pure
+        returns (uint8 nativeFlags, address receiver)
+    {
+        if (data.length == 348) {
+            nativeFlags = uint8(data[327]);
+            receiver = address(bytes20(data[328:348]));
+        }
+    }
+
     function _balanceOf(address trader, address token)
         internal
         view
@@ -126,4 +175,9 @@ contract HashflowExecutor is IExecutor, RestrictTransferFrom {
             ? trader.balance
             : IERC20(token).balanceOf(trader);
     }
+
+    /**
+     * @dev Allow receiving the native token unwrapped by the native out swaps
+     */
+    receive() external payable {}
 }
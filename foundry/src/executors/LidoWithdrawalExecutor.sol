@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
+import {
+    IERC20,
+    SafeERC20
+} from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+
+error LidoWithdrawalExecutor__InvalidDataLength();
+error LidoWithdrawalExecutor__ZeroAddress();
+
+interface ILidoWithdrawalQueue {
+    function requestWithdrawals(uint256[] calldata amounts, address owner)
+        external
+        returns (uint256[] memory requestIds);
+}
+
+/// @title LidoWithdrawalExecutor
+/// @notice Executor requesting a withdrawal of stETH for ETH on the Lido
+/// withdrawal queue
+/// @dev The request must be the last swap of the solution. The queue mints
+/// withdrawal request NFTs (unstETH) to the receiver, which claims the ETH once
+/// the requests are finalized. Nothing is received by the end of the
+/// transaction, so the calculated amount is the amount of stETH received by
+/// the withdrawal queue, which the router checks.
+contract LidoWithdrawalExecutor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    /// @dev 1 (transfer type) + 20 (receiver)
+    uint256 private constant _DATA_LENGTH = 21;
+    /// @dev The max amount of stETH of a single withdrawal request
+    uint256 private constant _MAX_REQUEST_AMOUNT = 1000 ether;
+
+    /// @notice The Lido withdrawal queue (unstETH)
+    address public immutable withdrawalQueue;
+    /// @notice The stETH token
+    address public immutable stEth;
+
+    constructor(address _withdrawalQueue, address _stEth, address _permit2)
+        RestrictTransferFrom(_permit2)
+    {
+        if (_withdrawalQueue == address(0) || _stEth == address(0)) {
+            revert LidoWithdrawalExecutor__ZeroAddress();
+        }
+        withdrawalQueue = _withdrawalQueue;
+        stEth = _stEth;
+    }
+
+    /// @notice Requests the withdrawal of the given amount of stETH
+    /// @param givenAmount The amount of stETH to withdraw
+    /// @param data Encoded request data
+    /// @return calculatedAmount The amount of stETH received by the queue
+    // slither-disable-next-line locked-ether
+    function swap(uint256 givenAmount, bytes calldata data)
+        external
+        payable
+        returns (uint256 calculatedAmount)
+    {
+        (TransferType transferType, address receiver) = _decodeData(data);
+
+        _transfer(address(this), transferType, stEth, givenAmount);
+        // stETH transfers can round the amount received down by a few wei
+        uint256 balance = IERC20(stEth).balanceOf(address(this));
+        uint256 requestedAmount = balance < givenAmount ? balance : givenAmount;
+
+        uint256 queueBalanceBefore =
+            IERC20(stEth).balanceOf(withdrawalQueue);
+        IERC20(stEth).forceApprove(withdrawalQueue, requestedAmount);
+        // slither-disable-next-line unused-return
+        ILidoWithdrawalQueue(withdrawalQueue).requestWithdrawals(
+            _splitRequests(requestedAmount), receiver
+        );
+        // The queue pulls the stETH with the same rounding, so return what it
+        // received for the router's check to match the queue's balance change
+        calculatedAmount =
+            IERC20(stEth).balanceOf(withdrawalQueue) - queueBalanceBefore;
+    }
+
+    /// @dev Splits the amount into evenly sized requests, as each request is
+    /// capped by the withdrawal queue
+    function _splitRequests(uint256 amount)
+        internal
+        pure
+        returns (uint256[] memory amounts)
+    {
+        uint256 nRequests =
+            (amount + _MAX_REQUEST_AMOUNT - 1) / _MAX_REQUEST_AMOUNT;
+        amounts = new uint256[](nRequests);
+        uint256 requestAmount = amount / nRequests;
+        for (uint256 i = 0; i < nRequests - 1; i++) {
+            amounts[i] = requestAmount;
+        }
+        amounts[nRequests - 1] = amount - requestAmount * (nRequests - 1);
+    }
+
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (TransferType transferType, address receiver)
+    {
+        if (!LibUserData.hasValidLength(data, _DATA_LENGTH)) {
+            revert LidoWithdrawalExecutor__InvalidDataLength();
+        }
+        transferType = TransferType(uint8(data[0]));
+        receiver = address(bytes20(data[1:21]));
+    }
+}
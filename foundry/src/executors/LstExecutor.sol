@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
+import {
+    IERC20,
+    SafeERC20
+} from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import {Address} from "@openzeppelin/contracts/utils/Address.sol";
+
+error LstExecutor__InvalidDataLength();
+error LstExecutor__ZeroAddress();
+error LstExecutor__RedeemNotSupported();
+
+/// @notice Arguments of the mint and redeem functions of a liquid staking
+/// token, besides their selector
+enum LstCallArgs {
+    // e.g. Rocketpool `deposit()`
+    None,
+    // The amount in, e.g. rETH `burn(uint256 amount)`
+    Amount,
+    // A zero word, e.g. stETH `submit(address referral)` or mETH
+    // `stake(uint256 minMETHAmount)`
+    Zero,
+    // The token in, the amount in and a zero word, e.g. the teller
+    // `deposit(address asset, uint256 amount, uint256 minimumMint)` of vaults
+    TokenAmountZero
+}
+
+/// @title LstExecutor
+/// @notice Executor minting a liquid staking token from its deposit token
+/// (ETH or an ERC20), or redeeming it back into the deposit token if the
+/// token supports synchronous redemptions
+/// @dev One executor is deployed per token, with the functions it calls set
+/// at deployment, so that adding a token doesn't need a new executor. The
+/// tokens minted or redeemed are measured on the balance of the executor and
+/// forwarded to the receiver. The minimum amounts out are left to the router's
+/// check of the solution output.
+contract LstExecutor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    /// @dev 1 (is mint) + 1 (transfer type) + 20 (receiver)
+    uint256 private constant _DATA_LENGTH = 22;
+
+    /// @notice The liquid staking token
+    address public immutable lstToken;
+    /// @notice The token deposited to mint the liquid staking token, or
+    /// address(0) for ETH
+    address public immutable depositToken;
+    /// @notice The contract called to mint, and its function
+    address public immutable mintTarget;
+    bytes4 public immutable mintSelector;
+    LstCallArgs public immutable mintArgs;
+    /// @notice The contract called to redeem, and its function (zero if
+    /// redemptions aren't supported)
+    address public immutable redeemTarget;
+    bytes4 public immutable redeemSelector;
+    LstCallArgs public immutable redeemArgs;
+
+    constructor(
+        address _permit2,
+        address _lstToken,
+        address _depositToken,
+        address _mintTarget,
+        bytes4 _mintSelector,
+        LstCallArgs _mintArgs,
+        address _redeemTarget,
+        bytes4 _redeemSelector,
+        LstCallArgs _redeemArgs
+    ) RestrictTransferFrom(_permit2) {
+        if (_lstToken == address(0) || _mintTarget == address(0)) {
+            revert LstExecutor__ZeroAddress();
+        }
+        if (_redeemSelector != bytes4(0) && _redeemTarget == address(0)) {
+            revert LstExecutor__ZeroAddress();
+        }
+        lstToken = _lstToken;
+        depositToken = _depositToken;
+        mintTarget = _mintTarget;
+        mintSelector = _mintSelector;
+        mintArgs = _mintArgs;
+        redeemTarget = _redeemTarget;
+        redeemSelector = _redeemSelector;
+        redeemArgs = _redeemArgs;
+    }
+
+    /// @dev Required to receive ETH from redemptions
+    receive() external payable {}
+
+    /// @notice Mints or redeems the given amount of the liquid staking token
+    /// @param givenAmount The amount of token in
+    /// @param data Encoded swap data
+    /// @return calculatedAmount The amount of token out received by the
+    /// receiver
+    // slither-disable-next-line locked-ether
+    function swap(uint256 givenAmount, bytes calldata data)
+        external
+        payable
+        returns (uint256 calculatedAmount)
+    {
+        (bool isMint, TransferType transferType, address receiver) =
+            _decodeData(data);
+
+        if (isMint) {
+            calculatedAmount = _call(
+                depositToken,
+                lstToken,
+                mintTarget,
+                mintSelector,
+                mintArgs,
+                transferType,
+                givenAmount
+            );
+        } else {
+            if (redeemSelector == bytes4(0)) {
+                revert LstExecutor__RedeemNotSupported();
+            }
+            calculatedAmount = _call(
+                lstToken,
+                depositToken,
+                redeemTarget,
+                redeemSelector,
+                redeemArgs,
+                transferType,
+                givenAmount
+            );
+        }
+
+        address tokenOut = isMint ? lstToken : depositToken;
+        if (receiver != address(this)) {
+            if (tokenOut == address(0)) {
+                Address.sendValue(payable(receiver), calculatedAmount);
+            } else {
+                IERC20(tokenOut).safeTransfer(receiver, calculatedAmount);
+            }
+        }
+    }
+
+    /// @dev Calls the given function with the amount of token in, and returns
+    /// the amount of token out received by the executor. ETH in is sent along
+    /// with the call to the executor, so there is nothing to transfer in.
+    function _call(
+        address tokenIn,
+        address tokenOut,
+        address target,
+        bytes4 selector,
+        LstCallArgs args,
+        TransferType transferType,
+        uint256 amount
+    ) internal returns (uint256 amountOut) {
+        uint256 value;
+        if (tokenIn == address(0)) {
+            value = amount;
+        } else {
+            _transfer(address(this), transferType, tokenIn, amount);
+            if (target != tokenIn) {
+                IERC20(tokenIn).forceApprove(target, amount);
+            }
+        }
+
+        bytes memory callData;
+        if (args == LstCallArgs.None) {
+            callData = abi.encodePacked(selector);
+        } else if (args == LstCallArgs.Amount) {
+            callData = abi.encodeWithSelector(selector, amount);
+        } else if (args == LstCallArgs.Zero) {
+            callData = abi.encodeWithSelector(selector, uint256(0));
+        } else {
+            callData =
+                abi.encodeWithSelector(selector, tokenIn, amount, uint256(0));
+        }
+
+        uint256 balanceBefore = _balance(tokenOut);
+        // slither-disable-next-line arbitrary-send-eth
+        Address.functionCallWithValue(target, callData, value);
+        amountOut = _balance(tokenOut) - balanceBefore;
+    }
+
+    function _balance(address token) internal view returns (uint256) {
+        return token == address(0)
+            ? address(this).balance
+            : IERC20(token).balanceOf(address(this));
+    }
+
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (bool isMint, TransferType transferType, address receiver)
+    {
+        if (!LibUserData.hasValidLength(data, _DATA_LENGTH)) {
+            revert LstExecutor__InvalidDataLength();
+        }
+        isMint = uint8(data[0]) == 1;
+        transferType = TransferType(uint8(data[1]));
+        receiver = address(bytes20(data[2:22]));
+    }
+}
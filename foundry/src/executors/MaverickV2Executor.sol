@@ -5,6 +5,7 @@ import "@interfaces/IExecutor.sol";
 import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
 import "@openzeppelin/contracts/utils/Address.sol";
 import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
 
 error MaverickV2Executor__InvalidDataLength();
 error MaverickV2Executor__InvalidTarget();
@@ -65,7 +66,7 @@ contract MaverickV2Executor is IExecutor, RestrictTransferFrom {
             TransferType transferType
         )
     {
-        if (data.length != 61) {
+        if (!LibUserData.hasValidLength(data, 61)) {
             revert MaverickV2Executor__InvalidDataLength();
         }
         inToken = IERC20(address(bytes20(data[0:20])));
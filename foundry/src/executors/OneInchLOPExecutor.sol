@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "../RestrictTransferFrom.sol";
+import {
+    IERC20,
+    SafeERC20
+} from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+
+error OneInchLOPExecutor__InvalidDataLength();
+error OneInchLOPExecutor__ZeroAddress();
+error OneInchLOPExecutor__InvalidOrderAssets();
+
+interface IOrderMixin {
+    /// @dev Addresses are packed into the lower 160 bits
+    struct Order {
+        uint256 salt;
+        uint256 maker;
+        uint256 receiver;
+        uint256 makerAsset;
+        uint256 takerAsset;
+        uint256 makingAmount;
+        uint256 takingAmount;
+        uint256 makerTraits;
+    }
+
+    function fillOrderArgs(
+        Order calldata order,
+        bytes32 r,
+        bytes32 vs,
+        uint256 amount,
+        uint256 takerTraits,
+        bytes calldata args
+    )
+        external
+        payable
+        returns (
+            uint256 makingAmount,
+            uint256 takingAmount,
+            bytes32 orderHash
+        );
+}
+
+/// @title OneInchLOPExecutor
+/// @notice Executor filling resting maker orders of the 1inch Limit Order
+/// Protocol (v4)
+/// @dev Orders are filled by taking amount: the given amount is the amount of
+/// taker asset sold, capped at the taking amount of the order. The maker asset
+/// is sent to the receiver as the target of the fill.
+contract OneInchLOPExecutor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    /// @dev Taker traits flag: the first 20 bytes of the args are the
+    /// receiver of the maker asset
+    uint256 private constant _ARGS_HAS_TARGET = 1 << 251;
+    /// @dev Taker traits offset of the length of the order extension in the
+    /// args
+    uint256 private constant _ARGS_EXTENSION_LENGTH_OFFSET = 224;
+    /// @dev 20 (token in) + 20 (token out) + 1 (transfer type) + 1 (approval
+    /// needed) + 20 (receiver) + 256 (order) + 32 (r) + 32 (vs)
+    uint256 private constant _MIN_DATA_LENGTH = 382;
+
+    /// @notice The 1inch Limit Order Protocol address
+    address public immutable limitOrderProtocol;
+
+    constructor(address _limitOrderProtocol, address _permit2)
+        RestrictTransferFrom(_permit2)
+    {
+        if (_limitOrderProtocol == address(0)) {
+            revert OneInchLOPExecutor__ZeroAddress();
+        }
+        limitOrderProtocol = _limitOrderProtocol;
+    }
+
+    /// @notice Fills a 1inch limit order
+    /// @param givenAmount The amount of input token to swap
+    /// @param data Encoded swap data containing the tokens and the signed order
+    /// @return calculatedAmount The amount of output token received
+    function swap(uint256 givenAmount, bytes calldata data)
+        external
+        payable
+        returns (uint256 calculatedAmount)
+    {
+        (
+            address tokenIn,
+            address tokenOut,
+            TransferType transferType,
+            bool approvalNeeded,
+            address receiver,
+            IOrderMixin.Order memory order,
+            bytes32 r,
+            bytes32 vs,
+            bytes calldata extension
+        ) = _decodeData(data);
+
+        if (
+            address(uint160(order.takerAsset)) != tokenIn
+                || address(uint160(order.makerAsset)) != tokenOut
+        ) {
+            revert OneInchLOPExecutor__InvalidOrderAssets();
+        }
+
+        uint256 amount =
+            givenAmount > order.takingAmount ? order.takingAmount : givenAmount;
+
+        _transfer(address(this), transferType, tokenIn, amount);
+
+        if (approvalNeeded) {
+            // slither-disable-next-line unused-return
+            IERC20(tokenIn).forceApprove(limitOrderProtocol, type(uint256).max);
+        }
+
+        uint256 takerTraits = _ARGS_HAS_TARGET
+            | (extension.length << _ARGS_EXTENSION_LENGTH_OFFSET);
+
+        // slither-disable-next-line unused-return
+        (calculatedAmount,,) = IOrderMixin(limitOrderProtocol).fillOrderArgs(
+            order,
+            r,
+            vs,
+            amount,
+            takerTraits,
+            abi.encodePacked(receiver, extension)
+        );
+    }
+
+    /// @dev Decodes the packed calldata
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (
+            address tokenIn,
+            address tokenOut,
+            TransferType transferType,
+            bool approvalNeeded,
+            address receiver,
+            IOrderMixin.Order memory order,
+            bytes32 r,
+            bytes32 vs,
+            bytes calldata extension
+        )
+    {
+        if (data.length < _MIN_DATA_LENGTH) {
+            revert OneInchLOPExecutor__InvalidDataLength();
+        }
+
+        tokenIn = address(bytes20(data[0:20]));
+        tokenOut = address(bytes20(data[20:40]));
+        transferType = TransferType(uint8(data[40]));
+        approvalNeeded = data[41] != 0;
+        receiver = address(bytes20(data[42:62]));
+        order = abi.decode(data[62:318], (IOrderMixin.Order));
+        r = bytes32(data[318:350]);
+        vs = bytes32(data[350:382]);
+        extension = data[382:];
+    }
+}
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
+import {
+    IERC20,
+    SafeERC20
+} from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+
+error PendleExecutor__InvalidDataLength();
+error PendleExecutor__ZeroAddress();
+
+interface IStandardizedYield {
+    function deposit(
+        address receiver,
+        address tokenIn,
+        uint256 amountTokenToDeposit,
+        uint256 minSharesOut
+    ) external payable returns (uint256 amountSharesOut);
+
+    function redeem(
+        address receiver,
+        uint256 amountSharesToRedeem,
+        address tokenOut,
+        uint256 minTokenOut,
+        bool burnFromInternalBalance
+    ) external returns (uint256 amountTokenOut);
+}
+
+interface IPendleRouter {
+    struct ApproxParams {
+        uint256 guessMin;
+        uint256 guessMax;
+        uint256 guessOffchain;
+        uint256 maxIteration;
+        uint256 eps;
+    }
+
+    enum OrderType {
+        SY_FOR_PT,
+        PT_FOR_SY,
+        SY_FOR_YT,
+        YT_FOR_SY
+    }
+
+    struct Order {
+        uint256 salt;
+        uint256 expiry;
+        uint256 nonce;
+        OrderType orderType;
+        address token;
+        address YT;
+        address maker;
+        address receiver;
+        uint256 makingAmount;
+        uint256 lnImpliedRate;
+        uint256 failSafeRate;
+        bytes permit;
+    }
+
+    struct FillOrderParams {
+        Order order;
+        bytes signature;
+        uint256 makingAmount;
+    }
+
+    struct LimitOrderData {
+        address limitRouter;
+        uint256 epsSkipMarket;
+        FillOrderParams[] normalFills;
+        FillOrderParams[] flashFills;
+        bytes optData;
+    }
+
+    function swapExactSyForPt(
+        address receiver,
+        address market,
+        uint256 exactSyIn,
+        uint256 minPtOut,
+        ApproxParams calldata guessPtOut,
+        LimitOrderData calldata limit
+    ) external returns (uint256 netPtOut, uint256 netSyFee);
+
+    function swapExactPtForSy(
+        address receiver,
+        address market,
+        uint256 exactPtIn,
+        uint256 minSyOut,
+        LimitOrderData calldata limit
+    ) external returns (uint256 netSyOut, uint256 netSyFee);
+}
+
+enum PendleDirection {
+    SyToPt,
+    PtToSy,
+    MintSy,
+    RedeemSy
+}
+
+/// @title PendleExecutor
+/// @notice Executor swapping on Pendle V2 markets: between the SY and the PT of
+/// a market (through the Pendle router), or between the SY and its underlying
+/// tokens (minting or redeeming the SY)
+/// @dev The minimum amounts out are left to the router's check of the solution
+/// output. Limit orders are not filled.
+contract PendleExecutor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    /// @dev 1 (direction) + 1 (transfer type) + 20 (receiver) + 20 (market) +
+    /// 20 (SY) + 20 (PT or underlying token)
+    uint256 private constant _DATA_LENGTH = 82;
+    /// @dev Iterations and precision (1e-4) of the router's search of the PT
+    /// amount bought for an exact SY amount in, as used by the Pendle SDK
+    uint256 private constant _APPROX_MAX_ITERATION = 256;
+    uint256 private constant _APPROX_EPS = 1e14;
+
+    /// @notice The Pendle router
+    address public immutable router;
+
+    constructor(address _router, address _permit2)
+        RestrictTransferFrom(_permit2)
+    {
+        if (_router == address(0)) {
+            revert PendleExecutor__ZeroAddress();
+        }
+        router = _router;
+    }
+
+    /// @notice Swaps the given amount on a Pendle market or SY
+    /// @param givenAmount The amount of token in to swap
+    /// @param data Encoded swap data
+    /// @return calculatedAmount The amount of token out received by the
+    /// receiver
+    // slither-disable-next-line locked-ether
+    function swap(uint256 givenAmount, bytes calldata data)
+        external
+        payable
+        returns (uint256 calculatedAmount)
+    {
+        (
+            PendleDirection direction,
+            TransferType transferType,
+            address receiver,
+            address market,
+            address sy,
+            address token
+        ) = _decodeData(data);
+
+        IPendleRouter.LimitOrderData memory noLimitOrders;
+        if (direction == PendleDirection.SyToPt) {
+            _transfer(address(this), transferType, sy, givenAmount);
+            IERC20(sy).forceApprove(router, givenAmount);
+            (calculatedAmount,) = IPendleRouter(router).swapExactSyForPt(
+                receiver,
+                market,
+                givenAmount,
+                0,
+                IPendleRouter.ApproxParams(
+                    0,
+                    type(uint256).max,
+                    0,
+                    _APPROX_MAX_ITERATION,
+                    _APPROX_EPS
+                ),
+                noLimitOrders
+            );
+        } else if (direction == PendleDirection.PtToSy) {
+            _transfer(address(this), transferType, token, givenAmount);
+            IERC20(token).forceApprove(router, givenAmount);
+            (calculatedAmount,) = IPendleRouter(router).swapExactPtForSy(
+                receiver, market, givenAmount, 0, noLimitOrders
+            );
+        } else if (direction == PendleDirection.MintSy) {
+            _transfer(address(this), transferType, token, givenAmount);
+            IERC20(token).forceApprove(sy, givenAmount);
+            calculatedAmount = IStandardizedYield(sy).deposit(
+                receiver, token, givenAmount, 0
+            );
+        } else {
+            _transfer(address(this), transferType, sy, givenAmount);
+            calculatedAmount = IStandardizedYield(sy).redeem(
+                receiver, givenAmount, token, 0, false
+            );
+        }
+    }
+
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (
+            PendleDirection direction,
+            TransferType transferType,
+            address receiver,
+            address market,
+            address sy,
+            address token
+        )
+    {
+        if (!LibUserData.hasValidLength(data, _DATA_LENGTH)) {
+            revert PendleExecutor__InvalidDataLength();
+        }
+        direction = PendleDirection(uint8(data[0]));
+        transferType = TransferType(uint8(data[1]));
+        receiver = address(bytes20(data[2:22]));
+        market = address(bytes20(data[22:42]));
+        sy = address(bytes20(data[42:62]));
+        token = address(bytes20(data[62:82]));
+    }
+}
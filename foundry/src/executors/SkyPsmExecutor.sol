@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "@interfaces/IExecutor.sol";
+import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import {RestrictTransferFrom} from "../RestrictTransferFrom.sol";
+import {LibUserData} from "../../lib/LibUserData.sol";
+
+error SkyPsmExecutor__InvalidDataLength();
+error SkyPsmExecutor__InvalidDirection();
+
+/// @dev Interface shared by the Sky Lite PSM and its USDS wrapper.
+interface ISkyPsm {
+    function sellGem(address usr, uint256 gemAmt)
+        external
+        returns (uint256 stableOutWad);
+
+    function buyGem(address usr, uint256 gemAmt)
+        external
+        returns (uint256 stableInWad);
+
+    function tout() external view returns (uint256);
+
+    function to18ConversionFactor() external view returns (uint256);
+}
+
+enum SkyPsmDirection {
+    SellGem,
+    BuyGem
+}
+
+contract SkyPsmExecutor is IExecutor, RestrictTransferFrom {
+    using SafeERC20 for IERC20;
+
+    uint256 private constant WAD = 1e18;
+
+    constructor(address _permit2) RestrictTransferFrom(_permit2) {}
+
+    // slither-disable-next-line locked-ether
+    function swap(uint256 givenAmount, bytes calldata data)
+        external
+        payable
+        returns (uint256 calculatedAmount)
+    {
+        address tokenIn;
+        address psm;
+        address receiver;
+        TransferType transferType;
+        SkyPsmDirection direction;
+        bool convertDecimals;
+        bool approvalNeeded;
+
+        (
+            tokenIn,
+            psm,
+            receiver,
+            transferType,
+            direction,
+            convertDecimals,
+            approvalNeeded
+        ) = _decodeData(data);
+
+        _transfer(address(this), transferType, tokenIn, givenAmount);
+        if (approvalNeeded) {
+            // The PSM transfers the token in from this contract
+            IERC20(tokenIn).forceApprove(psm, type(uint256).max);
+        }
+
+        if (direction == SkyPsmDirection.SellGem) {
+            calculatedAmount = ISkyPsm(psm).sellGem(receiver, givenAmount);
+        } else if (direction == SkyPsmDirection.BuyGem) {
+            // The PSM takes the gem amount in 18 decimals plus the `tout` fee, so we buy the
+            // largest gem amount the given amount pays for
+            uint256 conversionFactor =
+                convertDecimals ? ISkyPsm(psm).to18ConversionFactor() : 1;
+            calculatedAmount = (givenAmount * WAD)
+                / (conversionFactor * (WAD + ISkyPsm(psm).tout()));
+            ISkyPsm(psm).buyGem(receiver, calculatedAmount);
+        } else {
+            revert SkyPsmExecutor__InvalidDirection();
+        }
+    }
+
+    function _decodeData(bytes calldata data)
+        internal
+        pure
+        returns (
+            address tokenIn,
+            address psm,
+            address receiver,
+            TransferType transferType,
+            SkyPsmDirection direction,
+            bool convertDecimals,
+            bool approvalNeeded
+        )
+    {
+        if (!LibUserData.hasValidLength(data, 64)) {
+            revert SkyPsmExecutor__InvalidDataLength();
+        }
+        tokenIn = address(bytes20(data[0:20]));
+        psm = address(bytes20(data[20:40]));
+        receiver = address(bytes20(data[40:60]));
+        transferType = TransferType(uint8(data[60]));
+        direction = SkyPsmDirection(uint8(data[61]));
+        convertDecimals = data[62] != 0;
+        approvalNeeded = data[63] != 0;
+    }
+}
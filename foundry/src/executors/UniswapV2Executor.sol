@@ -41,6 +41,20 @@ contract UniswapV2Executor is IExecutor, RestrictTransferFrom {
         self = address(this);
     }
 
+    /// @dev Flag of the mode byte of exact out swaps.
+    uint8 private constant EXACT_OUT_FLAG = 1;
+    /// @dev Flag of the mode byte of swaps of fee-on-transfer tokens.
+    uint8 private constant FEE_ON_TRANSFER_FLAG = 2;
+
+    /**
+     * @dev The given amount is the amount in, unless the data ends with a
+     * mode byte with the exact out flag. In that case, it is the amount out
+     * and the calculated amount is the amount in.
+     * With the fee-on-transfer flag, the amounts are measured from balance
+     * differences, like `swapExactTokensForTokensSupportingFeeOnTransferTokens`
+     * does: the amount in is what the pool received, and the calculated amount
+     * is what the receiver received.
+     */
     // slither-disable-next-line locked-ether
     function swap(uint256 givenAmount, bytes calldata data)
         external
@@ -58,15 +72,31 @@ contract UniswapV2Executor is IExecutor, RestrictTransferFrom {
 
         _verifyPairAddress(target);
 
-        calculatedAmount = _getAmountOut(target, givenAmount, zeroForOne);
+        if (_hasFlag(data, FEE_ON_TRANSFER_FLAG)) {
+            return _swapSupportingFeeOnTransfer(
+                givenAmount, tokenIn, target, receiver, zeroForOne, transferType
+            );
+        }
 
-        _transfer(target, transferType, address(tokenIn), givenAmount);
+        uint256 amountIn;
+        uint256 amountOut;
+        if (_hasFlag(data, EXACT_OUT_FLAG)) {
+            amountOut = givenAmount;
+            amountIn = _getAmountIn(target, amountOut, zeroForOne);
+            calculatedAmount = amountIn;
+        } else {
+            amountIn = givenAmount;
+            amountOut = _getAmountOut(target, amountIn, zeroForOne);
+            calculatedAmount = amountOut;
+        }
+
+        _transfer(target, transferType, address(tokenIn), amountIn);
 
         IUniswapV2Pair pool = IUniswapV2Pair(target);
         if (zeroForOne) {
-            pool.swap(0, calculatedAmount, receiver, "");
+            pool.swap(0, amountOut, receiver, "");
         } else {
-            pool.swap(calculatedAmount, 0, receiver, "");
+            pool.swap(amountOut, 0, receiver, "");
         }
     }
 
@@ -81,7 +111,7 @@ contract UniswapV2Executor is IExecutor, RestrictTransferFrom {
             TransferType transferType
         )
     {
-        if (data.length != 62) {
+        if (data.length != 62 && data.length != 63) {
             revert UniswapV2Executor__InvalidDataLength();
         }
         inToken = IERC20(address(bytes20(data[0:20])));
@@ -91,14 +121,46 @@ contract UniswapV2Executor is IExecutor, RestrictTransferFrom {
         transferType = TransferType(uint8(data[61]));
     }
 
-    function _getAmountOut(address target, uint256 amountIn, bool zeroForOne)
+    function _hasFlag(bytes calldata data, uint8 flag)
+        internal
+        pure
+        returns (bool)
+    {
+        return data.length == 63 && (uint8(data[62]) & flag) != 0;
+    }
+
+    function _swapSupportingFeeOnTransfer(
+        uint256 givenAmount,
+        IERC20 tokenIn,
+        address target,
+        address receiver,
+        bool zeroForOne,
+        TransferType transferType
+    ) internal returns (uint256 calculatedAmount) {
+        _transfer(target, transferType, address(tokenIn), givenAmount);
+
+        IUniswapV2Pair pool = IUniswapV2Pair(target);
+        (uint112 reserveIn, uint112 reserveOut) =
+            _getReserves(pool, zeroForOne);
+        uint256 amountIn = tokenIn.balanceOf(target) - reserveIn;
+        uint256 amountOut =
+            _getAmountOutFromReserves(amountIn, reserveIn, reserveOut);
+
+        IERC20 tokenOut = IERC20(zeroForOne ? pool.token1() : pool.token0());
+        uint256 balanceBefore = tokenOut.balanceOf(receiver);
+        if (zeroForOne) {
+            pool.swap(0, amountOut, receiver, "");
+        } else {
+            pool.swap(amountOut, 0, receiver, "");
+        }
+        calculatedAmount = tokenOut.balanceOf(receiver) - balanceBefore;
+    }
+
+    function _getReserves(IUniswapV2Pair pair, bool zeroForOne)
         internal
         view
-        returns (uint256 amount)
+        returns (uint112 reserveIn, uint112 reserveOut)
     {
-        IUniswapV2Pair pair = IUniswapV2Pair(target);
-        uint112 reserveIn;
-        uint112 reserveOut;
         if (zeroForOne) {
             // slither-disable-next-line unused-return
             (reserveIn, reserveOut,) = pair.getReserves();
@@ -106,7 +168,23 @@ contract UniswapV2Executor is IExecutor, RestrictTransferFrom {
             // slither-disable-next-line unused-return
             (reserveOut, reserveIn,) = pair.getReserves();
         }
+    }
+
+    function _getAmountOut(address target, uint256 amountIn, bool zeroForOne)
+        internal
+        view
+        returns (uint256 amount)
+    {
+        (uint112 reserveIn, uint112 reserveOut) =
+            _getReserves(IUniswapV2Pair(target), zeroForOne);
+        amount = _getAmountOutFromReserves(amountIn, reserveIn, reserveOut);
+    }
 
+    function _getAmountOutFromReserves(
+        uint256 amountIn,
+        uint112 reserveIn,
+        uint112 reserveOut
+    ) internal view returns (uint256 amount) {
         require(reserveIn > 0 && reserveOut > 0, "L");
         uint256 amountInWithFee = amountIn * (10000 - feeBps);
         uint256 numerator = amountInWithFee * uint256(reserveOut);
@@ -114,6 +192,20 @@ contract UniswapV2Executor is IExecutor, RestrictTransferFrom {
         amount = numerator / denominator;
     }
 
+    function _getAmountIn(address target, uint256 amountOut, bool zeroForOne)
+        internal
+        view
+        returns (uint256 amount)
+    {
+        (uint112 reserveIn, uint112 reserveOut) =
+            _getReserves(IUniswapV2Pair(target), zeroForOne);
+        require(reserveIn > 0 && amountOut < reserveOut, "L");
+        uint256 numerator = uint256(reserveIn) * amountOut * 10000;
+        uint256 denominator =
+            (uint256(reserveOut) - amountOut) * (10000 - feeBps);
+        amount = (numerator / denominator) + 1;
+    }
+
     function _verifyPairAddress(address target) internal view {
         address token0 = IUniswapV2Pair(target).token0();
         address token1 = IUniswapV2Pair(target).token1();
@@ -38,11 +38,16 @@ contract UniswapV3Executor is IExecutor, ICallback, RestrictTransferFrom {
         self = address(this);
     }
 
+    /**
+     * @dev The given amount is the amount in, unless the data ends with a
+     * non-zero exact out flag. In that case, it is the amount out and the
+     * calculated amount is the amount in.
+     */
     // slither-disable-next-line locked-ether
-    function swap(uint256 amountIn, bytes calldata data)
+    function swap(uint256 givenAmount, bytes calldata data)
         external
         payable
-        returns (uint256 amountOut)
+        returns (uint256 calculatedAmount)
     {
         (
             address tokenIn,
@@ -62,22 +67,27 @@ contract UniswapV3Executor is IExecutor, ICallback, RestrictTransferFrom {
 
         bytes memory callbackData =
             _makeV3CallbackData(tokenIn, tokenOut, fee, transferType);
+        bool exactOut = _isExactOut(data);
 
         {
             (amount0, amount1) = pool.swap(
                 receiver,
                 zeroForOne,
-                // positive means exactIn
-                int256(amountIn),
+                // positive means exactIn, negative means exactOut
+                exactOut ? -int256(givenAmount) : int256(givenAmount),
                 zeroForOne ? MIN_SQRT_RATIO + 1 : MAX_SQRT_RATIO - 1,
                 callbackData
             );
         }
 
-        if (zeroForOne) {
-            amountOut = amount1 > 0 ? uint256(amount1) : uint256(-amount1);
+        // The calculated amount is the amount out for exact in swaps, and the
+        // amount in for exact out swaps
+        if (zeroForOne != exactOut) {
+            calculatedAmount =
+                amount1 > 0 ? uint256(amount1) : uint256(-amount1);
         } else {
-            amountOut = amount0 > 0 ? uint256(amount0) : uint256(-amount0);
+            calculatedAmount =
+                amount0 > 0 ? uint256(amount0) : uint256(-amount0);
         }
     }
 
@@ -150,7 +160,7 @@ contract UniswapV3Executor is IExecutor, ICallback, RestrictTransferFrom {
             TransferType transferType
         )
     {
-        if (data.length != 85) {
+        if (data.length != 85 && data.length != 86) {
             revert UniswapV3Executor__InvalidDataLength();
         }
         tokenIn = address(bytes20(data[0:20]));
@@ -162,6 +172,10 @@ contract UniswapV3Executor is IExecutor, ICallback, RestrictTransferFrom {
         transferType = TransferType(uint8(data[84]));
     }
 
+    function _isExactOut(bytes calldata data) internal pure returns (bool) {
+        return data.length == 86 && data[85] != 0;
+    }
+
     function _makeV3CallbackData(
         address tokenIn,
         address tokenOut,
@@ -42,6 +42,7 @@ error UniswapV4Executor__V4TooMuchRequested(
 );
 error UniswapV4Executor__InvalidAngstromAttestationDataLength(uint256 length);
 error UniswapV4Executor__ZeroAddressAngstromHook();
+error UniswapV4Executor__InvalidSplit();
 
 contract UniswapV4Executor is
     IExecutor,
@@ -61,6 +62,9 @@ contract UniswapV4Executor is
 
     bytes4 constant SWAP_EXACT_INPUT_SINGLE_SELECTOR = 0x6022fbcd;
     bytes4 constant SWAP_EXACT_INPUT_SELECTOR = 0x044f0d3d;
+    bytes4 constant SWAP_EXACT_INPUT_SPLIT_SELECTOR = 0x41191c69;
+    // Value of the zeroForOne byte marking a swap split over several branches
+    uint8 constant SPLIT_BRANCHES = 2;
 
     struct UniswapV4Pool {
         address intermediaryToken;
@@ -70,6 +74,11 @@ contract UniswapV4Executor is
         bytes hookData;
     }
 
+    struct UniswapV4Branch {
+        uint24 split;
+        PathKey[] path;
+    }
+
     constructor(
         IPoolManager _poolManager,
         address _angstromHook,
@@ -99,6 +108,9 @@ contract UniswapV4Executor is
         virtual
         returns (uint256 calculatedAmount)
     {
+        if (data.length > 40 && uint8(data[40]) == SPLIT_BRANCHES) {
+            return _swapSplit(amountIn, data);
+        }
         (
             address tokenIn,
             address tokenOut,
@@ -126,18 +138,7 @@ contract UniswapV4Executor is
                 pools[0].hookData
             );
         } else {
-            PathKey[] memory path = new PathKey[](pools.length);
-            for (uint256 i = 0; i < pools.length; i++) {
-                path[i] = PathKey({
-                    intermediateCurrency: Currency.wrap(
-                        pools[i].intermediaryToken
-                    ),
-                    fee: pools[i].fee,
-                    tickSpacing: pools[i].tickSpacing,
-                    hooks: IHooks(pools[i].hook),
-                    hookData: pools[i].hookData
-                });
-            }
+            PathKey[] memory path = _toPath(pools);
 
             Currency currencyIn = Currency.wrap(tokenIn);
             swapData = abi.encodeWithSelector(
@@ -156,6 +157,34 @@ contract UniswapV4Executor is
         return amountOut;
     }
 
+    /**
+     * @dev Swaps through several branches from tokenIn to tokenOut within one unlock, so the
+     * input is settled and the output taken only once.
+     */
+    function _swapSplit(uint256 amountIn, bytes calldata data)
+        internal
+        returns (uint256 calculatedAmount)
+    {
+        (
+            address tokenIn,
+            address tokenOut,
+            TransferType transferType,
+            address receiver,
+            UniswapV4Branch[] memory branches
+        ) = _decodeSplitData(data);
+        bytes memory swapData = abi.encodeWithSelector(
+            this.swapExactInputSplit.selector,
+            Currency.wrap(tokenIn),
+            Currency.wrap(tokenOut),
+            branches,
+            amountIn,
+            transferType,
+            receiver
+        );
+        bytes memory result = poolManager.unlock(swapData);
+        calculatedAmount = abi.decode(result, (uint128));
+    }
+
     // slither-disable-next-line dead-code
     function _decodeData(bytes calldata data)
         internal
@@ -180,8 +209,58 @@ contract UniswapV4Executor is
         transferType = TransferType(uint8(data[41]));
         receiver = address(bytes20(data[42:62]));
 
-        bytes calldata remaining = data[62:];
+        pools = _decodePools(data[62:]);
+    }
+
+    /**
+     * @dev Decodes the data of a swap split over several branches. After the header (with the
+     * zeroForOne byte set to SPLIT_BRANCHES), the branches are ple encoded, each one being its
+     * split (uint24, 0 for the remainder) followed by its pools, encoded like the pools of a
+     * sequential swap.
+     */
+    function _decodeSplitData(bytes calldata data)
+        internal
+        view
+        virtual
+        returns (
+            address tokenIn,
+            address tokenOut,
+            TransferType transferType,
+            address receiver,
+            UniswapV4Branch[] memory branches
+        )
+    {
+        if (data.length < 62) {
+            revert UniswapV4Executor__InvalidDataLength();
+        }
+
+        tokenIn = address(bytes20(data[0:20]));
+        tokenOut = address(bytes20(data[20:40]));
+        transferType = TransferType(uint8(data[41]));
+        receiver = address(bytes20(data[42:62]));
+
+        bytes calldata encodedBranches = data[62:];
+        branches = new UniswapV4Branch[](encodedBranches.size());
+        bytes calldata branch;
+        for (uint256 i = 0; i < branches.length; i++) {
+            (branch, encodedBranches) = encodedBranches.next();
+            if (branch.length < 3) {
+                revert UniswapV4Executor__InvalidDataLength();
+            }
+            branches[i] = UniswapV4Branch(
+                uint24(bytes3(branch[0:3])), _toPath(_decodePools(branch[3:]))
+            );
+        }
+    }
 
+    /**
+     * @dev Decodes the pools of a swap: the first one, followed by the ple encoded other ones.
+     */
+    function _decodePools(bytes calldata remaining)
+        internal
+        view
+        returns (UniswapV4Pool[] memory pools)
+    {
         // Decode first pool with hook data
         if (remaining.length < 48) {
             // 20 + 3 + 3 + 20 + 2 = 48 minimum
@@ -265,6 +344,23 @@ contract UniswapV4Executor is
         }
     }
 
+    function _toPath(UniswapV4Pool[] memory pools)
+        internal
+        pure
+        returns (PathKey[] memory path)
+    {
+        path = new PathKey[](pools.length);
+        for (uint256 i = 0; i < pools.length; i++) {
+            path[i] = PathKey({
+                intermediateCurrency: Currency.wrap(pools[i].intermediaryToken),
+                fee: pools[i].fee,
+                tickSpacing: pools[i].tickSpacing,
+                hooks: IHooks(pools[i].hook),
+                hookData: pools[i].hookData
+            });
+        }
+    }
+
     /**
      * @notice Handles the callback from the pool manager. This is used for callbacks from the router.
      */
@@ -303,11 +399,12 @@ contract UniswapV4Executor is
         if (
             selector != SWAP_EXACT_INPUT_SELECTOR
                 && selector != SWAP_EXACT_INPUT_SINGLE_SELECTOR
+                && selector != SWAP_EXACT_INPUT_SPLIT_SELECTOR
         ) {
             revert UniswapV4Executor__UnknownCallback(selector);
         }
 
-        // here we expect to call either `swapExactInputSingle` or `swapExactInput`. See `swap` to see how we encode the selector and the calldata
+        // here we expect to call either `swapExactInputSingle`, `swapExactInput` or `swapExactInputSplit`. See `swap` to see how we encode the selector and the calldata
         // slither-disable-next-line low-level-calls
         (bool success, bytes memory returnData) = _self.delegatecall(data);
         if (!success) {
@@ -366,10 +463,72 @@ contract UniswapV4Executor is
         TransferType transferType,
         address receiver
     ) external returns (uint128) {
+        _settle(currencyIn, amountIn, transferType);
+        (uint128 amountOut, Currency currencyOut) =
+            _swapPath(currencyIn, path, amountIn);
+        _take(currencyOut, receiver, _mapTakeAmount(amountOut, currencyOut));
+        return amountOut;
+    }
+
+    /**
+     * @notice Performs an exact input swap split over several paths from the same currency to
+     * the same currency. It settles the input and takes the output only once, for all the paths.
+     * @param currencyIn The currency of the input token.
+     * @param currencyOut The currency of the output token.
+     * @param branches The paths to swap along, with their share of the input amount. The share
+     * is a fraction of the input amount scaled to 0xffffff, or 0 for the remaining amount.
+     * @param amountIn The amount of tokens to swap in.
+     * @param transferType The type of action necessary to pay back the pool.
+     * @param receiver The address of the receiver.
+     */
+    function swapExactInputSplit(
+        Currency currencyIn,
+        Currency currencyOut,
+        UniswapV4Branch[] calldata branches,
+        uint128 amountIn,
+        TransferType transferType,
+        address receiver
+    ) external returns (uint128) {
+        _settle(currencyIn, amountIn, transferType);
         uint128 amountOut = 0;
-        Currency swapCurrencyIn = currencyIn;
+        uint256 remainingAmountIn = amountIn;
+        for (uint256 i = 0; i < branches.length; i++) {
+            uint256 branchAmountIn = branches[i].split > 0
+                ? (uint256(amountIn) * branches[i].split) / 0xffffff
+                : remainingAmountIn;
+            if (branchAmountIn > remainingAmountIn) {
+                revert UniswapV4Executor__InvalidSplit();
+            }
+            remainingAmountIn -= branchAmountIn;
+            (uint128 branchAmountOut, Currency branchCurrencyOut) =
+                _swapPath(currencyIn, branches[i].path, branchAmountIn);
+            if (
+                Currency.unwrap(branchCurrencyOut)
+                    != Currency.unwrap(currencyOut)
+            ) {
+                revert UniswapV4Executor__InvalidSplit();
+            }
+            amountOut += branchAmountOut;
+        }
+        if (remainingAmountIn != 0) {
+            revert UniswapV4Executor__InvalidSplit();
+        }
+        _take(currencyOut, receiver, _mapTakeAmount(amountOut, currencyOut));
+        return amountOut;
+    }
+
+    /**
+     * @dev Swaps along a path, without settling nor taking any currency.
+     * @return amountOut The amount out of the last swap of the path.
+     * @return currencyOut The currency out of the last swap of the path.
+     */
+    function _swapPath(
+        Currency currencyIn,
+        PathKey[] calldata path,
+        uint256 amountIn
+    ) private returns (uint128 amountOut, Currency currencyOut) {
+        currencyOut = currencyIn;
         uint256 swapAmountIn = amountIn;
-        _settle(currencyIn, amountIn, transferType);
         unchecked {
             uint256 pathLength = path.length;
             PathKey calldata pathKey;
@@ -377,7 +536,7 @@ contract UniswapV4Executor is
             for (uint256 i = 0; i < pathLength; i++) {
                 pathKey = path[i];
                 (PoolKey memory poolKey, bool zeroForOne) =
-                    pathKey.getPoolAndSwapDirection(swapCurrencyIn);
+                    pathKey.getPoolAndSwapDirection(currencyOut);
                 // The output delta will always be positive, except for when interacting with certain hook pools
                 amountOut = _swap(
                         poolKey,
@@ -387,16 +546,9 @@ contract UniswapV4Executor is
                     ).toUint128();
 
                 swapAmountIn = amountOut;
-                swapCurrencyIn = pathKey.intermediateCurrency;
+                currencyOut = pathKey.intermediateCurrency;
             }
         }
-
-        _take(
-            swapCurrencyIn, // at the end of the loop this is actually currency out
-            receiver,
-            _mapTakeAmount(amountOut, swapCurrencyIn)
-        );
-        return amountOut;
     }
 
     function _swap(
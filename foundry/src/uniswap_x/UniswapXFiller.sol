@@ -12,12 +12,19 @@ import "@openzeppelin/contracts/utils/Address.sol";
 import {TychoRouter} from "../TychoRouter.sol";
 
 error UniswapXFiller__AddressZero();
-error UniswapXFiller__BatchExecutionNotSupported();
-
+error UniswapXFiller__UnknownReactor();
+
+/// @title UniswapXFiller
+/// @notice Fills UniswapX orders using the TychoRouter as the fill source.
+/// @dev Orders are executed on the default reactor, or on any other reactor
+/// holding the REACTOR_ROLE (e.g. the Dutch V3 and priority order reactors).
+/// A batch of orders is filled with a single TychoRouter call: the callback
+/// data holds the approval flags followed by the router calldata, and the
+/// approval flags apply to the inputs and outputs of every order of the batch.
 contract UniswapXFiller is AccessControl, IReactorCallback {
     using SafeERC20 for IERC20;
 
-    // UniswapX V2DutchOrder Reactor
+    // Default reactor (UniswapX V2DutchOrder Reactor)
     IReactor public immutable reactor;
     address public immutable tychoRouter;
     address public immutable nativeAddress;
@@ -58,25 +65,54 @@ contract UniswapXFiller is AccessControl, IReactorCallback {
         reactor.executeWithCallback(order, callbackData);
     }
 
+    /// @notice Executes the given orders on the default reactor, filling all
+    /// of them with a single TychoRouter call.
+    function executeBatch(
+        SignedOrder[] calldata orders,
+        bytes calldata callbackData
+    ) external onlyRole(EXECUTOR_ROLE) {
+        reactor.executeBatchWithCallback(orders, callbackData);
+    }
+
+    /// @notice Executes the given order on another reactor.
+    /// @param targetReactor The reactor to execute on, must hold REACTOR_ROLE
+    function executeWithReactor(
+        IReactor targetReactor,
+        SignedOrder calldata order,
+        bytes calldata callbackData
+    ) external onlyRole(EXECUTOR_ROLE) {
+        _checkReactor(targetReactor);
+        targetReactor.executeWithCallback(order, callbackData);
+    }
+
+    /// @notice Executes the given orders on another reactor, filling all of
+    /// them with a single TychoRouter call.
+    /// @param targetReactor The reactor to execute on, must hold REACTOR_ROLE
+    function executeBatchWithReactor(
+        IReactor targetReactor,
+        SignedOrder[] calldata orders,
+        bytes calldata callbackData
+    ) external onlyRole(EXECUTOR_ROLE) {
+        _checkReactor(targetReactor);
+        targetReactor.executeBatchWithCallback(orders, callbackData);
+    }
+
     function reactorCallback(
         ResolvedOrder[] calldata resolvedOrders,
         bytes calldata callbackData
     ) external onlyRole(REACTOR_ROLE) {
-        if (resolvedOrders.length != 1) {
-            revert UniswapXFiller__BatchExecutionNotSupported();
-        }
-
-        ResolvedOrder memory order = resolvedOrders[0];
-
         bool tokenInApprovalNeeded = bool(uint8(callbackData[0]) == 1);
         bool tokenOutApprovalNeeded = bool(uint8(callbackData[1]) == 1);
         bytes calldata tychoCalldata = bytes(callbackData[2:]);
 
         // The TychoRouter will take the input tokens from the filler
         if (tokenInApprovalNeeded) {
-            // Native ETH input is not supported by UniswapX
-            IERC20(order.input.token)
-                .forceApprove(tychoRouter, type(uint256).max);
+            for (uint256 i = 0; i < resolvedOrders.length; i++) {
+                // Native ETH input is not supported by UniswapX
+                // slither-disable-next-line calls-loop
+                IERC20(resolvedOrders[i].input.token)
+                    .forceApprove(tychoRouter, type(uint256).max);
+            }
         }
 
         // slither-disable-next-line low-level-calls
@@ -93,17 +129,34 @@ contract UniswapXFiller is AccessControl, IReactorCallback {
         }
 
         if (tokenOutApprovalNeeded) {
-            // Multiple outputs are possible when taking fees - but token itself should
-            // not change.
-            OutputToken memory output = order.outputs[0];
-            if (output.token != nativeAddress) {
-                IERC20 token = IERC20(output.token);
-                token.forceApprove(address(reactor), type(uint256).max);
-            } else {
-                // With native ETH - the filler is responsible for transferring back
-                // to the reactor.
-                Address.sendValue(payable(address(reactor)), output.amount);
+            // The outputs are taken by the reactor executing the orders
+            uint256 nativeAmount = 0;
+            for (uint256 i = 0; i < resolvedOrders.length; i++) {
+                // Multiple outputs are possible when taking fees - but token itself should
+                // not change.
+                OutputToken[] calldata outputs = resolvedOrders[i].outputs;
+                if (outputs[0].token != nativeAddress) {
+                    // slither-disable-next-line calls-loop
+                    IERC20(outputs[0].token)
+                        .forceApprove(msg.sender, type(uint256).max);
+                } else {
+                    // With native ETH - the filler is responsible for transferring back
+                    // to the reactor.
+                    for (uint256 j = 0; j < outputs.length; j++) {
+                        nativeAmount += outputs[j].amount;
+                    }
+                }
             }
+            if (nativeAmount > 0) {
+                Address.sendValue(payable(msg.sender), nativeAmount);
+            }
+        }
+    }
+
+    /// @dev Reverts if the given reactor is not allowed to call back the filler
+    function _checkReactor(IReactor targetReactor) internal view {
+        if (!hasRole(REACTOR_ROLE, address(targetReactor))) {
+            revert UniswapXFiller__UnknownReactor();
         }
     }
 
@@ -29,6 +29,13 @@ contract BaseConstants {
     // Pool that uses the new factory
     address SLIPSTREAMS_WETH_BMI_POOL =
         0x1b7088833548A8C4Ab12a900a2f4bdFA49221081;
+
+    // Aerodrome V2 Factory
+    address AERODROME_V2_FACTORY_BASE =
+        0x420DD381b31aEf6683db6B902084cB0FFECe40Da;
+    // Aerodrome V2 volatile pool
+    address AERODROME_V2_WETH_USDC_POOL =
+        0xcDAC0d6c6C59727a65F871236188350531885C43;
 }
 
 contract Constants is Test, BaseConstants {
@@ -156,6 +163,12 @@ contract Constants is Test, BaseConstants {
     // Hashflow Router
     address HASHFLOW_ROUTER = 0x55084eE0fEf03f14a305cd24286359A35D735151;
 
+    // 1inch Limit Order Protocol
+    address ONEINCH_LOP = 0x111111125421cA6dc452d289314280a0f8842A65;
+
+    // Across Spoke Pool
+    address ACROSS_SPOKE_POOL = 0x5c7BCd6E7De5423a257D81B442095A1a6ced35C5;
+
     // Pool Code Init Hashes
     bytes32 USV2_POOL_CODE_INIT_HASH =
         0x96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f;
@@ -193,6 +206,35 @@ contract Constants is Test, BaseConstants {
     address constant REDEMPTION_MANAGER_ADDR =
         address(0xDadEf1fFBFeaAB4f68A9fD181395F68b4e4E7Ae0);
 
+    // Frax Ether addresses
+    address constant FRXETH_ADDR =
+        address(0x5E8422345238F34275888049021821E8E08CAa1f);
+    address constant SFRXETH_ADDR =
+        address(0xac3E018457B222d93114458476f3E3416Abbe38F);
+    address constant FRXETH_MINTER_ADDR =
+        address(0xbAFA44EFE7901E04E39Dad13167D089C559c1138);
+
+    // Pendle router
+    address constant PENDLE_ROUTER =
+        address(0x888888888889758F76e7103c6CbF23ABbF58F946);
+
+    // Lido withdrawal queue (unstETH)
+    address constant LIDO_WITHDRAWAL_QUEUE_ADDR =
+        address(0x889edC2eDab5f40e902b864aD4d7AdE8E412F9B1);
+
+    // Sky PSM addresses
+    address constant USDS_ADDR =
+        address(0xdC035D45d973E3EC169d2276DDab16f1e407384F);
+    address constant SKY_PSM_ADDR =
+        address(0xf6e72Db5454dd049d0788e411b06CfAF16853042);
+    address constant SKY_USDS_PSM_ADDR =
+        address(0xA188EEC8F81263234dA3622A406892F3D630f98c);
+
+    // Ambient addresses
+    address constant AMBIENT_DEX_ADDR =
+        address(0xAaAaAAAaA24eEeb8d57D431224f73832bC34f688);
+    uint256 constant AMBIENT_POOL_IDX = 420;
+
     /**
      * @dev Deploys a dummy contract with non-empty bytecode
      */
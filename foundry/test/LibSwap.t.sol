@@ -83,4 +83,148 @@ contract LibSwapTest is Test {
         assertEq(decodedExecutor, executor);
         assertEq(decodedProtocolData, protocolData);
     }
+
+    function testWideSplitSwap() public view {
+        uint16 tokenInIndex = 300;
+        uint16 tokenOutIndex = 2;
+        uint24 split = 3;
+        address executor = 0x1234567890123456789012345678901234567890;
+        bytes memory protocolData = abi.encodePacked(uint256(456));
+
+        bytes memory swap = abi.encodePacked(
+            tokenInIndex, tokenOutIndex, split, executor, protocolData
+        );
+        this.assertWideSplitSwap(
+            swap, tokenInIndex, tokenOutIndex, split, executor, protocolData
+        );
+    }
+
+    function assertWideSplitSwap(
+        bytes calldata swap,
+        uint16 tokenInIndex,
+        uint16 tokenOutIndex,
+        uint24 split,
+        address executor,
+        bytes calldata protocolData
+    ) public pure {
+        (
+            uint16 decodedTokenInIndex,
+            uint16 decodedTokenOutIndex,
+            uint24 decodedSplit,
+            address decodedExecutor,
+            bytes memory decodedProtocolData
+        ) = swap.decodeWideSplitSwap();
+        assertEq(decodedTokenInIndex, tokenInIndex);
+        assertEq(decodedTokenOutIndex, tokenOutIndex);
+        assertEq(decodedSplit, split);
+        assertEq(decodedExecutor, executor);
+        assertEq(decodedProtocolData, protocolData);
+    }
+
+    function testPreciseSplitSwap() public view {
+        uint8 tokenInIndex = 1;
+        uint8 tokenOutIndex = 2;
+        uint32 split = 333_333_333;
+        address executor = 0x1234567890123456789012345678901234567890;
+        bytes memory protocolData = abi.encodePacked(uint256(456));
+
+        bytes memory swap = abi.encodePacked(
+            tokenInIndex, tokenOutIndex, split, executor, protocolData
+        );
+        this.assertPreciseSplitSwap(
+            swap, tokenInIndex, tokenOutIndex, split, executor, protocolData
+        );
+    }
+
+    function assertPreciseSplitSwap(
+        bytes calldata swap,
+        uint8 tokenInIndex,
+        uint8 tokenOutIndex,
+        uint32 split,
+        address executor,
+        bytes calldata protocolData
+    ) public pure {
+        (
+            uint8 decodedTokenInIndex,
+            uint8 decodedTokenOutIndex,
+            uint32 decodedSplit,
+            address decodedExecutor,
+            bytes memory decodedProtocolData
+        ) = swap.decodePreciseSplitSwap();
+        assertEq(decodedTokenInIndex, tokenInIndex);
+        assertEq(decodedTokenOutIndex, tokenOutIndex);
+        assertEq(decodedSplit, split);
+        assertEq(decodedExecutor, executor);
+        assertEq(decodedProtocolData, protocolData);
+    }
+
+    function testIndexedSequentialSwap() public view {
+        address executor0 = 0x1234567890123456789012345678901234567890;
+        address executor1 = 0x2345678901234567890123456789012345678901;
+        bytes memory protocolData = abi.encodePacked(uint256(345));
+
+        bytes memory data = abi.encodePacked(
+            uint8(2), executor0, executor1, uint8(1), protocolData
+        );
+        this.assertIndexedSequentialSwap(data, executor1, protocolData);
+    }
+
+    function assertIndexedSequentialSwap(
+        bytes calldata data,
+        address executor,
+        bytes calldata protocolData
+    ) public pure {
+        (bytes calldata executors, bytes calldata swap) =
+            data.decodeExecutorTable();
+        assertEq(executors.length, 40);
+        (address decodedExecutor, bytes memory decodedProtocolData) =
+            swap.decodeIndexedSequentialSwap(executors);
+        assertEq(decodedExecutor, executor);
+        assertEq(decodedProtocolData, protocolData);
+    }
+
+    function testIndexedSplitSwap() public view {
+        uint8 tokenInIndex = 1;
+        uint8 tokenOutIndex = 2;
+        uint24 split = 3;
+        address executor = 0x1234567890123456789012345678901234567890;
+        bytes memory protocolData = abi.encodePacked(uint256(567));
+
+        bytes memory data = abi.encodePacked(
+            uint8(1),
+            executor,
+            tokenInIndex,
+            tokenOutIndex,
+            split,
+            uint8(0),
+            protocolData
+        );
+        this.assertIndexedSplitSwap(
+            data, tokenInIndex, tokenOutIndex, split, executor, protocolData
+        );
+    }
+
+    function assertIndexedSplitSwap(
+        bytes calldata data,
+        uint8 tokenInIndex,
+        uint8 tokenOutIndex,
+        uint24 split,
+        address executor,
+        bytes calldata protocolData
+    ) public pure {
+        (bytes calldata executors, bytes calldata swap) =
+            data.decodeExecutorTable();
+        (
+            uint8 decodedTokenInIndex,
+            uint8 decodedTokenOutIndex,
+            uint24 decodedSplit,
+            address decodedExecutor,
+            bytes memory decodedProtocolData
+        ) = swap.decodeIndexedSplitSwap(executors);
+        assertEq(decodedTokenInIndex, tokenInIndex);
+        assertEq(decodedTokenOutIndex, tokenOutIndex);
+        assertEq(decodedSplit, split);
+        assertEq(decodedExecutor, executor);
+        assertEq(decodedProtocolData, protocolData);
+    }
 }
@@ -85,4 +85,90 @@ contract Permit2TestHelper is Constants {
 
         return abi.encodePacked(r, s, v);
     }
+
+    /**
+     * @dev Handles the Permit2 batch approval process for Alice, allowing the spender to spend
+     *      `amounts[i]` of `tokens[i]` on her behalf with a single signature.
+     *
+     * @param tokens The addresses of the tokens being approved.
+     * @param spender The address allowed to transfer the tokens.
+     * @param amounts The amounts of tokens to approve for transfer.
+     * @return permitBatch The `PermitBatch` struct containing the approval details.
+     * @return signature The EIP-712 signature for the approval.
+     */
+    function handlePermit2BatchApproval(
+        address[] memory tokens,
+        address spender,
+        uint256[] memory amounts
+    ) internal returns (IAllowanceTransfer.PermitBatch memory, bytes memory) {
+        IAllowanceTransfer.PermitDetails[] memory details =
+            new IAllowanceTransfer.PermitDetails[](tokens.length);
+        for (uint256 i = 0; i < tokens.length; i++) {
+            IERC20(tokens[i]).approve(PERMIT2_ADDRESS, amounts[i]);
+            details[i] = IAllowanceTransfer.PermitDetails({
+                token: tokens[i],
+                amount: uint160(amounts[i]),
+                expiration: uint48(block.timestamp + 1 days),
+                nonce: 0
+            });
+        }
+        IAllowanceTransfer.PermitBatch memory permitBatch = IAllowanceTransfer
+            .PermitBatch({
+            details: details,
+            spender: spender,
+            sigDeadline: block.timestamp + 1 days
+        });
+
+        bytes memory signature = signPermit2Batch(permitBatch, ALICE_PK);
+        return (permitBatch, signature);
+    }
+
+    /**
+     * @dev Signs a Permit2 `PermitBatch` struct with the given private key.
+     * @param permit The `PermitBatch` struct to sign.
+     * @param privateKey The private key of the signer.
+     * @return The signature as a `bytes` array.
+     */
+    function signPermit2Batch(
+        IAllowanceTransfer.PermitBatch memory permit,
+        uint256 privateKey
+    ) internal view returns (bytes memory) {
+        bytes32 _PERMIT_DETAILS_TYPEHASH = keccak256(
+            "PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)"
+        );
+        bytes32 _PERMIT_BATCH_TYPEHASH = keccak256(
+            "PermitBatch(PermitDetails[] details,address spender,uint256 sigDeadline)PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)"
+        );
+        bytes32 domainSeparator = keccak256(
+            abi.encode(
+                keccak256(
+                    "EIP712Domain(string name,uint256 chainId,address verifyingContract)"
+                ),
+                keccak256("Permit2"),
+                block.chainid,
+                PERMIT2_ADDRESS
+            )
+        );
+        bytes32[] memory detailsHashes = new bytes32[](permit.details.length);
+        for (uint256 i = 0; i < permit.details.length; i++) {
+            detailsHashes[i] = keccak256(
+                abi.encode(_PERMIT_DETAILS_TYPEHASH, permit.details[i])
+            );
+        }
+        bytes32 permitHash = keccak256(
+            abi.encode(
+                _PERMIT_BATCH_TYPEHASH,
+                keccak256(abi.encodePacked(detailsHashes)),
+                permit.spender,
+                permit.sigDeadline
+            )
+        );
+
+        bytes32 digest = keccak256(
+            abi.encodePacked("\x19\x01", domainSeparator, permitHash)
+        );
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(privateKey, digest);
+
+        return abi.encodePacked(r, s, v);
+    }
 }
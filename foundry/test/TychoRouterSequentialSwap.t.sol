@@ -94,6 +94,121 @@ contract TychoRouterSequentialSwapTest is TychoRouterTestSetup {
         assertEq(IERC20(WETH_ADDR).balanceOf(tychoRouterAddr), 0);
     }
 
+    function testSequentialSwapIndexed() public {
+        // Trade 1 WETH for USDC through DAI - see _getSequentialSwaps for more info
+        // Both swaps refer to the Uniswap V2 executor by its index in the table
+        uint256 amountIn = 1 ether;
+        deal(WETH_ADDR, ALICE, amountIn);
+
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(tychoRouterAddr, amountIn);
+
+        address[] memory executors = new address[](1);
+        executors[0] = address(usv2Executor);
+        bytes[] memory swaps = new bytes[](2);
+        swaps[0] = encodeIndexedSequentialSwap(
+            uint8(0),
+            encodeUniswapV2Swap(
+                WETH_ADDR,
+                WETH_DAI_POOL,
+                DAI_USDC_POOL,
+                false,
+                RestrictTransferFrom.TransferType.TransferFrom
+            )
+        );
+        swaps[1] = encodeIndexedSequentialSwap(
+            uint8(0),
+            encodeUniswapV2Swap(
+                DAI_ADDR,
+                DAI_USDC_POOL,
+                ALICE,
+                true,
+                RestrictTransferFrom.TransferType.None
+            )
+        );
+
+        tychoRouter.sequentialSwapIndexed(
+            amountIn,
+            WETH_ADDR,
+            USDC_ADDR,
+            1000_000000, // min amount
+            false,
+            false,
+            ALICE,
+            true,
+            bytes.concat(encodeExecutorTable(executors), pleEncode(swaps))
+        );
+
+        uint256 usdcBalance = IERC20(USDC_ADDR).balanceOf(ALICE);
+        assertEq(usdcBalance, 2005810530);
+        assertEq(IERC20(WETH_ADDR).balanceOf(tychoRouterAddr), 0);
+    }
+
+    function testSequentialSwapIndexedUnapprovedExecutor() public {
+        uint256 amountIn = 1 ether;
+        deal(WETH_ADDR, ALICE, amountIn);
+
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(tychoRouterAddr, amountIn);
+
+        address[] memory executors = new address[](2);
+        executors[0] = address(usv2Executor);
+        executors[1] = BOB;
+        bytes[] memory swaps = new bytes[](1);
+        swaps[0] = encodeIndexedSequentialSwap(
+            uint8(1),
+            encodeUniswapV2Swap(
+                WETH_ADDR,
+                WETH_DAI_POOL,
+                ALICE,
+                false,
+                RestrictTransferFrom.TransferType.TransferFrom
+            )
+        );
+
+        vm.expectRevert(
+            abi.encodeWithSelector(
+                Dispatcher__UnapprovedExecutor.selector, BOB
+            )
+        );
+        tychoRouter.sequentialSwapIndexed(
+            amountIn,
+            WETH_ADDR,
+            DAI_ADDR,
+            1, // min amount
+            false,
+            false,
+            ALICE,
+            true,
+            bytes.concat(encodeExecutorTable(executors), pleEncode(swaps))
+        );
+    }
+
+    function testSequentialSwapIndexedEmptyExecutorTable() public {
+        uint256 amountIn = 1 ether;
+        deal(WETH_ADDR, ALICE, amountIn);
+
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(tychoRouterAddr, amountIn);
+
+        bytes[] memory swaps = _getSequentialSwaps();
+
+        vm.expectRevert(TychoRouter__InvalidDataLength.selector);
+        tychoRouter.sequentialSwapIndexed(
+            amountIn,
+            WETH_ADDR,
+            USDC_ADDR,
+            1000_000000, // min amount
+            false,
+            false,
+            ALICE,
+            true,
+            bytes.concat(
+                encodeExecutorTable(new address[](0)), pleEncode(swaps)
+            )
+        );
+    }
+
     function testSequentialSwapUndefinedMinAmount() public {
         // Trade 1 WETH for USDC through DAI - see _getSequentialSwaps for more info
         uint256 amountIn = 1 ether;
@@ -4,6 +4,8 @@ pragma solidity ^0.8.26;
 import "@src/executors/UniswapV4Executor.sol";
 import {TychoRouter} from "@src/TychoRouter.sol";
 import "./TychoRouterTestSetup.sol";
+import "./protocols/UniswapV4Utils.sol";
+import "@interfaces/IReceiverCallback.sol";
 
 contract TychoRouterSingleSwapTest is TychoRouterTestSetup {
     function testSingleSwapPermit2() public {
@@ -93,6 +95,301 @@ contract TychoRouterSingleSwapTest is TychoRouterTestSetup {
         vm.stopPrank();
     }
 
+    function testExactOutSingleSwap() public {
+        // Buy exactly 2000 DAI with at most 1 WETH with 1 swap on Uniswap V2
+        uint256 maxAmountIn = 1 ether;
+        uint256 amountOut = 2000 * 1e18;
+
+        deal(WETH_ADDR, ALICE, maxAmountIn);
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(address(tychoRouterAddr), maxAmountIn);
+
+        bytes memory protocolData = abi.encodePacked(
+            encodeUniswapV2Swap(
+                WETH_ADDR,
+                WETH_DAI_POOL,
+                ALICE,
+                false,
+                RestrictTransferFrom.TransferType.TransferFrom
+            ),
+            // exact out
+            true
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv2Executor), protocolData);
+
+        uint256 amountIn = tychoRouter.exactOutSingleSwap(
+            amountOut,
+            WETH_ADDR,
+            DAI_ADDR,
+            maxAmountIn,
+            false,
+            ALICE,
+            true,
+            swap
+        );
+
+        assertLt(amountIn, maxAmountIn);
+        assertEq(IERC20(DAI_ADDR).balanceOf(ALICE), amountOut);
+        // Only the amount needed was taken from the user
+        assertEq(IERC20(WETH_ADDR).balanceOf(ALICE), maxAmountIn - amountIn);
+        assertEq(IERC20(WETH_ADDR).balanceOf(tychoRouterAddr), 0);
+
+        vm.stopPrank();
+    }
+
+    function testSingleSwapWithOutputFee() public {
+        // Trade 1 WETH for DAI with 1 swap on Uniswap V2, taking a 0.25% fee on the DAI bought
+        // The swap sends the DAI to the router, which sends the fee to BOB and the rest to ALICE
+        uint256 amountIn = 1 ether;
+
+        deal(WETH_ADDR, ALICE, amountIn);
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(address(tychoRouterAddr), amountIn);
+
+        bytes memory protocolData = encodeUniswapV2Swap(
+            WETH_ADDR,
+            WETH_DAI_POOL,
+            tychoRouterAddr,
+            false,
+            RestrictTransferFrom.TransferType.TransferFrom
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv2Executor), protocolData);
+
+        uint256 amountOut = tychoRouter.singleSwapWithFee(
+            amountIn,
+            WETH_ADDR,
+            DAI_ADDR,
+            2000 * 1e18,
+            false,
+            false,
+            ALICE,
+            true,
+            TychoRouter.Fee(BOB, 25, false),
+            swap
+        );
+
+        uint256 swapAmountOut = 2018817438608734439722;
+        uint256 feeAmount = swapAmountOut * 25 / 10000;
+        assertEq(amountOut, swapAmountOut - feeAmount);
+        assertEq(IERC20(DAI_ADDR).balanceOf(ALICE), amountOut);
+        assertEq(IERC20(DAI_ADDR).balanceOf(BOB), feeAmount);
+        assertEq(IERC20(DAI_ADDR).balanceOf(tychoRouterAddr), 0);
+
+        vm.stopPrank();
+    }
+
+    function testSingleSwapWithInputFee() public {
+        // Trade 1 WETH for DAI with 1 swap on Uniswap V2, taking a 0.25% fee on the WETH sold
+        // The fee is transferred from ALICE to BOB, and the rest of the WETH is swapped
+        uint256 amountIn = 1 ether;
+        uint256 feeAmount = amountIn * 25 / 10000;
+
+        deal(WETH_ADDR, ALICE, amountIn);
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(address(tychoRouterAddr), amountIn);
+
+        bytes memory protocolData = encodeUniswapV2Swap(
+            WETH_ADDR,
+            WETH_DAI_POOL,
+            ALICE,
+            false,
+            RestrictTransferFrom.TransferType.TransferFrom
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv2Executor), protocolData);
+
+        uint256 amountOut = tychoRouter.singleSwapWithFee(
+            amountIn,
+            WETH_ADDR,
+            DAI_ADDR,
+            2000 * 1e18,
+            false,
+            false,
+            ALICE,
+            true,
+            TychoRouter.Fee(BOB, 25, true),
+            swap
+        );
+
+        assertGt(amountOut, 0);
+        assertEq(IERC20(DAI_ADDR).balanceOf(ALICE), amountOut);
+        assertEq(IERC20(WETH_ADDR).balanceOf(BOB), feeAmount);
+        assertEq(IERC20(WETH_ADDR).balanceOf(ALICE), 0);
+
+        vm.stopPrank();
+    }
+
+    function testSingleSwapWithInvalidFee() public {
+        uint256 amountIn = 1 ether;
+
+        deal(WETH_ADDR, ALICE, amountIn);
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(address(tychoRouterAddr), amountIn);
+
+        bytes memory protocolData = encodeUniswapV2Swap(
+            WETH_ADDR,
+            WETH_DAI_POOL,
+            ALICE,
+            false,
+            RestrictTransferFrom.TransferType.TransferFrom
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv2Executor), protocolData);
+
+        vm.expectRevert(TychoRouter__InvalidFee.selector);
+        tychoRouter.singleSwapWithFee(
+            amountIn,
+            WETH_ADDR,
+            DAI_ADDR,
+            2000 * 1e18,
+            false,
+            false,
+            ALICE,
+            true,
+            TychoRouter.Fee(BOB, 10001, true),
+            swap
+        );
+    }
+
+    function testSingleSwapWithCallback() public {
+        // Trade 1 WETH for DAI with 1 swap on Uniswap V2, calling back the receiver with the DAI bought
+        // The swap sends the DAI to the router, which sends it to the receiver and calls it back
+        uint256 amountIn = 1 ether;
+        ReceiverCallbackMock receiver = new ReceiverCallbackMock();
+
+        deal(WETH_ADDR, ALICE, amountIn);
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(address(tychoRouterAddr), amountIn);
+
+        bytes memory protocolData = encodeUniswapV2Swap(
+            WETH_ADDR,
+            WETH_DAI_POOL,
+            tychoRouterAddr,
+            false,
+            RestrictTransferFrom.TransferType.TransferFrom
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv2Executor), protocolData);
+
+        uint256 amountOut = tychoRouter.singleSwapWithCallback(
+            amountIn,
+            WETH_ADDR,
+            DAI_ADDR,
+            2000 * 1e18,
+            false,
+            false,
+            address(receiver),
+            true,
+            hex"beef",
+            swap
+        );
+
+        assertEq(amountOut, 2018817438608734439722);
+        assertEq(IERC20(DAI_ADDR).balanceOf(address(receiver)), amountOut);
+        assertEq(IERC20(DAI_ADDR).balanceOf(tychoRouterAddr), 0);
+        assertEq(receiver.token(), DAI_ADDR);
+        assertEq(receiver.amount(), amountOut);
+        assertEq(receiver.data(), hex"beef");
+
+        vm.stopPrank();
+    }
+
+    function testSingleSwapWithCallbackOutputETH() public {
+        // Trade 3000 USDC for ETH with 1 swap on Uniswap V4, calling back the receiver with the ETH bought
+        // The swap sends the ETH to the router, which sends it to the receiver and calls it back
+        uint256 amountIn = 3000_000000;
+        ReceiverCallbackMock receiver = new ReceiverCallbackMock();
+
+        deal(USDC_ADDR, ALICE, amountIn);
+        vm.startPrank(ALICE);
+        IERC20(USDC_ADDR).approve(address(tychoRouterAddr), amountIn);
+
+        UniswapV4Executor.UniswapV4Pool[] memory pools =
+            new UniswapV4Executor.UniswapV4Pool[](1);
+        pools[0] = UniswapV4Executor.UniswapV4Pool({
+            intermediaryToken: address(0),
+            fee: uint24(3000),
+            tickSpacing: int24(60),
+            hook: address(0),
+            hookData: bytes("")
+        });
+
+        bytes memory protocolData = UniswapV4Utils.encodeExactInput(
+            USDC_ADDR,
+            address(0),
+            false,
+            RestrictTransferFrom.TransferType.TransferFrom,
+            tychoRouterAddr,
+            pools
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv4Executor), protocolData);
+
+        uint256 amountOut = tychoRouter.singleSwapWithCallback(
+            amountIn,
+            USDC_ADDR,
+            address(0),
+            1 ether,
+            false,
+            false,
+            address(receiver),
+            true,
+            hex"beef",
+            swap
+        );
+
+        assertEq(amountOut, 1474406268748155809);
+        assertEq(address(receiver).balance, amountOut);
+        assertEq(tychoRouterAddr.balance, 0);
+        assertEq(receiver.token(), address(0));
+        assertEq(receiver.amount(), amountOut);
+        assertEq(receiver.data(), hex"beef");
+
+        vm.stopPrank();
+    }
+
+    function testSingleSwapWithCallbackNotImplemented() public {
+        uint256 amountIn = 1 ether;
+
+        deal(WETH_ADDR, ALICE, amountIn);
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(address(tychoRouterAddr), amountIn);
+
+        bytes memory protocolData = encodeUniswapV2Swap(
+            WETH_ADDR,
+            WETH_DAI_POOL,
+            tychoRouterAddr,
+            false,
+            RestrictTransferFrom.TransferType.TransferFrom
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv2Executor), protocolData);
+
+        // ALICE has no code to call back
+        vm.expectRevert();
+        tychoRouter.singleSwapWithCallback(
+            amountIn,
+            WETH_ADDR,
+            DAI_ADDR,
+            2000 * 1e18,
+            false,
+            false,
+            ALICE,
+            true,
+            hex"beef",
+            swap
+        );
+    }
+
     function testSingleSwapUndefinedMinAmount() public {
         // Trade 1 WETH for DAI with 1 swap on Uniswap V2
         // Checks amount out at the end
@@ -424,3 +721,21 @@ contract TychoRouterSingleSwapTest is TychoRouterTestSetup {
         assertEq(balanceAfter - balanceBefore, 2018817438608734439722);
     }
 }
+
+contract ReceiverCallbackMock is IReceiverCallback {
+    address public token;
+    uint256 public amount;
+    bytes public data;
+
+    function receiveSwapOutput(
+        address token_,
+        uint256 amount_,
+        bytes calldata data_
+    ) external {
+        token = token_;
+        amount = amount_;
+        data = data_;
+    }
+
+    receive() external payable {}
+}
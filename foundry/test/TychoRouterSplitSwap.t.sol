@@ -122,6 +122,67 @@ contract TychoRouterSplitSwapTest is TychoRouterTestSetup {
         assertEq(IERC20(WETH_ADDR).balanceOf(tychoRouterAddr), 0);
     }
 
+    function testSplitSwapPermit2Batch() public {
+        // Two trades of 1 WETH for USDC through DAI and WBTC - see _getSplitSwaps for more info.
+        // A single batch permit approves the WETH of both trades, and DAI.
+        uint256 amountIn = 1 ether;
+        deal(WETH_ADDR, ALICE, 2 * amountIn);
+
+        vm.startPrank(ALICE);
+        address[] memory tokens = new address[](2);
+        tokens[0] = WETH_ADDR;
+        tokens[1] = DAI_ADDR;
+        uint256[] memory amounts = new uint256[](2);
+        amounts[0] = 2 * amountIn;
+        amounts[1] = 1000 ether;
+        (
+            IAllowanceTransfer.PermitBatch memory permitBatch,
+            bytes memory signature
+        ) = handlePermit2BatchApproval(tokens, tychoRouterAddr, amounts);
+
+        bytes[] memory swaps = _getSplitSwaps(true);
+
+        tychoRouter.splitSwapPermit2Batch(
+            amountIn,
+            WETH_ADDR,
+            USDC_ADDR,
+            1, // min amount
+            false,
+            false,
+            4,
+            ALICE,
+            permitBatch,
+            signature,
+            pleEncode(swaps)
+        );
+        uint256 usdcBalance = IERC20(USDC_ADDR).balanceOf(ALICE);
+        assertEq(usdcBalance, 1989737355);
+        (uint160 daiAllowance,,) = IAllowanceTransfer(PERMIT2_ADDRESS)
+            .allowance(ALICE, DAI_ADDR, tychoRouterAddr);
+        assertEq(daiAllowance, 1000 ether);
+
+        // The second trade spends the allowance granted by the first one
+        IAllowanceTransfer.PermitBatch memory emptyPermitBatch;
+        tychoRouter.splitSwapPermit2Batch(
+            amountIn,
+            WETH_ADDR,
+            USDC_ADDR,
+            1, // min amount
+            false,
+            false,
+            4,
+            ALICE,
+            emptyPermitBatch,
+            "",
+            pleEncode(swaps)
+        );
+
+        assertGt(IERC20(USDC_ADDR).balanceOf(ALICE), usdcBalance);
+        assertEq(IERC20(WETH_ADDR).balanceOf(ALICE), 0);
+        assertEq(IERC20(WETH_ADDR).balanceOf(tychoRouterAddr), 0);
+        vm.stopPrank();
+    }
+
     function testSplitSwapNoPermit2() public {
         // Trade 1 WETH for USDC through DAI and WBTC - see _getSplitSwaps for more info
         uint256 amountIn = 1 ether;
@@ -150,6 +211,191 @@ contract TychoRouterSplitSwapTest is TychoRouterTestSetup {
         assertEq(IERC20(WETH_ADDR).balanceOf(ALICE), 0);
     }
 
+    function testSplitSwapIndexed() public {
+        // Trade 1 WETH for USDC through DAI and WBTC - see _getSplitSwaps for more info
+        // All the swaps refer to the Uniswap V2 executor by its index in the table
+        uint256 amountIn = 1 ether;
+        deal(WETH_ADDR, ALICE, amountIn);
+
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(tychoRouterAddr, amountIn);
+
+        address[] memory executors = new address[](1);
+        executors[0] = address(usv2Executor);
+        bytes[] memory swaps = new bytes[](4);
+        // WETH -> WBTC (60%)
+        swaps[0] = encodeIndexedSplitSwap(
+            uint8(0),
+            uint8(1),
+            (0xffffff * 60) / 100, // 60%
+            uint8(0),
+            encodeUniswapV2Swap(
+                WETH_ADDR,
+                WETH_WBTC_POOL,
+                tychoRouterAddr,
+                false,
+                RestrictTransferFrom.TransferType.TransferFrom
+            )
+        );
+        // WBTC -> USDC
+        swaps[1] = encodeIndexedSplitSwap(
+            uint8(1),
+            uint8(2),
+            uint24(0),
+            uint8(0),
+            encodeUniswapV2Swap(
+                WBTC_ADDR,
+                USDC_WBTC_POOL,
+                ALICE,
+                true,
+                RestrictTransferFrom.TransferType.Transfer
+            )
+        );
+        // WETH -> DAI
+        swaps[2] = encodeIndexedSplitSwap(
+            uint8(0),
+            uint8(3),
+            uint24(0),
+            uint8(0),
+            encodeUniswapV2Swap(
+                WETH_ADDR,
+                WETH_DAI_POOL,
+                tychoRouterAddr,
+                false,
+                RestrictTransferFrom.TransferType.TransferFrom
+            )
+        );
+        // DAI -> USDC
+        swaps[3] = encodeIndexedSplitSwap(
+            uint8(3),
+            uint8(2),
+            uint24(0),
+            uint8(0),
+            encodeUniswapV2Swap(
+                DAI_ADDR,
+                DAI_USDC_POOL,
+                ALICE,
+                true,
+                RestrictTransferFrom.TransferType.Transfer
+            )
+        );
+
+        tychoRouter.splitSwapIndexed(
+            amountIn,
+            WETH_ADDR,
+            USDC_ADDR,
+            1000_000000, // min amount
+            false,
+            false,
+            4,
+            ALICE,
+            true,
+            bytes.concat(encodeExecutorTable(executors), pleEncode(swaps))
+        );
+
+        uint256 usdcBalance = IERC20(USDC_ADDR).balanceOf(ALICE);
+        assertEq(usdcBalance, 1989737355);
+        assertEq(IERC20(WETH_ADDR).balanceOf(ALICE), 0);
+    }
+
+    function testMultiInputSplitSwap() public {
+        // Trade 1 WETH and 0.01 WBTC for USDC
+        //  1 WETH    ->   DAI   ->
+        //                            USDC
+        //  0.01 WBTC           ->
+        //        (univ2)     (univ2)
+        uint256 amountInWeth = 1 ether;
+        uint256 amountInWbtc = 1_000_000;
+        deal(WETH_ADDR, ALICE, amountInWeth);
+        deal(WBTC_ADDR, ALICE, amountInWbtc);
+
+        vm.startPrank(ALICE);
+        IERC20(WETH_ADDR).approve(tychoRouterAddr, amountInWeth);
+        IERC20(WBTC_ADDR).approve(tychoRouterAddr, amountInWbtc);
+
+        bytes[] memory swaps = new bytes[](3);
+        // WETH -> DAI
+        swaps[0] = encodeSplitSwap(
+            uint8(0),
+            uint8(2),
+            uint24(0),
+            address(usv2Executor),
+            encodeUniswapV2Swap(
+                WETH_ADDR,
+                WETH_DAI_POOL,
+                tychoRouterAddr,
+                false,
+                RestrictTransferFrom.TransferType.Transfer
+            )
+        );
+        // DAI -> USDC
+        swaps[1] = encodeSplitSwap(
+            uint8(2),
+            uint8(3),
+            uint24(0),
+            address(usv2Executor),
+            encodeUniswapV2Swap(
+                DAI_ADDR,
+                DAI_USDC_POOL,
+                ALICE,
+                true,
+                RestrictTransferFrom.TransferType.Transfer
+            )
+        );
+        // WBTC -> USDC
+        swaps[2] = encodeSplitSwap(
+            uint8(1),
+            uint8(3),
+            uint24(0),
+            address(usv2Executor),
+            encodeUniswapV2Swap(
+                WBTC_ADDR,
+                USDC_WBTC_POOL,
+                ALICE,
+                true,
+                RestrictTransferFrom.TransferType.Transfer
+            )
+        );
+
+        uint256[] memory amountsIn = new uint256[](2);
+        amountsIn[0] = amountInWeth;
+        amountsIn[1] = amountInWbtc;
+        address[] memory tokensIn = new address[](2);
+        tokensIn[0] = WETH_ADDR;
+        tokensIn[1] = WBTC_ADDR;
+
+        uint256 amountOut = tychoRouter.multiInputSplitSwap(
+            amountsIn,
+            tokensIn,
+            USDC_ADDR,
+            1000_000000, // min amount
+            false,
+            4,
+            ALICE,
+            true,
+            pleEncode(swaps)
+        );
+
+        uint256 usdcBalance = IERC20(USDC_ADDR).balanceOf(ALICE);
+        assertGt(amountOut, 1000_000000);
+        assertEq(usdcBalance, amountOut);
+        assertEq(IERC20(WETH_ADDR).balanceOf(ALICE), 0);
+        assertEq(IERC20(WBTC_ADDR).balanceOf(ALICE), 0);
+        assertEq(IERC20(WETH_ADDR).balanceOf(tychoRouterAddr), 0);
+        assertEq(IERC20(WBTC_ADDR).balanceOf(tychoRouterAddr), 0);
+    }
+
+    function testMultiInputSplitSwapInvalidDataLength() public {
+        uint256[] memory amountsIn = new uint256[](2);
+        address[] memory tokensIn = new address[](1);
+        tokensIn[0] = WETH_ADDR;
+
+        vm.expectRevert(TychoRouter__InvalidDataLength.selector);
+        tychoRouter.multiInputSplitSwap(
+            amountsIn, tokensIn, USDC_ADDR, 1, false, 3, ALICE, true, ""
+        );
+    }
+
     function testSplitSwapUndefinedMinAmount() public {
         // Min amount should always be non-zero. If zero, swap attempt should revert.
         uint256 amountIn = 1 ether;
@@ -18,9 +18,10 @@ import {
 import {UniswapV4Executor} from "../src/executors/UniswapV4Executor.sol";
 import {FluidV1Executor} from "../src/executors/FluidV1Executor.sol";
 import {SlipstreamsExecutor} from "../src/executors/SlipstreamsExecutor.sol";
-import {RocketpoolExecutor} from "../src/executors/RocketpoolExecutor.sol";
+import {LstExecutor, LstCallArgs} from "../src/executors/LstExecutor.sol";
 import {ERC4626Executor} from "../src/executors/ERC4626Executor.sol";
 import {EtherfiExecutor} from "../src/executors/EtherfiExecutor.sol";
+import {AerodromeV2Executor} from "../src/executors/AerodromeV2Executor.sol";
 
 // Test utilities and mocks
 import "./Constants.sol";
@@ -58,14 +59,14 @@ contract TychoRouterExposed is TychoRouter {
         uint256 nTokens,
         bytes calldata swaps
     ) external returns (uint256) {
-        return _splitSwap(amountIn, nTokens, swaps);
+        return _splitSwap(amountIn, nTokens, swaps[0:0], swaps);
     }
 
     function exposedSequentialSwap(uint256 amountIn, bytes calldata swaps)
         external
         returns (uint256)
     {
-        return _sequentialSwap(amountIn, swaps);
+        return _sequentialSwap(amountIn, swaps[0:0], swaps);
     }
 }
 
@@ -86,10 +87,11 @@ contract TychoRouterTestSetup is Constants, Permit2TestHelper, TestUtils {
     HashflowExecutor public hashflowExecutor;
     FluidV1Executor public fluidV1Executor;
     SlipstreamsExecutor public slipstreamsExecutor;
-    RocketpoolExecutor public rocketpoolExecutor;
+    LstExecutor public rocketpoolExecutor;
 
     ERC4626Executor public erc4626Executor;
     EtherfiExecutor public etherfiExecutor;
+    AerodromeV2Executor public aerodromeV2Executor;
 
     function getChain() public view virtual returns (string memory) {
         return "mainnet";
@@ -157,8 +159,9 @@ contract TychoRouterTestSetup is Constants, Permit2TestHelper, TestUtils {
             new MaverickV2Executor(MAVERICK_V2_FACTORY, PERMIT2_ADDRESS);
         balancerV3Executor = new BalancerV3Executor(PERMIT2_ADDRESS);
         bebopExecutor = new BebopExecutor(BEBOP_SETTLEMENT, PERMIT2_ADDRESS);
-        hashflowExecutor =
-            new HashflowExecutor(HASHFLOW_ROUTER, PERMIT2_ADDRESS);
+        hashflowExecutor = new HashflowExecutor(
+            HASHFLOW_ROUTER, WETH_ADDR, PERMIT2_ADDRESS
+        );
         fluidV1Executor =
             new FluidV1Executor(FLUIDV1_LIQUIDITY, PERMIT2_ADDRESS);
         slipstreamsExecutor = new SlipstreamsExecutor(
@@ -166,8 +169,17 @@ contract TychoRouterTestSetup is Constants, Permit2TestHelper, TestUtils {
             SLIPSTREAMS_NEW_FACTORY_BASE,
             PERMIT2_ADDRESS
         );
-        rocketpoolExecutor =
-            new RocketpoolExecutor(ROCKET_DEPOSIT_POOL, PERMIT2_ADDRESS);
+        rocketpoolExecutor = new LstExecutor(
+            PERMIT2_ADDRESS,
+            RETH_ADDR,
+            address(0),
+            ROCKET_DEPOSIT_POOL,
+            bytes4(keccak256("deposit()")),
+            LstCallArgs.None,
+            RETH_ADDR,
+            bytes4(keccak256("burn(uint256)")),
+            LstCallArgs.Amount
+        );
         erc4626Executor = new ERC4626Executor(PERMIT2_ADDRESS);
         ekuboV3Executor = new EkuboV3Executor(PERMIT2_ADDRESS);
         etherfiExecutor = new EtherfiExecutor(
@@ -178,8 +190,10 @@ contract TychoRouterTestSetup is Constants, Permit2TestHelper, TestUtils {
             0xCd5fE23C85820F7B72D0926FC9b05b43E359b7ee,
             0xDadEf1fFBFeaAB4f68A9fD181395F68b4e4E7Ae0
         );
+        aerodromeV2Executor =
+            new AerodromeV2Executor(AERODROME_V2_FACTORY_BASE, PERMIT2_ADDRESS);
 
-        address[] memory executors = new address[](17);
+        address[] memory executors = new address[](18);
         executors[0] = address(usv2Executor);
         executors[1] = address(usv3Executor);
         executors[2] = address(pancakev3Executor);
@@ -197,6 +211,7 @@ contract TychoRouterTestSetup is Constants, Permit2TestHelper, TestUtils {
         executors[14] = address(erc4626Executor);
         executors[15] = address(ekuboV3Executor);
         executors[16] = address(etherfiExecutor);
+        executors[17] = address(aerodromeV2Executor);
 
         return executors;
     }
@@ -242,6 +257,36 @@ contract TychoRouterTestSetup is Constants, Permit2TestHelper, TestUtils {
         );
     }
 
+    function encodeExecutorTable(address[] memory executors)
+        internal
+        pure
+        returns (bytes memory encoded)
+    {
+        encoded = abi.encodePacked(uint8(executors.length));
+        for (uint256 i = 0; i < executors.length; i++) {
+            encoded = bytes.concat(encoded, abi.encodePacked(executors[i]));
+        }
+    }
+
+    function encodeIndexedSequentialSwap(
+        uint8 executorIndex,
+        bytes memory protocolData
+    ) internal pure returns (bytes memory) {
+        return abi.encodePacked(executorIndex, protocolData);
+    }
+
+    function encodeIndexedSplitSwap(
+        uint8 tokenInIndex,
+        uint8 tokenOutIndex,
+        uint24 split,
+        uint8 executorIndex,
+        bytes memory protocolData
+    ) internal pure returns (bytes memory) {
+        return abi.encodePacked(
+            tokenInIndex, tokenOutIndex, split, executorIndex, protocolData
+        );
+    }
+
     function encodeUniswapV2Swap(
         address tokenIn,
         address target,
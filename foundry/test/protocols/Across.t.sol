@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "../TestUtils.sol";
+import "@src/executors/AcrossExecutor.sol";
+import {Constants} from "../Constants.sol";
+
+contract AcrossExecutorExposed is AcrossExecutor {
+    constructor(
+        address _spokePool,
+        uint32 _fillDeadlineBuffer,
+        address _permit2
+    ) AcrossExecutor(_spokePool, _fillDeadlineBuffer, _permit2) {}
+
+    function decodeData(bytes calldata data)
+        external
+        pure
+        returns (
+            TransferType transferType,
+            address inputToken,
+            address outputToken,
+            uint256 destinationChainId,
+            address recipient,
+            address depositor,
+            uint16 relayFeeBps
+        )
+    {
+        return _decodeData(data);
+    }
+}
+
+contract AcrossExecutorTest is Constants, TestUtils {
+    AcrossExecutorExposed acrossExecutor;
+
+    IERC20 USDC = IERC20(USDC_ADDR);
+    // USDC on Base
+    address BASE_USDC = 0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913;
+
+    function setUp() public {
+        vm.createSelectFork(vm.rpcUrl("mainnet"), 23124275);
+        acrossExecutor = new AcrossExecutorExposed(
+            ACROSS_SPOKE_POOL, 3600, PERMIT2_ADDRESS
+        );
+    }
+
+    function _encodeData(uint16 relayFeeBps)
+        internal
+        view
+        returns (bytes memory)
+    {
+        return abi.encodePacked(
+            uint8(RestrictTransferFrom.TransferType.None),
+            USDC_ADDR,
+            BASE_USDC,
+            uint256(8453),
+            BOB,
+            ALICE,
+            relayFeeBps
+        );
+    }
+
+    function testDecodeData() public view {
+        (
+            RestrictTransferFrom.TransferType transferType,
+            address inputToken,
+            address outputToken,
+            uint256 destinationChainId,
+            address recipient,
+            address depositor,
+            uint16 relayFeeBps
+        ) = acrossExecutor.decodeData(_encodeData(5));
+
+        assertEq(
+            uint8(transferType), uint8(RestrictTransferFrom.TransferType.None)
+        );
+        assertEq(inputToken, USDC_ADDR);
+        assertEq(outputToken, BASE_USDC);
+        assertEq(destinationChainId, 8453);
+        assertEq(recipient, BOB);
+        assertEq(depositor, ALICE);
+        assertEq(relayFeeBps, 5);
+    }
+
+    function testDecodeDataInvalidLength() public {
+        vm.expectRevert(AcrossExecutor__InvalidDataLength.selector);
+        acrossExecutor.decodeData(abi.encodePacked(_encodeData(5), uint8(0)));
+    }
+
+    function testSwapDeposits() public {
+        uint256 amountIn = 1000_000000;
+        deal(USDC_ADDR, address(acrossExecutor), amountIn);
+        uint256 spokePoolBalanceBefore = USDC.balanceOf(ACROSS_SPOKE_POOL);
+
+        uint256 calculatedAmount =
+            acrossExecutor.swap(amountIn, _encodeData(5));
+
+        assertEq(calculatedAmount, amountIn);
+        assertEq(USDC.balanceOf(address(acrossExecutor)), 0);
+        assertEq(
+            USDC.balanceOf(ACROSS_SPOKE_POOL) - spokePoolBalanceBefore,
+            amountIn
+        );
+    }
+
+    function testSwapInvalidRelayFee() public {
+        uint256 amountIn = 1000_000000;
+        deal(USDC_ADDR, address(acrossExecutor), amountIn);
+
+        vm.expectRevert(AcrossExecutor__InvalidRelayFee.selector);
+        acrossExecutor.swap(amountIn, _encodeData(10000));
+    }
+}
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "../TychoRouterTestSetup.sol";
+import "@src/executors/AerodromeV2Executor.sol";
+import {Constants} from "../Constants.sol";
+import {Test} from "../../lib/forge-std/src/Test.sol";
+
+contract AerodromeV2ExecutorExposed is AerodromeV2Executor {
+    constructor(address _factory, address _permit2)
+        AerodromeV2Executor(_factory, _permit2)
+    {}
+
+    function decodeData(bytes calldata data)
+        external
+        pure
+        returns (
+            IERC20 inToken,
+            address target,
+            address receiver,
+            bool zeroForOne,
+            bool stable,
+            address poolFactory,
+            RestrictTransferFrom.TransferType transferType
+        )
+    {
+        return _decodeData(data);
+    }
+}
+
+contract AerodromeV2ExecutorTest is Test, TestUtils, Constants {
+    using SafeERC20 for IERC20;
+
+    AerodromeV2ExecutorExposed aerodromeV2Exposed;
+
+    function setUp() public {
+        uint256 forkBlock = 38086214;
+        vm.createSelectFork(vm.rpcUrl("base"), forkBlock);
+
+        aerodromeV2Exposed = new AerodromeV2ExecutorExposed(
+            AERODROME_V2_FACTORY_BASE, PERMIT2_ADDRESS
+        );
+    }
+
+    function testDecodeParams() public view {
+        bytes memory data = abi.encodePacked(
+            BASE_WETH,
+            address(2),
+            address(3),
+            false,
+            true,
+            AERODROME_V2_FACTORY_BASE,
+            RestrictTransferFrom.TransferType.Transfer
+        );
+
+        (
+            IERC20 tokenIn,
+            address target,
+            address receiver,
+            bool zeroForOne,
+            bool stable,
+            address poolFactory,
+            RestrictTransferFrom.TransferType transferType
+        ) = aerodromeV2Exposed.decodeData(data);
+
+        assertEq(address(tokenIn), BASE_WETH);
+        assertEq(target, address(2));
+        assertEq(receiver, address(3));
+        assertEq(zeroForOne, false);
+        assertEq(stable, true);
+        assertEq(poolFactory, AERODROME_V2_FACTORY_BASE);
+        assertEq(
+            uint8(transferType),
+            uint8(RestrictTransferFrom.TransferType.Transfer)
+        );
+    }
+
+    function testDecodeParamsInvalidDataLength() public {
+        bytes memory invalidParams =
+            abi.encodePacked(BASE_WETH, address(2), address(3));
+
+        vm.expectRevert(AerodromeV2Executor__InvalidDataLength.selector);
+        aerodromeV2Exposed.decodeData(invalidParams);
+    }
+
+    function testSwap() public {
+        uint256 amountIn = 10 ** 18;
+        deal(BASE_WETH, address(aerodromeV2Exposed), amountIn);
+
+        // WETH is token0 of the pool
+        bytes memory data = abi.encodePacked(
+            BASE_WETH,
+            AERODROME_V2_WETH_USDC_POOL,
+            BOB,
+            true,
+            false,
+            AERODROME_V2_FACTORY_BASE,
+            RestrictTransferFrom.TransferType.Transfer
+        );
+
+        uint256 amountOut = aerodromeV2Exposed.swap(amountIn, data);
+
+        assertGt(amountOut, 0);
+        assertEq(IERC20(BASE_USDC).balanceOf(BOB), amountOut);
+        assertEq(IERC20(BASE_WETH).balanceOf(address(aerodromeV2Exposed)), 0);
+    }
+
+    function testSwapFailureWrongCurve() public {
+        uint256 amountIn = 10 ** 18;
+        deal(BASE_WETH, address(aerodromeV2Exposed), amountIn);
+
+        // The pool is volatile, so it isn't the stable pool of the factory
+        bytes memory data = abi.encodePacked(
+            BASE_WETH,
+            AERODROME_V2_WETH_USDC_POOL,
+            BOB,
+            true,
+            true,
+            AERODROME_V2_FACTORY_BASE,
+            RestrictTransferFrom.TransferType.Transfer
+        );
+
+        vm.expectRevert(AerodromeV2Executor__InvalidTarget.selector);
+        aerodromeV2Exposed.swap(amountIn, data);
+    }
+
+    function testSwapFailureInvalidFactory() public {
+        uint256 amountIn = 10 ** 18;
+        deal(BASE_WETH, address(aerodromeV2Exposed), amountIn);
+
+        bytes memory data = abi.encodePacked(
+            BASE_WETH,
+            AERODROME_V2_WETH_USDC_POOL,
+            BOB,
+            true,
+            false,
+            DUMMY,
+            RestrictTransferFrom.TransferType.Transfer
+        );
+
+        vm.expectRevert(AerodromeV2Executor__InvalidFactory.selector);
+        aerodromeV2Exposed.swap(amountIn, data);
+    }
+}
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "../TestUtils.sol";
+import "@src/executors/AmbientExecutor.sol";
+import {Constants} from "../Constants.sol";
+
+contract AmbientExecutorExposed is AmbientExecutor {
+    constructor(address _permit2) AmbientExecutor(_permit2) {}
+
+    function decodeParams(bytes calldata data)
+        external
+        pure
+        returns (
+            address base,
+            address quote,
+            uint256 poolIdx,
+            bool isBuy,
+            address dex,
+            address receiver,
+            TransferType transferType,
+            bool approvalNeeded
+        )
+    {
+        return _decodeData(data);
+    }
+}
+
+contract AmbientExecutorTest is Constants, TestUtils {
+    AmbientExecutorExposed ambientExposed;
+
+    function setUp() public {
+        uint256 forkBlock = 23934489;
+        vm.createSelectFork(vm.rpcUrl("mainnet"), forkBlock);
+        ambientExposed = new AmbientExecutorExposed(PERMIT2_ADDRESS);
+    }
+
+    function testDecodeParams() public view {
+        bytes memory params = abi.encodePacked(
+            address(0),
+            USDC_ADDR,
+            AMBIENT_POOL_IDX,
+            true,
+            AMBIENT_DEX_ADDR,
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            false
+        );
+
+        (
+            address base,
+            address quote,
+            uint256 poolIdx,
+            bool isBuy,
+            address dex,
+            address receiver,
+            RestrictTransferFrom.TransferType transferType,
+            bool approvalNeeded
+        ) = ambientExposed.decodeParams(params);
+
+        assertEq(base, address(0));
+        assertEq(quote, USDC_ADDR);
+        assertEq(poolIdx, AMBIENT_POOL_IDX);
+        assertEq(isBuy, true);
+        assertEq(dex, AMBIENT_DEX_ADDR);
+        assertEq(receiver, BOB);
+        assertEq(
+            uint8(transferType), uint8(RestrictTransferFrom.TransferType.None)
+        );
+        assertEq(approvalNeeded, false);
+    }
+
+    function testDecodeParamsInvalidDataLength() public {
+        bytes memory invalidParams =
+            abi.encodePacked(address(0), USDC_ADDR, AMBIENT_POOL_IDX, true);
+
+        vm.expectRevert(AmbientExecutor__InvalidDataLength.selector);
+        ambientExposed.decodeParams(invalidParams);
+    }
+
+    function testSwapEthForUsdc() public {
+        uint256 amountIn = 1 ether;
+        deal(address(ambientExposed), amountIn);
+        bytes memory protocolData = abi.encodePacked(
+            address(0),
+            USDC_ADDR,
+            AMBIENT_POOL_IDX,
+            true,
+            AMBIENT_DEX_ADDR,
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            false
+        );
+
+        uint256 amountOut = ambientExposed.swap(amountIn, protocolData);
+
+        assertGt(amountOut, 0);
+        assertEq(IERC20(USDC_ADDR).balanceOf(BOB), amountOut);
+        assertEq(address(ambientExposed).balance, 0);
+    }
+
+    function testSwapUsdcForEth() public {
+        uint256 amountIn = 1000_000000;
+        deal(USDC_ADDR, address(ambientExposed), amountIn);
+        bytes memory protocolData = abi.encodePacked(
+            address(0),
+            USDC_ADDR,
+            AMBIENT_POOL_IDX,
+            false,
+            AMBIENT_DEX_ADDR,
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            true
+        );
+
+        uint256 balanceBefore = BOB.balance;
+        uint256 amountOut = ambientExposed.swap(amountIn, protocolData);
+
+        assertGt(amountOut, 0);
+        assertEq(BOB.balance - balanceBefore, amountOut);
+        assertEq(IERC20(USDC_ADDR).balanceOf(address(ambientExposed)), 0);
+    }
+}
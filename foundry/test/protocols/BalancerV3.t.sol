@@ -18,7 +18,10 @@ contract BalancerV3ExecutorExposed is BalancerV3Executor {
             IERC20 tokenOut,
             address poolId,
             TransferType transferType,
-            address receiver
+            address receiver,
+            bytes memory hookData,
+            address bufferTokenIn,
+            address bufferTokenOut
         )
     {
         return _decodeData(data);
@@ -57,7 +60,10 @@ contract BalancerV3ExecutorTest is Constants, TestUtils {
             IERC20 tokenOut,
             address poolId,
             RestrictTransferFrom.TransferType transferType,
-            address receiver
+            address receiver,
+            bytes memory hookData,
+            address bufferTokenIn,
+            address bufferTokenOut
         ) = balancerV3Exposed.decodeParams(params);
 
         assertEq(amountGiven, 1 ether);
@@ -68,6 +74,79 @@ contract BalancerV3ExecutorTest is Constants, TestUtils {
             uint8(transferType), uint8(RestrictTransferFrom.TransferType.None)
         );
         assertEq(receiver, BOB);
+        assertEq(hookData.length, 0);
+        assertEq(bufferTokenIn, address(0));
+        assertEq(bufferTokenOut, address(0));
+    }
+
+    function testDecodeParamsWithHookData() public view {
+        bytes memory params = abi.encodePacked(
+            uint256(1 ether),
+            osETH_ADDR,
+            waEthWETH_ADDR,
+            WETH_osETH_pool,
+            RestrictTransferFrom.TransferType.None,
+            BOB,
+            uint16(2),
+            hex"abcd"
+        );
+
+        (,,,,, address receiver, bytes memory hookData,,) =
+            balancerV3Exposed.decodeParams(params);
+
+        assertEq(receiver, BOB);
+        assertEq(hookData, hex"abcd");
+    }
+
+    function testDecodeParamsWithBufferTokens() public view {
+        bytes memory params = abi.encodePacked(
+            uint256(1 ether),
+            WETH_ADDR,
+            osETH_ADDR,
+            WETH_osETH_pool,
+            RestrictTransferFrom.TransferType.None,
+            BOB,
+            uint16(0),
+            waEthWETH_ADDR,
+            address(0)
+        );
+
+        (
+            ,,,,,,
+            bytes memory hookData,
+            address bufferTokenIn,
+            address bufferTokenOut
+        ) = balancerV3Exposed.decodeParams(params);
+
+        assertEq(hookData.length, 0);
+        assertEq(bufferTokenIn, waEthWETH_ADDR);
+        assertEq(bufferTokenOut, address(0));
+    }
+
+    function testSwapThroughBuffer() public {
+        uint256 amountIn = 10 ** 18;
+        // WETH is wrapped into waEthWETH by its buffer before the pool swap
+        bytes memory protocolData = abi.encodePacked(
+            WETH_ADDR,
+            osETH_ADDR,
+            WETH_osETH_pool,
+            RestrictTransferFrom.TransferType.Transfer,
+            BOB,
+            uint16(0),
+            waEthWETH_ADDR,
+            address(0)
+        );
+
+        deal(WETH_ADDR, address(balancerV3Exposed), amountIn);
+
+        uint256 balanceBefore = IERC20(osETH_ADDR).balanceOf(BOB);
+
+        uint256 amountOut = balancerV3Exposed.swap(amountIn, protocolData);
+
+        uint256 balanceAfter = IERC20(osETH_ADDR).balanceOf(BOB);
+        assertGt(balanceAfter, balanceBefore);
+        assertEq(balanceAfter - balanceBefore, amountOut);
+        assertEq(IERC20(WETH_ADDR).balanceOf(address(balancerV3Exposed)), 0);
     }
 
     function testSwapInvalidDataLength() public {
@@ -39,7 +39,8 @@ contract CurveExecutorExposed is CurveExecutor {
             int128 j,
             bool tokenApprovalNeeded,
             RestrictTransferFrom.TransferType transferType,
-            address receiver
+            address receiver,
+            bool useEth
         )
     {
         return _decodeData(data);
@@ -71,7 +72,8 @@ contract CurveExecutorTest is Test, TestUtils, Constants {
             uint8(0),
             true,
             RestrictTransferFrom.TransferType.None,
-            ALICE
+            ALICE,
+            false
         );
 
         (
@@ -83,7 +85,8 @@ contract CurveExecutorTest is Test, TestUtils, Constants {
             int128 j,
             bool tokenApprovalNeeded,
             RestrictTransferFrom.TransferType transferType,
-            address receiver
+            address receiver,
+            bool useEth
         ) = curveExecutorExposed.decodeData(data);
 
         assertEq(tokenIn, WETH_ADDR);
@@ -97,6 +100,7 @@ contract CurveExecutorTest is Test, TestUtils, Constants {
             uint8(transferType), uint8(RestrictTransferFrom.TransferType.None)
         );
         assertEq(receiver, ALICE);
+        assertEq(useEth, false);
     }
 
     function testTriPool() public {
@@ -255,6 +259,50 @@ contract CurveExecutorTest is Test, TestUtils, Constants {
         assertEq(IERC20(USDE_ADDR).balanceOf(ALICE), amountOut);
     }
 
+    function testStableNGPoolExchangeReceived() public {
+        // Swapping USDC -> USDE on the same pool, sending the USDC to it first
+        uint256 amountIn = 100 * 10 ** 6;
+        deal(USDC_ADDR, address(curveExecutorExposed), amountIn);
+
+        bytes memory data = _getData(
+            USDC_ADDR,
+            USDE_ADDR,
+            USDE_USDC_POOL,
+            10,
+            ALICE,
+            RestrictTransferFrom.TransferType.None
+        );
+
+        uint256 amountOut = curveExecutorExposed.swap(amountIn, data);
+
+        assertEq(amountOut, 100064812138999986170);
+        assertEq(IERC20(USDE_ADDR).balanceOf(ALICE), amountOut);
+    }
+
+    function testStableNGPoolWithoutExchangeReceived() public {
+        // Rebasing coins can reach the executor 1 wei short of the amount in,
+        // in which case only the balance held is swapped
+        uint256 amountIn = 100 * 10 ** 6;
+        deal(USDC_ADDR, address(curveExecutorExposed), amountIn - 1);
+
+        bytes memory data = _getData(
+            USDC_ADDR,
+            USDE_ADDR,
+            USDE_USDC_POOL,
+            11,
+            ALICE,
+            RestrictTransferFrom.TransferType.None
+        );
+
+        uint256 amountOut = curveExecutorExposed.swap(amountIn, data);
+
+        assertGt(amountOut, 0);
+        assertEq(IERC20(USDE_ADDR).balanceOf(ALICE), amountOut);
+        assertEq(
+            IERC20(USDC_ADDR).balanceOf(address(curveExecutorExposed)), 0
+        );
+    }
+
     function testDolaFraxPyusdPool() public {
         // Swapping DOLA -> FRAXPYUSD on a CryptoSwapNG, deployed by factory 0x6A8cbed756804B16E05E741eDaBd5cB544AE21bf (meta pool)
         uint256 amountIn = 100 * 10 ** 6;
@@ -405,6 +453,9 @@ contract CurveExecutorTest is Test, TestUtils, Constants {
         RestrictTransferFrom.TransferType transferType
     ) internal view returns (bytes memory data) {
         (int128 i, int128 j) = _getIndexes(tokenIn, tokenOut, pool);
+        // crypto pools hold WETH and wrap the ETH swapped themselves
+        bool useEth = (poolType == 2 || poolType == 3)
+            && (tokenIn == ETH_ADDR_FOR_CURVE || tokenOut == ETH_ADDR_FOR_CURVE);
         data = abi.encodePacked(
             tokenIn,
             tokenOut,
@@ -414,7 +465,8 @@ contract CurveExecutorTest is Test, TestUtils, Constants {
             uint8(uint256(uint128(j))),
             true,
             transferType,
-            receiver
+            receiver,
+            useEth
         );
     }
 
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "../TestUtils.sol";
+import "@src/executors/FraxEtherExecutor.sol";
+import {Constants} from "../Constants.sol";
+
+contract FraxEtherExecutorExposed is FraxEtherExecutor {
+    constructor(
+        address _frxEthMinterAddress,
+        address _frxEthAddress,
+        address _sfrxEthAddress,
+        address _permit2
+    )
+        FraxEtherExecutor(
+            _frxEthMinterAddress, _frxEthAddress, _sfrxEthAddress, _permit2
+        )
+    {}
+
+    function decodeParams(bytes calldata data)
+        external
+        pure
+        returns (
+            address receiver,
+            TransferType transferType,
+            FraxEtherDirection direction,
+            bool approvalNeeded
+        )
+    {
+        return _decodeData(data);
+    }
+}
+
+contract FraxEtherExecutorTest is Constants, TestUtils {
+    FraxEtherExecutorExposed fraxEtherExposed;
+
+    function setUp() public {
+        uint256 forkBlock = 23934489;
+        vm.createSelectFork(vm.rpcUrl("mainnet"), forkBlock);
+        fraxEtherExposed = new FraxEtherExecutorExposed(
+            FRXETH_MINTER_ADDR, FRXETH_ADDR, SFRXETH_ADDR, PERMIT2_ADDRESS
+        );
+    }
+
+    function testDecodeParams() public view {
+        bytes memory params = abi.encodePacked(
+            BOB,
+            RestrictTransferFrom.TransferType.Transfer,
+            FraxEtherDirection.FrxEthToSfrxEth,
+            true
+        );
+
+        (
+            address receiver,
+            RestrictTransferFrom.TransferType transferType,
+            FraxEtherDirection direction,
+            bool approvalNeeded
+        ) = fraxEtherExposed.decodeParams(params);
+
+        assertEq(receiver, BOB);
+        assertEq(
+            uint8(transferType),
+            uint8(RestrictTransferFrom.TransferType.Transfer)
+        );
+        assertEq(uint8(direction), uint8(FraxEtherDirection.FrxEthToSfrxEth));
+        assertEq(approvalNeeded, true);
+    }
+
+    function testDecodeParamsInvalidDataLength() public {
+        bytes memory invalidParams =
+            abi.encodePacked(BOB, RestrictTransferFrom.TransferType.None);
+
+        vm.expectRevert(FraxEtherExecutor__InvalidDataLength.selector);
+        fraxEtherExposed.decodeParams(invalidParams);
+    }
+
+    function testSwapEthToFrxEth() public {
+        uint256 amountIn = 1 ether;
+        bytes memory protocolData = abi.encodePacked(
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            FraxEtherDirection.EthToFrxEth,
+            false
+        );
+
+        vm.deal(address(this), amountIn);
+        uint256 balanceBefore = IERC20(FRXETH_ADDR).balanceOf(BOB);
+
+        uint256 amountOut =
+            fraxEtherExposed.swap{value: amountIn}(amountIn, protocolData);
+
+        uint256 balanceAfter = IERC20(FRXETH_ADDR).balanceOf(BOB);
+        assertEq(amountOut, amountIn);
+        assertEq(balanceAfter - balanceBefore, amountOut);
+    }
+
+    function testSwapFrxEthToSfrxEth() public {
+        uint256 amountIn = 1 ether;
+        deal(FRXETH_ADDR, address(fraxEtherExposed), amountIn);
+        bytes memory protocolData = abi.encodePacked(
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            FraxEtherDirection.FrxEthToSfrxEth,
+            true
+        );
+
+        uint256 balanceBefore = IERC20(SFRXETH_ADDR).balanceOf(BOB);
+        uint256 amountOut = fraxEtherExposed.swap(amountIn, protocolData);
+        uint256 balanceAfter = IERC20(SFRXETH_ADDR).balanceOf(BOB);
+
+        assertGt(amountOut, 0);
+        assertEq(balanceAfter - balanceBefore, amountOut);
+        assertEq(IERC20(FRXETH_ADDR).balanceOf(address(fraxEtherExposed)), 0);
+    }
+
+    function testSwapSfrxEthToFrxEth() public {
+        uint256 amountIn = 1 ether;
+        deal(SFRXETH_ADDR, address(fraxEtherExposed), amountIn);
+        bytes memory protocolData = abi.encodePacked(
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            FraxEtherDirection.SfrxEthToFrxEth,
+            false
+        );
+
+        uint256 balanceBefore = IERC20(FRXETH_ADDR).balanceOf(BOB);
+        uint256 amountOut = fraxEtherExposed.swap(amountIn, protocolData);
+        uint256 balanceAfter = IERC20(FRXETH_ADDR).balanceOf(BOB);
+
+        // sfrxETH accrues staking rewards, so a share is worth more than 1 frxETH
+        assertGt(amountOut, amountIn);
+        assertEq(balanceAfter - balanceBefore, amountOut);
+    }
+}
@@ -31,6 +31,18 @@ contract HashflowUtils is Test {
         );
     }
 
+    function encodeNativeRfqtQuote(
+        IHashflowRouter.RFQTQuote memory quote,
+        uint8 nativeFlags,
+        address receiver
+    ) internal pure returns (bytes memory) {
+        return abi.encodePacked(
+            encodeRfqtQuoteWithDefaults(quote),
+            nativeFlags, // nativeFlags (1 byte)
+            receiver // receiver (20 bytes)
+        );
+    }
+
     function encodeRfqtQuoteWithDefaults(IHashflowRouter.RFQTQuote memory quote)
         internal
         pure
@@ -53,7 +65,9 @@ contract HashflowExecutorECR20Test is Constants, TestUtils, HashflowUtils {
     function setUp() public {
         forkBlock = 23188416; // Using expiry date: 1755766775, ECR20
         vm.createSelectFork("mainnet", forkBlock);
-        executor = new HashflowExecutorExposed(HASHFLOW_ROUTER, PERMIT2_ADDRESS);
+        executor = new HashflowExecutorExposed(
+            HASHFLOW_ROUTER, WETH_ADDR, PERMIT2_ADDRESS
+        );
     }
 
     function testDecodeParams() public view {
@@ -122,6 +136,40 @@ contract HashflowExecutorECR20Test is Constants, TestUtils, HashflowUtils {
         executor.decodeData(invalidData);
     }
 
+    function testDecodeNativeParams() public view {
+        bytes memory encodedQuote = encodeRfqtQuoteWithDefaults(rfqtQuote());
+        (uint8 nativeFlags, address receiver) =
+            executor.decodeNativeData(encodedQuote);
+        assertEq(nativeFlags, 0, "Native flags mismatch");
+        assertEq(receiver, address(0), "Receiver mismatch");
+
+        encodedQuote = encodeNativeRfqtQuote(rfqtQuote(), 2, BOB);
+        (nativeFlags, receiver) = executor.decodeNativeData(encodedQuote);
+        assertEq(nativeFlags, 2, "Native flags mismatch");
+        assertEq(receiver, BOB, "Receiver mismatch");
+    }
+
+    function testSwapNativeIn() public {
+        // The quote sells WETH, which the executor wraps from native ETH
+        address trader = address(ALICE);
+        IHashflowRouter.RFQTQuote memory quote = rfqtQuote();
+        uint256 amountIn = quote.baseTokenAmount;
+        bytes memory encodedQuote =
+            encodeNativeRfqtQuote(quote, 1, address(executor));
+
+        vm.deal(address(executor), amountIn);
+        uint256 balanceBefore = USDC.balanceOf(trader);
+
+        vm.prank(trader);
+        uint256 amountOut = executor.swap(amountIn, encodedQuote);
+
+        uint256 balanceAfter = USDC.balanceOf(trader);
+        assertEq(balanceAfter - balanceBefore, amountOut);
+        assertEq(amountOut, quote.quoteTokenAmount);
+        assertEq(address(executor).balance, 0);
+        assertEq(WETH.balanceOf(address(executor)), 0);
+    }
+
     function testSwapNoSlippage() public {
         address trader = address(ALICE);
         IHashflowRouter.RFQTQuote memory quote = rfqtQuote();
@@ -217,7 +265,9 @@ contract HashflowExecutorNativeTest is Constants, HashflowUtils {
     function setUp() public {
         forkBlock = 23188504; // Using expiry date: 1755767859, Native
         vm.createSelectFork("mainnet", forkBlock);
-        executor = new HashflowExecutorExposed(HASHFLOW_ROUTER, PERMIT2_ADDRESS);
+        executor = new HashflowExecutorExposed(
+            HASHFLOW_ROUTER, WETH_ADDR, PERMIT2_ADDRESS
+        );
     }
 
     function testSwapNoSlippage() public {
@@ -268,8 +318,8 @@ contract HashflowExecutorNativeTest is Constants, HashflowUtils {
 }
 
 contract HashflowExecutorExposed is HashflowExecutor {
-    constructor(address _hashflowRouter, address _permit2)
-        HashflowExecutor(_hashflowRouter, _permit2)
+    constructor(address _hashflowRouter, address _weth, address _permit2)
+        HashflowExecutor(_hashflowRouter, _weth, _permit2)
     {}
 
     function decodeData(bytes calldata data)
@@ -283,6 +333,14 @@ contract HashflowExecutorExposed is HashflowExecutor {
     {
         return _decodeData(data);
     }
+
+    function decodeNativeData(bytes calldata data)
+        external
+        pure
+        returns (uint8 nativeFlags, address receiver)
+    {
+        return _decodeNativeData(data);
+    }
 }
 
 contract TychoRouterSingleSwapTestForHashflow is TychoRouterTestSetup {
@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "../TestUtils.sol";
+import "../TychoRouterTestSetup.sol";
+import "@src/executors/LidoWithdrawalExecutor.sol";
+
+interface ILido {
+    function submit(address referral) external payable returns (uint256);
+}
+
+interface IUnstEth {
+    function balanceOf(address owner) external view returns (uint256);
+}
+
+contract LidoWithdrawalExecutorExposed is LidoWithdrawalExecutor {
+    constructor(address _withdrawalQueue, address _stEth, address _permit2)
+        LidoWithdrawalExecutor(_withdrawalQueue, _stEth, _permit2)
+    {}
+
+    function decodeData(bytes calldata data)
+        external
+        pure
+        returns (TransferType transferType, address receiver)
+    {
+        return _decodeData(data);
+    }
+
+    function splitRequests(uint256 amount)
+        external
+        pure
+        returns (uint256[] memory)
+    {
+        return _splitRequests(amount);
+    }
+}
+
+contract LidoWithdrawalExecutorTest is Constants, TestUtils {
+    LidoWithdrawalExecutorExposed lidoWithdrawalExecutor;
+
+    function setUp() public {
+        vm.createSelectFork(vm.rpcUrl("mainnet"), 23934489);
+        lidoWithdrawalExecutor = new LidoWithdrawalExecutorExposed(
+            LIDO_WITHDRAWAL_QUEUE_ADDR, STETH_ADDR, PERMIT2_ADDRESS
+        );
+    }
+
+    function _mintStEth(address to, uint256 amount) internal {
+        vm.deal(address(this), amount);
+        ILido(STETH_ADDR).submit{value: amount}(address(0));
+        IERC20(STETH_ADDR).transfer(to, amount);
+    }
+
+    function testDecodeData() public view {
+        (RestrictTransferFrom.TransferType transferType, address receiver) =
+        lidoWithdrawalExecutor.decodeData(
+            abi.encodePacked(RestrictTransferFrom.TransferType.Transfer, BOB)
+        );
+
+        assertEq(
+            uint8(transferType),
+            uint8(RestrictTransferFrom.TransferType.Transfer)
+        );
+        assertEq(receiver, BOB);
+    }
+
+    function testDecodeDataInvalidLength() public {
+        vm.expectRevert(LidoWithdrawalExecutor__InvalidDataLength.selector);
+        lidoWithdrawalExecutor.decodeData(
+            abi.encodePacked(RestrictTransferFrom.TransferType.None)
+        );
+    }
+
+    function testSplitRequests() public view {
+        uint256[] memory amounts =
+            lidoWithdrawalExecutor.splitRequests(2500 ether);
+
+        assertEq(amounts.length, 3);
+        assertEq(amounts[0] + amounts[1] + amounts[2], 2500 ether);
+        for (uint256 i = 0; i < amounts.length; i++) {
+            assertLe(amounts[i], 1000 ether);
+        }
+    }
+
+    function testSwapRequestsWithdrawal() public {
+        uint256 amountIn = 10 ether;
+        _mintStEth(address(lidoWithdrawalExecutor), amountIn);
+        uint256 queueBalanceBefore =
+            IERC20(STETH_ADDR).balanceOf(LIDO_WITHDRAWAL_QUEUE_ADDR);
+
+        uint256 calculatedAmount = lidoWithdrawalExecutor.swap(
+            amountIn,
+            abi.encodePacked(RestrictTransferFrom.TransferType.None, BOB)
+        );
+
+        // stETH transfers can round down by a few wei
+        assertApproxEqAbs(calculatedAmount, amountIn, 2);
+        // The calculated amount is what the queue received
+        assertEq(
+            IERC20(STETH_ADDR).balanceOf(LIDO_WITHDRAWAL_QUEUE_ADDR)
+                - queueBalanceBefore,
+            calculatedAmount
+        );
+        assertEq(IUnstEth(LIDO_WITHDRAWAL_QUEUE_ADDR).balanceOf(BOB), 1);
+    }
+
+    function testSwapFromEncodedCalldata() public {
+        // The receiver of the encoded calldata is BOB
+        bytes memory protocolData =
+            loadCallDataFromFile("test_encode_lido_withdrawal");
+        _mintStEth(address(lidoWithdrawalExecutor), 1 ether);
+        uint256 amountIn =
+            IERC20(STETH_ADDR).balanceOf(address(lidoWithdrawalExecutor));
+
+        lidoWithdrawalExecutor.swap(amountIn, protocolData);
+
+        assertEq(IUnstEth(LIDO_WITHDRAWAL_QUEUE_ADDR).balanceOf(BOB), 1);
+    }
+}
+
+contract TychoRouterForLidoWithdrawalTest is TychoRouterTestSetup {
+    LidoWithdrawalExecutor lidoWithdrawalExecutor;
+
+    function getForkBlock() public pure override returns (uint256) {
+        return 23934489;
+    }
+
+    function setUp() public override {
+        super.setUp();
+        lidoWithdrawalExecutor = new LidoWithdrawalExecutor(
+            LIDO_WITHDRAWAL_QUEUE_ADDR, STETH_ADDR, PERMIT2_ADDRESS
+        );
+        address[] memory executors = new address[](1);
+        executors[0] = address(lidoWithdrawalExecutor);
+        vm.prank(EXECUTOR_SETTER);
+        tychoRouter.setExecutors(executors);
+    }
+
+    function testSequentialSwapIntoWithdrawalRequest() public {
+        // Swaps ETH for stETH on Curve, then requests the withdrawal of the
+        // stETH for BOB. The router checks the stETH received by the queue,
+        // which the stETH transfers round down by a few wei.
+        uint256 amountIn = 1 ether;
+        deal(ALICE, amountIn);
+        uint256 queueBalanceBefore =
+            IERC20(STETH_ADDR).balanceOf(LIDO_WITHDRAWAL_QUEUE_ADDR);
+
+        bytes[] memory swaps = new bytes[](2);
+        // ETH -> stETH, on the StEthPool holding [ETH, stETH]
+        swaps[0] = encodeSequentialSwap(
+            address(curveExecutor),
+            abi.encodePacked(
+                ETH_ADDR_FOR_CURVE,
+                STETH_ADDR,
+                STETH_POOL,
+                uint8(1),
+                uint8(0),
+                uint8(1),
+                true,
+                RestrictTransferFrom.TransferType.None,
+                tychoRouterAddr,
+                false
+            )
+        );
+        // stETH -> withdrawal request
+        swaps[1] = encodeSequentialSwap(
+            address(lidoWithdrawalExecutor),
+            abi.encodePacked(RestrictTransferFrom.TransferType.None, BOB)
+        );
+
+        vm.prank(ALICE);
+        uint256 amountOut = tychoRouter.sequentialSwap{value: amountIn}(
+            amountIn,
+            address(0),
+            STETH_ADDR,
+            0.99 ether,
+            false,
+            false,
+            LIDO_WITHDRAWAL_QUEUE_ADDR,
+            false,
+            pleEncode(swaps)
+        );
+
+        assertEq(
+            IERC20(STETH_ADDR).balanceOf(LIDO_WITHDRAWAL_QUEUE_ADDR)
+                - queueBalanceBefore,
+            amountOut
+        );
+        assertEq(IUnstEth(LIDO_WITHDRAWAL_QUEUE_ADDR).balanceOf(BOB), 1);
+        assertLe(IERC20(STETH_ADDR).balanceOf(tychoRouterAddr), 2);
+    }
+}
@@ -2,30 +2,46 @@
 pragma solidity ^0.8.26;
 
 import "../TychoRouterTestSetup.sol";
-import {CommonBase} from "../../lib/forge-std/src/Base.sol";
 import {Constants} from "../Constants.sol";
 import {RestrictTransferFrom} from "../../src/RestrictTransferFrom.sol";
 import {
-    RocketpoolExecutor,
-    RocketpoolExecutor__InvalidDataLength,
-    IRocketTokenRETH
-} from "../../src/executors/RocketpoolExecutor.sol";
-import {StdAssertions} from "../../lib/forge-std/src/StdAssertions.sol";
-import {StdChains} from "../../lib/forge-std/src/StdChains.sol";
-import {StdCheats, StdCheatsSafe} from "../../lib/forge-std/src/StdCheats.sol";
-import {StdUtils} from "../../lib/forge-std/src/StdUtils.sol";
+    LstExecutor,
+    LstCallArgs,
+    LstExecutor__InvalidDataLength,
+    LstExecutor__RedeemNotSupported
+} from "../../src/executors/LstExecutor.sol";
 import {TestUtils} from "../TestUtils.sol";
 
-contract RocketpoolExecutorExposed is RocketpoolExecutor {
-    constructor(address _rocketDepositPool, address _permit2)
-        RocketpoolExecutor(_rocketDepositPool, _permit2)
+contract LstExecutorExposed is LstExecutor {
+    constructor(
+        address _permit2,
+        address _lstToken,
+        address _depositToken,
+        address _mintTarget,
+        bytes4 _mintSelector,
+        LstCallArgs _mintArgs,
+        address _redeemTarget,
+        bytes4 _redeemSelector,
+        LstCallArgs _redeemArgs
+    )
+        LstExecutor(
+            _permit2,
+            _lstToken,
+            _depositToken,
+            _mintTarget,
+            _mintSelector,
+            _mintArgs,
+            _redeemTarget,
+            _redeemSelector,
+            _redeemArgs
+        )
     {}
 
     function decodeParams(bytes calldata data)
         external
         pure
         returns (
-            bool isDeposit,
+            bool isMint,
             RestrictTransferFrom.TransferType transferType,
             address receiver
         )
@@ -34,13 +50,24 @@ contract RocketpoolExecutorExposed is RocketpoolExecutor {
     }
 }
 
+/// @notice Tests the LST executor configured for Rocketpool: deposit() on the
+/// deposit pool and burn(uint256) on rETH
 contract RocketpoolExecutorTest is TestUtils, Constants {
-    RocketpoolExecutorExposed rocketpoolExecutor;
+    LstExecutorExposed rocketpoolExecutor;
 
     modifier setUpFork(uint256 blockNumber) {
         vm.createSelectFork(vm.rpcUrl("mainnet"), blockNumber);
-        rocketpoolExecutor =
-            new RocketpoolExecutorExposed(ROCKET_DEPOSIT_POOL, PERMIT2_ADDRESS);
+        rocketpoolExecutor = new LstExecutorExposed(
+            PERMIT2_ADDRESS,
+            RETH_ADDR,
+            address(0),
+            ROCKET_DEPOSIT_POOL,
+            bytes4(keccak256("deposit()")),
+            LstCallArgs.None,
+            RETH_ADDR,
+            bytes4(keccak256("burn(uint256)")),
+            LstCallArgs.Amount
+        );
         _;
     }
 
@@ -48,18 +75,18 @@ contract RocketpoolExecutorTest is TestUtils, Constants {
 
     function testDecodeParams() public view {
         bytes memory params = abi.encodePacked(
-            uint8(1), // isDeposit = true
+            uint8(1), // isMint = true
             RestrictTransferFrom.TransferType.Transfer,
             BOB
         );
 
         (
-            bool isDeposit,
+            bool isMint,
             RestrictTransferFrom.TransferType transferType,
             address receiver
         ) = rocketpoolExecutor.decodeParams(params);
 
-        assertTrue(isDeposit);
+        assertTrue(isMint);
         assertEq(
             uint8(transferType),
             uint8(RestrictTransferFrom.TransferType.Transfer)
@@ -69,18 +96,18 @@ contract RocketpoolExecutorTest is TestUtils, Constants {
 
     function testDecodeParamsBurn() public view {
         bytes memory params = abi.encodePacked(
-            uint8(0), // isDeposit = false (burn)
+            uint8(0), // isMint = false (burn)
             RestrictTransferFrom.TransferType.Transfer,
             ALICE
         );
 
         (
-            bool isDeposit,
+            bool isMint,
             RestrictTransferFrom.TransferType transferType,
             address receiver
         ) = rocketpoolExecutor.decodeParams(params);
 
-        assertFalse(isDeposit);
+        assertFalse(isMint);
         assertEq(
             uint8(transferType),
             uint8(RestrictTransferFrom.TransferType.Transfer)
@@ -91,18 +118,47 @@ contract RocketpoolExecutorTest is TestUtils, Constants {
     function testDecodeParamsInvalidDataLength() public {
         bytes memory invalidParams = abi.encodePacked(uint8(1), BOB);
 
-        vm.expectRevert(RocketpoolExecutor__InvalidDataLength.selector);
+        vm.expectRevert(LstExecutor__InvalidDataLength.selector);
         rocketpoolExecutor.decodeParams(invalidParams);
     }
 
+    function testDecodeParamsWithUserData() public view {
+        bytes memory params = abi.encodePacked(
+            uint8(1), // isMint = true
+            RestrictTransferFrom.TransferType.Transfer,
+            BOB,
+            uint16(2), // user data length
+            hex"abcd" // user data
+        );
+
+        (bool isMint,, address receiver) =
+            rocketpoolExecutor.decodeParams(params);
+
+        assertTrue(isMint);
+        assertEq(receiver, BOB);
+    }
+
+    function testDecodeParamsInvalidUserDataLength() public {
+        bytes memory params = abi.encodePacked(
+            uint8(1), // isMint = true
+            RestrictTransferFrom.TransferType.Transfer,
+            BOB,
+            uint16(3), // user data length
+            hex"abcd" // user data
+        );
+
+        vm.expectRevert(LstExecutor__InvalidDataLength.selector);
+        rocketpoolExecutor.decodeParams(params);
+    }
+
     /// Test deposit
     /// Tx 0xe0f1db165b621cb1e50b629af9d47e064be464fbcc7f2bcba3df1d27dbb916be at block 24480105
     /// User deposited 85 ETH and received 73382345660413064855 rETH (0.05% fee)
     function testSwapDeposit() public setUpFork(24480104) {
-        IRocketTokenRETH RETH = IRocketTokenRETH(RETH_ADDR);
+        IERC20 RETH = IERC20(RETH_ADDR);
         uint256 amountIn = 85 ether;
         bytes memory protocolData = abi.encodePacked(
-            uint8(1), // isDeposit = true
+            uint8(1), // isMint = true
             RestrictTransferFrom.TransferType.Transfer,
             BOB
         );
@@ -122,7 +178,7 @@ contract RocketpoolExecutorTest is TestUtils, Constants {
     function testSwapBurn() public setUpFork(24481337) {
         uint256 amountIn = 2_515_686_112_138_065_226;
         bytes memory protocolData = abi.encodePacked(
-            uint8(0), // isDeposit = false (burn)
+            uint8(0), // isMint = false (burn)
             RestrictTransferFrom.TransferType.Transfer,
             BOB
         );
@@ -143,12 +199,12 @@ contract RocketpoolExecutorTest is TestUtils, Constants {
             loadCallDataFromFile("test_encode_rocketpool_deposit");
 
         (
-            bool isDeposit,
+            bool isMint,
             RestrictTransferFrom.TransferType transferType,
             address receiver
         ) = rocketpoolExecutor.decodeParams(protocolData);
 
-        assertTrue(isDeposit);
+        assertTrue(isMint);
         assertEq(
             uint8(transferType),
             uint8(RestrictTransferFrom.TransferType.Transfer)
@@ -162,12 +218,12 @@ contract RocketpoolExecutorTest is TestUtils, Constants {
             loadCallDataFromFile("test_encode_rocketpool_burn");
 
         (
-            bool isDeposit,
+            bool isMint,
             RestrictTransferFrom.TransferType transferType,
             address receiver
         ) = rocketpoolExecutor.decodeParams(protocolData);
 
-        assertFalse(isDeposit);
+        assertFalse(isMint);
         assertEq(
             uint8(transferType),
             uint8(RestrictTransferFrom.TransferType.Transfer)
@@ -179,7 +235,7 @@ contract RocketpoolExecutorTest is TestUtils, Constants {
     /// Tx 0xe0f1db165b621cb1e50b629af9d47e064be464fbcc7f2bcba3df1d27dbb916be at block 24480105
     /// User deposited 85 ETH and received 73382345660413064855 rETH
     function testSwapDepositIntegration() public setUpFork(24480104) {
-        IRocketTokenRETH RETH = IRocketTokenRETH(RETH_ADDR);
+        IERC20 RETH = IERC20(RETH_ADDR);
         bytes memory protocolData =
             loadCallDataFromFile("test_encode_rocketpool_deposit");
 
@@ -214,6 +270,55 @@ contract RocketpoolExecutorTest is TestUtils, Constants {
     }
 }
 
+/// @notice Tests the LST executor configured for Lido: submit(address referral)
+/// on stETH, without redemptions (see LidoWithdrawalExecutor)
+contract LidoLstExecutorTest is TestUtils, Constants {
+    LstExecutorExposed lidoExecutor;
+
+    function setUp() public {
+        vm.createSelectFork(vm.rpcUrl("mainnet"), 24480104);
+        lidoExecutor = new LstExecutorExposed(
+            PERMIT2_ADDRESS,
+            STETH_ADDR,
+            address(0),
+            STETH_ADDR,
+            bytes4(keccak256("submit(address)")),
+            LstCallArgs.Zero,
+            address(0),
+            bytes4(0),
+            LstCallArgs.None
+        );
+    }
+
+    function testSwapSubmit() public {
+        uint256 amountIn = 10 ether;
+        bytes memory protocolData = abi.encodePacked(
+            uint8(1), // isMint = true
+            RestrictTransferFrom.TransferType.Transfer,
+            BOB
+        );
+
+        vm.deal(address(lidoExecutor), amountIn);
+
+        uint256 amountOut = lidoExecutor.swap(amountIn, protocolData);
+
+        // stETH shares round the balances down by up to 2 wei
+        assertApproxEqAbs(amountOut, amountIn, 2);
+        assertApproxEqAbs(IERC20(STETH_ADDR).balanceOf(BOB), amountOut, 2);
+    }
+
+    function testSwapRedeemNotSupported() public {
+        bytes memory protocolData = abi.encodePacked(
+            uint8(0), // isMint = false (redeem)
+            RestrictTransferFrom.TransferType.Transfer,
+            BOB
+        );
+
+        vm.expectRevert(LstExecutor__RedeemNotSupported.selector);
+        lidoExecutor.swap(1 ether, protocolData);
+    }
+}
+
 // Rocketpool deposit and burn tests are separated into different contracts because they
 // require different fork blocks to test against real historical transactions.
 
@@ -225,7 +330,7 @@ contract RocketpoolDepositTest is TychoRouterTestSetup {
     }
 
     function testSingleSwap() public {
-        IRocketTokenRETH RETH = IRocketTokenRETH(RETH_ADDR);
+        IERC20 RETH = IERC20(RETH_ADDR);
 
         uint256 amountIn = 85 ether;
         bytes memory callData = loadCallDataFromFile(
@@ -259,7 +364,7 @@ contract RocketpoolBurnTest is TychoRouterTestSetup {
     }
 
     function testSingleSwap() public {
-        IRocketTokenRETH RETH = IRocketTokenRETH(RETH_ADDR);
+        IERC20 RETH = IERC20(RETH_ADDR);
 
         uint256 amountIn = 2_515_686_112_138_065_226;
         bytes memory callData = loadCallDataFromFile(
@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "../TestUtils.sol";
+import "@src/executors/OneInchLOPExecutor.sol";
+import {Constants} from "../Constants.sol";
+
+interface IOrderHash {
+    function hashOrder(IOrderMixin.Order calldata order)
+        external
+        view
+        returns (bytes32);
+}
+
+contract OneInchLOPExecutorExposed is OneInchLOPExecutor {
+    constructor(address _limitOrderProtocol, address _permit2)
+        OneInchLOPExecutor(_limitOrderProtocol, _permit2)
+    {}
+
+    function decodeData(bytes calldata data)
+        external
+        pure
+        returns (
+            address tokenIn,
+            address tokenOut,
+            TransferType transferType,
+            bool approvalNeeded,
+            address receiver,
+            IOrderMixin.Order memory order,
+            bytes32 r,
+            bytes32 vs,
+            bytes memory extension
+        )
+    {
+        return _decodeData(data);
+    }
+}
+
+contract OneInchLOPExecutorTest is Constants, TestUtils {
+    OneInchLOPExecutorExposed lopExecutor;
+
+    IERC20 WETH = IERC20(WETH_ADDR);
+    IERC20 USDC = IERC20(USDC_ADDR);
+
+    address maker;
+    uint256 makerKey;
+
+    function setUp() public {
+        vm.createSelectFork(vm.rpcUrl("mainnet"), 23124275);
+        lopExecutor =
+            new OneInchLOPExecutorExposed(ONEINCH_LOP, PERMIT2_ADDRESS);
+        (maker, makerKey) = makeAddrAndKey("maker");
+    }
+
+    /// @dev Order selling 1 WETH for 3000 USDC, signed by the maker
+    function _signedOrder()
+        internal
+        returns (IOrderMixin.Order memory order, bytes32 r, bytes32 vs)
+    {
+        deal(WETH_ADDR, maker, 1 ether);
+        vm.prank(maker);
+        WETH.approve(ONEINCH_LOP, type(uint256).max);
+
+        order = IOrderMixin.Order({
+            salt: 1,
+            maker: uint256(uint160(maker)),
+            receiver: 0,
+            makerAsset: uint256(uint160(WETH_ADDR)),
+            takerAsset: uint256(uint160(USDC_ADDR)),
+            makingAmount: 1 ether,
+            takingAmount: 3000_000000,
+            makerTraits: 0
+        });
+        bytes32 orderHash = IOrderHash(ONEINCH_LOP).hashOrder(order);
+        (uint8 v, bytes32 sigR, bytes32 s) = vm.sign(makerKey, orderHash);
+        r = sigR;
+        vs = bytes32((uint256(v - 27) << 255) | uint256(s));
+    }
+
+    function testDecodeData() public {
+        (IOrderMixin.Order memory order, bytes32 r, bytes32 vs) =
+            _signedOrder();
+        bytes memory params = abi.encodePacked(
+            USDC_ADDR,
+            WETH_ADDR,
+            uint8(RestrictTransferFrom.TransferType.Transfer),
+            true,
+            BOB,
+            abi.encode(order),
+            r,
+            vs,
+            hex"abcd"
+        );
+
+        (
+            address tokenIn,
+            address tokenOut,
+            RestrictTransferFrom.TransferType transferType,
+            bool approvalNeeded,
+            address receiver,
+            IOrderMixin.Order memory decodedOrder,
+            bytes32 decodedR,
+            bytes32 decodedVs,
+            bytes memory extension
+        ) = lopExecutor.decodeData(params);
+
+        assertEq(tokenIn, USDC_ADDR);
+        assertEq(tokenOut, WETH_ADDR);
+        assertEq(
+            uint8(transferType),
+            uint8(RestrictTransferFrom.TransferType.Transfer)
+        );
+        assertTrue(approvalNeeded);
+        assertEq(receiver, BOB);
+        assertEq(
+            keccak256(abi.encode(decodedOrder)), keccak256(abi.encode(order))
+        );
+        assertEq(decodedR, r);
+        assertEq(decodedVs, vs);
+        assertEq(extension, hex"abcd");
+    }
+
+    function testDecodeDataInvalidLength() public {
+        vm.expectRevert(OneInchLOPExecutor__InvalidDataLength.selector);
+        lopExecutor.decodeData(abi.encodePacked(USDC_ADDR, WETH_ADDR));
+    }
+
+    function testFillOrder() public {
+        (IOrderMixin.Order memory order, bytes32 r, bytes32 vs) =
+            _signedOrder();
+        uint256 amountIn = 3000_000000;
+        deal(USDC_ADDR, address(lopExecutor), amountIn);
+
+        bytes memory params = abi.encodePacked(
+            USDC_ADDR,
+            WETH_ADDR,
+            uint8(RestrictTransferFrom.TransferType.None),
+            true,
+            BOB,
+            abi.encode(order),
+            r,
+            vs
+        );
+
+        uint256 amountOut = lopExecutor.swap(amountIn, params);
+
+        assertEq(amountOut, 1 ether);
+        assertEq(WETH.balanceOf(BOB), 1 ether);
+        assertEq(USDC.balanceOf(maker), amountIn);
+        assertEq(USDC.balanceOf(address(lopExecutor)), 0);
+    }
+
+    function testPartialFillOrder() public {
+        (IOrderMixin.Order memory order, bytes32 r, bytes32 vs) =
+            _signedOrder();
+        uint256 amountIn = 1500_000000;
+        deal(USDC_ADDR, address(lopExecutor), amountIn);
+
+        bytes memory params = abi.encodePacked(
+            USDC_ADDR,
+            WETH_ADDR,
+            uint8(RestrictTransferFrom.TransferType.None),
+            true,
+            BOB,
+            abi.encode(order),
+            r,
+            vs
+        );
+
+        uint256 amountOut = lopExecutor.swap(amountIn, params);
+
+        assertEq(amountOut, 0.5 ether);
+        assertEq(WETH.balanceOf(BOB), 0.5 ether);
+    }
+
+    function testFillOrderInvalidAssets() public {
+        (IOrderMixin.Order memory order, bytes32 r, bytes32 vs) =
+            _signedOrder();
+
+        // The order sells WETH, not USDC
+        bytes memory params = abi.encodePacked(
+            WETH_ADDR,
+            USDC_ADDR,
+            uint8(RestrictTransferFrom.TransferType.None),
+            true,
+            BOB,
+            abi.encode(order),
+            r,
+            vs
+        );
+
+        vm.expectRevert(OneInchLOPExecutor__InvalidOrderAssets.selector);
+        lopExecutor.swap(1 ether, params);
+    }
+}
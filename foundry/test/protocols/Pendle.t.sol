@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "../TestUtils.sol";
+import "@src/executors/PendleExecutor.sol";
+import {Constants} from "../Constants.sol";
+
+contract PendleExecutorExposed is PendleExecutor {
+    constructor(address _router, address _permit2)
+        PendleExecutor(_router, _permit2)
+    {}
+
+    function decodeData(bytes calldata data)
+        external
+        pure
+        returns (
+            PendleDirection direction,
+            TransferType transferType,
+            address receiver,
+            address market,
+            address sy,
+            address token
+        )
+    {
+        return _decodeData(data);
+    }
+}
+
+contract PendleExecutorTest is Constants, TestUtils {
+    PendleExecutorExposed pendleExecutor;
+
+    // The market, SY and PT of the encoded calldata
+    address MARKET = 0xA36b60A14A1A5247912584768C6e53E1a269a9F7;
+    address SY = 0xAbF8165dE2aE3FA5e5D2A0aEe5dDB3C9Ae7b9cA8;
+    address PT = 0x9F56094C450763769BA0EA9Fe2876070c0fD5F77;
+
+    function setUp() public {
+        pendleExecutor = new PendleExecutorExposed(PENDLE_ROUTER, PERMIT2_ADDRESS);
+    }
+
+    function testDecodeData() public view {
+        (
+            PendleDirection direction,
+            RestrictTransferFrom.TransferType transferType,
+            address receiver,
+            address market,
+            address sy,
+            address token
+        ) = pendleExecutor.decodeData(
+            loadCallDataFromFile("test_encode_pendle_sy_to_pt")
+        );
+
+        assertEq(uint8(direction), uint8(PendleDirection.SyToPt));
+        assertEq(
+            uint8(transferType),
+            uint8(RestrictTransferFrom.TransferType.Transfer)
+        );
+        assertEq(receiver, BOB);
+        assertEq(market, MARKET);
+        assertEq(sy, SY);
+        assertEq(token, PT);
+    }
+
+    function testDecodeDataInvalidLength() public {
+        bytes memory data = abi.encodePacked(
+            PendleDirection.PtToSy,
+            RestrictTransferFrom.TransferType.None,
+            BOB,
+            MARKET,
+            SY
+        );
+
+        vm.expectRevert(PendleExecutor__InvalidDataLength.selector);
+        pendleExecutor.decodeData(data);
+    }
+
+    function testConstructorZeroRouter() public {
+        vm.expectRevert(PendleExecutor__ZeroAddress.selector);
+        new PendleExecutor(address(0), PERMIT2_ADDRESS);
+    }
+}
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: BUSL-1.1
+pragma solidity ^0.8.26;
+
+import "../TestUtils.sol";
+import "@src/executors/SkyPsmExecutor.sol";
+import {Constants} from "../Constants.sol";
+
+contract SkyPsmExecutorExposed is SkyPsmExecutor {
+    constructor(address _permit2) SkyPsmExecutor(_permit2) {}
+
+    function decodeParams(bytes calldata data)
+        external
+        pure
+        returns (
+            address tokenIn,
+            address psm,
+            address receiver,
+            TransferType transferType,
+            SkyPsmDirection direction,
+            bool convertDecimals,
+            bool approvalNeeded
+        )
+    {
+        return _decodeData(data);
+    }
+}
+
+contract SkyPsmExecutorTest is Constants, TestUtils {
+    SkyPsmExecutorExposed skyPsmExposed;
+
+    function setUp() public {
+        uint256 forkBlock = 23934489;
+        vm.createSelectFork(vm.rpcUrl("mainnet"), forkBlock);
+        skyPsmExposed = new SkyPsmExecutorExposed(PERMIT2_ADDRESS);
+    }
+
+    function testDecodeParams() public view {
+        bytes memory params = abi.encodePacked(
+            USDC_ADDR,
+            SKY_PSM_ADDR,
+            BOB,
+            RestrictTransferFrom.TransferType.Transfer,
+            SkyPsmDirection.SellGem,
+            true,
+            true
+        );
+
+        (
+            address tokenIn,
+            address psm,
+            address receiver,
+            RestrictTransferFrom.TransferType transferType,
+            SkyPsmDirection direction,
+            bool convertDecimals,
+            bool approvalNeeded
+        ) = skyPsmExposed.decodeParams(params);
+
+        assertEq(tokenIn, USDC_ADDR);
+        assertEq(psm, SKY_PSM_ADDR);
+        assertEq(receiver, BOB);
+        assertEq(
+            uint8(transferType),
+            uint8(RestrictTransferFrom.TransferType.Transfer)
+        );
+        assertEq(uint8(direction), uint8(SkyPsmDirection.SellGem));
+        assertEq(convertDecimals, true);
+        assertEq(approvalNeeded, true);
+    }
+
+    function testDecodeParamsInvalidDataLength() public {
+        bytes memory invalidParams = abi.encodePacked(
+            USDC_ADDR, SKY_PSM_ADDR, BOB, RestrictTransferFrom.TransferType.None
+        );
+
+        vm.expectRevert(SkyPsmExecutor__InvalidDataLength.selector);
+        skyPsmExposed.decodeParams(invalidParams);
+    }
+
+    function testSwapSellGemForDai() public {
+        uint256 amountIn = 1000_000000;
+        deal(USDC_ADDR, address(skyPsmExposed), amountIn);
+        bytes memory protocolData = abi.encodePacked(
+            USDC_ADDR,
+            SKY_PSM_ADDR,
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            SkyPsmDirection.SellGem,
+            true,
+            true
+        );
+
+        uint256 balanceBefore = IERC20(DAI_ADDR).balanceOf(BOB);
+        uint256 amountOut = skyPsmExposed.swap(amountIn, protocolData);
+        uint256 balanceAfter = IERC20(DAI_ADDR).balanceOf(BOB);
+
+        // The PSM doesn't charge a fee to sell gems
+        assertEq(amountOut, 1000 ether);
+        assertEq(balanceAfter - balanceBefore, amountOut);
+        assertEq(IERC20(USDC_ADDR).balanceOf(address(skyPsmExposed)), 0);
+    }
+
+    function testSwapBuyGemWithDai() public {
+        uint256 amountIn = 1000 ether;
+        deal(DAI_ADDR, address(skyPsmExposed), amountIn);
+        bytes memory protocolData = abi.encodePacked(
+            DAI_ADDR,
+            SKY_PSM_ADDR,
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            SkyPsmDirection.BuyGem,
+            true,
+            true
+        );
+
+        uint256 balanceBefore = IERC20(USDC_ADDR).balanceOf(BOB);
+        uint256 amountOut = skyPsmExposed.swap(amountIn, protocolData);
+        uint256 balanceAfter = IERC20(USDC_ADDR).balanceOf(BOB);
+
+        assertEq(amountOut, 1000_000000);
+        assertEq(balanceAfter - balanceBefore, amountOut);
+        assertEq(IERC20(DAI_ADDR).balanceOf(address(skyPsmExposed)), 0);
+    }
+
+    function testSwapBuyGemWithUsds() public {
+        uint256 amountIn = 1000 ether;
+        deal(USDS_ADDR, address(skyPsmExposed), amountIn);
+        bytes memory protocolData = abi.encodePacked(
+            USDS_ADDR,
+            SKY_USDS_PSM_ADDR,
+            BOB,
+            RestrictTransferFrom.TransferType.None,
+            SkyPsmDirection.BuyGem,
+            true,
+            true
+        );
+
+        uint256 balanceBefore = IERC20(USDC_ADDR).balanceOf(BOB);
+        uint256 amountOut = skyPsmExposed.swap(amountIn, protocolData);
+        uint256 balanceAfter = IERC20(USDC_ADDR).balanceOf(BOB);
+
+        assertEq(amountOut, 1000_000000);
+        assertEq(balanceAfter - balanceBefore, amountOut);
+    }
+}
@@ -191,6 +191,29 @@ contract UniswapV2ExecutorTest is Constants, Permit2TestHelper, TestUtils {
         assertGe(finalBalance, amountOut);
     }
 
+    function testSwapFeeOnTransfer() public {
+        uint256 amountIn = 10 ** 18;
+        uint256 amountOut = 1847751195973566072891;
+        bool zeroForOne = false;
+        bytes memory protocolData = abi.encodePacked(
+            WETH_ADDR,
+            WETH_DAI_POOL,
+            BOB,
+            zeroForOne,
+            RestrictTransferFrom.TransferType.Transfer,
+            uint8(2) // fee-on-transfer flag
+        );
+
+        deal(WETH_ADDR, address(uniswapV2Exposed), amountIn);
+        uint256 balanceBefore = DAI.balanceOf(BOB);
+        uint256 calculatedAmount =
+            uniswapV2Exposed.swap(amountIn, protocolData);
+
+        // Without transfer fees, the balance differences match the amounts
+        assertEq(calculatedAmount, amountOut);
+        assertEq(DAI.balanceOf(BOB) - balanceBefore, amountOut);
+    }
+
     function testDecodeIntegration() public view {
         bytes memory protocolData =
             hex"c02aaa39b223fe8d0a0e5c4f27ead9083c756cc288e6a0c2ddd26feeb64f039a2c41296fcb3f564000000000000000000000000000000000000000010001";
@@ -31,6 +31,20 @@ contract UniswapV4ExecutorExposed is UniswapV4Executor {
     {
         return _decodeData(data);
     }
+
+    function decodeSplitData(bytes calldata data)
+        external
+        view
+        returns (
+            address tokenIn,
+            address tokenOut,
+            RestrictTransferFrom.TransferType transferType,
+            address receiver,
+            UniswapV4Branch[] memory branches
+        )
+    {
+        return _decodeSplitData(data);
+    }
 }
 
 contract UniswapV4ExecutorTest is Constants, TestUtils {
@@ -226,6 +240,114 @@ contract UniswapV4ExecutorTest is Constants, TestUtils {
         assertTrue(IERC20(WBTC_ADDR).balanceOf(ALICE) == amountOut);
     }
 
+    function _usdeUsdtPools()
+        internal
+        view
+        returns (UniswapV4Executor.UniswapV4Pool[] memory pools)
+    {
+        pools = new UniswapV4Executor.UniswapV4Pool[](1);
+        pools[0] = UniswapV4Executor.UniswapV4Pool({
+            intermediaryToken: USDT_ADDR,
+            fee: uint24(100),
+            tickSpacing: int24(1),
+            hook: address(0),
+            hookData: bytes("")
+        });
+    }
+
+    function testDecodeSplitParams() public view {
+        UniswapV4Executor.UniswapV4Pool[][] memory branches =
+            new UniswapV4Executor.UniswapV4Pool[][](2);
+        branches[0] = _usdeUsdtPools();
+        branches[1] = new UniswapV4Executor.UniswapV4Pool[](2);
+        branches[1][0] = UniswapV4Executor.UniswapV4Pool({
+            intermediaryToken: USDC_ADDR,
+            fee: uint24(100),
+            tickSpacing: int24(1),
+            hook: address(0),
+            hookData: bytes("")
+        });
+        branches[1][1] = UniswapV4Executor.UniswapV4Pool({
+            intermediaryToken: USDT_ADDR,
+            fee: uint24(10),
+            tickSpacing: int24(1),
+            hook: address(0),
+            hookData: bytes("0x12345")
+        });
+        uint24[] memory splits = new uint24[](2);
+        splits[0] = 0x999999;
+
+        bytes memory data = UniswapV4Utils.encodeExactInputSplit(
+            USDE_ADDR,
+            USDT_ADDR,
+            RestrictTransferFrom.TransferType.Transfer,
+            ALICE,
+            splits,
+            branches
+        );
+
+        (
+            address tokenIn,
+            address tokenOut,
+            RestrictTransferFrom.TransferType transferType,
+            address receiver,
+            UniswapV4Executor.UniswapV4Branch[] memory decodedBranches
+        ) = uniswapV4Exposed.decodeSplitData(data);
+
+        assertEq(tokenIn, USDE_ADDR);
+        assertEq(tokenOut, USDT_ADDR);
+        assertEq(
+            uint8(transferType),
+            uint8(RestrictTransferFrom.TransferType.Transfer)
+        );
+        assertEq(receiver, ALICE);
+        assertEq(decodedBranches.length, 2);
+        assertEq(decodedBranches[0].split, 0x999999);
+        assertEq(decodedBranches[0].path.length, 1);
+        assertEq(
+            Currency.unwrap(decodedBranches[0].path[0].intermediateCurrency),
+            USDT_ADDR
+        );
+        assertEq(decodedBranches[1].split, 0);
+        assertEq(decodedBranches[1].path.length, 2);
+        assertEq(
+            Currency.unwrap(decodedBranches[1].path[0].intermediateCurrency),
+            USDC_ADDR
+        );
+        assertEq(decodedBranches[1].path[1].fee, 10);
+        assertEq(decodedBranches[1].path[1].hookData, bytes("0x12345"));
+    }
+
+    function testSplitSwap() public {
+        // USDE ──(60%)──> USDT
+        //      └─(40%)──> USDT
+        uint256 amountIn = 100 ether;
+        deal(USDE_ADDR, address(uniswapV4Exposed), amountIn);
+        uint256 usdeBalanceBeforePool = USDE.balanceOf(POOL_MANAGER);
+
+        UniswapV4Executor.UniswapV4Pool[][] memory branches =
+            new UniswapV4Executor.UniswapV4Pool[][](2);
+        branches[0] = _usdeUsdtPools();
+        branches[1] = _usdeUsdtPools();
+        uint24[] memory splits = new uint24[](2);
+        splits[0] = 0x999999;
+
+        bytes memory data = UniswapV4Utils.encodeExactInputSplit(
+            USDE_ADDR,
+            USDT_ADDR,
+            RestrictTransferFrom.TransferType.Transfer,
+            ALICE,
+            splits,
+            branches
+        );
+
+        uint256 amountOut = uniswapV4Exposed.swap(amountIn, data);
+        assertEq(USDE.balanceOf(POOL_MANAGER), usdeBalanceBeforePool + amountIn);
+        assertEq(USDE.balanceOf(address(uniswapV4Exposed)), 0);
+        assertGt(amountOut, 0);
+        assertEq(USDT.balanceOf(ALICE), amountOut);
+    }
+
     function testSingleSwapEulerHook() public {
         // Replicating tx: 0xb372306a81c6e840f4ec55f006da6b0b097f435802a2e6fd216998dd12fb4aca
         address hook = address(0x69058613588536167BA0AA94F0CC1Fe420eF28a8);
@@ -619,6 +741,62 @@ contract TychoRouterForUniswapV4Test is TychoRouterTestSetup {
         console.logUint(balanceAfter - balanceBefore);
         assertEq(balanceAfter - balanceBefore, 1474406268748155809);
     }
+
+    function testSingleSwapUSV4WithOutputFeeOutputETH() public {
+        // Trade 3000 USDC for ETH with 1 swap on Uniswap V4, taking a 0.25% fee on the ETH bought
+        // The swap sends the ETH to the router, which sends the fee to BOB and the rest to ALICE
+        //
+        //   USDC ───(USV4)──> ETH
+        //
+        uint256 amountIn = 3000_000000;
+        deal(USDC_ADDR, ALICE, amountIn);
+        uint256 balanceBefore = ALICE.balance;
+
+        UniswapV4Executor.UniswapV4Pool[] memory pools =
+            new UniswapV4Executor.UniswapV4Pool[](1);
+        pools[0] = UniswapV4Executor.UniswapV4Pool({
+            intermediaryToken: address(0),
+            fee: uint24(3000),
+            tickSpacing: int24(60),
+            hook: address(0),
+            hookData: bytes("")
+        });
+
+        bytes memory protocolData = UniswapV4Utils.encodeExactInput(
+            USDC_ADDR,
+            address(0),
+            false,
+            RestrictTransferFrom.TransferType.TransferFrom,
+            tychoRouterAddr,
+            pools
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv4Executor), protocolData);
+
+        vm.startPrank(ALICE);
+        IERC20(USDC_ADDR).approve(tychoRouterAddr, amountIn);
+        uint256 amountOut = tychoRouter.singleSwapWithFee(
+            amountIn,
+            USDC_ADDR,
+            address(0),
+            1 ether,
+            false,
+            false,
+            ALICE,
+            true,
+            TychoRouter.Fee(BOB, 25, false),
+            swap
+        );
+        vm.stopPrank();
+
+        uint256 swapAmountOut = 1474406268748155809;
+        uint256 feeAmount = swapAmountOut * 25 / 10000;
+        assertEq(amountOut, swapAmountOut - feeAmount);
+        assertEq(ALICE.balance - balanceBefore, amountOut);
+        assertEq(BOB.balance, feeAmount);
+        assertEq(tychoRouterAddr.balance, 0);
+    }
 }
 
 contract TychoRouterForUniswapV4AndEulerTest is TychoRouterTestSetup {
@@ -46,6 +46,39 @@ library UniswapV4Utils {
         );
     }
 
+    function encodeExactInputSplit(
+        address tokenIn,
+        address tokenOut,
+        RestrictTransferFrom.TransferType transferType,
+        address receiver,
+        uint24[] memory splits,
+        UniswapV4Executor.UniswapV4Pool[][] memory branches
+    ) public pure returns (bytes memory) {
+        require(splits.length == branches.length, "Splits length mismatch");
+
+        bytes[] memory encodedBranches = new bytes[](branches.length);
+        for (uint256 i = 0; i < branches.length; i++) {
+            // Reuse the pools encoding of a sequential swap, without its header
+            bytes memory sequential = encodeExactInput(
+                tokenIn, tokenOut, false, transferType, receiver, branches[i]
+            );
+            bytes memory pools = new bytes(sequential.length - 62);
+            for (uint256 j = 0; j < pools.length; j++) {
+                pools[j] = sequential[62 + j];
+            }
+            encodedBranches[i] = abi.encodePacked(bytes3(splits[i]), pools);
+        }
+
+        return abi.encodePacked(
+            tokenIn,
+            tokenOut,
+            uint8(2),
+            transferType,
+            receiver,
+            pleEncode(encodedBranches)
+        );
+    }
+
     function pleEncode(bytes[] memory data)
         public
         pure
@@ -114,6 +114,109 @@ contract UniswapXFillerTest is Test, TychoRouterTestSetup {
         assertGe(IERC20(DAI_ADDR).balanceOf(BOB), amountOut);
     }
 
+    function testBatchCallback() public {
+        fillerSetup();
+        // Two orders selling WETH for DAI, filled with a single router call
+        uint256 amountIn = 10 ** 18;
+        uint256 amountOut = 1847751195973566072891;
+        bool zeroForOne = false;
+        bytes memory protocolData = abi.encodePacked(
+            WETH_ADDR,
+            WETH_DAI_POOL,
+            address(filler),
+            zeroForOne,
+            RestrictTransferFrom.TransferType.TransferFrom
+        );
+
+        bytes memory swap =
+            encodeSingleSwap(address(usv2Executor), protocolData);
+
+        bytes memory tychoRouterData = abi.encodeWithSelector(
+            tychoRouter.singleSwap.selector,
+            amountIn,
+            WETH_ADDR,
+            DAI_ADDR,
+            2008817438608734439722,
+            false,
+            false,
+            address(filler),
+            true,
+            swap
+        );
+
+        bytes memory callbackData =
+            abi.encodePacked(true, true, tychoRouterData);
+
+        deal(WETH_ADDR, address(filler), amountIn);
+
+        ResolvedOrder[] memory orders = new ResolvedOrder[](2);
+        for (uint256 i = 0; i < orders.length; i++) {
+            OutputToken[] memory outputs = new OutputToken[](1);
+            outputs[0] = OutputToken({
+                token: address(DAI_ADDR), amount: amountOut / 2, recipient: BOB
+            });
+            // Irrelevant fields for this test - we only need token info for
+            // the sake of testing.
+            orders[i] = ResolvedOrder({
+                info: OrderInfo({
+                    reactor: address(0),
+                    swapper: address(0),
+                    nonce: i,
+                    deadline: 0,
+                    additionalValidationContract: address(0),
+                    additionalValidationData: ""
+                }),
+                input: InputToken({
+                    token: address(WETH_ADDR),
+                    amount: amountIn / 2,
+                    maxAmount: amountIn / 2
+                }),
+                outputs: outputs,
+                sig: "",
+                hash: ""
+            });
+        }
+
+        vm.startPrank(REACTOR);
+        filler.reactorCallback(orders, callbackData);
+        vm.stopPrank();
+
+        // Check that the funds of both orders are in the filler
+        uint256 finalBalance = IERC20(DAI_ADDR).balanceOf(address(filler));
+        assertGe(finalBalance, amountOut);
+
+        // Check that the proper approval was set
+        vm.startPrank(REACTOR);
+        IERC20(DAI_ADDR).transferFrom(address(filler), BOB, amountOut);
+        vm.stopPrank();
+        assertGe(IERC20(DAI_ADDR).balanceOf(BOB), amountOut);
+    }
+
+    function testExecuteWithUnknownReactor() public {
+        fillerSetup();
+        SignedOrder memory order = SignedOrder({order: "", sig: ""});
+
+        vm.startPrank(EXECUTOR);
+        vm.expectRevert(UniswapXFiller__UnknownReactor.selector);
+        filler.executeWithReactor(IReactor(BOB), order, "");
+
+        SignedOrder[] memory orders = new SignedOrder[](1);
+        orders[0] = order;
+        vm.expectRevert(UniswapXFiller__UnknownReactor.selector);
+        filler.executeBatchWithReactor(IReactor(BOB), orders, "");
+        vm.stopPrank();
+    }
+
+    function testExecuteBatchMissingRole() public {
+        fillerSetup();
+        SignedOrder[] memory orders = new SignedOrder[](0);
+
+        vm.startPrank(BOB);
+        vm.expectRevert();
+        filler.executeBatch(orders, "");
+        vm.stopPrank();
+    }
+
     function testExecuteIntegration() public {
         fillerSetup();
 
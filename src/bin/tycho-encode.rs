@@ -20,7 +20,9 @@ use tycho_execution::encoding::{
 #[derive(Parser)]
 /// Encode swap transactions for the Tycho router
 ///
-/// Reads a JSON object from stdin with the following structure:
+/// Reads a JSON solution from the input file, or from stdin if no file is given, and prints the
+/// encoded swaps, the function signature, the target address and, for the router, the full
+/// calldata of the call as JSON. The solution has the following structure:
 /// ```json
 /// {
 ///     "sender": "0x...",
@@ -52,6 +54,9 @@ pub struct Cli {
     pub command: Commands,
     #[arg(short, long)]
     chain: Chain,
+    /// Path of the JSON solution to encode. Read from stdin if not set
+    #[arg(short, long)]
+    input: Option<String>,
     #[arg(short, long)]
     executors_file_path: Option<String>,
     #[arg(short, long)]
@@ -72,14 +77,21 @@ pub enum Commands {
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
-    // Read from stdin until EOF
     let mut buffer = String::new();
-    io::stdin()
-        .read_to_string(&mut buffer)
-        .map_err(|e| format!("Failed to read from stdin: {e}"))?;
+    if let Some(input_path) = &cli.input {
+        buffer = fs::read_to_string(input_path)
+            .map_err(|e| format!("Failed to read the solution from {input_path:?}: {e}"))?;
+    } else {
+        // Read from stdin until EOF
+        io::stdin()
+            .read_to_string(&mut buffer)
+            .map_err(|e| format!("Failed to read from stdin: {e}"))?;
+    }
 
     if buffer.trim().is_empty() {
-        return Err("No input provided. Expected JSON input on stdin.".into());
+        return Err(
+            "No input provided. Expected a JSON solution on stdin or in the input file.".into()
+        );
     }
     let solution: Solution = serde_json::from_str(&buffer)?;
 
@@ -97,8 +109,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 } else {
                     None
                 };
-            let swap_encoder_registry = SwapEncoderRegistry::new(Chain::Ethereum)
-                .add_default_encoders(executors_addresses)?;
+            let swap_encoder_registry =
+                SwapEncoderRegistry::new(chain).add_default_encoders(executors_addresses)?;
             builder = builder.swap_encoder_registry(swap_encoder_registry);
             if let Some(router_address) = cli.router_address {
                 builder = builder.router_address(router_address);
@@ -107,7 +119,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 builder = builder.user_transfer_type(user_transfer_type);
             }
             #[allow(deprecated)]
-            if let Some(swapper_pk) = cli.swapper_pk {
+            if let Some(swapper_pk) = cli.swapper_pk.clone() {
                 builder = builder.swapper_pk(swapper_pk);
             }
             builder.build()?
@@ -115,10 +127,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::TychoExecutor => TychoExecutorEncoderBuilder::new().build()?,
     };
 
-    let encoded_solutions = encoder.encode_solutions(vec![solution])?;
+    let encoded_solutions = encoder.encode_solutions(vec![solution.clone()])?;
+    // The full router call can only be assembled if the permit, if any, can be signed
+    let transaction = match cli.command {
+        Commands::TychoRouter
+            if encoded_solutions[0].permit.is_none() || cli.swapper_pk.is_some() =>
+        {
+            #[allow(deprecated)]
+            let transactions = encoder.encode_full_calldata(vec![solution])?;
+            transactions.into_iter().next()
+        }
+        _ => None,
+    };
     let encoded = serde_json::json!({
             "swaps": format!("0x{}", hex::encode(&encoded_solutions[0].swaps)),
             "interacting_with": format!("0x{}", hex::encode(&encoded_solutions[0].interacting_with)),
+            "calldata": transaction
+                .as_ref()
+                .map(|transaction| format!("0x{}", hex::encode(&transaction.data))),
+            "value": transaction
+                .as_ref()
+                .map(|transaction| transaction.value.to_string()),
             "function_signature": format!("{}",&encoded_solutions[0].function_signature),
             "n_tokens": format!("{}", &encoded_solutions[0].n_tokens),
             "permit": match encoded_solutions[0].permit.as_ref() {
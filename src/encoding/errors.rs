@@ -1,7 +1,7 @@
-use std::{io, str::Utf8Error};
+use std::{fmt, io, str::Utf8Error};
 
 use thiserror::Error;
-use tycho_common::simulation::errors::SimulationError;
+use tycho_common::{simulation::errors::SimulationError, Bytes};
 
 /// Represents the outer-level, user-facing errors of the tycho-execution encoding package.
 ///
@@ -13,6 +13,17 @@ use tycho_common::simulation::errors::SimulationError;
 /// - `RecoverableError`: Indicates that the encoding has failed with a recoverable error. Retrying
 ///   at a later time may succeed. It may have failed due to a temporary issue, such as a network
 ///   problem.
+/// - `CalldataTooLarge`: The encoded calldata exceeds the calldata size budget of the chain (see
+///   `CalldataBudget`). The report details where the bytes come from.
+/// - `QuoteTimeout`: An RFQ quote request didn't answer within the timeout of its `RfqConfig`, on
+///   its last attempt. Retrying at a later time may succeed.
+/// - `QuoteExpired`: An RFQ quote expires before the solution can be executed (see
+///   `RfqConfig::check_quote_expiry`). Requesting a new quote may succeed.
+/// - `UnsafeToken`: A token of the solution is denylisted or can't be transferred, e.g. because it
+///   is paused or an address is blacklisted (see `TokenSafetyChecker`).
+/// - `Timeout`: A solution wasn't encoded before its deadline (see
+///   `TychoEncoder::encode_solutions_streaming`), e.g. because of a slow RFQ quote. Retrying at a
+///   later time may succeed.
 #[derive(Error, Debug, PartialEq)]
 pub enum EncodingError {
     #[error("Invalid input: {0}")]
@@ -23,6 +34,63 @@ pub enum EncodingError {
     RecoverableError(String),
     #[error("Not implemented: {0}")]
     NotImplementedError(String),
+    #[error("Calldata too large: {0}")]
+    CalldataTooLarge(Box<CalldataSizeReport>),
+    #[error("Quote timeout: {0}")]
+    QuoteTimeout(String),
+    #[error("Quote expired: {0}")]
+    QuoteExpired(String),
+    #[error("Unsafe token: {0}")]
+    UnsafeToken(Box<UnsafeTokenReport>),
+    #[error("Timeout: {0}")]
+    Timeout(String),
+}
+
+/// Breakdown of calldata that exceeds its size budget, so solvers can adapt their solutions.
+///
+/// # Fields
+/// * `calldata_bytes`: Size of the calldata
+/// * `max_bytes`: Calldata size budget of the chain
+/// * `swap_bytes`: Size of every encoded swap, in the order of the encoded solution. Swaps grouped
+///   into a single encoded swap (e.g. consecutive Uniswap V4 swaps) are counted together.
+/// * `suggestions`: Changes to the solution that would reduce the calldata size
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalldataSizeReport {
+    pub calldata_bytes: usize,
+    pub max_bytes: usize,
+    pub swap_bytes: Vec<usize>,
+    pub suggestions: Vec<String>,
+}
+
+impl fmt::Display for CalldataSizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes (max {} bytes), swaps: {:?} bytes.",
+            self.calldata_bytes, self.max_bytes, self.swap_bytes
+        )?;
+        for suggestion in &self.suggestions {
+            write!(f, " {suggestion}.")?;
+        }
+        Ok(())
+    }
+}
+
+/// A token rejected by the token safety checks.
+///
+/// # Fields
+/// * `token`: Address of the token
+/// * `reason`: Why the token was rejected (e.g. the revert message of its transfer)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsafeTokenReport {
+    pub token: Bytes,
+    pub reason: String,
+}
+
+impl fmt::Display for UnsafeTokenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.token, self.reason)
+    }
 }
 
 impl From<io::Error> for EncodingError {
@@ -0,0 +1,407 @@
+use alloy::{
+    core::sol,
+    primitives::{aliases::U40, Address, Bytes as AlloyBytes, B256},
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol_types::{eip712_domain, Eip712Domain, SolStruct},
+};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::utils::{biguint_to_u256, bytes_to_address},
+    serde_primitives::biguint_string,
+};
+
+/// Name of the EIP-712 domain of the Angstrom contract.
+const ANGSTROM_DOMAIN_NAME: &str = "Angstrom";
+/// Version of the EIP-712 domain of the Angstrom contract.
+const ANGSTROM_DOMAIN_VERSION: &str = "v1";
+
+sol! {
+    #[derive(Debug)]
+    struct ExactFlashOrder {
+        bool exact_in;
+        uint128 amount;
+        uint128 max_extra_fee_asset0;
+        uint256 min_price;
+        bool use_internal;
+        address asset_in;
+        address asset_out;
+        address recipient;
+        bytes hook_data;
+        uint64 valid_for_block;
+    }
+
+    #[derive(Debug)]
+    struct ExactStandingOrder {
+        bool exact_in;
+        uint128 amount;
+        uint128 max_extra_fee_asset0;
+        uint256 min_price;
+        bool use_internal;
+        address asset_in;
+        address asset_out;
+        address recipient;
+        bytes hook_data;
+        uint64 nonce;
+        uint40 deadline;
+    }
+}
+
+/// How long an Angstrom order can be executed for.
+///
+/// Variants:
+/// - `Flash`: The order is only valid in the given block.
+/// - `Standing`: The order is valid until the deadline (a timestamp in seconds), and can be
+///   executed once per nonce.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AngstromOrderValidity {
+    Flash { valid_for_block: u64 },
+    Standing { nonce: u64, deadline: u64 },
+}
+
+/// A private order to be submitted to the Angstrom auction, as opposed to a public swap through
+/// the Angstrom hook (which only needs an attestation, see `UniswapV4SwapEncoder`).
+///
+/// # Fields
+/// * `exact_in`: True if `amount` is the amount of `asset_in` to sell, false if it is the amount of
+///   `asset_out` to buy
+/// * `amount`: Amount to sell (exact in) or buy (exact out)
+/// * `max_extra_fee_asset0`: Maximum gas fee the order pays, in the pool's asset0
+/// * `min_price`: Minimum price of the order, as `asset_out` per `asset_in` in ray (1e27)
+/// * `use_internal`: Whether the order settles against the signer's balance deposited in Angstrom
+///   instead of transferring the tokens
+/// * `asset_in`: Token sold
+/// * `asset_out`: Token bought
+/// * `recipient`: Receiver of the bought tokens. The zero address means the signer.
+/// * `hook_data`: Data for the hook called by Angstrom after the order is executed, if any
+/// * `validity`: Whether the order is a flash or a standing order
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AngstromOrder {
+    pub exact_in: bool,
+    #[serde(with = "biguint_string")]
+    pub amount: BigUint,
+    #[serde(with = "biguint_string")]
+    pub max_extra_fee_asset0: BigUint,
+    #[serde(with = "biguint_string")]
+    pub min_price: BigUint,
+    pub use_internal: bool,
+    pub asset_in: Bytes,
+    pub asset_out: Bytes,
+    pub recipient: Bytes,
+    #[serde(default)]
+    pub hook_data: Bytes,
+    pub validity: AngstromOrderValidity,
+}
+
+/// Signature envelope of an Angstrom order, as Angstrom verifies it.
+///
+/// Variants:
+/// - `Ecdsa`: Signature of the order hash by an EOA, encoded as `v (1 byte) || r || s`.
+/// - `Contract`: Signature validated by the `from` smart contract wallet (ERC-1271), encoded as
+///   `from (20 bytes) || signature`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AngstromSignature {
+    Ecdsa { v: u8, r: B256, s: B256 },
+    Contract { from: Bytes, signature: Bytes },
+}
+
+impl AngstromSignature {
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            AngstromSignature::Ecdsa { v, r, s } => {
+                let mut encoded = Vec::with_capacity(65);
+                encoded.push(*v);
+                encoded.extend_from_slice(r.as_slice());
+                encoded.extend_from_slice(s.as_slice());
+                encoded
+            }
+            AngstromSignature::Contract { from, signature } => {
+                let mut encoded = from.to_vec();
+                encoded.extend_from_slice(signature);
+                encoded
+            }
+        }
+    }
+}
+
+/// Payload submitted to Angstrom for a private order.
+///
+/// # Fields
+/// * `order`: The signed order
+/// * `order_hash`: EIP-712 hash of the order in the Angstrom domain, i.e. what was signed
+/// * `is_ecdsa`: Whether the signature is an ECDSA signature or an ERC-1271 one
+/// * `signature`: The encoded signature envelope (see `AngstromSignature`)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AngstromOrderSubmission {
+    pub order: AngstromOrder,
+    pub order_hash: Bytes,
+    pub is_ecdsa: bool,
+    pub signature: Bytes,
+}
+
+/// Hashes and signs Angstrom private orders, for solvers participating in the Angstrom auction.
+///
+/// # Fields
+/// * `domain`: EIP-712 domain of the Angstrom contract on the chain
+#[derive(Clone, Debug)]
+pub struct AngstromOrderEncoder {
+    domain: Eip712Domain,
+}
+
+impl AngstromOrderEncoder {
+    pub fn new(chain: Chain, angstrom_address: &Bytes) -> Result<Self, EncodingError> {
+        let verifying_contract = bytes_to_address(angstrom_address)?;
+        let domain = eip712_domain! {
+            name: ANGSTROM_DOMAIN_NAME,
+            version: ANGSTROM_DOMAIN_VERSION,
+            chain_id: chain.id(),
+            verifying_contract: verifying_contract,
+        };
+        Ok(Self { domain })
+    }
+
+    /// Returns the EIP-712 hash of the order, which is what the signer of the order signs.
+    pub fn order_hash(&self, order: &AngstromOrder) -> Result<B256, EncodingError> {
+        Self::validate_order(order)?;
+        let exact_in = order.exact_in;
+        let amount = to_u128(&order.amount, "amount")?;
+        let max_extra_fee_asset0 = to_u128(&order.max_extra_fee_asset0, "max_extra_fee_asset0")?;
+        let min_price = biguint_to_u256(&order.min_price);
+        let use_internal = order.use_internal;
+        let asset_in = bytes_to_address(&order.asset_in)?;
+        let asset_out = bytes_to_address(&order.asset_out)?;
+        let recipient = recipient_address(&order.recipient)?;
+        let hook_data = AlloyBytes::from(order.hook_data.to_vec());
+        let hash = match order.validity {
+            AngstromOrderValidity::Flash { valid_for_block } => ExactFlashOrder {
+                exact_in,
+                amount,
+                max_extra_fee_asset0,
+                min_price,
+                use_internal,
+                asset_in,
+                asset_out,
+                recipient,
+                hook_data,
+                valid_for_block,
+            }
+            .eip712_signing_hash(&self.domain),
+            AngstromOrderValidity::Standing { nonce, deadline } => ExactStandingOrder {
+                exact_in,
+                amount,
+                max_extra_fee_asset0,
+                min_price,
+                use_internal,
+                asset_in,
+                asset_out,
+                recipient,
+                hook_data,
+                nonce,
+                deadline: U40::try_from(deadline).map_err(|_| {
+                    EncodingError::InvalidInput(format!(
+                        "Angstrom order deadline {deadline} doesn't fit in 40 bits"
+                    ))
+                })?,
+            }
+            .eip712_signing_hash(&self.domain),
+        };
+        Ok(hash)
+    }
+
+    /// Signs the order with an EOA and returns its submission payload.
+    ///
+    /// # Warning
+    /// This is only an **example implementation** provided for reference purposes.
+    /// **Do not rely on this in production.** You should implement your own version.
+    pub fn sign_order(
+        &self,
+        order: AngstromOrder,
+        signer: &PrivateKeySigner,
+    ) -> Result<AngstromOrderSubmission, EncodingError> {
+        let hash = self.order_hash(&order)?;
+        let signature = signer
+            .sign_hash_sync(&hash)
+            .map_err(|e| {
+                EncodingError::FatalError(format!("Failed to sign Angstrom order with error: {e}"))
+            })?;
+        let signature = AngstromSignature::Ecdsa {
+            v: 27 + signature.v() as u8,
+            r: B256::from(signature.r()),
+            s: B256::from(signature.s()),
+        };
+        self.encode_submission(order, &signature)
+    }
+
+    /// Returns the submission payload of an order signed outside of this encoder (e.g. by a smart
+    /// contract wallet).
+    pub fn encode_submission(
+        &self,
+        order: AngstromOrder,
+        signature: &AngstromSignature,
+    ) -> Result<AngstromOrderSubmission, EncodingError> {
+        if let AngstromSignature::Contract { from, .. } = signature {
+            bytes_to_address(from)?;
+        }
+        let order_hash = self.order_hash(&order)?;
+        Ok(AngstromOrderSubmission {
+            order,
+            order_hash: Bytes::from(order_hash.to_vec()),
+            is_ecdsa: matches!(signature, AngstromSignature::Ecdsa { .. }),
+            signature: Bytes::from(signature.encode()),
+        })
+    }
+
+    fn validate_order(order: &AngstromOrder) -> Result<(), EncodingError> {
+        if order.asset_in == order.asset_out {
+            return Err(EncodingError::InvalidInput(
+                "Angstrom order must sell and buy different assets".to_string(),
+            ));
+        }
+        if order.amount == BigUint::ZERO {
+            return Err(EncodingError::InvalidInput(
+                "Angstrom order amount must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn to_u128(value: &BigUint, field: &str) -> Result<u128, EncodingError> {
+    u128::try_from(value).map_err(|_| {
+        EncodingError::InvalidInput(format!("Angstrom order {field} doesn't fit in 128 bits"))
+    })
+}
+
+fn recipient_address(recipient: &Bytes) -> Result<Address, EncodingError> {
+    if recipient.is_empty() {
+        return Ok(Address::ZERO);
+    }
+    bytes_to_address(recipient)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn angstrom_address() -> Bytes {
+        Bytes::from_str("0x0000000aa232009084Bd71A5797d089AA4Edfad4").unwrap()
+    }
+
+    fn order(validity: AngstromOrderValidity) -> AngstromOrder {
+        AngstromOrder {
+            exact_in: true,
+            amount: BigUint::from(1_000_000u64),
+            max_extra_fee_asset0: BigUint::from(10_000u64),
+            min_price: BigUint::from_str("1000000000000000000000000000").unwrap(),
+            use_internal: false,
+            asset_in: Bytes::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap(),
+            asset_out: Bytes::from_str("0xdAC17F958D2ee523a2206206994597C13D831ec7").unwrap(),
+            recipient: Bytes::new(),
+            hook_data: Bytes::new(),
+            validity,
+        }
+    }
+
+    #[test]
+    fn test_order_hash_depends_on_validity() {
+        let encoder = AngstromOrderEncoder::new(Chain::Ethereum, &angstrom_address()).unwrap();
+        let flash = order(AngstromOrderValidity::Flash { valid_for_block: 21_000_000 });
+        let standing = order(AngstromOrderValidity::Standing { nonce: 1, deadline: 1_750_000_000 });
+
+        let flash_hash = encoder.order_hash(&flash).unwrap();
+        assert_eq!(flash_hash, encoder.order_hash(&flash).unwrap());
+        assert_ne!(flash_hash, encoder.order_hash(&standing).unwrap());
+
+        let other_chain = AngstromOrderEncoder::new(Chain::Base, &angstrom_address()).unwrap();
+        assert_ne!(flash_hash, other_chain.order_hash(&flash).unwrap());
+    }
+
+    #[test]
+    fn test_sign_order() {
+        let encoder = AngstromOrderEncoder::new(Chain::Ethereum, &angstrom_address()).unwrap();
+        let signer = PrivateKeySigner::from_str(
+            "0x123456789abcdef123456789abcdef123456789abcdef123456789abcdef1234",
+        )
+        .unwrap();
+        let order = order(AngstromOrderValidity::Flash { valid_for_block: 21_000_000 });
+
+        let submission = encoder
+            .sign_order(order.clone(), &signer)
+            .unwrap();
+
+        assert!(submission.is_ecdsa);
+        assert_eq!(submission.signature.len(), 65);
+        let v = submission.signature[0];
+        assert!(v == 27 || v == 28);
+        let hash = B256::from_slice(&submission.order_hash);
+        assert_eq!(hash, encoder.order_hash(&order).unwrap());
+        let signature = alloy::primitives::Signature::from_scalars_and_parity(
+            B256::from_slice(&submission.signature[1..33]),
+            B256::from_slice(&submission.signature[33..65]),
+            v == 28,
+        );
+        assert_eq!(
+            signature
+                .recover_address_from_prehash(&hash)
+                .unwrap(),
+            signer.address()
+        );
+    }
+
+    #[test]
+    fn test_encode_contract_signature_submission() {
+        let encoder = AngstromOrderEncoder::new(Chain::Ethereum, &angstrom_address()).unwrap();
+        let wallet = Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap();
+        let signature = AngstromSignature::Contract {
+            from: wallet.clone(),
+            signature: Bytes::from(vec![0xab; 3]),
+        };
+
+        let submission = encoder
+            .encode_submission(
+                order(AngstromOrderValidity::Standing { nonce: 7, deadline: 1_750_000_000 }),
+                &signature,
+            )
+            .unwrap();
+
+        assert!(!submission.is_ecdsa);
+        assert_eq!(
+            hex::encode(&submission.signature),
+            "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2ababab"
+        );
+    }
+
+    #[test]
+    fn test_order_hash_fails_for_same_assets() {
+        let encoder = AngstromOrderEncoder::new(Chain::Ethereum, &angstrom_address()).unwrap();
+        let mut order = order(AngstromOrderValidity::Flash { valid_for_block: 21_000_000 });
+        order.asset_out = order.asset_in.clone();
+
+        assert_eq!(
+            encoder.order_hash(&order),
+            Err(EncodingError::InvalidInput(
+                "Angstrom order must sell and buy different assets".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_order_hash_fails_for_amount_overflow() {
+        let encoder = AngstromOrderEncoder::new(Chain::Ethereum, &angstrom_address()).unwrap();
+        let mut order = order(AngstromOrderValidity::Flash { valid_for_block: 21_000_000 });
+        order.amount = BigUint::from(u128::MAX) + 1u32;
+
+        assert_eq!(
+            encoder.order_hash(&order),
+            Err(EncodingError::InvalidInput(
+                "Angstrom order amount doesn't fit in 128 bits".to_string()
+            ))
+        );
+    }
+}
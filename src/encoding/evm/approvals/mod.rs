@@ -1,2 +1,3 @@
 pub mod permit2;
+pub mod permit_signer;
 pub mod protocol_approvals_manager;
@@ -15,6 +15,8 @@ use tokio::{
 };
 use tycho_common::Bytes;
 
+#[cfg(feature = "failure-injection")]
+use crate::encoding::evm::failure_injection::{inject, InjectionPoint};
 use crate::encoding::{
     errors::EncodingError,
     evm::{
@@ -58,6 +60,37 @@ sol! {
         uint48 expiration;
         uint48 nonce;
     }
+
+    #[derive(Debug)]
+    struct PermitBatch {
+        PermitDetails[] details;
+        address spender;
+        uint256 sigDeadline;
+    }
+}
+
+impl From<&PermitDetails> for models::PermitDetails {
+    fn from(sol: &PermitDetails) -> Self {
+        models::PermitDetails {
+            token: Bytes::from(sol.token.to_vec()),
+            amount: BigUint::from_bytes_be(&sol.amount.to_be_bytes::<20>()),
+            expiration: BigUint::from_bytes_be(&sol.expiration.to_be_bytes::<6>()),
+            nonce: BigUint::from_bytes_be(&sol.nonce.to_be_bytes::<6>()),
+        }
+    }
+}
+
+impl TryFrom<&models::PermitDetails> for PermitDetails {
+    type Error = EncodingError;
+
+    fn try_from(d: &models::PermitDetails) -> Result<Self, EncodingError> {
+        Ok(PermitDetails {
+            token: bytes_to_address(&d.token)?,
+            amount: U160::from(biguint_to_u256(&d.amount)),
+            expiration: U48::from(biguint_to_u256(&d.expiration)),
+            nonce: U48::from(biguint_to_u256(&d.nonce)),
+        })
+    }
 }
 
 impl TryFrom<&PermitSingle> for models::PermitSingle {
@@ -65,16 +98,7 @@ impl TryFrom<&PermitSingle> for models::PermitSingle {
 
     fn try_from(sol: &PermitSingle) -> Result<Self, EncodingError> {
         Ok(models::PermitSingle {
-            details: models::PermitDetails {
-                token: Bytes::from(sol.details.token.to_vec()),
-                amount: BigUint::from_bytes_be(&sol.details.amount.to_be_bytes::<20>()),
-                expiration: BigUint::from_bytes_be(
-                    &sol.details
-                        .expiration
-                        .to_be_bytes::<6>(),
-                ),
-                nonce: BigUint::from_bytes_be(&sol.details.nonce.to_be_bytes::<6>()),
-            },
+            details: models::PermitDetails::from(&sol.details),
             spender: Bytes::from(sol.spender.to_vec()),
             sig_deadline: BigUint::from_bytes_be(&sol.sigDeadline.to_be_bytes::<32>()),
         })
@@ -86,12 +110,37 @@ impl TryFrom<&models::PermitSingle> for PermitSingle {
 
     fn try_from(p: &models::PermitSingle) -> Result<Self, EncodingError> {
         Ok(PermitSingle {
-            details: PermitDetails {
-                token: bytes_to_address(&p.details.token)?,
-                amount: U160::from(biguint_to_u256(&p.details.amount)),
-                expiration: U48::from(biguint_to_u256(&p.details.expiration)),
-                nonce: U48::from(biguint_to_u256(&p.details.nonce)),
-            },
+            details: PermitDetails::try_from(&p.details)?,
+            spender: bytes_to_address(&p.spender)?,
+            sigDeadline: biguint_to_u256(&p.sig_deadline),
+        })
+    }
+}
+
+impl From<&PermitBatch> for models::PermitBatch {
+    fn from(sol: &PermitBatch) -> Self {
+        models::PermitBatch {
+            details: sol
+                .details
+                .iter()
+                .map(models::PermitDetails::from)
+                .collect(),
+            spender: Bytes::from(sol.spender.to_vec()),
+            sig_deadline: BigUint::from_bytes_be(&sol.sigDeadline.to_be_bytes::<32>()),
+        }
+    }
+}
+
+impl TryFrom<&models::PermitBatch> for PermitBatch {
+    type Error = EncodingError;
+
+    fn try_from(p: &models::PermitBatch) -> Result<Self, EncodingError> {
+        Ok(PermitBatch {
+            details: p
+                .details
+                .iter()
+                .map(PermitDetails::try_from)
+                .collect::<Result<Vec<_>, _>>()?,
             spender: bytes_to_address(&p.spender)?,
             sigDeadline: biguint_to_u256(&p.sig_deadline),
         })
@@ -118,6 +167,22 @@ impl Permit2 {
         spender: &Bytes,
         token: &Bytes,
     ) -> Result<Allowance, EncodingError> {
+        block_in_place(|| {
+            self.runtime_handle
+                .block_on(self.get_existing_allowance_async(owner, spender, token))
+        })
+    }
+
+    /// Fetches allowance data like `get_existing_allowance`, awaiting the call instead of blocking
+    /// on it.
+    async fn get_existing_allowance_async(
+        &self,
+        owner: &Bytes,
+        spender: &Bytes,
+        token: &Bytes,
+    ) -> Result<Allowance, EncodingError> {
+        #[cfg(feature = "failure-injection")]
+        inject(InjectionPoint::AllowanceLookup)?;
         let args = (bytes_to_address(owner)?, bytes_to_address(token)?, bytes_to_address(spender)?);
         let data = encode_input("allowance(address,address,address)", args.abi_encode());
         let tx = TransactionRequest {
@@ -126,11 +191,7 @@ impl Permit2 {
             ..Default::default()
         };
 
-        let output = block_in_place(|| {
-            self.runtime_handle
-                .block_on(async { self.client.call(tx).await })
-        });
-        match output {
+        match self.client.call(tx).await {
             Ok(response) => {
                 let allowance: Allowance = Allowance::abi_decode(&response).map_err(|_| {
                     EncodingError::FatalError(
@@ -151,13 +212,38 @@ impl Permit2 {
         owner: &Bytes,
         token: &Bytes,
         amount: &BigUint,
+    ) -> Result<models::PermitSingle, EncodingError> {
+        let (_, _, nonce) = self.get_existing_allowance(owner, spender, token)?;
+        Self::build_permit(spender, token, amount, nonce)
+    }
+
+    /// Creates permit single like [`Permit2::get_permit`], awaiting the allowance lookup instead of
+    /// blocking on it.
+    pub async fn get_permit_async(
+        &self,
+        spender: &Bytes,
+        owner: &Bytes,
+        token: &Bytes,
+        amount: &BigUint,
+    ) -> Result<models::PermitSingle, EncodingError> {
+        let (_, _, nonce) = self
+            .get_existing_allowance_async(owner, spender, token)
+            .await?;
+        Self::build_permit(spender, token, amount, nonce)
+    }
+
+    /// Builds the permit single for the given Permit2 nonce, expiring from now.
+    fn build_permit(
+        spender: &Bytes,
+        token: &Bytes,
+        amount: &BigUint,
+        nonce: U48,
     ) -> Result<models::PermitSingle, EncodingError> {
         let current_time = Utc::now()
             .naive_utc()
             .and_utc()
             .timestamp() as u64;
 
-        let (_, _, nonce) = self.get_existing_allowance(owner, spender, token)?;
         let expiration = U48::from(current_time + PERMIT_EXPIRATION);
         let sig_deadline = U256::from(current_time + PERMIT_SIG_EXPIRATION);
         let amount = U160::from(biguint_to_u256(amount));
@@ -172,6 +258,77 @@ impl Permit2 {
 
         models::PermitSingle::try_from(&permit_single)
     }
+
+    /// Creates a permit batch approving the spender for all the given tokens and amounts, with a
+    /// single signature. Each token has its own Permit2 nonce, so one allowance is fetched per
+    /// token.
+    pub fn get_permit_batch(
+        &self,
+        spender: &Bytes,
+        owner: &Bytes,
+        tokens: &[(Bytes, BigUint)],
+    ) -> Result<models::PermitBatch, EncodingError> {
+        block_in_place(|| {
+            self.runtime_handle
+                .block_on(self.get_permit_batch_async(spender, owner, tokens))
+        })
+    }
+
+    /// Creates a permit batch like [`Permit2::get_permit_batch`], awaiting the allowance lookups
+    /// instead of blocking on them.
+    pub async fn get_permit_batch_async(
+        &self,
+        spender: &Bytes,
+        owner: &Bytes,
+        tokens: &[(Bytes, BigUint)],
+    ) -> Result<models::PermitBatch, EncodingError> {
+        let mut nonces = Vec::with_capacity(tokens.len());
+        for (token, _) in tokens {
+            let (_, _, nonce) = self
+                .get_existing_allowance_async(owner, spender, token)
+                .await?;
+            nonces.push(nonce);
+        }
+        Self::build_permit_batch(spender, tokens, &nonces)
+    }
+
+    /// Builds the permit batch for the given Permit2 nonces (one per token), expiring from now.
+    fn build_permit_batch(
+        spender: &Bytes,
+        tokens: &[(Bytes, BigUint)],
+        nonces: &[U48],
+    ) -> Result<models::PermitBatch, EncodingError> {
+        if tokens.is_empty() {
+            return Err(EncodingError::InvalidInput(
+                "A permit batch must approve at least one token".to_string(),
+            ));
+        }
+        let current_time = Utc::now()
+            .naive_utc()
+            .and_utc()
+            .timestamp() as u64;
+
+        let expiration = U48::from(current_time + PERMIT_EXPIRATION);
+        let sig_deadline = U256::from(current_time + PERMIT_SIG_EXPIRATION);
+
+        let details = tokens
+            .iter()
+            .zip(nonces)
+            .map(|((token, amount), nonce)| {
+                Ok(PermitDetails {
+                    token: bytes_to_address(token)?,
+                    amount: U160::from(biguint_to_u256(amount)),
+                    expiration,
+                    nonce: *nonce,
+                })
+            })
+            .collect::<Result<Vec<_>, EncodingError>>()?;
+
+        let permit_batch =
+            PermitBatch { details, spender: bytes_to_address(spender)?, sigDeadline: sig_deadline };
+
+        Ok(models::PermitBatch::from(&permit_batch))
+    }
 }
 
 #[cfg(test)]
@@ -271,6 +428,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_permit_batch() {
+        let spender = Bytes::from_str("0xba12222222228d8ba445958a75a0704d566bf2c8").unwrap();
+        let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let tokens =
+            vec![(usdc.clone(), BigUint::from(1000u64)), (dai.clone(), BigUint::from(2000u64))];
+
+        let permit_batch =
+            Permit2::build_permit_batch(&spender, &tokens, &[U48::from(0), U48::from(2)]).unwrap();
+
+        let expiration = BigUint::from(Utc::now().timestamp() as u64 + PERMIT_EXPIRATION);
+        let expected_permit_batch = models::PermitBatch {
+            details: vec![
+                models::PermitDetails {
+                    token: usdc,
+                    amount: BigUint::from(1000u64),
+                    expiration: expiration.clone(),
+                    nonce: BigUint::from(0u64),
+                },
+                models::PermitDetails {
+                    token: dai,
+                    amount: BigUint::from(2000u64),
+                    expiration,
+                    nonce: BigUint::from(2u64),
+                },
+            ],
+            spender,
+            sig_deadline: BigUint::from(Utc::now().timestamp() as u64 + PERMIT_SIG_EXPIRATION),
+        };
+        assert_eq!(permit_batch, expected_permit_batch);
+        assert_eq!(permit_batch.details[0].expiration, permit_batch.details[1].expiration);
+        let sol_permit_batch = PermitBatch::try_from(&permit_batch).unwrap();
+        assert_eq!(models::PermitBatch::from(&sol_permit_batch), permit_batch);
+    }
+
+    #[test]
+    fn test_build_permit_batch_without_tokens() {
+        let spender = Bytes::from_str("0xba12222222228d8ba445958a75a0704d566bf2c8").unwrap();
+
+        let result = Permit2::build_permit_batch(&spender, &[], &[]);
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
     /// This test actually calls the permit method on the Permit2 contract to verify the encoded
     /// data works. It requires an Anvil fork, so please run with the following command: anvil
     /// --fork-url <RPC-URL> And set up the following env var as RPC_URL=127.0.0.1:8545
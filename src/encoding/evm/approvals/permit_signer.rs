@@ -0,0 +1,330 @@
+use std::sync::Arc;
+
+use alloy::{
+    primitives::{Bytes as AlloyBytes, FixedBytes, TxKind},
+    providers::Provider,
+    rpc::types::{TransactionInput, TransactionRequest},
+    signers::local::PrivateKeySigner,
+    sol_types::SolValue,
+};
+use tokio::task::block_in_place;
+use tycho_common::Bytes;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        encoding_utils::{encode_input, permit_signing_hash, sign_permit, sign_permit_batch},
+        utils::{bytes_to_address, get_client, get_runtime},
+    },
+    models::{PermitBatch, PermitSingle},
+};
+
+/// Value returned by `isValidSignature` for a valid ERC-1271 signature.
+const ERC1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// Produces the signature that Permit2 verifies for a permit, i.e. a signature of its EIP-712
+/// hash (see `permit_signing_hash`).
+///
+/// Permit2 recovers the signer of 65-byte (or 64-byte compact) signatures when the owner of the
+/// tokens is an EOA, and calls `isValidSignature(hash, signature)` on the owner when it's a smart
+/// contract wallet (ERC-1271). In the latter case, the signature bytes are only interpreted by the
+/// wallet.
+pub trait PermitSigner: Send + Sync {
+    fn sign_permit(
+        &self,
+        chain_id: u64,
+        permit_single: &PermitSingle,
+    ) -> Result<Vec<u8>, EncodingError>;
+
+    /// Produces the signature of a batch permit (see `permit_batch_signing_hash`). Signers that
+    /// only support single permits don't need to implement it.
+    fn sign_permit_batch(
+        &self,
+        _chain_id: u64,
+        _permit_batch: &PermitBatch,
+    ) -> Result<Vec<u8>, EncodingError> {
+        Err(EncodingError::FatalError(
+            "The permit signer doesn't support batch permits".to_string(),
+        ))
+    }
+}
+
+impl PermitSigner for PrivateKeySigner {
+    fn sign_permit(
+        &self,
+        chain_id: u64,
+        permit_single: &PermitSingle,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let signature = sign_permit(chain_id, permit_single, self.clone())?;
+        Ok(signature.as_bytes().to_vec())
+    }
+
+    fn sign_permit_batch(
+        &self,
+        chain_id: u64,
+        permit_batch: &PermitBatch,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let signature = sign_permit_batch(chain_id, permit_batch, self.clone())?;
+        Ok(signature.as_bytes().to_vec())
+    }
+}
+
+/// A signature produced outside of this crate (e.g. by a KMS or a custody provider) from the
+/// permit's EIP-712 typed data (see `TychoRouterEncoder::permit_typed_data`), so no private key
+/// needs to be given to the encoder.
+///
+/// The signature is returned as is for any permit: it must be the signature of the permit of the
+/// encoded solution it's assembled with (see `TychoRouterEncoder::encode_calldata_with_signature`).
+#[derive(Clone, Debug)]
+pub struct DetachedSignature {
+    signature: Vec<u8>,
+}
+
+impl DetachedSignature {
+    pub fn new(signature: Vec<u8>) -> Self {
+        Self { signature }
+    }
+}
+
+impl PermitSigner for DetachedSignature {
+    fn sign_permit(
+        &self,
+        _chain_id: u64,
+        _permit_single: &PermitSingle,
+    ) -> Result<Vec<u8>, EncodingError> {
+        Ok(self.signature.clone())
+    }
+
+    fn sign_permit_batch(
+        &self,
+        _chain_id: u64,
+        _permit_batch: &PermitBatch,
+    ) -> Result<Vec<u8>, EncodingError> {
+        Ok(self.signature.clone())
+    }
+}
+
+/// Signs permits on behalf of a smart contract wallet, for solutions whose sender is the wallet.
+///
+/// The signature of the permit hash is delegated to `signer` (e.g. an owner key of the wallet)
+/// and passed as is to the wallet's `isValidSignature`. Wallets that expect another format (e.g.
+/// a hash wrapped in their own EIP-712 domain, or several owner signatures) need their own
+/// [`PermitSigner`].
+///
+/// # Fields
+/// * `wallet`: Address of the smart contract wallet, i.e. the sender of the solutions
+/// * `signer`: Signer producing the signature the wallet validates
+#[derive(Clone)]
+pub struct Erc1271Signer {
+    wallet: Bytes,
+    signer: Arc<dyn PermitSigner>,
+}
+
+impl Erc1271Signer {
+    pub fn new(wallet: Bytes, signer: Arc<dyn PermitSigner>) -> Self {
+        Self { wallet, signer }
+    }
+
+    pub fn wallet(&self) -> &Bytes {
+        &self.wallet
+    }
+
+    /// Checks that the wallet accepts the signature of the permit, by calling its
+    /// `isValidSignature(bytes32,bytes)` like Permit2 does. Wallets may also revert on invalid
+    /// signatures, which is returned as an error. Requires the `RPC_URL` environment variable to
+    /// be set.
+    pub fn verify(
+        &self,
+        chain_id: u64,
+        permit_single: &PermitSingle,
+        signature: &[u8],
+    ) -> Result<bool, EncodingError> {
+        let hash = permit_signing_hash(chain_id, permit_single)?;
+        let args = (hash, AlloyBytes::from(signature.to_vec()));
+        let data = encode_input("isValidSignature(bytes32,bytes)", args.abi_encode());
+        let tx = TransactionRequest {
+            to: Some(TxKind::from(bytes_to_address(&self.wallet)?)),
+            input: TransactionInput { input: Some(AlloyBytes::from(data)), data: None },
+            ..Default::default()
+        };
+
+        let (handle, _runtime) = get_runtime()?;
+        let client = block_in_place(|| handle.block_on(get_client()))?;
+        let output = block_in_place(|| handle.block_on(async { client.call(tx).await }));
+        match output {
+            Ok(response) => {
+                let magic_value = FixedBytes::<4>::abi_decode(&response).map_err(|_| {
+                    EncodingError::FatalError(
+                        "Failed to decode response for isValidSignature".to_string(),
+                    )
+                })?;
+                Ok(magic_value.0 == ERC1271_MAGIC_VALUE)
+            }
+            Err(err) => Err(EncodingError::RecoverableError(format!(
+                "Call to isValidSignature failed with error: {err}"
+            ))),
+        }
+    }
+}
+
+impl PermitSigner for Erc1271Signer {
+    fn sign_permit(
+        &self,
+        chain_id: u64,
+        permit_single: &PermitSingle,
+    ) -> Result<Vec<u8>, EncodingError> {
+        self.signer
+            .sign_permit(chain_id, permit_single)
+    }
+
+    fn sign_permit_batch(
+        &self,
+        chain_id: u64,
+        permit_batch: &PermitBatch,
+    ) -> Result<Vec<u8>, EncodingError> {
+        self.signer
+            .sign_permit_batch(chain_id, permit_batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use alloy::{
+        dyn_abi::TypedData,
+        primitives::{Signature, B256},
+    };
+    use num_bigint::BigUint;
+
+    use super::*;
+    use crate::encoding::{
+        evm::encoding_utils::{
+            permit_batch_signing_hash, permit_batch_typed_data, permit_typed_data,
+        },
+        models::PermitDetails,
+    };
+
+    fn owner() -> PrivateKeySigner {
+        PrivateKeySigner::from_bytes(
+            &B256::from_str("0x123456789abcdef123456789abcdef123456789abcdef123456789abcdef1234")
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn permit() -> PermitSingle {
+        PermitSingle {
+            details: PermitDetails {
+                token: Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+                amount: BigUint::from(1000u32),
+                expiration: BigUint::from(1_800_000_000u64),
+                nonce: BigUint::ZERO,
+            },
+            spender: Bytes::from("0x3Ede3eCa2a72B3aeCC820E955B36f38437D01395"),
+            sig_deadline: BigUint::from(1_800_000_000u64),
+        }
+    }
+
+    #[test]
+    fn test_private_key_signer_signs_permit() {
+        let signature = owner()
+            .sign_permit(1, &permit())
+            .unwrap();
+
+        assert_eq!(signature.len(), 65);
+    }
+
+    #[test]
+    fn test_erc1271_signer_delegates_to_wallet_signer() {
+        let wallet = Bytes::from("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2");
+        let erc1271_signer = Erc1271Signer::new(wallet.clone(), Arc::new(owner()));
+
+        let signature = erc1271_signer
+            .sign_permit(1, &permit())
+            .unwrap();
+
+        assert_eq!(
+            signature,
+            owner()
+                .sign_permit(1, &permit())
+                .unwrap()
+        );
+        assert_eq!(*erc1271_signer.wallet(), wallet);
+    }
+
+    #[test]
+    fn test_private_key_signer_signs_permit_batch() {
+        let permit_batch = PermitBatch {
+            details: vec![
+                permit().details,
+                PermitDetails {
+                    token: Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+                    amount: BigUint::from(2000u32),
+                    expiration: BigUint::from(1_800_000_000u64),
+                    nonce: BigUint::from(3u32),
+                },
+            ],
+            spender: permit().spender,
+            sig_deadline: BigUint::from(1_800_000_000u64),
+        };
+
+        let signature = owner()
+            .sign_permit_batch(1, &permit_batch)
+            .unwrap();
+
+        let hash = permit_batch_signing_hash(1, &permit_batch).unwrap();
+        let recovered = Signature::try_from(signature.as_slice())
+            .unwrap()
+            .recover_address_from_prehash(&hash)
+            .unwrap();
+        assert_eq!(recovered, owner().address());
+    }
+
+    #[test]
+    fn test_permit_typed_data_hashes_to_the_signing_hash() {
+        let typed_data: TypedData =
+            serde_json::from_value(permit_typed_data(1, &permit()).unwrap()).unwrap();
+
+        assert_eq!(typed_data.primary_type, "PermitSingle");
+        assert_eq!(
+            typed_data
+                .eip712_signing_hash()
+                .unwrap(),
+            permit_signing_hash(1, &permit()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_permit_batch_typed_data_hashes_to_the_signing_hash() {
+        let permit_batch = PermitBatch {
+            details: vec![permit().details, permit().details],
+            spender: permit().spender,
+            sig_deadline: BigUint::from(1_800_000_000u64),
+        };
+        let typed_data: TypedData =
+            serde_json::from_value(permit_batch_typed_data(8453, &permit_batch).unwrap()).unwrap();
+
+        assert_eq!(
+            typed_data
+                .eip712_signing_hash()
+                .unwrap(),
+            permit_batch_signing_hash(8453, &permit_batch).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_detached_signature() {
+        let signature = owner()
+            .sign_permit(1, &permit())
+            .unwrap();
+        let detached_signature = DetachedSignature::new(signature.clone());
+
+        assert_eq!(
+            detached_signature
+                .sign_permit(1, &permit())
+                .unwrap(),
+            signature
+        );
+    }
+}
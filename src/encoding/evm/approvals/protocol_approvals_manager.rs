@@ -11,12 +11,15 @@ use tokio::{
     task::block_in_place,
 };
 
+#[cfg(feature = "failure-injection")]
+use crate::encoding::evm::failure_injection::{inject, InjectionPoint};
 use crate::encoding::{
     errors::EncodingError,
     evm::{
         encoding_utils::encode_input,
         utils::{get_client, get_runtime, EVMProvider},
     },
+    metrics::{measure_async, EncodingStage},
 };
 
 /// A manager for checking if an approval is needed for interacting with a certain spender.
@@ -33,6 +36,12 @@ impl ProtocolApprovalsManager {
         Ok(Self { client, runtime_handle: handle, runtime })
     }
 
+    /// Creates a manager from an async context, without blocking on the client creation.
+    pub async fn new_async() -> Result<Self, EncodingError> {
+        let client = get_client().await?;
+        Ok(Self { client, runtime_handle: Handle::current(), runtime: None })
+    }
+
     /// Checks the current allowance for the given token, owner, and spender, and returns true
     /// if the current allowance is zero.
     pub fn approval_needed(
@@ -41,34 +50,51 @@ impl ProtocolApprovalsManager {
         owner_address: Address,
         spender_address: Address,
     ) -> Result<bool, EncodingError> {
-        let args = (owner_address, spender_address);
-        let data = encode_input("allowance(address,address)", args.abi_encode());
-        let tx = TransactionRequest {
-            to: Some(TxKind::from(token)),
-            input: TransactionInput { input: Some(Bytes::from(data)), data: None },
-            ..Default::default()
-        };
-
-        let output = block_in_place(|| {
+        block_in_place(|| {
             self.runtime_handle
-                .block_on(async { self.client.call(tx).await })
-        });
-        match output {
-            Ok(response) => {
-                let allowance: U256 = U256::abi_decode(&response).map_err(|_| {
-                    EncodingError::FatalError("Failed to decode response for allowance".to_string())
-                })?;
+                .block_on(self.approval_needed_async(token, owner_address, spender_address))
+        })
+    }
 
-                if allowance < U256::MAX / U256::from(2) {
-                    return Ok(true)
-                }
+    /// Checks if an approval is needed like [`ProtocolApprovalsManager::approval_needed`],
+    /// awaiting the allowance call instead of blocking on it.
+    pub async fn approval_needed_async(
+        &self,
+        token: Address,
+        owner_address: Address,
+        spender_address: Address,
+    ) -> Result<bool, EncodingError> {
+        measure_async(EncodingStage::ApprovalCheck, Some(token.to_string()), async {
+            #[cfg(feature = "failure-injection")]
+            inject(InjectionPoint::AllowanceLookup)?;
+            let args = (owner_address, spender_address);
+            let data = encode_input("allowance(address,address)", args.abi_encode());
+            let tx = TransactionRequest {
+                to: Some(TxKind::from(token)),
+                input: TransactionInput { input: Some(Bytes::from(data)), data: None },
+                ..Default::default()
+            };
 
-                Ok(false)
+            match self.client.call(tx).await {
+                Ok(response) => {
+                    let allowance: U256 = U256::abi_decode(&response).map_err(|_| {
+                        EncodingError::FatalError(
+                            "Failed to decode response for allowance".to_string(),
+                        )
+                    })?;
+
+                    if allowance < U256::MAX / U256::from(2) {
+                        return Ok(true)
+                    }
+
+                    Ok(false)
+                }
+                Err(err) => Err(EncodingError::RecoverableError(format!(
+                    "Allowance call failed with error: {err}"
+                ))),
             }
-            Err(err) => Err(EncodingError::RecoverableError(format!(
-                "Allowance call failed with error: {err}"
-            ))),
-        }
+        })
+        .await
     }
 }
 
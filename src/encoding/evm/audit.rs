@@ -0,0 +1,312 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use alloy::primitives::keccak256;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tycho_common::Bytes;
+
+use crate::encoding::{
+    errors::EncodingError,
+    models::{EncodedSolution, Solution},
+};
+
+/// A tamper-evident record of one encoded solution.
+///
+/// Records form a hash chain: every `content_hash` commits to the `previous_hash` of the record
+/// before it, so removing, reordering or editing a record breaks the chain (see [`verify_chain`]).
+///
+/// # Fields
+/// * `sequence`: Position of the record in the chain, starting at 0.
+/// * `timestamp`: Unix timestamp (in milliseconds) at which the solution was encoded.
+/// * `inputs_fingerprint`: Keccak256 hash of the canonical JSON representation of the `Solution`.
+/// * `swaps_hash`: Keccak256 hash of the encoded swaps. The router call isn't assembled yet when
+///   the solution is recorded, so its full calldata isn't hashed.
+/// * `interacting_with`: Address of the contract to be called.
+/// * `function_signature`: The signature of the function to be called.
+/// * `previous_hash`: Content hash of the previous record (32 zero bytes for the first record).
+/// * `content_hash`: Keccak256 hash over all the other fields of this record.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub sequence: u64,
+    pub timestamp: i64,
+    pub inputs_fingerprint: Bytes,
+    pub swaps_hash: Bytes,
+    pub interacting_with: Bytes,
+    pub function_signature: String,
+    pub previous_hash: Bytes,
+    pub content_hash: Bytes,
+}
+
+impl AuditRecord {
+    /// Computes the content hash of the record from all its other fields.
+    pub fn compute_content_hash(&self) -> Bytes {
+        let mut preimage = Vec::new();
+        preimage.extend(self.previous_hash.to_vec());
+        preimage.extend(self.sequence.to_be_bytes());
+        preimage.extend(self.timestamp.to_be_bytes());
+        preimage.extend(self.inputs_fingerprint.to_vec());
+        preimage.extend(self.swaps_hash.to_vec());
+        preimage.extend(self.interacting_with.to_vec());
+        preimage.extend(self.function_signature.as_bytes());
+        Bytes::from(keccak256(preimage).to_vec())
+    }
+}
+
+/// A destination for audit records.
+pub trait AuditSink: Send + Sync {
+    /// Persists a single audit record. Returning an error makes the encoding fail, so that no
+    /// solution is ever handed out without being recorded.
+    fn write_record(&self, record: &AuditRecord) -> Result<(), EncodingError>;
+}
+
+/// Writes each audit record as one JSON line into the given writer (a file, stdout, etc).
+pub struct JsonLinesSink<W: Write + Send> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write + Send> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+}
+
+impl<W: Write + Send> AuditSink for JsonLinesSink<W> {
+    fn write_record(&self, record: &AuditRecord) -> Result<(), EncodingError> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| EncodingError::FatalError("Audit log writer is poisoned".to_string()))?;
+        let line = serde_json::to_string(record)?;
+        writeln!(writer, "{line}")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Forwards each audit record to a user-provided callback.
+pub struct CallbackSink<F: Fn(&AuditRecord) + Send + Sync> {
+    callback: F,
+}
+
+impl<F: Fn(&AuditRecord) + Send + Sync> CallbackSink<F> {
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<F: Fn(&AuditRecord) + Send + Sync> AuditSink for CallbackSink<F> {
+    fn write_record(&self, record: &AuditRecord) -> Result<(), EncodingError> {
+        (self.callback)(record);
+        Ok(())
+    }
+}
+
+/// Audit log that records every solution encoded by a `TychoRouterEncoder`.
+///
+/// Cloning the audit log shares the underlying sink and hash chain, so clones of the same encoder
+/// keep appending to the same chain.
+#[derive(Clone)]
+pub struct AuditLog {
+    sink: Arc<dyn AuditSink>,
+    /// The sequence number and content hash of the last written record.
+    last_record: Arc<Mutex<Option<(u64, Bytes)>>>,
+}
+
+impl AuditLog {
+    pub fn new(sink: Arc<dyn AuditSink>) -> Self {
+        Self { sink, last_record: Arc::new(Mutex::new(None)) }
+    }
+
+    /// Builds the next record of the chain for the given solution and writes it to the sink.
+    pub fn record(
+        &self,
+        solution: &Solution,
+        encoded_solution: &EncodedSolution,
+    ) -> Result<AuditRecord, EncodingError> {
+        let mut last_record = self
+            .last_record
+            .lock()
+            .map_err(|_| EncodingError::FatalError("Audit log state is poisoned".to_string()))?;
+        let (sequence, previous_hash) = match last_record.as_ref() {
+            Some((sequence, hash)) => (sequence + 1, hash.clone()),
+            None => (0, Bytes::zero(32)),
+        };
+
+        let mut record = AuditRecord {
+            sequence,
+            timestamp: Utc::now().timestamp_millis(),
+            inputs_fingerprint: solution_fingerprint(solution)?,
+            swaps_hash: Bytes::from(keccak256(&encoded_solution.swaps).to_vec()),
+            interacting_with: encoded_solution
+                .interacting_with
+                .clone(),
+            function_signature: encoded_solution
+                .function_signature
+                .clone(),
+            previous_hash,
+            content_hash: Bytes::new(),
+        };
+        record.content_hash = record.compute_content_hash();
+
+        self.sink.write_record(&record)?;
+        *last_record = Some((record.sequence, record.content_hash.clone()));
+        Ok(record)
+    }
+}
+
+/// Computes a fingerprint of the given solution.
+///
+/// The solution is first converted into a JSON value, whose maps are ordered by key, so the
+/// fingerprint doesn't depend on the iteration order of hash maps (like static attributes).
+/// Protocol states are not part of the fingerprint, since they are not serializable.
+pub fn solution_fingerprint(solution: &Solution) -> Result<Bytes, EncodingError> {
+    let canonical_json = serde_json::to_vec(&serde_json::to_value(solution)?)?;
+    Ok(Bytes::from(keccak256(canonical_json).to_vec()))
+}
+
+/// Verifies that the given records form an untampered hash chain.
+///
+/// Returns an `EncodingError::InvalidInput` pointing to the first record that doesn't match.
+pub fn verify_chain(records: &[AuditRecord]) -> Result<(), EncodingError> {
+    let mut previous: Option<&AuditRecord> = None;
+    for record in records {
+        if let Some(previous) = previous {
+            if record.sequence != previous.sequence + 1 ||
+                record.previous_hash != previous.content_hash
+            {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Audit record {} is not linked to the previous record",
+                    record.sequence
+                )));
+            }
+        }
+        if record.compute_content_hash() != record.content_hash {
+            return Err(EncodingError::InvalidInput(format!(
+                "Content hash of audit record {} does not match its fields",
+                record.sequence
+            )));
+        }
+        previous = Some(record);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use num_bigint::BigUint;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::Swap;
+
+    fn solution() -> Solution {
+        Solution {
+            given_token: Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            given_amount: BigUint::from(1000u32),
+            checked_token: Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+            checked_amount: BigUint::from(900u32),
+            swaps: vec![Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+                Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+            )],
+            ..Default::default()
+        }
+    }
+
+    fn encoded_solution(swaps: Vec<u8>) -> EncodedSolution {
+        EncodedSolution {
+            swaps,
+            interacting_with: Bytes::from("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66"),
+            function_signature:
+                "singleSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)"
+                    .to_string(),
+            n_tokens: 0,
+            permit: None,
+            permit_batch: None,
+            native_action: None,
+            state_versions: vec![],
+            callback_allowlist: None,
+            summary: Default::default(),
+            gas_estimate: 0,
+            debug_info: None,
+            degraded_groups: vec![],
+        }
+    }
+
+    #[test]
+    fn test_audit_log_builds_verifiable_chain() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let records_clone = records.clone();
+        let audit_log = AuditLog::new(Arc::new(CallbackSink::new(move |record: &AuditRecord| {
+            records_clone
+                .lock()
+                .unwrap()
+                .push(record.clone())
+        })));
+
+        audit_log
+            .record(&solution(), &encoded_solution(vec![1, 2, 3]))
+            .unwrap();
+        audit_log
+            .record(&solution(), &encoded_solution(vec![4, 5, 6]))
+            .unwrap();
+
+        let records = records.lock().unwrap().clone();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].previous_hash, Bytes::zero(32));
+        assert_eq!(records[1].previous_hash, records[0].content_hash);
+        assert_eq!(records[0].inputs_fingerprint, records[1].inputs_fingerprint);
+        assert!(verify_chain(&records).is_ok());
+    }
+
+    #[test]
+    fn test_solution_fingerprint_is_stable() {
+        // The fingerprint mustn't change across releases, or recorded fingerprints can't be
+        // matched anymore
+        let mut static_attributes = HashMap::new();
+        static_attributes.insert("fee".to_string(), Bytes::from("0x1e"));
+        static_attributes.insert("tick_spacing".to_string(), Bytes::from("0x3c"));
+        static_attributes.insert("supports_fot".to_string(), Bytes::from("0x00"));
+        let mut solution = solution();
+        solution.swaps = vec![Swap::new(
+            ProtocolComponent { static_attributes, ..solution.swaps[0].component().clone() },
+            solution.given_token.clone(),
+            solution.checked_token.clone(),
+        )];
+
+        assert_eq!(
+            solution_fingerprint(&solution).unwrap(),
+            Bytes::from("0x2af250d97f3a0214c31ba8e2c7bc8889d13e7354438b8b18874e59e66462fc49")
+        );
+    }
+
+    #[test]
+    fn test_verify_chain_detects_tampering() {
+        let audit_log = AuditLog::new(Arc::new(JsonLinesSink::new(Vec::new())));
+        let first = audit_log
+            .record(&solution(), &encoded_solution(vec![1, 2, 3]))
+            .unwrap();
+        let second = audit_log
+            .record(&solution(), &encoded_solution(vec![4, 5, 6]))
+            .unwrap();
+
+        let mut tampered = second.clone();
+        tampered.swaps_hash = Bytes::from(keccak256([7u8, 8, 9]).to_vec());
+        assert!(verify_chain(&[first.clone(), tampered]).is_err());
+        // A missing record breaks the chain as well
+        let third = audit_log
+            .record(&solution(), &encoded_solution(vec![7, 8, 9]))
+            .unwrap();
+        assert!(verify_chain(&[first, third]).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{collections::HashMap, str::FromStr};
+
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        constants::{PROTOCOL_SPECIFIC_CONFIG, SETTLEMENT_ADDRESS_KEYS},
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+    },
+    models::Solution,
+};
+
+/// Collects the contracts allowed to call back into the router while a solution is executed.
+///
+/// The allowlist of a solution contains the executors of all its swaps and the settlement
+/// contracts these protocols interact with (e.g. the Bebop settlement contract or the Balancer V2
+/// vault).
+///
+/// The allowlist is advisory: it is neither encoded in the router calldata nor enforced by the
+/// TychoRouter, which accepts callbacks from any contract. It is meant for off-chain checks, e.g.
+/// comparing it with the contracts that called back into the router in a simulated trace.
+///
+/// # Fields
+/// * `executors`: Executor address of every registered protocol, by protocol system
+/// * `settlements`: Settlement contract addresses of every protocol that uses them, by protocol
+///   system
+#[derive(Clone, Debug)]
+pub struct CallbackAllowlist {
+    executors: HashMap<String, Bytes>,
+    settlements: HashMap<String, Vec<Bytes>>,
+}
+
+impl CallbackAllowlist {
+    /// Builds the allowlist from the executors of the registry and the settlement contracts
+    /// configured for the chain.
+    pub fn new(
+        chain: Chain,
+        swap_encoder_registry: &SwapEncoderRegistry,
+    ) -> Result<Self, EncodingError> {
+        let protocol_specific_config: HashMap<Chain, HashMap<String, HashMap<String, String>>> =
+            serde_json::from_str(PROTOCOL_SPECIFIC_CONFIG)?;
+        let mut settlements = HashMap::new();
+        if let Some(chain_config) = protocol_specific_config.get(&chain) {
+            for (protocol, keys) in SETTLEMENT_ADDRESS_KEYS.iter() {
+                let Some(protocol_config) = chain_config.get(*protocol) else {
+                    continue;
+                };
+                let addresses = keys
+                    .iter()
+                    .filter_map(|key| protocol_config.get(*key))
+                    .map(|address| {
+                        Bytes::from_str(address).map_err(|_| {
+                            EncodingError::FatalError(format!(
+                                "Invalid settlement address {address} for {protocol}"
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                settlements.insert(protocol.to_string(), addresses);
+            }
+        }
+        Ok(Self { executors: swap_encoder_registry.executor_addresses(), settlements })
+    }
+
+    /// Returns the callback targets of the solution, without duplicates and in the order of the
+    /// swaps.
+    pub fn targets(&self, solution: &Solution) -> Result<Vec<Bytes>, EncodingError> {
+        let mut targets: Vec<Bytes> = Vec::new();
+        for swap in &solution.swaps {
+            let protocol_system = &swap.component().protocol_system;
+            let executor = self
+                .executors
+                .get(protocol_system)
+                .ok_or_else(|| {
+                    EncodingError::InvalidInput(format!(
+                        "No executor found for protocol {protocol_system}"
+                    ))
+                })?;
+            let settlements = self
+                .settlements
+                .get(protocol_system)
+                .into_iter()
+                .flatten();
+            for target in std::iter::once(executor).chain(settlements) {
+                if !targets.contains(target) {
+                    targets.push(target.clone());
+                }
+            }
+        }
+        Ok(targets)
+    }
+}
+
+/// Packs the callback targets into their 20-byte addresses concatenated, e.g. to pass them to a
+/// contract of the integrator. The TychoRouter doesn't take the allowlist.
+pub fn encode_callback_allowlist(targets: &[Bytes]) -> Vec<u8> {
+    targets
+        .iter()
+        .flat_map(|target| target.to_vec())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::Swap;
+
+    fn swap(protocol_system: &str) -> Swap {
+        Swap::new(
+            ProtocolComponent {
+                id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                protocol_system: protocol_system.to_string(),
+                ..Default::default()
+            },
+            Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+        )
+    }
+
+    #[test]
+    fn test_callback_allowlist_targets() {
+        let executors = r#"{"ethereum": {"uniswap_v2": "0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f", "vm:balancer_v2": "0x2e234DAe75C793f67A35089C9d99245E1C58470b"}}"#;
+        let registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors.to_string()))
+            .unwrap();
+        let allowlist = CallbackAllowlist::new(Chain::Ethereum, &registry).unwrap();
+        let solution = Solution {
+            swaps: vec![swap("uniswap_v2"), swap("vm:balancer_v2"), swap("uniswap_v2")],
+            ..Default::default()
+        };
+
+        let targets = allowlist.targets(&solution).unwrap();
+
+        assert_eq!(
+            targets,
+            vec![
+                Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+                Bytes::from("0x2e234DAe75C793f67A35089C9d99245E1C58470b"),
+                // Balancer V2 vault
+                Bytes::from("0xba12222222228d8ba445958a75a0704d566bf2c8"),
+            ]
+        );
+        assert_eq!(encode_callback_allowlist(&targets).len(), 60);
+
+        let solution = Solution { swaps: vec![swap("uniswap_v3")], ..Default::default() };
+        assert!(matches!(allowlist.targets(&solution), Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,202 @@
+use tycho_common::models::Chain;
+
+use crate::encoding::{
+    errors::{CalldataSizeReport, EncodingError},
+    evm::constants::CALLDATA_SIZE_BUDGETS,
+    models::EncodedSolution,
+};
+
+/// Share of the swap bytes above which a single encoded swap is reported as the main contributor.
+const DOMINANT_SWAP_SHARE_PERCENT: usize = 50;
+
+/// Maximum size of the calldata of a solution.
+///
+/// Calldata above the practical limit of a chain is only rejected when the transaction is
+/// submitted, usually with an error that doesn't tell which part of the solution is too large.
+/// Checking the calldata right after encoding lets solvers adapt their solutions instead, using
+/// the breakdown of the returned `EncodingError::CalldataTooLarge`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalldataBudget {
+    max_bytes: usize,
+}
+
+impl CalldataBudget {
+    pub fn new(max_bytes: usize) -> Self {
+        Self { max_bytes }
+    }
+
+    /// Returns the default budget of the chain (see `CALLDATA_SIZE_BUDGETS`), if any.
+    pub fn for_chain(chain: Chain) -> Option<Self> {
+        CALLDATA_SIZE_BUDGETS
+            .get(&chain)
+            .map(|max_bytes| Self::new(*max_bytes))
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Raises an `EncodingError::CalldataTooLarge` if the calldata of the encoded solution is
+    /// larger than the budget.
+    ///
+    /// Solvers building the router call themselves from `TychoEncoder::encode_solutions` can call
+    /// this with the size of their calldata.
+    pub fn check(
+        &self,
+        encoded_solution: &EncodedSolution,
+        calldata_bytes: usize,
+    ) -> Result<(), EncodingError> {
+        if calldata_bytes <= self.max_bytes {
+            return Ok(());
+        }
+        let swap_bytes = swap_bytes(encoded_solution);
+        let suggestions = suggestions(encoded_solution, &swap_bytes);
+        Err(EncodingError::CalldataTooLarge(Box::new(CalldataSizeReport {
+            calldata_bytes,
+            max_bytes: self.max_bytes,
+            swap_bytes,
+            suggestions,
+        })))
+    }
+}
+
+/// Returns the size of every encoded swap. Sequential and split swaps are prefix-length encoded
+/// (see `ple_encode`), and the prefixes are counted with their swap. The executor table of indexed
+/// swaps is shared by all the swaps, so it isn't counted.
+fn swap_bytes(encoded_solution: &EncodedSolution) -> Vec<usize> {
+    let swaps = &encoded_solution.swaps;
+    let signature = &encoded_solution.function_signature;
+    if signature.starts_with("single") || signature.starts_with("exactOutSingle") {
+        return vec![swaps.len()];
+    }
+    let mut sizes = Vec::new();
+    let mut offset = match swaps.first() {
+        Some(n_executors) if signature.contains("Indexed(") => 1 + 20 * *n_executors as usize,
+        _ => 0,
+    };
+    while offset + 2 <= swaps.len() {
+        let length = u16::from_be_bytes([swaps[offset], swaps[offset + 1]]) as usize;
+        let size = (2 + length).min(swaps.len() - offset);
+        sizes.push(size);
+        offset += size;
+    }
+    sizes
+}
+
+fn suggestions(encoded_solution: &EncodedSolution, swap_bytes: &[usize]) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    let total: usize = swap_bytes.iter().sum();
+    if swap_bytes.len() > 1 {
+        if encoded_solution
+            .function_signature
+            .starts_with("split")
+        {
+            suggestions.push(format!(
+                "Reduce the number of splits: the {} swaps take {total} bytes",
+                swap_bytes.len()
+            ));
+        } else {
+            suggestions.push(format!(
+                "Use a route with fewer hops: the {} swaps take {total} bytes",
+                swap_bytes.len()
+            ));
+        }
+        if let Some((index, size)) = swap_bytes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, size)| **size)
+        {
+            if size * 100 > total * DOMINANT_SWAP_SHARE_PERCENT {
+                suggestions.push(format!(
+                    "Replace swap {index}, which alone takes {size} bytes (e.g. with a pool \
+                     instead of an RFQ quote)"
+                ));
+            }
+        }
+    }
+    if encoded_solution.permit.is_some() {
+        suggestions
+            .push("Use a standard approval instead of Permit2 to drop the permit".to_string());
+    }
+    suggestions.push("Split the trade into several transactions".to_string());
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use tycho_common::Bytes;
+
+    use super::*;
+    use crate::encoding::evm::utils::ple_encode;
+
+    fn encoded_solution(function_signature: &str, swaps: Vec<u8>) -> EncodedSolution {
+        EncodedSolution {
+            swaps,
+            interacting_with: Bytes::zero(20),
+            function_signature: function_signature.to_string(),
+            n_tokens: 0,
+            permit: None,
+            permit_batch: None,
+            native_action: None,
+            state_versions: vec![],
+            callback_allowlist: None,
+            summary: Default::default(),
+            gas_estimate: 0,
+            debug_info: None,
+            degraded_groups: vec![],
+        }
+    }
+
+    #[test]
+    fn test_check_calldata_budget() {
+        let budget = CalldataBudget::new(1_000);
+        let swaps = ple_encode(vec![vec![0; 100], vec![0; 700], vec![0; 50]]).unwrap();
+        let encoded_solution = encoded_solution("splitSwap(uint256,address)", swaps);
+
+        assert!(budget
+            .check(&encoded_solution, 1_000)
+            .is_ok());
+        let Err(EncodingError::CalldataTooLarge(report)) = budget.check(&encoded_solution, 1_200)
+        else {
+            panic!("Expected the calldata to be too large");
+        };
+        assert_eq!(report.calldata_bytes, 1_200);
+        assert_eq!(report.max_bytes, 1_000);
+        assert_eq!(report.swap_bytes, vec![102, 702, 52]);
+        assert!(report.suggestions[0].starts_with("Reduce the number of splits"));
+        assert!(report.suggestions[1].starts_with("Replace swap 1"));
+    }
+
+    #[test]
+    fn test_check_calldata_budget_indexed_swaps() {
+        let budget = CalldataBudget::new(100);
+        let mut swaps = vec![1];
+        swaps.extend([0xaa; 20]);
+        swaps.extend(ple_encode(vec![vec![0; 30], vec![0; 40]]).unwrap());
+        let encoded_solution = encoded_solution("sequentialSwapIndexed(uint256,address)", swaps);
+
+        let Err(EncodingError::CalldataTooLarge(report)) = budget.check(&encoded_solution, 400)
+        else {
+            panic!("Expected the calldata to be too large");
+        };
+        assert_eq!(report.swap_bytes, vec![32, 42]);
+    }
+
+    #[test]
+    fn test_check_calldata_budget_single_swap() {
+        let budget = CalldataBudget::new(100);
+        let encoded_solution = encoded_solution("singleSwap(uint256,address)", vec![0; 150]);
+
+        let Err(EncodingError::CalldataTooLarge(report)) = budget.check(&encoded_solution, 400)
+        else {
+            panic!("Expected the calldata to be too large");
+        };
+        assert_eq!(report.swap_bytes, vec![150]);
+        assert_eq!(report.suggestions, vec!["Split the trade into several transactions"]);
+    }
+
+    #[test]
+    fn test_calldata_budget_for_chain() {
+        assert_eq!(CalldataBudget::for_chain(Chain::Arbitrum), Some(CalldataBudget::new(95_000)));
+    }
+}
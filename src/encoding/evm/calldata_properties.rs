@@ -0,0 +1,662 @@
+// This module is only meant for property tests of the encoders, and can be used by integration
+// tests as well.
+use std::collections::HashMap;
+
+use num_bigint::{BigInt, BigUint};
+use proptest::prelude::*;
+use tycho_common::{models::protocol::ProtocolComponent, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        decoding::{decode_router_call, DecodedProtocolData, DecodedSolution, DecodedStrategy},
+        encoding_utils::encode_input,
+        group_swaps::{group_swaps, SwapGroup},
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+        utils::{ple_decode, ple_encode},
+    },
+    models::{EncodedSolution, Solution, Swap},
+};
+
+/// The protocols of the swaps generated by [`arb_swap`]. Their swaps are encoded offline, without
+/// any RPC call (with a fixed `ApprovalPolicy` for Balancer V2), and their protocol data decoded.
+pub const FUZZED_PROTOCOLS: [&str; 4] =
+    ["uniswap_v2", "uniswap_v3", "uniswap_v4", "vm:balancer_v2"];
+
+/// Generates a random non-zero address, so that it is never taken for the native token.
+pub fn arb_address() -> impl Strategy<Value = Bytes> {
+    prop::array::uniform20(any::<u8>())
+        .prop_filter("The zero address is the native token", |address| {
+            address.iter().any(|byte| *byte != 0)
+        })
+        .prop_map(|address| Bytes::from(address.to_vec()))
+}
+
+/// Generates a random component of one of the [`FUZZED_PROTOCOLS`], with the static attributes
+/// its swap encoder requires.
+pub fn arb_component() -> impl Strategy<Value = ProtocolComponent> {
+    prop_oneof![
+        arb_address().prop_map(|pool| component("uniswap_v2", &pool, vec![])),
+        (arb_address(), prop::sample::select(vec![100u32, 500, 3000, 10000])).prop_map(
+            |(pool, fee)| component("uniswap_v3", &pool, vec![("fee", int_attribute(fee.into()))])
+        ),
+        (prop::array::uniform32(any::<u8>()), 0u32..1_000_000, 1i32..1000).prop_map(
+            |(pool_id, fee, tick_spacing)| component(
+                "uniswap_v4",
+                &pool_id,
+                vec![
+                    ("key_lp_fee", int_attribute(fee.into())),
+                    ("tick_spacing", int_attribute(tick_spacing.into())),
+                ]
+            )
+        ),
+        prop::array::uniform32(any::<u8>()).prop_map(|pool_id| component(
+            "vm:balancer_v2",
+            &pool_id,
+            vec![]
+        )),
+    ]
+}
+
+/// Generates a random swap between the given tokens, on a component of one of the
+/// [`FUZZED_PROTOCOLS`].
+pub fn arb_swap(token_in: Bytes, token_out: Bytes) -> impl Strategy<Value = Swap> {
+    arb_component()
+        .prop_map(move |component| Swap::new(component, token_in.clone(), token_out.clone()))
+}
+
+/// Generates a random sequential solution, swapping through 2 to 5 distinct tokens. Consecutive
+/// swaps on groupable protocols are grouped when encoded, so that the solution may also be
+/// encoded as a single swap.
+pub fn arb_sequential_solution() -> impl Strategy<Value = Solution> {
+    (prop::collection::hash_set(arb_address(), 2..=5), arb_address(), 1u128.., 1u128..)
+        .prop_flat_map(|(tokens, receiver, given_amount, checked_amount)| {
+            let tokens: Vec<Bytes> = tokens.into_iter().collect();
+            let swaps: Vec<_> = tokens
+                .windows(2)
+                .map(|pair| arb_swap(pair[0].clone(), pair[1].clone()))
+                .collect();
+            (Just(tokens), swaps, Just(receiver), Just(given_amount), Just(checked_amount))
+        })
+        .prop_map(|(tokens, swaps, receiver, given_amount, checked_amount)| Solution {
+            given_token: tokens[0].clone(),
+            given_amount: BigUint::from(given_amount),
+            checked_token: tokens[tokens.len() - 1].clone(),
+            checked_amount: BigUint::from(checked_amount),
+            sender: receiver.clone(),
+            receiver,
+            swaps,
+            ..Default::default()
+        })
+}
+
+/// Generates a random split solution, splitting the given token over 2 to 4 swaps to the checked
+/// token. Every swap but the last one takes 5% to 30% of the given amount, and the last one the
+/// remainder.
+pub fn arb_split_solution() -> impl Strategy<Value = Solution> {
+    (
+        prop::collection::hash_set(arb_address(), 2),
+        prop::collection::vec(1u32..=6, 1..=3),
+        arb_address(),
+        1u128..,
+        1u128..,
+    )
+        .prop_flat_map(|(tokens, splits, receiver, given_amount, checked_amount)| {
+            let tokens: Vec<Bytes> = tokens.into_iter().collect();
+            let swaps: Vec<_> = (0..=splits.len())
+                .map(|_| arb_swap(tokens[0].clone(), tokens[1].clone()))
+                .collect();
+            (
+                Just(tokens),
+                swaps,
+                Just(splits),
+                Just(receiver),
+                Just(given_amount),
+                Just(checked_amount),
+            )
+        })
+        .prop_map(|(tokens, swaps, splits, receiver, given_amount, checked_amount)| {
+            let swaps = swaps
+                .into_iter()
+                .enumerate()
+                .map(|(i, swap)| match splits.get(i) {
+                    Some(split) => swap.split(f64::from(*split) * 0.05),
+                    None => swap,
+                })
+                .collect();
+            Solution {
+                given_token: tokens[0].clone(),
+                given_amount: BigUint::from(given_amount),
+                checked_token: tokens[1].clone(),
+                checked_amount: BigUint::from(checked_amount),
+                sender: receiver.clone(),
+                receiver,
+                swaps,
+                ..Default::default()
+            }
+        })
+}
+
+/// Generates a random sequential or split solution (see [`arb_sequential_solution`] and
+/// [`arb_split_solution`]).
+pub fn arb_solution() -> impl Strategy<Value = Solution> {
+    prop_oneof![arb_sequential_solution(), arb_split_solution()]
+}
+
+/// Checks the structural invariants of the calldata of a router call encoding the given solution,
+/// and returns the call decoded back (see `decode_router_call`).
+///
+/// The invariants are:
+/// - The calldata is the selector of the encoded function followed by ABI words.
+/// - The swaps argument points to the encoded swaps, which end the calldata, padded with zeros.
+/// - The swaps of sequential and split solutions are prefix-length encoded, one segment per swap
+///   group, after the executor table of indexed functions. The swap group of single swaps isn't.
+/// - Every swap group starts with its split header for split solutions, and with its executor or
+///   its index in the executor table. The executor is the one of the group's protocol.
+/// - The decoded call matches the solution: tokens, amounts and receiver, and the tokens of every
+///   swap group.
+///
+/// Only the functions taking the funds of the user with a transfer or permit2 are supported, and
+/// split headers with 8-bit token indices (see `TychoRouterEncoder::with_wide_split_indices`).
+///
+/// Returns an `EncodingError::FatalError` describing the first violated invariant, or the error of
+/// the decoder.
+pub fn check_calldata_layout(
+    solution: &Solution,
+    encoded_solution: &EncodedSolution,
+    calldata: &[u8],
+    swap_encoder_registry: &SwapEncoderRegistry,
+) -> Result<DecodedSolution, EncodingError> {
+    let function_signature = &encoded_solution.function_signature;
+    let selector = encode_input(function_signature, vec![]);
+    check(calldata.starts_with(&selector), || {
+        format!("The calldata doesn't start with the selector of {function_signature}")
+    })?;
+    let args = &calldata[selector.len()..];
+    check(args.len().is_multiple_of(32), || {
+        format!("The arguments are {} bytes long, not a whole number of ABI words", args.len())
+    })?;
+    let swaps = abi_swaps_argument(function_signature, args)?;
+    check(swaps == encoded_solution.swaps.as_slice(), || {
+        "The swaps argument differs from the encoded swaps".to_string()
+    })?;
+
+    let groups = group_swaps(&solution.swaps, swap_encoder_registry);
+    check_swaps_layout(function_signature, swaps, groups.len())?;
+
+    let decoded = decode_router_call(calldata, swap_encoder_registry)?;
+    check(decoded.function_signature == *function_signature, || {
+        format!("The calldata was decoded as a call to {}", decoded.function_signature)
+    })?;
+    check(
+        decoded.given_token == solution.given_token &&
+            decoded.given_amount == solution.given_amount,
+        || {
+            format!(
+                "The call gives {} of {}, instead of {} of {}",
+                decoded.given_amount,
+                decoded.given_token,
+                solution.given_amount,
+                solution.given_token
+            )
+        },
+    )?;
+    check(
+        decoded.checked_token == solution.checked_token &&
+            decoded.checked_amount == solution.checked_amount,
+        || {
+            format!(
+                "The call checks {} of {}, instead of {} of {}",
+                decoded.checked_amount,
+                decoded.checked_token,
+                solution.checked_amount,
+                solution.checked_token
+            )
+        },
+    )?;
+    check(decoded.receiver == solution.receiver, || {
+        format!(
+            "The call sends the tokens to {}, instead of {}",
+            decoded.receiver, solution.receiver
+        )
+    })?;
+    check(decoded.n_tokens == encoded_solution.n_tokens, || {
+        format!(
+            "The call swaps {} tokens, instead of {}",
+            decoded.n_tokens, encoded_solution.n_tokens
+        )
+    })?;
+    check(decoded.swaps.len() == groups.len(), || {
+        format!("The call has {} swaps, for {} swap groups", decoded.swaps.len(), groups.len())
+    })?;
+
+    for (i, (group, swap)) in groups
+        .iter()
+        .zip(&decoded.swaps)
+        .enumerate()
+    {
+        let swap_encoder = swap_encoder_registry
+            .get_encoder(&group.protocol_system)
+            .ok_or_else(|| {
+                EncodingError::FatalError(format!(
+                    "Swap {i}: no encoder for protocol {}",
+                    group.protocol_system
+                ))
+            })?;
+        let executor = swap_encoder.group_executor_address(&group.swaps)?;
+        check(swap.executor == executor, || {
+            format!(
+                "Swap {i}: executor {} instead of {executor} for protocol {}",
+                swap.executor, group.protocol_system
+            )
+        })?;
+        check(
+            swap.protocol_systems
+                .contains(&group.protocol_system),
+            || {
+                format!(
+                    "Swap {i}: the executor {executor} isn't registered for protocol {}",
+                    group.protocol_system
+                )
+            },
+        )?;
+        if let Some(split) = swap.split {
+            check((split.split == 0) == (group.split == 0.0), || {
+                format!("Swap {i}: split {} for a group split of {}", split.split, group.split)
+            })?;
+            check(
+                usize::from(split.token_in) < decoded.n_tokens &&
+                    usize::from(split.token_out) < decoded.n_tokens,
+                || {
+                    format!(
+                        "Swap {i}: token indices {} and {} out of the {} tokens",
+                        split.token_in, split.token_out, decoded.n_tokens
+                    )
+                },
+            )?;
+        }
+        check_group_tokens(i, &swap.protocol_data, group)?;
+    }
+    Ok(decoded)
+}
+
+/// Returns the encoded swaps of the router call, the last argument of every router function, and
+/// checks that the `bytes` argument is well formed.
+fn abi_swaps_argument<'a>(
+    function_signature: &str,
+    args: &'a [u8],
+) -> Result<&'a [u8], EncodingError> {
+    let params = function_signature
+        .find('(')
+        .and_then(|start| function_signature[start + 1..].strip_suffix(')'))
+        .map(split_abi_types)
+        .unwrap_or_default();
+    check(params.last() == Some(&"bytes"), || {
+        format!("The last argument of {function_signature} isn't the swaps")
+    })?;
+    let head_length = 32 *
+        params
+            .iter()
+            .map(|param| abi_head_words(param))
+            .sum::<usize>();
+
+    let offset = abi_word(args, head_length - 32)?;
+    check(offset >= head_length && offset % 32 == 0, || {
+        format!("The swaps offset {offset} doesn't point after the {head_length} bytes of the head")
+    })?;
+    let length = abi_word(args, offset)?;
+    let start = offset + 32;
+    check(start + length.div_ceil(32) * 32 == args.len(), || {
+        format!(
+            "The {length} bytes of swaps at offset {offset} don't end the {} bytes of arguments",
+            args.len()
+        )
+    })?;
+    check(
+        args[start + length..]
+            .iter()
+            .all(|byte| *byte == 0),
+        || "The swaps aren't padded with zeros".to_string(),
+    )?;
+    Ok(&args[start..start + length])
+}
+
+/// Checks the layout of the encoded swaps of a router call, independently from the decoder.
+fn check_swaps_layout(
+    function_signature: &str,
+    swaps: &[u8],
+    n_groups: usize,
+) -> Result<(), EncodingError> {
+    let strategy = if function_signature.starts_with("sequential") {
+        DecodedStrategy::Sequential
+    } else if function_signature.starts_with("split") {
+        DecodedStrategy::Split
+    } else {
+        DecodedStrategy::Single
+    };
+    if strategy == DecodedStrategy::Single {
+        check(n_groups == 1, || format!("{n_groups} swap groups encoded as a single swap"))?;
+        return check(swaps.len() > 20, || {
+            format!("The single swap is {} bytes long, without protocol data", swaps.len())
+        });
+    }
+
+    let indexed = function_signature.contains("Indexed");
+    let (n_executors, segments) = if indexed {
+        let n_executors = usize::from(*swaps.first().unwrap_or(&0));
+        let table_length = 1 + 20 * n_executors;
+        check(n_executors > 0 && swaps.len() > table_length, || {
+            format!("The executor table of {n_executors} executors overflows the swaps")
+        })?;
+        (n_executors, &swaps[table_length..])
+    } else {
+        (0, swaps)
+    };
+    let decoded_segments = ple_decode(segments)?;
+    check(ple_encode(decoded_segments.clone())? == segments, || {
+        "The swaps don't re-encode to the same prefix-length encoding".to_string()
+    })?;
+    check(decoded_segments.len() == n_groups, || {
+        format!("{} encoded swaps for {n_groups} swap groups", decoded_segments.len())
+    })?;
+
+    let header_length = if strategy == DecodedStrategy::Split { 5 } else { 0 };
+    let executor_length = if indexed { 1 } else { 20 };
+    for (i, segment) in decoded_segments.iter().enumerate() {
+        check(segment.len() > header_length + executor_length, || {
+            format!("Swap {i} is {} bytes long, without protocol data", segment.len())
+        })?;
+        if indexed {
+            let index = usize::from(segment[header_length]);
+            check(index < n_executors, || {
+                format!("Swap {i}: executor index {index} out of the {n_executors} executors")
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the decoded protocol data swaps the tokens of the swap group.
+///
+/// Only the protocol data of the [`FUZZED_PROTOCOLS`] is checked: the other protocols may swap a
+/// substitute of the native token (e.g. Curve).
+fn check_group_tokens(
+    i: usize,
+    protocol_data: &DecodedProtocolData,
+    group: &SwapGroup,
+) -> Result<(), EncodingError> {
+    let (token_in, token_out) = match protocol_data {
+        DecodedProtocolData::UniswapV2 { token_in, .. } => (token_in, None),
+        DecodedProtocolData::UniswapV3 { token_in, token_out, .. } |
+        DecodedProtocolData::BalancerV2 { token_in, token_out, .. } => (token_in, Some(token_out)),
+        DecodedProtocolData::UniswapV4 { group_token_in, group_token_out, pools, .. } => {
+            check(pools.len() == group.swaps.len(), || {
+                format!(
+                    "Swap {i}: {} pools for a group of {} swaps",
+                    pools.len(),
+                    group.swaps.len()
+                )
+            })?;
+            (group_token_in, Some(group_token_out))
+        }
+        DecodedProtocolData::Curve { .. } | DecodedProtocolData::Unknown(_) => return Ok(()),
+    };
+    check(
+        *token_in == group.token_in &&
+            token_out.is_none_or(|token_out| *token_out == group.token_out),
+        || {
+            format!(
+                "Swap {i}: swaps {token_in} to {token_out:?}, instead of {} to {}",
+                group.token_in, group.token_out
+            )
+        },
+    )
+}
+
+/// Splits a list of comma-separated ABI types, e.g. the parameters of a function signature.
+fn split_abi_types(types: &str) -> Vec<&str> {
+    let mut split = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, char) in types.char_indices() {
+        match char {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                split.push(&types[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !types.is_empty() {
+        split.push(&types[start..]);
+    }
+    split
+}
+
+/// Returns the fields of a tuple type, or `None` for the other types.
+fn abi_tuple_fields(abi_type: &str) -> Option<Vec<&str>> {
+    abi_type
+        .strip_prefix('(')
+        .and_then(|fields| fields.strip_suffix(')'))
+        .map(split_abi_types)
+}
+
+fn abi_is_dynamic(abi_type: &str) -> bool {
+    abi_type == "bytes" ||
+        abi_type == "string" ||
+        abi_type.ends_with("[]") ||
+        abi_tuple_fields(abi_type).is_some_and(|fields| {
+            fields
+                .iter()
+                .any(|field| abi_is_dynamic(field))
+        })
+}
+
+/// Returns the number of words of an ABI type in the head of the arguments: the offset of dynamic
+/// types, or the words of every field of static tuples.
+fn abi_head_words(abi_type: &str) -> usize {
+    if abi_is_dynamic(abi_type) {
+        return 1;
+    }
+    abi_tuple_fields(abi_type).map_or(1, |fields| {
+        fields
+            .iter()
+            .map(|field| abi_head_words(field))
+            .sum()
+    })
+}
+
+/// Reads the ABI word at the given position of the arguments as an offset or a length.
+fn abi_word(args: &[u8], position: usize) -> Result<usize, EncodingError> {
+    let word = args
+        .get(position..position + 32)
+        .ok_or_else(|| {
+            EncodingError::FatalError(format!(
+                "The word at {position} is out of the {} bytes of arguments",
+                args.len()
+            ))
+        })?;
+    check(word[..24].iter().all(|byte| *byte == 0), || {
+        format!("The word at {position} is too large for an offset or a length")
+    })?;
+    Ok(u64::from_be_bytes(word[24..].try_into().expect("8 bytes")) as usize)
+}
+
+fn check(holds: bool, violation: impl FnOnce() -> String) -> Result<(), EncodingError> {
+    if holds {
+        Ok(())
+    } else {
+        Err(EncodingError::FatalError(violation()))
+    }
+}
+
+fn component(
+    protocol_system: &str,
+    id: &[u8],
+    static_attributes: Vec<(&str, Bytes)>,
+) -> ProtocolComponent {
+    ProtocolComponent {
+        id: format!("0x{}", hex::encode(id)),
+        protocol_system: protocol_system.to_string(),
+        static_attributes: static_attributes
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect::<HashMap<_, _>>(),
+        ..Default::default()
+    }
+}
+
+fn int_attribute(value: i64) -> Bytes {
+    Bytes::from(BigInt::from(value).to_signed_bytes_be())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, str::FromStr};
+
+    use tycho_common::models::Chain;
+
+    use super::*;
+    use crate::encoding::{
+        evm::{encoding_utils::encode_tycho_router_call, tycho_encoders::TychoRouterEncoder},
+        models::{ApprovalPolicy, UserTransferType},
+        tycho_encoder::TychoEncoder,
+    };
+
+    fn get_swap_encoder_registry() -> SwapEncoderRegistry {
+        let executors_addresses =
+            fs::read_to_string("config/test_executor_addresses.json").unwrap();
+        SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap()
+    }
+
+    fn get_tycho_router_encoder(executor_table: bool) -> TychoRouterEncoder {
+        let encoder = TychoRouterEncoder::new(
+            Chain::Ethereum,
+            get_swap_encoder_registry(),
+            Bytes::from_str("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66").unwrap(),
+            UserTransferType::TransferFrom,
+            None,
+            false,
+        )
+        .unwrap()
+        .with_approval_policy(ApprovalPolicy::AlwaysApprove);
+        if executor_table {
+            encoder.with_executor_table()
+        } else {
+            encoder
+        }
+    }
+
+    fn encode(encoder: &TychoRouterEncoder, solution: &Solution) -> (EncodedSolution, Vec<u8>) {
+        let encoded_solution = encoder
+            .encode_solutions(vec![solution.clone()])
+            .unwrap()
+            .remove(0);
+        let transaction = encode_tycho_router_call(
+            Chain::Ethereum.id(),
+            encoded_solution.clone(),
+            solution,
+            &UserTransferType::TransferFrom,
+            &Bytes::zero(20),
+            None,
+        )
+        .unwrap();
+        (encoded_solution, transaction.data)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_calldata_layout(solution in arb_solution(), executor_table in any::<bool>()) {
+            let registry = get_swap_encoder_registry();
+            let (encoded_solution, calldata) =
+                encode(&get_tycho_router_encoder(executor_table), &solution);
+
+            let decoded = check_calldata_layout(&solution, &encoded_solution, &calldata, &registry);
+
+            prop_assert!(decoded.is_ok(), "{:?}", decoded.err());
+        }
+    }
+
+    #[test]
+    fn test_check_calldata_layout_detects_corrupted_calldata() {
+        let registry = get_swap_encoder_registry();
+        let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let usdc = Bytes::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+        let solution = Solution {
+            given_token: weth.clone(),
+            given_amount: BigUint::from(1_000_000_000_000_000_000u64),
+            checked_token: usdc.clone(),
+            checked_amount: BigUint::from(1u32),
+            sender: dai.clone(),
+            receiver: dai.clone(),
+            swaps: vec![
+                Swap::new(
+                    component(
+                        "uniswap_v2",
+                        &Bytes::from_str("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11").unwrap(),
+                        vec![],
+                    ),
+                    weth,
+                    dai.clone(),
+                ),
+                Swap::new(
+                    component(
+                        "uniswap_v2",
+                        &Bytes::from_str("0xAE461cA67B15dc8dc81CE7615e0320dA1A9aB8D5").unwrap(),
+                        vec![],
+                    ),
+                    dai,
+                    usdc,
+                ),
+            ],
+            ..Default::default()
+        };
+        let (encoded_solution, calldata) = encode(&get_tycho_router_encoder(false), &solution);
+        assert!(check_calldata_layout(&solution, &encoded_solution, &calldata, &registry).is_ok());
+
+        // An extra word after the swaps
+        let mut extended = calldata.clone();
+        extended.extend([0u8; 32]);
+        assert!(matches!(
+            check_calldata_layout(&solution, &encoded_solution, &extended, &registry),
+            Err(EncodingError::FatalError(_))
+        ));
+
+        // The prefix-length of the first swap one byte too short
+        let mut encoded_swaps = encoded_solution.swaps.clone();
+        encoded_swaps[1] -= 1;
+        let corrupted_solution =
+            EncodedSolution { swaps: encoded_swaps, ..encoded_solution.clone() };
+        let corrupted_calldata = encode_tycho_router_call(
+            Chain::Ethereum.id(),
+            corrupted_solution.clone(),
+            &solution,
+            &UserTransferType::TransferFrom,
+            &Bytes::zero(20),
+            None,
+        )
+        .unwrap()
+        .data;
+        assert!(check_calldata_layout(
+            &solution,
+            &corrupted_solution,
+            &corrupted_calldata,
+            &registry
+        )
+        .is_err());
+
+        // Another receiver than the solution's
+        let other_receiver = Solution {
+            receiver: Bytes::from_str("0x0000000000000000000000000000000000000001").unwrap(),
+            ..solution.clone()
+        };
+        assert!(check_calldata_layout(&other_receiver, &encoded_solution, &calldata, &registry)
+            .is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use num_bigint::BigUint;
+use tycho_common::{
+    models::{token::Token, Chain},
+    Bytes,
+};
+
+use crate::encoding::{
+    errors::EncodingError,
+    models::{Solution, Swap},
+};
+
+/// Rejects solutions whose checked amount is off by orders of magnitude from the amount they are
+/// expected to produce, e.g. an amount scaled with the decimals of another token.
+///
+/// The checked amount is compared with a reference amount of the checked token:
+/// * The expected amount of the solution, if it's set.
+/// * Otherwise, for exact in solutions whose swaps form a single path with AMM protocol states, the
+///   given amount simulated through the states of the swaps.
+///
+/// Solutions without a reference amount, and solutions with a zero checked amount (no min amount
+/// out), are not checked.
+///
+/// # Fields
+/// * `max_factor`: Maximum factor between the checked amount and the reference amount, in either
+///   direction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CheckedAmountGuard {
+    max_factor: u64,
+}
+
+impl CheckedAmountGuard {
+    /// Factor used by default: a million, far above any slippage, while catching the amounts
+    /// scaled with decimals differing by more than 6 (e.g. 12 between USDC and DAI).
+    pub const DEFAULT_MAX_FACTOR: u64 = 1_000_000;
+
+    pub fn new(max_factor: u64) -> Result<Self, EncodingError> {
+        if max_factor < 2 {
+            return Err(EncodingError::InvalidInput(format!(
+                "The max factor of the checked amount guard must be at least 2. Found {max_factor}"
+            )));
+        }
+        Ok(Self { max_factor })
+    }
+
+    pub fn max_factor(&self) -> u64 {
+        self.max_factor
+    }
+
+    /// Raises an `EncodingError::InvalidInput` if the checked amount of the solution differs from
+    /// its reference amount by more than `max_factor`.
+    pub fn validate(&self, solution: &Solution, chain: Chain) -> Result<(), EncodingError> {
+        if solution.checked_amount == BigUint::ZERO {
+            return Ok(());
+        }
+        let (reference_amount, source) = match &solution.expected_amount {
+            Some(expected_amount) => (expected_amount.clone(), "expected amount"),
+            None => match simulated_amount_out(solution, chain) {
+                Some(amount_out) => (amount_out, "amount simulated with the protocol states"),
+                None => return Ok(()),
+            },
+        };
+
+        let checked_amount = &solution.checked_amount;
+        if checked_amount > &(&reference_amount * self.max_factor) ||
+            checked_amount * self.max_factor < reference_amount
+        {
+            return Err(EncodingError::InvalidInput(format!(
+                "The checked amount {checked_amount} of {} differs from the {source} \
+                 {reference_amount} by more than {}x. The amount was likely scaled with the \
+                 decimals of another token",
+                solution.checked_token, self.max_factor
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for CheckedAmountGuard {
+    fn default() -> Self {
+        Self { max_factor: Self::DEFAULT_MAX_FACTOR }
+    }
+}
+
+/// Returns the amount out of an exact in solution simulated through the protocol states of its
+/// swaps, or `None` if the swaps don't form a single path from the given token to the checked
+/// token or any of them has no AMM state (RFQ states only quote signed amounts).
+///
+/// The tokens are simulated with 18 decimals, which the AMM states don't depend on to compute
+/// amounts.
+fn simulated_amount_out(solution: &Solution, chain: Chain) -> Option<BigUint> {
+    if solution.exact_out ||
+        !solution
+            .additional_given_tokens
+            .is_empty()
+    {
+        return None;
+    }
+    let is_simulable = |swap: &Swap| {
+        let capabilities = swap.capabilities();
+        capabilities.has_state && !capabilities.indicatively_priced && swap.get_split() == 0.0
+    };
+    if solution.swaps.is_empty() || !solution.swaps.iter().all(is_simulable) {
+        return None;
+    }
+
+    let token = |address: &Bytes| Token::new(address, "", 18, 0, &[], chain, 100);
+    let mut token_in = solution.swaps[0].token_in();
+    let mut amount = solution.given_amount.clone();
+    for swap in &solution.swaps {
+        if swap.token_in() != token_in {
+            return None;
+        }
+        amount = swap
+            .amm_state()
+            .ok()?
+            .get_amount_out(amount, &token(swap.token_in()), &token(swap.token_out()))
+            .ok()?
+            .amount;
+        token_in = swap.token_out();
+    }
+    (token_in == &solution.checked_token).then_some(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::evm::testing_utils::MockAmmState;
+
+    fn weth() -> Bytes {
+        Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+    }
+
+    fn usdc() -> Bytes {
+        Bytes::from("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+    }
+
+    fn solution(checked_amount: u128) -> Solution {
+        // 1 WETH ──(USV2, 3000 USDC per WETH)──> USDC
+        let state = MockAmmState::constant_product(
+            weth(),
+            BigUint::from(1_000_000_000_000_000_000_000u128),
+            usdc(),
+            BigUint::from(3_000_000_000_000u64),
+        );
+        let swap = Swap::new(
+            ProtocolComponent {
+                id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
+                protocol_system: "uniswap_v2".to_string(),
+                ..Default::default()
+            },
+            weth(),
+            usdc(),
+        )
+        .protocol_state(Arc::new(state));
+        Solution {
+            given_token: weth(),
+            given_amount: BigUint::from(1_000_000_000_000_000_000u64),
+            checked_token: usdc(),
+            checked_amount: BigUint::from(checked_amount),
+            swaps: vec![swap],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_simulated_amount() {
+        let guard = CheckedAmountGuard::default();
+
+        // ~2997 USDC (6 decimals) with 0.1% slippage
+        assert!(guard
+            .validate(&solution(2_994_000_000), Chain::Ethereum)
+            .is_ok());
+        // 2994 USDC scaled with 18 decimals
+        let result = guard.validate(&solution(2_994_000_000_000_000_000_000), Chain::Ethereum);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        // 0.000003 USDC
+        let result = guard.validate(&solution(3), Chain::Ethereum);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_validate_expected_amount() {
+        let guard = CheckedAmountGuard::new(1000).unwrap();
+        // 2994 USDC scaled with 12 decimals instead of 6
+        let solution = Solution {
+            swaps: vec![],
+            expected_amount: Some(BigUint::from(3_000_000_000u64)),
+            ..solution(2_994_000_000_000_000)
+        };
+
+        let result = guard.validate(&solution, Chain::Ethereum);
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_validate_without_reference_amount() {
+        let guard = CheckedAmountGuard::default();
+        let solution = Solution { swaps: vec![], ..solution(2_994_000_000_000_000_000_000) };
+
+        assert!(guard
+            .validate(&solution, Chain::Ethereum)
+            .is_ok());
+    }
+}
@@ -0,0 +1,163 @@
+use std::{fmt, str::FromStr};
+
+use alloy::primitives::{Address, B256};
+use tycho_common::{models::protocol::ProtocolComponent, Bytes};
+
+use crate::encoding::{errors::EncodingError, evm::constants::COMPONENT_ID_FORMATS};
+
+/// How a protocol identifies its components.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComponentIdFormat {
+    /// The id is the address of the pool contract (e.g. Balancer V3 pools).
+    Address,
+    /// The id is a 32 bytes hash (e.g. Balancer V2 pool ids).
+    Hash,
+    /// The id is the address of the pool contract on most chains, but a 32 bytes hash on the ones
+    /// keying their pools by hash. The pool contract of a hash id is then the single contract
+    /// address of the component.
+    AddressOrHash,
+}
+
+impl fmt::Display for ComponentIdFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentIdFormat::Address => write!(f, "an address"),
+            ComponentIdFormat::Hash => write!(f, "a 32 bytes hash"),
+            ComponentIdFormat::AddressOrHash => write!(f, "an address or a 32 bytes hash"),
+        }
+    }
+}
+
+/// The id of a protocol component, parsed and validated according to its protocol (see
+/// `COMPONENT_ID_FORMATS`).
+///
+/// Protocols without a known format accept both addresses and hashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComponentId {
+    Address(Address),
+    Hash(B256),
+}
+
+impl ComponentId {
+    /// Parses the id of the component, failing if it doesn't match the format of its protocol.
+    pub fn parse(component: &ProtocolComponent) -> Result<Self, EncodingError> {
+        let format = COMPONENT_ID_FORMATS
+            .get(component.protocol_system.as_str())
+            .copied()
+            .unwrap_or(ComponentIdFormat::AddressOrHash);
+        let invalid = || {
+            EncodingError::FatalError(format!(
+                "Invalid component id {} for {}: expected {format}",
+                component.id, component.protocol_system
+            ))
+        };
+        let id = Bytes::from_str(&component.id).map_err(|_| invalid())?;
+        let id = match id.len() {
+            20 => ComponentId::Address(Address::from_slice(&id)),
+            32 => ComponentId::Hash(B256::from_slice(&id)),
+            _ => return Err(invalid()),
+        };
+        match (format, id) {
+            (ComponentIdFormat::Address, ComponentId::Hash(_)) |
+            (ComponentIdFormat::Hash, ComponentId::Address(_)) => Err(invalid()),
+            _ => Ok(id),
+        }
+    }
+
+    /// Returns the raw bytes of the id.
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            ComponentId::Address(address) => address.as_slice(),
+            ComponentId::Hash(hash) => hash.as_slice(),
+        }
+    }
+
+    /// Returns the address of the component's pool contract: the id itself for address ids, or
+    /// the single contract address of the component for hash ids.
+    pub fn pool_address(&self, component: &ProtocolComponent) -> Result<Address, EncodingError> {
+        match self {
+            ComponentId::Address(address) => Ok(*address),
+            ComponentId::Hash(_) => match component.contract_addresses.as_slice() {
+                [address] if address.len() == 20 => Ok(Address::from_slice(address)),
+                _ => Err(EncodingError::FatalError(format!(
+                    "The {} component {} is identified by a hash, so it needs exactly one \
+                     contract address to interact with",
+                    component.protocol_system, component.id
+                ))),
+            },
+        }
+    }
+}
+
+/// Parses the id of the component and returns the address of its pool contract (see
+/// [`ComponentId::pool_address`]).
+pub fn component_pool_address(component: &ProtocolComponent) -> Result<Address, EncodingError> {
+    ComponentId::parse(component)?.pool_address(component)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(
+        protocol_system: &str,
+        id: &str,
+        contract_addresses: Vec<Bytes>,
+    ) -> ProtocolComponent {
+        ProtocolComponent {
+            id: id.to_string(),
+            protocol_system: protocol_system.to_string(),
+            contract_addresses,
+            ..Default::default()
+        }
+    }
+
+    const POOL: &str = "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11";
+    const POOL_ID: &str = "0x5c6ee304399dbdb9c8ef030ab642b10820db8f56000200000000000000000014";
+
+    #[test]
+    fn test_parse_address_id() {
+        let component = component("uniswap_v2", POOL, vec![]);
+        let id = ComponentId::parse(&component).unwrap();
+
+        assert_eq!(id, ComponentId::Address(Address::from_str(POOL).unwrap()));
+        assert_eq!(id.pool_address(&component).unwrap(), Address::from_str(POOL).unwrap());
+    }
+
+    #[test]
+    fn test_parse_hash_id_with_contract_address() {
+        let component = component("uniswap_v2", POOL_ID, vec![Bytes::from_str(POOL).unwrap()]);
+        let id = ComponentId::parse(&component).unwrap();
+
+        assert_eq!(id, ComponentId::Hash(B256::from_str(POOL_ID).unwrap()));
+        assert_eq!(id.as_slice().len(), 32);
+        assert_eq!(component_pool_address(&component).unwrap(), Address::from_str(POOL).unwrap());
+    }
+
+    #[test]
+    fn test_hash_id_without_contract_address() {
+        let component = component("vm:curve", POOL_ID, vec![]);
+
+        let result = component_pool_address(&component);
+
+        assert!(matches!(
+            result,
+            Err(EncodingError::FatalError(msg)) if msg.contains("needs exactly one contract address")
+        ));
+    }
+
+    #[test]
+    fn test_parse_rejects_unexpected_format() {
+        let hash_for_address_protocol = component("vm:balancer_v3", POOL_ID, vec![]);
+        let address_for_hash_protocol = component("vm:balancer_v2", POOL, vec![]);
+        let not_hex = component("uniswap_v3", "i-am-not-an-id", vec![]);
+
+        for component in [hash_for_address_protocol, address_for_hash_protocol, not_hex] {
+            let result = ComponentId::parse(&component);
+            assert!(matches!(
+                result,
+                Err(EncodingError::FatalError(msg)) if msg.starts_with("Invalid component id")
+            ));
+        }
+    }
+}
@@ -1,9 +1,19 @@
-use std::{collections::HashSet, sync::LazyLock};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use tycho_common::models::Chain;
+
+use crate::encoding::evm::component_id::ComponentIdFormat;
 
 pub const DEFAULT_EXECUTORS_JSON: &str = include_str!("../../../config/executor_addresses.json");
 pub const DEFAULT_ROUTERS_JSON: &str = include_str!("../../../config/router_addresses.json");
 pub const PROTOCOL_SPECIFIC_CONFIG: &str =
     include_str!("../../../config/protocol_specific_addresses.json");
+pub const PROTOCOL_CAPABILITIES_JSON: &str =
+    include_str!("../../../config/protocol_capabilities.json");
 
 /// The number of blocks in the future for which to fetch Angstrom Attestations
 ///
@@ -12,61 +22,207 @@ pub const PROTOCOL_SPECIFIC_CONFIG: &str =
 /// expiring if the transaction is not sent fast enough.
 pub const ANGSTROM_DEFAULT_BLOCKS_IN_FUTURE: u64 = 5;
 
-/// These protocols support the optimization of grouping swaps.
-///
-/// This requires special encoding to send call data of multiple swaps to a single executor,
-/// as if it were a single swap. The protocol likely uses flash accounting to save gas on token
-/// transfers.
-pub static GROUPABLE_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+/// Maximum time to wait for the response of a single RPC endpoint before failing over to the next
+/// one.
+pub const RPC_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time during which a failing RPC endpoint is only tried after the healthy ones. It grows with the
+/// number of consecutive failures of the endpoint, up to 4 times this value.
+pub const RPC_ENDPOINT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Protocols whose executors support exact out swaps, i.e. swaps for a given amount out.
+pub static EXACT_OUT_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    let mut set = HashSet::new();
+    set.insert("uniswap_v2");
+    set.insert("sushiswap_v2");
+    set.insert("pancakeswap_v2");
+    set.insert("uniswap_v3");
+    set.insert("pancakeswap_v3");
+    set
+});
+
+/// These groupable protocols use simple concatenation instead of PLE when forming swap groups.
+pub static NON_PLE_ENCODED_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
     let mut set = HashSet::new();
-    set.insert("uniswap_v4");
-    set.insert("uniswap_v4_hooks");
-    set.insert("vm:balancer_v3");
     set.insert("ekubo_v2");
     set.insert("ekubo_v3");
     set
 });
 
-/// These protocols expect funds to be in the router at the time of swap and do the transfer
-/// themselves from `msg.sender`.
-/// Any protocols that are not defined here need an external in transfer to the pool.
-/// This transfer can be from the router, from the user or from the previous pool.
-pub static FUNDS_IN_ROUTER_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+/// Protocols whose single swaps can be executed without the router (see
+/// `DirectExecutionEncoder`), mapped to whether their pools can be called directly, after the
+/// amount in was transferred to them (Uniswap V2 pairs).
+pub static DIRECT_EXECUTION_PROTOCOLS: LazyLock<HashMap<&'static str, bool>> =
+    LazyLock::new(|| {
+        let mut map = HashMap::new();
+        map.insert("uniswap_v2", true);
+        map.insert("sushiswap_v2", true);
+        map.insert("pancakeswap_v2", true);
+        map.insert("uniswap_v3", false);
+        map.insert("pancakeswap_v3", false);
+        map
+    });
+
+/// These protocols are Request-for-Quote protocols. Their quotes can be requested for either the
+/// native token or its wrapped version, so their swap tokens can be aligned with the rest of the
+/// solution (see `TychoRouterEncoder`).
+pub static RFQ_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
     let mut set = HashSet::new();
-    set.insert("vm:balancer_v2");
-    set.insert("vm:curve");
     set.insert("rfq:bebop");
     set.insert("rfq:hashflow");
-    set.insert("rocketpool");
-    set.insert("erc4626");
-    set.insert("etherfi");
     set
 });
 
-/// The in transfer needs to be performed inside the callback logic. This means, the tokens can not
-/// be sent directly from the previous pool into a pool of this protocol. The tokens need to be sent
-/// to the router and only then transferred into the pool. This is the case for uniswap v3 because
-/// of the callback logic. The only way for this to work it would be to call the second swap during
-/// the callback of the first swap. This is currently not supported.
-/// The protocols here are disjoint from the ones defined in FUNDS_IN_ROUTER_PROTOCOLS.
-pub static CALLBACK_CONSTRAINED_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+/// These protocols bridge their token in to another chain, where their token out is delivered to
+/// a recipient. Nothing is received on this chain, so they can only be the last swap of a solution
+/// (see `TychoRouterEncoder`).
+pub static BRIDGE_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    let mut set = HashSet::new();
+    set.insert("bridge:across");
+    set
+});
+
+/// These protocols request a withdrawal of their token in, whose token out is claimed later by the
+/// owner of the request (e.g. with a withdrawal request NFT). Nothing is received by the end of the
+/// transaction, so they can only be the last swap of a solution (see `TychoRouterEncoder`).
+pub static WITHDRAWAL_REQUEST_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    let mut set = HashSet::new();
+    set.insert("lido_withdrawal");
+    set
+});
+
+/// These protocols key their pools on the native token itself (instead of its wrapped version) for
+/// native token pairs. The same pair can thus exist as a native-keyed and as a wrapped-keyed pool,
+/// and the encoder has to infer the wrap/unwrap steps from the pool used at the boundaries of the
+/// solution (see `TychoRouterEncoder`).
+pub static NATIVE_KEYED_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
     let mut set = HashSet::new();
-    set.insert("uniswap_v3");
-    set.insert("pancakeswap_v3");
     set.insert("uniswap_v4");
     set.insert("uniswap_v4_hooks");
     set.insert("ekubo_v2");
     set.insert("ekubo_v3");
-    set.insert("vm:balancer_v3");
-    set.insert("fluid_v1");
-    set.insert("aerodrome_slipstreams");
+    set.insert("ambient");
     set
 });
 
-/// These groupable protocols use simple concatenation instead of PLE when forming swap groups.
-pub static NON_PLE_ENCODED_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+/// These protocols convert between the assets and the shares of a vault (e.g. DAI and sDAI), whose
+/// amounts differ by the vault's share price. The amounts of the swaps around them are checked to
+/// account for the conversion (see `SequentialSwapValidator::validate_vault_conversions`).
+pub static VAULT_PROTOCOLS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
     let mut set = HashSet::new();
-    set.insert("ekubo_v2");
-    set.insert("ekubo_v3");
+    set.insert("erc4626");
     set
 });
+
+/// Keys of the protocol specific config (see `PROTOCOL_SPECIFIC_CONFIG`) holding the addresses of
+/// the external settlement contracts a protocol interacts with during a swap, by protocol.
+pub static SETTLEMENT_ADDRESS_KEYS: LazyLock<HashMap<&'static str, Vec<&'static str>>> =
+    LazyLock::new(|| {
+        let mut map = HashMap::new();
+        map.insert("vm:balancer_v2", vec!["vault_address"]);
+        map.insert("rfq:bebop", vec!["bebop_settlement_address"]);
+        map.insert("rfq:hashflow", vec!["hashflow_router_address"]);
+        map.insert("rfq:oneinch_lop", vec!["limit_order_protocol_address"]);
+        map.insert("etherfi", vec!["redemption_manager_address"]);
+        map.insert("frax_ether", vec!["frxeth_minter_address"]);
+        map.insert("sky_psm", vec!["psm_address", "usds_psm_address"]);
+        map.insert("ambient", vec!["croc_swap_dex_address"]);
+        map.insert("lido_withdrawal", vec!["withdrawal_queue_address"]);
+        map.insert("pendle_v2", vec!["router_address"]);
+        map.insert("bridge:across", vec!["spoke_pool_address"]);
+        map
+    });
+
+/// How the protocols whose encoders read their component ids identify their components (see
+/// `ComponentId`). Protocols not listed here accept both addresses and hashes.
+pub static COMPONENT_ID_FORMATS: LazyLock<HashMap<&'static str, ComponentIdFormat>> =
+    LazyLock::new(|| {
+        let mut map = HashMap::new();
+        map.insert("uniswap_v2", ComponentIdFormat::AddressOrHash);
+        map.insert("sushiswap_v2", ComponentIdFormat::AddressOrHash);
+        map.insert("pancakeswap_v2", ComponentIdFormat::AddressOrHash);
+        map.insert("uniswap_v3", ComponentIdFormat::AddressOrHash);
+        map.insert("pancakeswap_v3", ComponentIdFormat::AddressOrHash);
+        map.insert("vm:curve", ComponentIdFormat::AddressOrHash);
+        map.insert("vm:maverick_v2", ComponentIdFormat::AddressOrHash);
+        map.insert("vm:balancer_v2", ComponentIdFormat::Hash);
+        map.insert("vm:balancer_v3", ComponentIdFormat::Address);
+        map.insert("fluid_v1", ComponentIdFormat::Address);
+        map.insert("aerodrome_slipstreams", ComponentIdFormat::Address);
+        map.insert("velodrome_slipstreams", ComponentIdFormat::Address);
+        map.insert("aerodrome_v2", ComponentIdFormat::Address);
+        map.insert("velodrome_v2", ComponentIdFormat::Address);
+        map.insert("erc4626", ComponentIdFormat::Address);
+        map.insert("pendle_v2", ComponentIdFormat::Address);
+        map
+    });
+
+/// Tokens whose balances rebase, by chain. Their balances are derived from an amount of shares,
+/// which makes them drift (and lose 1-2 wei on every transfer due to rounding) between encoding
+/// and execution (see `rebasing`).
+pub static REBASING_TOKENS: LazyLock<HashMap<Chain, HashSet<&'static str>>> = LazyLock::new(|| {
+    let mut map = HashMap::new();
+    // stETH
+    map.insert(Chain::Ethereum, HashSet::from(["0xae7ab96520de3a18e5e111b5eaab095312d7fe84"]));
+    map
+});
+
+/// Static gas estimates of a single swap, by protocol: the gas of the executor call and of the
+/// swap on the pool, including its token transfers. Swaps on protocols missing from this table are
+/// estimated at `DEFAULT_SWAP_GAS_ESTIMATE`. The estimates can be overridden with the
+/// `gas_estimate` key of the protocol specific config (see `PROTOCOL_SPECIFIC_CONFIG`).
+pub static SWAP_GAS_ESTIMATES: LazyLock<HashMap<&'static str, u64>> = LazyLock::new(|| {
+    let mut map = HashMap::new();
+    map.insert("uniswap_v2", 65_000);
+    map.insert("sushiswap_v2", 65_000);
+    map.insert("pancakeswap_v2", 65_000);
+    map.insert("uniswap_v3", 110_000);
+    map.insert("pancakeswap_v3", 110_000);
+    map.insert("aerodrome_slipstreams", 110_000);
+    map.insert("velodrome_slipstreams", 110_000);
+    map.insert("aerodrome_v2", 80_000);
+    map.insert("velodrome_v2", 80_000);
+    map.insert("uniswap_v4", 120_000);
+    map.insert("uniswap_v4_hooks", 160_000);
+    map.insert("ekubo_v2", 100_000);
+    map.insert("ekubo_v3", 100_000);
+    map.insert("vm:balancer_v2", 120_000);
+    map.insert("vm:balancer_v3", 140_000);
+    map.insert("vm:curve", 140_000);
+    map.insert("vm:maverick_v2", 120_000);
+    map.insert("ambient", 130_000);
+    map.insert("fluid_v1", 150_000);
+    map.insert("erc4626", 90_000);
+    map.insert("rocketpool", 150_000);
+    map.insert("lido", 100_000);
+    map.insert("mantle_meth", 120_000);
+    map.insert("etherfi", 120_000);
+    map.insert("frax_ether", 100_000);
+    map.insert("sky_psm", 110_000);
+    map.insert("lido_withdrawal", 160_000);
+    map.insert("pendle_v2", 200_000);
+    map.insert("rfq:bebop", 150_000);
+    map.insert("rfq:hashflow", 140_000);
+    map.insert("rfq:oneinch_lop", 150_000);
+    map.insert("bridge:across", 100_000);
+    map
+});
+
+/// Gas estimate of a swap on a protocol without a static estimate (see `SWAP_GAS_ESTIMATES`).
+pub const DEFAULT_SWAP_GAS_ESTIMATE: u64 = 150_000;
+
+/// Gas estimate of a router call besides its swaps: the intrinsic transaction cost, the checks of
+/// the router and the transfers of the tokens of the user in and out of the router.
+pub const ROUTER_GAS_OVERHEAD: u64 = 50_000;
+
+/// Practical calldata size limits, in bytes, by chain. Larger transactions are rejected by the
+/// node's transaction pool (128 KiB including the signature and other fields for geth-based
+/// chains) or by the sequencer (Arbitrum's `max-tx-data-size`).
+pub static CALLDATA_SIZE_BUDGETS: LazyLock<HashMap<Chain, usize>> = LazyLock::new(|| {
+    let mut map = HashMap::new();
+    map.insert(Chain::Ethereum, 120_000);
+    map.insert(Chain::Base, 120_000);
+    map.insert(Chain::Unichain, 120_000);
+    map.insert(Chain::Arbitrum, 95_000);
+    map
+});
@@ -0,0 +1,252 @@
+use alloy::{core::sol, primitives::U256, sol_types::SolValue};
+use tycho_common::models::Chain;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        encoding_utils::{encode_input, encode_tycho_router_call},
+        strategy_encoder::wrap_adapter::ChainTokens,
+        utils::{biguint_to_u256, bytes_to_address},
+    },
+    models::{EncodedSolution, Solution, UserTransferType},
+};
+
+/// Signatures of the ERC20 functions called by the token interactions.
+const APPROVE: &str = "approve(address,uint256)";
+const TRANSFER: &str = "transfer(address,uint256)";
+
+sol! {
+    /// An interaction executed by the CoW Protocol settlement contract (see `GPv2Interaction.Data`).
+    #[derive(Debug, PartialEq, Eq)]
+    struct GPv2Interaction {
+        address target;
+        uint256 value;
+        bytes callData;
+    }
+}
+
+/// Converts an encoded solution into the interactions of a CoW Protocol settlement, in execution
+/// order.
+///
+/// The settlement contract holds the traded tokens, so it is the sender (and usually the receiver)
+/// of the solution. Its token in is sent to the Tycho router according to the user transfer type:
+/// * `TransferFrom`: the router is approved for the amount in before the router call. For exact out
+///   solutions, which may spend less than the approved maximum amount in, the approval is reset
+///   after the call.
+/// * `None`: the amount in is transferred to the router before the router call.
+/// * `TransferFromPermit2`: not supported, as the settlement contract can't sign permits.
+///
+/// Native tokens in are sent as the value of the router call instead. The router call is encoded
+/// with `encode_tycho_router_call`, so the same responsibility applies to its checked amount.
+///
+/// Returns an `EncodingError::InvalidInput` if the user transfer type is `TransferFromPermit2`.
+pub fn encode_settlement_interactions(
+    chain: Chain,
+    encoded_solution: EncodedSolution,
+    solution: &Solution,
+    user_transfer_type: &UserTransferType,
+) -> Result<Vec<GPv2Interaction>, EncodingError> {
+    if *user_transfer_type == UserTransferType::TransferFromPermit2 {
+        return Err(EncodingError::InvalidInput(
+            "CoW settlements can't sign permits. Use the TransferFrom or None user transfer type"
+                .to_string(),
+        ));
+    }
+    let native_address = ChainTokens::for_chain(chain)?.native;
+    let router = bytes_to_address(&encoded_solution.interacting_with)?;
+    let (token_in, amount_in) = if solution.exact_out {
+        (&solution.checked_token, &solution.checked_amount)
+    } else {
+        (&solution.given_token, &solution.given_amount)
+    };
+    let token_in_interaction = |signature: &str, amount: U256| -> Result<_, EncodingError> {
+        Ok(GPv2Interaction {
+            target: bytes_to_address(token_in)?,
+            value: U256::ZERO,
+            callData: encode_input(signature, (router, amount).abi_encode()).into(),
+        })
+    };
+
+    let transaction = encode_tycho_router_call(
+        chain.id(),
+        encoded_solution,
+        solution,
+        user_transfer_type,
+        &native_address,
+        None,
+    )?;
+    let router_call = GPv2Interaction {
+        target: router,
+        value: biguint_to_u256(&transaction.value),
+        callData: transaction.data.into(),
+    };
+
+    if *token_in == native_address {
+        return Ok(vec![router_call]);
+    }
+    let amount_in = biguint_to_u256(amount_in);
+    let interactions = match user_transfer_type {
+        UserTransferType::TransferFrom if solution.exact_out => vec![
+            token_in_interaction(APPROVE, amount_in)?,
+            router_call,
+            token_in_interaction(APPROVE, U256::ZERO)?,
+        ],
+        UserTransferType::TransferFrom => {
+            vec![token_in_interaction(APPROVE, amount_in)?, router_call]
+        }
+        _ => vec![token_in_interaction(TRANSFER, amount_in)?, router_call],
+    };
+    Ok(interactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use num_bigint::BigUint;
+    use tycho_common::{models::protocol::ProtocolComponent, Bytes};
+
+    use super::*;
+    use crate::encoding::models::Swap;
+
+    fn router() -> Bytes {
+        Bytes::from_str("0xfD0b31d2E955fA55e3fa641Fe90e08b677188d35").unwrap()
+    }
+
+    fn settlement() -> Bytes {
+        Bytes::from_str("0x9008D19f58AAbD9eD0D60971565AA8510560ab41").unwrap()
+    }
+
+    fn weth() -> Bytes {
+        Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap()
+    }
+
+    fn dai() -> Bytes {
+        Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap()
+    }
+
+    fn encoded_solution() -> EncodedSolution {
+        EncodedSolution {
+            swaps: vec![0xab, 0xcd],
+            interacting_with: router(),
+            function_signature:
+                "singleSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)"
+                    .to_string(),
+            n_tokens: 0,
+            permit: None,
+            permit_batch: None,
+            native_action: None,
+            state_versions: vec![],
+            callback_allowlist: None,
+            summary: Default::default(),
+            gas_estimate: 0,
+            debug_info: None,
+            degraded_groups: vec![],
+        }
+    }
+
+    fn solution(given_token: Bytes) -> Solution {
+        Solution {
+            given_token,
+            given_amount: BigUint::from(1000u32),
+            checked_token: dai(),
+            checked_amount: BigUint::from(990u32),
+            sender: settlement(),
+            receiver: settlement(),
+            swaps: vec![Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                dai(),
+            )],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_encode_settlement_interactions_transfer_from() {
+        let solution = solution(weth());
+
+        let interactions = encode_settlement_interactions(
+            Chain::Ethereum,
+            encoded_solution(),
+            &solution,
+            &UserTransferType::TransferFrom,
+        )
+        .unwrap();
+
+        assert_eq!(interactions.len(), 2);
+        let approval = &interactions[0];
+        assert_eq!(approval.target, bytes_to_address(&weth()).unwrap());
+        assert_eq!(approval.value, U256::ZERO);
+        assert_eq!(
+            approval.callData.to_vec(),
+            encode_input(
+                APPROVE,
+                (bytes_to_address(&router()).unwrap(), U256::from(1000)).abi_encode()
+            )
+        );
+        let router_call = &interactions[1];
+        let transaction = encode_tycho_router_call(
+            1,
+            encoded_solution(),
+            &solution,
+            &UserTransferType::TransferFrom,
+            &Chain::Ethereum.native_token().address,
+            None,
+        )
+        .unwrap();
+        assert_eq!(router_call.target, bytes_to_address(&router()).unwrap());
+        assert_eq!(router_call.value, U256::ZERO);
+        assert_eq!(router_call.callData.to_vec(), transaction.data);
+    }
+
+    #[test]
+    fn test_encode_settlement_interactions_transfer() {
+        let interactions = encode_settlement_interactions(
+            Chain::Ethereum,
+            encoded_solution(),
+            &solution(weth()),
+            &UserTransferType::None,
+        )
+        .unwrap();
+
+        assert_eq!(interactions.len(), 2);
+        assert_eq!(
+            interactions[0].callData.to_vec(),
+            encode_input(
+                TRANSFER,
+                (bytes_to_address(&router()).unwrap(), U256::from(1000)).abi_encode()
+            )
+        );
+    }
+
+    #[test]
+    fn test_encode_settlement_interactions_native_in() {
+        let interactions = encode_settlement_interactions(
+            Chain::Ethereum,
+            encoded_solution(),
+            &solution(Chain::Ethereum.native_token().address),
+            &UserTransferType::TransferFrom,
+        )
+        .unwrap();
+
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions[0].value, U256::from(1000));
+    }
+
+    #[test]
+    fn test_encode_settlement_interactions_permit2() {
+        let result = encode_settlement_interactions(
+            Chain::Ethereum,
+            encoded_solution(),
+            &solution(weth()),
+            &UserTransferType::TransferFromPermit2,
+        );
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
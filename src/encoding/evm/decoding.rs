@@ -0,0 +1,1246 @@
+use std::collections::HashMap;
+
+use alloy::{
+    primitives::{Address, Bytes as AlloyBytes, U256},
+    sol_types::SolValue,
+};
+use num_bigint::BigUint;
+use tycho_common::Bytes;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        approvals::permit2::{PermitBatch, PermitSingle},
+        encoding_utils::encode_input,
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+        utils::ple_decode,
+    },
+    models::{self, TransferType, UserTransferType},
+};
+
+/// The swap functions of the Tycho router, whose calls can be decoded.
+const ROUTER_FUNCTIONS: [&str; 12] = [
+    "singleSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)",
+    "singleSwapPermit2(uint256,address,address,uint256,bool,bool,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+    "exactOutSingleSwap(uint256,address,address,uint256,bool,address,bool,bytes)",
+    "sequentialSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)",
+    "sequentialSwapPermit2(uint256,address,address,uint256,bool,bool,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+    "sequentialSwapIndexed(uint256,address,address,uint256,bool,bool,address,bool,bytes)",
+    "sequentialSwapPermit2Indexed(uint256,address,address,uint256,bool,bool,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+    "splitSwap(uint256,address,address,uint256,bool,bool,uint256,address,bool,bytes)",
+    "splitSwapPermit2(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+    "splitSwapPermit2Batch(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48)[],address,uint256),bytes,bytes)",
+    "splitSwapIndexed(uint256,address,address,uint256,bool,bool,uint256,address,bool,bytes)",
+    "splitSwapPermit2Indexed(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+];
+
+/// Arguments of `singleSwap` and `sequentialSwap` (and its indexed variant).
+type SwapArgs = (U256, Address, Address, U256, bool, bool, Address, bool, AlloyBytes);
+/// Arguments of `singleSwapPermit2` and `sequentialSwapPermit2` (and its indexed variant).
+type SwapPermit2Args =
+    (U256, Address, Address, U256, bool, bool, Address, PermitSingle, AlloyBytes, AlloyBytes);
+/// Arguments of `exactOutSingleSwap`.
+type ExactOutSwapArgs = (U256, Address, Address, U256, bool, Address, bool, AlloyBytes);
+/// Arguments of `splitSwap` (and its indexed variant).
+type SplitSwapArgs = (U256, Address, Address, U256, bool, bool, U256, Address, bool, AlloyBytes);
+/// Arguments of `splitSwapPermit2` (and its indexed variant).
+type SplitSwapPermit2Args =
+    (U256, Address, Address, U256, bool, bool, U256, Address, PermitSingle, AlloyBytes, AlloyBytes);
+/// Arguments of `splitSwapPermit2Batch`.
+type SplitSwapPermit2BatchArgs =
+    (U256, Address, Address, U256, bool, bool, U256, Address, PermitBatch, AlloyBytes, AlloyBytes);
+
+/// The strategy a decoded router call was encoded with.
+///
+/// Variants:
+/// - `Single`: A single swap (or group of swaps), called without a swap header.
+/// - `Sequential`: Swaps executed one after the other.
+/// - `Split`: Swaps with split headers, referring to the tokens of the solution by index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodedStrategy {
+    Single,
+    Sequential,
+    Split,
+}
+
+/// A call to the Tycho router, decoded back from its calldata.
+///
+/// # Fields
+/// * `function_signature`: The signature of the called router function
+/// * `strategy`: The strategy the swaps were encoded with
+/// * `indexed`: Whether the swaps refer to their executors in a shared executor table
+/// * `exact_out`: Whether the call is an exact out swap, for a given amount out
+/// * `given_amount`: The amount in, or the amount out of exact out swaps
+/// * `given_token`: The token sold, or the token bought by exact out swaps
+/// * `checked_token`: The token bought, or the token sold by exact out swaps
+/// * `checked_amount`: The minimum amount out, or the maximum amount in of exact out swaps
+/// * `wrap`: Whether the native token is wrapped before the swaps
+/// * `unwrap`: Whether the wrapped native token is unwrapped after the swaps
+/// * `n_tokens`: The number of tokens of split solutions, 0 for the other strategies
+/// * `receiver`: The receiver of the checked token (or of the given token of exact out swaps)
+/// * `user_transfer_type`: How the funds of the user are transferred into the router
+/// * `permit`: The permit2 single permit, for the `Permit2` functions
+/// * `permit_batch`: The permit2 batch permit, for `splitSwapPermit2Batch`
+/// * `signature`: The signature of the permit, for the `Permit2` functions
+/// * `swaps`: The decoded swaps, in the order they are executed
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedSolution {
+    pub function_signature: String,
+    pub strategy: DecodedStrategy,
+    pub indexed: bool,
+    pub exact_out: bool,
+    pub given_amount: BigUint,
+    pub given_token: Bytes,
+    pub checked_token: Bytes,
+    pub checked_amount: BigUint,
+    pub wrap: bool,
+    pub unwrap: bool,
+    pub n_tokens: usize,
+    pub receiver: Bytes,
+    pub user_transfer_type: UserTransferType,
+    pub permit: Option<models::PermitSingle>,
+    pub permit_batch: Option<models::PermitBatch>,
+    pub signature: Option<Bytes>,
+    pub swaps: Vec<DecodedSwap>,
+}
+
+/// The header of a swap of a split solution.
+///
+/// # Fields
+/// * `token_in`: Index of the input token in the tokens of the solution
+/// * `token_out`: Index of the output token in the tokens of the solution
+/// * `split`: Fraction of the available input token swapped, as a `uint24` (`0xffffff` is 100%).
+///   Remainder swaps, taking what is left of the token, have a split of 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SplitHeader {
+    pub token_in: u8,
+    pub token_out: u8,
+    pub split: u32,
+}
+
+/// A decoded swap (or group of swaps) of a router call.
+///
+/// # Fields
+/// * `split`: The split header, for split solutions
+/// * `executor`: The executor performing the swap
+/// * `protocol_systems`: The protocol systems of the registry using this executor, sorted. Empty if
+///   the executor is unknown to the registry.
+/// * `protocol_data`: The protocol data of the swap
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedSwap {
+    pub split: Option<SplitHeader>,
+    pub executor: Bytes,
+    pub protocol_systems: Vec<String>,
+    pub protocol_data: DecodedProtocolData,
+}
+
+/// A Uniswap V4 pool of a (possibly grouped) Uniswap V4 swap.
+///
+/// # Fields
+/// * `token_out`: The token bought on the pool
+/// * `fee`: The LP fee of the pool
+/// * `tick_spacing`: The tick spacing of the pool
+/// * `hook`: The hook of the pool, the zero address if it has none
+/// * `hook_data`: The data passed to the hook
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniswapV4Pool {
+    pub token_out: Bytes,
+    pub fee: u32,
+    pub tick_spacing: i32,
+    pub hook: Bytes,
+    pub hook_data: Bytes,
+}
+
+/// The protocol data of a swap, decoded according to the protocol of its executor.
+///
+/// Variants:
+/// - `UniswapV2`: A swap on a Uniswap V2 pool (or fork)
+/// - `UniswapV3`: A swap on a Uniswap V3 pool (or fork)
+/// - `UniswapV4`: A swap on one or more Uniswap V4 pools, grouped between the group tokens
+/// - `BalancerV2`: A swap on a Balancer V2 pool
+/// - `Curve`: A swap on a Curve pool
+/// - `Unknown`: The raw protocol data, for the executors of other protocols or unknown executors
+///
+/// The `user_data` of a variant is the user data segment appended to the protocol data of swaps
+/// with user data (see `append_user_data`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodedProtocolData {
+    UniswapV2 {
+        token_in: Bytes,
+        pool: Bytes,
+        receiver: Bytes,
+        zero_for_one: bool,
+        transfer_type: TransferType,
+        exact_out: bool,
+        fee_on_transfer: bool,
+    },
+    UniswapV3 {
+        token_in: Bytes,
+        token_out: Bytes,
+        fee: u32,
+        receiver: Bytes,
+        pool: Bytes,
+        zero_for_one: bool,
+        transfer_type: TransferType,
+        exact_out: bool,
+    },
+    UniswapV4 {
+        group_token_in: Bytes,
+        group_token_out: Bytes,
+        zero_for_one: bool,
+        transfer_type: TransferType,
+        receiver: Bytes,
+        pools: Vec<UniswapV4Pool>,
+    },
+    BalancerV2 {
+        token_in: Bytes,
+        token_out: Bytes,
+        pool_id: Bytes,
+        receiver: Bytes,
+        approval_needed: bool,
+        transfer_type: TransferType,
+        user_data: Option<Bytes>,
+    },
+    Curve {
+        token_in: Bytes,
+        token_out: Bytes,
+        pool: Bytes,
+        pool_type: u8,
+        i: u8,
+        j: u8,
+        approval_needed: bool,
+        transfer_type: TransferType,
+        receiver: Bytes,
+        use_eth: bool,
+        user_data: Option<Bytes>,
+    },
+    Unknown(Bytes),
+}
+
+/// Decodes a call to the Tycho router (e.g. the data of a submitted transaction) back into its
+/// arguments and swaps. This is the inverse of `encode_tycho_router_call`, meant for monitoring and
+/// debugging.
+///
+/// The protocol of every swap is identified by its executor, with the executors of the given
+/// registry.
+///
+/// Returns an `EncodingError::InvalidInput` if the selector isn't one of a router swap function or
+/// if the calldata is malformed.
+pub fn decode_router_call(
+    calldata: &[u8],
+    swap_encoder_registry: &SwapEncoderRegistry,
+) -> Result<DecodedSolution, EncodingError> {
+    let selector = calldata.get(..4).ok_or_else(|| {
+        EncodingError::InvalidInput("The calldata is shorter than a function selector".to_string())
+    })?;
+    let function_signature = ROUTER_FUNCTIONS
+        .iter()
+        .find(|signature| encode_input(signature, vec![]) == selector)
+        .ok_or_else(|| {
+            EncodingError::InvalidInput(format!(
+                "Unknown Tycho router selector 0x{}",
+                alloy::hex::encode(selector)
+            ))
+        })?;
+    let (mut solution, encoded_swaps) = decode_arguments(function_signature, &calldata[4..])?;
+
+    let mut executor_protocols: HashMap<Bytes, Vec<String>> = HashMap::new();
+    for (protocol_system, executor) in swap_encoder_registry.executor_addresses() {
+        executor_protocols
+            .entry(executor)
+            .or_default()
+            .push(protocol_system);
+    }
+    for protocol_systems in executor_protocols.values_mut() {
+        protocol_systems.sort();
+    }
+    solution.swaps =
+        decode_swaps(solution.strategy, solution.indexed, &encoded_swaps, &executor_protocols)?;
+    Ok(solution)
+}
+
+/// The arguments of a router function call.
+///
+/// `transfer_from` is `None` for the `Permit2` functions, which always transfer the funds of the
+/// user with permit2.
+#[derive(Default)]
+struct RouterArguments {
+    given_amount: U256,
+    given_token: Address,
+    checked_token: Address,
+    checked_amount: U256,
+    wrap: bool,
+    unwrap: bool,
+    n_tokens: U256,
+    receiver: Address,
+    transfer_from: Option<bool>,
+    permit: Option<PermitSingle>,
+    permit_batch: Option<PermitBatch>,
+    signature: Option<AlloyBytes>,
+    swaps: AlloyBytes,
+}
+
+/// Decodes the ABI encoded arguments of the router function. Returns the solution without its
+/// swaps, and the encoded swaps.
+fn decode_arguments(
+    function_signature: &str,
+    args: &[u8],
+) -> Result<(DecodedSolution, AlloyBytes), EncodingError> {
+    let function_name = &function_signature[..function_signature
+        .find('(')
+        .unwrap_or(function_signature.len())];
+    let invalid_arguments = |err: alloy::sol_types::Error| {
+        EncodingError::InvalidInput(format!(
+            "Failed to decode the arguments of {function_name}: {err}"
+        ))
+    };
+    let strategy = if function_name.starts_with("sequential") {
+        DecodedStrategy::Sequential
+    } else if function_name.starts_with("split") {
+        DecodedStrategy::Split
+    } else {
+        DecodedStrategy::Single
+    };
+    let permit2 = function_name.contains("Permit2");
+
+    let arguments = if function_name == "exactOutSingleSwap" {
+        // The given token is bought and the checked token is sold, up to the checked amount
+        let (
+            given_amount,
+            checked_token,
+            given_token,
+            checked_amount,
+            unwrap,
+            receiver,
+            transfer_from,
+            swaps,
+        ) = ExactOutSwapArgs::abi_decode_params(args).map_err(invalid_arguments)?;
+        RouterArguments {
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            unwrap,
+            receiver,
+            transfer_from: Some(transfer_from),
+            swaps,
+            ..Default::default()
+        }
+    } else if function_name == "splitSwapPermit2Batch" {
+        let (
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            n_tokens,
+            receiver,
+            permit_batch,
+            signature,
+            swaps,
+        ) = SplitSwapPermit2BatchArgs::abi_decode_params(args).map_err(invalid_arguments)?;
+        // The solutions after the first one of a batch carry an empty permit
+        let has_permit = !permit_batch.details.is_empty();
+        RouterArguments {
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            n_tokens,
+            receiver,
+            permit_batch: has_permit.then_some(permit_batch),
+            signature: has_permit.then_some(signature),
+            swaps,
+            ..Default::default()
+        }
+    } else if strategy == DecodedStrategy::Split && permit2 {
+        let (
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            n_tokens,
+            receiver,
+            permit,
+            signature,
+            swaps,
+        ) = SplitSwapPermit2Args::abi_decode_params(args).map_err(invalid_arguments)?;
+        RouterArguments {
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            n_tokens,
+            receiver,
+            permit: Some(permit),
+            signature: Some(signature),
+            swaps,
+            ..Default::default()
+        }
+    } else if strategy == DecodedStrategy::Split {
+        let (
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            n_tokens,
+            receiver,
+            transfer_from,
+            swaps,
+        ) = SplitSwapArgs::abi_decode_params(args).map_err(invalid_arguments)?;
+        RouterArguments {
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            n_tokens,
+            receiver,
+            transfer_from: Some(transfer_from),
+            swaps,
+            ..Default::default()
+        }
+    } else if permit2 {
+        let (
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            receiver,
+            permit,
+            signature,
+            swaps,
+        ) = SwapPermit2Args::abi_decode_params(args).map_err(invalid_arguments)?;
+        RouterArguments {
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            receiver,
+            permit: Some(permit),
+            signature: Some(signature),
+            swaps,
+            ..Default::default()
+        }
+    } else {
+        let (
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            receiver,
+            transfer_from,
+            swaps,
+        ) = SwapArgs::abi_decode_params(args).map_err(invalid_arguments)?;
+        RouterArguments {
+            given_amount,
+            given_token,
+            checked_token,
+            checked_amount,
+            wrap,
+            unwrap,
+            receiver,
+            transfer_from: Some(transfer_from),
+            swaps,
+            ..Default::default()
+        }
+    };
+
+    let user_transfer_type = match arguments.transfer_from {
+        None => UserTransferType::TransferFromPermit2,
+        Some(true) => UserTransferType::TransferFrom,
+        Some(false) => UserTransferType::None,
+    };
+    let solution = DecodedSolution {
+        function_signature: function_signature.to_string(),
+        strategy,
+        indexed: function_name.ends_with("Indexed"),
+        exact_out: function_name == "exactOutSingleSwap",
+        given_amount: u256_to_biguint(arguments.given_amount),
+        given_token: address_to_bytes(arguments.given_token),
+        checked_token: address_to_bytes(arguments.checked_token),
+        checked_amount: u256_to_biguint(arguments.checked_amount),
+        wrap: arguments.wrap,
+        unwrap: arguments.unwrap,
+        n_tokens: usize::try_from(arguments.n_tokens).map_err(|_| {
+            EncodingError::InvalidInput(format!("Invalid number of tokens {}", arguments.n_tokens))
+        })?,
+        receiver: address_to_bytes(arguments.receiver),
+        user_transfer_type,
+        permit: arguments
+            .permit
+            .as_ref()
+            .map(models::PermitSingle::try_from)
+            .transpose()?,
+        permit_batch: arguments
+            .permit_batch
+            .as_ref()
+            .map(models::PermitBatch::from),
+        signature: arguments
+            .signature
+            .map(|signature| Bytes::from(signature.to_vec())),
+        swaps: vec![],
+    };
+    Ok((solution, arguments.swaps))
+}
+
+/// Decodes the encoded swaps of a router call.
+///
+/// Single swaps are encoded as the executor followed by the protocol data. The swaps of the other
+/// strategies are prefix-length encoded, with an extra header for split swaps, and refer to their
+/// executor by its index in the executor table preceding them if `indexed` is set.
+fn decode_swaps(
+    strategy: DecodedStrategy,
+    indexed: bool,
+    encoded_swaps: &[u8],
+    executor_protocols: &HashMap<Bytes, Vec<String>>,
+) -> Result<Vec<DecodedSwap>, EncodingError> {
+    if strategy == DecodedStrategy::Single {
+        let mut reader = PackedReader::new("single swap", encoded_swaps);
+        let executor = reader.address()?;
+        return Ok(vec![decode_swap(None, executor, reader.rest(), executor_protocols)?]);
+    }
+    let (executor_table, encoded_swaps) = if indexed {
+        let mut reader = PackedReader::new("executor table", encoded_swaps);
+        let n_executors = reader.u8()?;
+        let executors = (0..n_executors)
+            .map(|_| reader.address())
+            .collect::<Result<Vec<_>, _>>()?;
+        (Some(executors), reader.rest())
+    } else {
+        (None, encoded_swaps)
+    };
+    ple_decode(encoded_swaps)?
+        .iter()
+        .map(|encoded_swap| {
+            let mut reader = PackedReader::new("swap header", encoded_swap);
+            let split = if strategy == DecodedStrategy::Split {
+                Some(SplitHeader {
+                    token_in: reader.u8()?,
+                    token_out: reader.u8()?,
+                    split: reader.uint24()?,
+                })
+            } else {
+                None
+            };
+            let executor = match &executor_table {
+                Some(executors) => {
+                    let index = reader.u8()?;
+                    executors
+                        .get(index as usize)
+                        .cloned()
+                        .ok_or_else(|| {
+                            EncodingError::InvalidInput(format!(
+                                "Executor index {index} is out of the executor table of {} \
+                                 executors",
+                                executors.len()
+                            ))
+                        })?
+                }
+                None => reader.address()?,
+            };
+            decode_swap(split, executor, reader.rest(), executor_protocols)
+        })
+        .collect()
+}
+
+fn decode_swap(
+    split: Option<SplitHeader>,
+    executor: Bytes,
+    protocol_data: &[u8],
+    executor_protocols: &HashMap<Bytes, Vec<String>>,
+) -> Result<DecodedSwap, EncodingError> {
+    let protocol_systems = executor_protocols
+        .get(&executor)
+        .cloned()
+        .unwrap_or_default();
+    // Protocols sharing an executor share the layout of its protocol data
+    let protocol_data = match protocol_systems.first() {
+        Some(protocol_system) => decode_protocol_data(protocol_system, protocol_data)?,
+        None => DecodedProtocolData::Unknown(Bytes::from(protocol_data.to_vec())),
+    };
+    Ok(DecodedSwap { split, executor, protocol_systems, protocol_data })
+}
+
+/// Decodes the protocol data encoded by the swap encoder of the given protocol system.
+///
+/// Protocols without a decoder return their raw protocol data (`DecodedProtocolData::Unknown`).
+/// Returns an `EncodingError::InvalidInput` if the data doesn't match the layout of the protocol.
+pub fn decode_protocol_data(
+    protocol_system: &str,
+    protocol_data: &[u8],
+) -> Result<DecodedProtocolData, EncodingError> {
+    let mut reader = PackedReader::new(protocol_system, protocol_data);
+    let decoded = match protocol_system {
+        "uniswap_v2" | "sushiswap_v2" | "pancakeswap_v2" => {
+            let token_in = reader.address()?;
+            let pool = reader.address()?;
+            let receiver = reader.address()?;
+            let zero_for_one = reader.bool()?;
+            let transfer_type = reader.transfer_type()?;
+            let (exact_out, fee_on_transfer) = reader.uniswap_v2_mode()?;
+            DecodedProtocolData::UniswapV2 {
+                token_in,
+                pool,
+                receiver,
+                zero_for_one,
+                transfer_type,
+                exact_out,
+                fee_on_transfer,
+            }
+        }
+        "uniswap_v3" | "pancakeswap_v3" => DecodedProtocolData::UniswapV3 {
+            token_in: reader.address()?,
+            token_out: reader.address()?,
+            fee: reader.uint24()?,
+            receiver: reader.address()?,
+            pool: reader.address()?,
+            zero_for_one: reader.bool()?,
+            transfer_type: reader.transfer_type()?,
+            exact_out: reader.exact_out_flag()?,
+        },
+        "uniswap_v4" => {
+            let group_token_in = reader.address()?;
+            let group_token_out = reader.address()?;
+            let zero_for_one = reader.bool()?;
+            let transfer_type = reader.transfer_type()?;
+            let receiver = reader.address()?;
+            let mut pools = vec![reader.uniswap_v4_pool()?];
+            // The pools of the other swaps of the group are prefix-length encoded
+            for encoded_pool in ple_decode(reader.rest())? {
+                let mut pool_reader = PackedReader::new(protocol_system, &encoded_pool);
+                pools.push(pool_reader.uniswap_v4_pool()?);
+                pool_reader.finish()?;
+            }
+            DecodedProtocolData::UniswapV4 {
+                group_token_in,
+                group_token_out,
+                zero_for_one,
+                transfer_type,
+                receiver,
+                pools,
+            }
+        }
+        "vm:balancer_v2" => DecodedProtocolData::BalancerV2 {
+            token_in: reader.address()?,
+            token_out: reader.address()?,
+            pool_id: Bytes::from(reader.take(32)?.to_vec()),
+            receiver: reader.address()?,
+            approval_needed: reader.bool()?,
+            transfer_type: reader.transfer_type()?,
+            user_data: reader.user_data()?,
+        },
+        "vm:curve" => DecodedProtocolData::Curve {
+            token_in: reader.address()?,
+            token_out: reader.address()?,
+            pool: reader.address()?,
+            pool_type: reader.u8()?,
+            i: reader.u8()?,
+            j: reader.u8()?,
+            approval_needed: reader.bool()?,
+            transfer_type: reader.transfer_type()?,
+            receiver: reader.address()?,
+            use_eth: reader.bool()?,
+            user_data: reader.user_data()?,
+        },
+        _ => DecodedProtocolData::Unknown(Bytes::from(reader.rest().to_vec())),
+    };
+    reader.finish()?;
+    Ok(decoded)
+}
+
+/// Reads the fields of packed encoded data (`abi.encodePacked`), in order.
+struct PackedReader<'a> {
+    context: &'a str,
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> PackedReader<'a> {
+    fn new(context: &'a str, data: &'a [u8]) -> Self {
+        Self { context, data, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], EncodingError> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + len)
+            .ok_or_else(|| {
+                EncodingError::InvalidInput(format!(
+                    "Truncated {} data: expected {len} bytes at offset {} of {} bytes",
+                    self.context,
+                    self.offset,
+                    self.data.len()
+                ))
+            })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn address(&mut self) -> Result<Bytes, EncodingError> {
+        Ok(Bytes::from(self.take(20)?.to_vec()))
+    }
+
+    fn u8(&mut self) -> Result<u8, EncodingError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn uint24(&mut self) -> Result<u32, EncodingError> {
+        let bytes = self.take(3)?;
+        Ok(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+    }
+
+    fn int24(&mut self) -> Result<i32, EncodingError> {
+        // Sign-extends the 24 bits value
+        Ok(((self.uint24()? << 8) as i32) >> 8)
+    }
+
+    fn bool(&mut self) -> Result<bool, EncodingError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(EncodingError::InvalidInput(format!(
+                "Invalid boolean {value} in {} data",
+                self.context
+            ))),
+        }
+    }
+
+    fn transfer_type(&mut self) -> Result<TransferType, EncodingError> {
+        match self.u8()? {
+            0 => Ok(TransferType::TransferFrom),
+            1 => Ok(TransferType::Transfer),
+            2 => Ok(TransferType::None),
+            value => Err(EncodingError::InvalidInput(format!(
+                "Invalid transfer type {value} in {} data",
+                self.context
+            ))),
+        }
+    }
+
+    /// Reads the flag that swap encoders only append to exact out swaps.
+    fn exact_out_flag(&mut self) -> Result<bool, EncodingError> {
+        if self.offset == self.data.len() {
+            return Ok(false);
+        }
+        self.bool()
+    }
+
+    /// Reads the mode byte that the Uniswap V2 encoder only appends to exact out and
+    /// fee-on-transfer swaps, as the exact out and fee-on-transfer flags.
+    fn uniswap_v2_mode(&mut self) -> Result<(bool, bool), EncodingError> {
+        if self.offset == self.data.len() {
+            return Ok((false, false));
+        }
+        match self.u8()? {
+            0 => Ok((false, false)),
+            1 => Ok((true, false)),
+            2 => Ok((false, true)),
+            value => Err(EncodingError::InvalidInput(format!(
+                "Invalid mode {value} in {} data",
+                self.context
+            ))),
+        }
+    }
+
+    /// Reads the user data segment (`user_data_length (u16) | user_data`) that swap encoders only
+    /// append to swaps with user data.
+    fn user_data(&mut self) -> Result<Option<Bytes>, EncodingError> {
+        if self.offset == self.data.len() {
+            return Ok(None);
+        }
+        let user_data_len = self.take(2)?;
+        let user_data_len = u16::from_be_bytes([user_data_len[0], user_data_len[1]]) as usize;
+        Ok(Some(Bytes::from(self.take(user_data_len)?.to_vec())))
+    }
+
+    fn uniswap_v4_pool(&mut self) -> Result<UniswapV4Pool, EncodingError> {
+        let token_out = self.address()?;
+        let fee = self.uint24()?;
+        let tick_spacing = self.int24()?;
+        let hook = self.address()?;
+        let hook_data_len = self.take(2)?;
+        let hook_data_len = u16::from_be_bytes([hook_data_len[0], hook_data_len[1]]) as usize;
+        let hook_data = Bytes::from(self.take(hook_data_len)?.to_vec());
+        Ok(UniswapV4Pool { token_out, fee, tick_spacing, hook, hook_data })
+    }
+
+    /// Reads all the remaining bytes.
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.offset..];
+        self.offset = self.data.len();
+        rest
+    }
+
+    /// Raises an error if there are bytes left after the last field.
+    fn finish(&self) -> Result<(), EncodingError> {
+        if self.offset != self.data.len() {
+            return Err(EncodingError::InvalidInput(format!(
+                "{} unexpected trailing bytes in {} data",
+                self.data.len() - self.offset,
+                self.context
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn address_to_bytes(address: Address) -> Bytes {
+    Bytes::from(address.to_vec())
+}
+
+fn u256_to_biguint(value: U256) -> BigUint {
+    BigUint::from_bytes_be(&value.to_be_bytes::<32>())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, str::FromStr};
+
+    use alloy::hex;
+    use num_bigint::BigInt;
+    use tycho_common::models::{protocol::ProtocolComponent, Chain};
+
+    use super::*;
+    use crate::encoding::{
+        evm::tycho_encoders::TychoRouterEncoder,
+        models::{Solution, Swap},
+        tycho_encoder::TychoEncoder,
+    };
+
+    fn weth() -> Bytes {
+        Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap()
+    }
+
+    fn usdc() -> Bytes {
+        Bytes::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap()
+    }
+
+    fn dai() -> Bytes {
+        Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap()
+    }
+
+    fn pepe() -> Bytes {
+        Bytes::from_str("0x6982508145454Ce325dDbE47a25d4ec3d2311933").unwrap()
+    }
+
+    fn receiver() -> Bytes {
+        Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap()
+    }
+
+    fn get_swap_encoder_registry() -> SwapEncoderRegistry {
+        let executors_addresses =
+            fs::read_to_string("config/test_executor_addresses.json").unwrap();
+        SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap()
+    }
+
+    fn get_tycho_router_encoder() -> TychoRouterEncoder {
+        TychoRouterEncoder::new(
+            Chain::Ethereum,
+            get_swap_encoder_registry(),
+            Bytes::from_str("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66").unwrap(),
+            UserTransferType::TransferFrom,
+            None,
+            false,
+        )
+        .unwrap()
+    }
+
+    fn swap_univ2(pool: &str, token_in: Bytes, token_out: Bytes) -> Swap {
+        Swap::new(
+            ProtocolComponent {
+                id: pool.to_string(),
+                protocol_system: "uniswap_v2".to_string(),
+                ..Default::default()
+            },
+            token_in,
+            token_out,
+        )
+    }
+
+    fn swap_weth_usdc_univ3() -> Swap {
+        Swap::new(
+            ProtocolComponent {
+                id: "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640".to_string(),
+                protocol_system: "uniswap_v3".to_string(),
+                static_attributes: HashMap::from([(
+                    "fee".to_string(),
+                    Bytes::from(BigInt::from(500).to_signed_bytes_be()),
+                )]),
+                ..Default::default()
+            },
+            weth(),
+            usdc(),
+        )
+    }
+
+    fn swap_univ4(
+        pool: &str,
+        token_in: Bytes,
+        token_out: Bytes,
+        fee: u32,
+        tick_spacing: i32,
+    ) -> Swap {
+        Swap::new(
+            ProtocolComponent {
+                id: pool.to_string(),
+                protocol_system: "uniswap_v4".to_string(),
+                static_attributes: HashMap::from([
+                    ("key_lp_fee".to_string(), Bytes::from(BigInt::from(fee).to_signed_bytes_be())),
+                    (
+                        "tick_spacing".to_string(),
+                        Bytes::from(BigInt::from(tick_spacing).to_signed_bytes_be()),
+                    ),
+                ]),
+                ..Default::default()
+            },
+            token_in,
+            token_out,
+        )
+    }
+
+    #[allow(deprecated)]
+    fn encode_and_decode(encoder: &TychoRouterEncoder, solution: Solution) -> DecodedSolution {
+        let transactions = encoder
+            .encode_full_calldata(vec![solution])
+            .unwrap();
+        decode_router_call(&transactions[0].data, &get_swap_encoder_registry()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_single_swap() {
+        let solution = Solution {
+            given_token: weth(),
+            given_amount: BigUint::from(1_000_000_000_000_000_000u64),
+            checked_token: dai(),
+            checked_amount: BigUint::from(2_000_000_000_000_000_000u64),
+            sender: receiver(),
+            receiver: receiver(),
+            swaps: vec![swap_univ2("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11", weth(), dai())],
+            ..Default::default()
+        };
+
+        let decoded = encode_and_decode(&get_tycho_router_encoder(), solution.clone());
+
+        assert!(decoded
+            .function_signature
+            .starts_with("singleSwap("));
+        assert_eq!(decoded.strategy, DecodedStrategy::Single);
+        assert!(!decoded.indexed && !decoded.exact_out && !decoded.wrap && !decoded.unwrap);
+        assert_eq!(decoded.given_amount, solution.given_amount);
+        assert_eq!(decoded.given_token, weth());
+        assert_eq!(decoded.checked_token, dai());
+        assert_eq!(decoded.checked_amount, solution.checked_amount);
+        assert_eq!(decoded.receiver, receiver());
+        assert_eq!(decoded.user_transfer_type, UserTransferType::TransferFrom);
+        assert_eq!(decoded.permit, None);
+        assert_eq!(
+            decoded.swaps,
+            vec![DecodedSwap {
+                split: None,
+                executor: Bytes::from_str("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f").unwrap(),
+                protocol_systems: vec![
+                    "pancakeswap_v2".to_string(),
+                    "sushiswap_v2".to_string(),
+                    "uniswap_v2".to_string()
+                ],
+                protocol_data: DecodedProtocolData::UniswapV2 {
+                    token_in: weth(),
+                    pool: Bytes::from_str("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11").unwrap(),
+                    receiver: receiver(),
+                    zero_for_one: false,
+                    transfer_type: TransferType::TransferFrom,
+                    exact_out: false,
+                    fee_on_transfer: false,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_single_swap_group() {
+        let eth = Bytes::zero(20);
+        let solution = Solution {
+            given_token: usdc(),
+            given_amount: BigUint::from(1_000_000_000u64),
+            checked_token: pepe(),
+            checked_amount: BigUint::from(1u32),
+            sender: receiver(),
+            receiver: receiver(),
+            swaps: vec![
+                swap_univ4(
+                    "0xdce6394339af00981949f5f3baf27e3610c76326a700af57e4b3e3ae4977f78d",
+                    usdc(),
+                    eth.clone(),
+                    3000,
+                    60,
+                ),
+                swap_univ4(
+                    "0xecd73ecbf77219f21f129c8836d5d686bbc27d264742ddad620500e3e548e2c9",
+                    eth.clone(),
+                    pepe(),
+                    25000,
+                    500,
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let decoded = encode_and_decode(&get_tycho_router_encoder(), solution);
+
+        assert_eq!(decoded.strategy, DecodedStrategy::Single);
+        assert_eq!(decoded.swaps.len(), 1);
+        assert_eq!(decoded.swaps[0].protocol_systems, vec!["uniswap_v4".to_string()]);
+        let DecodedProtocolData::UniswapV4 { group_token_in, group_token_out, pools, .. } =
+            &decoded.swaps[0].protocol_data
+        else {
+            panic!("Expected a Uniswap V4 swap, got {:?}", decoded.swaps[0].protocol_data);
+        };
+        assert_eq!(*group_token_in, usdc());
+        assert_eq!(*group_token_out, pepe());
+        assert_eq!(
+            *pools,
+            vec![
+                UniswapV4Pool {
+                    token_out: eth.clone(),
+                    fee: 3000,
+                    tick_spacing: 60,
+                    hook: Bytes::zero(20),
+                    hook_data: Bytes::default(),
+                },
+                UniswapV4Pool {
+                    token_out: pepe(),
+                    fee: 25000,
+                    tick_spacing: 500,
+                    hook: Bytes::zero(20),
+                    hook_data: Bytes::default(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_sequential_swap_with_executor_table() {
+        let solution = Solution {
+            given_token: weth(),
+            given_amount: BigUint::from(1_000_000_000_000_000_000u64),
+            checked_token: dai(),
+            checked_amount: BigUint::from(1u32),
+            sender: receiver(),
+            receiver: receiver(),
+            swaps: vec![
+                swap_weth_usdc_univ3(),
+                swap_univ2("0xAE461cA67B15dc8dc81CE7615e0320dA1A9aB8D5", usdc(), dai()),
+            ],
+            ..Default::default()
+        };
+
+        let decoded =
+            encode_and_decode(&get_tycho_router_encoder().with_executor_table(), solution);
+
+        assert!(decoded
+            .function_signature
+            .starts_with("sequentialSwapIndexed("));
+        assert_eq!(decoded.strategy, DecodedStrategy::Sequential);
+        assert!(decoded.indexed);
+        let executors: Vec<Bytes> = decoded
+            .swaps
+            .iter()
+            .map(|swap| swap.executor.clone())
+            .collect();
+        assert_eq!(
+            executors,
+            vec![
+                Bytes::from_str("0x2e234DAe75C793f67A35089C9d99245E1C58470b").unwrap(),
+                Bytes::from_str("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f").unwrap(),
+            ]
+        );
+        assert!(matches!(
+            &decoded.swaps[0].protocol_data,
+            DecodedProtocolData::UniswapV3 { token_in, token_out, fee: 500, pool, .. }
+                if *token_in == weth() && *token_out == usdc() &&
+                    *pool == Bytes::from_str("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640").unwrap()
+        ));
+        assert!(matches!(
+            &decoded.swaps[1].protocol_data,
+            DecodedProtocolData::UniswapV2 { token_in, receiver: swap_receiver, .. }
+                if *token_in == usdc() && *swap_receiver == receiver()
+        ));
+    }
+
+    #[test]
+    fn test_decode_split_swap() {
+        let solution = Solution {
+            given_token: weth(),
+            given_amount: BigUint::from(1_000_000_000_000_000_000u64),
+            checked_token: usdc(),
+            checked_amount: BigUint::from(1u32),
+            sender: receiver(),
+            receiver: receiver(),
+            swaps: vec![
+                swap_weth_usdc_univ3().split(0.5),
+                swap_univ2("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc", weth(), usdc()),
+            ],
+            ..Default::default()
+        };
+
+        let decoded = encode_and_decode(&get_tycho_router_encoder(), solution);
+
+        assert!(decoded
+            .function_signature
+            .starts_with("splitSwap("));
+        assert_eq!(decoded.strategy, DecodedStrategy::Split);
+        assert_eq!(decoded.n_tokens, 2);
+        let splits: Vec<Option<SplitHeader>> = decoded
+            .swaps
+            .iter()
+            .map(|swap| swap.split)
+            .collect();
+        assert_eq!(
+            splits,
+            vec![
+                Some(SplitHeader { token_in: 0, token_out: 1, split: 8_388_608 }),
+                Some(SplitHeader { token_in: 0, token_out: 1, split: 0 }),
+            ]
+        );
+        assert!(matches!(decoded.swaps[0].protocol_data, DecodedProtocolData::UniswapV3 { .. }));
+        assert!(matches!(decoded.swaps[1].protocol_data, DecodedProtocolData::UniswapV2 { .. }));
+    }
+
+    #[test]
+    fn test_decode_balancer_v2_protocol_data() {
+        // Encoded by `BalancerV2SwapEncoder` (see `test_encode_balancer_v2`)
+        let protocol_data = hex::decode(concat!(
+            "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            "ba100000625a3754423978a60c9317c58a424e3d",
+            "5c6ee304399dbdb9c8ef030ab642b10820db8f56000200000000000000000014",
+            "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+            "01",
+            "02"
+        ))
+        .unwrap();
+
+        let decoded = decode_protocol_data("vm:balancer_v2", &protocol_data).unwrap();
+
+        assert_eq!(
+            decoded,
+            DecodedProtocolData::BalancerV2 {
+                token_in: weth(),
+                token_out: Bytes::from_str("0xba100000625a3754423978a60c9317c58a424e3D").unwrap(),
+                pool_id: Bytes::from_str(
+                    "0x5c6ee304399dbdb9c8ef030ab642b10820db8f56000200000000000000000014"
+                )
+                .unwrap(),
+                receiver: Bytes::from_str("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de").unwrap(),
+                approval_needed: true,
+                transfer_type: TransferType::None,
+                user_data: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_curve_protocol_data() {
+        // Encoded by `CurveSwapEncoder` (see `test_curve_encode_tripool`)
+        let protocol_data = hex::decode(concat!(
+            "6b175474e89094c44da98b954eedeac495271d0f",
+            "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "bebc44782c7db0a1a60cb6fe97d0b483032ff1c7",
+            "01",
+            "00",
+            "01",
+            "01",
+            "02",
+            "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+            "00",
+        ))
+        .unwrap();
+
+        let decoded = decode_protocol_data("vm:curve", &protocol_data).unwrap();
+
+        assert_eq!(
+            decoded,
+            DecodedProtocolData::Curve {
+                token_in: dai(),
+                token_out: usdc(),
+                pool: Bytes::from_str("0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7").unwrap(),
+                pool_type: 1,
+                i: 0,
+                j: 1,
+                approval_needed: true,
+                transfer_type: TransferType::None,
+                receiver: Bytes::from_str("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de").unwrap(),
+                use_eth: false,
+                user_data: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_curve_protocol_data_with_user_data() {
+        let protocol_data = hex::decode(concat!(
+            "6b175474e89094c44da98b954eedeac495271d0f",
+            "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "bebc44782c7db0a1a60cb6fe97d0b483032ff1c7",
+            "01",
+            "00",
+            "01",
+            "01",
+            "02",
+            "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+            "00",
+            // user data length
+            "0002",
+            // user data
+            "abcd",
+        ))
+        .unwrap();
+
+        let decoded = decode_protocol_data("vm:curve", &protocol_data).unwrap();
+        assert!(matches!(
+            decoded,
+            DecodedProtocolData::Curve { user_data: Some(user_data), .. }
+                if user_data == Bytes::from("0xabcd")
+        ));
+
+        // The user data segment must match its length
+        let result = decode_protocol_data("vm:curve", &protocol_data[..protocol_data.len() - 1]);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_decode_invalid_calldata() {
+        let registry = get_swap_encoder_registry();
+
+        assert!(matches!(
+            decode_router_call(&[0x01, 0x02, 0x03, 0x04], &registry),
+            Err(EncodingError::InvalidInput(msg)) if msg.starts_with("Unknown Tycho router selector")
+        ));
+        assert!(matches!(
+            decode_protocol_data("uniswap_v2", &[0u8; 30]),
+            Err(EncodingError::InvalidInput(msg)) if msg.starts_with("Truncated uniswap_v2 data")
+        ));
+        assert!(matches!(
+            decode_protocol_data("uniswap_v2", &[0u8; 65]),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("trailing bytes")
+        ));
+    }
+}
@@ -0,0 +1,390 @@
+use alloy::{
+    primitives::{Address, U256},
+    sol_types::SolValue,
+};
+use num_bigint::BigUint;
+use tycho_common::Bytes;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        component_id::component_pool_address,
+        constants::DIRECT_EXECUTION_PROTOCOLS,
+        encoding_utils::encode_input,
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+        utils::{append_user_data, biguint_to_u256, bytes_to_address, summarize_encoded_swaps},
+    },
+    models::{
+        ApprovalPolicy, EncodedSolution, EncodingContext, Solution, Transaction, TransferType,
+    },
+    swap_encoder::SwapEncoder,
+    tycho_encoder::TychoEncoder,
+};
+
+/// Signature of the executor function performing a swap (see `IExecutor`).
+const EXECUTOR_SWAP: &str = "swap(uint256,bytes)";
+/// Signature of the Uniswap V2 pair function sending the amounts out of a swap.
+const UNISWAP_V2_PAIR_SWAP: &str = "swap(uint256,uint256,address,bytes)";
+
+/// Encodes single swaps on Uniswap V2 and V3 pools (and their forks) to be executed without the
+/// Tycho router, by a contract holding the funds itself.
+///
+/// The encoded solutions target the executor of their swap: `interacting_with` is the executor
+/// address and `swaps` the data of its `swap(uint256,bytes)` function. Like the router, the
+/// contract must delegate call the executor, which pays the pool from the contract's balance (and
+/// handles the Uniswap V3 callback, which the contract must forward to the executor).
+///
+/// Uniswap V2 pairs can also be called directly (see `encode_pool_call`), saving the executor call.
+///
+/// There are no router guardrails: the amount out is not checked and no native token is wrapped or
+/// unwrapped, so the calling contract is responsible for verifying the amount received.
+#[derive(Clone)]
+pub struct DirectExecutionEncoder {
+    swap_encoder_registry: SwapEncoderRegistry,
+}
+
+impl DirectExecutionEncoder {
+    pub fn new(swap_encoder_registry: SwapEncoderRegistry) -> Result<Self, EncodingError> {
+        Ok(Self { swap_encoder_registry })
+    }
+
+    /// Returns the swap encoder of the only swap of the solution.
+    fn swap_encoder(&self, solution: &Solution) -> Result<&dyn SwapEncoder, EncodingError> {
+        let protocol_system = &solution.swaps[0]
+            .component()
+            .protocol_system;
+        self.swap_encoder_registry
+            .get_encoder(protocol_system)
+            .map(|swap_encoder| swap_encoder.as_ref())
+            .ok_or_else(|| {
+                EncodingError::InvalidInput(format!(
+                    "Swap encoder not found for protocol: {protocol_system}"
+                ))
+            })
+    }
+
+    /// Encodes the data of the executor swap of a valid solution.
+    fn encode_executor_data(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
+        let swap = &solution.swaps[0];
+        let swap_encoder = self.swap_encoder(solution)?;
+        let encoding_context = EncodingContext {
+            receiver: solution.receiver.clone(),
+            exact_out: false,
+            router_address: None,
+            group_token_in: swap.token_in().clone(),
+            group_token_out: swap.token_out().clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: solution.execution_deadline,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let protocol_data = append_user_data(
+            swap_encoder,
+            swap,
+            swap_encoder.encode_swap(swap, &encoding_context)?,
+        )?;
+        let summary =
+            summarize_encoded_swaps("", &protocol_data, 1, &[encoding_context.transfer_type]);
+        Ok(EncodedSolution {
+            swaps: protocol_data,
+            interacting_with: swap_encoder.group_executor_address(&solution.swaps)?,
+            function_signature: EXECUTOR_SWAP.to_string(),
+            n_tokens: 0,
+            permit: None,
+            permit_batch: None,
+            native_action: None,
+            state_versions: solution.state_versions(),
+            callback_allowlist: None,
+            summary,
+            gas_estimate: self
+                .swap_encoder_registry
+                .gas_estimate(&swap.component().protocol_system),
+            debug_info: None,
+            degraded_groups: vec![],
+        })
+    }
+
+    /// Encodes the call of the Uniswap V2 pair of the solution, sending its checked amount to the
+    /// receiver.
+    ///
+    /// The given amount must have been transferred to the pair earlier in the same transaction, as
+    /// the pair only checks its balances after sending the amount out. The checked amount is thus
+    /// the exact amount bought: it must not exceed the amount out of the pair for the given amount.
+    ///
+    /// Returns an `EncodingError::InvalidInput` if the solution is not a valid single swap on a
+    /// Uniswap V2 pair (or fork).
+    pub fn encode_pool_call(&self, solution: &Solution) -> Result<Transaction, EncodingError> {
+        self.validate_solution(solution)?;
+        let swap = &solution.swaps[0];
+        let protocol_system = &swap.component().protocol_system;
+        if DIRECT_EXECUTION_PROTOCOLS.get(protocol_system.as_str()) != Some(&true) {
+            return Err(EncodingError::InvalidInput(format!(
+                "The pools of {protocol_system} can't be called directly"
+            )));
+        }
+        let amount_out = biguint_to_u256(&solution.checked_amount);
+        let zero_to_one = bytes_to_address(swap.token_in())? < bytes_to_address(swap.token_out())?;
+        let (amount0_out, amount1_out) =
+            if zero_to_one { (U256::ZERO, amount_out) } else { (amount_out, U256::ZERO) };
+        let args = (
+            amount0_out,
+            amount1_out,
+            bytes_to_address(&solution.receiver)?,
+            alloy::primitives::Bytes::new(),
+        );
+        Ok(Transaction {
+            to: Bytes::from(
+                component_pool_address(swap.component())?
+                    .as_slice()
+                    .to_vec(),
+            ),
+            value: BigUint::ZERO,
+            data: encode_input(UNISWAP_V2_PAIR_SWAP, args.abi_encode_params()),
+        })
+    }
+}
+
+impl TychoEncoder for DirectExecutionEncoder {
+    fn encode_solutions(
+        &self,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        solutions
+            .iter()
+            .map(|solution| {
+                self.validate_solution(solution)?;
+                self.encode_executor_data(solution)
+            })
+            .collect()
+    }
+
+    /// Encodes the executor calls of the solutions, swapping their given amount.
+    fn encode_full_calldata(
+        &self,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<Transaction>, EncodingError> {
+        solutions
+            .iter()
+            .map(|solution| {
+                self.validate_solution(solution)?;
+                let encoded_solution = self.encode_executor_data(solution)?;
+                let args = (
+                    biguint_to_u256(&solution.given_amount),
+                    alloy::primitives::Bytes::from(encoded_solution.swaps),
+                );
+                Ok(Transaction {
+                    to: encoded_solution.interacting_with,
+                    value: BigUint::ZERO,
+                    data: encode_input(EXECUTOR_SWAP, args.abi_encode_params()),
+                })
+            })
+            .collect()
+    }
+
+    /// Raises an `EncodingError` if the solution is not considered valid.
+    ///
+    /// A solution is considered valid if all the following conditions are met:
+    /// * The solution is an exact in solution with exactly one swap, on a protocol supporting
+    ///   direct execution (see `DIRECT_EXECUTION_PROTOCOLS`).
+    /// * The swap sells the given token for the checked token.
+    /// * The solution has no native action, integrator fee or additional given tokens, which
+    ///   require the router.
+    fn validate_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
+        let [swap] = solution.swaps.as_slice() else {
+            return Err(EncodingError::InvalidInput(format!(
+                "Direct execution only supports exactly one swap. Found {}",
+                solution.swaps.len()
+            )));
+        };
+        let protocol_system = &swap.component().protocol_system;
+        if !DIRECT_EXECUTION_PROTOCOLS.contains_key(protocol_system.as_str()) {
+            return Err(EncodingError::InvalidInput(format!(
+                "Direct execution is not supported for protocol {protocol_system}"
+            )));
+        }
+        if solution.exact_out {
+            return Err(EncodingError::InvalidInput(
+                "Direct execution only supports exact in solutions".to_string(),
+            ));
+        }
+        if *swap.token_in() != solution.given_token || *swap.token_out() != solution.checked_token {
+            return Err(EncodingError::InvalidInput(
+                "The swap must sell the given token for the checked token".to_string(),
+            ));
+        }
+        if solution.native_action.is_some() ||
+            solution.fee.is_some() ||
+            !solution
+                .additional_given_tokens
+                .is_empty()
+        {
+            return Err(EncodingError::InvalidInput(
+                "Native actions, fees and additional given tokens require the router".to_string(),
+            ));
+        }
+        if bytes_to_address(&solution.receiver)? == Address::ZERO {
+            return Err(EncodingError::InvalidInput(
+                "The receiver can't be the zero address".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, str::FromStr};
+
+    use alloy::hex::encode;
+    use tycho_common::models::{protocol::ProtocolComponent, Chain};
+
+    use super::*;
+    use crate::encoding::models::{NativeAction, Swap};
+
+    fn weth() -> Bytes {
+        Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap()
+    }
+
+    fn dai() -> Bytes {
+        Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap()
+    }
+
+    fn encoder() -> DirectExecutionEncoder {
+        let executors_addresses =
+            fs::read_to_string("config/test_executor_addresses.json").unwrap();
+        let swap_encoder_registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap();
+        DirectExecutionEncoder::new(swap_encoder_registry).unwrap()
+    }
+
+    fn solution(protocol_system: &str) -> Solution {
+        Solution {
+            given_token: weth(),
+            given_amount: BigUint::from(1_000000000000000000u64),
+            checked_token: dai(),
+            checked_amount: BigUint::from(2000_000000000000000000u128),
+            sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+            // The receiver was generated with `makeAddr("bob") using forge`
+            receiver: Bytes::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
+            swaps: vec![Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: protocol_system.to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                dai(),
+            )],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_direct_execution_encode_solutions() {
+        let encoded_solution = encoder()
+            .encode_solutions(vec![solution("uniswap_v2")])
+            .unwrap()
+            .remove(0);
+
+        assert_eq!(
+            encoded_solution.interacting_with,
+            Bytes::from_str("0x5615deb798bb3e4dfa0139dfa1b3d433cc23b72f").unwrap()
+        );
+        assert_eq!(encoded_solution.function_signature, "swap(uint256,bytes)");
+        assert_eq!(
+            encode(&encoded_solution.swaps),
+            String::from(concat!(
+                // in token
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                // component id
+                "a478c2975ab1ea89e8196811f51a7b7ade33eb11",
+                // receiver
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // zero for one
+                "00",
+                // transfer type Transfer
+                "01",
+            ))
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_direct_execution_encode_full_calldata() {
+        let solution = solution("uniswap_v2");
+        let encoded_solution = encoder()
+            .encode_solutions(vec![solution.clone()])
+            .unwrap()
+            .remove(0);
+
+        let transaction = encoder()
+            .encode_full_calldata(vec![solution])
+            .unwrap()
+            .remove(0);
+
+        assert_eq!(transaction.to, encoded_solution.interacting_with);
+        assert_eq!(transaction.value, BigUint::ZERO);
+        assert_eq!(
+            transaction.data,
+            encode_input(
+                EXECUTOR_SWAP,
+                (
+                    U256::from(1_000000000000000000u64),
+                    alloy::primitives::Bytes::from(encoded_solution.swaps)
+                )
+                    .abi_encode_params()
+            )
+        );
+    }
+
+    #[test]
+    fn test_direct_execution_encode_pool_call() {
+        let transaction = encoder()
+            .encode_pool_call(&solution("uniswap_v2"))
+            .unwrap();
+
+        // WETH is token1 of the pair, so DAI (token0) is sent out
+        let expected_args = (
+            U256::from(2000_000000000000000000u128),
+            U256::ZERO,
+            Address::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
+            alloy::primitives::Bytes::new(),
+        );
+        assert_eq!(
+            transaction.to,
+            Bytes::from_str("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11").unwrap()
+        );
+        assert_eq!(
+            transaction.data,
+            encode_input(UNISWAP_V2_PAIR_SWAP, expected_args.abi_encode_params())
+        );
+
+        // Uniswap V3 pools call back the payer, so they can't be called directly
+        let result = encoder().encode_pool_call(&solution("uniswap_v3"));
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_direct_execution_validate_solution() {
+        let encoder = encoder();
+        assert!(encoder
+            .validate_solution(&solution("uniswap_v3"))
+            .is_ok());
+
+        let unsupported_protocol = solution("uniswap_v4");
+        let two_swaps = Solution {
+            swaps: vec![solution("uniswap_v2").swaps[0].clone(); 2],
+            ..solution("uniswap_v2")
+        };
+        let exact_out = Solution { exact_out: true, ..solution("uniswap_v2") };
+        let unwrap =
+            Solution { native_action: Some(NativeAction::Unwrap), ..solution("uniswap_v2") };
+        for invalid in [unsupported_protocol, two_swaps, exact_out, unwrap] {
+            assert!(matches!(
+                encoder.validate_solution(&invalid),
+                Err(EncodingError::InvalidInput(_))
+            ));
+        }
+    }
+}
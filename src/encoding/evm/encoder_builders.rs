@@ -1,4 +1,4 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use alloy::{primitives::B256, signers::local::PrivateKeySigner};
 use tycho_common::{models::Chain, Bytes};
@@ -6,11 +6,26 @@ use tycho_common::{models::Chain, Bytes};
 use crate::encoding::{
     errors::EncodingError,
     evm::{
+        approvals::permit_signer::PermitSigner,
+        audit::AuditLog,
+        callback_allowlist::CallbackAllowlist,
+        calldata_budget::CalldataBudget,
+        checked_amount_guard::CheckedAmountGuard,
         constants::DEFAULT_ROUTERS_JSON,
-        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
-        tycho_encoders::{TychoExecutorEncoder, TychoRouterEncoder},
+        environments::{
+            validate_environment, validate_environments, verify_code_hashes, verify_code_presence,
+            EnvironmentConfig,
+        },
+        idempotency::IdempotencyCache,
+        staleness::StalenessGuard,
+        strategy_encoder::wrap_adapter::WrapVariant,
+        support_matrix::SupportMatrix,
+        swap_encoder::swap_encoder_registry::{SharedSwapEncoderRegistry, SwapEncoderRegistry},
+        token_safety::TokenSafetyChecker,
+        tycho_encoders::{MultiChainTychoRouterEncoder, TychoExecutorEncoder, TychoRouterEncoder},
     },
-    models::UserTransferType,
+    metrics::EncodingMetrics,
+    models::{ApprovalPolicy, Environment, RfqConfig, SlippageConfig, UserTransferType},
     tycho_encoder::TychoEncoder,
 };
 
@@ -21,9 +36,38 @@ pub struct TychoRouterEncoderBuilder {
     chain: Option<Chain>,
     user_transfer_type: Option<UserTransferType>,
     swap_encoder_registry: Option<SwapEncoderRegistry>,
+    shared_registry: Option<SharedSwapEncoderRegistry>,
     router_address: Option<Bytes>,
     swapper_pk: Option<String>,
     historical_trade: bool,
+    audit_log: Option<AuditLog>,
+    idempotency_cache: Option<Arc<IdempotencyCache>>,
+    expected_code_hashes: HashMap<Bytes, Bytes>,
+    simulation_environment: Option<EnvironmentConfig>,
+    verify_code_hashes: bool,
+    verify_code_presence: bool,
+    staleness_guard: Option<StalenessGuard>,
+    restrict_callback_targets: bool,
+    require_cyclic_profit: bool,
+    permit_signer: Option<Arc<dyn PermitSigner>>,
+    calldata_budget: Option<CalldataBudget>,
+    default_slippage: Option<SlippageConfig>,
+    executor_table: bool,
+    wide_split_indices: bool,
+    precise_splits: bool,
+    split_branch_groups: bool,
+    ungrouped_fallback: bool,
+    disable_native_action_inference: bool,
+    checked_amount_guard: Option<CheckedAmountGuard>,
+    disable_checked_amount_guard: bool,
+    token_safety_checker: Option<TokenSafetyChecker>,
+    split_normalization: Option<f64>,
+    wrap_variant: Option<WrapVariant>,
+    approval_policy: Option<ApprovalPolicy>,
+    encoding_metrics: Option<Arc<dyn EncodingMetrics>>,
+    rfq_config: Option<RfqConfig>,
+    strict_protocol_support: bool,
+    protocol_capabilities: Option<String>,
 }
 
 impl Default for TychoRouterEncoderBuilder {
@@ -37,10 +81,39 @@ impl TychoRouterEncoderBuilder {
         TychoRouterEncoderBuilder {
             chain: None,
             swap_encoder_registry: None,
+            shared_registry: None,
             router_address: None,
             swapper_pk: None,
             user_transfer_type: None,
             historical_trade: false,
+            audit_log: None,
+            idempotency_cache: None,
+            expected_code_hashes: HashMap::new(),
+            simulation_environment: None,
+            verify_code_hashes: false,
+            verify_code_presence: false,
+            staleness_guard: None,
+            restrict_callback_targets: false,
+            require_cyclic_profit: false,
+            permit_signer: None,
+            calldata_budget: None,
+            default_slippage: None,
+            executor_table: false,
+            wide_split_indices: false,
+            precise_splits: false,
+            split_branch_groups: false,
+            ungrouped_fallback: false,
+            disable_native_action_inference: false,
+            checked_amount_guard: None,
+            disable_checked_amount_guard: false,
+            token_safety_checker: None,
+            split_normalization: None,
+            wrap_variant: None,
+            approval_policy: None,
+            encoding_metrics: None,
+            rfq_config: None,
+            strict_protocol_support: false,
+            protocol_capabilities: None,
         }
     }
     pub fn chain(mut self, chain: Chain) -> Self {
@@ -58,6 +131,19 @@ impl TychoRouterEncoderBuilder {
         self
     }
 
+    /// Sets a shared registry instead of the `swap_encoder_registry`, so that its executors can be
+    /// reloaded at runtime (see `TychoRouterEncoder::with_shared_registry`). The `rfq_config`, if
+    /// set, is applied to the shared registry. The environment checks are only done with the
+    /// registry current at build time.
+    pub fn shared_swap_encoder_registry(
+        mut self,
+        shared_registry: SharedSwapEncoderRegistry,
+    ) -> Self {
+        self.swap_encoder_registry = Some(shared_registry.current());
+        self.shared_registry = Some(shared_registry);
+        self
+    }
+
     /// Sets the `router_address` manually.
     /// If it's not set, the default router address will be used (config/router_addresses.json)
     pub fn router_address(mut self, router_address: Bytes) -> Self {
@@ -74,6 +160,231 @@ impl TychoRouterEncoderBuilder {
         self
     }
 
+    /// Sets whether the encoded trades are historical (see `historical_trade`).
+    ///
+    /// Historical trades are encoded offline, so that past blocks can be encoded deterministically
+    /// (e.g. for backtesting): the swap encoders skip their approval lookups (approving every
+    /// spender), RFQ swaps settle the quotes recorded in their user data (see `recorded_quote`)
+    /// instead of requesting new ones, and Curve pools must have a known type.
+    pub fn historical(mut self, historical: bool) -> Self {
+        self.historical_trade = historical;
+        self
+    }
+
+    /// Sets an `audit_log` that records every encoded solution (content hash, timestamp, inputs
+    /// fingerprint and calldata hash) as a tamper-evident hash chain.
+    pub fn audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Sets an `idempotency_cache` used to deduplicate solutions with the same idempotency key.
+    /// Keep a clone of the `Arc` to read the cache statistics.
+    pub fn idempotency_cache(mut self, idempotency_cache: Arc<IdempotencyCache>) -> Self {
+        self.idempotency_cache = Some(idempotency_cache);
+        self
+    }
+
+    /// Sets a `staleness_guard` that rejects solutions with swaps derived from outdated indexer
+    /// states. Keep a clone of the guard to report new blocks to it.
+    pub fn staleness_guard(mut self, staleness_guard: StalenessGuard) -> Self {
+        self.staleness_guard = Some(staleness_guard);
+        self
+    }
+
+    /// Sets a `simulation_environment` (e.g. a staging router with its own executors). Solutions
+    /// can then be encoded for it with `encode_solutions_for_environment`.
+    pub fn simulation_environment(mut self, simulation_environment: EnvironmentConfig) -> Self {
+        self.simulation_environment = Some(simulation_environment);
+        self
+    }
+
+    /// Sets the `expected_code_hashes` of the production router and executors, by address. Every
+    /// contract of the production environment must then be listed, which protects against
+    /// configuring contracts of another environment by mistake.
+    pub fn expected_code_hashes(mut self, expected_code_hashes: HashMap<Bytes, Bytes>) -> Self {
+        self.expected_code_hashes = expected_code_hashes;
+        self
+    }
+
+    /// Sets the `verify_code_hashes` manually to true.
+    /// If set to true, the deployed code of every contract with an expected code hash is fetched
+    /// (this requires the `RPC_URL` env var) and compared against it when building the encoder.
+    pub fn verify_code_hashes(mut self) -> Self {
+        self.verify_code_hashes = true;
+        self
+    }
+
+    /// Sets the `verify_code_presence` manually to true.
+    /// If set to true, the deployed code of the router and of every executor is fetched (this
+    /// requires the `RPC_URL` env var) when building the encoder, and building fails if any of
+    /// them has no code, e.g. because of an executor address configured for another chain.
+    pub fn verify_code_presence(mut self) -> Self {
+        self.verify_code_presence = true;
+        self
+    }
+
+    /// Sets the `restrict_callback_targets` manually to true.
+    /// If set to true, every encoded solution carries the allowlist of contracts that may call back
+    /// into the router during the swap (the executors and settlement contracts involved in the
+    /// solution). Only use it with router versions that support restricting callback targets.
+    pub fn restrict_callback_targets(mut self) -> Self {
+        self.restrict_callback_targets = true;
+        self
+    }
+
+    /// Sets the `require_cyclic_profit` manually to true.
+    /// If set to true, cyclic solutions (e.g. USDC -> WETH -> USDC) are rejected unless their
+    /// checked amount is above their given amount, so their min amount out enforces a profit.
+    pub fn require_cyclic_profit(mut self) -> Self {
+        self.require_cyclic_profit = true;
+        self
+    }
+
+    /// Sets the `permit_signer` for the encoder. This is used instead of the `swapper_pk` to sign
+    /// permit2 objects when encoding the full calldata, e.g. with an `Erc1271Signer` if the
+    /// sender is a smart contract wallet.
+    pub fn permit_signer(mut self, permit_signer: Arc<dyn PermitSigner>) -> Self {
+        self.permit_signer = Some(permit_signer);
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of the calldata encoded with `encode_full_calldata`. By
+    /// default, the practical limit of the chain is used (e.g. lower on Arbitrum than on Ethereum).
+    pub fn max_calldata_bytes(mut self, max_bytes: usize) -> Self {
+        self.calldata_budget = Some(CalldataBudget::new(max_bytes));
+        self
+    }
+
+    /// Sets the `default_slippage` used to derive the checked amount of solutions that set an
+    /// `expected_amount` but no slippage of their own.
+    pub fn default_slippage(mut self, default_slippage: SlippageConfig) -> Self {
+        self.default_slippage = Some(default_slippage);
+        self
+    }
+
+    /// Encodes the executors of sequential and split solutions in a shared table, with the swaps
+    /// referring to them by index. This reduces the calldata size (see
+    /// `TychoRouterEncoder::with_executor_table`), but requires a router supporting the `Indexed`
+    /// swap functions.
+    pub fn executor_table(mut self) -> Self {
+        self.executor_table = true;
+        self
+    }
+
+    /// Encodes the token indices of split solutions on 2 bytes, so that the large split graphs of
+    /// graph solvers can have more than 256 distinct tokens (see
+    /// `TychoRouterEncoder::with_wide_split_indices`). Requires a router supporting the
+    /// `splitSwapWide` functions, and can't be combined with an executor table or precise splits.
+    pub fn wide_split_indices(mut self) -> Self {
+        self.wide_split_indices = true;
+        self
+    }
+
+    /// Encodes the splits of split solutions on 4 bytes with a 1e9 basis instead of 3 bytes (see
+    /// `TychoRouterEncoder::with_precise_splits`). Requires a router supporting the
+    /// `splitSwapPrecise` functions, and can't be combined with an executor table or wide split
+    /// indices.
+    pub fn precise_splits(mut self) -> Self {
+        self.precise_splits = true;
+        self
+    }
+
+    /// Groups the Uniswap V4 branches splitting a token into the same output token into one swap
+    /// execution, settling the input and taking the output once for all of them (see
+    /// `TychoRouterEncoder::with_split_branch_groups`). Requires a Uniswap V4 executor supporting
+    /// split branches.
+    pub fn split_branch_groups(mut self) -> Self {
+        self.split_branch_groups = true;
+        self
+    }
+
+    /// Encodes the swaps of the groups failing to encode as individual swaps instead of failing the
+    /// solution, reporting the degraded groups in the encoded solution (see
+    /// `TychoRouterEncoder::with_ungrouped_fallback`).
+    pub fn ungrouped_fallback(mut self) -> Self {
+        self.ungrouped_fallback = true;
+        self
+    }
+
+    /// Disables the inference of the wrap and unwrap of solutions without a native action (see
+    /// `TychoRouterEncoder::without_native_action_inference`).
+    pub fn disable_native_action_inference(mut self) -> Self {
+        self.disable_native_action_inference = true;
+        self
+    }
+
+    /// Sets the `checked_amount_guard` rejecting the checked amounts off by orders of magnitude,
+    /// instead of the default guard (see `TychoRouterEncoder::with_checked_amount_guard`).
+    pub fn checked_amount_guard(mut self, checked_amount_guard: CheckedAmountGuard) -> Self {
+        self.checked_amount_guard = Some(checked_amount_guard);
+        self
+    }
+
+    /// Disables the checked amount guard (see
+    /// `TychoRouterEncoder::without_checked_amount_guard`).
+    pub fn disable_checked_amount_guard(mut self) -> Self {
+        self.disable_checked_amount_guard = true;
+        self
+    }
+
+    /// Sets a `token_safety_checker` rejecting the solutions involving denylisted tokens or tokens
+    /// that can't be transferred (see `TychoRouterEncoder::with_token_safety_checker`).
+    pub fn token_safety_checker(mut self, token_safety_checker: TokenSafetyChecker) -> Self {
+        self.token_safety_checker = Some(token_safety_checker);
+        self
+    }
+
+    /// Sets the `split_normalization` epsilon. If set, the split percentages of split solutions
+    /// summing to 100% within the epsilon are rescaled, and the splits below it folded into the
+    /// largest swap of their token, before being validated. Disabled by default.
+    pub fn split_normalization(mut self, split_normalization: Option<f64>) -> Self {
+        self.split_normalization = split_normalization;
+        self
+    }
+
+    /// Sets the `wrap_variant` manually.
+    /// If it's not set, the variant configured for the chain is used (the `wrap_variant` key of its
+    /// `tycho_router` config in config/protocol_specific_addresses.json), defaulting to WETH9.
+    pub fn wrap_variant(mut self, wrap_variant: WrapVariant) -> Self {
+        self.wrap_variant = Some(wrap_variant);
+        self
+    }
+
+    /// Sets the `approval_policy` of the swap encoders (see `ApprovalPolicy`). If it's not set, the
+    /// router's allowances are looked up on chain (`ApprovalPolicy::Live`).
+    pub fn approval_policy(mut self, approval_policy: ApprovalPolicy) -> Self {
+        self.approval_policy = Some(approval_policy);
+        self
+    }
+
+    /// Sets the `encoding_metrics` callback, receiving the timings and failure reasons of the
+    /// encoding stages (solutions, strategies, RFQ quotes and approval checks) of the encoder.
+    pub fn encoding_metrics(mut self, encoding_metrics: Arc<dyn EncodingMetrics>) -> Self {
+        self.encoding_metrics = Some(encoding_metrics);
+        self
+    }
+
+    /// Sets the `rfq_config` (timeout, retries, backoff and expiry buffer) of the quote requests of
+    /// the RFQ swap encoders. A quote not received in time returns an
+    /// `EncodingError::QuoteTimeout`, and a quote expiring too soon an
+    /// `EncodingError::QuoteExpired`.
+    pub fn rfq_config(mut self, rfq_config: RfqConfig) -> Self {
+        self.rfq_config = Some(rfq_config);
+        self
+    }
+
+    /// Sets the `strict_protocol_support` manually to true.
+    /// If set to true, building fails if a protocol of the registry has no capabilities in the
+    /// given config (defaults to config/protocol_capabilities.json), and solutions using a protocol
+    /// not deployed on the chain or a feature its executor doesn't support (exact out, native
+    /// token) are rejected (see `SupportMatrix`).
+    pub fn strict_protocol_support(mut self, protocol_capabilities: Option<String>) -> Self {
+        self.strict_protocol_support = true;
+        self.protocol_capabilities = protocol_capabilities;
+        self
+    }
+
     /// Sets the `swapper_pk` for the encoder. This is used to sign permit2 objects. This is only
     /// needed if you intend to get the full calldata for the transfer. We do not recommend
     /// using this option, you should sign and create the function calldata entirely on your
@@ -92,6 +403,13 @@ impl TychoRouterEncoderBuilder {
         if let (Some(chain), Some(user_transfer_type), Some(swap_encoder_registry)) =
             (self.chain, self.user_transfer_type, self.swap_encoder_registry)
         {
+            let swap_encoder_registry = match &self.rfq_config {
+                Some(rfq_config) => swap_encoder_registry.with_rfq_config(rfq_config),
+                None => swap_encoder_registry,
+            };
+            if let (Some(shared_registry), Some(_)) = (&self.shared_registry, &self.rfq_config) {
+                shared_registry.replace(swap_encoder_registry.clone())?;
+            }
             let tycho_router_address;
             if let Some(address) = self.router_address {
                 tycho_router_address = address;
@@ -117,14 +435,218 @@ impl TychoRouterEncoderBuilder {
                 None
             };
 
-            Ok(Box::new(TychoRouterEncoder::new(
+            let production =
+                EnvironmentConfig::new(tycho_router_address.clone(), swap_encoder_registry.clone())
+                    .with_expected_code_hashes(self.expected_code_hashes);
+            match &self.simulation_environment {
+                Some(simulation) => validate_environments(&production, simulation)?,
+                None => validate_environment(Environment::Production, &production)?,
+            }
+            if self.verify_code_hashes {
+                verify_code_hashes(Environment::Production, &production)?;
+                if let Some(simulation) = &self.simulation_environment {
+                    verify_code_hashes(Environment::Simulation, simulation)?;
+                }
+            }
+            if self.verify_code_presence {
+                verify_code_presence(Environment::Production, &production)?;
+                if let Some(simulation) = &self.simulation_environment {
+                    verify_code_presence(Environment::Simulation, simulation)?;
+                }
+            }
+
+            let callback_allowlist = if self.restrict_callback_targets {
+                Some(CallbackAllowlist::new(chain, &swap_encoder_registry)?)
+            } else {
+                None
+            };
+            let support_matrix = if self.strict_protocol_support {
+                Some(SupportMatrix::new(
+                    chain,
+                    &swap_encoder_registry,
+                    self.protocol_capabilities.clone(),
+                )?)
+            } else {
+                None
+            };
+            let mut encoder = TychoRouterEncoder::new(
                 chain,
                 swap_encoder_registry,
                 tycho_router_address,
-                user_transfer_type,
-                signer,
+                user_transfer_type.clone(),
+                signer.clone(),
                 self.historical_trade,
-            )?))
+            )?;
+            if let Some(mut simulation) = self.simulation_environment {
+                if let Some(rfq_config) = &self.rfq_config {
+                    simulation.swap_encoder_registry = simulation
+                        .swap_encoder_registry
+                        .with_rfq_config(rfq_config);
+                }
+                let simulation_callback_allowlist = if self.restrict_callback_targets {
+                    Some(CallbackAllowlist::new(chain, &simulation.swap_encoder_registry)?)
+                } else {
+                    None
+                };
+                let simulation_support_matrix = if self.strict_protocol_support {
+                    Some(SupportMatrix::new(
+                        chain,
+                        &simulation.swap_encoder_registry,
+                        self.protocol_capabilities.clone(),
+                    )?)
+                } else {
+                    None
+                };
+                let mut simulation_encoder = TychoRouterEncoder::new(
+                    chain,
+                    simulation.swap_encoder_registry,
+                    simulation.router_address,
+                    user_transfer_type,
+                    signer,
+                    self.historical_trade,
+                )?;
+                if let Some(audit_log) = &self.audit_log {
+                    simulation_encoder = simulation_encoder.with_audit_log(audit_log.clone());
+                }
+                if let Some(staleness_guard) = &self.staleness_guard {
+                    simulation_encoder =
+                        simulation_encoder.with_staleness_guard(staleness_guard.clone());
+                }
+                if let Some(callback_allowlist) = simulation_callback_allowlist {
+                    simulation_encoder =
+                        simulation_encoder.with_callback_allowlist(callback_allowlist);
+                }
+                if self.require_cyclic_profit {
+                    simulation_encoder = simulation_encoder.with_cyclic_profit_check();
+                }
+                if let Some(permit_signer) = &self.permit_signer {
+                    simulation_encoder =
+                        simulation_encoder.with_permit_signer(permit_signer.clone());
+                }
+                if let Some(calldata_budget) = &self.calldata_budget {
+                    simulation_encoder =
+                        simulation_encoder.with_calldata_budget(calldata_budget.clone());
+                }
+                if let Some(default_slippage) = self.default_slippage {
+                    simulation_encoder = simulation_encoder.with_default_slippage(default_slippage);
+                }
+                if self.executor_table {
+                    simulation_encoder = simulation_encoder.with_executor_table();
+                }
+                if self.wide_split_indices {
+                    simulation_encoder = simulation_encoder.with_wide_split_indices()?;
+                }
+                if self.precise_splits {
+                    simulation_encoder = simulation_encoder.with_precise_splits()?;
+                }
+                if self.split_branch_groups {
+                    simulation_encoder = simulation_encoder.with_split_branch_groups();
+                }
+                if self.ungrouped_fallback {
+                    simulation_encoder = simulation_encoder.with_ungrouped_fallback();
+                }
+                if self.disable_native_action_inference {
+                    simulation_encoder = simulation_encoder.without_native_action_inference();
+                }
+                if let Some(checked_amount_guard) = self.checked_amount_guard {
+                    simulation_encoder =
+                        simulation_encoder.with_checked_amount_guard(checked_amount_guard);
+                }
+                if self.disable_checked_amount_guard {
+                    simulation_encoder = simulation_encoder.without_checked_amount_guard();
+                }
+                if let Some(token_safety_checker) = &self.token_safety_checker {
+                    simulation_encoder =
+                        simulation_encoder.with_token_safety_checker(token_safety_checker.clone());
+                }
+                if let Some(epsilon) = self.split_normalization {
+                    simulation_encoder = simulation_encoder.with_split_normalization(epsilon);
+                }
+                if let Some(wrap_variant) = self.wrap_variant {
+                    simulation_encoder = simulation_encoder.with_wrap_variant(wrap_variant);
+                }
+                if let Some(approval_policy) = self.approval_policy {
+                    simulation_encoder = simulation_encoder.with_approval_policy(approval_policy);
+                }
+                if let Some(encoding_metrics) = &self.encoding_metrics {
+                    simulation_encoder =
+                        simulation_encoder.with_encoding_metrics(encoding_metrics.clone());
+                }
+                if let Some(support_matrix) = simulation_support_matrix {
+                    simulation_encoder = simulation_encoder.with_support_matrix(support_matrix);
+                }
+                encoder = encoder.with_simulation_encoder(simulation_encoder);
+            }
+            if let Some(audit_log) = self.audit_log {
+                encoder = encoder.with_audit_log(audit_log);
+            }
+            if let Some(idempotency_cache) = self.idempotency_cache {
+                encoder = encoder.with_idempotency_cache(idempotency_cache);
+            }
+            if let Some(staleness_guard) = self.staleness_guard {
+                encoder = encoder.with_staleness_guard(staleness_guard);
+            }
+            if let Some(callback_allowlist) = callback_allowlist {
+                encoder = encoder.with_callback_allowlist(callback_allowlist);
+            }
+            if self.require_cyclic_profit {
+                encoder = encoder.with_cyclic_profit_check();
+            }
+            if let Some(permit_signer) = self.permit_signer {
+                encoder = encoder.with_permit_signer(permit_signer);
+            }
+            if let Some(calldata_budget) = self.calldata_budget {
+                encoder = encoder.with_calldata_budget(calldata_budget);
+            }
+            if let Some(default_slippage) = self.default_slippage {
+                encoder = encoder.with_default_slippage(default_slippage);
+            }
+            if let Some(shared_registry) = self.shared_registry {
+                encoder = encoder.with_shared_registry(shared_registry);
+            }
+            if self.executor_table {
+                encoder = encoder.with_executor_table();
+            }
+            if self.wide_split_indices {
+                encoder = encoder.with_wide_split_indices()?;
+            }
+            if self.precise_splits {
+                encoder = encoder.with_precise_splits()?;
+            }
+            if self.split_branch_groups {
+                encoder = encoder.with_split_branch_groups();
+            }
+            if self.ungrouped_fallback {
+                encoder = encoder.with_ungrouped_fallback();
+            }
+            if self.disable_native_action_inference {
+                encoder = encoder.without_native_action_inference();
+            }
+            if let Some(checked_amount_guard) = self.checked_amount_guard {
+                encoder = encoder.with_checked_amount_guard(checked_amount_guard);
+            }
+            if self.disable_checked_amount_guard {
+                encoder = encoder.without_checked_amount_guard();
+            }
+            if let Some(token_safety_checker) = self.token_safety_checker {
+                encoder = encoder.with_token_safety_checker(token_safety_checker);
+            }
+            if let Some(epsilon) = self.split_normalization {
+                encoder = encoder.with_split_normalization(epsilon);
+            }
+            if let Some(wrap_variant) = self.wrap_variant {
+                encoder = encoder.with_wrap_variant(wrap_variant);
+            }
+            if let Some(approval_policy) = self.approval_policy {
+                encoder = encoder.with_approval_policy(approval_policy);
+            }
+            if let Some(encoding_metrics) = self.encoding_metrics {
+                encoder = encoder.with_encoding_metrics(encoding_metrics);
+            }
+            if let Some(support_matrix) = support_matrix {
+                encoder = encoder.with_support_matrix(support_matrix);
+            }
+            Ok(Box::new(encoder))
         } else {
             Err(EncodingError::FatalError(
                 "Please set the chain, user transfer type and swap encoder registry before building the encoder"
@@ -134,6 +656,123 @@ impl TychoRouterEncoderBuilder {
     }
 }
 
+/// Builder pattern for constructing a `MultiChainTychoRouterEncoder` from a single executors'
+/// addresses file keyed by chain.
+pub struct MultiChainTychoRouterEncoderBuilder {
+    chains: Option<Vec<Chain>>,
+    user_transfer_type: Option<UserTransferType>,
+    executors_addresses: Option<String>,
+    router_addresses: HashMap<Chain, Bytes>,
+    historical_trade: bool,
+}
+
+impl Default for MultiChainTychoRouterEncoderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MultiChainTychoRouterEncoderBuilder {
+    pub fn new() -> Self {
+        MultiChainTychoRouterEncoderBuilder {
+            chains: None,
+            user_transfer_type: None,
+            executors_addresses: None,
+            router_addresses: HashMap::new(),
+            historical_trade: false,
+        }
+    }
+
+    /// Sets the `chains` to build an encoder for.
+    /// If it's not set, an encoder is built for every chain of the executors' addresses file.
+    pub fn chains(mut self, chains: Vec<Chain>) -> Self {
+        self.chains = Some(chains);
+        self
+    }
+
+    pub fn user_transfer_type(mut self, user_transfer_type: UserTransferType) -> Self {
+        self.user_transfer_type = Some(user_transfer_type);
+        self
+    }
+
+    /// Sets the content of the executors' addresses file, keyed by chain.
+    /// If it's not set, the default executors will be used (config/executor_addresses.json)
+    pub fn executors_addresses(mut self, executors_addresses: String) -> Self {
+        self.executors_addresses = Some(executors_addresses);
+        self
+    }
+
+    /// Sets the `router_address` of a chain manually.
+    /// If it's not set, the default router address of the chain will be used
+    /// (config/router_addresses.json)
+    pub fn router_address(mut self, chain: Chain, router_address: Bytes) -> Self {
+        self.router_addresses
+            .insert(chain, router_address);
+        self
+    }
+
+    /// Sets the `historical_trade` manually to true, for the encoders of all chains (see
+    /// `TychoRouterEncoderBuilder::historical_trade`).
+    pub fn historical_trade(mut self) -> Self {
+        self.historical_trade = true;
+        self
+    }
+
+    /// Sets whether the trades encoded for all chains are historical (see
+    /// `TychoRouterEncoderBuilder::historical`).
+    pub fn historical(mut self, historical: bool) -> Self {
+        self.historical_trade = historical;
+        self
+    }
+
+    /// Builds the `MultiChainTychoRouterEncoder` instance.
+    /// Returns an error if the user transfer type has not been set, or if a requested chain has no
+    /// executors or router address.
+    pub fn build(mut self) -> Result<MultiChainTychoRouterEncoder, EncodingError> {
+        let user_transfer_type = self.user_transfer_type.ok_or_else(|| {
+            EncodingError::FatalError(
+                "Please set the user transfer type before building the encoder".to_string(),
+            )
+        })?;
+        let mut registries = SwapEncoderRegistry::new_multichain(self.executors_addresses)?;
+        let chains = match self.chains {
+            Some(chains) => chains,
+            None => registries.keys().copied().collect(),
+        };
+        let default_routers: HashMap<Chain, Bytes> = serde_json::from_str(DEFAULT_ROUTERS_JSON)?;
+
+        let mut encoders = HashMap::new();
+        for chain in chains {
+            let swap_encoder_registry = registries
+                .remove(&chain)
+                .ok_or_else(|| {
+                    EncodingError::FatalError(format!("No executors found for chain {chain:?}"))
+                })?;
+            let router_address = match self.router_addresses.remove(&chain) {
+                Some(address) => address,
+                None => default_routers
+                    .get(&chain)
+                    .ok_or_else(|| {
+                        EncodingError::FatalError(format!(
+                            "No default router address found for chain {chain:?}"
+                        ))
+                    })?
+                    .to_owned(),
+            };
+            let encoder = TychoRouterEncoder::new(
+                chain,
+                swap_encoder_registry,
+                router_address,
+                user_transfer_type.clone(),
+                None,
+                self.historical_trade,
+            )?;
+            encoders.insert(chain, encoder);
+        }
+        Ok(MultiChainTychoRouterEncoder::new(encoders))
+    }
+}
+
 /// Builder pattern for constructing a `TychoExecutorEncoder` with customizable options.
 pub struct TychoExecutorEncoderBuilder {
     swap_encoder_registry: Option<SwapEncoderRegistry>,
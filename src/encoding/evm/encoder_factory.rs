@@ -0,0 +1,219 @@
+use std::{collections::HashMap, sync::Arc};
+
+use once_cell::sync::OnceCell;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        approvals::permit2::Permit2, constants::DEFAULT_ROUTERS_JSON,
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+        tycho_encoders::TychoRouterEncoder,
+    },
+    models::UserTransferType,
+};
+
+/// The configuration of a single tenant of an `EncoderFactory`.
+///
+/// # Fields
+/// * `user_transfer_type`: How the funds of the tenant's users are transferred into the router
+/// * `router_address`: Optional router of the tenant. Defaults to the router of the chain
+///   (config/router_addresses.json)
+#[derive(Clone, Debug, PartialEq)]
+pub struct TenantConfig {
+    pub user_transfer_type: UserTransferType,
+    pub router_address: Option<Bytes>,
+}
+
+impl TenantConfig {
+    pub fn new(user_transfer_type: UserTransferType) -> Self {
+        Self { user_transfer_type, router_address: None }
+    }
+
+    /// Sets the router of the tenant, instead of the default router of the chain.
+    pub fn with_router_address(mut self, router_address: Bytes) -> Self {
+        self.router_address = Some(router_address);
+        self
+    }
+}
+
+/// Creates `TychoRouterEncoder`s for many tenants (e.g. the users of a multi-tenant service, with
+/// distinct routers and transfer types), sharing the state that is expensive to build and
+/// identical for all of them.
+///
+/// Encoders built with the `TychoRouterEncoderBuilder` are fully independent: each of them clones
+/// its own swap encoders and connects its own RPC client for permit2. The encoders created by a
+/// factory share the swap encoders (and their caches) of its registry and a single Permit2
+/// client, so creating one per tenant, or even per request, is cheap.
+///
+/// # Fields
+/// * `chain`: Chain of the encoders
+/// * `swap_encoder_registry`: Registry shared by the encoders of all tenants
+/// * `default_router_address`: Router of the tenants that don't configure their own
+/// * `historical_trade`: Whether the encoded trades are meant for historical blocks (see
+///   `TychoRouterEncoderBuilder::historical_trade`)
+/// * `permit2`: Permit2 client shared by the tenants using permit2 transfers, connected when the
+///   first of their encoders is created
+#[derive(Clone)]
+pub struct EncoderFactory {
+    chain: Chain,
+    swap_encoder_registry: SwapEncoderRegistry,
+    default_router_address: Option<Bytes>,
+    historical_trade: bool,
+    permit2: Arc<OnceCell<Permit2>>,
+}
+
+impl EncoderFactory {
+    pub fn new(
+        chain: Chain,
+        swap_encoder_registry: SwapEncoderRegistry,
+    ) -> Result<Self, EncodingError> {
+        let default_routers: HashMap<Chain, Bytes> = serde_json::from_str(DEFAULT_ROUTERS_JSON)?;
+        Ok(Self {
+            chain,
+            swap_encoder_registry,
+            default_router_address: default_routers.get(&chain).cloned(),
+            historical_trade: false,
+            permit2: Arc::new(OnceCell::new()),
+        })
+    }
+
+    /// Marks the trades encoded by all the tenants as historical (see
+    /// `TychoRouterEncoderBuilder::historical_trade`).
+    pub fn with_historical_trade(mut self) -> Self {
+        self.historical_trade = true;
+        self
+    }
+
+    /// Returns the registry shared by the encoders of all tenants.
+    pub fn swap_encoder_registry(&self) -> &SwapEncoderRegistry {
+        &self.swap_encoder_registry
+    }
+
+    /// Creates the encoder of a tenant.
+    ///
+    /// Returns an error if the tenant doesn't configure a router and the chain has no default one,
+    /// or if the Permit2 client can't be connected for a tenant using permit2 transfers.
+    pub fn encoder(&self, tenant: &TenantConfig) -> Result<TychoRouterEncoder, EncodingError> {
+        let router_address = tenant
+            .router_address
+            .clone()
+            .or_else(|| self.default_router_address.clone())
+            .ok_or_else(|| {
+                EncodingError::FatalError(format!(
+                    "No router address configured for the tenant and no default router address \
+                     found for chain {:?}",
+                    self.chain
+                ))
+            })?;
+        let permit2 = if tenant.user_transfer_type == UserTransferType::TransferFromPermit2 {
+            Some(
+                self.permit2
+                    .get_or_try_init(Permit2::new)?
+                    .clone(),
+            )
+        } else {
+            None
+        };
+        TychoRouterEncoder::new_with_permit2(
+            self.chain,
+            self.swap_encoder_registry.clone(),
+            router_address,
+            tenant.user_transfer_type.clone(),
+            None,
+            self.historical_trade,
+            permit2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, str::FromStr};
+
+    use num_bigint::BigUint;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::{
+        models::{Solution, Swap},
+        tycho_encoder::TychoEncoder,
+    };
+
+    fn factory() -> EncoderFactory {
+        let executors_addresses =
+            fs::read_to_string("config/test_executor_addresses.json").unwrap();
+        let registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap();
+        EncoderFactory::new(Chain::Ethereum, registry).unwrap()
+    }
+
+    fn solution() -> Solution {
+        let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let swap = Swap::new(
+            ProtocolComponent {
+                id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                protocol_system: "uniswap_v2".to_string(),
+                ..Default::default()
+            },
+            weth.clone(),
+            dai.clone(),
+        );
+        Solution {
+            given_amount: BigUint::from(1000u32),
+            given_token: weth,
+            checked_token: dai,
+            swaps: vec![swap],
+            receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_encoders_of_tenants() {
+        let factory = factory();
+        let custom_router = Bytes::from_str("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66").unwrap();
+        let default_tenant = TenantConfig::new(UserTransferType::TransferFrom);
+        let custom_tenant =
+            TenantConfig::new(UserTransferType::None).with_router_address(custom_router.clone());
+
+        let default_encoder = factory
+            .encoder(&default_tenant)
+            .unwrap();
+        let custom_encoder = factory.encoder(&custom_tenant).unwrap();
+
+        let default_encoded = default_encoder
+            .encode_solutions(vec![solution()])
+            .unwrap();
+        let custom_encoded = custom_encoder
+            .encode_solutions(vec![solution()])
+            .unwrap();
+        let default_routers: HashMap<Chain, Bytes> =
+            serde_json::from_str(DEFAULT_ROUTERS_JSON).unwrap();
+        assert_eq!(default_encoded[0].interacting_with, default_routers[&Chain::Ethereum]);
+        assert_eq!(custom_encoded[0].interacting_with, custom_router);
+        // The transfer type of the tenant is encoded in the swaps
+        assert_ne!(default_encoded[0].swaps, custom_encoded[0].swaps);
+    }
+
+    #[test]
+    fn test_registry_is_shared() {
+        let factory = factory();
+        let registry = factory.swap_encoder_registry().clone();
+
+        assert!(registry.shares_encoders_with(factory.swap_encoder_registry()));
+        // Registering an encoder for a single tenant doesn't affect the shared registry
+        let encoder = registry
+            .get_encoder("uniswap_v2")
+            .unwrap()
+            .clone();
+        let tenant_registry = registry.register_encoder("uniswap_v2_fork", encoder);
+        assert!(!tenant_registry.shares_encoders_with(factory.swap_encoder_registry()));
+        assert!(factory
+            .swap_encoder_registry()
+            .get_encoder("uniswap_v2_fork")
+            .is_none());
+    }
+}
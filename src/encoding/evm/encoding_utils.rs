@@ -1,23 +1,47 @@
 use std::str::FromStr;
 
 use alloy::{
-    primitives::{Address, Keccak256, U256},
+    core::sol,
+    primitives::{Address, Bytes as AlloyBytes, Keccak256, B256, U256},
     signers::{local::PrivateKeySigner, Signature, SignerSync},
-    sol_types::{eip712_domain, SolStruct, SolValue},
+    sol_types::{eip712_domain, Eip712Domain, SolStruct, SolValue},
 };
 use num_bigint::BigUint;
+use serde_json::{json, Value};
 use tycho_common::Bytes;
 
 use crate::encoding::{
     errors::EncodingError,
     evm::{
-        approvals::permit2::PermitSingle,
+        approvals::{
+            permit2::{PermitBatch, PermitSingle},
+            permit_signer::PermitSigner,
+        },
         utils::{biguint_to_u256, bytes_to_address},
     },
     models,
-    models::{EncodedSolution, NativeAction, Solution, Transaction, UserTransferType},
+    models::{
+        CallSpec, EncodedSolution, Multicall, NativeAction, Solution, Transaction, UserTransferType,
+    },
 };
 
+sol! {
+    /// A call of Multicall3's `aggregate3Value`.
+    struct Call3Value {
+        address target;
+        bool allowFailure;
+        uint256 value;
+        bytes callData;
+    }
+
+    /// A call of a smart account's `executeBatch`.
+    struct BatchCall {
+        address target;
+        uint256 value;
+        bytes data;
+    }
+}
+
 /// Encodes a transaction for the Tycho Router using one of its supported swap methods.
 ///
 /// # Overview
@@ -30,6 +54,12 @@ use crate::encoding::{
 /// - `sequentialSwapPermit2`
 /// - `splitSwap`
 /// - `splitSwapPermit2`
+/// - `splitSwapPermit2Batch`
+/// - `multiInputSplitSwap`, selling every given token of the solution
+/// - `singleSwapWithFee`, `sequentialSwapWithFee` and `splitSwapWithFee`, taking the fee of the
+///   solution
+/// - `singleSwapWithCallback`, `sequentialSwapWithCallback` and `splitSwapWithCallback`, calling
+///   back the receiver with the receiver callback of the solution
 ///
 /// The encoding includes handling of native asset wrapping/unwrapping, permit2 support,
 /// and proper input argument formatting based on the function signature string.
@@ -63,7 +93,9 @@ use crate::encoding::{
 /// - `solution`: The high-level solution including tokens, amounts, and receiver info.
 /// - `user_transfer_type`: The desired transfer method.
 /// - `native_address`: The address used to represent the native token
-/// - `signer`: Optional signer for permit2
+/// - `permit_signer`: Optional signer for permit2. A `PrivateKeySigner` for EOA senders, or e.g. an
+///   `Erc1271Signer` for smart contract wallet senders, whose permits Permit2 verifies through
+///   ERC-1271.
 ///
 /// # Returns
 /// A `Result<Transaction, EncodingError>` that either contains the full transaction data (to,
@@ -78,10 +110,11 @@ pub fn encode_tycho_router_call(
     solution: &Solution,
     user_transfer_type: &UserTransferType,
     native_address: &Bytes,
-    signer: Option<PrivateKeySigner>,
+    permit_signer: Option<&dyn PermitSigner>,
 ) -> Result<Transaction, EncodingError> {
     let (mut unwrap, mut wrap) = (false, false);
-    if let Some(action) = solution.native_action.clone() {
+    // The encoded solution's native action includes any wrap/unwrap step inserted while encoding
+    if let Some(action) = encoded_solution.native_action.clone() {
         match action {
             NativeAction::Wrap => wrap = true,
             NativeAction::Unwrap => unwrap = true,
@@ -99,15 +132,120 @@ pub fn encode_tycho_router_call(
             PermitSingle::try_from(&p)
                 .map_err(|_| EncodingError::InvalidInput("Invalid permit".to_string()))?,
         );
-        let signer = signer
+        let permit_signer = permit_signer
             .ok_or(EncodingError::FatalError("Signer must be set to use permit2".to_string()))?;
-        let signature = sign_permit(chain_id, &p, signer)?;
-        (permit, signature.as_bytes().to_vec())
+        let signature = permit_signer.sign_permit(chain_id, &p)?;
+        (permit, signature)
     } else {
         (None, vec![])
     };
 
     let method_calldata = if encoded_solution
+        .function_signature
+        .contains("WithFee")
+    {
+        let fee = solution.fee.as_ref().ok_or_else(|| {
+            EncodingError::FatalError("The solution fee must be set to take a fee".to_string())
+        })?;
+        let fee = (bytes_to_address(&fee.recipient)?, fee.bps, fee.on_input);
+        if encoded_solution
+            .function_signature
+            .starts_with("splitSwap")
+        {
+            (
+                given_amount,
+                given_token,
+                checked_token,
+                min_amount_out,
+                wrap,
+                unwrap,
+                n_tokens,
+                receiver,
+                user_transfer_type == &UserTransferType::TransferFrom,
+                fee,
+                encoded_solution.swaps,
+            )
+                .abi_encode()
+        } else {
+            (
+                given_amount,
+                given_token,
+                checked_token,
+                min_amount_out,
+                wrap,
+                unwrap,
+                receiver,
+                user_transfer_type == &UserTransferType::TransferFrom,
+                fee,
+                encoded_solution.swaps,
+            )
+                .abi_encode()
+        }
+    } else if encoded_solution
+        .function_signature
+        .contains("WithCallback")
+    {
+        let callback_data = solution
+            .receiver_callback
+            .clone()
+            .ok_or_else(|| {
+                EncodingError::FatalError(
+                    "The solution receiver callback must be set to call back the receiver"
+                        .to_string(),
+                )
+            })?
+            .to_vec();
+        if encoded_solution
+            .function_signature
+            .starts_with("splitSwap")
+        {
+            (
+                given_amount,
+                given_token,
+                checked_token,
+                min_amount_out,
+                wrap,
+                unwrap,
+                n_tokens,
+                receiver,
+                user_transfer_type == &UserTransferType::TransferFrom,
+                callback_data,
+                encoded_solution.swaps,
+            )
+                .abi_encode()
+        } else {
+            (
+                given_amount,
+                given_token,
+                checked_token,
+                min_amount_out,
+                wrap,
+                unwrap,
+                receiver,
+                user_transfer_type == &UserTransferType::TransferFrom,
+                callback_data,
+                encoded_solution.swaps,
+            )
+                .abi_encode()
+        }
+    } else if encoded_solution
+        .function_signature
+        .contains("exactOutSingleSwap")
+    {
+        // For exact out solutions, the given token is bought and the checked token is sold, up to
+        // the checked amount
+        (
+            given_amount,
+            checked_token,
+            given_token,
+            min_amount_out,
+            unwrap,
+            receiver,
+            user_transfer_type == &UserTransferType::TransferFrom,
+            encoded_solution.swaps,
+        )
+            .abi_encode()
+    } else if encoded_solution
         .function_signature
         .contains("singleSwapPermit2")
     {
@@ -177,6 +315,64 @@ pub fn encode_tycho_router_call(
             encoded_solution.swaps,
         )
             .abi_encode()
+    } else if encoded_solution
+        .function_signature
+        .contains("multiInputSplitSwap")
+    {
+        let (tokens_in, amounts_in): (Vec<Address>, Vec<U256>) = solution
+            .given_tokens()
+            .into_iter()
+            .map(|(token, amount)| Ok((bytes_to_address(token)?, biguint_to_u256(amount))))
+            .collect::<Result<Vec<_>, EncodingError>>()?
+            .into_iter()
+            .unzip();
+        (
+            amounts_in,
+            tokens_in,
+            checked_token,
+            min_amount_out,
+            unwrap,
+            n_tokens,
+            receiver,
+            user_transfer_type == &UserTransferType::TransferFrom,
+            encoded_solution.swaps,
+        )
+            .abi_encode()
+    } else if encoded_solution
+        .function_signature
+        .contains("splitSwapPermit2Batch")
+    {
+        // Solutions after the first one of a batch carry no permit, and spend the allowance
+        // granted by the first one
+        let (permit_batch, signature) = match encoded_solution.permit_batch {
+            Some(p) => {
+                let permit_batch = PermitBatch::try_from(&p)
+                    .map_err(|_| EncodingError::InvalidInput("Invalid permit batch".to_string()))?;
+                let permit_signer = permit_signer.ok_or(EncodingError::FatalError(
+                    "Signer must be set to use permit2".to_string(),
+                ))?;
+                let signature = permit_signer.sign_permit_batch(chain_id, &p)?;
+                (permit_batch, signature)
+            }
+            None => (
+                PermitBatch { details: vec![], spender: Address::ZERO, sigDeadline: U256::ZERO },
+                vec![],
+            ),
+        };
+        (
+            given_amount,
+            given_token,
+            checked_token,
+            min_amount_out,
+            wrap,
+            unwrap,
+            n_tokens,
+            receiver,
+            permit_batch,
+            signature,
+            encoded_solution.swaps,
+        )
+            .abi_encode()
     } else if encoded_solution
         .function_signature
         .contains("splitSwapPermit2")
@@ -219,12 +415,119 @@ pub fn encode_tycho_router_call(
     };
 
     let contract_interaction = encode_input(&encoded_solution.function_signature, method_calldata);
-    let value = if solution.given_token == *native_address {
+    let value = native_call_value(solution, native_address);
+    Ok(Transaction { to: encoded_solution.interacting_with, value, data: contract_interaction })
+}
+
+/// Returns the amount of native token to send with the Tycho router call of the given solution:
+/// the given amount if the solution sells the native token, else zero.
+///
+/// The router wraps this amount if the solution wraps the native token, and otherwise forwards it
+/// from its balance to the swaps taking the native token in (see
+/// `TransferOptimization::validate_native_flow`).
+pub fn native_call_value(solution: &Solution, native_address: &Bytes) -> BigUint {
+    if !solution.exact_out && solution.given_token == *native_address {
         solution.given_amount.clone()
     } else {
         BigUint::ZERO
+    }
+}
+
+/// Composes the given calls into a single transaction executing them in order through the given
+/// multicall-capable contract, e.g. to withdraw the token in from a lending protocol before the
+/// Tycho router call (see `encode_tycho_router_call`) and to deposit the token out after it.
+///
+/// Only Multicall3 can allow calls to fail: composing a call allowing failure with another
+/// multicall returns an `EncodingError::InvalidInput`. The value of the calls is sent with the
+/// transaction for Multicall3, and paid by the Safe or the account otherwise (see `Multicall`).
+///
+/// # Warning
+/// The calls are composed as they are. The caller is responsible for the approvals and token
+/// movements between them, e.g. for the Tycho router to be able to spend the withdrawn tokens.
+pub fn compose_calls(
+    multicall: &Multicall,
+    calls: Vec<CallSpec>,
+) -> Result<Transaction, EncodingError> {
+    if calls.is_empty() {
+        return Err(EncodingError::InvalidInput("No calls to compose".to_string()));
+    }
+    if !matches!(multicall, Multicall::Multicall3(_)) &&
+        calls
+            .iter()
+            .any(|call| call.allow_failure)
+    {
+        return Err(EncodingError::InvalidInput(
+            "Only Multicall3 can allow calls to fail".to_string(),
+        ));
+    }
+
+    let transaction = match multicall {
+        Multicall::Multicall3(address) => {
+            let value = calls
+                .iter()
+                .map(|call| &call.value)
+                .sum();
+            let calls = calls
+                .into_iter()
+                .map(|call| {
+                    Ok(Call3Value {
+                        target: bytes_to_address(&call.to)?,
+                        allowFailure: call.allow_failure,
+                        value: biguint_to_u256(&call.value),
+                        callData: call.data.into(),
+                    })
+                })
+                .collect::<Result<Vec<_>, EncodingError>>()?;
+            Transaction {
+                to: address.clone(),
+                value,
+                data: encode_input(
+                    "aggregate3Value((address,bool,uint256,bytes)[])",
+                    (calls,).abi_encode(),
+                ),
+            }
+        }
+        Multicall::SafeMultiSend(address) => {
+            let mut transactions = Vec::new();
+            for call in calls {
+                // Operation 0 (call), target, value, data length and data, packed
+                transactions.push(0u8);
+                transactions.extend(bytes_to_address(&call.to)?);
+                transactions.extend(biguint_to_u256(&call.value).to_be_bytes::<32>());
+                transactions.extend(U256::from(call.data.len()).to_be_bytes::<32>());
+                transactions.extend(call.data);
+            }
+            Transaction {
+                to: address.clone(),
+                value: BigUint::ZERO,
+                data: encode_input(
+                    "multiSend(bytes)",
+                    (AlloyBytes::from(transactions),).abi_encode(),
+                ),
+            }
+        }
+        Multicall::AccountBatch(address) => {
+            let calls = calls
+                .into_iter()
+                .map(|call| {
+                    Ok(BatchCall {
+                        target: bytes_to_address(&call.to)?,
+                        value: biguint_to_u256(&call.value),
+                        data: call.data.into(),
+                    })
+                })
+                .collect::<Result<Vec<_>, EncodingError>>()?;
+            Transaction {
+                to: address.clone(),
+                value: BigUint::ZERO,
+                data: encode_input(
+                    "executeBatch((address,uint256,bytes)[])",
+                    (calls,).abi_encode(),
+                ),
+            }
+        }
     };
-    Ok(Transaction { to: encoded_solution.interacting_with, value, data: contract_interaction })
+    Ok(transaction)
 }
 
 /// Signs a Permit2 `PermitSingle` struct using the EIP-712 signing scheme.
@@ -241,15 +544,7 @@ pub fn sign_permit(
     permit_single: &models::PermitSingle,
     signer: PrivateKeySigner,
 ) -> Result<Signature, EncodingError> {
-    let permit2_address = Address::from_str("0x000000000022D473030F116dDEE9F6B43aC78BA3")
-        .map_err(|_| EncodingError::FatalError("Permit2 address not valid".to_string()))?;
-    let domain = eip712_domain! {
-        name: "Permit2",
-        chain_id: chain_id,
-        verifying_contract: permit2_address,
-    };
-    let permit_single: PermitSingle = PermitSingle::try_from(permit_single)?;
-    let hash = permit_single.eip712_signing_hash(&domain);
+    let hash = permit_signing_hash(chain_id, permit_single)?;
     signer
         .sign_hash_sync(&hash)
         .map_err(|e| {
@@ -257,6 +552,152 @@ pub fn sign_permit(
         })
 }
 
+/// Returns the EIP-712 hash of a Permit2 `PermitSingle`, which is what the owner of the tokens
+/// signs (or, for smart contract wallets, what Permit2 passes to `isValidSignature`).
+pub fn permit_signing_hash(
+    chain_id: u64,
+    permit_single: &models::PermitSingle,
+) -> Result<B256, EncodingError> {
+    let permit_single: PermitSingle = PermitSingle::try_from(permit_single)?;
+    Ok(permit_single.eip712_signing_hash(&permit2_domain(chain_id)?))
+}
+
+/// Signs a Permit2 `PermitBatch` struct using the EIP-712 signing scheme, like `sign_permit`.
+///
+/// # Warning
+/// This is only an **example implementation** provided for reference purposes.
+/// **Do not rely on this in production.** You should implement your own version.
+pub fn sign_permit_batch(
+    chain_id: u64,
+    permit_batch: &models::PermitBatch,
+    signer: PrivateKeySigner,
+) -> Result<Signature, EncodingError> {
+    let hash = permit_batch_signing_hash(chain_id, permit_batch)?;
+    signer
+        .sign_hash_sync(&hash)
+        .map_err(|e| {
+            EncodingError::FatalError(format!(
+                "Failed to sign permit2 batch approval with error: {e}"
+            ))
+        })
+}
+
+/// Returns the EIP-712 hash of a Permit2 `PermitBatch`, which is what the owner of the tokens
+/// signs (or, for smart contract wallets, what Permit2 passes to `isValidSignature`).
+pub fn permit_batch_signing_hash(
+    chain_id: u64,
+    permit_batch: &models::PermitBatch,
+) -> Result<B256, EncodingError> {
+    let permit_batch: PermitBatch = PermitBatch::try_from(permit_batch)?;
+    Ok(permit_batch.eip712_signing_hash(&permit2_domain(chain_id)?))
+}
+
+/// Returns the EIP-712 typed data of a Permit2 `PermitSingle` (the `eth_signTypedData_v4`
+/// payload, with its domain and message), for signers outside of this crate like a KMS or a
+/// custody provider. Signing it produces the same signature as signing `permit_signing_hash`.
+pub fn permit_typed_data(
+    chain_id: u64,
+    permit_single: &models::PermitSingle,
+) -> Result<Value, EncodingError> {
+    // Checks that the permit fits the Permit2 types
+    PermitSingle::try_from(permit_single)?;
+    Ok(json!({
+        "types": {
+            "EIP712Domain": eip712_domain_type(),
+            "PermitSingle": [
+                {"name": "details", "type": "PermitDetails"},
+                {"name": "spender", "type": "address"},
+                {"name": "sigDeadline", "type": "uint256"},
+            ],
+            "PermitDetails": permit_details_type(),
+        },
+        "primaryType": "PermitSingle",
+        "domain": permit2_domain_json(chain_id)?,
+        "message": {
+            "details": permit_details_json(&permit_single.details)?,
+            "spender": bytes_to_address(&permit_single.spender)?.to_string(),
+            "sigDeadline": permit_single.sig_deadline.to_string(),
+        },
+    }))
+}
+
+/// Returns the EIP-712 typed data of a Permit2 `PermitBatch`, like `permit_typed_data`.
+pub fn permit_batch_typed_data(
+    chain_id: u64,
+    permit_batch: &models::PermitBatch,
+) -> Result<Value, EncodingError> {
+    PermitBatch::try_from(permit_batch)?;
+    let details = permit_batch
+        .details
+        .iter()
+        .map(permit_details_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(json!({
+        "types": {
+            "EIP712Domain": eip712_domain_type(),
+            "PermitBatch": [
+                {"name": "details", "type": "PermitDetails[]"},
+                {"name": "spender", "type": "address"},
+                {"name": "sigDeadline", "type": "uint256"},
+            ],
+            "PermitDetails": permit_details_type(),
+        },
+        "primaryType": "PermitBatch",
+        "domain": permit2_domain_json(chain_id)?,
+        "message": {
+            "details": details,
+            "spender": bytes_to_address(&permit_batch.spender)?.to_string(),
+            "sigDeadline": permit_batch.sig_deadline.to_string(),
+        },
+    }))
+}
+
+fn eip712_domain_type() -> Value {
+    json!([
+        {"name": "name", "type": "string"},
+        {"name": "chainId", "type": "uint256"},
+        {"name": "verifyingContract", "type": "address"},
+    ])
+}
+
+fn permit_details_type() -> Value {
+    json!([
+        {"name": "token", "type": "address"},
+        {"name": "amount", "type": "uint160"},
+        {"name": "expiration", "type": "uint48"},
+        {"name": "nonce", "type": "uint48"},
+    ])
+}
+
+fn permit_details_json(details: &models::PermitDetails) -> Result<Value, EncodingError> {
+    Ok(json!({
+        "token": bytes_to_address(&details.token)?.to_string(),
+        "amount": details.amount.to_string(),
+        "expiration": details.expiration.to_string(),
+        "nonce": details.nonce.to_string(),
+    }))
+}
+
+fn permit2_domain_json(chain_id: u64) -> Result<Value, EncodingError> {
+    let domain = permit2_domain(chain_id)?;
+    Ok(json!({
+        "name": domain.name,
+        "chainId": chain_id,
+        "verifyingContract": domain.verifying_contract.map(|address| address.to_string()),
+    }))
+}
+
+/// The EIP-712 domain of the Permit2 contract on the given chain.
+fn permit2_domain(chain_id: u64) -> Result<Eip712Domain, EncodingError> {
+    let permit2_address = Address::from_str("0x000000000022D473030F116dDEE9F6B43aC78BA3")
+        .map_err(|_| EncodingError::FatalError("Permit2 address not valid".to_string()))?;
+    Ok(eip712_domain! {
+        name: "Permit2",
+        chain_id: chain_id,
+        verifying_contract: permit2_address,
+    })
+}
+
 /// Encodes the input data for a function call to the given function selector.
 pub fn encode_input(selector: &str, mut encoded_args: Vec<u8>) -> Vec<u8> {
     let mut hasher = Keccak256::new();
@@ -278,3 +719,121 @@ pub fn encode_input(selector: &str, mut encoded_args: Vec<u8>) -> Vec<u8> {
     call_data.extend(encoded_args);
     call_data
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::{hex::encode, sol_types::SolCall};
+
+    use super::*;
+
+    sol! {
+        struct Call3Value {
+            address target;
+            bool allowFailure;
+            uint256 value;
+            bytes callData;
+        }
+
+        struct BatchCall {
+            address target;
+            uint256 value;
+            bytes data;
+        }
+
+        function aggregate3Value(Call3Value[] calls);
+        function multiSend(bytes transactions);
+        function executeBatch(BatchCall[] calls);
+    }
+
+    fn calls() -> Vec<CallSpec> {
+        vec![
+            CallSpec {
+                to: Bytes::from("0x87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2"),
+                value: BigUint::ZERO,
+                data: vec![0x69, 0x32, 0x8d, 0xec],
+                allow_failure: false,
+            },
+            Transaction {
+                to: Bytes::from("0xfD0b31d2E955fA55e3fa641Fe90e08b677188d35"),
+                value: BigUint::from(1_000u32),
+                data: vec![0xaa, 0xbb, 0xcc, 0xdd, 0x01],
+            }
+            .into(),
+        ]
+    }
+
+    #[test]
+    fn test_compose_calls_multicall3() {
+        let multicall =
+            Multicall::Multicall3(Bytes::from("0xcA11bde05977b3631167028862bE2a173976CA11"));
+
+        let transaction = compose_calls(&multicall, calls()).unwrap();
+
+        assert_eq!(transaction.to, Bytes::from("0xcA11bde05977b3631167028862bE2a173976CA11"));
+        assert_eq!(transaction.value, BigUint::from(1_000u32));
+        let decoded = aggregate3ValueCall::abi_decode(&transaction.data).unwrap();
+        assert_eq!(decoded.calls.len(), 2);
+        assert_eq!(
+            decoded.calls[1].target,
+            Address::from_str("0xfD0b31d2E955fA55e3fa641Fe90e08b677188d35").unwrap()
+        );
+        assert_eq!(decoded.calls[1].value, U256::from(1_000u32));
+        assert_eq!(decoded.calls[1].callData.to_vec(), vec![0xaa, 0xbb, 0xcc, 0xdd, 0x01]);
+        assert!(!decoded.calls[1].allowFailure);
+    }
+
+    #[test]
+    fn test_compose_calls_safe_multi_send() {
+        let multicall =
+            Multicall::SafeMultiSend(Bytes::from("0x9641d764fc13c8B624c04430C7356C1C7C8102e2"));
+
+        let transaction = compose_calls(&multicall, calls()).unwrap();
+
+        assert_eq!(transaction.value, BigUint::ZERO);
+        let decoded = multiSendCall::abi_decode(&transaction.data).unwrap();
+        assert_eq!(
+            encode(&decoded.transactions),
+            String::from(concat!(
+                // call 1: operation, target, value, data length and data
+                "00",
+                "87870bca3f3fd6335c3f4ce8392d69350b4fa4e2",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000004",
+                "69328dec",
+                // call 2
+                "00",
+                "fd0b31d2e955fa55e3fa641fe90e08b677188d35",
+                "00000000000000000000000000000000000000000000000000000000000003e8",
+                "0000000000000000000000000000000000000000000000000000000000000005",
+                "aabbccdd01",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_compose_calls_account_batch() {
+        let account = Bytes::from("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2");
+        let multicall = Multicall::AccountBatch(account.clone());
+
+        let transaction = compose_calls(&multicall, calls()).unwrap();
+
+        assert_eq!(transaction.to, account);
+        assert_eq!(transaction.value, BigUint::ZERO);
+        let decoded = executeBatchCall::abi_decode(&transaction.data).unwrap();
+        assert_eq!(decoded.calls.len(), 2);
+        assert_eq!(decoded.calls[0].data.to_vec(), vec![0x69, 0x32, 0x8d, 0xec]);
+    }
+
+    #[test]
+    fn test_compose_calls_invalid() {
+        let multicall = Multicall::AccountBatch(Bytes::from("0x01"));
+
+        let result = compose_calls(&multicall, vec![]);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+        let mut calls = calls();
+        calls[0].allow_failure = true;
+        let result = compose_calls(&multicall, calls);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use alloy::{primitives::keccak256, providers::Provider};
+use tokio::task::block_in_place;
+use tycho_common::Bytes;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+        utils::{bytes_to_address, get_client, get_runtime},
+    },
+    models::Environment,
+};
+
+/// The router and executors of one environment (e.g. production or simulation/staging).
+///
+/// # Fields
+/// * `router_address`: Address of the Tycho router of this environment
+/// * `swap_encoder_registry`: Registry with the executors of this environment
+/// * `expected_code_hashes`: Keccak256 hash of the deployed code of every contract (router and
+///   executors) of this environment, by address. If empty, no code hash checks are performed.
+#[derive(Clone)]
+pub struct EnvironmentConfig {
+    pub router_address: Bytes,
+    pub swap_encoder_registry: SwapEncoderRegistry,
+    pub expected_code_hashes: HashMap<Bytes, Bytes>,
+}
+
+impl EnvironmentConfig {
+    pub fn new(router_address: Bytes, swap_encoder_registry: SwapEncoderRegistry) -> Self {
+        Self { router_address, swap_encoder_registry, expected_code_hashes: HashMap::new() }
+    }
+
+    /// Sets the expected code hashes of the contracts of this environment.
+    pub fn with_expected_code_hashes(
+        mut self,
+        expected_code_hashes: HashMap<Bytes, Bytes>,
+    ) -> Self {
+        self.expected_code_hashes = expected_code_hashes;
+        self
+    }
+
+    /// Returns the addresses of all contracts used by this environment, with a description of
+    /// each one (used in error messages).
+    fn contracts(&self) -> Vec<(String, Bytes)> {
+        let mut contracts = vec![("router".to_string(), self.router_address.clone())];
+        let mut executors: Vec<(String, Bytes)> = self
+            .swap_encoder_registry
+            .executor_addresses()
+            .into_iter()
+            .map(|(protocol, address)| (format!("{protocol} executor"), address))
+            .collect();
+        executors.sort_by(|a, b| a.0.cmp(&b.0));
+        contracts.extend(executors);
+        contracts
+    }
+}
+
+/// Checks that every contract used by the environment is listed in its expected code hashes (if
+/// any), without performing any RPC calls.
+///
+/// This catches, for example, a production executor configured together with a staging router.
+pub fn validate_environment(
+    environment: Environment,
+    config: &EnvironmentConfig,
+) -> Result<(), EncodingError> {
+    if config.expected_code_hashes.is_empty() {
+        return Ok(());
+    }
+    for (contract, address) in config.contracts() {
+        if !config
+            .expected_code_hashes
+            .contains_key(&address)
+        {
+            return Err(EncodingError::InvalidInput(format!(
+                "The {contract} {address} is not part of the {environment:?} environment"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the production and simulation environments are not mixed up, without performing
+/// any RPC calls.
+///
+/// The two environments must use different routers and each of them must be valid on its own
+/// (see [`validate_environment`]).
+pub fn validate_environments(
+    production: &EnvironmentConfig,
+    simulation: &EnvironmentConfig,
+) -> Result<(), EncodingError> {
+    if production.router_address == simulation.router_address {
+        return Err(EncodingError::InvalidInput(format!(
+            "The production and simulation environments use the same router {}",
+            production.router_address
+        )));
+    }
+    validate_environment(Environment::Production, production)?;
+    validate_environment(Environment::Simulation, simulation)
+}
+
+/// Fetches the deployed code of every contract of the environment and checks that its hash is the
+/// expected one. Requires the `RPC_URL` environment variable to be set.
+pub fn verify_code_hashes(
+    environment: Environment,
+    config: &EnvironmentConfig,
+) -> Result<(), EncodingError> {
+    if config.expected_code_hashes.is_empty() {
+        return Ok(());
+    }
+    let (handle, _runtime) = get_runtime()?;
+    let client = block_in_place(|| handle.block_on(get_client()))?;
+    for (contract, address) in config.contracts() {
+        let Some(expected_hash) = config
+            .expected_code_hashes
+            .get(&address)
+        else {
+            continue;
+        };
+        let code = block_in_place(|| {
+            handle.block_on(async {
+                client
+                    .get_code_at(bytes_to_address(&address)?)
+                    .await
+                    .map_err(|err| {
+                        EncodingError::RecoverableError(format!(
+                            "Failed to fetch the code of {address}: {err}"
+                        ))
+                    })
+            })
+        })?;
+        let code_hash = Bytes::from(keccak256(&code).to_vec());
+        if code_hash != *expected_hash {
+            return Err(EncodingError::InvalidInput(format!(
+                "The {contract} {address} of the {environment:?} environment has code hash \
+                 {code_hash}, expected {expected_hash}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Fetches the deployed code of every contract of the environment and checks that there is code
+/// at each address. Requires the `RPC_URL` environment variable to be set.
+///
+/// Unlike [`verify_code_hashes`], this doesn't need the expected code hashes, which makes it
+/// useful on chains where the contracts are compiled differently (e.g. zkEVM rollups) or when
+/// bringing up a new chain, where a misconfigured executor address would otherwise only be
+/// detected by failing swaps.
+pub fn verify_code_presence(
+    environment: Environment,
+    config: &EnvironmentConfig,
+) -> Result<(), EncodingError> {
+    let (handle, _runtime) = get_runtime()?;
+    let client = block_in_place(|| handle.block_on(get_client()))?;
+    for (contract, address) in config.contracts() {
+        let code = block_in_place(|| {
+            handle.block_on(async {
+                client
+                    .get_code_at(bytes_to_address(&address)?)
+                    .await
+                    .map_err(|err| {
+                        EncodingError::RecoverableError(format!(
+                            "Failed to fetch the code of {address}: {err}"
+                        ))
+                    })
+            })
+        })?;
+        if code.is_empty() {
+            return Err(EncodingError::InvalidInput(format!(
+                "The {contract} {address} of the {environment:?} environment has no deployed code"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tycho_common::models::Chain;
+
+    use super::*;
+
+    fn registry(executor_address: &str) -> SwapEncoderRegistry {
+        let executors = format!(r#"{{"ethereum": {{"uniswap_v2": "{executor_address}"}}}}"#);
+        SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_validate_environments_same_router() {
+        let router = Bytes::from("0x3Ede3eCa2a72B3aeCC820E955B36f38437D01395");
+        let production = EnvironmentConfig::new(
+            router.clone(),
+            registry("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+        );
+        let simulation =
+            EnvironmentConfig::new(router, registry("0x2e234DAe75C793f67A35089C9d99245E1C58470b"));
+
+        assert!(matches!(
+            validate_environments(&production, &simulation),
+            Err(EncodingError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_environments_mixed_up_executor() {
+        let production_router = Bytes::from("0x3Ede3eCa2a72B3aeCC820E955B36f38437D01395");
+        let production_executor = Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f");
+        let staging_router = Bytes::from("0xF62849F9A0B5Bf2913b396098F7c7019b51A820a");
+        let staging_executor = Bytes::from("0x2e234DAe75C793f67A35089C9d99245E1C58470b");
+
+        let production = EnvironmentConfig::new(
+            production_router,
+            registry("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+        );
+        let staging_code_hashes = HashMap::from([
+            (staging_router.clone(), Bytes::from(keccak256([1u8]).to_vec())),
+            (staging_executor, Bytes::from(keccak256([2u8]).to_vec())),
+        ]);
+
+        // The staging router is configured with the production executor
+        let simulation = EnvironmentConfig::new(
+            staging_router.clone(),
+            registry(&production_executor.to_string()),
+        )
+        .with_expected_code_hashes(staging_code_hashes.clone());
+        let result = validate_environments(&production, &simulation);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+        let simulation = EnvironmentConfig::new(
+            staging_router,
+            registry("0x2e234DAe75C793f67A35089C9d99245E1C58470b"),
+        )
+        .with_expected_code_hashes(staging_code_hashes);
+        assert!(validate_environments(&production, &simulation).is_ok());
+    }
+}
@@ -0,0 +1,183 @@
+// This module is only compiled with the `failure-injection` feature. Without it, the injection
+// points are no-ops.
+use std::{cell::RefCell, collections::HashMap, thread, time::Duration};
+
+use crate::encoding::errors::EncodingError;
+
+/// The network-dependent operations of the encoders where failures can be injected.
+///
+/// # Variants
+/// * `AllowanceLookup`: ERC20 and Permit2 allowance RPC calls.
+/// * `RfqQuote`: Signed quote requests to RFQ protocols (e.g. Bebop and Hashflow).
+/// * `AttestationFetch`: Angstrom attestation requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InjectionPoint {
+    AllowanceLookup,
+    RfqQuote,
+    AttestationFetch,
+}
+
+/// How an injection point misbehaves.
+///
+/// # Fields
+/// * `error_rate`: Probability (between 0 and 1) of failing with an
+///   `EncodingError::RecoverableError`.
+/// * `latency`: Delay added before the operation (whether it fails or not).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InjectionRule {
+    pub error_rate: f64,
+    pub latency: Duration,
+}
+
+impl InjectionRule {
+    pub fn new(error_rate: f64) -> Self {
+        Self { error_rate, latency: Duration::ZERO }
+    }
+
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+}
+
+/// Injects failures and latencies into the network-dependent operations of the encoders, so that
+/// retry logic built on top of them can be tested deterministically.
+///
+/// Failures are drawn from a pseudo-random generator seeded with `seed`, so the same seed and the
+/// same sequence of operations always produce the same failures. The injector is installed for the
+/// current thread only (see [`FailureInjector::install`]), which keeps tests running in parallel
+/// isolated from each other.
+#[derive(Clone, Debug)]
+pub struct FailureInjector {
+    rules: HashMap<InjectionPoint, InjectionRule>,
+    state: u64,
+    injected: HashMap<InjectionPoint, u64>,
+}
+
+thread_local! {
+    static FAILURE_INJECTOR: RefCell<Option<FailureInjector>> = const { RefCell::new(None) };
+}
+
+impl FailureInjector {
+    pub fn new(seed: u64) -> Self {
+        // A zero state would make the xorshift generator always return zero
+        Self { rules: HashMap::new(), state: seed.max(1), injected: HashMap::new() }
+    }
+
+    /// Sets the rule of the given injection point.
+    pub fn rule(mut self, point: InjectionPoint, rule: InjectionRule) -> Self {
+        self.rules.insert(point, rule);
+        self
+    }
+
+    /// Installs the injector for the current thread until the returned guard is dropped.
+    pub fn install(self) -> InjectionGuard {
+        let previous = FAILURE_INJECTOR.with(|injector| injector.replace(Some(self)));
+        InjectionGuard { previous }
+    }
+
+    /// Returns how many failures were injected so far at each injection point, by the injector
+    /// installed for the current thread.
+    pub fn injected_failures() -> HashMap<InjectionPoint, u64> {
+        FAILURE_INJECTOR.with(|injector| {
+            injector
+                .borrow()
+                .as_ref()
+                .map(|injector| injector.injected.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn apply(&mut self, point: InjectionPoint) -> (Duration, bool) {
+        let Some(rule) = self.rules.get(&point).cloned() else {
+            return (Duration::ZERO, false);
+        };
+        let fail = self.next_f64() < rule.error_rate;
+        if fail {
+            *self.injected.entry(point).or_insert(0) += 1;
+        }
+        (rule.latency, fail)
+    }
+}
+
+/// Uninstalls a [`FailureInjector`] when dropped, restoring the previously installed one (if any).
+pub struct InjectionGuard {
+    previous: Option<FailureInjector>,
+}
+
+impl Drop for InjectionGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        FAILURE_INJECTOR.with(|injector| injector.replace(previous));
+    }
+}
+
+/// Applies the rule of the injector installed for the current thread to the given injection
+/// point: sleeps for its latency and returns an `EncodingError::RecoverableError` if a failure is
+/// drawn.
+pub(crate) fn inject(point: InjectionPoint) -> Result<(), EncodingError> {
+    let (latency, fail) = FAILURE_INJECTOR.with(|injector| {
+        injector
+            .borrow_mut()
+            .as_mut()
+            .map(|injector| injector.apply(point))
+            .unwrap_or_default()
+    });
+    if !latency.is_zero() {
+        thread::sleep(latency);
+    }
+    if fail {
+        return Err(EncodingError::RecoverableError(format!("Injected failure at {point:?}")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failures(seed: u64) -> Vec<bool> {
+        let _guard = FailureInjector::new(seed)
+            .rule(InjectionPoint::RfqQuote, InjectionRule::new(0.5))
+            .install();
+        (0..20)
+            .map(|_| inject(InjectionPoint::RfqQuote).is_err())
+            .collect()
+    }
+
+    #[test]
+    fn test_injection_is_deterministic() {
+        assert_eq!(failures(42), failures(42));
+        assert!(failures(42).contains(&true));
+        assert!(failures(42).contains(&false));
+    }
+
+    #[test]
+    fn test_injection_points_and_guard() {
+        {
+            let _guard = FailureInjector::new(1)
+                .rule(InjectionPoint::AllowanceLookup, InjectionRule::new(1.0))
+                .install();
+            assert!(matches!(
+                inject(InjectionPoint::AllowanceLookup),
+                Err(EncodingError::RecoverableError(_))
+            ));
+            // Points without a rule never fail
+            assert!(inject(InjectionPoint::AttestationFetch).is_ok());
+            assert_eq!(
+                FailureInjector::injected_failures(),
+                HashMap::from([(InjectionPoint::AllowanceLookup, 1)])
+            );
+        }
+        // The injector is uninstalled with its guard
+        assert!(inject(InjectionPoint::AllowanceLookup).is_ok());
+    }
+}
@@ -1,6 +1,8 @@
 use tycho_common::Bytes;
 
-use crate::encoding::{evm::constants::GROUPABLE_PROTOCOLS, models::Swap};
+use crate::encoding::{
+    evm::swap_encoder::swap_encoder_registry::SwapEncoderRegistry, models::Swap,
+};
 
 /// Represents a group of swaps that can be encoded into a single swap execution for gas
 /// optimization.
@@ -30,23 +32,46 @@ impl PartialEq for SwapGroup {
     }
 }
 
+impl SwapGroup {
+    /// Returns the receiver override of the group, set on its last swap (see
+    /// [`Swap::receiver_override`]).
+    pub fn receiver_override(&self) -> Option<&Bytes> {
+        self.swaps
+            .last()
+            .and_then(|swap| swap.get_receiver_override().as_ref())
+    }
+}
+
+/// Returns the protocol system the swaps of the given one are grouped and encoded with: Uniswap V4
+/// hooks pools share the PoolManager of Uniswap V4, so their swaps are grouped with its swaps.
+pub fn group_protocol_system(protocol_system: &str) -> &str {
+    if protocol_system == "uniswap_v4_hooks" {
+        "uniswap_v4"
+    } else {
+        protocol_system
+    }
+}
+
 /// Group consecutive swaps which can be encoded into one swap execution for gas optimization.
 ///
 /// An example where this applies is the case of USV4, which uses a PoolManager contract
-/// to save token transfers on consecutive swaps.
-pub fn group_swaps(swaps: &Vec<Swap>) -> Vec<SwapGroup> {
+/// to save token transfers on consecutive swaps. The protocols whose encoders have the `groupable`
+/// capability are grouped (see `SwapEncoder::capabilities`).
+pub fn group_swaps(
+    swaps: &Vec<Swap>,
+    swap_encoder_registry: &SwapEncoderRegistry,
+) -> Vec<SwapGroup> {
     let mut grouped_swaps: Vec<SwapGroup> = Vec::new();
     let mut current_group: Option<SwapGroup> = None;
     let mut last_swap_protocol = "".to_string();
     let mut groupable_protocol;
     let mut last_swap_out_token = Bytes::default();
     for swap in swaps {
-        let mut current_swap_protocol = swap.component().protocol_system.clone();
-        // Normalize uniswap_v4_hooks to uniswap_v4 for grouping (same PoolManager)
-        if current_swap_protocol == "uniswap_v4_hooks" {
-            current_swap_protocol = "uniswap_v4".to_string();
-        };
-        groupable_protocol = GROUPABLE_PROTOCOLS.contains(&current_swap_protocol.as_str());
+        let current_swap_protocol =
+            group_protocol_system(&swap.component().protocol_system).to_string();
+        groupable_protocol = swap_encoder_registry
+            .capabilities(&current_swap_protocol)
+            .is_some_and(|capabilities| capabilities.groupable);
 
         // Split 0 can also mean that the swap is the remaining part of a branch of splits,
         // so we need to check the last swap's out token as well
@@ -83,12 +108,176 @@ pub fn group_swaps(swaps: &Vec<Swap>) -> Vec<SwapGroup> {
     grouped_swaps
 }
 
+/// Merges consecutive groups splitting the same input token into the same output token into one
+/// group with several branches, for the protocols that can execute split branches within one swap
+/// execution (the `split_groupable` capability of their encoders).
+///
+/// The branches of a merged group are its groups, in order. The first swap of each branch carries
+/// its split of the merged group's amount (the last branch takes the remainder), while the merged
+/// group carries the split of the whole solution's amount of the token: the sum of its branches'
+/// splits, or 0 if its last branch takes the remainder of the token.
+///
+/// `swaps` are all the swaps of the solution, used to compute the share of the remainder of the
+/// token.
+pub fn group_split_branches(
+    swaps: &[Swap],
+    groups: Vec<SwapGroup>,
+    swap_encoder_registry: &SwapEncoderRegistry,
+) -> Vec<SwapGroup> {
+    let mut merged_groups = Vec::with_capacity(groups.len());
+    let mut branches: Vec<SwapGroup> = Vec::new();
+    for group in groups {
+        let joins_branches = branches.last().is_some_and(|last| {
+            last.split != 0f64 &&
+                last.protocol_system == group.protocol_system &&
+                last.token_in == group.token_in &&
+                last.token_out == group.token_out &&
+                can_branch(&group, swap_encoder_registry)
+        });
+        if !joins_branches {
+            merged_groups.extend(merge_branches(
+                swaps,
+                std::mem::take(&mut branches),
+                swap_encoder_registry,
+            ));
+        }
+        branches.push(group);
+    }
+    merged_groups.extend(merge_branches(swaps, branches, swap_encoder_registry));
+    merged_groups
+}
+
+/// Whether the group can be a branch of a merged group: its branches are told apart by their first
+/// swap, the only one taking the group's input token.
+fn can_branch(group: &SwapGroup, swap_encoder_registry: &SwapEncoderRegistry) -> bool {
+    swap_encoder_registry
+        .capabilities(&group.protocol_system)
+        .is_some_and(|capabilities| capabilities.split_groupable) &&
+        group.token_in != group.token_out &&
+        group.receiver_override().is_none() &&
+        group
+            .swaps
+            .iter()
+            .skip(1)
+            .all(|swap| *swap.token_in() != group.token_in)
+}
+
+/// Merges the given branches into one group, or returns them as they are if there are less than two
+/// of them or if they don't take any share of their token.
+fn merge_branches(
+    swaps: &[Swap],
+    branches: Vec<SwapGroup>,
+    swap_encoder_registry: &SwapEncoderRegistry,
+) -> Vec<SwapGroup> {
+    if branches.len() < 2 ||
+        !branches
+            .iter()
+            .all(|branch| can_branch(branch, swap_encoder_registry))
+    {
+        return branches;
+    }
+    let token_in = branches[0].token_in.clone();
+    let takes_remainder = branches
+        .last()
+        .is_some_and(|branch| branch.split == 0f64);
+    let branches_split: f64 = branches
+        .iter()
+        .map(|branch| branch.split)
+        .sum();
+    let share = if takes_remainder {
+        // The remainder of the token, plus the splits of the other branches
+        let token_split: f64 = swaps
+            .iter()
+            .filter(|swap| *swap.token_in() == token_in)
+            .map(Swap::get_split)
+            .sum();
+        1f64 - token_split + branches_split
+    } else {
+        branches_split
+    };
+    if share <= 0f64 {
+        return branches;
+    }
+
+    let n_branches = branches.len();
+    let mut merged = SwapGroup {
+        token_in,
+        token_out: branches[0].token_out.clone(),
+        protocol_system: branches[0].protocol_system.clone(),
+        swaps: Vec::new(),
+        split: if takes_remainder { 0f64 } else { share },
+    };
+    for (i, branch) in branches.into_iter().enumerate() {
+        // The last branch takes the remainder, so that rounding doesn't leave any dust
+        let branch_split = if i + 1 == n_branches { 0f64 } else { branch.split / share };
+        let mut branch_swaps = branch.swaps.into_iter();
+        if let Some(first_swap) = branch_swaps.next() {
+            merged
+                .swaps
+                .push(first_swap.split(branch_split));
+        }
+        merged.swaps.extend(branch_swaps);
+    }
+    vec![merged]
+}
+
+/// Splits the groups of several swaps sharing a swap with the degraded groups into one group per
+/// swap, so that every swap is encoded as its own swap execution (see the ungrouped fallback of the
+/// strategy encoders).
+///
+/// The swaps of a split group are taken from the solution's `swaps`, with their original splits of
+/// the solution's amount: the first swaps of the branches of a merged group (see
+/// `group_split_branches`) only carry their split of the group's amount.
+pub fn ungroup_swaps(
+    swaps: &[Swap],
+    groups: Vec<SwapGroup>,
+    degraded: &[SwapGroup],
+) -> Vec<SwapGroup> {
+    let is_degraded = |swap: &Swap| {
+        degraded
+            .iter()
+            .flat_map(|group| group.swaps.iter())
+            .any(|degraded_swap| same_hop(swap, degraded_swap))
+    };
+    let mut ungrouped = Vec::with_capacity(groups.len());
+    for group in groups {
+        if group.swaps.len() < 2 || !group.swaps.iter().any(is_degraded) {
+            ungrouped.push(group);
+            continue;
+        }
+        for swap in group.swaps.iter() {
+            let swap = swaps
+                .iter()
+                .find(|solution_swap| same_hop(solution_swap, swap))
+                .unwrap_or(swap);
+            ungrouped.push(SwapGroup {
+                token_in: swap.token_in().clone(),
+                token_out: swap.token_out().clone(),
+                protocol_system: group.protocol_system.clone(),
+                swaps: vec![swap.clone()],
+                split: swap.get_split(),
+            });
+        }
+    }
+    ungrouped
+}
+
+/// Whether the swaps go through the same component between the same tokens, whatever their splits.
+fn same_hop(swap: &Swap, other: &Swap) -> bool {
+    swap.component().id == other.component().id &&
+        swap.token_in() == other.token_in() &&
+        swap.token_out() == other.token_out()
+}
+
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{fs, str::FromStr};
 
     use alloy::primitives::hex;
-    use tycho_common::{models::protocol::ProtocolComponent, Bytes};
+    use tycho_common::{
+        models::{protocol::ProtocolComponent, Chain},
+        Bytes,
+    };
 
     use super::*;
     use crate::encoding::models::Swap;
@@ -97,6 +286,14 @@ mod tests {
         Bytes::from(hex!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").to_vec())
     }
 
+    fn registry() -> SwapEncoderRegistry {
+        let executors_addresses =
+            fs::read_to_string("config/test_executor_addresses.json").unwrap();
+        SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap()
+    }
+
     #[test]
     fn test_group_swaps_simple() {
         // The first and second swaps can be grouped since there is no split, and they are
@@ -127,7 +324,7 @@ mod tests {
             dai.clone(),
         );
         let swaps = vec![swap_weth_wbtc.clone(), swap_wbtc_usdc.clone(), swap_usdc_dai.clone()];
-        let grouped_swaps = group_swaps(&swaps);
+        let grouped_swaps = group_swaps(&swaps, &registry());
 
         assert_eq!(
             grouped_swaps,
@@ -195,7 +392,7 @@ mod tests {
             swap_weth_dai.clone(),
             swap_dai_usdc.clone(),
         ];
-        let grouped_swaps = group_swaps(&swaps);
+        let grouped_swaps = group_swaps(&swaps, &registry());
 
         assert_eq!(
             grouped_swaps,
@@ -274,7 +471,7 @@ mod tests {
             swap_weth_dai.clone(),
             swap_dai_usdc.clone(),
         ];
-        let grouped_swaps = group_swaps(&swaps);
+        let grouped_swaps = group_swaps(&swaps, &registry());
 
         assert_eq!(
             grouped_swaps,
@@ -330,7 +527,7 @@ mod tests {
             dai.clone(),
         );
         let swaps = vec![swap_weth_wbtc.clone(), swap_wbtc_usdc.clone(), swap_usdc_dai.clone()];
-        let grouped_swaps = group_swaps(&swaps);
+        let grouped_swaps = group_swaps(&swaps, &registry());
 
         assert_eq!(grouped_swaps.len(), 2);
         // First group should contain both uniswap_v4 and uniswap_v4_hooks swaps
@@ -346,4 +543,178 @@ mod tests {
         assert_eq!(grouped_swaps[1].token_out, dai);
         assert_eq!(grouped_swaps[1].protocol_system, "uniswap_v2");
     }
+
+    #[test]
+    fn test_group_split_branches() {
+        // The USV4 branches of WETH into USDC are merged, with their splits of the merged group.
+        // The USV2 swap takes 25% of WETH, so the merged group takes the remaining 75%.
+        //
+        //   WETH ──(USV2, 25%)──> USDC
+        //        ├─(USV4, 37.5%)──> USDC
+        //        └─(USV4, rest)──> WBTC ──(USV4)──> USDC
+
+        let weth = weth();
+        let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+        let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+
+        let swap_weth_usdc_univ2 = Swap::new(
+            ProtocolComponent { protocol_system: "uniswap_v2".to_string(), ..Default::default() },
+            weth.clone(),
+            usdc.clone(),
+        )
+        .split(0.25);
+        let swap_weth_usdc = Swap::new(
+            ProtocolComponent { protocol_system: "uniswap_v4".to_string(), ..Default::default() },
+            weth.clone(),
+            usdc.clone(),
+        )
+        .split(0.375);
+        let swap_weth_wbtc = Swap::new(
+            ProtocolComponent { protocol_system: "uniswap_v4".to_string(), ..Default::default() },
+            weth.clone(),
+            wbtc.clone(),
+        );
+        let swap_wbtc_usdc = Swap::new(
+            ProtocolComponent { protocol_system: "uniswap_v4".to_string(), ..Default::default() },
+            wbtc.clone(),
+            usdc.clone(),
+        );
+        let swaps = vec![
+            swap_weth_usdc_univ2.clone(),
+            swap_weth_usdc.clone(),
+            swap_weth_wbtc.clone(),
+            swap_wbtc_usdc.clone(),
+        ];
+        let registry = registry();
+        let grouped_swaps = group_split_branches(&swaps, group_swaps(&swaps, &registry), &registry);
+
+        assert_eq!(
+            grouped_swaps,
+            vec![
+                SwapGroup {
+                    swaps: vec![swap_weth_usdc_univ2],
+                    token_in: weth.clone(),
+                    token_out: usdc.clone(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    split: 0.25f64,
+                },
+                SwapGroup {
+                    swaps: vec![swap_weth_usdc.split(0.5), swap_weth_wbtc, swap_wbtc_usdc],
+                    token_in: weth,
+                    token_out: usdc,
+                    protocol_system: "uniswap_v4".to_string(),
+                    split: 0f64,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_split_branches_different_token_out() {
+        // The USV4 branches end in different tokens, so they can't be merged.
+        //
+        //   WETH ──(USV4, 40%)──> USDC
+        //        └─(USV4, rest)──> WBTC
+
+        let weth = weth();
+        let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+        let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+
+        let swaps = vec![
+            Swap::new(
+                ProtocolComponent {
+                    protocol_system: "uniswap_v4".to_string(),
+                    ..Default::default()
+                },
+                weth.clone(),
+                usdc,
+            )
+            .split(0.4),
+            Swap::new(
+                ProtocolComponent {
+                    protocol_system: "uniswap_v4".to_string(),
+                    ..Default::default()
+                },
+                weth,
+                wbtc,
+            ),
+        ];
+        let registry = registry();
+        let grouped_swaps = group_split_branches(&swaps, group_swaps(&swaps, &registry), &registry);
+
+        assert_eq!(grouped_swaps, group_swaps(&swaps, &registry));
+    }
+
+    #[test]
+    fn test_ungroup_swaps() {
+        // The degraded USV4 group is split into its swaps, with their original splits, while the
+        // other USV4 group is kept.
+        //
+        //   WETH ──(USV4, 40%)──> WBTC ──(USV4)──> USDC
+        //        └─(USV2, rest)──> DAI ──(USV4)──> USDT ──(USV4)──> USDC
+
+        let weth = weth();
+        let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+        let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let usdt = Bytes::from_str("0xdac17f958d2ee523a2206206994597c13d831ec7").unwrap();
+        let usv4_swap = |id: &str, token_in: &Bytes, token_out: &Bytes| {
+            Swap::new(
+                ProtocolComponent {
+                    id: id.to_string(),
+                    protocol_system: "uniswap_v4".to_string(),
+                    ..Default::default()
+                },
+                token_in.clone(),
+                token_out.clone(),
+            )
+        };
+
+        let swap_weth_wbtc = usv4_swap("0x01", &weth, &wbtc).split(0.4);
+        let swap_wbtc_usdc = usv4_swap("0x02", &wbtc, &usdc);
+        let swap_weth_dai = Swap::new(
+            ProtocolComponent {
+                id: "0x03".to_string(),
+                protocol_system: "uniswap_v2".to_string(),
+                ..Default::default()
+            },
+            weth.clone(),
+            dai.clone(),
+        );
+        let swap_dai_usdt = usv4_swap("0x04", &dai, &usdt);
+        let swap_usdt_usdc = usv4_swap("0x05", &usdt, &usdc);
+        let swaps = vec![
+            swap_weth_wbtc.clone(),
+            swap_wbtc_usdc.clone(),
+            swap_weth_dai.clone(),
+            swap_dai_usdt.clone(),
+            swap_usdt_usdc.clone(),
+        ];
+        let grouped_swaps = group_swaps(&swaps, &registry());
+        let degraded = vec![grouped_swaps[0].clone()];
+
+        let ungrouped_swaps = ungroup_swaps(&swaps, grouped_swaps.clone(), &degraded);
+
+        assert_eq!(
+            ungrouped_swaps,
+            vec![
+                SwapGroup {
+                    swaps: vec![swap_weth_wbtc],
+                    token_in: weth.clone(),
+                    token_out: wbtc.clone(),
+                    protocol_system: "uniswap_v4".to_string(),
+                    split: 0.4f64,
+                },
+                SwapGroup {
+                    swaps: vec![swap_wbtc_usdc],
+                    token_in: wbtc,
+                    token_out: usdc,
+                    protocol_system: "uniswap_v4".to_string(),
+                    split: 0f64,
+                },
+                grouped_swaps[1].clone(),
+                grouped_swaps[2].clone(),
+            ]
+        );
+    }
 }
@@ -0,0 +1,287 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tycho_common::Bytes;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::audit::solution_fingerprint,
+    models::{EncodedSolution, Solution},
+};
+
+/// Statistics of an [`IdempotencyCache`].
+///
+/// # Fields
+/// * `hits`: Number of solutions served from the cache.
+/// * `misses`: Number of solutions with an idempotency key that had to be encoded.
+/// * `evictions`: Number of entries removed because they expired or the cache was full.
+/// * `entries`: Number of entries currently in the cache.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+}
+
+struct CacheEntry {
+    inserted_at: Instant,
+    fingerprint: Bytes,
+    encoded_solution: EncodedSolution,
+}
+
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    stats: CacheStats,
+}
+
+/// A short-lived, in-process cache of encoded solutions indexed by their idempotency key.
+///
+/// Retrying a solution with the same `idempotency_key` returns the previously encoded solution
+/// without encoding it again, so no new RFQ quotes are requested and no approval checks are
+/// performed. Reusing a key for a different solution is rejected.
+///
+/// The cache is meant to be shared (through an `Arc`) between the encoder and the caller, who can
+/// read the cache statistics at any time.
+pub struct IdempotencyCache {
+    ttl: Duration,
+    max_entries: usize,
+    state: Mutex<CacheState>,
+}
+
+impl IdempotencyCache {
+    /// Creates a cache whose entries expire after `ttl`. Once `max_entries` is reached, the oldest
+    /// entry is evicted to make room for a new one.
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            state: Mutex::new(CacheState { entries: HashMap::new(), stats: CacheStats::default() }),
+        }
+    }
+
+    /// Returns the cached encoded solution for the solution's idempotency key, or encodes it with
+    /// `encode` and caches the result. Solutions without an idempotency key are always encoded.
+    pub fn get_or_encode<F>(
+        &self,
+        solution: &Solution,
+        encode: F,
+    ) -> Result<EncodedSolution, EncodingError>
+    where
+        F: FnOnce(&Solution) -> Result<EncodedSolution, EncodingError>,
+    {
+        let Some(key) = &solution.idempotency_key else {
+            return encode(solution);
+        };
+        let fingerprint = solution_fingerprint(solution)?;
+        if let Some(encoded_solution) = self.lookup(key, &fingerprint)? {
+            return Ok(encoded_solution);
+        }
+
+        // The lock is not held while encoding, since encoding might request RFQ quotes.
+        let encoded_solution = encode(solution)?;
+        self.insert(key, fingerprint, &encoded_solution)?;
+        Ok(encoded_solution)
+    }
+
+    /// Returns the cached encoded solution like [`IdempotencyCache::get_or_encode`], awaiting
+    /// the `encode` future of the solution on a cache miss. On a hit, the future is dropped
+    /// without being polled.
+    pub async fn get_or_encode_async<F>(
+        &self,
+        solution: &Solution,
+        encode: F,
+    ) -> Result<EncodedSolution, EncodingError>
+    where
+        F: Future<Output = Result<EncodedSolution, EncodingError>>,
+    {
+        let Some(key) = &solution.idempotency_key else {
+            return encode.await;
+        };
+        let fingerprint = solution_fingerprint(solution)?;
+        if let Some(encoded_solution) = self.lookup(key, &fingerprint)? {
+            return Ok(encoded_solution);
+        }
+
+        let encoded_solution = encode.await?;
+        self.insert(key, fingerprint, &encoded_solution)?;
+        Ok(encoded_solution)
+    }
+
+    /// Returns the encoded solution cached for the key, counting the lookup as a hit or a miss.
+    fn lookup(
+        &self,
+        key: &str,
+        fingerprint: &Bytes,
+    ) -> Result<Option<EncodedSolution>, EncodingError> {
+        let mut state = self.lock_state()?;
+        self.evict_expired(&mut state);
+        if let Some(entry) = state.entries.get(key) {
+            if entry.fingerprint != *fingerprint {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Idempotency key {key} was already used for a different solution"
+                )));
+            }
+            let encoded_solution = entry.encoded_solution.clone();
+            state.stats.hits += 1;
+            return Ok(Some(encoded_solution));
+        }
+        state.stats.misses += 1;
+        Ok(None)
+    }
+
+    /// Caches the encoded solution for the key, evicting the oldest entry if the cache is full.
+    fn insert(
+        &self,
+        key: &str,
+        fingerprint: Bytes,
+        encoded_solution: &EncodedSolution,
+    ) -> Result<(), EncodingError> {
+        let mut state = self.lock_state()?;
+        if !state.entries.contains_key(key) && state.entries.len() >= self.max_entries {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.inserted_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+                state.stats.evictions += 1;
+            }
+        }
+        if self.max_entries > 0 {
+            state.entries.insert(
+                key.to_string(),
+                CacheEntry {
+                    inserted_at: Instant::now(),
+                    fingerprint,
+                    encoded_solution: encoded_solution.clone(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the current statistics of the cache.
+    pub fn stats(&self) -> Result<CacheStats, EncodingError> {
+        let mut state = self.lock_state()?;
+        self.evict_expired(&mut state);
+        let mut stats = state.stats.clone();
+        stats.entries = state.entries.len();
+        Ok(stats)
+    }
+
+    fn evict_expired(&self, state: &mut CacheState) {
+        let ttl = self.ttl;
+        let before = state.entries.len();
+        state
+            .entries
+            .retain(|_, entry| entry.inserted_at.elapsed() < ttl);
+        state.stats.evictions += (before - state.entries.len()) as u64;
+    }
+
+    fn lock_state(&self) -> Result<std::sync::MutexGuard<'_, CacheState>, EncodingError> {
+        self.state
+            .lock()
+            .map_err(|_| EncodingError::FatalError("Idempotency cache is poisoned".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn solution(key: &str, given_amount: u32) -> Solution {
+        Solution {
+            given_token: Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            given_amount: BigUint::from(given_amount),
+            checked_token: Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+            idempotency_key: Some(key.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn encoded_solution() -> EncodedSolution {
+        EncodedSolution {
+            swaps: vec![1, 2, 3],
+            interacting_with: Bytes::from("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66"),
+            function_signature: "singleSwap".to_string(),
+            n_tokens: 0,
+            permit: None,
+            permit_batch: None,
+            native_action: None,
+            state_versions: vec![],
+            callback_allowlist: None,
+            summary: Default::default(),
+            gas_estimate: 0,
+            debug_info: None,
+            degraded_groups: vec![],
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_does_not_encode_again() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60), 10);
+        let calls = Cell::new(0);
+        let encode = |_: &Solution| {
+            calls.set(calls.get() + 1);
+            Ok(encoded_solution())
+        };
+
+        let first = cache
+            .get_or_encode(&solution("a", 1000), encode)
+            .unwrap();
+        let second = cache
+            .get_or_encode(&solution("a", 1000), encode)
+            .unwrap();
+
+        assert_eq!(calls.get(), 1);
+        assert_eq!(first.swaps, second.swaps);
+        assert_eq!(
+            cache.stats().unwrap(),
+            CacheStats { hits: 1, misses: 1, evictions: 0, entries: 1 }
+        );
+    }
+
+    #[test]
+    fn test_key_reused_for_different_solution() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60), 10);
+        cache
+            .get_or_encode(&solution("a", 1000), |_| Ok(encoded_solution()))
+            .unwrap();
+
+        let result = cache.get_or_encode(&solution("a", 2000), |_| Ok(encoded_solution()));
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_expired_and_full_cache_evictions() {
+        let cache = IdempotencyCache::new(Duration::ZERO, 10);
+        cache
+            .get_or_encode(&solution("a", 1000), |_| Ok(encoded_solution()))
+            .unwrap();
+        assert_eq!(cache.stats().unwrap().entries, 0);
+        assert_eq!(cache.stats().unwrap().evictions, 1);
+
+        let cache = IdempotencyCache::new(Duration::from_secs(60), 1);
+        cache
+            .get_or_encode(&solution("a", 1000), |_| Ok(encoded_solution()))
+            .unwrap();
+        cache
+            .get_or_encode(&solution("b", 1000), |_| Ok(encoded_solution()))
+            .unwrap();
+        assert_eq!(
+            cache.stats().unwrap(),
+            CacheStats { hits: 0, misses: 2, evictions: 1, entries: 1 }
+        );
+    }
+}
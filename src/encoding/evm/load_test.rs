@@ -0,0 +1,272 @@
+// This module is only meant for capacity planning and is not used by the encoders themselves.
+use std::{
+    collections::HashMap,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::encoding::{errors::EncodingError, models::Solution, tycho_encoder::TychoEncoder};
+
+/// Configuration of a load test.
+///
+/// # Fields
+/// * `rate_per_second`: Number of solutions to encode per second. The solutions are replayed in a
+///   loop until the test is over.
+/// * `duration`: Total duration of the test.
+/// * `slice_duration`: Duration of each time slice of the report.
+#[derive(Clone, Debug)]
+pub struct LoadTestConfig {
+    pub rate_per_second: f64,
+    pub duration: Duration,
+    pub slice_duration: Duration,
+}
+
+impl Default for LoadTestConfig {
+    fn default() -> Self {
+        Self {
+            rate_per_second: 10.0,
+            duration: Duration::from_secs(60),
+            slice_duration: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Latency statistics of a set of encodings.
+///
+/// # Fields
+/// * `requests`: Number of solutions encoded (successfully or not).
+/// * `failures`: Number of failed encodings by error kind (e.g. `RecoverableError`).
+/// * `p50`: Median encode latency.
+/// * `p99`: 99th percentile of the encode latency.
+/// * `max`: Highest encode latency.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyReport {
+    pub requests: usize,
+    pub failures: HashMap<String, usize>,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyReport {
+    fn new(samples: &[Sample]) -> Self {
+        let mut latencies: Vec<Duration> = samples
+            .iter()
+            .map(|sample| sample.latency)
+            .collect();
+        latencies.sort();
+        let mut failures = HashMap::new();
+        for sample in samples {
+            if let Some(kind) = &sample.error_kind {
+                *failures
+                    .entry(kind.to_string())
+                    .or_insert(0) += 1;
+            }
+        }
+        Self {
+            requests: samples.len(),
+            failures,
+            p50: percentile(&latencies, 50),
+            p99: percentile(&latencies, 99),
+            max: latencies
+                .last()
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the number of failed encodings.
+    pub fn failed(&self) -> usize {
+        self.failures.values().sum()
+    }
+}
+
+/// Report of a load test, for the whole test and for each of its time slices.
+///
+/// # Fields
+/// * `total`: Statistics over the whole test.
+/// * `slices`: Statistics of each time slice, in chronological order. A slow-down while RFQ
+///   endpoints throttle the encoder shows up in the slices, even if it's hidden in the total.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoadTestReport {
+    pub total: LatencyReport,
+    pub slices: Vec<LatencyReport>,
+}
+
+struct Sample {
+    started_at: Duration,
+    latency: Duration,
+    error_kind: Option<&'static str>,
+}
+
+/// Replays the given solutions against the encoder at the configured rate and reports the encode
+/// latencies and failures.
+///
+/// Solutions are encoded one at a time, each one at its scheduled time (or right after the
+/// previous one if the encoder can't keep up). Whether network calls are made (e.g. RFQ quote
+/// requests) only depends on the encoder and the protocol states attached to the swaps, so the
+/// same harness can be used against mock states (see `testing_utils`) and real endpoints.
+pub fn run_load_test(
+    encoder: &dyn TychoEncoder,
+    solutions: &[Solution],
+    config: &LoadTestConfig,
+) -> Result<LoadTestReport, EncodingError> {
+    if solutions.is_empty() {
+        return Err(EncodingError::InvalidInput("No solutions to replay".to_string()));
+    }
+    if config.rate_per_second.is_nan() ||
+        config.rate_per_second <= 0.0 ||
+        config.slice_duration.is_zero()
+    {
+        return Err(EncodingError::InvalidInput(
+            "The rate and the slice duration of a load test must be positive".to_string(),
+        ));
+    }
+
+    let interval = Duration::from_secs_f64(1.0 / config.rate_per_second);
+    let start = Instant::now();
+    let mut samples = Vec::new();
+    for (i, solution) in solutions.iter().cycle().enumerate() {
+        let scheduled_at = interval.mul_f64(i as f64);
+        if scheduled_at >= config.duration {
+            break;
+        }
+        if let Some(wait) = scheduled_at.checked_sub(start.elapsed()) {
+            thread::sleep(wait);
+        }
+
+        let started_at = start.elapsed();
+        let result = encoder.encode_solutions(vec![solution.clone()]);
+        samples.push(Sample {
+            started_at,
+            latency: start.elapsed() - started_at,
+            error_kind: result.err().map(|err| error_kind(&err)),
+        });
+    }
+
+    let n_slices = config
+        .duration
+        .as_nanos()
+        .div_ceil(config.slice_duration.as_nanos())
+        .max(1) as usize;
+    let mut slices: Vec<Vec<Sample>> = (0..n_slices)
+        .map(|_| Vec::new())
+        .collect();
+    let total = LatencyReport::new(&samples);
+    for sample in samples {
+        let slice = (sample.started_at.as_nanos() / config.slice_duration.as_nanos()) as usize;
+        slices[slice.min(n_slices - 1)].push(sample);
+    }
+    Ok(LoadTestReport {
+        total,
+        slices: slices
+            .iter()
+            .map(|samples| LatencyReport::new(samples))
+            .collect(),
+    })
+}
+
+fn error_kind(error: &EncodingError) -> &'static str {
+    match error {
+        EncodingError::InvalidInput(_) => "InvalidInput",
+        EncodingError::FatalError(_) => "FatalError",
+        EncodingError::RecoverableError(_) => "RecoverableError",
+        EncodingError::NotImplementedError(_) => "NotImplementedError",
+        EncodingError::CalldataTooLarge(_) => "CalldataTooLarge",
+        EncodingError::QuoteTimeout(_) => "QuoteTimeout",
+        EncodingError::QuoteExpired(_) => "QuoteExpired",
+        EncodingError::UnsafeToken(_) => "UnsafeToken",
+        EncodingError::Timeout(_) => "Timeout",
+    }
+}
+
+/// Nearest-rank percentile of sorted latencies.
+fn percentile(sorted: &[Duration], percentile: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percentile * sorted.len())
+        .div_ceil(100)
+        .max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use num_bigint::BigUint;
+
+    use super::*;
+    use crate::encoding::models::{EncodedSolution, Transaction};
+
+    /// Fails every third solution with a recoverable error, like a throttled RFQ endpoint.
+    struct FlakyEncoder {
+        calls: AtomicUsize,
+    }
+
+    impl TychoEncoder for FlakyEncoder {
+        fn encode_solutions(
+            &self,
+            _solutions: Vec<Solution>,
+        ) -> Result<Vec<EncodedSolution>, EncodingError> {
+            if self
+                .calls
+                .fetch_add(1, Ordering::Relaxed) %
+                3 ==
+                2
+            {
+                return Err(EncodingError::RecoverableError("Quote request timed out".to_string()));
+            }
+            Ok(vec![])
+        }
+
+        fn encode_full_calldata(
+            &self,
+            _solutions: Vec<Solution>,
+        ) -> Result<Vec<Transaction>, EncodingError> {
+            Ok(vec![])
+        }
+
+        fn validate_solution(&self, _solution: &Solution) -> Result<(), EncodingError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_run_load_test() {
+        let encoder = FlakyEncoder { calls: AtomicUsize::new(0) };
+        let solutions = vec![Solution { given_amount: BigUint::from(1u32), ..Default::default() }];
+        let config = LoadTestConfig {
+            rate_per_second: 100.0,
+            duration: Duration::from_millis(60),
+            slice_duration: Duration::from_millis(20),
+        };
+
+        let report = run_load_test(&encoder, &solutions, &config).unwrap();
+
+        assert_eq!(report.total.requests, 6);
+        assert_eq!(report.total.failures, HashMap::from([("RecoverableError".to_string(), 2)]));
+        assert_eq!(report.slices.len(), 3);
+        assert_eq!(
+            report
+                .slices
+                .iter()
+                .map(|slice| slice.requests)
+                .sum::<usize>(),
+            6
+        );
+        assert!(report.total.p50 <= report.total.p99);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<Duration> = (1..=100)
+            .map(Duration::from_millis)
+            .collect();
+        assert_eq!(percentile(&latencies, 50), Duration::from_millis(50));
+        assert_eq!(percentile(&latencies, 99), Duration::from_millis(99));
+        assert_eq!(percentile(&latencies[..1], 99), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use alloy::{
+    primitives::{Address, Bytes as AlloyBytes, Keccak256, U256},
+    sol_types::SolValue,
+};
+use tycho_common::Bytes;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        constants::RFQ_PROTOCOLS,
+        encoding_utils::encode_input,
+        environments::EnvironmentConfig,
+        utils::{ple_decode, ple_encode},
+    },
+};
+
+const SINGLE_SWAP: &str =
+    "singleSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)";
+const SEQUENTIAL_SWAP: &str =
+    "sequentialSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)";
+const SPLIT_SWAP: &str =
+    "splitSwap(uint256,address,address,uint256,bool,bool,uint256,address,bool,bytes)";
+const PERMIT2_SWAPS: [&str; 3] = [
+    "singleSwapPermit2(uint256,address,address,uint256,bool,bool,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+    "sequentialSwapPermit2(uint256,address,address,uint256,bool,bool,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+    "splitSwapPermit2(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+];
+
+/// Length of the split swap header: token in index (1 byte), token out index (1 byte) and split
+/// (3 bytes).
+const SPLIT_HEADER_LENGTH: usize = 5;
+
+type SequentialArgs = (U256, Address, Address, U256, bool, bool, Address, bool, AlloyBytes);
+type SplitArgs = (U256, Address, Address, U256, bool, bool, U256, Address, bool, AlloyBytes);
+
+/// A part of the calldata that can't be migrated to the new router.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnsupportedConstruct {
+    /// The function selector is not one of the router's swap functions.
+    UnknownFunction(Bytes),
+    /// The Permit2 signature authorizes the old router as spender.
+    Permit2Signature,
+    /// The executor of the swap is not part of the old environment.
+    UnknownExecutor { swap_index: usize, executor: Bytes },
+    /// The protocol has no executor in the new environment.
+    MissingExecutor { swap_index: usize, protocol: String },
+    /// The swap carries a quote signed for the old router (e.g. the RFQ taker).
+    SignedQuote { swap_index: usize, protocol: String },
+}
+
+/// Migrates calldata encoded for a router of one environment to the router of another one (e.g.
+/// while migrating to a new router version with queued orders encoded against the old one).
+///
+/// Swaps are migrated at the executor level: every executor of the old environment is replaced by
+/// the executor of the same protocol in the new environment, and the old router address is
+/// replaced by the new one wherever it appears in the protocol data (e.g. as receiver). This is
+/// only correct if both router versions share the same swap encoding. Calldata that can't be
+/// migrated this way is reported with its [`UnsupportedConstruct`]s, and has to be encoded again
+/// from the original solution.
+///
+/// # Fields
+/// * `old_router`: Address of the router the calldata was encoded for
+/// * `new_router`: Address of the router to migrate the calldata to
+/// * `executors`: Protocol and new executor address, by old executor address
+#[derive(Clone, Debug)]
+pub struct RouterMigration {
+    old_router: Bytes,
+    new_router: Bytes,
+    executors: HashMap<Bytes, (String, Option<Bytes>)>,
+}
+
+impl RouterMigration {
+    pub fn new(old: &EnvironmentConfig, new: &EnvironmentConfig) -> Self {
+        let new_executors = new
+            .swap_encoder_registry
+            .executor_addresses();
+        let executors = old
+            .swap_encoder_registry
+            .executor_addresses()
+            .into_iter()
+            .map(|(protocol, executor)| {
+                let new_executor = new_executors.get(&protocol).cloned();
+                (executor, (protocol, new_executor))
+            })
+            .collect();
+        Self {
+            old_router: old.router_address.clone(),
+            new_router: new.router_address.clone(),
+            executors,
+        }
+    }
+
+    /// Returns the parts of the calldata that can't be migrated. The calldata can be migrated with
+    /// [`RouterMigration::migrate`] if none are found.
+    pub fn check(&self, calldata: &[u8]) -> Result<Vec<UnsupportedConstruct>, EncodingError> {
+        match self.migrate_calldata(calldata) {
+            Ok(_) => Ok(vec![]),
+            Err(MigrationFailure::Unsupported(unsupported)) => Ok(unsupported),
+            Err(MigrationFailure::Encoding(err)) => Err(err),
+        }
+    }
+
+    /// Re-encodes the calldata for the new router. Returns an `EncodingError::InvalidInput`
+    /// listing the unsupported constructs if the calldata can't be migrated.
+    pub fn migrate(&self, calldata: &[u8]) -> Result<Vec<u8>, EncodingError> {
+        self.migrate_calldata(calldata)
+            .map_err(|failure| match failure {
+                MigrationFailure::Unsupported(unsupported) => EncodingError::InvalidInput(format!(
+                    "Calldata can't be migrated to router {}: {unsupported:?}",
+                    self.new_router
+                )),
+                MigrationFailure::Encoding(err) => err,
+            })
+    }
+
+    fn migrate_calldata(&self, calldata: &[u8]) -> Result<Vec<u8>, MigrationFailure> {
+        if calldata.len() < 4 {
+            return Err(EncodingError::InvalidInput("Calldata is too short".to_string()).into());
+        }
+        let (selector, args) = calldata.split_at(4);
+        let decode_error =
+            |_| EncodingError::InvalidInput("Failed to decode the router call".to_string());
+
+        if PERMIT2_SWAPS
+            .iter()
+            .any(|signature| function_selector(signature) == selector)
+        {
+            return Err(MigrationFailure::Unsupported(vec![UnsupportedConstruct::Permit2Signature]));
+        }
+        if selector == function_selector(SINGLE_SWAP) {
+            let mut args = SequentialArgs::abi_decode_params(args).map_err(decode_error)?;
+            let swaps = self.migrate_swaps(vec![args.8.to_vec()], 0)?;
+            args.8 = AlloyBytes::from(swaps.concat());
+            Ok(encode_input(SINGLE_SWAP, args.abi_encode()))
+        } else if selector == function_selector(SEQUENTIAL_SWAP) {
+            let mut args = SequentialArgs::abi_decode_params(args).map_err(decode_error)?;
+            let swaps = self.migrate_swaps(ple_decode(&args.8)?, 0)?;
+            args.8 = AlloyBytes::from(ple_encode(swaps)?);
+            Ok(encode_input(SEQUENTIAL_SWAP, args.abi_encode()))
+        } else if selector == function_selector(SPLIT_SWAP) {
+            let mut args = SplitArgs::abi_decode_params(args).map_err(decode_error)?;
+            let swaps = self.migrate_swaps(ple_decode(&args.9)?, SPLIT_HEADER_LENGTH)?;
+            args.9 = AlloyBytes::from(ple_encode(swaps)?);
+            Ok(encode_input(SPLIT_SWAP, args.abi_encode()))
+        } else {
+            Err(MigrationFailure::Unsupported(vec![UnsupportedConstruct::UnknownFunction(
+                Bytes::from(selector.to_vec()),
+            )]))
+        }
+    }
+
+    /// Migrates the encoded swaps, each one being `header | executor | protocol data`.
+    fn migrate_swaps(
+        &self,
+        swaps: Vec<Vec<u8>>,
+        header_length: usize,
+    ) -> Result<Vec<Vec<u8>>, MigrationFailure> {
+        let mut unsupported = vec![];
+        let mut migrated = Vec::with_capacity(swaps.len());
+        for (swap_index, swap) in swaps.into_iter().enumerate() {
+            if swap.len() < header_length + 20 {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Swap {swap_index} is too short to contain an executor"
+                ))
+                .into());
+            }
+            let (header, rest) = swap.split_at(header_length);
+            let (executor, protocol_data) = rest.split_at(20);
+            let executor = Bytes::from(executor.to_vec());
+
+            let Some((protocol, new_executor)) = self.executors.get(&executor) else {
+                unsupported.push(UnsupportedConstruct::UnknownExecutor { swap_index, executor });
+                continue;
+            };
+            if RFQ_PROTOCOLS.contains(protocol.as_str()) {
+                unsupported.push(UnsupportedConstruct::SignedQuote {
+                    swap_index,
+                    protocol: protocol.clone(),
+                });
+                continue;
+            }
+            let Some(new_executor) = new_executor else {
+                unsupported.push(UnsupportedConstruct::MissingExecutor {
+                    swap_index,
+                    protocol: protocol.clone(),
+                });
+                continue;
+            };
+
+            let mut migrated_swap = header.to_vec();
+            migrated_swap.extend(new_executor.to_vec());
+            migrated_swap.extend(replace_address(
+                protocol_data,
+                &self.old_router,
+                &self.new_router,
+            ));
+            migrated.push(migrated_swap);
+        }
+        if !unsupported.is_empty() {
+            return Err(MigrationFailure::Unsupported(unsupported));
+        }
+        Ok(migrated)
+    }
+}
+
+enum MigrationFailure {
+    Unsupported(Vec<UnsupportedConstruct>),
+    Encoding(EncodingError),
+}
+
+impl From<EncodingError> for MigrationFailure {
+    fn from(err: EncodingError) -> Self {
+        MigrationFailure::Encoding(err)
+    }
+}
+
+fn function_selector(signature: &str) -> [u8; 4] {
+    let mut hasher = Keccak256::new();
+    hasher.update(signature.as_bytes());
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&hasher.finalize()[..4]);
+    selector
+}
+
+/// Replaces every occurrence of the `old` address in `data` by the `new` one.
+fn replace_address(data: &[u8], old: &Bytes, new: &Bytes) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i..].starts_with(old) {
+            result.extend(new.to_vec());
+            i += old.len();
+        } else {
+            result.push(data[i]);
+            i += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tycho_common::models::Chain;
+
+    use super::*;
+    use crate::encoding::evm::swap_encoder::swap_encoder_registry::SwapEncoderRegistry;
+
+    const OLD_ROUTER: &str = "0x3Ede3eCa2a72B3aeCC820E955B36f38437D01395";
+    const NEW_ROUTER: &str = "0xF62849F9A0B5Bf2913b396098F7c7019b51A820a";
+    const OLD_USV2_EXECUTOR: &str = "0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f";
+    const NEW_USV2_EXECUTOR: &str = "0x2e234DAe75C793f67A35089C9d99245E1C58470b";
+
+    fn environment(router: &str, executors: &str) -> EnvironmentConfig {
+        let registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(format!(r#"{{"ethereum": {executors}}}"#)))
+            .unwrap();
+        EnvironmentConfig::new(Bytes::from_str(router).unwrap(), registry)
+    }
+
+    fn migration() -> RouterMigration {
+        RouterMigration::new(
+            &environment(
+                OLD_ROUTER,
+                &format!(
+                    r#"{{"uniswap_v2": "{OLD_USV2_EXECUTOR}", "rfq:bebop": "0x1d1499e622D69689cdf9004d05Ec547d650Ff211"}}"#
+                ),
+            ),
+            &environment(NEW_ROUTER, &format!(r#"{{"uniswap_v2": "{NEW_USV2_EXECUTOR}"}}"#)),
+        )
+    }
+
+    fn sequential_calldata(executors: &[&str]) -> Vec<u8> {
+        let swaps = executors
+            .iter()
+            .map(|executor| {
+                let mut swap = Bytes::from_str(executor)
+                    .unwrap()
+                    .to_vec();
+                // Pool, then the old router as receiver
+                swap.extend([0xaa; 20]);
+                swap.extend(
+                    Bytes::from_str(OLD_ROUTER)
+                        .unwrap()
+                        .to_vec(),
+                );
+                swap
+            })
+            .collect();
+        let args: SequentialArgs = (
+            U256::from(100),
+            Address::ZERO,
+            Address::ZERO,
+            U256::from(90),
+            false,
+            false,
+            Address::ZERO,
+            true,
+            AlloyBytes::from(ple_encode(swaps).unwrap()),
+        );
+        encode_input(SEQUENTIAL_SWAP, args.abi_encode())
+    }
+
+    #[test]
+    fn test_migrate_sequential_swap() {
+        let migrated = migration()
+            .migrate(&sequential_calldata(&[OLD_USV2_EXECUTOR, OLD_USV2_EXECUTOR]))
+            .unwrap();
+
+        let args = SequentialArgs::abi_decode_params(&migrated[4..]).unwrap();
+        let swaps = ple_decode(&args.8).unwrap();
+        assert_eq!(swaps.len(), 2);
+        let mut expected_swap = Bytes::from_str(NEW_USV2_EXECUTOR)
+            .unwrap()
+            .to_vec();
+        expected_swap.extend([0xaa; 20]);
+        expected_swap.extend(
+            Bytes::from_str(NEW_ROUTER)
+                .unwrap()
+                .to_vec(),
+        );
+        assert_eq!(swaps[0], expected_swap);
+        assert_eq!(args.3, U256::from(90));
+    }
+
+    #[test]
+    fn test_check_unsupported_constructs() {
+        let unknown_executor = "0x0000000000000000000000000000000000000001";
+        let unsupported = migration()
+            .check(&sequential_calldata(&[
+                OLD_USV2_EXECUTOR,
+                "0x1d1499e622D69689cdf9004d05Ec547d650Ff211",
+                unknown_executor,
+            ]))
+            .unwrap();
+
+        assert_eq!(
+            unsupported,
+            vec![
+                UnsupportedConstruct::SignedQuote {
+                    swap_index: 1,
+                    protocol: "rfq:bebop".to_string()
+                },
+                UnsupportedConstruct::UnknownExecutor {
+                    swap_index: 2,
+                    executor: Bytes::from_str(unknown_executor).unwrap()
+                },
+            ]
+        );
+        assert!(matches!(
+            migration().migrate(&[0x12, 0x34, 0x56, 0x78]),
+            Err(EncodingError::InvalidInput(_))
+        ));
+    }
+}
@@ -1,11 +1,41 @@
+pub mod angstrom;
 pub mod approvals;
+pub mod audit;
+pub mod callback_allowlist;
+pub mod calldata_budget;
+#[cfg(feature = "test-utils")]
+pub mod calldata_properties;
+pub mod checked_amount_guard;
+pub mod component_id;
 mod constants;
+pub mod cow;
+pub mod decoding;
+pub mod direct_execution;
 pub mod encoder_builders;
-mod encoding_utils;
+pub mod encoder_factory;
+pub mod encoding_utils;
+pub mod environments;
+#[cfg(feature = "failure-injection")]
+pub mod failure_injection;
 mod group_swaps;
+pub mod idempotency;
+#[cfg(feature = "load-test")]
+pub mod load_test;
+pub mod migration;
+pub mod rebasing;
+pub mod receipt;
+pub mod rfq_feedback;
+pub mod rpc;
+pub mod simulation;
+pub mod staleness;
 pub mod strategy_encoder;
+pub mod support_matrix;
 pub mod swap_encoder;
 #[cfg(feature = "test-utils")]
 pub mod testing_utils;
+pub mod token_safety;
+pub mod transaction;
 pub mod tycho_encoders;
+pub mod uniswap_x;
+pub mod user_operation;
 pub mod utils;
@@ -0,0 +1,146 @@
+use num_bigint::BigUint;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{errors::EncodingError, evm::constants::REBASING_TOKENS};
+
+/// Number of wei a rebasing token transfer can lose due to the rounding of the share math.
+pub const REBASING_TRANSFER_ROUNDING: u32 = 2;
+
+/// Returns whether the token's balances rebase on the given chain (e.g. stETH).
+pub fn is_rebasing_token(chain: Chain, token: &Bytes) -> bool {
+    REBASING_TOKENS
+        .get(&chain)
+        .is_some_and(|tokens| {
+            tokens.contains(
+                token
+                    .to_string()
+                    .to_lowercase()
+                    .as_str(),
+            )
+        })
+}
+
+/// Exchange rate between the shares and the balances of a rebasing token, e.g. stETH's
+/// `getTotalShares()` and `getTotalPooledEther()`.
+///
+/// Conversions round down in both directions, like the token contract does.
+///
+/// # Fields
+/// * `total_shares`: Total amount of shares of the token
+/// * `total_pooled`: Total balance backing these shares
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareRate {
+    total_shares: BigUint,
+    total_pooled: BigUint,
+}
+
+impl ShareRate {
+    pub fn new(total_shares: BigUint, total_pooled: BigUint) -> Result<Self, EncodingError> {
+        if total_shares == BigUint::ZERO || total_pooled == BigUint::ZERO {
+            return Err(EncodingError::InvalidInput(
+                "The total shares and total pooled amount of a rebasing token must be positive"
+                    .to_string(),
+            ));
+        }
+        Ok(Self { total_shares, total_pooled })
+    }
+
+    /// Returns the shares backing the given balance (stETH's `getSharesByPooledEth`).
+    pub fn to_shares(&self, amount: &BigUint) -> BigUint {
+        amount * &self.total_shares / &self.total_pooled
+    }
+
+    /// Returns the balance of the given shares (stETH's `getPooledEthByShares`).
+    pub fn to_amount(&self, shares: &BigUint) -> BigUint {
+        shares * &self.total_pooled / &self.total_shares
+    }
+}
+
+/// Returns the checked amount to encode for a rebasing checked token, so that receiving at least
+/// `min_shares` is enough for the solution to succeed.
+///
+/// The router checks raw balances, so the min shares are converted to a balance at the given rate,
+/// lowered by `rebase_tolerance_bps` to absorb a negative rebase between encoding and execution,
+/// and by the transfer rounding of the share math. The encoder sets the result as the
+/// `checked_amount` of the solutions with `checked_shares`.
+pub fn checked_amount_for_shares(
+    min_shares: &BigUint,
+    rate: &ShareRate,
+    rebase_tolerance_bps: u32,
+) -> Result<BigUint, EncodingError> {
+    if rebase_tolerance_bps > 10_000 {
+        return Err(EncodingError::InvalidInput(format!(
+            "The rebase tolerance must be at most 10000 bps, got {rebase_tolerance_bps}"
+        )));
+    }
+    let amount = rate.to_amount(min_shares) * (10_000 - rebase_tolerance_bps) / 10_000u32;
+    let rounding = BigUint::from(REBASING_TRANSFER_ROUNDING);
+    if amount <= rounding {
+        return Ok(BigUint::ZERO);
+    }
+    Ok(amount - rounding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steth_rate() -> ShareRate {
+        // 9.5M shares backing 10M stETH
+        ShareRate::new(
+            BigUint::from(9_500_000u64) * BigUint::from(10u64).pow(18),
+            BigUint::from(10_000_000u64) * BigUint::from(10u64).pow(18),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_share_rate_conversions() {
+        let rate = steth_rate();
+        let amount = BigUint::from(1_000_000u64);
+
+        let shares = rate.to_shares(&amount);
+
+        assert_eq!(shares, BigUint::from(950_000u64));
+        assert_eq!(rate.to_amount(&shares), amount);
+        // Conversions round down
+        assert_eq!(rate.to_amount(&BigUint::from(1u64)), BigUint::from(1u64));
+        assert_eq!(rate.to_shares(&BigUint::from(1u64)), BigUint::ZERO);
+        assert!(ShareRate::new(BigUint::ZERO, BigUint::from(1u64)).is_err());
+    }
+
+    #[test]
+    fn test_checked_amount_for_shares() {
+        let rate = steth_rate();
+        let min_shares = BigUint::from(950_000u64);
+
+        assert_eq!(
+            checked_amount_for_shares(&min_shares, &rate, 0).unwrap(),
+            BigUint::from(999_998u64)
+        );
+        // 1 bps of negative rebase tolerance
+        assert_eq!(
+            checked_amount_for_shares(&min_shares, &rate, 1).unwrap(),
+            BigUint::from(999_898u64)
+        );
+        assert_eq!(
+            checked_amount_for_shares(&BigUint::from(1u64), &rate, 0).unwrap(),
+            BigUint::ZERO
+        );
+        assert!(matches!(
+            checked_amount_for_shares(&min_shares, &rate, 10_001),
+            Err(EncodingError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_rebasing_token() {
+        let steth = Bytes::from("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84");
+        assert!(is_rebasing_token(Chain::Ethereum, &steth));
+        assert!(!is_rebasing_token(Chain::Base, &steth));
+        assert!(!is_rebasing_token(
+            Chain::Ethereum,
+            &Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+        ));
+    }
+}
@@ -0,0 +1,409 @@
+use alloy::{
+    primitives::{keccak256, Address, Log, B256, U256},
+    rpc::types::TransactionReceipt,
+};
+use num_bigint::{BigInt, BigUint};
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{strategy_encoder::wrap_adapter::ChainTokens, utils::bytes_to_address},
+    models::Solution,
+};
+
+/// Signature of the ERC20 `Transfer` event.
+const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+/// Signature of the WETH `Deposit` event, emitted when the native token is wrapped.
+const DEPOSIT_EVENT: &str = "Deposit(address,uint256)";
+/// Signature of the WETH `Withdrawal` event, emitted when the native token is unwrapped.
+const WITHDRAWAL_EVENT: &str = "Withdrawal(address,uint256)";
+
+/// The outcome of an executed solution, decoded from the token events of its transaction.
+///
+/// The router doesn't emit swap events, so the amounts are measured from the ERC20 `Transfer`
+/// events (and the wrapped native token `Deposit` and `Withdrawal` events for the native token),
+/// which don't change across router versions.
+///
+/// # Fields
+/// * `transaction_hash`: Hash of the transaction
+/// * `success`: Whether the transaction succeeded. Failed transactions have no amounts.
+/// * `gas_used`: Gas used by the transaction
+/// * `amount_in`: Amount of the token sold transferred from the sender: the given amount for exact
+///   in solutions, the amount of the checked token for exact out solutions
+/// * `amount_out`: Amount of the token bought transferred to the receiver: the amount of the
+///   checked token for exact in solutions, the given amount for exact out solutions
+/// * `surplus`: Amount beyond the checked amount: `amount_out - checked_amount` for exact in
+///   solutions, `checked_amount - amount_in` for exact out solutions. Negative if the checked
+///   amount wasn't met (e.g. with fee-on-transfer tokens).
+/// * `hops`: The amounts of every swap of the solution, in order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionReport {
+    pub transaction_hash: Bytes,
+    pub success: bool,
+    pub gas_used: u64,
+    pub amount_in: Option<BigUint>,
+    pub amount_out: Option<BigUint>,
+    pub surplus: Option<BigInt>,
+    pub hops: Vec<HopReport>,
+}
+
+/// The amounts of one swap of an executed solution.
+///
+/// The amounts are measured from the transfers into and out of the pool, so they are only known for
+/// components whose id is the address of the pool holding the tokens (e.g. Uniswap V2 and V3
+/// pools), and not for vault-based protocols (e.g. Uniswap V4 or Balancer) or native tokens.
+///
+/// # Fields
+/// * `component_id`: Id of the component of the swap
+/// * `token_in`: Token sold by the swap
+/// * `token_out`: Token bought by the swap
+/// * `amount_in`: Amount of the token in transferred to the pool
+/// * `amount_out`: Amount of the token out transferred from the pool
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HopReport {
+    pub component_id: String,
+    pub token_in: Bytes,
+    pub token_out: Bytes,
+    pub amount_in: Option<BigUint>,
+    pub amount_out: Option<BigUint>,
+}
+
+/// A token movement decoded from a log: an ERC20 transfer, or a wrap or unwrap of the native
+/// token (from or to the zero address).
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TokenMovement {
+    token: Address,
+    from: Address,
+    to: Address,
+    amount: U256,
+}
+
+/// Decodes the receipt of the transaction executing the solution into an `ExecutionReport`.
+///
+/// `router_address` is the address of the router the solution was sent to, which wraps and unwraps
+/// the native token.
+pub fn decode_execution_report(
+    chain: Chain,
+    receipt: &TransactionReceipt,
+    solution: &Solution,
+    router_address: &Bytes,
+) -> Result<ExecutionReport, EncodingError> {
+    let logs: Vec<Log> = receipt
+        .inner
+        .logs()
+        .iter()
+        .map(|log| log.inner.clone())
+        .collect();
+    decode_logs(
+        chain,
+        Bytes::from(receipt.transaction_hash.to_vec()),
+        receipt.inner.status(),
+        receipt.gas_used,
+        &logs,
+        solution,
+        router_address,
+    )
+}
+
+/// Decodes the logs of a transaction executing the solution (see `decode_execution_report`).
+pub fn decode_logs(
+    chain: Chain,
+    transaction_hash: Bytes,
+    success: bool,
+    gas_used: u64,
+    logs: &[Log],
+    solution: &Solution,
+    router_address: &Bytes,
+) -> Result<ExecutionReport, EncodingError> {
+    let chain_tokens = ChainTokens::for_chain(chain)?;
+    let router = bytes_to_address(router_address)?;
+    let movements: Vec<TokenMovement> = logs
+        .iter()
+        .filter_map(|log| decode_movement(log, &chain_tokens.wrapped, router))
+        .collect();
+
+    let hops = solution
+        .swaps
+        .iter()
+        .map(|swap| {
+            let pool = swap
+                .component()
+                .id
+                .parse::<Address>()
+                .ok();
+            let amount = |token: &Bytes, into_pool: bool| {
+                let pool = pool?;
+                let token = bytes_to_address(token).ok()?;
+                sum(movements.iter().filter(|movement| {
+                    movement.token == token &&
+                        if into_pool { movement.to == pool } else { movement.from == pool }
+                }))
+            };
+            HopReport {
+                component_id: swap.component().id.clone(),
+                token_in: swap.token_in().clone(),
+                token_out: swap.token_out().clone(),
+                amount_in: amount(swap.token_in(), true),
+                amount_out: amount(swap.token_out(), false),
+            }
+        })
+        .collect();
+
+    if !success {
+        return Ok(ExecutionReport {
+            transaction_hash,
+            success,
+            gas_used,
+            amount_in: None,
+            amount_out: None,
+            surplus: None,
+            hops,
+        });
+    }
+
+    let (sold_token, bought_token) = if solution.exact_out {
+        (&solution.checked_token, &solution.given_token)
+    } else {
+        (&solution.given_token, &solution.checked_token)
+    };
+    let sender = bytes_to_address(&solution.sender)?;
+    let receiver = bytes_to_address(&solution.receiver)?;
+    // The native token is wrapped by the router when sold, and unwrapped by it when bought
+    let transfers = |token: &Bytes, from: Address, to: Address, native: (Address, Address)| {
+        if *token == chain_tokens.native {
+            let wrapped = bytes_to_address(&chain_tokens.wrapped).ok()?;
+            return sum(movements.iter().filter(|movement| {
+                movement.token == wrapped && (movement.from, movement.to) == native
+            }));
+        }
+        let token = bytes_to_address(token).ok()?;
+        sum(movements.iter().filter(|movement| {
+            movement.token == token &&
+                (movement.from == from || from == Address::ZERO) &&
+                (movement.to == to || to == Address::ZERO)
+        }))
+    };
+    let amount_in = transfers(sold_token, sender, Address::ZERO, (Address::ZERO, router));
+    let amount_out = transfers(bought_token, Address::ZERO, receiver, (router, Address::ZERO));
+    let checked_amount = BigInt::from(solution.checked_amount.clone());
+    let surplus = if solution.exact_out {
+        amount_in
+            .as_ref()
+            .map(|amount_in| checked_amount - BigInt::from(amount_in.clone()))
+    } else {
+        amount_out
+            .as_ref()
+            .map(|amount_out| BigInt::from(amount_out.clone()) - checked_amount)
+    };
+
+    Ok(ExecutionReport {
+        transaction_hash,
+        success,
+        gas_used,
+        amount_in,
+        amount_out,
+        surplus,
+        hops,
+    })
+}
+
+/// Decodes an ERC20 `Transfer` event, or a `Deposit` (from the zero address) or `Withdrawal` (to
+/// the zero address) event of the wrapped native token by the router.
+fn decode_movement(log: &Log, wrapped: &Bytes, router: Address) -> Option<TokenMovement> {
+    let topics = log.data.topics();
+    let event = *topics.first()?;
+    let amount = U256::try_from_be_slice(log.data.data.get(..32)?)?;
+    let topic_address = |topic: &B256| Address::from_slice(&topic[12..]);
+
+    if event == keccak256(TRANSFER_EVENT) && topics.len() == 3 {
+        return Some(TokenMovement {
+            token: log.address,
+            from: topic_address(&topics[1]),
+            to: topic_address(&topics[2]),
+            amount,
+        });
+    }
+    if log.address.as_slice() != wrapped.as_ref() || topics.len() != 2 {
+        return None;
+    }
+    let account = topic_address(&topics[1]);
+    if account != router {
+        return None;
+    }
+    if event == keccak256(DEPOSIT_EVENT) {
+        Some(TokenMovement { token: log.address, from: Address::ZERO, to: account, amount })
+    } else if event == keccak256(WITHDRAWAL_EVENT) {
+        Some(TokenMovement { token: log.address, from: account, to: Address::ZERO, amount })
+    } else {
+        None
+    }
+}
+
+/// Sums the amounts of the movements, or returns `None` if there are none.
+fn sum<'a>(movements: impl Iterator<Item = &'a TokenMovement>) -> Option<BigUint> {
+    movements
+        .map(|movement| BigUint::from_bytes_be(&movement.amount.to_be_bytes::<32>()))
+        .reduce(|total, amount| total + amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::LogData;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::Swap;
+
+    const USV2_POOL: &str = "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11";
+
+    fn router() -> Bytes {
+        Bytes::from("0xfD0b31d2E955fA55e3fa641Fe90e08b677188d35")
+    }
+
+    fn user() -> Bytes {
+        Bytes::from("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2")
+    }
+
+    fn weth() -> Bytes {
+        Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+    }
+
+    fn dai() -> Bytes {
+        Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f")
+    }
+
+    fn address(address: &Bytes) -> Address {
+        bytes_to_address(address).unwrap()
+    }
+
+    fn topic(address: Address) -> B256 {
+        address.into_word()
+    }
+
+    fn transfer(token: &Bytes, from: Address, to: Address, amount: u64) -> Log {
+        Log {
+            address: address(token),
+            data: LogData::new_unchecked(
+                vec![keccak256(TRANSFER_EVENT), topic(from), topic(to)],
+                U256::from(amount)
+                    .to_be_bytes::<32>()
+                    .to_vec()
+                    .into(),
+            ),
+        }
+    }
+
+    fn deposit(amount: u64) -> Log {
+        Log {
+            address: address(&weth()),
+            data: LogData::new_unchecked(
+                vec![keccak256(DEPOSIT_EVENT), topic(address(&router()))],
+                U256::from(amount)
+                    .to_be_bytes::<32>()
+                    .to_vec()
+                    .into(),
+            ),
+        }
+    }
+
+    fn solution(given_token: Bytes) -> Solution {
+        Solution {
+            sender: user(),
+            receiver: user(),
+            given_token,
+            given_amount: BigUint::from(1000u32),
+            checked_token: dai(),
+            checked_amount: BigUint::from(2_900_000u32),
+            swaps: vec![Swap::new(
+                ProtocolComponent {
+                    id: USV2_POOL.to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                dai(),
+            )],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_logs() {
+        let pool = USV2_POOL.parse::<Address>().unwrap();
+        let logs = vec![
+            transfer(&weth(), address(&user()), pool, 1000),
+            transfer(&dai(), pool, address(&user()), 2_950_000),
+        ];
+
+        let report = decode_logs(
+            Chain::Ethereum,
+            Bytes::from(vec![0xab; 32]),
+            true,
+            120_000,
+            &logs,
+            &solution(weth()),
+            &router(),
+        )
+        .unwrap();
+
+        assert_eq!(report.amount_in, Some(BigUint::from(1000u32)));
+        assert_eq!(report.amount_out, Some(BigUint::from(2_950_000u32)));
+        assert_eq!(report.surplus, Some(BigInt::from(50_000)));
+        assert_eq!(
+            report.hops,
+            vec![HopReport {
+                component_id: USV2_POOL.to_string(),
+                token_in: weth(),
+                token_out: dai(),
+                amount_in: Some(BigUint::from(1000u32)),
+                amount_out: Some(BigUint::from(2_950_000u32)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_logs_native_token_in() {
+        let pool = USV2_POOL.parse::<Address>().unwrap();
+        let logs = vec![
+            deposit(1000),
+            transfer(&weth(), address(&router()), pool, 1000),
+            // Transfers to other addresses than the receiver (e.g. fees) are not counted
+            transfer(&dai(), pool, address(&router()), 2_950_000),
+            transfer(&dai(), address(&router()), address(&user()), 2_850_000),
+        ];
+
+        let report = decode_logs(
+            Chain::Ethereum,
+            Bytes::from(vec![0xab; 32]),
+            true,
+            120_000,
+            &logs,
+            &solution(Chain::Ethereum.native_token().address),
+            &router(),
+        )
+        .unwrap();
+
+        assert_eq!(report.amount_in, Some(BigUint::from(1000u32)));
+        assert_eq!(report.amount_out, Some(BigUint::from(2_850_000u32)));
+        assert_eq!(report.surplus, Some(BigInt::from(-50_000)));
+    }
+
+    #[test]
+    fn test_decode_logs_failed_transaction() {
+        let report = decode_logs(
+            Chain::Ethereum,
+            Bytes::from(vec![0xab; 32]),
+            false,
+            50_000,
+            &[],
+            &solution(weth()),
+            &router(),
+        )
+        .unwrap();
+
+        assert!(!report.success);
+        assert_eq!(report.amount_out, None);
+        assert_eq!(report.surplus, None);
+        assert_eq!(report.hops[0].amount_in, None);
+    }
+}
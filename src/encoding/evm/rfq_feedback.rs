@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use chrono::Utc;
+use num_bigint::{BigInt, BigUint};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tycho_common::{simulation::indicatively_priced::SignedQuote, Bytes};
+
+use crate::encoding::{
+    evm::encoding_utils::encode_input, models::Swap, serde_primitives::biguint_string,
+};
+
+/// Signature of the Bebop settlement function filling a quote of a single maker.
+pub(crate) const BEBOP_SWAP_SINGLE: &str = "swapSingle((uint256,address,address,uint256,address,address,uint256,uint256,address,uint256,uint256),(bytes,uint256),uint256)";
+
+/// A callback receiving the RFQ feedback events.
+pub type RfqObserver = Arc<dyn Fn(&RfqFeedbackEvent) + Send + Sync>;
+
+static OBSERVERS: Lazy<RwLock<HashMap<u64, RfqObserver>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+static NEXT_OBSERVER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Handle of a registered observer, used to unregister it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RfqObserverHandle(u64);
+
+/// The maker behind an RFQ quote, parsed from the quote attributes.
+///
+/// # Fields
+/// * `maker`: Address of the maker: the `maker_address` of Bebop single maker quotes, or the
+///   `external_account` of Hashflow quotes.
+/// * `pool`: The Hashflow pool the quote is settled against.
+/// * `quote_id`: Identifier of the quote: the maker nonce of Bebop quotes, or the `tx_id` of
+///   Hashflow quotes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MakerIdentity {
+    pub maker: Option<Bytes>,
+    pub pool: Option<Bytes>,
+    pub quote_id: Option<Bytes>,
+}
+
+impl MakerIdentity {
+    /// Parses the maker identity from the attributes of a quote of the given protocol. Fields that
+    /// can't be found are left empty.
+    pub fn from_quote(protocol_system: &str, quote_attributes: &HashMap<String, Bytes>) -> Self {
+        match protocol_system {
+            "rfq:bebop" => {
+                let mut identity = MakerIdentity::default();
+                if let Some(calldata) = quote_attributes.get("calldata") {
+                    // swapSingle(Single order, ...): the static order starts right after the
+                    // selector, with the maker address and nonce as its third and fourth words
+                    if calldata.len() >= 4 + 4 * 32 &&
+                        calldata[..4] == encode_input(BEBOP_SWAP_SINGLE, vec![])[..]
+                    {
+                        identity.maker = Some(Bytes::from(calldata[4 + 64 + 12..4 + 96].to_vec()));
+                        identity.quote_id = Some(Bytes::from(calldata[4 + 96..4 + 128].to_vec()));
+                    }
+                }
+                if let Some(maker) = quote_attributes.get("maker") {
+                    identity.maker = Some(maker.clone());
+                }
+                identity
+            }
+            "rfq:hashflow" => MakerIdentity {
+                maker: quote_attributes
+                    .get("external_account")
+                    .cloned(),
+                pool: quote_attributes.get("pool").cloned(),
+                quote_id: quote_attributes.get("tx_id").cloned(),
+            },
+            _ => MakerIdentity {
+                maker: quote_attributes.get("maker").cloned(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// An RFQ quote that was encoded into a solution.
+///
+/// # Fields
+/// * `protocol_system`: The RFQ protocol of the quote (e.g. `rfq:bebop`).
+/// * `component_id`: The id of the component the quote was requested from.
+/// * `token_in`: The token sold to the maker.
+/// * `token_out`: The token bought from the maker.
+/// * `amount_in`: The quoted amount of `token_in`.
+/// * `quoted_amount_out`: The quoted amount of `token_out`.
+/// * `maker`: The identity of the maker.
+/// * `timestamp`: Unix timestamp (in milliseconds) at which the quote was encoded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RfqQuoteRecord {
+    pub protocol_system: String,
+    pub component_id: String,
+    pub token_in: Bytes,
+    pub token_out: Bytes,
+    #[serde(with = "biguint_string")]
+    pub amount_in: BigUint,
+    #[serde(with = "biguint_string")]
+    pub quoted_amount_out: BigUint,
+    pub maker: MakerIdentity,
+    pub timestamp: i64,
+}
+
+impl RfqQuoteRecord {
+    /// Builds the record of the signed quote settling the given swap.
+    pub fn new(swap: &Swap, signed_quote: &SignedQuote) -> Self {
+        let component = swap.component();
+        Self {
+            protocol_system: component.protocol_system.clone(),
+            component_id: component.id.clone(),
+            token_in: swap.token_in().clone(),
+            token_out: swap.token_out().clone(),
+            amount_in: signed_quote.amount_in.clone(),
+            quoted_amount_out: signed_quote.amount_out.clone(),
+            maker: MakerIdentity::from_quote(
+                &component.protocol_system,
+                &signed_quote.quote_attributes,
+            ),
+            timestamp: Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// The realized outcome of an encoded RFQ quote, once the transaction settling it was executed.
+///
+/// # Fields
+/// * `quote`: The quote as it was encoded.
+/// * `realized_amount_out`: The amount of `token_out` actually received, parsed from the receipt.
+/// * `transaction_hash`: The hash of the transaction settling the quote.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RfqExecutionRecord {
+    pub quote: RfqQuoteRecord,
+    #[serde(with = "biguint_string")]
+    pub realized_amount_out: BigUint,
+    pub transaction_hash: Bytes,
+}
+
+impl RfqExecutionRecord {
+    /// Returns the slippage of the realized amount against the quoted one, in basis points.
+    /// Negative values mean that less than quoted was received.
+    pub fn slippage_bps(&self) -> f64 {
+        if self.quote.quoted_amount_out == BigUint::from(0u8) {
+            return 0.0;
+        }
+        let difference = BigInt::from(self.realized_amount_out.clone()) -
+            BigInt::from(self.quote.quoted_amount_out.clone());
+        let scaled: BigInt =
+            difference * 1_000_000 / BigInt::from(self.quote.quoted_amount_out.clone());
+        // the slippage, in hundredths of basis points, fits in an i128 for any realistic amounts
+        i128::try_from(&scaled).unwrap_or(i128::MAX) as f64 / 100.0
+    }
+}
+
+/// An event of the quote-vs-execution feedback loop.
+///
+/// Variants:
+/// - `Quoted`: A quote was encoded.
+/// - `Executed`: The realized amount of an encoded quote was reported (see [`report_execution`]).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RfqFeedbackEvent {
+    Quoted(RfqQuoteRecord),
+    Executed(RfqExecutionRecord),
+}
+
+/// Registers a callback invoked with every RFQ feedback event, for all the encoders of the
+/// process. The callback runs on the encoding thread, so it should return quickly.
+pub fn register_rfq_observer(observer: RfqObserver) -> RfqObserverHandle {
+    let id = NEXT_OBSERVER_ID.fetch_add(1, Ordering::Relaxed);
+    OBSERVERS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(id, observer);
+    RfqObserverHandle(id)
+}
+
+/// Unregisters a callback registered with [`register_rfq_observer`].
+pub fn unregister_rfq_observer(handle: RfqObserverHandle) {
+    OBSERVERS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .remove(&handle.0);
+}
+
+/// Reports the realized amount of an encoded quote, after parsing the receipt of the transaction
+/// settling it, and notifies the observers.
+pub fn report_execution(
+    quote: RfqQuoteRecord,
+    realized_amount_out: BigUint,
+    transaction_hash: Bytes,
+) -> RfqExecutionRecord {
+    let record = RfqExecutionRecord { quote, realized_amount_out, transaction_hash };
+    notify(&RfqFeedbackEvent::Executed(record.clone()));
+    record
+}
+
+/// Notifies the observers that the signed quote settling the given swap was encoded.
+pub(crate) fn notify_quote(swap: &Swap, signed_quote: &SignedQuote) {
+    let observers = OBSERVERS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if observers.is_empty() {
+        return;
+    }
+    let event = RfqFeedbackEvent::Quoted(RfqQuoteRecord::new(swap, signed_quote));
+    for observer in observers.values() {
+        observer(&event);
+    }
+}
+
+fn notify(event: &RfqFeedbackEvent) {
+    let observers = OBSERVERS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for observer in observers.values() {
+        observer(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, sync::Mutex};
+
+    use alloy::primitives::{hex, U256};
+
+    use super::*;
+
+    #[test]
+    fn test_maker_identity_from_bebop_single_quote() {
+        let mut calldata = encode_input(BEBOP_SWAP_SINGLE, vec![]);
+        // expiry, taker_address, maker_address, maker_nonce
+        calldata.extend([0u8; 32]);
+        calldata.extend([0u8; 32]);
+        calldata.extend([0u8; 12]);
+        calldata.extend(hex!("ce79b081c0c924cb67848723ed3057234d10fc6b"));
+        calldata.extend(U256::from(7).to_be_bytes::<32>());
+        let attributes = HashMap::from([("calldata".to_string(), Bytes::from(calldata))]);
+
+        let identity = MakerIdentity::from_quote("rfq:bebop", &attributes);
+
+        assert_eq!(
+            identity.maker,
+            Some(Bytes::from_str("0xce79b081c0c924cb67848723ed3057234d10fc6b").unwrap())
+        );
+        assert_eq!(
+            identity.quote_id,
+            Some(Bytes::from(
+                U256::from(7)
+                    .to_be_bytes::<32>()
+                    .to_vec()
+            ))
+        );
+        assert_eq!(identity.pool, None);
+    }
+
+    #[test]
+    fn test_maker_identity_from_hashflow_quote() {
+        let attributes = HashMap::from([
+            ("pool".to_string(), Bytes::from_str("0x01").unwrap()),
+            ("external_account".to_string(), Bytes::from_str("0x02").unwrap()),
+            ("tx_id".to_string(), Bytes::from_str("0x03").unwrap()),
+        ]);
+
+        let identity = MakerIdentity::from_quote("rfq:hashflow", &attributes);
+
+        assert_eq!(
+            identity,
+            MakerIdentity {
+                maker: Some(Bytes::from_str("0x02").unwrap()),
+                pool: Some(Bytes::from_str("0x01").unwrap()),
+                quote_id: Some(Bytes::from_str("0x03").unwrap()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_report_execution() {
+        let quote = RfqQuoteRecord {
+            protocol_system: "rfq:hashflow".to_string(),
+            component_id: "test_report_execution".to_string(),
+            token_in: Bytes::zero(20),
+            token_out: Bytes::zero(20),
+            amount_in: BigUint::from(1_000u32),
+            quoted_amount_out: BigUint::from(2_000u32),
+            maker: MakerIdentity::default(),
+            timestamp: 0,
+        };
+        let events = Arc::new(Mutex::new(vec![]));
+        let events_clone = events.clone();
+        let handle = register_rfq_observer(Arc::new(move |event: &RfqFeedbackEvent| {
+            if let RfqFeedbackEvent::Executed(record) = event {
+                if record.quote.component_id == "test_report_execution" {
+                    events_clone
+                        .lock()
+                        .unwrap()
+                        .push(record.clone());
+                }
+            }
+        }));
+
+        let record = report_execution(quote, BigUint::from(1_990u32), Bytes::zero(32));
+        unregister_rfq_observer(handle);
+        report_execution(record.quote.clone(), BigUint::from(1u32), Bytes::zero(32));
+
+        assert_eq!(*events.lock().unwrap(), vec![record.clone()]);
+        assert_eq!(record.slippage_bps(), -50.0);
+    }
+}
@@ -0,0 +1,405 @@
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    providers::ProviderBuilder,
+    rpc::{
+        client::RpcClient,
+        json_rpc::{RequestPacket, ResponsePacket},
+    },
+    transports::{
+        http::{
+            reqwest::{Client, Url},
+            Http,
+        },
+        TransportError, TransportErrorKind, TransportFut,
+    },
+};
+use once_cell::sync::Lazy;
+use thiserror::Error;
+use tower::Service;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        constants::{RPC_ENDPOINT_COOLDOWN, RPC_REQUEST_TIMEOUT},
+        utils::EVMProvider,
+    },
+};
+
+/// Health of the RPC endpoints, shared by all the clients so that an endpoint failing for one
+/// lookup is tried last by the others.
+static ENDPOINT_HEALTH: Lazy<Mutex<HashMap<String, EndpointHealth>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+/// Configuration of the RPC endpoints used for all the on-chain lookups of the encoder (e.g.
+/// Permit2 allowances, protocol approvals or environment checks).
+///
+/// Requests are sent to the first healthy endpoint, and fail over to the next ones if it errors or
+/// doesn't answer within the request timeout. An endpoint that failed is considered unhealthy for
+/// `RPC_ENDPOINT_COOLDOWN` and is only tried after the healthy ones.
+///
+/// # Fields
+/// * `endpoints`: URLs of the RPC endpoints, in order of preference
+/// * `request_timeout`: Maximum time to wait for the response of a single endpoint
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RpcConfig {
+    endpoints: Vec<String>,
+    request_timeout: Duration,
+}
+
+impl RpcConfig {
+    pub fn new(endpoints: Vec<String>) -> Result<Self, EncodingError> {
+        if endpoints.is_empty() {
+            return Err(EncodingError::FatalError("No RPC endpoint configured".to_string()));
+        }
+        Ok(Self { endpoints, request_timeout: RPC_REQUEST_TIMEOUT })
+    }
+
+    /// Reads the configuration from the environment:
+    /// * `RPC_URL`: Comma-separated list of RPC endpoints, in order of preference
+    /// * `RPC_REQUEST_TIMEOUT_MS` (optional): Per-request timeout in milliseconds
+    pub fn from_env() -> Result<Self, EncodingError> {
+        let rpc_url = env::var("RPC_URL")
+            .map_err(|_| EncodingError::FatalError("Missing RPC_URL in environment".to_string()))?;
+        let config = Self::new(parse_endpoints(&rpc_url))?;
+        match env::var("RPC_REQUEST_TIMEOUT_MS") {
+            Ok(timeout) => {
+                let timeout = timeout.parse().map_err(|_| {
+                    EncodingError::FatalError(format!("Invalid RPC_REQUEST_TIMEOUT_MS: {timeout}"))
+                })?;
+                Ok(config.with_request_timeout(Duration::from_millis(timeout)))
+            }
+            Err(_) => Ok(config),
+        }
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    /// Builds a client that sends its requests through the configured endpoints.
+    pub fn connect(&self) -> Result<EVMProvider, EncodingError> {
+        let transport = FailoverTransport::new(self)?;
+        let client = RpcClient::new(transport, false);
+        Ok(Arc::new(ProviderBuilder::new().connect_client(client)))
+    }
+}
+
+/// Error returned when a request failed on every configured RPC endpoint.
+///
+/// Endpoints are identified by their position in the configuration and their host, so that API
+/// keys in the URLs are not leaked into logs.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("All RPC endpoints failed: {}", format_failures(.failures))]
+pub struct RpcEndpointsError {
+    pub failures: Vec<RpcEndpointFailure>,
+}
+
+/// Failure of a request on a single RPC endpoint.
+///
+/// # Fields
+/// * `index`: Position of the endpoint in the configuration
+/// * `host`: Host of the endpoint
+/// * `reason`: Why the request failed (e.g. a timeout or an HTTP error)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RpcEndpointFailure {
+    pub index: usize,
+    pub host: String,
+    pub reason: String,
+}
+
+fn format_failures(failures: &[RpcEndpointFailure]) -> String {
+    failures
+        .iter()
+        .map(|failure| format!("endpoint {} ({}): {}", failure.index, failure.host, failure.reason))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[derive(Clone, Debug)]
+struct Endpoint {
+    index: usize,
+    url: String,
+    host: String,
+    transport: Http<Client>,
+}
+
+/// Transport sending every request to the healthiest endpoint first, and failing over to the
+/// others (see `RpcConfig`).
+#[derive(Clone, Debug)]
+struct FailoverTransport {
+    endpoints: Arc<Vec<Endpoint>>,
+    request_timeout: Duration,
+}
+
+impl FailoverTransport {
+    fn new(config: &RpcConfig) -> Result<Self, EncodingError> {
+        let endpoints = config
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, url)| {
+                let parsed = Url::parse(url).map_err(|_| {
+                    EncodingError::FatalError(format!("Invalid RPC URL for endpoint {index}"))
+                })?;
+                Ok(Endpoint {
+                    index,
+                    url: url.clone(),
+                    host: endpoint_host(&parsed),
+                    transport: Http::new(parsed),
+                })
+            })
+            .collect::<Result<Vec<_>, EncodingError>>()?;
+        Ok(Self { endpoints: Arc::new(endpoints), request_timeout: config.request_timeout })
+    }
+
+    /// Returns the endpoints in the order they should be tried: the healthy ones in order of
+    /// preference, then the unhealthy ones by the end of their cooldown.
+    fn ordered_endpoints(&self) -> Vec<Endpoint> {
+        let now = Instant::now();
+        let health = ENDPOINT_HEALTH
+            .lock()
+            .map(|health| health.clone())
+            .unwrap_or_default();
+        let mut endpoints = self.endpoints.to_vec();
+        endpoints.sort_by_key(|endpoint| {
+            health
+                .get(&endpoint.url)
+                .and_then(|health| health.unhealthy_until)
+                .filter(|until| *until > now)
+        });
+        endpoints
+    }
+
+    async fn send(self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let mut failures = Vec::new();
+        for endpoint in self.ordered_endpoints() {
+            let mut transport = endpoint.transport.clone();
+            let reason =
+                match tokio::time::timeout(self.request_timeout, transport.call(request.clone()))
+                    .await
+                {
+                    Ok(Ok(response)) => {
+                        record_success(&endpoint.url);
+                        return Ok(response);
+                    }
+                    Ok(Err(err)) => err.to_string(),
+                    Err(_) => format!("timed out after {}ms", self.request_timeout.as_millis()),
+                };
+            record_failure(&endpoint.url);
+            failures.push(RpcEndpointFailure {
+                index: endpoint.index,
+                host: endpoint.host,
+                reason,
+            });
+        }
+        Err(TransportErrorKind::custom(RpcEndpointsError { failures }))
+    }
+}
+
+impl Service<RequestPacket> for FailoverTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().send(request))
+    }
+}
+
+fn record_success(url: &str) {
+    if let Ok(mut health) = ENDPOINT_HEALTH.lock() {
+        health.remove(url);
+    }
+}
+
+fn record_failure(url: &str) {
+    if let Ok(mut health) = ENDPOINT_HEALTH.lock() {
+        let health = health
+            .entry(url.to_string())
+            .or_default();
+        health.consecutive_failures += 1;
+        health.unhealthy_until =
+            Some(Instant::now() + RPC_ENDPOINT_COOLDOWN * health.consecutive_failures.min(4));
+    }
+}
+
+fn parse_endpoints(rpc_url: &str) -> Vec<String> {
+    rpc_url
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn endpoint_host(url: &Url) -> String {
+    match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => url.scheme().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::providers::Provider;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Serves `eth_chainId` requests with the given chain id, one connection at a time.
+    async fn serve_chain_id(chain_id: u64) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = vec![0; 4096];
+                let read = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..read]);
+                let body = request
+                    .split("\r\n\r\n")
+                    .nth(1)
+                    .unwrap_or_default();
+                let id = serde_json::from_str::<serde_json::Value>(body)
+                    .map(|request| request["id"].clone())
+                    .unwrap_or_default();
+                let response = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": format!("0x{chain_id:x}"),
+                })
+                .to_string();
+                let http_response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: \
+                     {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                );
+                stream
+                    .write_all(http_response.as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+        format!("http://{address}")
+    }
+
+    /// Returns the URL of a local port nothing listens on.
+    async fn unreachable_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        format!("http://{address}")
+    }
+
+    #[test]
+    fn test_parse_endpoints() {
+        assert_eq!(
+            parse_endpoints("https://rpc-1.xyz/key, https://rpc-2.xyz,,"),
+            vec!["https://rpc-1.xyz/key".to_string(), "https://rpc-2.xyz".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_rpc_config_requires_endpoints() {
+        assert_eq!(
+            RpcConfig::new(vec![]),
+            Err(EncodingError::FatalError("No RPC endpoint configured".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_endpoint_host_hides_path() {
+        let url = Url::parse("https://eth-mainnet.g.alchemy.com/v2/secret-key").unwrap();
+        assert_eq!(endpoint_host(&url), "eth-mainnet.g.alchemy.com");
+    }
+
+    #[test]
+    fn test_unhealthy_endpoints_are_tried_last() {
+        let config = RpcConfig::new(vec![
+            "http://unhealthy-endpoint.xyz".to_string(),
+            "http://healthy-endpoint.xyz".to_string(),
+        ])
+        .unwrap();
+        let transport = FailoverTransport::new(&config).unwrap();
+        record_failure("http://unhealthy-endpoint.xyz");
+
+        let ordered: Vec<usize> = transport
+            .ordered_endpoints()
+            .iter()
+            .map(|endpoint| endpoint.index)
+            .collect();
+        assert_eq!(ordered, vec![1, 0]);
+
+        record_success("http://unhealthy-endpoint.xyz");
+        let ordered: Vec<usize> = transport
+            .ordered_endpoints()
+            .iter()
+            .map(|endpoint| endpoint.index)
+            .collect();
+        assert_eq!(ordered, vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_next_endpoint() {
+        let failing = unreachable_endpoint().await;
+        let working = serve_chain_id(1).await;
+        let client = RpcConfig::new(vec![failing, working])
+            .unwrap()
+            .connect()
+            .unwrap();
+
+        assert_eq!(client.get_chain_id().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reports_all_failed_endpoints() {
+        let first = unreachable_endpoint().await;
+        let second = unreachable_endpoint().await;
+        let client = RpcConfig::new(vec![first.clone(), second.clone()])
+            .unwrap()
+            .connect()
+            .unwrap();
+
+        let err = client
+            .get_chain_id()
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("All RPC endpoints failed"));
+        assert!(err.contains(&format!("({})", first.trim_start_matches("http://"))));
+        assert!(err.contains(&format!("({})", second.trim_start_matches("http://"))));
+    }
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use alloy::{
+    primitives::{Bytes as AlloyBytes, TxKind, U256},
+    providers::Provider,
+    rpc::types::{BlockId, TransactionInput, TransactionRequest},
+    sol_types::SolValue,
+    transports::TransportError,
+};
+use num_bigint::BigUint;
+use tokio::task::block_in_place;
+use tycho_common::models::Chain;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        approvals::permit_signer::PermitSigner,
+        encoding_utils::encode_tycho_router_call,
+        strategy_encoder::wrap_adapter::ChainTokens,
+        utils::{biguint_to_u256, bytes_to_address, get_client, get_runtime},
+    },
+    models::{EncodedSolution, Solution, Transaction, UserTransferType},
+};
+
+/// The outcome of a successful simulation.
+///
+/// # Fields
+/// * `amount`: The amount returned by the router: the amount of the checked token received, or, for
+///   exact out solutions, the amount of the checked token sold
+/// * `checked_amount`: The checked amount the solution was simulated with (the minimum amount out,
+///   or the maximum amount in of exact out solutions)
+/// * `transaction`: The simulated transaction
+#[derive(Clone, Debug)]
+pub struct SimulationResult {
+    pub amount: BigUint,
+    pub checked_amount: BigUint,
+    pub transaction: Transaction,
+}
+
+/// Simulates encoded solutions with an `eth_call` to the router, before submitting them.
+///
+/// Encoding bugs (e.g. stale RFQ quotes, wrong transfer types or missing approvals) otherwise only
+/// show up as reverted transactions. The simulation sends the full router call from the sender of
+/// the solution, and fails if it reverts or doesn't meet the checked amount.
+///
+/// Requires the `RPC_URL` environment variable to be set (see `RpcConfig::from_env`).
+///
+/// # Fields
+/// * `chain`: Chain of the solutions
+/// * `user_transfer_type`: The user transfer type the solutions were encoded with
+/// * `permit_signer`: Optional signer of the permits, required to simulate permit2 solutions
+/// * `block`: Optional block to simulate the solutions at. Defaults to the latest block.
+#[derive(Clone)]
+pub struct SolutionSimulator {
+    chain: Chain,
+    user_transfer_type: UserTransferType,
+    permit_signer: Option<Arc<dyn PermitSigner>>,
+    block: Option<BlockId>,
+}
+
+impl SolutionSimulator {
+    pub fn new(chain: Chain, user_transfer_type: UserTransferType) -> Self {
+        Self { chain, user_transfer_type, permit_signer: None, block: None }
+    }
+
+    /// Sets the signer of the permits of permit2 solutions.
+    pub fn with_permit_signer(mut self, permit_signer: Arc<dyn PermitSigner>) -> Self {
+        self.permit_signer = Some(permit_signer);
+        self
+    }
+
+    /// Simulates the solutions at the given block instead of the latest one.
+    pub fn with_block_number(mut self, block_number: u64) -> Self {
+        self.block = Some(BlockId::number(block_number));
+        self
+    }
+
+    /// Simulates the encoded solution.
+    ///
+    /// Returns an `EncodingError::InvalidInput` if the call reverts or if the router returns an
+    /// amount beyond the checked amount, and an `EncodingError::RecoverableError` if the RPC
+    /// request fails.
+    pub fn simulate(
+        &self,
+        encoded_solution: &EncodedSolution,
+        solution: &Solution,
+    ) -> Result<SimulationResult, EncodingError> {
+        let (handle, _runtime) = get_runtime()?;
+        block_in_place(|| handle.block_on(self.simulate_async(encoded_solution, solution)))
+    }
+
+    /// Simulates the encoded solution, awaiting the RPC request (see `simulate`).
+    pub async fn simulate_async(
+        &self,
+        encoded_solution: &EncodedSolution,
+        solution: &Solution,
+    ) -> Result<SimulationResult, EncodingError> {
+        let (checked_amount, transaction) = self.transaction(encoded_solution, solution)?;
+        let request = TransactionRequest {
+            from: Some(bytes_to_address(&solution.sender)?),
+            to: Some(TxKind::from(bytes_to_address(&transaction.to)?)),
+            value: Some(biguint_to_u256(&transaction.value)),
+            input: TransactionInput {
+                input: Some(AlloyBytes::from(transaction.data.clone())),
+                data: None,
+            },
+            ..Default::default()
+        };
+        let client = get_client().await?;
+        let mut call = client.call(request);
+        if let Some(block) = self.block {
+            call = call.block(block);
+        }
+        let response = call.await.map_err(simulation_error)?;
+        let amount = check_simulated_amount(solution.exact_out, &checked_amount, &response)?;
+        Ok(SimulationResult { amount, checked_amount, transaction })
+    }
+
+    /// Builds the router call of the solution, with its checked amount resolved from its expected
+    /// amount and slippage if it has them.
+    fn transaction(
+        &self,
+        encoded_solution: &EncodedSolution,
+        solution: &Solution,
+    ) -> Result<(BigUint, Transaction), EncodingError> {
+        let checked_amount = solution.resolved_checked_amount(None)?;
+        let solution = Solution { checked_amount: checked_amount.clone(), ..solution.clone() };
+        let transaction = encode_tycho_router_call(
+            self.chain.id(),
+            encoded_solution.clone(),
+            &solution,
+            &self.user_transfer_type,
+            &ChainTokens::for_chain(self.chain)?.native,
+            self.permit_signer.as_deref(),
+        )?;
+        Ok((checked_amount, transaction))
+    }
+}
+
+/// Reverts are errors of the solution, while the other failures of the RPC request may succeed
+/// when retried.
+fn simulation_error(err: TransportError) -> EncodingError {
+    match err.as_error_resp() {
+        Some(payload) => {
+            let revert_data = payload
+                .data
+                .as_ref()
+                .map(|data| format!(" ({})", data.get()))
+                .unwrap_or_default();
+            EncodingError::InvalidInput(format!(
+                "The simulation of the solution reverted: {}{revert_data}",
+                payload.message
+            ))
+        }
+        None => EncodingError::RecoverableError(format!("Failed to simulate the solution: {err}")),
+    }
+}
+
+/// Decodes the amount returned by the router and checks it against the checked amount: the amount
+/// out must be at least the minimum amount out, and the amount in of exact out solutions at most
+/// the maximum amount in.
+fn check_simulated_amount(
+    exact_out: bool,
+    checked_amount: &BigUint,
+    response: &[u8],
+) -> Result<BigUint, EncodingError> {
+    let amount = U256::abi_decode(response).map_err(|_| {
+        EncodingError::FatalError("Failed to decode the amount returned by the router".to_string())
+    })?;
+    let amount = BigUint::from_bytes_be(&amount.to_be_bytes::<32>());
+    if exact_out && amount > *checked_amount {
+        return Err(EncodingError::InvalidInput(format!(
+            "The simulated amount in {amount} is above the maximum amount in {checked_amount}"
+        )));
+    }
+    if !exact_out && amount < *checked_amount {
+        return Err(EncodingError::InvalidInput(format!(
+            "The simulated amount out {amount} is below the minimum amount out {checked_amount}"
+        )));
+    }
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(amount: u64) -> Vec<u8> {
+        U256::from(amount).abi_encode()
+    }
+
+    #[test]
+    fn test_check_simulated_amount_out() {
+        let checked_amount = BigUint::from(1_000u32);
+
+        assert_eq!(
+            check_simulated_amount(false, &checked_amount, &response(1_000)).unwrap(),
+            BigUint::from(1_000u32)
+        );
+        assert!(matches!(
+            check_simulated_amount(false, &checked_amount, &response(999)),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("below the minimum amount out")
+        ));
+    }
+
+    #[test]
+    fn test_check_simulated_amount_in() {
+        let checked_amount = BigUint::from(1_000u32);
+
+        assert_eq!(
+            check_simulated_amount(true, &checked_amount, &response(999)).unwrap(),
+            BigUint::from(999u32)
+        );
+        assert!(matches!(
+            check_simulated_amount(true, &checked_amount, &response(1_001)),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("above the maximum amount in")
+        ));
+    }
+
+    #[test]
+    fn test_check_simulated_amount_invalid_response() {
+        let result = check_simulated_amount(false, &BigUint::from(1u32), &[0x01]);
+
+        assert!(matches!(result, Err(EncodingError::FatalError(_))));
+    }
+}
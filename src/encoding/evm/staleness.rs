@@ -0,0 +1,111 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use crate::encoding::{errors::EncodingError, models::Solution};
+
+/// Rejects solutions derived from indexer states that are too old.
+///
+/// The age of a swap's state is measured against the latest known block, which is the highest of
+/// the block reported through [`StalenessGuard::update_latest_block`] (e.g. by the indexer stream
+/// consumer) and the newest state version in the solution itself. Swaps without a state version
+/// are not checked.
+///
+/// Cloning the guard shares the latest known block, so it can be updated from another thread
+/// while the encoder is in use.
+#[derive(Clone, Debug)]
+pub struct StalenessGuard {
+    max_age_blocks: u64,
+    latest_block: Arc<AtomicU64>,
+}
+
+impl StalenessGuard {
+    pub fn new(max_age_blocks: u64) -> Self {
+        Self { max_age_blocks, latest_block: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// Reports a new block. Older blocks than the latest known block are ignored.
+    pub fn update_latest_block(&self, block_number: u64) {
+        self.latest_block
+            .fetch_max(block_number, Ordering::Relaxed);
+    }
+
+    /// Returns the latest block reported to the guard.
+    pub fn latest_block(&self) -> u64 {
+        self.latest_block
+            .load(Ordering::Relaxed)
+    }
+
+    /// Raises an `EncodingError::RecoverableError` if any swap of the solution was derived from a
+    /// state more than `max_age_blocks` blocks older than the latest known block.
+    pub fn validate(&self, solution: &Solution) -> Result<(), EncodingError> {
+        let state_versions = solution.state_versions();
+        let reference_block = state_versions
+            .iter()
+            .flatten()
+            .map(|version| version.block_number)
+            .max()
+            .unwrap_or_default()
+            .max(self.latest_block());
+
+        for (i, version) in state_versions.iter().enumerate() {
+            if let Some(version) = version {
+                let age = reference_block.saturating_sub(version.block_number);
+                if age > self.max_age_blocks {
+                    return Err(EncodingError::RecoverableError(format!(
+                        "Swap {i} was derived from the state of block {}, which is {age} blocks \
+                         older than block {reference_block} (max {})",
+                        version.block_number, self.max_age_blocks
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tycho_common::{models::protocol::ProtocolComponent, Bytes};
+
+    use super::*;
+    use crate::encoding::models::{StateVersion, Swap};
+
+    fn swap(block_number: Option<u64>) -> Swap {
+        let swap = Swap::new(
+            ProtocolComponent::default(),
+            Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+        );
+        match block_number {
+            Some(block_number) => swap.state_version(StateVersion::new(block_number)),
+            None => swap,
+        }
+    }
+
+    #[test]
+    fn test_mixed_snapshots_in_solution() {
+        let guard = StalenessGuard::new(2);
+        let solution =
+            Solution { swaps: vec![swap(Some(100)), swap(Some(102))], ..Default::default() };
+        assert!(guard.validate(&solution).is_ok());
+
+        let solution =
+            Solution { swaps: vec![swap(Some(100)), swap(Some(103))], ..Default::default() };
+        assert!(matches!(guard.validate(&solution), Err(EncodingError::RecoverableError(_))));
+    }
+
+    #[test]
+    fn test_stale_against_latest_block() {
+        let guard = StalenessGuard::new(2);
+        let solution = Solution { swaps: vec![swap(Some(100)), swap(None)], ..Default::default() };
+        assert!(guard.validate(&solution).is_ok());
+
+        guard.clone().update_latest_block(105);
+        // Older blocks don't move the latest block back
+        guard.update_latest_block(101);
+        assert_eq!(guard.latest_block(), 105);
+        assert!(guard.validate(&solution).is_err());
+    }
+}
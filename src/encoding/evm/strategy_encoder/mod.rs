@@ -2,3 +2,4 @@ pub mod strategy_encoders;
 mod strategy_validators;
 
 mod transfer_optimizations;
+pub mod wrap_adapter;
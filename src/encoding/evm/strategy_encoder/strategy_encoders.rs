@@ -1,41 +1,455 @@
-use std::collections::HashSet;
+use std::{borrow::Cow, collections::BTreeSet};
 
-use alloy::primitives::{aliases::U24, U8};
+use alloy::primitives::aliases::U24;
+use async_trait::async_trait;
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
     evm::{
-        constants::NON_PLE_ENCODED_PROTOCOLS,
-        group_swaps::group_swaps,
+        constants::{EXACT_OUT_PROTOCOLS, NON_PLE_ENCODED_PROTOCOLS, ROUTER_GAS_OVERHEAD},
+        group_swaps::{group_split_branches, group_swaps, ungroup_swaps, SwapGroup},
         strategy_encoder::{
             strategy_validators::{SequentialSwapValidator, SplitSwapValidator, SwapValidator},
             transfer_optimizations::TransferOptimization,
+            wrap_adapter::{ChainTokens, WrapVariant},
         },
-        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
-        utils::{get_token_position, percentage_to_uint24, ple_encode},
+        swap_encoder::swap_encoder_registry::{SharedSwapEncoderRegistry, SwapEncoderRegistry},
+        utils::{
+            append_user_data, get_token_position, ple_encode, splits_to_uint24, splits_to_uint32,
+            summarize_encoded_swaps,
+        },
+    },
+    models::{
+        ApprovalPolicy, DegradedSwapGroup, EncodedSolution, EncodingContext, EncodingDebugInfo,
+        Fee, Solution, SwapGroupDebugInfo, TransferType, UserTransferType,
     },
-    models::{EncodedSolution, EncodingContext, NativeAction, Solution, UserTransferType},
     strategy_encoder::StrategyEncoder,
     swap_encoder::SwapEncoder,
 };
 
+/// A group of swaps with the encoder and context to encode them with.
+///
+/// Strategies first plan the encoding of all their swap groups, then encode the groups (blocking
+/// or asynchronously) and finally assemble the encoded groups into the solution's swaps.
+///
+/// `in_between_optimization` is set if the previous group sends its token out directly to the pool
+/// of this group.
+struct PlannedSwapGroup<'a> {
+    grouped_swap: SwapGroup,
+    swap_encoder: &'a dyn SwapEncoder,
+    encoding_context: EncodingContext,
+    in_between_optimization: bool,
+}
+
+impl PlannedSwapGroup<'_> {
+    /// Describes how the token transfers of the group were decided.
+    fn debug_info(&self) -> Result<SwapGroupDebugInfo, EncodingError> {
+        Ok(SwapGroupDebugInfo {
+            protocol_system: self
+                .grouped_swap
+                .protocol_system
+                .clone(),
+            executor: self.executor_address()?,
+            token_in: self.grouped_swap.token_in.clone(),
+            token_out: self.grouped_swap.token_out.clone(),
+            transfer_type: self.encoding_context.transfer_type,
+            receiver: self.encoding_context.receiver.clone(),
+            in_between_optimization: self.in_between_optimization,
+        })
+    }
+
+    /// Returns the address of the executor performing the swaps of the group.
+    fn executor_address(&self) -> Result<Bytes, EncodingError> {
+        self.swap_encoder
+            .group_executor_address(&self.grouped_swap.swaps)
+    }
+
+    /// Encodes the protocol data of all the swaps in the group, each followed by its user data
+    /// segment if its executor accepts one.
+    fn encode(&self) -> Result<Vec<u8>, EncodingError> {
+        let mut protocol_data = Vec::with_capacity(self.grouped_swap.swaps.len());
+        for swap in self.grouped_swap.swaps.iter() {
+            let swap_data = self
+                .swap_encoder
+                .encode_swap(swap, &self.encoding_context)?;
+            protocol_data.push(append_user_data(self.swap_encoder, swap, swap_data)?);
+        }
+        self.pack(protocol_data)
+    }
+
+    /// Encodes the protocol data of all the swaps in the group, awaiting the swap encoder.
+    async fn encode_async(&self) -> Result<Vec<u8>, EncodingError> {
+        let mut protocol_data = Vec::with_capacity(self.grouped_swap.swaps.len());
+        for swap in self.grouped_swap.swaps.iter() {
+            let swap_data = self
+                .swap_encoder
+                .encode_swap_async(swap, &self.encoding_context)
+                .await?;
+            protocol_data.push(append_user_data(self.swap_encoder, swap, swap_data)?);
+        }
+        self.pack(protocol_data)
+    }
+
+    /// Packs the protocol data of the swaps in the group: the data of the swap taking the group's
+    /// input token comes first, followed by the data of the other swaps.
+    ///
+    /// A group splitting its input token over several branches (see `group_split_branches`) has
+    /// each branch packed this way, then the branches packed together by the swap encoder.
+    fn pack(&self, protocol_data: Vec<Vec<u8>>) -> Result<Vec<u8>, EncodingError> {
+        let swaps = &self.grouped_swap.swaps;
+        // A branch starts with a swap taking the group's input token, unless it continues the
+        // previous swap
+        let mut branches: Vec<(f64, Vec<u8>, Vec<Vec<u8>>)> = vec![];
+        for (i, (swap, protocol_data)) in swaps
+            .iter()
+            .zip(protocol_data)
+            .enumerate()
+        {
+            let starts_branch = self.encoding_context.group_token_in == *swap.token_in() &&
+                (i == 0 || swaps[i - 1].token_out() != swap.token_in());
+            match branches.last_mut() {
+                Some((_, _, grouped_protocol_data)) if !starts_branch => {
+                    grouped_protocol_data.push(protocol_data)
+                }
+                _ => branches.push((swap.get_split(), protocol_data, vec![])),
+            }
+        }
+
+        if branches.len() == 1 {
+            let (_, initial_protocol_data, grouped_protocol_data) = branches.remove(0);
+            return self.pack_sequence(initial_protocol_data, grouped_protocol_data);
+        }
+        let branches = branches
+            .into_iter()
+            .map(|(split, initial_protocol_data, grouped_protocol_data)| {
+                Ok((split, self.pack_sequence(initial_protocol_data, grouped_protocol_data)?))
+            })
+            .collect::<Result<Vec<_>, EncodingError>>()?;
+        self.swap_encoder
+            .pack_split_branches(branches)
+    }
+
+    /// Packs the protocol data of a sequence of swaps: the data of its first swap, followed by the
+    /// data of the other swaps.
+    fn pack_sequence(
+        &self,
+        mut initial_protocol_data: Vec<u8>,
+        grouped_protocol_data: Vec<Vec<u8>>,
+    ) -> Result<Vec<u8>, EncodingError> {
+        if !grouped_protocol_data.is_empty() {
+            if NON_PLE_ENCODED_PROTOCOLS.contains(
+                self.grouped_swap
+                    .protocol_system
+                    .as_str(),
+            ) {
+                for protocol_data in grouped_protocol_data {
+                    initial_protocol_data.extend(protocol_data);
+                }
+            } else {
+                initial_protocol_data.extend(ple_encode(grouped_protocol_data)?);
+            }
+        }
+        Ok(initial_protocol_data)
+    }
+}
+
+/// Encodes the protocol data of the planned swap groups, in order.
+///
+/// Fails with the index of the first group failing to encode, along with its error.
+fn encode_planned_groups(
+    groups: &[PlannedSwapGroup],
+) -> Result<Vec<Vec<u8>>, (usize, EncodingError)> {
+    groups
+        .iter()
+        .enumerate()
+        .map(|(i, group)| {
+            group
+                .encode()
+                .map_err(|error| (i, error))
+        })
+        .collect()
+}
+
+/// Encodes the protocol data of the planned swap groups, in order, awaiting the swap encoders.
+///
+/// Fails with the index of the first group failing to encode, along with its error.
+async fn encode_planned_groups_async(
+    groups: &[PlannedSwapGroup<'_>],
+) -> Result<Vec<Vec<u8>>, (usize, EncodingError)> {
+    let mut protocol_data = Vec::with_capacity(groups.len());
+    for (i, group) in groups.iter().enumerate() {
+        protocol_data.push(
+            group
+                .encode_async()
+                .await
+                .map_err(|error| (i, error))?,
+        );
+    }
+    Ok(protocol_data)
+}
+
+/// The swap groups which failed to encode as one swap execution, and whose swaps are planned as
+/// individual swaps instead (see `with_ungrouped_fallback`).
+#[derive(Default)]
+struct UngroupedFallback {
+    groups: Vec<SwapGroup>,
+    reports: Vec<DegradedSwapGroup>,
+}
+
+impl UngroupedFallback {
+    /// Degrades the planned group which failed to encode with the given error, or returns the error
+    /// if the group is a single swap, which can't be degraded any further.
+    fn degrade(
+        &mut self,
+        group: &PlannedSwapGroup,
+        error: EncodingError,
+    ) -> Result<(), EncodingError> {
+        let grouped_swap = &group.grouped_swap;
+        if grouped_swap.swaps.len() < 2 {
+            return Err(error);
+        }
+        self.reports.push(DegradedSwapGroup {
+            protocol_system: grouped_swap.protocol_system.clone(),
+            token_in: grouped_swap.token_in.clone(),
+            token_out: grouped_swap.token_out.clone(),
+            n_swaps: grouped_swap.swaps.len(),
+            reason: error.to_string(),
+        });
+        self.groups.push(grouped_swap.clone());
+        Ok(())
+    }
+}
+
+/// Describes how the token transfers of the planned swap groups were decided, in order.
+fn debug_info(groups: &[PlannedSwapGroup]) -> Result<EncodingDebugInfo, EncodingError> {
+    Ok(EncodingDebugInfo {
+        swap_groups: groups
+            .iter()
+            .map(PlannedSwapGroup::debug_info)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Estimates the gas of the router call executing the planned swap groups: the router overhead plus
+/// the static estimate of every swap, including the grouped ones.
+fn estimate_gas(swap_encoder_registry: &SwapEncoderRegistry, groups: &[PlannedSwapGroup]) -> u64 {
+    groups
+        .iter()
+        .flat_map(|group| group.grouped_swap.swaps.iter())
+        .map(|swap| swap_encoder_registry.gas_estimate(&swap.component().protocol_system))
+        .fold(ROUTER_GAS_OVERHEAD, u64::saturating_add)
+}
+
+/// A deduplicated table of the executors used by the swaps of a solution.
+///
+/// In the indexed encoding mode, the table is encoded before the swaps (a 1-byte count followed by
+/// the 20-byte address of every executor) and each swap refers to its executor by its 1-byte index
+/// in the table, instead of repeating the executor address. This reduces the calldata of solutions
+/// with many swaps, which matters on chains where calldata dominates the gas cost.
+#[derive(Default)]
+struct ExecutorTable {
+    executors: Vec<Bytes>,
+}
+
+impl ExecutorTable {
+    /// Returns the index of the executor in the table, adding it if it's not in the table yet.
+    fn index(&mut self, executor: &Bytes) -> Result<u8, EncodingError> {
+        if let Some(index) = self
+            .executors
+            .iter()
+            .position(|e| e == executor)
+        {
+            return Ok(index as u8);
+        }
+        if self.executors.len() == u8::MAX as usize {
+            return Err(EncodingError::FatalError(format!(
+                "The executor table can't hold more than {} executors",
+                u8::MAX
+            )));
+        }
+        self.executors.push(executor.clone());
+        Ok((self.executors.len() - 1) as u8)
+    }
+
+    /// Encodes the table followed by the given encoded swaps.
+    fn encode(self, encoded_swaps: Vec<u8>) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(1 + 20 * self.executors.len() + encoded_swaps.len());
+        encoded.push(self.executors.len() as u8);
+        for executor in self.executors {
+            encoded.extend(executor.to_vec());
+        }
+        encoded.extend(encoded_swaps);
+        encoded
+    }
+}
+
+/// Returns the signature of the router function taking indexed swaps (see `ExecutorTable`)
+/// matching the given one, e.g. `sequentialSwapIndexed` for `sequentialSwap`.
+fn indexed_function_signature(function_signature: &str) -> String {
+    function_signature.replacen('(', "Indexed(", 1)
+}
+
+/// Returns an `EncodingError::InvalidInput` if a split solution has more distinct tokens than its
+/// swaps can refer to with token indices of `index_bytes` bytes.
+fn validate_token_count(
+    n_tokens: usize,
+    index_bytes: u32,
+    hint: &str,
+) -> Result<(), EncodingError> {
+    let max_tokens = 1usize << (8 * index_bytes);
+    if n_tokens > max_tokens {
+        return Err(EncodingError::InvalidInput(format!(
+            "The solution has {n_tokens} distinct tokens, but the {index_bytes}-byte token \
+             indices of split swaps can only refer to {max_tokens}{hint}"
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the signature of the router function taking the integrator fee of the solution
+/// matching the given one, e.g. `sequentialSwapWithFee` for `sequentialSwap`. Returns the given
+/// signature if the solution has no fee.
+///
+/// The router only takes fees in the functions without permit2, an executor table, wide token
+/// indices, precise splits or an exact amount out, so an `EncodingError::InvalidInput` is returned
+/// for the others.
+fn fee_function_signature(
+    function_signature: &str,
+    fee: Option<&Fee>,
+) -> Result<String, EncodingError> {
+    if fee.is_none() {
+        return Ok(function_signature.to_string());
+    }
+    let Some(prefix) = function_signature.strip_suffix(",bytes)") else {
+        return Err(EncodingError::InvalidInput(format!(
+            "Integrator fees are not supported by {function_signature}"
+        )));
+    };
+    if ["Permit2", "Indexed", "Wide", "Precise", "exactOut"]
+        .iter()
+        .any(|variant| prefix.contains(variant))
+    {
+        return Err(EncodingError::InvalidInput(
+            "Integrator fees are only supported for exact in solutions, with the TransferFrom or \
+             None user transfer type and without an executor table, wide token indices or \
+             precise splits"
+                .to_string(),
+        ));
+    }
+    Ok(format!("{},(address,uint16,bool),bytes)", prefix.replacen('(', "WithFee(", 1)))
+}
+
+/// Returns the signature of the router function calling back the receiver with the given data
+/// after sending it the output (e.g. `sequentialSwapWithCallback` for `sequentialSwap`), or the
+/// given signature if there is no callback.
+///
+/// Like fees, callbacks are only supported by the functions without permit2, an executor table,
+/// wide token indices, precise splits or an exact amount out, and can't be combined with a fee, so
+/// an `EncodingError::InvalidInput` is returned for the others.
+fn callback_function_signature(
+    function_signature: String,
+    receiver_callback: Option<&Bytes>,
+) -> Result<String, EncodingError> {
+    if receiver_callback.is_none() {
+        return Ok(function_signature);
+    }
+    let Some(prefix) = function_signature.strip_suffix(",bytes)") else {
+        return Err(EncodingError::InvalidInput(format!(
+            "Receiver callbacks are not supported by {function_signature}"
+        )));
+    };
+    if prefix.contains("WithFee") {
+        return Err(EncodingError::InvalidInput(
+            "Receiver callbacks can't be combined with an integrator fee".to_string(),
+        ));
+    }
+    if ["Permit2", "Indexed", "Wide", "Precise", "exactOut"]
+        .iter()
+        .any(|variant| prefix.contains(variant))
+    {
+        return Err(EncodingError::InvalidInput(
+            "Receiver callbacks are only supported for exact in solutions, with the TransferFrom \
+             or None user transfer type and without an executor table, wide token indices or \
+             precise splits"
+                .to_string(),
+        ));
+    }
+    Ok(format!("{},bytes,bytes)", prefix.replacen('(', "WithCallback(", 1)))
+}
+
+/// Returns the transfer type of every planned swap group with its token in (see
+/// `TransferOptimization::validate_transfers`).
+fn group_transfers<'a>(groups: &'a [PlannedSwapGroup<'_>]) -> Vec<(TransferType, &'a Bytes)> {
+    groups
+        .iter()
+        .map(|group| (group.encoding_context.transfer_type, &group.grouped_swap.token_in))
+        .collect()
+}
+
+/// Returns the token in, the token out and the receiver of the planned swap groups, to check how
+/// the native token flows through them (see `TransferOptimization::validate_native_flow`).
+fn group_native_flows<'a>(
+    groups: &'a [PlannedSwapGroup<'_>],
+) -> Vec<(&'a Bytes, &'a Bytes, &'a Bytes)> {
+    groups
+        .iter()
+        .map(|group| {
+            (
+                &group.grouped_swap.token_in,
+                &group.grouped_swap.token_out,
+                &group.encoding_context.receiver,
+            )
+        })
+        .collect()
+}
+
+/// Returns the strategy with the current registry of its shared registry if it was reloaded since
+/// the strategy took it (see `SharedSwapEncoderRegistry`), else the strategy itself.
+fn with_current_registry<'a, S: Clone>(
+    strategy: &'a S,
+    registry: &SwapEncoderRegistry,
+    shared_registry: Option<&SharedSwapEncoderRegistry>,
+    set_registry: fn(&mut S, SwapEncoderRegistry),
+) -> Cow<'a, S> {
+    match shared_registry.map(SharedSwapEncoderRegistry::current) {
+        Some(current) if !current.shares_encoders_with(registry) => {
+            let mut strategy = strategy.clone();
+            set_registry(&mut strategy, current);
+            Cow::Owned(strategy)
+        }
+        _ => Cow::Borrowed(strategy),
+    }
+}
+
 /// Represents the encoder for a swap strategy which supports single swaps.
 ///
 /// # Fields
 /// * `swap_encoder_registry`: SwapEncoderRegistry, containing all possible swap encoders
 /// * `function_signature`: String, the signature for the swap function in the router contract
+/// * `exact_out_function_signature`: Optional signature of the exact out swap function. Exact out
+///   swaps are only supported with `TransferFrom` user transfers.
 /// * `router_address`: Address of the router to be used to execute swaps
 /// * `transfer_optimization`: TransferOptimization, responsible for optimizing the token transfers
 /// * `historical_trade`: Whether the swap is to be done in the current block or in an historical
 ///   one. This is relevant for checking token approvals in some protocols (like Balancer v2).
+/// * `wrap_variant`: How the router wraps and unwraps the native token of the chain (see
+///   `with_wrap_variant`)
+/// * `approval_policy`: How the swap encoders decide whether the router approves the protocols (see
+///   `with_approval_policy`)
+/// * `shared_registry`: Optional shared registry the swap encoders are taken from (see
+///   `with_shared_registry`)
 #[derive(Clone)]
 pub struct SingleSwapStrategyEncoder {
     swap_encoder_registry: SwapEncoderRegistry,
     function_signature: String,
+    exact_out_function_signature: Option<String>,
     router_address: Bytes,
     transfer_optimization: TransferOptimization,
     historical_trade: bool,
+    wrap_variant: WrapVariant,
+    approval_policy: ApprovalPolicy,
+    shared_registry: Option<SharedSwapEncoderRegistry>,
 }
 
 impl SingleSwapStrategyEncoder {
@@ -51,21 +465,101 @@ impl SingleSwapStrategyEncoder {
         } else {
             "singleSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)"
         }.to_string();
+        let exact_out_function_signature = (user_transfer_type == UserTransferType::TransferFrom)
+            .then(|| {
+                "exactOutSingleSwap(uint256,address,address,uint256,bool,address,bool,bytes)"
+                    .to_string()
+            });
 
+        let chain_tokens = ChainTokens::for_chain(chain)?;
         Ok(Self {
             function_signature,
+            exact_out_function_signature,
             swap_encoder_registry,
             router_address: router_address.clone(),
             transfer_optimization: TransferOptimization::new(
-                chain.native_token().address,
-                chain.wrapped_native_token().address,
+                chain_tokens.native,
+                chain_tokens.wrapped,
                 user_transfer_type,
                 router_address,
             ),
             historical_trade,
+            wrap_variant: WrapVariant::for_chain(chain)?,
+            approval_policy: ApprovalPolicy::default(),
+            shared_registry: None,
         })
     }
 
+    /// Sets how the router wraps and unwraps the native token, instead of the variant configured
+    /// for the chain (see `WrapVariant::for_chain`).
+    pub fn with_wrap_variant(mut self, wrap_variant: WrapVariant) -> Self {
+        self.wrap_variant = wrap_variant;
+        self
+    }
+
+    /// Sets how the swap encoders decide whether the router approves the protocols, instead of
+    /// looking its allowances up (see `ApprovalPolicy`).
+    pub fn with_approval_policy(mut self, approval_policy: ApprovalPolicy) -> Self {
+        self.approval_policy = approval_policy;
+        self
+    }
+
+    /// Takes the swap encoders from the given shared registry. Its current registry is used at the
+    /// start of every encoding, so that reloaded executors are picked up without rebuilding the
+    /// strategy.
+    pub fn with_shared_registry(mut self, shared_registry: SharedSwapEncoderRegistry) -> Self {
+        self.swap_encoder_registry = shared_registry.current();
+        self.shared_registry = Some(shared_registry);
+        self
+    }
+
+    /// Returns the strategy with the current registry of its shared registry.
+    fn current(&self) -> Cow<'_, Self> {
+        with_current_registry(
+            self,
+            &self.swap_encoder_registry,
+            self.shared_registry.as_ref(),
+            |strategy, registry| strategy.swap_encoder_registry = registry,
+        )
+    }
+
+    /// Returns the exact out function signature if the solution can be encoded as an exact out
+    /// single swap: one swap on a protocol supporting exact out swaps, paid with a `transferFrom`
+    /// of the sender's tokens and without wrapping.
+    fn validate_exact_out(
+        &self,
+        solution: &Solution,
+        protocol: &str,
+        wrap: bool,
+    ) -> Result<&String, EncodingError> {
+        let function_signature = self
+            .exact_out_function_signature
+            .as_ref()
+            .ok_or_else(|| {
+                EncodingError::InvalidInput(
+                    "Exact out solutions are only supported with the TransferFrom user transfer \
+                     type"
+                        .to_string(),
+                )
+            })?;
+        if !EXACT_OUT_PROTOCOLS.contains(protocol) {
+            return Err(EncodingError::InvalidInput(format!(
+                "Exact out swaps are not supported for protocol {protocol}"
+            )));
+        }
+        if solution.swaps.len() != 1 {
+            return Err(EncodingError::InvalidInput(
+                "Exact out solutions only support a single swap".to_string(),
+            ));
+        }
+        if wrap {
+            return Err(EncodingError::InvalidInput(
+                "Exact out solutions do not support wrapping the native token".to_string(),
+            ));
+        }
+        Ok(function_signature)
+    }
+
     /// Encodes information necessary for performing a single hop against a given executor for
     /// a protocol.
     fn encode_swap_header(&self, executor_address: Bytes, protocol_data: Vec<u8>) -> Vec<u8> {
@@ -74,11 +568,12 @@ impl SingleSwapStrategyEncoder {
         encoded.extend(protocol_data);
         encoded
     }
-}
 
-impl StrategyEncoder for SingleSwapStrategyEncoder {
-    fn encode_strategy(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
-        let grouped_swaps = group_swaps(&solution.swaps);
+    /// Validates the solution and plans the encoding of its single swap group.
+    ///
+    /// Returns the planned group and the signature of the router function to call.
+    fn plan(&self, solution: &Solution) -> Result<(PlannedSwapGroup<'_>, String), EncodingError> {
+        let grouped_swaps = group_swaps(&solution.swaps, &self.swap_encoder_registry);
         let number_of_groups = grouped_swaps.len();
         if number_of_groups != 1 {
             return Err(EncodingError::InvalidInput(format!(
@@ -87,7 +582,8 @@ impl StrategyEncoder for SingleSwapStrategyEncoder {
         }
 
         let grouped_swap = grouped_swaps
-            .first()
+            .into_iter()
+            .next()
             .ok_or_else(|| EncodingError::FatalError("Swap grouping failed".to_string()))?;
 
         if grouped_swap.split != 0f64 {
@@ -96,14 +592,21 @@ impl StrategyEncoder for SingleSwapStrategyEncoder {
             ))
         }
 
-        let (mut unwrap, mut wrap) = (false, false);
-        if let Some(action) = &solution.native_action {
-            match *action {
-                NativeAction::Wrap => wrap = true,
-                NativeAction::Unwrap => unwrap = true,
-            }
-        }
-        let protocol = &grouped_swap.protocol_system;
+        let (wrap, unwrap) = self
+            .wrap_variant
+            .native_action_flags(solution.native_action.as_ref())?;
+        let protocol = grouped_swap.protocol_system.as_str();
+        let function_signature = callback_function_signature(
+            fee_function_signature(
+                if solution.exact_out {
+                    self.validate_exact_out(solution, protocol, wrap)?
+                } else {
+                    &self.function_signature
+                },
+                solution.fee.as_ref(),
+            )?,
+            solution.receiver_callback.as_ref(),
+        )?;
         let swap_encoder = self
             .get_swap_encoder(protocol)
             .ok_or_else(|| {
@@ -112,12 +615,29 @@ impl StrategyEncoder for SingleSwapStrategyEncoder {
                 ))
             })?;
 
-        let swap_receiver =
-            if !unwrap { solution.receiver.clone() } else { self.router_address.clone() };
+        // Output fees are taken by the router, and receivers called back by it, so it must receive
+        // the amount out
+        let output_to_router = solution
+            .fee
+            .as_ref()
+            .is_some_and(|fee| !fee.on_input) ||
+            solution.receiver_callback.is_some();
+        let swap_receiver = if !unwrap && !output_to_router {
+            grouped_swap
+                .receiver_override()
+                .unwrap_or(&solution.receiver)
+                .clone()
+        } else {
+            self.router_address.clone()
+        };
 
+        // The sold token is the given token of exact in solutions and the checked token of exact
+        // out ones
+        let sell_token =
+            if solution.exact_out { &solution.checked_token } else { &solution.given_token };
         let transfer = self
             .transfer_optimization
-            .get_transfers(grouped_swap, &solution.given_token, wrap, false);
+            .get_transfers(&grouped_swap, sell_token, wrap, false, &self.swap_encoder_registry);
         let encoding_context = EncodingContext {
             receiver: swap_receiver,
             exact_out: solution.exact_out,
@@ -126,39 +646,79 @@ impl StrategyEncoder for SingleSwapStrategyEncoder {
             group_token_out: grouped_swap.token_out.clone(),
             transfer_type: transfer,
             historical_trade: self.historical_trade,
+            execution_deadline: solution.execution_deadline,
+            approval_policy: self.approval_policy,
         };
+        self.transfer_optimization
+            .validate_transfers(&[(transfer, &grouped_swap.token_in)], sell_token, true)?;
+
+        Ok((
+            PlannedSwapGroup {
+                grouped_swap,
+                swap_encoder: swap_encoder.as_ref(),
+                encoding_context,
+                in_between_optimization: false,
+            },
+            function_signature,
+        ))
+    }
 
-        let mut grouped_protocol_data: Vec<Vec<u8>> = vec![];
-        let mut initial_protocol_data: Vec<u8> = vec![];
-        for swap in grouped_swap.swaps.iter() {
-            let protocol_data = swap_encoder.encode_swap(swap, &encoding_context)?;
-            if encoding_context.group_token_in == *swap.token_in() {
-                initial_protocol_data = protocol_data;
-            } else {
-                grouped_protocol_data.push(protocol_data);
-            }
-        }
-
-        if !grouped_protocol_data.is_empty() {
-            if NON_PLE_ENCODED_PROTOCOLS.contains(grouped_swap.protocol_system.as_str()) {
-                for protocol_data in grouped_protocol_data {
-                    initial_protocol_data.extend(protocol_data);
-                }
-            } else {
-                initial_protocol_data.extend(ple_encode(grouped_protocol_data));
-            }
-        }
-
-        let swap_data =
-            self.encode_swap_header(swap_encoder.executor_address().clone(), initial_protocol_data);
+    /// Assembles the encoded solution from the protocol data of its planned swap group.
+    fn assemble(
+        &self,
+        solution: &Solution,
+        group: &PlannedSwapGroup,
+        function_signature: &str,
+        protocol_data: Vec<u8>,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let swap_data = self.encode_swap_header(group.executor_address()?, protocol_data);
+        let summary = summarize_encoded_swaps(
+            function_signature,
+            &swap_data,
+            solution.swaps.len(),
+            &[group.encoding_context.transfer_type],
+        );
+        let gas_estimate = estimate_gas(&self.swap_encoder_registry, std::slice::from_ref(group));
         Ok(EncodedSolution {
-            function_signature: self.function_signature.clone(),
+            function_signature: function_signature.to_string(),
             interacting_with: self.router_address.clone(),
             swaps: swap_data,
             permit: None,
+            permit_batch: None,
             n_tokens: 0,
+            native_action: solution.native_action.clone(),
+            state_versions: solution.state_versions(),
+            callback_allowlist: None,
+            summary,
+            gas_estimate,
+            debug_info: Some(debug_info(std::slice::from_ref(group))?),
+            degraded_groups: vec![],
         })
     }
+}
+
+#[async_trait]
+impl StrategyEncoder for SingleSwapStrategyEncoder {
+    fn name(&self) -> &'static str {
+        "single_swap"
+    }
+
+    fn encode_strategy(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
+        let this = self.current();
+        let (group, function_signature) = this.plan(solution)?;
+        let protocol_data = group.encode()?;
+        this.assemble(solution, &group, &function_signature, protocol_data)
+    }
+
+    async fn encode_strategy_async(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let this = self.current();
+        let (group, function_signature) = this.plan(solution)?;
+        let protocol_data = group.encode_async().await?;
+        this.assemble(solution, &group, &function_signature, protocol_data)
+    }
 
     fn get_swap_encoder(&self, protocol_system: &str) -> Option<&Box<dyn SwapEncoder>> {
         self.swap_encoder_registry
@@ -183,6 +743,17 @@ impl StrategyEncoder for SingleSwapStrategyEncoder {
 /// * `transfer_optimization`: TransferOptimization, responsible for optimizing the token transfers
 /// * `historical_trade`: Whether the swap is to be done in the current block or in an historical
 ///   one. This is relevant for checking token approvals in some protocols (like Balancer v2).
+/// * `executor_table`: Whether the executors are encoded in a shared table, with the swaps
+///   referring to them by index (see `with_executor_table`)
+/// * `wrap_variant`: How the router wraps and unwraps the native token of the chain (see
+///   `with_wrap_variant`)
+/// * `approval_policy`: How the swap encoders decide whether the router approves the protocols (see
+///   `with_approval_policy`)
+/// * `chain`: Chain of the solutions, used to simulate their vault swaps
+/// * `ungrouped_fallback`: Whether the groups of swaps failing to encode are encoded as individual
+///   swaps instead of failing the solution (see `with_ungrouped_fallback`)
+/// * `shared_registry`: Optional shared registry the swap encoders are taken from (see
+///   `with_shared_registry`)
 #[derive(Clone)]
 pub struct SequentialSwapStrategyEncoder {
     swap_encoder_registry: SwapEncoderRegistry,
@@ -193,6 +764,12 @@ pub struct SequentialSwapStrategyEncoder {
     sequential_swap_validator: SequentialSwapValidator,
     transfer_optimization: TransferOptimization,
     historical_trade: bool,
+    executor_table: bool,
+    wrap_variant: WrapVariant,
+    approval_policy: ApprovalPolicy,
+    chain: Chain,
+    ungrouped_fallback: bool,
+    shared_registry: Option<SharedSwapEncoderRegistry>,
 }
 
 impl SequentialSwapStrategyEncoder {
@@ -209,8 +786,8 @@ impl SequentialSwapStrategyEncoder {
             "sequentialSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)"
 
         }.to_string();
-        let native_token_address = chain.native_token().address;
-        let wrapped_token_address = chain.wrapped_native_token().address;
+        let ChainTokens { native: native_token_address, wrapped: wrapped_token_address } =
+            ChainTokens::for_chain(chain)?;
         Ok(Self {
             function_signature,
             swap_encoder_registry,
@@ -225,9 +802,68 @@ impl SequentialSwapStrategyEncoder {
                 router_address,
             ),
             historical_trade,
+            executor_table: false,
+            wrap_variant: WrapVariant::for_chain(chain)?,
+            approval_policy: ApprovalPolicy::default(),
+            shared_registry: None,
+            chain,
+            ungrouped_fallback: false,
         })
     }
 
+    /// Sets how the router wraps and unwraps the native token, instead of the variant configured
+    /// for the chain (see `WrapVariant::for_chain`).
+    pub fn with_wrap_variant(mut self, wrap_variant: WrapVariant) -> Self {
+        self.wrap_variant = wrap_variant;
+        self
+    }
+
+    /// Sets how the swap encoders decide whether the router approves the protocols, instead of
+    /// looking its allowances up (see `ApprovalPolicy`).
+    pub fn with_approval_policy(mut self, approval_policy: ApprovalPolicy) -> Self {
+        self.approval_policy = approval_policy;
+        self
+    }
+
+    /// Takes the swap encoders from the given shared registry. Its current registry is used at the
+    /// start of every encoding, so that reloaded executors are picked up without rebuilding the
+    /// strategy.
+    pub fn with_shared_registry(mut self, shared_registry: SharedSwapEncoderRegistry) -> Self {
+        self.swap_encoder_registry = shared_registry.current();
+        self.shared_registry = Some(shared_registry);
+        self
+    }
+
+    /// Returns the strategy with the current registry of its shared registry.
+    fn current(&self) -> Cow<'_, Self> {
+        with_current_registry(
+            self,
+            &self.swap_encoder_registry,
+            self.shared_registry.as_ref(),
+            |strategy, registry| strategy.swap_encoder_registry = registry,
+        )
+    }
+
+    /// Encodes the swaps of a group failing to encode as one swap execution (e.g. a Uniswap V4 swap
+    /// missing an attribute only needed when grouped) as individual swaps, with explicit transfers,
+    /// instead of failing the solution. The degraded groups are reported in the encoded solution
+    /// (see `EncodedSolution::degraded_groups`), so that the extra gas can be accounted for.
+    pub fn with_ungrouped_fallback(mut self) -> Self {
+        self.ungrouped_fallback = true;
+        self
+    }
+
+    /// Encodes the executors of the swaps in a deduplicated table placed before the swaps, with
+    /// every swap referring to its executor by a 1-byte index. The solutions are then encoded for
+    /// the `sequentialSwapIndexed` variants of the router functions.
+    pub fn with_executor_table(mut self) -> Self {
+        if !self.executor_table {
+            self.function_signature = indexed_function_signature(&self.function_signature);
+            self.executor_table = true;
+        }
+        self
+    }
+
     /// Encodes information necessary for performing a single hop against a given executor for
     /// a protocol.
     fn encode_swap_header(&self, executor_address: Bytes, protocol_data: Vec<u8>) -> Vec<u8> {
@@ -236,10 +872,14 @@ impl SequentialSwapStrategyEncoder {
         encoded.extend(protocol_data);
         encoded
     }
-}
 
-impl StrategyEncoder for SequentialSwapStrategyEncoder {
-    fn encode_strategy(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
+    /// Validates the solution and plans the encoding of its swap groups, in order. The swaps of the
+    /// degraded groups are planned individually (see `with_ungrouped_fallback`).
+    fn plan(
+        &self,
+        solution: &Solution,
+        degraded: &[SwapGroup],
+    ) -> Result<Vec<PlannedSwapGroup<'_>>, EncodingError> {
         self.sequential_swap_validator
             .validate_swap_path(
                 &solution.swaps,
@@ -249,18 +889,27 @@ impl StrategyEncoder for SequentialSwapStrategyEncoder {
                 &self.native_address,
                 &self.wrapped_address,
             )?;
-
-        let grouped_swaps = group_swaps(&solution.swaps);
-
-        let (mut wrap, mut unwrap) = (false, false);
-        if let Some(action) = &solution.native_action {
-            match *action {
-                NativeAction::Wrap => wrap = true,
-                NativeAction::Unwrap => unwrap = true,
-            }
-        }
-
-        let mut swaps = vec![];
+        self.sequential_swap_validator
+            .validate_vault_conversions(solution, self.chain)?;
+
+        let grouped_swaps = ungroup_swaps(
+            &solution.swaps,
+            group_swaps(&solution.swaps, &self.swap_encoder_registry),
+            degraded,
+        );
+
+        let (wrap, unwrap) = self
+            .wrap_variant
+            .native_action_flags(solution.native_action.as_ref())?;
+
+        // Output fees are taken by the router, and receivers called back by it, so it must receive
+        // the amount out
+        let output_to_router = solution
+            .fee
+            .as_ref()
+            .is_some_and(|fee| !fee.on_input) ||
+            solution.receiver_callback.is_some();
+        let mut groups = Vec::with_capacity(grouped_swaps.len());
         let mut next_in_between_swap_optimization_allowed = true;
         for (i, grouped_swap) in grouped_swaps.iter().enumerate() {
             let protocol = &grouped_swap.protocol_system;
@@ -276,7 +925,14 @@ impl StrategyEncoder for SequentialSwapStrategyEncoder {
             let next_swap = grouped_swaps.get(i + 1);
             let (swap_receiver, next_swap_optimization) = self
                 .transfer_optimization
-                .get_receiver(&solution.receiver, next_swap, unwrap)?;
+                .get_receiver(
+                    grouped_swap
+                        .receiver_override()
+                        .unwrap_or(&solution.receiver),
+                    next_swap,
+                    unwrap || output_to_router,
+                    &self.swap_encoder_registry,
+                )?;
             next_in_between_swap_optimization_allowed = next_swap_optimization;
 
             let transfer = self
@@ -286,6 +942,7 @@ impl StrategyEncoder for SequentialSwapStrategyEncoder {
                     &solution.given_token,
                     wrap,
                     in_between_swap_optimization_allowed,
+                    &self.swap_encoder_registry,
                 );
             let encoding_context = EncodingContext {
                 receiver: swap_receiver,
@@ -295,43 +952,123 @@ impl StrategyEncoder for SequentialSwapStrategyEncoder {
                 group_token_out: grouped_swap.token_out.clone(),
                 transfer_type: transfer,
                 historical_trade: self.historical_trade,
+                execution_deadline: solution.execution_deadline,
+                approval_policy: self.approval_policy,
             };
 
-            let mut grouped_protocol_data: Vec<Vec<u8>> = vec![];
-            let mut initial_protocol_data: Vec<u8> = vec![];
-            for swap in grouped_swap.swaps.iter() {
-                let protocol_data = swap_encoder.encode_swap(swap, &encoding_context)?;
-                if encoding_context.group_token_in == *swap.token_in() {
-                    initial_protocol_data = protocol_data;
-                } else {
-                    grouped_protocol_data.push(protocol_data);
-                }
-            }
-
-            if !grouped_protocol_data.is_empty() {
-                if NON_PLE_ENCODED_PROTOCOLS.contains(grouped_swap.protocol_system.as_str()) {
-                    for protocol_data in grouped_protocol_data {
-                        initial_protocol_data.extend(protocol_data);
-                    }
-                } else {
-                    initial_protocol_data.extend(ple_encode(grouped_protocol_data));
-                }
-            }
+            groups.push(PlannedSwapGroup {
+                grouped_swap: grouped_swap.clone(),
+                swap_encoder: swap_encoder.as_ref(),
+                encoding_context,
+                in_between_optimization: i > 0 && in_between_swap_optimization_allowed,
+            });
+        }
+        self.transfer_optimization
+            .validate_transfers(&group_transfers(&groups), &solution.given_token, true)?;
+        self.transfer_optimization
+            .validate_native_flow(&group_native_flows(&groups), solution, wrap, unwrap)?;
+        Ok(groups)
+    }
 
-            let swap_data = self
-                .encode_swap_header(swap_encoder.executor_address().clone(), initial_protocol_data);
+    /// Assembles the encoded solution from the protocol data of its planned swap groups.
+    fn assemble(
+        &self,
+        solution: &Solution,
+        groups: &[PlannedSwapGroup],
+        protocol_data: Vec<Vec<u8>>,
+        degraded_groups: Vec<DegradedSwapGroup>,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let function_signature = callback_function_signature(
+            fee_function_signature(&self.function_signature, solution.fee.as_ref())?,
+            solution.receiver_callback.as_ref(),
+        )?;
+        let mut executor_table = self
+            .executor_table
+            .then(ExecutorTable::default);
+        let mut swaps = vec![];
+        let mut transfer_types = vec![];
+        for (group, protocol_data) in groups.iter().zip(protocol_data) {
+            let executor_address = group.executor_address()?;
+            let executor = match executor_table.as_mut() {
+                Some(table) => Bytes::from(vec![table.index(&executor_address)?]),
+                None => executor_address,
+            };
+            let swap_data = self.encode_swap_header(executor, protocol_data);
             swaps.push(swap_data);
+            transfer_types.push(group.encoding_context.transfer_type);
         }
 
-        let encoded_swaps = ple_encode(swaps);
+        let mut encoded_swaps = ple_encode(swaps)?;
+        if let Some(table) = executor_table {
+            encoded_swaps = table.encode(encoded_swaps);
+        }
+        let summary = summarize_encoded_swaps(
+            &function_signature,
+            &encoded_swaps,
+            solution.swaps.len(),
+            &transfer_types,
+        );
+        let gas_estimate = estimate_gas(&self.swap_encoder_registry, groups);
         Ok(EncodedSolution {
             interacting_with: self.router_address.clone(),
-            function_signature: self.function_signature.clone(),
+            function_signature,
             swaps: encoded_swaps,
             permit: None,
+            permit_batch: None,
             n_tokens: 0,
+            native_action: solution.native_action.clone(),
+            state_versions: solution.state_versions(),
+            callback_allowlist: None,
+            summary,
+            gas_estimate,
+            debug_info: Some(debug_info(groups)?),
+            degraded_groups,
         })
     }
+}
+
+#[async_trait]
+impl StrategyEncoder for SequentialSwapStrategyEncoder {
+    fn name(&self) -> &'static str {
+        "sequential_swap"
+    }
+
+    fn encode_strategy(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
+        let this = self.current();
+        let mut fallback = UngroupedFallback::default();
+        loop {
+            let groups = this.plan(solution, &fallback.groups)?;
+            match encode_planned_groups(&groups) {
+                Ok(protocol_data) => {
+                    return this.assemble(solution, &groups, protocol_data, fallback.reports)
+                }
+                Err((i, error)) if this.ungrouped_fallback => {
+                    fallback.degrade(&groups[i], error)?
+                }
+                Err((_, error)) => return Err(error),
+            }
+        }
+    }
+
+    async fn encode_strategy_async(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let this = self.current();
+        let mut fallback = UngroupedFallback::default();
+        loop {
+            let groups = this.plan(solution, &fallback.groups)?;
+            match encode_planned_groups_async(&groups).await {
+                Ok(protocol_data) => {
+                    return this.assemble(solution, &groups, protocol_data, fallback.reports)
+                }
+                Err((i, error)) if this.ungrouped_fallback => {
+                    fallback.degrade(&groups[i], error)?
+                }
+                Err((_, error)) => return Err(error),
+            }
+        }
+    }
 
     fn get_swap_encoder(&self, protocol_system: &str) -> Option<&Box<dyn SwapEncoder>> {
         self.swap_encoder_registry
@@ -356,6 +1093,24 @@ impl StrategyEncoder for SequentialSwapStrategyEncoder {
 /// * `transfer_optimization`: TransferOptimization, responsible for optimizing the token transfers
 /// * `historical_trade`: Whether the swap is to be done in the current block or in an historical
 ///   one. This is relevant for checking token approvals in some protocols (like Balancer v2).
+/// * `executor_table`: Whether the executors are encoded in a shared table, with the swaps
+///   referring to them by index (see `with_executor_table`)
+/// * `split_normalization`: Optional epsilon within which the split percentages are normalized
+///   before being validated (see `with_split_normalization`)
+/// * `wrap_variant`: How the router wraps and unwraps the native token of the chain (see
+///   `with_wrap_variant`)
+/// * `approval_policy`: How the swap encoders decide whether the router approves the protocols (see
+///   `with_approval_policy`)
+/// * `wide_token_indices`: Whether the swaps refer to their tokens by a 2-byte index instead of a
+///   1-byte one (see `with_wide_token_indices`)
+/// * `precise_splits`: Whether the splits are encoded on 4 bytes with a 1e9 basis instead of 3
+///   bytes (see `with_precise_splits`)
+/// * `split_branch_groups`: Whether the split branches of a token executed by the same protocol are
+///   grouped into one swap execution (see `with_split_branch_groups`)
+/// * `ungrouped_fallback`: Whether the groups of swaps failing to encode are encoded as individual
+///   swaps instead of failing the solution (see `with_ungrouped_fallback`)
+/// * `shared_registry`: Optional shared registry the swap encoders are taken from (see
+///   `with_shared_registry`)
 #[derive(Clone)]
 pub struct SplitSwapStrategyEncoder {
     swap_encoder_registry: SwapEncoderRegistry,
@@ -366,6 +1121,15 @@ pub struct SplitSwapStrategyEncoder {
     router_address: Bytes,
     transfer_optimization: TransferOptimization,
     historical_trade: bool,
+    executor_table: bool,
+    split_normalization: Option<f64>,
+    wrap_variant: WrapVariant,
+    approval_policy: ApprovalPolicy,
+    wide_token_indices: bool,
+    precise_splits: bool,
+    split_branch_groups: bool,
+    ungrouped_fallback: bool,
+    shared_registry: Option<SharedSwapEncoderRegistry>,
 }
 
 impl SplitSwapStrategyEncoder {
@@ -381,8 +1145,8 @@ impl SplitSwapStrategyEncoder {
         } else {
                 "splitSwap(uint256,address,address,uint256,bool,bool,uint256,address,bool,bytes)"
         }.to_string();
-        let native_token_address = chain.native_token().address;
-        let wrapped_token_address = chain.wrapped_native_token().address;
+        let ChainTokens { native: native_token_address, wrapped: wrapped_token_address } =
+            ChainTokens::for_chain(chain)?;
         Ok(Self {
             function_signature,
             swap_encoder_registry,
@@ -397,33 +1161,248 @@ impl SplitSwapStrategyEncoder {
                 router_address,
             ),
             historical_trade,
+            executor_table: false,
+            split_normalization: None,
+            wrap_variant: WrapVariant::for_chain(chain)?,
+            approval_policy: ApprovalPolicy::default(),
+            shared_registry: None,
+            wide_token_indices: false,
+            precise_splits: false,
+            split_branch_groups: false,
+            ungrouped_fallback: false,
         })
     }
 
+    /// Sets how the router wraps and unwraps the native token, instead of the variant configured
+    /// for the chain (see `WrapVariant::for_chain`).
+    pub fn with_wrap_variant(mut self, wrap_variant: WrapVariant) -> Self {
+        self.wrap_variant = wrap_variant;
+        self
+    }
+
+    /// Sets how the swap encoders decide whether the router approves the protocols, instead of
+    /// looking its allowances up (see `ApprovalPolicy`).
+    pub fn with_approval_policy(mut self, approval_policy: ApprovalPolicy) -> Self {
+        self.approval_policy = approval_policy;
+        self
+    }
+
+    /// Takes the swap encoders from the given shared registry. Its current registry is used at the
+    /// start of every encoding, so that reloaded executors are picked up without rebuilding the
+    /// strategy.
+    pub fn with_shared_registry(mut self, shared_registry: SharedSwapEncoderRegistry) -> Self {
+        self.swap_encoder_registry = shared_registry.current();
+        self.shared_registry = Some(shared_registry);
+        self
+    }
+
+    /// Returns the strategy with the current registry of its shared registry.
+    fn current(&self) -> Cow<'_, Self> {
+        with_current_registry(
+            self,
+            &self.swap_encoder_registry,
+            self.shared_registry.as_ref(),
+            |strategy, registry| strategy.swap_encoder_registry = registry,
+        )
+    }
+
+    /// Normalizes the split percentages of the solutions within the given epsilon before
+    /// validating them (see `SplitSwapValidator::normalize_split_percentages`): the splits summing
+    /// to 100% are rescaled and the sub-dust splits folded into the largest swap of their token.
+    pub fn with_split_normalization(mut self, epsilon: f64) -> Self {
+        self.split_normalization = Some(epsilon);
+        self
+    }
+
+    /// Encodes the executors of the swaps in a deduplicated table placed before the swaps, with
+    /// every swap referring to its executor by a 1-byte index. The solutions are then encoded for
+    /// the `splitSwapIndexed` variants of the router functions.
+    ///
+    /// Has no effect if the encoder uses wide token indices or precise splits, as the router has no
+    /// indexed variant of the `splitSwapWide` and `splitSwapPrecise` functions.
+    pub fn with_executor_table(mut self) -> Self {
+        if !self.executor_table && !self.wide_token_indices && !self.precise_splits {
+            self.function_signature = indexed_function_signature(&self.function_signature);
+            self.executor_table = true;
+        }
+        self
+    }
+
+    /// Encodes the solutions for `splitSwapPermit2Batch`, which takes a Permit2 batch permit
+    /// instead of a single permit, so that several solutions (or given tokens) of the same sender
+    /// are authorized with one signature.
+    ///
+    /// Returns an error if the encoder doesn't use permit2 transfers or uses an executor table,
+    /// wide token indices or precise splits (the router has no such variant of
+    /// `splitSwapPermit2Batch`).
+    pub fn with_permit_batch(mut self) -> Result<Self, EncodingError> {
+        if self.executor_table ||
+            !self
+                .function_signature
+                .starts_with("splitSwapPermit2(")
+        {
+            return Err(EncodingError::FatalError(
+                "Batch permits are only supported with permit2 transfers and without an executor \
+                 table, wide token indices or precise splits"
+                    .to_string(),
+            ));
+        }
+        self.function_signature = "splitSwapPermit2Batch(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48)[],address,uint256),bytes,bytes)".to_string();
+        Ok(self)
+    }
+
+    /// Encodes the token indices of the swaps on 2 bytes instead of 1, raising the number of
+    /// distinct tokens a solution can have from 256 to 65536. This is meant for the large split
+    /// graphs of graph solvers. The solutions are then encoded for the `splitSwapWide` variants
+    /// of the router functions, which take no integrator fee.
+    ///
+    /// Returns an error if the encoder uses an executor table, batch permits or precise splits (the
+    /// router has no wide variant of these functions).
+    pub fn with_wide_token_indices(mut self) -> Result<Self, EncodingError> {
+        if self.wide_token_indices {
+            return Ok(self);
+        }
+        if !self
+            .function_signature
+            .starts_with("splitSwap(") &&
+            !self
+                .function_signature
+                .starts_with("splitSwapPermit2(")
+        {
+            return Err(EncodingError::FatalError(
+                "Wide token indices are not supported with an executor table, batch permits or \
+                 precise splits"
+                    .to_string(),
+            ));
+        }
+        self.function_signature = self
+            .function_signature
+            .replacen('(', "Wide(", 1);
+        self.wide_token_indices = true;
+        Ok(self)
+    }
+
+    /// Encodes the splits of the swaps on 4 bytes with 1e9 corresponding to 100%, instead of 3
+    /// bytes with 2^24 - 1. This lowers the rounding of every split from ~6e-8 to 1e-9 of the
+    /// amount, which matters for large trades split over many pools. The solutions are then
+    /// encoded for the `splitSwapPrecise` variants of the router functions, which take no
+    /// integrator fee.
+    ///
+    /// Returns an error if the encoder uses an executor table, batch permits or wide token indices
+    /// (the router has no precise variant of these functions).
+    pub fn with_precise_splits(mut self) -> Result<Self, EncodingError> {
+        if self.precise_splits {
+            return Ok(self);
+        }
+        if !self
+            .function_signature
+            .starts_with("splitSwap(") &&
+            !self
+                .function_signature
+                .starts_with("splitSwapPermit2(")
+        {
+            return Err(EncodingError::FatalError(
+                "Precise splits are not supported with an executor table, batch permits or wide \
+                 token indices"
+                    .to_string(),
+            ));
+        }
+        self.function_signature = self
+            .function_signature
+            .replacen('(', "Precise(", 1);
+        self.precise_splits = true;
+        Ok(self)
+    }
+
+    /// Groups consecutive branches splitting a token into the same output token through a protocol
+    /// with the `split_groupable` capability (like Uniswap V4) into one swap execution, with the
+    /// split of every branch encoded in the protocol data (see `group_split_branches`). This
+    /// saves the settle and take calls of all but one of the branches.
+    pub fn with_split_branch_groups(mut self) -> Self {
+        self.split_branch_groups = true;
+        self
+    }
+
+    /// Encodes the swaps of a group failing to encode as one swap execution (e.g. a Uniswap V4 swap
+    /// missing an attribute only needed when grouped) as individual swaps, with explicit transfers,
+    /// instead of failing the solution. The degraded groups are reported in the encoded solution
+    /// (see `EncodedSolution::degraded_groups`), so that the extra gas can be accounted for.
+    pub fn with_ungrouped_fallback(mut self) -> Self {
+        self.ungrouped_fallback = true;
+        self
+    }
+
     /// Encodes information necessary for performing a single hop against a given executor for
     /// a protocol as part of a split swap solution.
+    ///
+    /// The token indices take 2 bytes with wide token indices and 1 byte otherwise. They must have
+    /// been checked to fit (see `validate_token_count`). The split is given encoded (see
+    /// `encode_splits`).
     fn encode_swap_header(
         &self,
-        token_in: U8,
-        token_out: U8,
-        split: U24,
+        token_in: usize,
+        token_out: usize,
+        split: Vec<u8>,
         executor_address: Bytes,
         protocol_data: Vec<u8>,
     ) -> Vec<u8> {
         let mut encoded = Vec::new();
-        encoded.push(token_in.to_be_bytes_vec()[0]);
-        encoded.push(token_out.to_be_bytes_vec()[0]);
-        encoded.extend_from_slice(&split.to_be_bytes_vec());
+        if self.wide_token_indices {
+            encoded.extend_from_slice(&(token_in as u16).to_be_bytes());
+            encoded.extend_from_slice(&(token_out as u16).to_be_bytes());
+        } else {
+            encoded.push(token_in as u8);
+            encoded.push(token_out as u8);
+        }
+        encoded.extend(split);
         encoded.extend(executor_address.to_vec());
         encoded.extend(protocol_data);
         encoded
     }
-}
 
-impl StrategyEncoder for SplitSwapStrategyEncoder {
-    fn encode_strategy(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
-        self.split_swap_validator
+    /// Encodes the splits of the swaps on 4 bytes with precise splits and 3 bytes otherwise.
+    fn encode_splits(&self, splits: &[(&Bytes, f64)]) -> Vec<Vec<u8>> {
+        if self.precise_splits {
+            splits_to_uint32(splits)
+                .into_iter()
+                .map(|split| split.to_be_bytes().to_vec())
+                .collect()
+        } else {
+            splits_to_uint24(splits)
+                .into_iter()
+                .map(|split| split.to_be_bytes_vec())
+                .collect()
+        }
+    }
+
+    /// Returns the solution with its split percentages normalized, if split normalization is
+    /// enabled.
+    fn normalize_splits<'a>(&self, solution: &'a Solution) -> Cow<'a, Solution> {
+        match self.split_normalization {
+            Some(epsilon) => Cow::Owned(Solution {
+                swaps: self
+                    .split_swap_validator
+                    .normalize_split_percentages(&solution.swaps, epsilon),
+                ..solution.clone()
+            }),
+            None => Cow::Borrowed(solution),
+        }
+    }
+
+    /// Validates the solution and plans the encoding of its swap groups. The swaps of the degraded
+    /// groups are planned individually (see `with_ungrouped_fallback`).
+    ///
+    /// Returns the planned groups and the tokens of the solution, in the order expected by the
+    /// router.
+    fn plan(
+        &self,
+        solution: &Solution,
+        degraded: &[SwapGroup],
+    ) -> Result<(Vec<PlannedSwapGroup<'_>>, Vec<Bytes>), EncodingError> {
+        self.split_swap_validator
             .validate_split_percentages(&solution.swaps)?;
+        self.split_swap_validator
+            .validate_fee_on_transfer(&solution.swaps)?;
         self.split_swap_validator
             .validate_swap_path(
                 &solution.swaps,
@@ -436,31 +1415,31 @@ impl StrategyEncoder for SplitSwapStrategyEncoder {
 
         // The tokens array is composed of the given token, the checked token and all the
         // intermediary tokens in between. The contract expects the tokens to be in this order.
-        let solution_tokens: HashSet<&Bytes> = vec![&solution.given_token, &solution.checked_token]
-            .into_iter()
-            .collect();
-
-        let grouped_swaps = group_swaps(&solution.swaps);
+        // Ordered sets keep the token indices (and so the encoded swaps) stable across runs.
+        let solution_tokens: BTreeSet<&Bytes> =
+            vec![&solution.given_token, &solution.checked_token]
+                .into_iter()
+                .collect();
+
+        let mut grouped_swaps = group_swaps(&solution.swaps, &self.swap_encoder_registry);
+        if self.split_branch_groups {
+            grouped_swaps =
+                group_split_branches(&solution.swaps, grouped_swaps, &self.swap_encoder_registry);
+        }
+        let grouped_swaps = ungroup_swaps(&solution.swaps, grouped_swaps, degraded);
 
-        let intermediary_tokens: HashSet<&Bytes> = grouped_swaps
+        let intermediary_tokens: BTreeSet<&Bytes> = grouped_swaps
             .iter()
             .flat_map(|grouped_swap| vec![&grouped_swap.token_in, &grouped_swap.token_out])
             .collect();
-        let mut intermediary_tokens: Vec<&Bytes> = intermediary_tokens
+        let intermediary_tokens: Vec<&Bytes> = intermediary_tokens
             .difference(&solution_tokens)
             .cloned()
             .collect();
-        // this is only to make the test deterministic (same index for the same token for different
-        // runs)
-        intermediary_tokens.sort();
-
-        let (mut unwrap, mut wrap) = (false, false);
-        if let Some(action) = &solution.native_action {
-            match *action {
-                NativeAction::Wrap => wrap = true,
-                NativeAction::Unwrap => unwrap = true,
-            }
-        }
+
+        let (wrap, unwrap) = self
+            .wrap_variant
+            .native_action_flags(solution.native_action.as_ref())?;
 
         let mut tokens = Vec::with_capacity(2 + intermediary_tokens.len());
         if wrap {
@@ -475,9 +1454,26 @@ impl StrategyEncoder for SplitSwapStrategyEncoder {
         } else {
             tokens.push(&solution.checked_token);
         }
+        let tokens: Vec<Bytes> = tokens.into_iter().cloned().collect();
+        if self.wide_token_indices {
+            validate_token_count(tokens.len(), 2, "")?;
+        } else {
+            validate_token_count(
+                tokens.len(),
+                1,
+                ". Enable wide token indices to encode larger split graphs",
+            )?;
+        }
 
-        let mut swaps = vec![];
-        for grouped_swap in grouped_swaps.iter() {
+        // Output fees are taken by the router, and receivers called back by it, so it must receive
+        // the amount out
+        let output_to_router = solution
+            .fee
+            .as_ref()
+            .is_some_and(|fee| !fee.on_input) ||
+            solution.receiver_callback.is_some();
+        let mut groups = Vec::with_capacity(grouped_swaps.len());
+        for grouped_swap in grouped_swaps.into_iter() {
             let protocol = &grouped_swap.protocol_system;
             let swap_encoder = self
                 .get_swap_encoder(protocol)
@@ -487,14 +1483,25 @@ impl StrategyEncoder for SplitSwapStrategyEncoder {
                     ))
                 })?;
 
-            let swap_receiver = if !unwrap && grouped_swap.token_out == solution.checked_token {
-                solution.receiver.clone()
-            } else {
-                self.router_address.clone()
-            };
+            let swap_receiver =
+                if !unwrap && !output_to_router && grouped_swap.token_out == solution.checked_token
+                {
+                    grouped_swap
+                        .receiver_override()
+                        .unwrap_or(&solution.receiver)
+                        .clone()
+                } else {
+                    self.router_address.clone()
+                };
             let transfer = self
                 .transfer_optimization
-                .get_transfers(grouped_swap, &solution.given_token, wrap, false);
+                .get_transfers(
+                    &grouped_swap,
+                    &solution.given_token,
+                    wrap,
+                    false,
+                    &self.swap_encoder_registry,
+                );
             let encoding_context = EncodingContext {
                 receiver: swap_receiver,
                 exact_out: solution.exact_out,
@@ -503,53 +1510,505 @@ impl StrategyEncoder for SplitSwapStrategyEncoder {
                 group_token_out: grouped_swap.token_out.clone(),
                 transfer_type: transfer,
                 historical_trade: self.historical_trade,
+                execution_deadline: solution.execution_deadline,
+                approval_policy: self.approval_policy,
             };
 
-            let mut grouped_protocol_data: Vec<Vec<u8>> = vec![];
-            let mut initial_protocol_data: Vec<u8> = vec![];
-            for swap in grouped_swap.swaps.iter() {
-                let protocol_data = swap_encoder.encode_swap(swap, &encoding_context)?;
-                if encoding_context.group_token_in == *swap.token_in() {
-                    initial_protocol_data = protocol_data;
-                } else {
-                    grouped_protocol_data.push(protocol_data);
-                }
-            }
-
-            if !grouped_protocol_data.is_empty() {
-                if NON_PLE_ENCODED_PROTOCOLS.contains(grouped_swap.protocol_system.as_str()) {
-                    for protocol_data in grouped_protocol_data {
-                        initial_protocol_data.extend(protocol_data);
-                    }
-                } else {
-                    initial_protocol_data.extend(ple_encode(grouped_protocol_data));
-                }
-            }
+            groups.push(PlannedSwapGroup {
+                grouped_swap,
+                swap_encoder: swap_encoder.as_ref(),
+                encoding_context,
+                in_between_optimization: false,
+            });
+        }
+        self.transfer_optimization
+            .validate_transfers(&group_transfers(&groups), &solution.given_token, false)?;
+        self.transfer_optimization
+            .validate_native_flow(&group_native_flows(&groups), solution, wrap, unwrap)?;
+        Ok((groups, tokens))
+    }
 
+    /// Assembles the encoded solution from the protocol data of its planned swap groups.
+    fn assemble(
+        &self,
+        solution: &Solution,
+        groups: &[PlannedSwapGroup],
+        tokens: &[Bytes],
+        protocol_data: Vec<Vec<u8>>,
+        degraded_groups: Vec<DegradedSwapGroup>,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let function_signature = callback_function_signature(
+            fee_function_signature(&self.function_signature, solution.fee.as_ref())?,
+            solution.receiver_callback.as_ref(),
+        )?;
+        let tokens: Vec<&Bytes> = tokens.iter().collect();
+        let splits: Vec<(&Bytes, f64)> = groups
+            .iter()
+            .map(|group| (&group.grouped_swap.token_in, group.grouped_swap.split))
+            .collect();
+        let mut executor_table = self
+            .executor_table
+            .then(ExecutorTable::default);
+        let mut swaps = vec![];
+        let mut transfer_types = vec![];
+        for ((group, protocol_data), split) in groups
+            .iter()
+            .zip(protocol_data)
+            .zip(self.encode_splits(&splits))
+        {
+            let executor_address = group.executor_address()?;
+            let executor = match executor_table.as_mut() {
+                Some(table) => Bytes::from(vec![table.index(&executor_address)?]),
+                None => executor_address,
+            };
             let swap_data = self.encode_swap_header(
-                get_token_position(&tokens, &grouped_swap.token_in)?,
-                get_token_position(&tokens, &grouped_swap.token_out)?,
-                percentage_to_uint24(grouped_swap.split),
-                swap_encoder.executor_address().clone(),
-                initial_protocol_data,
+                get_token_position(&tokens, &group.grouped_swap.token_in)?,
+                get_token_position(&tokens, &group.grouped_swap.token_out)?,
+                split,
+                executor,
+                protocol_data,
             );
             swaps.push(swap_data);
+            transfer_types.push(group.encoding_context.transfer_type);
         }
 
-        let encoded_swaps = ple_encode(swaps);
+        let mut encoded_swaps = ple_encode(swaps)?;
+        if let Some(table) = executor_table {
+            encoded_swaps = table.encode(encoded_swaps);
+        }
         let tokens_len = if solution.given_token == solution.checked_token {
             tokens.len() - 1
         } else {
             tokens.len()
         };
+        let summary = summarize_encoded_swaps(
+            &function_signature,
+            &encoded_swaps,
+            solution.swaps.len(),
+            &transfer_types,
+        );
+        let gas_estimate = estimate_gas(&self.swap_encoder_registry, groups);
         Ok(EncodedSolution {
             interacting_with: self.router_address.clone(),
-            function_signature: self.function_signature.clone(),
+            function_signature,
             swaps: encoded_swaps,
             permit: None,
+            permit_batch: None,
             n_tokens: tokens_len,
+            native_action: solution.native_action.clone(),
+            state_versions: solution.state_versions(),
+            callback_allowlist: None,
+            summary,
+            gas_estimate,
+            debug_info: Some(debug_info(groups)?),
+            degraded_groups,
+        })
+    }
+}
+
+#[async_trait]
+impl StrategyEncoder for SplitSwapStrategyEncoder {
+    fn name(&self) -> &'static str {
+        "split_swap"
+    }
+
+    fn encode_strategy(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
+        let this = self.current();
+        let solution = &self.normalize_splits(solution);
+        let mut fallback = UngroupedFallback::default();
+        loop {
+            let (groups, tokens) = this.plan(solution, &fallback.groups)?;
+            match encode_planned_groups(&groups) {
+                Ok(protocol_data) => {
+                    return this.assemble(
+                        solution,
+                        &groups,
+                        &tokens,
+                        protocol_data,
+                        fallback.reports,
+                    )
+                }
+                Err((i, error)) if this.ungrouped_fallback => {
+                    fallback.degrade(&groups[i], error)?
+                }
+                Err((_, error)) => return Err(error),
+            }
+        }
+    }
+
+    async fn encode_strategy_async(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let this = self.current();
+        let solution = &self.normalize_splits(solution);
+        let mut fallback = UngroupedFallback::default();
+        loop {
+            let (groups, tokens) = this.plan(solution, &fallback.groups)?;
+            match encode_planned_groups_async(&groups).await {
+                Ok(protocol_data) => {
+                    return this.assemble(
+                        solution,
+                        &groups,
+                        &tokens,
+                        protocol_data,
+                        fallback.reports,
+                    )
+                }
+                Err((i, error)) if this.ungrouped_fallback => {
+                    fallback.degrade(&groups[i], error)?
+                }
+                Err((_, error)) => return Err(error),
+            }
+        }
+    }
+
+    fn get_swap_encoder(&self, protocol_system: &str) -> Option<&Box<dyn SwapEncoder>> {
+        self.swap_encoder_registry
+            .get_encoder(protocol_system)
+    }
+
+    fn clone_box(&self) -> Box<dyn StrategyEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+/// Represents the encoder for a swap strategy which supports split swaps selling several tokens:
+/// the given token and the additional given tokens of the solution, each with its own amount, for
+/// a single checked token (e.g. to consolidate a portfolio into one token).
+///
+/// The router transfers all the given tokens from the sender to itself before swapping (or expects
+/// them in the router for the `None` user transfer type), so every swap transfers its token in from
+/// the router. The swaps are encoded like split swaps, with the given tokens at the first token
+/// indexes, in the order of `Solution::given_tokens`.
+///
+/// # Fields
+/// * `swap_encoder_registry`: SwapEncoderRegistry, containing all possible swap encoders
+/// * `function_signature`: Optional signature of the swap function in the router contract. Permit2
+///   transfers are not supported, as a permit only authorizes a single token.
+/// * `native_address`: Address of the chain's native token
+/// * `wrapped_address`: Address of the chain's wrapped token
+/// * `split_swap_validator`: SplitSwapValidator, responsible for checking validity of the solutions
+/// * `router_address`: Address of the router to be used to execute swaps
+/// * `transfer_optimization`: TransferOptimization, responsible for optimizing the token transfers
+/// * `historical_trade`: Whether the swap is to be done in the current block or in an historical
+///   one. This is relevant for checking token approvals in some protocols (like Balancer v2).
+/// * `wrap_variant`: How the router wraps and unwraps the native token of the chain (see
+///   `with_wrap_variant`)
+/// * `approval_policy`: How the swap encoders decide whether the router approves the protocols (see
+///   `with_approval_policy`)
+/// * `shared_registry`: Optional shared registry the swap encoders are taken from (see
+///   `with_shared_registry`)
+#[derive(Clone)]
+pub struct MultiInputSplitSwapStrategyEncoder {
+    swap_encoder_registry: SwapEncoderRegistry,
+    function_signature: Option<String>,
+    native_address: Bytes,
+    wrapped_address: Bytes,
+    split_swap_validator: SplitSwapValidator,
+    router_address: Bytes,
+    transfer_optimization: TransferOptimization,
+    historical_trade: bool,
+    wrap_variant: WrapVariant,
+    approval_policy: ApprovalPolicy,
+    shared_registry: Option<SharedSwapEncoderRegistry>,
+}
+
+impl MultiInputSplitSwapStrategyEncoder {
+    pub fn new(
+        chain: Chain,
+        swap_encoder_registry: SwapEncoderRegistry,
+        user_transfer_type: UserTransferType,
+        router_address: Bytes,
+        historical_trade: bool,
+    ) -> Result<Self, EncodingError> {
+        let function_signature = (user_transfer_type != UserTransferType::TransferFromPermit2)
+            .then(|| {
+                "multiInputSplitSwap(uint256[],address[],address,uint256,bool,uint256,address,bool,bytes)"
+                    .to_string()
+            });
+        let ChainTokens { native: native_token_address, wrapped: wrapped_token_address } =
+            ChainTokens::for_chain(chain)?;
+        Ok(Self {
+            function_signature,
+            swap_encoder_registry,
+            native_address: native_token_address.clone(),
+            wrapped_address: wrapped_token_address.clone(),
+            split_swap_validator: SplitSwapValidator,
+            router_address: router_address.clone(),
+            // The given tokens are already in the router when the swaps are executed
+            transfer_optimization: TransferOptimization::new(
+                native_token_address,
+                wrapped_token_address,
+                UserTransferType::None,
+                router_address,
+            ),
+            historical_trade,
+            wrap_variant: WrapVariant::for_chain(chain)?,
+            approval_policy: ApprovalPolicy::default(),
+            shared_registry: None,
+        })
+    }
+
+    /// Sets how the router wraps and unwraps the native token, instead of the variant configured
+    /// for the chain (see `WrapVariant::for_chain`).
+    pub fn with_wrap_variant(mut self, wrap_variant: WrapVariant) -> Self {
+        self.wrap_variant = wrap_variant;
+        self
+    }
+
+    /// Sets how the swap encoders decide whether the router approves the protocols, instead of
+    /// looking its allowances up (see `ApprovalPolicy`).
+    pub fn with_approval_policy(mut self, approval_policy: ApprovalPolicy) -> Self {
+        self.approval_policy = approval_policy;
+        self
+    }
+
+    /// Takes the swap encoders from the given shared registry. Its current registry is used at the
+    /// start of every encoding, so that reloaded executors are picked up without rebuilding the
+    /// strategy.
+    pub fn with_shared_registry(mut self, shared_registry: SharedSwapEncoderRegistry) -> Self {
+        self.swap_encoder_registry = shared_registry.current();
+        self.shared_registry = Some(shared_registry);
+        self
+    }
+
+    /// Returns the strategy with the current registry of its shared registry.
+    fn current(&self) -> Cow<'_, Self> {
+        with_current_registry(
+            self,
+            &self.swap_encoder_registry,
+            self.shared_registry.as_ref(),
+            |strategy, registry| strategy.swap_encoder_registry = registry,
+        )
+    }
+
+    /// Encodes information necessary for performing a single hop against a given executor for
+    /// a protocol as part of a split swap solution.
+    fn encode_swap_header(
+        &self,
+        token_in: usize,
+        token_out: usize,
+        split: U24,
+        executor_address: Bytes,
+        protocol_data: Vec<u8>,
+    ) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encoded.push(token_in as u8);
+        encoded.push(token_out as u8);
+        encoded.extend_from_slice(&split.to_be_bytes_vec());
+        encoded.extend(executor_address.to_vec());
+        encoded.extend(protocol_data);
+        encoded
+    }
+
+    /// Validates the solution and plans the encoding of its swap groups.
+    ///
+    /// Returns the planned groups, the tokens of the solution in the order expected by the router
+    /// and the signature of the router function to call.
+    fn plan(
+        &self,
+        solution: &Solution,
+    ) -> Result<(Vec<PlannedSwapGroup<'_>>, Vec<Bytes>, String), EncodingError> {
+        let function_signature = self
+            .function_signature
+            .clone()
+            .ok_or_else(|| {
+                EncodingError::InvalidInput(
+                    "Multi input solutions are only supported with the TransferFrom or None user \
+                     transfer type"
+                        .to_string(),
+                )
+            })?;
+        let given_tokens: Vec<&Bytes> = solution
+            .given_tokens()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect();
+        self.split_swap_validator
+            .validate_split_percentages(&solution.swaps)?;
+        self.split_swap_validator
+            .validate_fee_on_transfer(&solution.swaps)?;
+        self.split_swap_validator
+            .validate_multi_input_swap_path(
+                &solution.swaps,
+                &given_tokens,
+                &solution.checked_token,
+                &solution.native_action,
+                &self.native_address,
+                &self.wrapped_address,
+            )?;
+
+        let (_, unwrap) = self
+            .wrap_variant
+            .native_action_flags(solution.native_action.as_ref())?;
+        let grouped_swaps = group_swaps(&solution.swaps, &self.swap_encoder_registry);
+
+        // The tokens array is composed of the given tokens, all the intermediary tokens and the
+        // checked token. Ordered sets keep the token indices (and so the encoded swaps) stable
+        // across runs.
+        let solution_tokens: BTreeSet<&Bytes> = given_tokens
+            .iter()
+            .copied()
+            .chain([&solution.checked_token])
+            .collect();
+        let intermediary_tokens: BTreeSet<&Bytes> = grouped_swaps
+            .iter()
+            .flat_map(|grouped_swap| vec![&grouped_swap.token_in, &grouped_swap.token_out])
+            .collect();
+        let mut tokens: Vec<Bytes> = given_tokens
+            .iter()
+            .map(|token| (*token).clone())
+            .collect();
+        tokens.extend(
+            intermediary_tokens
+                .difference(&solution_tokens)
+                .map(|token| (*token).clone()),
+        );
+        if unwrap {
+            tokens.push(self.wrapped_address.clone());
+        } else {
+            tokens.push(solution.checked_token.clone());
+        }
+        validate_token_count(tokens.len(), 1, "")?;
+
+        let mut groups = Vec::with_capacity(grouped_swaps.len());
+        for grouped_swap in grouped_swaps.into_iter() {
+            let protocol = &grouped_swap.protocol_system;
+            let swap_encoder = self
+                .get_swap_encoder(protocol)
+                .ok_or_else(|| {
+                    EncodingError::InvalidInput(format!(
+                        "Swap encoder not found for protocol: {protocol}",
+                    ))
+                })?;
+
+            let swap_receiver = if !unwrap && grouped_swap.token_out == solution.checked_token {
+                grouped_swap
+                    .receiver_override()
+                    .unwrap_or(&solution.receiver)
+                    .clone()
+            } else {
+                self.router_address.clone()
+            };
+            let transfer = self
+                .transfer_optimization
+                .get_transfers(
+                    &grouped_swap,
+                    &solution.given_token,
+                    false,
+                    false,
+                    &self.swap_encoder_registry,
+                );
+            let encoding_context = EncodingContext {
+                receiver: swap_receiver,
+                exact_out: false,
+                router_address: Some(self.router_address.clone()),
+                group_token_in: grouped_swap.token_in.clone(),
+                group_token_out: grouped_swap.token_out.clone(),
+                transfer_type: transfer,
+                historical_trade: self.historical_trade,
+                execution_deadline: solution.execution_deadline,
+                approval_policy: self.approval_policy,
+            };
+
+            groups.push(PlannedSwapGroup {
+                grouped_swap,
+                swap_encoder: swap_encoder.as_ref(),
+                encoding_context,
+                in_between_optimization: false,
+            });
+        }
+        Ok((groups, tokens, function_signature))
+    }
+
+    /// Assembles the encoded solution from the protocol data of its planned swap groups.
+    fn assemble(
+        &self,
+        solution: &Solution,
+        groups: &[PlannedSwapGroup],
+        tokens: &[Bytes],
+        function_signature: String,
+        protocol_data: Vec<Vec<u8>>,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let tokens: Vec<&Bytes> = tokens.iter().collect();
+        let splits: Vec<(&Bytes, f64)> = groups
+            .iter()
+            .map(|group| (&group.grouped_swap.token_in, group.grouped_swap.split))
+            .collect();
+        let mut swaps = vec![];
+        let mut transfer_types = vec![];
+        for ((group, protocol_data), split) in groups
+            .iter()
+            .zip(protocol_data)
+            .zip(splits_to_uint24(&splits))
+        {
+            let swap_data = self.encode_swap_header(
+                get_token_position(&tokens, &group.grouped_swap.token_in)?,
+                get_token_position(&tokens, &group.grouped_swap.token_out)?,
+                split,
+                group.executor_address()?,
+                protocol_data,
+            );
+            swaps.push(swap_data);
+            transfer_types.push(group.encoding_context.transfer_type);
+        }
+
+        let encoded_swaps = ple_encode(swaps)?;
+        let mut summary = summarize_encoded_swaps(
+            &function_signature,
+            &encoded_swaps,
+            solution.swaps.len(),
+            &transfer_types,
+        );
+        // The amounts and tokens arrays add a length word and a word per given token to the
+        // calldata, which are not part of the signature
+        let arrays_bytes = 2 * 32 * (1 + solution.given_tokens().len());
+        summary.calldata_bytes += arrays_bytes;
+        summary.calldata_gas += 16 * arrays_bytes as u64;
+        let gas_estimate = estimate_gas(&self.swap_encoder_registry, groups);
+        Ok(EncodedSolution {
+            interacting_with: self.router_address.clone(),
+            function_signature,
+            swaps: encoded_swaps,
+            permit: None,
+            permit_batch: None,
+            n_tokens: tokens.len(),
+            native_action: solution.native_action.clone(),
+            state_versions: solution.state_versions(),
+            callback_allowlist: None,
+            summary,
+            gas_estimate,
+            debug_info: Some(debug_info(groups)?),
+            degraded_groups: vec![],
         })
     }
+}
+
+#[async_trait]
+impl StrategyEncoder for MultiInputSplitSwapStrategyEncoder {
+    fn name(&self) -> &'static str {
+        "multi_input_split_swap"
+    }
+
+    fn encode_strategy(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
+        let this = self.current();
+        let (groups, tokens, function_signature) = this.plan(solution)?;
+        let protocol_data = encode_planned_groups(&groups).map_err(|(_, error)| error)?;
+        this.assemble(solution, &groups, &tokens, function_signature, protocol_data)
+    }
+
+    async fn encode_strategy_async(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let this = self.current();
+        let (groups, tokens, function_signature) = this.plan(solution)?;
+        let protocol_data = encode_planned_groups_async(&groups)
+            .await
+            .map_err(|(_, error)| error)?;
+        this.assemble(solution, &groups, &tokens, function_signature, protocol_data)
+    }
 
     fn get_swap_encoder(&self, protocol_system: &str) -> Option<&Box<dyn SwapEncoder>> {
         self.swap_encoder_registry
@@ -722,36 +2181,142 @@ mod tests {
             );
             assert_eq!(encoded_solution.interacting_with, router_address());
         }
-    }
-
-    mod sequential {
-        use super::*;
-        use crate::encoding::models::Swap;
 
         #[test]
-        fn test_sequential_swap_strategy_encoder_no_permit2() {
-            // Performs a sequential swap from WETH to USDC though WBTC using USV2 pools
-            //
-            //   WETH ───(USV2)──> WBTC ───(USV2)──> USDC
-
-            let weth = weth();
-            let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
-            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
-
-            let swap_weth_wbtc = Swap::new(
+        fn test_single_swap_strategy_encoder_exact_out() {
+            // Buys exactly 2000 DAI with WETH on a USV2 pool, paying at most 1 WETH
+            let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+            let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+            let swap = Swap::new(
                 ProtocolComponent {
-                    id: "0xBb2b8038a1640196FbE3e38816F3e67Cba72D940".to_string(),
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
                     protocol_system: "uniswap_v2".to_string(),
                     ..Default::default()
                 },
                 weth.clone(),
-                wbtc.clone(),
+                dai.clone(),
             );
-            let swap_wbtc_usdc = Swap::new(
-                ProtocolComponent {
-                    id: "0x004375Dff511095CC5A197A54140a24eFEF3A416".to_string(),
-                    protocol_system: "uniswap_v2".to_string(),
-                    ..Default::default()
+            let encoder = SingleSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap();
+            let solution = Solution {
+                exact_out: true,
+                given_token: dai,
+                given_amount: BigUint::from_str("2000_000000000000000000").unwrap(),
+                checked_token: weth,
+                checked_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap],
+                ..Default::default()
+            };
+
+            let encoded_solution = encoder
+                .encode_strategy(&solution)
+                .unwrap();
+
+            let expected_swap = String::from(concat!(
+                // Swap data
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token in
+                "a478c2975ab1ea89e8196811f51a7b7ade33eb11", // component id
+                "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2", // receiver
+                "00",                                       // zero2one
+                "00",                                       // transfer type TransferFrom
+                "01",                                       // exact out
+            ));
+            assert_eq!(encode(&encoded_solution.swaps), expected_swap);
+            assert_eq!(
+                encoded_solution.function_signature,
+                "exactOutSingleSwap(uint256,address,address,uint256,bool,address,bool,bytes)"
+            );
+        }
+
+        #[test]
+        fn test_single_swap_strategy_encoder_exact_out_unsupported() {
+            let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+            let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+            let swap = |protocol_system: &str| {
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: protocol_system.to_string(),
+                        ..Default::default()
+                    },
+                    weth.clone(),
+                    dai.clone(),
+                )
+            };
+            let solution = |protocol_system: &str| Solution {
+                exact_out: true,
+                given_token: dai.clone(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: weth.clone(),
+                checked_amount: BigUint::from(1000u32),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap(protocol_system)],
+                ..Default::default()
+            };
+            let encoder = |user_transfer_type| {
+                SingleSwapStrategyEncoder::new(
+                    eth_chain(),
+                    get_swap_encoder_registry(),
+                    user_transfer_type,
+                    router_address(),
+                    false,
+                )
+                .unwrap()
+            };
+
+            // Permit2 has no exact out entrypoint
+            assert!(matches!(
+                encoder(UserTransferType::TransferFromPermit2)
+                    .encode_strategy(&solution("uniswap_v2")),
+                Err(EncodingError::InvalidInput(_))
+            ));
+            // Neither do executors of other protocols
+            assert!(matches!(
+                encoder(UserTransferType::TransferFrom).encode_strategy(&solution("ekubo_v2")),
+                Err(EncodingError::InvalidInput(_))
+            ));
+        }
+    }
+
+    mod sequential {
+        use super::*;
+        use crate::encoding::{
+            evm::utils::ple_decode, models::Swap, swap_encoder::EncoderCapabilities,
+        };
+
+        #[test]
+        fn test_sequential_swap_strategy_encoder_no_permit2() {
+            // Performs a sequential swap from WETH to USDC though WBTC using USV2 pools
+            //
+            //   WETH ───(USV2)──> WBTC ───(USV2)──> USDC
+
+            let weth = weth();
+            let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+
+            let swap_weth_wbtc = Swap::new(
+                ProtocolComponent {
+                    id: "0xBb2b8038a1640196FbE3e38816F3e67Cba72D940".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth.clone(),
+                wbtc.clone(),
+            );
+            let swap_wbtc_usdc = Swap::new(
+                ProtocolComponent {
+                    id: "0x004375Dff511095CC5A197A54140a24eFEF3A416".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
                 },
                 wbtc.clone(),
                 usdc.clone(),
@@ -809,17 +2374,710 @@ mod tests {
                     .to_string()
             );
             assert_eq!(encoded_solution.interacting_with, router_address());
+            assert_eq!(encoded_solution.gas_estimate, ROUTER_GAS_OVERHEAD + 2 * 65_000);
+
+            let summary = &encoded_solution.summary;
+            assert_eq!(summary.n_hops, 2);
+            assert_eq!(summary.n_groups, 2);
+            assert_eq!(summary.swaps_bytes, 168);
+            // Selector, 9 head words, swaps length word, then the swaps padded to 6 words
+            assert_eq!(summary.calldata_bytes, 4 + 9 * 32 + 32 + 6 * 32);
+            assert_eq!(
+                summary.transfer_types,
+                std::collections::BTreeMap::from([
+                    (crate::encoding::models::TransferType::TransferFrom, 1),
+                    (crate::encoding::models::TransferType::None, 1),
+                ])
+            );
+
+            let debug_info = encoded_solution.debug_info.unwrap();
+            let [first, second] = debug_info.swap_groups.as_slice() else {
+                panic!("Expected two swap groups");
+            };
+            assert_eq!(first.transfer_type, crate::encoding::models::TransferType::TransferFrom);
+            assert_eq!(
+                first.receiver,
+                Bytes::from_str("0x004375Dff511095CC5A197A54140a24eFEF3A416").unwrap()
+            );
+            assert!(!first.in_between_optimization);
+            assert_eq!(second.transfer_type, crate::encoding::models::TransferType::None);
+            assert_eq!(second.receiver, solution.receiver);
+            assert!(second.in_between_optimization);
         }
-    }
 
-    mod split {
-        use super::*;
-        use crate::encoding::models::Swap;
+        #[test]
+        fn test_sequential_swap_strategy_encoder_unsupported_wrap_variant() {
+            // Unwrapping is rejected if the router can't unwrap the wrapped native token
+            //
+            //   DAI ───(USV2)──> WETH ───(unwrap)──> ETH
+
+            let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+            let swap_dai_weth = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                dai.clone(),
+                weth(),
+            );
+            let encoder = SequentialSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap();
+            let solution = Solution {
+                exact_out: false,
+                given_token: dai,
+                given_amount: BigUint::from_str("3000_000000000000000000").unwrap(),
+                checked_token: eth_chain().native_token().address,
+                checked_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap_dai_weth],
+                native_action: Some(crate::encoding::models::NativeAction::Unwrap),
+                ..Default::default()
+            };
+
+            assert!(encoder
+                .encode_strategy(&solution)
+                .is_ok());
+            let result = encoder
+                .with_wrap_variant(WrapVariant::Unsupported)
+                .encode_strategy(&solution);
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        }
+
+        #[test]
+        fn test_sequential_swap_strategy_encoder_output_fee() {
+            // Performs a sequential swap from WETH to USDC though WBTC using USV2 pools, taking a
+            // fee on the USDC bought. The last swap sends the USDC to the router, which takes the
+            // fee before sending the rest to the receiver.
+            //
+            //   WETH ───(USV2)──> WBTC ───(USV2)──> USDC
+
+            let weth = weth();
+            let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+
+            let swap_weth_wbtc = Swap::new(
+                ProtocolComponent {
+                    id: "0xBb2b8038a1640196FbE3e38816F3e67Cba72D940".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth.clone(),
+                wbtc.clone(),
+            );
+            let swap_wbtc_usdc = Swap::new(
+                ProtocolComponent {
+                    id: "0x004375Dff511095CC5A197A54140a24eFEF3A416".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                wbtc.clone(),
+                usdc.clone(),
+            );
+            let encoder = SequentialSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap();
+            let solution = Solution {
+                given_token: weth,
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: usdc,
+                checked_amount: BigUint::from_str("26173932").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap_weth_wbtc, swap_wbtc_usdc],
+                fee: Some(Fee {
+                    recipient: Bytes::from_str("0x2222222222222222222222222222222222222222")
+                        .unwrap(),
+                    bps: 25,
+                    on_input: false,
+                }),
+                ..Default::default()
+            };
+
+            let encoded_solution = encoder
+                .encode_strategy(&solution)
+                .unwrap();
+
+            let hex_calldata = encode(&encoded_solution.swaps);
+            let expected = String::from(concat!(
+                // swap 1
+                "0052",                                     // swap length
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token in
+                "bb2b8038a1640196fbe3e38816f3e67cba72d940", // component id
+                "004375dff511095cc5a197a54140a24efef3a416", // receiver (next pool)
+                "00",                                       // zero to one
+                "00",                                       // transfer type TransferFrom
+                // swap 2
+                "0052",                                     // swap length
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address
+                "2260fac5e5542a773aa44fbcfedf7c193bc2c599", // token in
+                "004375dff511095cc5a197a54140a24efef3a416", // component id
+                "6bc529dc7b81a031828ddce2bc419d01ff268c66", // receiver (router)
+                "01",                                       // zero to one
+                "02",                                       // transfer type None
+            ));
+
+            assert_eq!(hex_calldata, expected);
+            assert_eq!(
+                encoded_solution.function_signature,
+                "sequentialSwapWithFee(uint256,address,address,uint256,bool,bool,address,bool,(address,uint16,bool),bytes)"
+                    .to_string()
+            );
+        }
+
+        #[test]
+        fn test_sequential_swap_strategy_encoder_fee_permit2() {
+            // The router only takes fees in the functions without permit2
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+            let encoder = SequentialSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFromPermit2,
+                router_address(),
+                false,
+            )
+            .unwrap();
+            let solution = Solution {
+                given_token: weth(),
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: usdc.clone(),
+                checked_amount: BigUint::from_str("26173932").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![Swap::new(
+                    ProtocolComponent {
+                        id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth(),
+                    usdc,
+                )],
+                fee: Some(Fee {
+                    recipient: Bytes::from_str("0x2222222222222222222222222222222222222222")
+                        .unwrap(),
+                    bps: 25,
+                    on_input: true,
+                }),
+                ..Default::default()
+            };
+
+            let result = encoder.encode_strategy(&solution);
+
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        }
+
+        #[test]
+        fn test_sequential_swap_strategy_encoder_receiver_callback() {
+            // The last swap sends the USDC to the router, which sends it to the receiver and calls
+            // it back
+            //
+            //   WETH ───(USV2)──> WBTC ───(USV2)──> USDC
+            let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+            let swap_weth_wbtc = Swap::new(
+                ProtocolComponent {
+                    id: "0xBb2b8038a1640196FbE3e38816F3e67Cba72D940".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                wbtc.clone(),
+            );
+            let swap_wbtc_usdc = Swap::new(
+                ProtocolComponent {
+                    id: "0x004375Dff511095CC5A197A54140a24eFEF3A416".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                wbtc,
+                usdc.clone(),
+            );
+            let encoder = SequentialSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap();
+            let solution = Solution {
+                given_token: weth(),
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: usdc,
+                checked_amount: BigUint::from_str("26173932").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0x3333333333333333333333333333333333333333").unwrap(),
+                swaps: vec![swap_weth_wbtc, swap_wbtc_usdc],
+                receiver_callback: Some(Bytes::from_str("0xbeef").unwrap()),
+                ..Default::default()
+            };
+
+            let encoded_solution = encoder
+                .encode_strategy(&solution)
+                .unwrap();
+
+            let last_swap = ple_decode(&encoded_solution.swaps)
+                .unwrap()
+                .pop()
+                .unwrap();
+            // The receiver of the last swap is the router
+            assert_eq!(encode(&last_swap[60..80]), "6bc529dc7b81a031828ddce2bc419d01ff268c66");
+            assert_eq!(
+                encoded_solution.function_signature,
+                "sequentialSwapWithCallback(uint256,address,address,uint256,bool,bool,address,bool,bytes,bytes)"
+            );
+
+            // Callbacks can't be combined with a fee
+            let solution = Solution {
+                fee: Some(Fee {
+                    recipient: Bytes::from_str("0x2222222222222222222222222222222222222222")
+                        .unwrap(),
+                    bps: 25,
+                    on_input: true,
+                }),
+                ..solution
+            };
+            let result = encoder.encode_strategy(&solution);
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        }
+
+        #[test]
+        fn test_sequential_swap_strategy_encoder_executor_table() {
+            // Performs a sequential swap from WETH to USDC though WBTC using USV2 pools, with the
+            // USV2 executor encoded once in the executor table
+            //
+            //   WETH ───(USV2)──> WBTC ───(USV2)──> USDC
+
+            let weth = weth();
+            let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+
+            let swap_weth_wbtc = Swap::new(
+                ProtocolComponent {
+                    id: "0xBb2b8038a1640196FbE3e38816F3e67Cba72D940".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth.clone(),
+                wbtc.clone(),
+            );
+            let swap_wbtc_usdc = Swap::new(
+                ProtocolComponent {
+                    id: "0x004375Dff511095CC5A197A54140a24eFEF3A416".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                wbtc.clone(),
+                usdc.clone(),
+            );
+            let swap_encoder_registry = get_swap_encoder_registry();
+            let encoder = SequentialSwapStrategyEncoder::new(
+                eth_chain(),
+                swap_encoder_registry,
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap()
+            .with_executor_table();
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth,
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: usdc,
+                checked_amount: BigUint::from_str("26173932").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap_weth_wbtc, swap_wbtc_usdc],
+                ..Default::default()
+            };
+
+            let encoded_solution = encoder
+                .encode_strategy(&solution)
+                .unwrap();
+
+            let hex_calldata = encode(&encoded_solution.swaps);
+
+            let expected = String::from(concat!(
+                // executor table
+                "01",                                       // number of executors
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor 0
+                // swap 1
+                "003f",                                     // swap length
+                "00",                                       // executor index
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token in
+                "bb2b8038a1640196fbe3e38816f3e67cba72d940", // component id
+                "004375dff511095cc5a197a54140a24efef3a416", // receiver (next pool)
+                "00",                                       // zero to one
+                "00",                                       // transfer type TransferFrom
+                // swap 2
+                "003f",                                     // swap length
+                "00",                                       // executor index
+                "2260fac5e5542a773aa44fbcfedf7c193bc2c599", // token in
+                "004375dff511095cc5a197a54140a24efef3a416", // component id
+                "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2", // receiver (final user)
+                "01",                                       // zero to one
+                "02",                                       // transfer type None
+            ));
+
+            assert_eq!(hex_calldata, expected);
+            assert_eq!(
+                encoded_solution.function_signature,
+                "sequentialSwapIndexed(uint256,address,address,uint256,bool,bool,address,bool,bytes)"
+                    .to_string()
+            );
+            // The table saves 19 bytes per swap, minus the 21 bytes of the table entry
+            assert_eq!(encoded_solution.summary.swaps_bytes, 168 - 2 * 19 + 1 + 20);
+        }
+
+        /// Swap encoder failing to encode the swaps of a group of several swaps.
+        #[derive(Clone)]
+        struct UngroupableSwapEncoder {
+            executor_address: Bytes,
+        }
+
+        impl SwapEncoder for UngroupableSwapEncoder {
+            fn new(
+                executor_address: Bytes,
+                _chain: Chain,
+                _config: Option<HashMap<String, String>>,
+            ) -> Result<Self, EncodingError> {
+                Ok(Self { executor_address })
+            }
+
+            fn encode_swap(
+                &self,
+                swap: &Swap,
+                encoding_context: &EncodingContext,
+            ) -> Result<Vec<u8>, EncodingError> {
+                if encoding_context.group_token_in != *swap.token_in() ||
+                    encoding_context.group_token_out != *swap.token_out()
+                {
+                    return Err(EncodingError::InvalidInput("Can't be grouped".to_string()));
+                }
+                let mut encoded = swap.token_out().to_vec();
+                encoded.push(encoding_context.transfer_type as u8);
+                Ok(encoded)
+            }
+
+            fn executor_address(&self) -> &Bytes {
+                &self.executor_address
+            }
+
+            fn capabilities(&self) -> EncoderCapabilities {
+                EncoderCapabilities { groupable: true, ..Default::default() }
+            }
+
+            fn clone_box(&self) -> Box<dyn SwapEncoder> {
+                Box::new(self.clone())
+            }
+        }
+
+        #[test]
+        fn test_sequential_swap_strategy_encoder_given_token_swapped_twice() {
+            // The given token is swapped again after a cycle, which would transfer it from the
+            // user a second time, above the given amount the router allows
+            //
+            //   WETH ───(USV2)──> WBTC ───(USV2)──> WETH ───(USV2)──> USDC
+
+            let weth = weth();
+            let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+            let usv2_swap = |id: &str, token_in: &Bytes, token_out: &Bytes| {
+                Swap::new(
+                    ProtocolComponent {
+                        id: id.to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    token_in.clone(),
+                    token_out.clone(),
+                )
+            };
+            let encoder = SequentialSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap();
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth.clone(),
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: usdc.clone(),
+                checked_amount: BigUint::from_str("26173932").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![
+                    usv2_swap("0xBb2b8038a1640196FbE3e38816F3e67Cba72D940", &weth, &wbtc),
+                    usv2_swap("0xBb2b8038a1640196FbE3e38816F3e67Cba72D940", &wbtc, &weth),
+                    usv2_swap("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc", &weth, &usdc),
+                ],
+                ..Default::default()
+            };
+
+            let result = encoder.encode_strategy(&solution);
+
+            assert!(matches!(
+                result,
+                Err(EncodingError::InvalidInput(msg)) if msg.contains("transferred from the user by 2 swaps")
+            ));
+        }
+
+        #[test]
+        fn test_sequential_swap_strategy_encoder_ungrouped_fallback() {
+            // The USV4 swaps can't be encoded as a group, so they are encoded individually
+            //
+            //   WETH ───(USV4)──> WBTC ───(USV4)──> USDC
+
+            let weth = weth();
+            let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+
+            let swap_weth_wbtc = Swap::new(
+                ProtocolComponent {
+                    id: "0x0101010101010101010101010101010101010101".to_string(),
+                    protocol_system: "uniswap_v4".to_string(),
+                    ..Default::default()
+                },
+                weth.clone(),
+                wbtc.clone(),
+            );
+            let swap_wbtc_usdc = Swap::new(
+                ProtocolComponent {
+                    id: "0x0202020202020202020202020202020202020202".to_string(),
+                    protocol_system: "uniswap_v4".to_string(),
+                    ..Default::default()
+                },
+                wbtc.clone(),
+                usdc.clone(),
+            );
+            let swap_encoder_registry = get_swap_encoder_registry().register_encoder(
+                "uniswap_v4",
+                Box::new(UngroupableSwapEncoder {
+                    executor_address: Bytes::from("0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"),
+                }),
+            );
+            let encoder = SequentialSwapStrategyEncoder::new(
+                eth_chain(),
+                swap_encoder_registry,
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap();
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth.clone(),
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: usdc.clone(),
+                checked_amount: BigUint::from_str("26173932").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap_weth_wbtc, swap_wbtc_usdc],
+                ..Default::default()
+            };
+
+            // Without the fallback, the group fails the solution
+            let result = encoder.encode_strategy(&solution);
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            let encoded_solution = encoder
+                .with_ungrouped_fallback()
+                .encode_strategy(&solution)
+                .unwrap();
+
+            assert_eq!(encoded_solution.summary.n_groups, 2);
+            let swaps = ple_decode(&encoded_solution.swaps).unwrap();
+            assert_eq!(swaps.len(), 2);
+            assert_eq!(swaps[0][20..40], wbtc.to_vec());
+            assert_eq!(swaps[1][20..40], usdc.to_vec());
+            assert_eq!(
+                encoded_solution.degraded_groups,
+                vec![DegradedSwapGroup {
+                    protocol_system: "uniswap_v4".to_string(),
+                    token_in: weth,
+                    token_out: usdc,
+                    n_swaps: 2,
+                    reason: "Invalid input: Can't be grouped".to_string(),
+                }]
+            );
+        }
+    }
+
+    mod split {
+        use super::*;
+        use crate::encoding::models::Swap;
+
+        #[test]
+        fn test_split_input_cyclic_swap() {
+            // This test has start and end tokens that are the same
+            // The flow is:
+            //            ┌─ (USV3, 60% split) ──> WETH ─┐
+            //            │                              │
+            // USDC ──────┤                              ├──(USV2)──> USDC
+            //            │                              │
+            //            └─ (USV3, 40% split) ──> WETH ─┘
+
+            let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+
+            // USDC -> WETH (Pool 1) - 60% of input
+            let swap_usdc_weth_pool1 = Swap::new(
+                ProtocolComponent {
+                    id: "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640".to_string(), /* USDC-WETH USV3
+                                                                                   * Pool 1 */
+                    protocol_system: "uniswap_v3".to_string(),
+                    static_attributes: {
+                        let mut attrs = HashMap::new();
+                        attrs.insert(
+                            "fee".to_string(),
+                            Bytes::from(BigInt::from(500).to_signed_bytes_be()),
+                        );
+                        attrs
+                    },
+                    ..Default::default()
+                },
+                usdc.clone(),
+                weth.clone(),
+            )
+            .split(0.6f64);
+
+            // USDC -> WETH (Pool 2) - 40% of input (remaining)
+            let swap_usdc_weth_pool2 = Swap::new(
+                ProtocolComponent {
+                    id: "0x8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8".to_string(), /* USDC-WETH USV3
+                                                                                   * Pool 2 */
+                    protocol_system: "uniswap_v3".to_string(),
+                    static_attributes: {
+                        let mut attrs = HashMap::new();
+                        attrs.insert(
+                            "fee".to_string(),
+                            Bytes::from(BigInt::from(3000).to_signed_bytes_be()),
+                        );
+                        attrs
+                    },
+                    ..Default::default()
+                },
+                usdc.clone(),
+                weth.clone(),
+            );
+
+            // WETH -> USDC (Pool 2)
+            let swap_weth_usdc_pool2 = Swap::new(
+                ProtocolComponent {
+                    id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(), /* USDC-WETH USV2
+                                                                                   * Pool 2 */
+                    protocol_system: "uniswap_v2".to_string(),
+                    static_attributes: {
+                        let mut attrs = HashMap::new();
+                        attrs.insert(
+                            "fee".to_string(),
+                            Bytes::from(BigInt::from(3000).to_signed_bytes_be()),
+                        );
+                        attrs
+                    },
+                    ..Default::default()
+                },
+                weth.clone(),
+                usdc.clone(),
+            );
+            let swap_encoder_registry = get_swap_encoder_registry();
+            let encoder = SplitSwapStrategyEncoder::new(
+                eth_chain(),
+                swap_encoder_registry,
+                UserTransferType::TransferFromPermit2,
+                Bytes::from("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66"),
+                false,
+            )
+            .unwrap();
+
+            let solution = Solution {
+                exact_out: false,
+                given_token: usdc.clone(),
+                given_amount: BigUint::from_str("100000000").unwrap(), // 100 USDC (6 decimals)
+                checked_token: usdc.clone(),
+                checked_amount: BigUint::from_str("99574171").unwrap(), /* Expected output
+                                                                         * from
+                                                                         * test */
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap_usdc_weth_pool1, swap_usdc_weth_pool2, swap_weth_usdc_pool2],
+                ..Default::default()
+            };
+
+            let encoded_solution = encoder
+                .encode_strategy(&solution)
+                .unwrap();
+
+            let hex_calldata = hex::encode(&encoded_solution.swaps);
+
+            let expected_swaps = [
+                "006e",                                     // ple encoded swaps
+                "00",                                       // token in index
+                "01",                                       // token out index
+                "999999",                                   // split
+                "2e234dae75c793f67a35089c9d99245e1c58470b", // executor address
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", // token in
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token out
+                "0001f4",                                   // pool fee
+                "6bc529dc7b81a031828ddce2bc419d01ff268c66", // receiver
+                "88e6a0c2ddd26feeb64f039a2c41296fcb3f5640", // component id
+                "01",                                       // zero2one
+                "00",                                       // transfer type TransferFrom
+                "006e",                                     // ple encoded swaps
+                "00",                                       // token in index
+                "01",                                       // token out index
+                "000000",                                   // split
+                "2e234dae75c793f67a35089c9d99245e1c58470b", // executor address
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", // token in
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token out
+                "000bb8",                                   // pool fee
+                "6bc529dc7b81a031828ddce2bc419d01ff268c66", // receiver
+                "8ad599c3a0ff1de082011efddc58f1908eb6e6d8", // component id
+                "01",                                       // zero2one
+                "00",                                       // transfer type TransferFrom
+                "0057",                                     // ple encoded swaps
+                "01",                                       // token in index
+                "00",                                       // token out index
+                "000000",                                   // split
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address,
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token in
+                "b4e16d0168e52d35cacd2c6185b44281ec28c9dc", // component id,
+                "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2", // receiver
+                "00",                                       // zero2one
+                "01",                                       // transfer type Transfer
+            ]
+            .join("");
+            assert_eq!(hex_calldata, expected_swaps);
+            assert_eq!(
+                encoded_solution.function_signature,
+                "splitSwapPermit2(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)"
+                    .to_string()
+            );
+            assert_eq!(encoded_solution.interacting_with, router_address());
+        }
 
         #[test]
-        fn test_split_input_cyclic_swap() {
-            // This test has start and end tokens that are the same
-            // The flow is:
+        fn test_split_swap_strategy_encoder_executor_table() {
+            // Same flow as `test_split_input_cyclic_swap`, with the USV3 and USV2 executors encoded
+            // once in the executor table
             //            ┌─ (USV3, 60% split) ──> WETH ─┐
             //            │                              │
             // USDC ──────┤                              ├──(USV2)──> USDC
@@ -828,62 +3086,31 @@ mod tests {
 
             let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
             let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+            let usv3_pool = |id: &str, fee: u32| ProtocolComponent {
+                id: id.to_string(),
+                protocol_system: "uniswap_v3".to_string(),
+                static_attributes: HashMap::from([(
+                    "fee".to_string(),
+                    Bytes::from(BigInt::from(fee).to_signed_bytes_be()),
+                )]),
+                ..Default::default()
+            };
 
-            // USDC -> WETH (Pool 1) - 60% of input
             let swap_usdc_weth_pool1 = Swap::new(
-                ProtocolComponent {
-                    id: "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640".to_string(), /* USDC-WETH USV3
-                                                                                   * Pool 1 */
-                    protocol_system: "uniswap_v3".to_string(),
-                    static_attributes: {
-                        let mut attrs = HashMap::new();
-                        attrs.insert(
-                            "fee".to_string(),
-                            Bytes::from(BigInt::from(500).to_signed_bytes_be()),
-                        );
-                        attrs
-                    },
-                    ..Default::default()
-                },
+                usv3_pool("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640", 500),
                 usdc.clone(),
                 weth.clone(),
             )
             .split(0.6f64);
-
-            // USDC -> WETH (Pool 2) - 40% of input (remaining)
             let swap_usdc_weth_pool2 = Swap::new(
-                ProtocolComponent {
-                    id: "0x8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8".to_string(), /* USDC-WETH USV3
-                                                                                   * Pool 2 */
-                    protocol_system: "uniswap_v3".to_string(),
-                    static_attributes: {
-                        let mut attrs = HashMap::new();
-                        attrs.insert(
-                            "fee".to_string(),
-                            Bytes::from(BigInt::from(3000).to_signed_bytes_be()),
-                        );
-                        attrs
-                    },
-                    ..Default::default()
-                },
+                usv3_pool("0x8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8", 3000),
                 usdc.clone(),
                 weth.clone(),
             );
-
-            // WETH -> USDC (Pool 2)
             let swap_weth_usdc_pool2 = Swap::new(
                 ProtocolComponent {
-                    id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(), /* USDC-WETH USV2
-                                                                                   * Pool 2 */
+                    id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
                     protocol_system: "uniswap_v2".to_string(),
-                    static_attributes: {
-                        let mut attrs = HashMap::new();
-                        attrs.insert(
-                            "fee".to_string(),
-                            Bytes::from(BigInt::from(3000).to_signed_bytes_be()),
-                        );
-                        attrs
-                    },
                     ..Default::default()
                 },
                 weth.clone(),
@@ -897,16 +3124,15 @@ mod tests {
                 Bytes::from("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66"),
                 false,
             )
-            .unwrap();
+            .unwrap()
+            .with_executor_table();
 
             let solution = Solution {
                 exact_out: false,
                 given_token: usdc.clone(),
-                given_amount: BigUint::from_str("100000000").unwrap(), // 100 USDC (6 decimals)
+                given_amount: BigUint::from_str("100000000").unwrap(),
                 checked_token: usdc.clone(),
-                checked_amount: BigUint::from_str("99574171").unwrap(), /* Expected output
-                                                                         * from
-                                                                         * test */
+                checked_amount: BigUint::from_str("99574171").unwrap(),
                 sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
                 receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
                 swaps: vec![swap_usdc_weth_pool1, swap_usdc_weth_pool2, swap_weth_usdc_pool2],
@@ -920,11 +3146,14 @@ mod tests {
             let hex_calldata = hex::encode(&encoded_solution.swaps);
 
             let expected_swaps = [
-                "006e",                                     // ple encoded swaps
+                "02",                                       // number of executors
+                "2e234dae75c793f67a35089c9d99245e1c58470b", // executor 0 (USV3)
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor 1 (USV2)
+                "005b",                                     // ple encoded swaps
                 "00",                                       // token in index
                 "01",                                       // token out index
                 "999999",                                   // split
-                "2e234dae75c793f67a35089c9d99245e1c58470b", // executor address
+                "00",                                       // executor index
                 "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", // token in
                 "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token out
                 "0001f4",                                   // pool fee
@@ -932,11 +3161,11 @@ mod tests {
                 "88e6a0c2ddd26feeb64f039a2c41296fcb3f5640", // component id
                 "01",                                       // zero2one
                 "00",                                       // transfer type TransferFrom
-                "006e",                                     // ple encoded swaps
+                "005b",                                     // ple encoded swaps
                 "00",                                       // token in index
                 "01",                                       // token out index
                 "000000",                                   // split
-                "2e234dae75c793f67a35089c9d99245e1c58470b", // executor address
+                "00",                                       // executor index
                 "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", // token in
                 "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token out
                 "000bb8",                                   // pool fee
@@ -944,11 +3173,11 @@ mod tests {
                 "8ad599c3a0ff1de082011efddc58f1908eb6e6d8", // component id
                 "01",                                       // zero2one
                 "00",                                       // transfer type TransferFrom
-                "0057",                                     // ple encoded swaps
+                "0044",                                     // ple encoded swaps
                 "01",                                       // token in index
                 "00",                                       // token out index
                 "000000",                                   // split
-                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address,
+                "01",                                       // executor index
                 "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token in
                 "b4e16d0168e52d35cacd2c6185b44281ec28c9dc", // component id,
                 "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2", // receiver
@@ -959,10 +3188,301 @@ mod tests {
             assert_eq!(hex_calldata, expected_swaps);
             assert_eq!(
                 encoded_solution.function_signature,
-                "splitSwapPermit2(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)"
+                "splitSwapPermit2Indexed(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)"
                     .to_string()
             );
-            assert_eq!(encoded_solution.interacting_with, router_address());
+        }
+
+        #[test]
+        fn test_split_swap_strategy_encoder_permit_batch() {
+            let new_encoder = |user_transfer_type| {
+                SplitSwapStrategyEncoder::new(
+                    eth_chain(),
+                    get_swap_encoder_registry(),
+                    user_transfer_type,
+                    Bytes::from("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66"),
+                    false,
+                )
+                .unwrap()
+            };
+
+            let encoder = new_encoder(UserTransferType::TransferFromPermit2)
+                .with_permit_batch()
+                .unwrap();
+
+            assert_eq!(
+                encoder.function_signature,
+                "splitSwapPermit2Batch(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48)[],address,uint256),bytes,bytes)"
+            );
+            assert!(new_encoder(UserTransferType::TransferFrom)
+                .with_permit_batch()
+                .is_err());
+            assert!(new_encoder(UserTransferType::TransferFromPermit2)
+                .with_executor_table()
+                .with_permit_batch()
+                .is_err());
+        }
+
+        #[test]
+        fn test_split_swap_strategy_encoder_wide_token_indices() {
+            // A chain of 257 USV2 swaps going through 258 distinct tokens, more than the 1-byte
+            // token indices can refer to
+            let token = |i: usize| Bytes::from_str(&format!("0x{i:040x}")).unwrap();
+            let swaps: Vec<Swap> = (1..258)
+                .map(|i| {
+                    Swap::new(
+                        ProtocolComponent {
+                            id: format!("0x{:040x}", 0x10000 + i),
+                            protocol_system: "uniswap_v2".to_string(),
+                            ..Default::default()
+                        },
+                        token(i),
+                        token(i + 1),
+                    )
+                })
+                .collect();
+            let solution = Solution {
+                exact_out: false,
+                given_token: token(1),
+                given_amount: BigUint::from_str("1000000000000000000").unwrap(),
+                checked_token: token(258),
+                checked_amount: BigUint::from_str("1").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps,
+                ..Default::default()
+            };
+            let new_encoder = |user_transfer_type| {
+                SplitSwapStrategyEncoder::new(
+                    eth_chain(),
+                    get_swap_encoder_registry(),
+                    user_transfer_type,
+                    Bytes::from("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66"),
+                    false,
+                )
+                .unwrap()
+            };
+
+            let result = new_encoder(UserTransferType::TransferFrom).encode_strategy(&solution);
+            assert!(matches!(
+                result,
+                Err(EncodingError::InvalidInput(msg)) if msg.starts_with("The solution has 258 distinct tokens")
+            ));
+
+            let encoded_solution = new_encoder(UserTransferType::TransferFrom)
+                .with_wide_token_indices()
+                .unwrap()
+                .encode_strategy(&solution)
+                .unwrap();
+            assert_eq!(
+                encoded_solution.function_signature,
+                "splitSwapWide(uint256,address,address,uint256,bool,bool,uint256,address,bool,bytes)"
+            );
+            assert_eq!(encoded_solution.n_tokens, 258);
+            let hex_calldata = hex::encode(&encoded_solution.swaps);
+            let first_swap = [
+                "0059",                                     // ple encoded swaps
+                "0000",                                     // token in index
+                "0001",                                     // token out index
+                "000000",                                   // split
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address
+            ]
+            .join("");
+            assert!(hex_calldata.starts_with(&first_swap));
+            let last_swap_header = [
+                "0100",                                     // token in index
+                "0101",                                     // token out index
+                "000000",                                   // split
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address
+            ]
+            .join("");
+            assert!(hex_calldata.contains(&last_swap_header));
+
+            let permit2_encoder = new_encoder(UserTransferType::TransferFromPermit2)
+                .with_wide_token_indices()
+                .unwrap();
+            assert_eq!(
+                permit2_encoder.function_signature,
+                "splitSwapPermit2Wide(uint256,address,address,uint256,bool,bool,uint256,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)"
+            );
+            assert!(permit2_encoder
+                .with_permit_batch()
+                .is_err());
+            assert!(new_encoder(UserTransferType::TransferFrom)
+                .with_executor_table()
+                .with_wide_token_indices()
+                .is_err());
+        }
+
+        #[test]
+        fn test_split_swap_strategy_encoder_precise_splits() {
+            //   WETH ──(USV2, 12.3456789%)──> DAI
+            //        └─(USV2, rest)─────────> DAI
+            let weth = weth();
+            let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+            let usv2_swap = |id: &str, split: f64| {
+                Swap::new(
+                    ProtocolComponent {
+                        id: id.to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth.clone(),
+                    dai.clone(),
+                )
+                .split(split)
+            };
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth.clone(),
+                given_amount: BigUint::from_str("1000000000000000000").unwrap(),
+                checked_token: dai.clone(),
+                checked_amount: BigUint::from_str("1").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![
+                    usv2_swap("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11", 0.123456789),
+                    usv2_swap("0xC3D03e4F041Fd4cD388c549Ee2A29a9E5075882f", 0.0),
+                ],
+                ..Default::default()
+            };
+            let new_encoder = |user_transfer_type| {
+                SplitSwapStrategyEncoder::new(
+                    eth_chain(),
+                    get_swap_encoder_registry(),
+                    user_transfer_type,
+                    Bytes::from("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66"),
+                    false,
+                )
+                .unwrap()
+            };
+
+            let encoded_solution = new_encoder(UserTransferType::TransferFrom)
+                .with_precise_splits()
+                .unwrap()
+                .encode_strategy(&solution)
+                .unwrap();
+
+            assert_eq!(
+                encoded_solution.function_signature,
+                "splitSwapPrecise(uint256,address,address,uint256,bool,bool,uint256,address,bool,bytes)"
+            );
+            let hex_calldata = hex::encode(&encoded_solution.swaps);
+            let first_swap_header = [
+                "00",                                       // token in index
+                "01",                                       // token out index
+                "075bcd15",                                 // split (123456789 / 1e9)
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address
+            ]
+            .join("");
+            assert!(hex_calldata.contains(&first_swap_header));
+
+            assert!(new_encoder(UserTransferType::TransferFrom)
+                .with_precise_splits()
+                .unwrap()
+                .with_wide_token_indices()
+                .is_err());
+            assert!(new_encoder(UserTransferType::TransferFromPermit2)
+                .with_permit_batch()
+                .unwrap()
+                .with_precise_splits()
+                .is_err());
+        }
+
+        #[test]
+        fn test_split_swap_strategy_encoder_split_branch_groups() {
+            // The two USV4 branches are executed within one unlock of the pool manager
+            //
+            //   WETH ──(USV4, 60%)──> USDC
+            //        └─(USV4, rest)──> USDC
+            let weth = weth();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+            let usv4_swap = |fee: i32, split: f64| {
+                let mut static_attributes: HashMap<String, Bytes> = HashMap::new();
+                static_attributes.insert(
+                    "key_lp_fee".into(),
+                    Bytes::from(BigInt::from(fee).to_signed_bytes_be()),
+                );
+                static_attributes.insert(
+                    "tick_spacing".into(),
+                    Bytes::from(BigInt::from(10).to_signed_bytes_be()),
+                );
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0x000000000004444c5dc75cB358380D2e3dE08A90".to_string(),
+                        protocol_system: "uniswap_v4".to_string(),
+                        static_attributes,
+                        ..Default::default()
+                    },
+                    weth.clone(),
+                    usdc.clone(),
+                )
+                .split(split)
+            };
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth.clone(),
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: usdc.clone(),
+                checked_amount: BigUint::from_str("1").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![usv4_swap(500, 0.6), usv4_swap(3000, 0f64)],
+                ..Default::default()
+            };
+            let encoder = SplitSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap();
+
+            let encoded_solution = encoder
+                .clone()
+                .encode_strategy(&solution)
+                .unwrap();
+            // One swap per branch, each with its own settle and take
+            assert_eq!(encoded_solution.summary.n_groups, 2);
+
+            let encoded_solution = encoder
+                .with_split_branch_groups()
+                .encode_strategy(&solution)
+                .unwrap();
+            assert_eq!(encoded_solution.summary.n_groups, 1);
+            let hex_calldata = encode(&encoded_solution.swaps);
+            let header = [
+                "00c1",                                     // ple encoded swaps
+                "00",                                       // token in index
+                "01",                                       // token out index
+                "000000",                                   // split
+                "f62849f9a0b5bf2913b396098f7c7019b51a820a", // executor address
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // group token in
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", // group token out
+                "02",                                       // split branches
+            ]
+            .join("");
+            assert!(hex_calldata.starts_with(&header));
+            let first_branch = [
+                "0033",                                     // ple encoded branch
+                "999999",                                   // split (60%)
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", // token out
+                "0001f4",                                   // fee
+            ]
+            .join("");
+            assert!(hex_calldata.contains(&first_branch));
+            let second_branch = [
+                "0033",                                     // ple encoded branch
+                "000000",                                   // split (remainder)
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", // token out
+                "000bb8",                                   // fee
+                "00000a",                                   // tick spacing
+                "0000000000000000000000000000000000000000", // hook address
+                "0000",                                     // hook data length
+            ]
+            .join("");
+            assert!(hex_calldata.ends_with(&second_branch));
         }
 
         #[test]
@@ -1112,4 +3632,126 @@ mod tests {
             assert_eq!(encoded_solution.interacting_with, router_address());
         }
     }
+
+    mod multi_input {
+        use super::*;
+        use crate::encoding::models::{GivenToken, Swap};
+
+        fn univ2_swap(id: &str, token_in: &Bytes, token_out: &Bytes) -> Swap {
+            Swap::new(
+                ProtocolComponent {
+                    id: id.to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                token_in.clone(),
+                token_out.clone(),
+            )
+        }
+
+        #[test]
+        fn test_multi_input_split_swap_strategy_encoder() {
+            // Consolidates WETH and WBTC into USDC
+            //
+            // WETH ──(USV2)──┐
+            //                ├──> USDC
+            // WBTC ──(USV2)──┘
+
+            let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+            let encoder = MultiInputSplitSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFrom,
+                router_address(),
+                false,
+            )
+            .unwrap();
+
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth(),
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                additional_given_tokens: vec![GivenToken {
+                    token: wbtc.clone(),
+                    amount: BigUint::from_str("1000000").unwrap(),
+                }],
+                checked_token: usdc.clone(),
+                checked_amount: BigUint::from_str("2000_000000").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![
+                    univ2_swap("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc", &weth(), &usdc),
+                    univ2_swap("0x004375Dff511095CC5A197A54140a24eFEF3A416", &wbtc, &usdc),
+                ],
+                ..Default::default()
+            };
+
+            let encoded_solution = encoder
+                .encode_strategy(&solution)
+                .unwrap();
+
+            let expected_swaps = [
+                "0057",                                     // ple encoded swaps
+                "00",                                       // token in index
+                "02",                                       // token out index
+                "000000",                                   // split
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", // token in
+                "b4e16d0168e52d35cacd2c6185b44281ec28c9dc", // component id
+                "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2", // receiver
+                "00",                                       // zero2one
+                "01",                                       // transfer type Transfer
+                "0057",                                     // ple encoded swaps
+                "01",                                       // token in index
+                "02",                                       // token out index
+                "000000",                                   // split
+                "5615deb798bb3e4dfa0139dfa1b3d433cc23b72f", // executor address
+                "2260fac5e5542a773aa44fbcfedf7c193bc2c599", // token in
+                "004375dff511095cc5a197a54140a24efef3a416", // component id
+                "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2", // receiver
+                "01",                                       // zero2one
+                "01",                                       // transfer type Transfer
+            ]
+            .join("");
+
+            assert_eq!(encode(&encoded_solution.swaps), expected_swaps);
+            assert_eq!(encoded_solution.n_tokens, 3);
+            assert_eq!(
+                encoded_solution.function_signature,
+                "multiInputSplitSwap(uint256[],address[],address,uint256,bool,uint256,address,bool,bytes)"
+            );
+            assert_eq!(encoded_solution.interacting_with, router_address());
+        }
+
+        #[test]
+        fn test_multi_input_split_swap_strategy_encoder_permit2() {
+            let encoder = MultiInputSplitSwapStrategyEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                UserTransferType::TransferFromPermit2,
+                router_address(),
+                false,
+            )
+            .unwrap();
+            let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+            let solution = Solution {
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: usdc.clone(),
+                checked_amount: BigUint::from(1u32),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![univ2_swap(
+                    "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc",
+                    &weth(),
+                    &usdc,
+                )],
+                ..Default::default()
+            };
+
+            // The router can't pull several tokens with a single permit
+            let result = encoder.encode_strategy(&solution);
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        }
+    }
 }
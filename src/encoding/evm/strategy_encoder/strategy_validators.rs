@@ -1,12 +1,33 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+};
 
-use tycho_common::Bytes;
+use num_bigint::BigUint;
+use tycho_common::{
+    models::{token::Token, Chain},
+    Bytes,
+};
 
 use crate::encoding::{
     errors::EncodingError,
-    models::{NativeAction, Swap},
+    evm::{constants::VAULT_PROTOCOLS, utils::supports_fee_on_transfer},
+    models::{NativeAction, Solution, SplitSpec, Swap},
 };
 
+/// Static attributes holding the total assets and the total supply of shares of a vault, used to
+/// convert amounts when the swap has no protocol state.
+const VAULT_TOTAL_ASSETS_ATTRIBUTE: &str = "total_assets";
+const VAULT_TOTAL_SUPPLY_ATTRIBUTE: &str = "total_supply";
+
+/// Tolerance, in basis points, of the amounts compared with the converted amount of a vault swap.
+/// It covers the rounding of the conversions.
+const VAULT_CONVERSION_TOLERANCE_BPS: u32 = 1;
+
+/// Above this many basis points over the amount expected by the next swap, the converted amount of
+/// a vault swap is reported as a likely unit mismatch, though the solution is still encoded.
+const VAULT_CONVERSION_WARNING_BPS: u32 = 100;
+
 pub trait SwapValidator {
     /// Raises an error if swaps do not represent a valid path from the given token to the checked
     /// token.
@@ -46,60 +67,69 @@ pub trait SwapValidator {
             checked_token
         };
 
-        // Build directed graph of token flows
-        let mut graph: HashMap<&Bytes, HashSet<&Bytes>> = HashMap::new();
-        let mut all_tokens = HashSet::new();
-        for swap in swaps {
-            graph
-                .entry(swap.token_in())
-                .or_default()
-                .insert(swap.token_out());
-            all_tokens.insert(swap.token_in());
-            all_tokens.insert(swap.token_out());
-        }
-
-        // BFS from validation_given
-        let mut visited = HashSet::new();
-        let mut queue = VecDeque::new();
-        queue.push_back(given_token);
+        validate_token_graph(swaps, &[given_token], checked_token)
+    }
+}
 
-        while let Some(token) = queue.pop_front() {
-            if !visited.insert(token) {
-                continue;
-            }
+/// Raises an error unless the checked token is reachable from the given tokens through the swaps,
+/// and every token of the swaps is connected to the given tokens.
+fn validate_token_graph(
+    swaps: &[Swap],
+    given_tokens: &[&Bytes],
+    checked_token: &Bytes,
+) -> Result<(), EncodingError> {
+    // Build directed graph of token flows
+    let mut graph: HashMap<&Bytes, HashSet<&Bytes>> = HashMap::new();
+    let mut all_tokens = HashSet::new();
+    for swap in swaps {
+        graph
+            .entry(swap.token_in())
+            .or_default()
+            .insert(swap.token_out());
+        all_tokens.insert(swap.token_in());
+        all_tokens.insert(swap.token_out());
+    }
 
-            // Early success check - if we've reached the checked token and visited all tokens
-            if token == checked_token && visited.len() == all_tokens.len() {
-                return Ok(());
-            }
+    // BFS from the given tokens
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<&Bytes> = given_tokens.iter().copied().collect();
 
-            if let Some(next_tokens) = graph.get(token) {
-                for &next_token in next_tokens {
-                    if !visited.contains(next_token) {
-                        queue.push_back(next_token);
-                    }
-                }
-            }
+    while let Some(token) = queue.pop_front() {
+        if !visited.insert(token) {
+            continue;
         }
 
-        // After BFS completes, check if both conditions are met:
-        // 1. The checked token is in the visited set
-        // 2. All unique tokens from the swaps are visited
-        if visited.contains(checked_token) && visited.len() == all_tokens.len() {
+        // Early success check - if we've reached the checked token and visited all tokens
+        if token == checked_token && visited.len() == all_tokens.len() {
             return Ok(());
         }
 
-        // If we get here, either checked_token wasn't reached or not all tokens were visited
-        if !visited.contains(checked_token) {
-            Err(EncodingError::InvalidInput(
-                "Checked token is not reachable through swap path".to_string(),
-            ))
-        } else {
-            Err(EncodingError::InvalidInput(
-                "Some tokens are not connected to the main path".to_string(),
-            ))
+        if let Some(next_tokens) = graph.get(token) {
+            for &next_token in next_tokens {
+                if !visited.contains(next_token) {
+                    queue.push_back(next_token);
+                }
+            }
         }
     }
+
+    // After BFS completes, check if both conditions are met:
+    // 1. The checked token is in the visited set
+    // 2. All unique tokens from the swaps are visited
+    if visited.contains(checked_token) && visited.len() == all_tokens.len() {
+        return Ok(());
+    }
+
+    // If we get here, either checked_token wasn't reached or not all tokens were visited
+    if !visited.contains(checked_token) {
+        Err(EncodingError::InvalidInput(
+            "Checked token is not reachable through swap path".to_string(),
+        ))
+    } else {
+        Err(EncodingError::InvalidInput(
+            "Some tokens are not connected to the main path".to_string(),
+        ))
+    }
 }
 
 /// Validates whether a sequence of split swaps represents a valid solution.
@@ -112,18 +142,19 @@ impl SplitSwapValidator {
     /// Raises an error if the split percentages are invalid.
     ///
     /// Split percentages are considered valid if all the following conditions are met:
-    /// * Each split amount is < 1 (100%)
-    /// * There is exactly one 0% split for each token, and it's the last swap specified, signifying
-    ///   to the router to send the remainder of the token to the designated protocol
+    /// * Each split amount is a finite number < 1 (100%)
+    /// * There is exactly one remainder (0%) split for each token, and it's the last swap
+    ///   specified, signifying to the router to send the remainder of the token to the designated
+    ///   protocol (see [`SplitSpec`])
     /// * The sum of all non-remainder splits for each token is < 1 (100%)
     /// * There are no negative split amounts
     pub fn validate_split_percentages(&self, swaps: &[Swap]) -> Result<(), EncodingError> {
         let mut swaps_by_token: HashMap<&Bytes, Vec<&Swap>> = HashMap::new();
         for swap in swaps {
-            if swap.get_split() >= 1.0 {
+            let split = swap.get_split();
+            if !split.is_finite() || split >= 1.0 {
                 return Err(EncodingError::InvalidInput(format!(
-                    "Split percentage must be less than 1 (100%), got {}",
-                    swap.get_split()
+                    "Split percentage must be less than 1 (100%), got {split}"
                 )));
             }
             swaps_by_token
@@ -135,39 +166,55 @@ impl SplitSwapValidator {
         for (token, token_swaps) in swaps_by_token {
             // Single swaps don't need remainder handling
             if token_swaps.len() == 1 {
-                if token_swaps[0].get_split() != 0.0 {
+                if let SplitSpec::Fraction(fraction) = token_swaps[0].get_split_spec() {
                     return Err(EncodingError::InvalidInput(format!(
-                        "Single swap must have 0% split for token {token}",
+                        "Single swap must have 0% split for token {token}: it takes the remainder \
+                         (100%) of the token, got a split of {fraction}",
                     )));
                 }
                 continue;
             }
 
-            let mut found_zero_split = false;
+            let mut remainder_positions = vec![];
             let mut total_percentage = 0.0;
             for (i, swap) in token_swaps.iter().enumerate() {
-                match (swap.get_split() == 0.0, i == token_swaps.len() - 1) {
-                    (true, false) => {
+                match swap.get_split_spec() {
+                    SplitSpec::Remainder => remainder_positions.push(i),
+                    SplitSpec::Fraction(fraction) if fraction < 0.0 => {
                         return Err(EncodingError::InvalidInput(format!(
-                            "The 0% split for token {token} must be the last swap",
-                        )))
-                    }
-                    (true, true) => found_zero_split = true,
-                    (false, _) => {
-                        if swap.get_split() < 0.0 {
-                            return Err(EncodingError::InvalidInput(format!(
-                                "All splits must be >= 0% for token {token}"
-                            )));
-                        }
-                        total_percentage += swap.get_split();
+                            "All splits must be >= 0% for token {token}, got {fraction}"
+                        )));
                     }
+                    SplitSpec::Fraction(fraction) => total_percentage += fraction,
                 }
             }
 
-            if !found_zero_split {
-                return Err(EncodingError::InvalidInput(format!(
-                    "Token {token} must have exactly one 0% split for remainder handling"
-                )));
+            let last = token_swaps.len() - 1;
+            match remainder_positions.as_slice() {
+                [] => {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Token {token} must have exactly one 0% split for remainder handling: \
+                         none of its {} swaps is the remainder, mark the last one with \
+                         `Swap::remainder`",
+                        token_swaps.len()
+                    )));
+                }
+                [position] if *position == last => {}
+                [position] => {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "The 0% split for token {token} must be the last swap: the remainder is \
+                         swap {} of {}",
+                        position + 1,
+                        token_swaps.len()
+                    )));
+                }
+                positions => {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Token {token} must have exactly one 0% split for remainder handling, \
+                         found {} remainder swaps",
+                        positions.len()
+                    )));
+                }
             }
 
             // Total must be <100% to leave room for remainder
@@ -182,6 +229,182 @@ impl SplitSwapValidator {
 
         Ok(())
     }
+
+    /// Returns the swaps with their split percentages normalized, so that upstream rounding
+    /// errors (e.g. splits of 0.5999999 and 0.4) pass `validate_split_percentages`.
+    ///
+    /// For every token, within the given `epsilon`:
+    /// * Sub-dust splits (below `epsilon`) are folded into the largest swap of the token with the
+    ///   same token out, and their swaps removed.
+    /// * If the token has no remainder swap and its splits sum to 1 (100%), they are rescaled to
+    ///   sum exactly to 1 and its last swap takes the remainder.
+    /// * A single swap with a split of 1 (100%) takes the remainder.
+    ///
+    /// The other splits are left unchanged, for `validate_split_percentages` to reject them.
+    pub fn normalize_split_percentages(&self, swaps: &[Swap], epsilon: f64) -> Vec<Swap> {
+        let mut swaps = swaps.to_vec();
+        let mut indices_by_token: HashMap<Bytes, Vec<usize>> = HashMap::new();
+        for (i, swap) in swaps.iter().enumerate() {
+            indices_by_token
+                .entry(swap.token_in().clone())
+                .or_default()
+                .push(i);
+        }
+
+        let mut removed = HashSet::new();
+        for indices in indices_by_token.values() {
+            let fraction = |swap: &Swap| match swap.get_split_spec() {
+                SplitSpec::Fraction(fraction) => Some(fraction),
+                SplitSpec::Remainder => None,
+            };
+            // The share of the remainder swap is what the other swaps leave
+            let share = |swaps: &[Swap], i: usize| {
+                fraction(&swaps[i]).unwrap_or_else(|| {
+                    1.0 - indices
+                        .iter()
+                        .filter_map(|j| fraction(&swaps[*j]))
+                        .sum::<f64>()
+                })
+            };
+
+            for &i in indices {
+                let Some(dust) =
+                    fraction(&swaps[i]).filter(|split| *split > 0.0 && *split < epsilon)
+                else {
+                    continue;
+                };
+                let largest = indices
+                    .iter()
+                    .copied()
+                    .filter(|j| {
+                        *j != i &&
+                            !removed.contains(j) &&
+                            swaps[*j].token_out() == swaps[i].token_out()
+                    })
+                    .max_by(|a, b| share(&swaps, *a).total_cmp(&share(&swaps, *b)));
+                let Some(largest) = largest else {
+                    continue;
+                };
+                if let Some(split) = fraction(&swaps[largest]) {
+                    swaps[largest] = swaps[largest]
+                        .clone()
+                        .split(split + dust);
+                }
+                // Clearing the split of the removed swap excludes it from the shares of the token
+                swaps[i] = swaps[i].clone().remainder();
+                removed.insert(i);
+            }
+
+            let remaining: Vec<usize> = indices
+                .iter()
+                .copied()
+                .filter(|i| !removed.contains(i))
+                .collect();
+            let fractions: Option<Vec<f64>> = remaining
+                .iter()
+                .map(|i| fraction(&swaps[*i]))
+                .collect();
+            let Some(fractions) = fractions else {
+                continue;
+            };
+            let total: f64 = fractions.iter().sum();
+            if (total - 1.0).abs() > epsilon {
+                continue;
+            }
+            let Some((last, others)) = remaining.split_last() else {
+                continue;
+            };
+            for (i, split) in others.iter().zip(fractions) {
+                swaps[*i] = swaps[*i].clone().split(split / total);
+            }
+            swaps[*last] = swaps[*last].clone().remainder();
+        }
+
+        swaps
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !removed.contains(i))
+            .map(|(_, swap)| swap)
+            .collect()
+    }
+
+    /// Raises an error if a swap flagged as fee-on-transfer (see `supports_fee_on_transfer`)
+    /// splits its token in with other swaps.
+    ///
+    /// The router splits a token according to the amount it expects to hold, which the transfer
+    /// fees make inaccurate: the splits would be computed from more tokens than the router
+    /// received. Fee-on-transfer swaps are only safe when they take the whole token in.
+    pub fn validate_fee_on_transfer(&self, swaps: &[Swap]) -> Result<(), EncodingError> {
+        for swap in swaps
+            .iter()
+            .filter(|swap| supports_fee_on_transfer(swap))
+        {
+            let n_swaps = swaps
+                .iter()
+                .filter(|other| other.token_in() == swap.token_in())
+                .count();
+            if n_swaps > 1 {
+                return Err(EncodingError::InvalidInput(format!(
+                    "The fee-on-transfer swap on pool {} splits its token in {} with {} other \
+                     swaps, which is unsafe: fee-on-transfer swaps must take the whole token",
+                    swap.component().id,
+                    swap.token_in(),
+                    n_swaps - 1
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Raises an error if the swaps do not represent a valid path from all the given tokens to the
+    /// checked token.
+    ///
+    /// A path is considered valid if all the following conditions are met:
+    /// * Every given token is sold by at least one swap, so no given token is left in the router
+    /// * No swap buys a given token (the amounts of the given tokens are fixed)
+    /// * The checked token is reachable from the given tokens through the swap path
+    /// * There are no tokens which are unconnected from the given tokens
+    ///
+    /// If the checked token is the native token and the native action is UNWRAP, it is converted to
+    /// the wrapped token before validating the swap path.
+    pub fn validate_multi_input_swap_path(
+        &self,
+        swaps: &[Swap],
+        given_tokens: &[&Bytes],
+        checked_token: &Bytes,
+        native_action: &Option<NativeAction>,
+        native_address: &Bytes,
+        wrapped_address: &Bytes,
+    ) -> Result<(), EncodingError> {
+        let checked_token =
+            if *checked_token == *native_address && *native_action == Some(NativeAction::Unwrap) {
+                wrapped_address
+            } else {
+                checked_token
+            };
+        for given_token in given_tokens {
+            if !swaps
+                .iter()
+                .any(|swap| swap.token_in() == *given_token)
+            {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Given token {given_token} is not sold by any swap"
+                )));
+            }
+        }
+        if let Some(swap) = swaps
+            .iter()
+            .find(|swap| given_tokens.contains(&swap.token_out()))
+        {
+            return Err(EncodingError::InvalidInput(format!(
+                "The swap on pool {} buys the given token {}, which is not supported by multi \
+                 input solutions",
+                swap.component().id,
+                swap.token_out()
+            )));
+        }
+        validate_token_graph(swaps, given_tokens, checked_token)
+    }
 }
 
 /// Validates whether a sequence of sequential swaps represents a valid solution.
@@ -190,6 +413,117 @@ pub struct SequentialSwapValidator;
 
 impl SwapValidator for SequentialSwapValidator {}
 
+impl SequentialSwapValidator {
+    /// Raises an error if the amounts of an exact in solution don't account for the asset/share
+    /// conversions of its vault swaps (see `VAULT_PROTOCOLS`), e.g. a DAI amount propagated as an
+    /// sDAI amount.
+    ///
+    /// The amount in of every vault swap (its estimated amount in, or the given amount for the
+    /// first swap) is converted with the vault's rate (see `vault_amount_out`). The converted
+    /// amount must cover the estimated amount in of the next swap, or the checked amount if the
+    /// vault swap is the last one, within `VAULT_CONVERSION_TOLERANCE_BPS`. Otherwise, the router
+    /// call would revert on the checked amount. A converted amount exceeding the next swap's
+    /// estimate by more than `VAULT_CONVERSION_WARNING_BPS` is only logged.
+    ///
+    /// Vault swaps without a known amount in or rate are skipped, as are the solutions with an
+    /// output fee (their checked amount is net of the fee).
+    pub fn validate_vault_conversions(
+        &self,
+        solution: &Solution,
+        chain: Chain,
+    ) -> Result<(), EncodingError> {
+        if solution.exact_out {
+            return Ok(());
+        }
+        let output_fee = solution
+            .fee
+            .as_ref()
+            .is_some_and(|fee| !fee.on_input);
+        for (i, swap) in solution.swaps.iter().enumerate() {
+            if !VAULT_PROTOCOLS.contains(
+                swap.component()
+                    .protocol_system
+                    .as_str(),
+            ) {
+                continue;
+            }
+            let amount_in = match swap.get_estimated_amount_in() {
+                Some(amount) => amount,
+                None if i == 0 => &solution.given_amount,
+                None => continue,
+            };
+            let Some(amount_out) = vault_amount_out(swap, amount_in, chain) else {
+                continue;
+            };
+            let expected = match solution.swaps.get(i + 1) {
+                Some(next_swap) => match next_swap.get_estimated_amount_in() {
+                    Some(amount) => amount,
+                    None => continue,
+                },
+                None if output_fee => continue,
+                None => &solution.checked_amount,
+            };
+            if &amount_out * 10_000u32 < expected * (10_000 - VAULT_CONVERSION_TOLERANCE_BPS) {
+                return Err(EncodingError::InvalidInput(format!(
+                    "The {} swap on {} converts {amount_in} {} into {amount_out} {}, less than \
+                     the {expected} expected after it. The amounts likely mix up the assets and \
+                     the shares of the vault",
+                    swap.component().protocol_system,
+                    swap.component().id,
+                    swap.token_in(),
+                    swap.token_out(),
+                )));
+            }
+            if i + 1 < solution.swaps.len() &&
+                &amount_out * 10_000u32 > expected * (10_000 + VAULT_CONVERSION_WARNING_BPS)
+            {
+                tracing::warn!(
+                    protocol_system = %swap.component().protocol_system,
+                    component_id = %swap.component().id,
+                    %amount_in,
+                    %amount_out,
+                    %expected,
+                    "Vault swap converts into more than the next swap expects, the amounts likely \
+                     mix up the assets and the shares of the vault"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the amount out of a vault swap for the given amount in: the shares minted by a deposit,
+/// or the assets redeemed for shares.
+///
+/// The amount is simulated with the swap's protocol state if it has one. Otherwise, it's derived
+/// from the `total_assets` and `total_supply` static attributes of the vault, rounding down like
+/// ERC4626's `previewDeposit` and `previewRedeem`. Returns `None` if neither is available.
+fn vault_amount_out(swap: &Swap, amount_in: &BigUint, chain: Chain) -> Option<BigUint> {
+    if let Some(state) = swap.get_protocol_state() {
+        let token = |address: &Bytes| Token::new(address, "", 18, 0, &[], chain, 100);
+        return state
+            .get_amount_out(amount_in.clone(), &token(swap.token_in()), &token(swap.token_out()))
+            .ok()
+            .map(|result| result.amount);
+    }
+    let attributes = &swap.component().static_attributes;
+    let total_assets = BigUint::from_bytes_be(attributes.get(VAULT_TOTAL_ASSETS_ATTRIBUTE)?);
+    let total_supply = BigUint::from_bytes_be(attributes.get(VAULT_TOTAL_SUPPLY_ATTRIBUTE)?);
+    if total_assets == BigUint::ZERO || total_supply == BigUint::ZERO {
+        return None;
+    }
+    // A deposit buys the shares of the vault, whose address is the component id
+    let deposit = Bytes::from_str(&swap.component().id)
+        .ok()
+        .as_ref() ==
+        Some(swap.token_out());
+    if deposit {
+        Some(amount_in * total_supply / total_assets)
+    } else {
+        Some(amount_in * total_assets / total_supply)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -534,6 +868,175 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_normalize_split_percentages() {
+        let validator = SplitSwapValidator;
+        let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let swap = |id: &str, token_out: &Bytes, split: f64| {
+            Swap::new(
+                ProtocolComponent {
+                    id: id.to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth.clone(),
+                token_out.clone(),
+            )
+            .split(split)
+        };
+        let splits = |swaps: &[Swap]| {
+            swaps
+                .iter()
+                .map(|swap| (swap.component().id.clone(), swap.get_split()))
+                .collect::<Vec<_>>()
+        };
+
+        // The splits summing to 0.9999999 are rescaled, with the last swap as the remainder
+        let swaps = vec![swap("pool1", &dai, 0.5999999), swap("pool2", &usdc, 0.4)];
+        let normalized = validator.normalize_split_percentages(&swaps, 1e-6);
+        assert_eq!(
+            splits(&normalized),
+            vec![("pool1".to_string(), 0.5999999 / (0.5999999 + 0.4)), ("pool2".to_string(), 0.0)]
+        );
+        assert!(validator
+            .validate_split_percentages(&normalized)
+            .is_ok());
+
+        // The dust split is folded into the largest swap with the same token out
+        let swaps = vec![
+            swap("pool1", &dai, 0.3),
+            swap("pool2", &dai, 0.0000001),
+            swap("pool3", &usdc, 0.2),
+            swap("pool4", &dai, 0.0),
+        ];
+        let normalized = validator.normalize_split_percentages(&swaps, 1e-6);
+        assert_eq!(
+            splits(&normalized),
+            vec![
+                ("pool1".to_string(), 0.3),
+                ("pool3".to_string(), 0.2),
+                ("pool4".to_string(), 0.0)
+            ]
+        );
+        let swaps = vec![
+            swap("pool1", &dai, 0.6),
+            swap("pool2", &dai, 0.0000001),
+            swap("pool3", &usdc, 0.0),
+        ];
+        let normalized = validator.normalize_split_percentages(&swaps, 1e-6);
+        assert_eq!(
+            splits(&normalized),
+            vec![("pool1".to_string(), 0.6 + 0.0000001), ("pool3".to_string(), 0.0)]
+        );
+
+        // A single swap of 100% takes the remainder
+        let normalized = validator.normalize_split_percentages(&[swap("pool1", &dai, 1.0)], 1e-6);
+        assert_eq!(splits(&normalized), vec![("pool1".to_string(), 0.0)]);
+
+        // Splits beyond the epsilon are left unchanged
+        let swaps = vec![swap("pool1", &dai, 0.5), swap("pool2", &usdc, 0.4)];
+        let normalized = validator.normalize_split_percentages(&swaps, 1e-6);
+        assert_eq!(splits(&normalized), splits(&swaps));
+        assert!(validator
+            .validate_split_percentages(&normalized)
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_fee_on_transfer() {
+        let validator = SplitSwapValidator;
+        let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let swap = |id: &str, token_in: &Bytes, token_out: &Bytes| {
+            Swap::new(
+                ProtocolComponent {
+                    id: id.to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                token_in.clone(),
+                token_out.clone(),
+            )
+        };
+
+        let fot_swap = |id: &str, token_in: &Bytes, token_out: &Bytes| {
+            Swap::new(
+                ProtocolComponent {
+                    id: id.to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    static_attributes: HashMap::from([(
+                        "supports_fot".to_string(),
+                        Bytes::from(vec![1u8]),
+                    )]),
+                    ..Default::default()
+                },
+                token_in.clone(),
+                token_out.clone(),
+            )
+        };
+
+        // A fee-on-transfer swap taking the whole token in is safe
+        let swaps = vec![
+            fot_swap("pool1", &weth, &dai),
+            swap("pool2", &dai, &usdc).split(0.5),
+            swap("pool3", &dai, &usdc),
+        ];
+        assert!(validator
+            .validate_fee_on_transfer(&swaps)
+            .is_ok());
+
+        // Splitting the token in of a fee-on-transfer swap isn't
+        let swaps = vec![
+            swap("pool1", &weth, &dai),
+            fot_swap("pool2", &dai, &usdc).split(0.5),
+            swap("pool3", &dai, &usdc),
+        ];
+        assert!(matches!(
+            validator.validate_fee_on_transfer(&swaps),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("pool2")
+        ));
+    }
+
+    #[test]
+    fn test_validate_swaps_multiple_remainders() {
+        let validator = SplitSwapValidator;
+        let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let swap = |id: &str| {
+            Swap::new(
+                ProtocolComponent {
+                    id: id.to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth.clone(),
+                dai.clone(),
+            )
+        };
+
+        let two_remainders =
+            vec![swap("pool1").split(0.5), swap("pool2").remainder(), swap("pool3")];
+        assert!(matches!(
+            validator.validate_split_percentages(&two_remainders),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("found 2 remainder swaps")
+        ));
+
+        let not_a_number = vec![swap("pool1").split(f64::NAN), swap("pool2").remainder()];
+        assert!(matches!(
+            validator.validate_split_percentages(&not_a_number),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("must be less than 1")
+        ));
+
+        let valid = vec![
+            swap("pool1").split_spec(SplitSpec::Fraction(0.5)),
+            swap("pool2").split_spec(SplitSpec::Remainder),
+        ];
+        assert_eq!(validator.validate_split_percentages(&valid), Ok(()));
+    }
+
     #[test]
     fn test_validate_path_wrap_eth_given_token() {
         let validator = SplitSwapValidator;
@@ -589,4 +1092,160 @@ mod tests {
         );
         assert_eq!(result, Ok(()));
     }
+
+    #[test]
+    fn test_validate_multi_input_swap_path() {
+        let validator = SplitSwapValidator;
+        let eth = Bytes::from_str("0x0000000000000000000000000000000000000000").unwrap();
+        let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        let wbtc = Bytes::from_str("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let swap = |id: &str, token_in: &Bytes, token_out: &Bytes| {
+            Swap::new(
+                ProtocolComponent {
+                    id: id.to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                token_in.clone(),
+                token_out.clone(),
+            )
+        };
+
+        // WETH and WBTC are consolidated into USDC, WBTC through DAI
+        let swaps = vec![
+            swap("pool1", &weth, &usdc),
+            swap("pool2", &wbtc, &dai),
+            swap("pool3", &dai, &usdc),
+        ];
+        let result = validator.validate_multi_input_swap_path(
+            &swaps,
+            &[&weth, &wbtc],
+            &usdc,
+            &None,
+            &eth,
+            &weth,
+        );
+        assert_eq!(result, Ok(()));
+
+        // DAI is given but never sold
+        let result = validator.validate_multi_input_swap_path(
+            &swaps,
+            &[&weth, &wbtc, &dai],
+            &usdc,
+            &None,
+            &eth,
+            &weth,
+        );
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+        // WBTC is given but also bought
+        let swaps = vec![swap("pool1", &weth, &wbtc), swap("pool2", &wbtc, &usdc)];
+        let result = validator.validate_multi_input_swap_path(
+            &swaps,
+            &[&weth, &wbtc],
+            &usdc,
+            &None,
+            &eth,
+            &weth,
+        );
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    fn sdai_swap(token_in: &Bytes, token_out: &Bytes) -> Swap {
+        // 115 DAI back 100 sDAI
+        Swap::new(
+            ProtocolComponent {
+                id: "0x83F20F44975D03b1b09e64809B757c47f942BEeA".to_string(),
+                protocol_system: "erc4626".to_string(),
+                static_attributes: HashMap::from([
+                    ("total_assets".to_string(), Bytes::from(vec![115u8])),
+                    ("total_supply".to_string(), Bytes::from(vec![100u8])),
+                ]),
+                ..Default::default()
+            },
+            token_in.clone(),
+            token_out.clone(),
+        )
+    }
+
+    #[test]
+    fn test_validate_vault_conversions_deposit() {
+        // DAI ──(sDAI deposit)──> sDAI ──(USV2)──> USDC
+        let validator = SequentialSwapValidator;
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let sdai = Bytes::from_str("0x83F20F44975D03b1b09e64809B757c47f942BEeA").unwrap();
+        let usdc = Bytes::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+        let solution = |sdai_amount: u32| Solution {
+            given_token: dai.clone(),
+            given_amount: BigUint::from(1150u32),
+            checked_token: usdc.clone(),
+            checked_amount: BigUint::from(1u32),
+            swaps: vec![
+                sdai_swap(&dai, &sdai),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0xAE461cA67B15dc8dc81CE7615e0320dA1A9aB8D5".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    sdai.clone(),
+                    usdc.clone(),
+                )
+                .estimated_amount_in(BigUint::from(sdai_amount)),
+            ],
+            ..Default::default()
+        };
+
+        // 1150 DAI are deposited for 1000 sDAI
+        assert!(validator
+            .validate_vault_conversions(&solution(1000), Chain::Ethereum)
+            .is_ok());
+        // The DAI amount was propagated as the sDAI amount
+        assert!(matches!(
+            validator.validate_vault_conversions(&solution(1150), Chain::Ethereum),
+            Err(EncodingError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_vault_conversions_redeem() {
+        // sDAI ──(sDAI redeem)──> DAI
+        let validator = SequentialSwapValidator;
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let sdai = Bytes::from_str("0x83F20F44975D03b1b09e64809B757c47f942BEeA").unwrap();
+        let solution = |checked_amount: u32| Solution {
+            given_token: sdai.clone(),
+            given_amount: BigUint::from(1000u32),
+            checked_token: dai.clone(),
+            checked_amount: BigUint::from(checked_amount),
+            swaps: vec![sdai_swap(&sdai, &dai)],
+            ..Default::default()
+        };
+
+        // 1000 sDAI are redeemed for 1150 DAI
+        assert!(validator
+            .validate_vault_conversions(&solution(1140), Chain::Ethereum)
+            .is_ok());
+        assert!(matches!(
+            validator.validate_vault_conversions(&solution(1200), Chain::Ethereum),
+            Err(EncodingError::InvalidInput(_))
+        ));
+
+        // Vaults without a known rate are skipped
+        let swap = Swap::new(
+            ProtocolComponent {
+                id: "0x83F20F44975D03b1b09e64809B757c47f942BEeA".to_string(),
+                protocol_system: "erc4626".to_string(),
+                ..Default::default()
+            },
+            sdai.clone(),
+            dai.clone(),
+        );
+        let solution = Solution { swaps: vec![swap], ..solution(1200) };
+        assert!(validator
+            .validate_vault_conversions(&solution, Chain::Ethereum)
+            .is_ok());
+    }
 }
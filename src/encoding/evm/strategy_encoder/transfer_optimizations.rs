@@ -1,14 +1,13 @@
-use std::str::FromStr;
-
+use num_bigint::BigUint;
 use tycho_common::Bytes;
 
 use crate::encoding::{
     errors::EncodingError,
     evm::{
-        constants::{CALLBACK_CONSTRAINED_PROTOCOLS, FUNDS_IN_ROUTER_PROTOCOLS},
-        group_swaps::SwapGroup,
+        component_id::component_pool_address, encoding_utils::native_call_value,
+        group_swaps::SwapGroup, swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
     },
-    models::{TransferType, UserTransferType},
+    models::{Solution, TransferType, UserTransferType},
 };
 
 /// A struct that defines how the tokens will be transferred into the given pool given the solution.
@@ -31,18 +30,30 @@ impl TransferOptimization {
     }
 
     /// Returns the transfer type that should be used for the current transfer.
+    ///
+    /// The tokens are transferred to the pool unless the encoder of its protocol has the
+    /// `funds_in_router` capability (see `SwapEncoder::capabilities`).
     pub fn get_transfers(
         &self,
         swap: &SwapGroup,
         given_token: &Bytes,
         wrap: bool,
         in_between_swap_optimization: bool,
+        swap_encoder_registry: &SwapEncoderRegistry,
     ) -> TransferType {
         let is_first_swap = swap.token_in == *given_token;
-        let in_transfer_required: bool =
-            !FUNDS_IN_ROUTER_PROTOCOLS.contains(&swap.protocol_system.as_str());
+        let in_transfer_required: bool = !swap_encoder_registry
+            .capabilities(&swap.protocol_system)
+            .is_some_and(|capabilities| capabilities.funds_in_router);
 
-        if swap.token_in == self.native_token {
+        if swap
+            .swaps
+            .iter()
+            .all(|swap| swap.is_registration())
+        {
+            // Registration swaps don't move any funds
+            TransferType::None
+        } else if swap.token_in == self.native_token {
             // Funds are already in router. All protocols currently take care of native transfers.
             TransferType::None
         } else if (swap.token_in == self.wrapped_token) && wrap {
@@ -74,6 +85,112 @@ impl TransferOptimization {
         }
     }
 
+    /// Checks that the transfers of the swap groups, given with their token in, respect the
+    /// restrictions of the router on the transfers from the user (see `RestrictTransferFrom`),
+    /// which would otherwise make the solution revert:
+    /// * With `UserTransferType::None`, the tokens are already in the router, so nothing can be
+    ///   transferred from the user.
+    /// * Otherwise, only the given token can be transferred from the user, up to the given amount.
+    ///   With `single_transfer_from`, only one swap can thus transfer it (e.g. in sequential
+    ///   solutions, where every swap takes the full amount out of the previous one).
+    pub fn validate_transfers(
+        &self,
+        transfers: &[(TransferType, &Bytes)],
+        given_token: &Bytes,
+        single_transfer_from: bool,
+    ) -> Result<(), EncodingError> {
+        let transfers_from: Vec<&Bytes> = transfers
+            .iter()
+            .filter(|(transfer_type, _)| *transfer_type == TransferType::TransferFrom)
+            .map(|(_, token_in)| *token_in)
+            .collect();
+        if transfers_from.is_empty() {
+            return Ok(());
+        }
+        if self.user_transfer_type == UserTransferType::None {
+            return Err(EncodingError::InvalidInput(
+                "The tokens in are already in the router (UserTransferType::None), so no swap can \
+                 transfer them from the user"
+                    .to_string(),
+            ));
+        }
+        if let Some(token_in) = transfers_from
+            .iter()
+            .find(|token_in| **token_in != given_token)
+        {
+            return Err(EncodingError::InvalidInput(format!(
+                "Only the given token {given_token} can be transferred from the user. Found a \
+                 transfer of {token_in}"
+            )));
+        }
+        if single_transfer_from && transfers_from.len() > 1 {
+            return Err(EncodingError::InvalidInput(format!(
+                "The given token {given_token} is transferred from the user by {} swaps, while the \
+                 router only allows the given amount to be transferred. Swap it once, or route \
+                 the swaps of the given token after the first one through another token",
+                transfers_from.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks how the native token flows through the swap groups, given with their token in,
+    /// token out and receiver in the order they are executed.
+    ///
+    /// Executors taking the native token in forward it from the router balance as the value of the
+    /// protocol call, so the router must hold it when they run: sent with the router call (see
+    /// `native_call_value`) and not wrapped, or sent to the router by an earlier group. Raises an
+    /// error if:
+    /// * A group takes the native token in while the router holds none.
+    /// * A group sends the native token taken by a later group anywhere but to the router (e.g. to
+    ///   the pool of the next group), where no executor can forward it from.
+    /// * A group outputs the native token which no later group takes and which is not the token
+    ///   bought by the solution, so that it would be stranded in the router (e.g. the ETH of a USDC
+    ///   -> ETH -> stETH solution whose Lido swap is missing).
+    pub fn validate_native_flow(
+        &self,
+        flows: &[(&Bytes, &Bytes, &Bytes)],
+        solution: &Solution,
+        wrap: bool,
+        unwrap: bool,
+    ) -> Result<(), EncodingError> {
+        let mut native_in_router =
+            !wrap && native_call_value(solution, &self.native_token) != BigUint::ZERO;
+        let bought_token =
+            if solution.exact_out { &solution.given_token } else { &solution.checked_token };
+        let native_bought = *bought_token == self.native_token && !unwrap;
+        for (i, (token_in, token_out, receiver)) in flows.iter().enumerate() {
+            if **token_in == self.native_token && !native_in_router {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Swap group {i} takes the native token in, which is forwarded from the router \
+                     balance, but the router holds none: it is neither sent with the call nor \
+                     sent to the router by an earlier swap"
+                )));
+            }
+            if **token_out != self.native_token {
+                continue;
+            }
+            let taken_later = flows[i + 1..]
+                .iter()
+                .any(|(later_token_in, _, _)| **later_token_in == self.native_token);
+            if taken_later {
+                if **receiver != self.router_address {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Swap group {i} sends the native token to {receiver}, while a later swap \
+                         takes it from the router balance"
+                    )));
+                }
+                native_in_router = true;
+            } else if !native_bought {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Swap group {i} outputs the native token, which no later swap takes and which \
+                     is not the token bought by the solution. It would be stranded in the router"
+                )));
+            }
+        }
+        Ok(())
+    }
+
     // Returns the optimized receiver of the swap. This is used to chain swaps together and avoid
     // unnecessary token transfers.
     // Returns the receiver address and a boolean indicating whether the receiver is optimized (this
@@ -83,19 +200,21 @@ impl TransferOptimization {
         solution_receiver: &Bytes,
         next_swap: Option<&SwapGroup>,
         unwrap: bool,
+        swap_encoder_registry: &SwapEncoderRegistry,
     ) -> Result<(Bytes, bool), EncodingError> {
         if let Some(next) = next_swap {
+            let capabilities = swap_encoder_registry
+                .capabilities(&next.protocol_system)
+                .unwrap_or_default();
             // if the protocol of the next swap supports transfer in optimization
-            if !FUNDS_IN_ROUTER_PROTOCOLS.contains(&next.protocol_system.as_str()) {
+            if !capabilities.funds_in_router {
                 // if the protocol does not allow for chained swaps, we can't optimize the
                 // receiver of this swap nor the transfer in of the next swap
-                if CALLBACK_CONSTRAINED_PROTOCOLS.contains(&next.protocol_system.as_str()) {
+                if capabilities.callback_constrained {
                     Ok((self.router_address.clone(), false))
                 } else {
                     Ok((
-                        Bytes::from_str(&next.swaps[0].component().id.clone()).map_err(|_| {
-                            EncodingError::FatalError("Invalid component id".to_string())
-                        })?,
+                        Bytes::from(component_pool_address(next.swaps[0].component())?.to_vec()),
                         true,
                     ))
                 }
@@ -116,9 +235,11 @@ impl TransferOptimization {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+
     use alloy::primitives::hex;
     use rstest::rstest;
-    use tycho_common::models::protocol::ProtocolComponent;
+    use tycho_common::models::{protocol::ProtocolComponent, Chain};
 
     use super::*;
     use crate::encoding::models::Swap;
@@ -143,6 +264,14 @@ mod tests {
         Bytes::from("0x5615deb798bb3e4dfa0139dfa1b3d433cc23b72f")
     }
 
+    fn registry() -> SwapEncoderRegistry {
+        let executors_addresses =
+            fs::read_to_string("config/test_executor_addresses.json").unwrap();
+        SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap()
+    }
+
     #[rstest]
     // First swap tests
     // WETH -(univ2)-> DAI we expect a transfer from the user to the protocol
@@ -192,11 +321,132 @@ mod tests {
         };
         let optimization =
             TransferOptimization::new(eth(), weth(), user_transfer_type, router_address());
-        let transfer =
-            optimization.get_transfers(&swap, &given_token, wrap, in_between_swap_optimization);
+        let transfer = optimization.get_transfers(
+            &swap,
+            &given_token,
+            wrap,
+            in_between_swap_optimization,
+            &registry(),
+        );
         assert_eq!(transfer, expected_transfer);
     }
 
+    #[test]
+    fn test_get_transfers_registration_swap() {
+        let swaps = vec![Swap::new(
+            ProtocolComponent {
+                protocol_system: "rfq:bebop".to_string(),
+                id: "bebop-rfq".to_string(),
+                ..Default::default()
+            },
+            usdc(),
+            dai(),
+        )
+        .registration(true)];
+        let swap = SwapGroup {
+            protocol_system: "rfq:bebop".to_string(),
+            token_in: usdc(),
+            token_out: dai(),
+            split: 0f64,
+            swaps,
+        };
+        let optimization = TransferOptimization::new(
+            eth(),
+            weth(),
+            UserTransferType::TransferFrom,
+            router_address(),
+        );
+        let transfer = optimization.get_transfers(&swap, &usdc(), false, false, &registry());
+        assert_eq!(transfer, TransferType::None);
+    }
+
+    #[test]
+    fn test_validate_transfers() {
+        let optimization = |user_transfer_type| {
+            TransferOptimization::new(eth(), weth(), user_transfer_type, router_address())
+        };
+        let (weth, usdc) = (weth(), usdc());
+        let transfer_from_weth = (TransferType::TransferFrom, &weth);
+
+        let user_transfers = optimization(UserTransferType::TransferFrom);
+        assert!(user_transfers
+            .validate_transfers(&[transfer_from_weth, (TransferType::Transfer, &usdc)], &weth, true)
+            .is_ok());
+        // The splits of the given token share the given amount
+        assert!(user_transfers
+            .validate_transfers(&[transfer_from_weth, transfer_from_weth], &weth, false)
+            .is_ok());
+        // A sequential solution swapping the given token twice
+        let result = user_transfers.validate_transfers(
+            &[transfer_from_weth, transfer_from_weth],
+            &weth,
+            true,
+        );
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        let result =
+            user_transfers.validate_transfers(&[(TransferType::TransferFrom, &usdc)], &weth, false);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+        let result = optimization(UserTransferType::None).validate_transfers(
+            &[transfer_from_weth],
+            &weth,
+            false,
+        );
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_validate_native_flow() {
+        let optimization = TransferOptimization::new(
+            eth(),
+            weth(),
+            UserTransferType::TransferFrom,
+            router_address(),
+        );
+        let (eth, usdc, router, pool) = (eth(), usdc(), router_address(), receiver());
+        let sell_usdc = Solution {
+            given_token: usdc.clone(),
+            given_amount: BigUint::from(1000u32),
+            checked_token: dai(),
+            ..Default::default()
+        };
+
+        // USDC -> ETH -> DAI, the ETH is sent to the router for the second swap to forward it
+        assert!(optimization
+            .validate_native_flow(
+                &[(&usdc, &eth, &router), (&eth, &dai(), &pool)],
+                &sell_usdc,
+                false,
+                false
+            )
+            .is_ok());
+        // The ETH is sent to the pool of the second swap instead
+        let result = optimization.validate_native_flow(
+            &[(&usdc, &eth, &pool), (&eth, &dai(), &pool)],
+            &sell_usdc,
+            false,
+            false,
+        );
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        // No swap takes the ETH bought by the first swap
+        let result = optimization.validate_native_flow(
+            &[(&usdc, &eth, &router), (&usdc, &dai(), &pool)],
+            &sell_usdc,
+            false,
+            false,
+        );
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+        // ETH sent with the call, unless the router wraps it
+        let sell_eth = Solution { given_token: eth.clone(), ..sell_usdc };
+        assert!(optimization
+            .validate_native_flow(&[(&eth, &dai(), &pool)], &sell_eth, false, false)
+            .is_ok());
+        let result =
+            optimization.validate_native_flow(&[(&eth, &dai(), &pool)], &sell_eth, true, false);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
     fn receiver() -> Bytes {
         Bytes::from("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2")
     }
@@ -249,7 +499,8 @@ mod tests {
             })
         };
 
-        let result = optimization.get_receiver(&receiver(), next_swap.as_ref(), unwrap);
+        let result =
+            optimization.get_receiver(&receiver(), next_swap.as_ref(), unwrap, &registry());
 
         assert!(result.is_ok());
         let (actual_receiver, optimization_flag) = result.unwrap();
@@ -0,0 +1,187 @@
+use std::{collections::HashMap, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError, evm::constants::PROTOCOL_SPECIFIC_CONFIG, models::NativeAction,
+};
+
+/// Key of the protocol specific config (see `PROTOCOL_SPECIFIC_CONFIG`) holding the router
+/// settings of a chain.
+const ROUTER_CONFIG_KEY: &str = "tycho_router";
+
+/// How the wrapped native token of a chain is wrapped and unwrapped.
+///
+/// The router wraps the native token in and unwraps the wrapped token out of the solutions with a
+/// native action. Wrapped native tokens sharing the WETH9 interface (e.g. WETH, WPOL or WAVAX) only
+/// differ in their address, which is taken from the chain. The others can't be wrapped by the
+/// router.
+///
+/// The variant of a chain is set with the `wrap_variant` key of its `tycho_router` config, and
+/// defaults to `Weth9`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapVariant {
+    /// The wrapped native token implements WETH9's `deposit()` and `withdraw(uint256)`, called by
+    /// the router.
+    #[default]
+    Weth9,
+    /// The wrapped native token has a different interface, so the router can't wrap or unwrap it.
+    /// Solutions with a native action are rejected, as their router call would revert. They can
+    /// swap through the wrapping contract instead, if a protocol supports it.
+    Unsupported,
+}
+
+impl FromStr for WrapVariant {
+    type Err = EncodingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "weth9" => Ok(WrapVariant::Weth9),
+            "unsupported" => Ok(WrapVariant::Unsupported),
+            _ => Err(EncodingError::FatalError(format!("Unknown wrap variant: {s}"))),
+        }
+    }
+}
+
+impl WrapVariant {
+    /// Returns the wrap variant configured for the chain, or `Weth9` if none is configured.
+    pub fn for_chain(chain: Chain) -> Result<Self, EncodingError> {
+        let protocol_specific_config: HashMap<Chain, HashMap<String, HashMap<String, String>>> =
+            serde_json::from_str(PROTOCOL_SPECIFIC_CONFIG)?;
+        protocol_specific_config
+            .get(&chain)
+            .and_then(|config| config.get(ROUTER_CONFIG_KEY))
+            .and_then(|config| config.get("wrap_variant"))
+            .map_or(Ok(WrapVariant::default()), |variant| variant.parse())
+    }
+
+    /// Returns whether the router must wrap the native token in and unwrap the wrapped token out
+    /// of a solution with the given native action, as `(wrap, unwrap)`.
+    ///
+    /// Returns an `EncodingError::InvalidInput` if the native token can't be wrapped by the router.
+    pub fn native_action_flags(
+        &self,
+        native_action: Option<&NativeAction>,
+    ) -> Result<(bool, bool), EncodingError> {
+        match (self, native_action) {
+            (_, None) => Ok((false, false)),
+            (WrapVariant::Weth9, Some(NativeAction::Wrap)) => Ok((true, false)),
+            (WrapVariant::Weth9, Some(NativeAction::Unwrap)) => Ok((false, true)),
+            (WrapVariant::Unsupported, Some(action)) => Err(EncodingError::InvalidInput(format!(
+                "The router can't {} the native token on this chain. Swap through the wrapping \
+                 contract instead",
+                match action {
+                    NativeAction::Wrap => "wrap",
+                    NativeAction::Unwrap => "unwrap",
+                }
+            ))),
+        }
+    }
+}
+
+/// The native token of a chain and its wrapped token, as wrapped and unwrapped by the router.
+///
+/// They are set with the `native_token_address` and `wrapped_native_token_address` keys of the
+/// `tycho_router` config of the chain, and default to the tokens of the chain in `tycho_common`.
+/// Chains whose tokens aren't known to `tycho_common` must configure both, as
+/// `Chain::native_token` panics for them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainTokens {
+    pub native: Bytes,
+    pub wrapped: Bytes,
+}
+
+impl ChainTokens {
+    pub fn for_chain(chain: Chain) -> Result<Self, EncodingError> {
+        let protocol_specific_config: HashMap<Chain, HashMap<String, HashMap<String, String>>> =
+            serde_json::from_str(PROTOCOL_SPECIFIC_CONFIG)?;
+        let router_config = protocol_specific_config
+            .get(&chain)
+            .and_then(|config| config.get(ROUTER_CONFIG_KEY));
+        let configured_token = |key: &str| {
+            router_config
+                .and_then(|config| config.get(key))
+                .map(|address| {
+                    Bytes::from_str(address).map_err(|_| {
+                        EncodingError::FatalError(format!(
+                            "Invalid {key} in the router config of {chain:?}"
+                        ))
+                    })
+                })
+                .transpose()
+        };
+        Ok(ChainTokens {
+            native: match configured_token("native_token_address")? {
+                Some(native) => native,
+                None => chain.native_token().address,
+            },
+            wrapped: match configured_token("wrapped_native_token_address")? {
+                Some(wrapped) => wrapped,
+                None => chain.wrapped_native_token().address,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_variant_for_chain() {
+        assert_eq!(WrapVariant::for_chain(Chain::Ethereum).unwrap(), WrapVariant::Weth9);
+        // Chains without a router config default to WETH9
+        assert_eq!(WrapVariant::for_chain(Chain::Arbitrum).unwrap(), WrapVariant::Weth9);
+        assert_eq!(
+            "unsupported"
+                .parse::<WrapVariant>()
+                .unwrap(),
+            WrapVariant::Unsupported
+        );
+        assert!("weth10".parse::<WrapVariant>().is_err());
+    }
+
+    #[test]
+    fn test_chain_tokens_for_chain() {
+        let chain_tokens = ChainTokens::for_chain(Chain::Ethereum).unwrap();
+        assert_eq!(chain_tokens.native, Chain::Ethereum.native_token().address);
+        assert_eq!(
+            chain_tokens.wrapped,
+            Chain::Ethereum
+                .wrapped_native_token()
+                .address
+        );
+    }
+
+    #[test]
+    fn test_native_action_flags() {
+        let weth9 = WrapVariant::Weth9;
+        assert_eq!(weth9.native_action_flags(None).unwrap(), (false, false));
+        assert_eq!(
+            weth9
+                .native_action_flags(Some(&NativeAction::Wrap))
+                .unwrap(),
+            (true, false)
+        );
+        assert_eq!(
+            weth9
+                .native_action_flags(Some(&NativeAction::Unwrap))
+                .unwrap(),
+            (false, true)
+        );
+
+        let unsupported = WrapVariant::Unsupported;
+        assert_eq!(
+            unsupported
+                .native_action_flags(None)
+                .unwrap(),
+            (false, false)
+        );
+        assert!(matches!(
+            unsupported.native_action_flags(Some(&NativeAction::Unwrap)),
+            Err(EncodingError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        constants::PROTOCOL_CAPABILITIES_JSON, strategy_encoder::wrap_adapter::ChainTokens,
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+    },
+    models::Solution,
+};
+
+/// The features the executor of a protocol supports (config/protocol_capabilities.json).
+///
+/// # Fields
+/// * `exact_out`: Whether the executor supports exact out swaps, i.e. swaps for a given amount out
+/// * `grouping`: Whether consecutive swaps of the protocol can be grouped into a single call to its
+///   executor
+/// * `native`: Whether the pools of the protocol can take or output the native token itself
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProtocolCapabilities {
+    pub exact_out: bool,
+    pub grouping: bool,
+    pub native: bool,
+}
+
+/// A protocol executable by the router of a chain.
+///
+/// # Fields
+/// * `system`: Protocol system, as named by the indexer (e.g. `uniswap_v2` or `vm:balancer_v3`)
+/// * `executor_address`: Address of the executor of the protocol on the chain
+/// * `capabilities`: Features supported by the executor
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProtocolInfo {
+    pub system: String,
+    pub executor_address: Bytes,
+    pub capabilities: ProtocolCapabilities,
+}
+
+/// The protocols supported by the router of a chain, with their executor and capabilities.
+///
+/// Built from the executors of the registry (the protocols deployed on the chain) and the
+/// protocol capabilities config. Solutions using a protocol that isn't deployed on the chain, or a
+/// feature its executor doesn't support, are rejected with a descriptive error instead of being
+/// encoded into calldata that reverts.
+///
+/// # Fields
+/// * `chain`: Chain of the router
+/// * `executors`: Executor address of every protocol deployed on the chain, by protocol system
+/// * `capabilities`: Capabilities of every protocol deployed on the chain, by protocol system
+#[derive(Clone, Debug)]
+pub struct SupportMatrix {
+    chain: Chain,
+    executors: HashMap<String, Bytes>,
+    capabilities: HashMap<String, ProtocolCapabilities>,
+}
+
+impl SupportMatrix {
+    /// Builds the matrix of the protocols of the registry, with the capabilities of the given
+    /// config (keyed by protocol system, like the default config/protocol_capabilities.json).
+    ///
+    /// Returns an `EncodingError::FatalError` if a protocol of the registry has no capabilities
+    /// configured or its executor is the zero address.
+    pub fn new(
+        chain: Chain,
+        swap_encoder_registry: &SwapEncoderRegistry,
+        protocol_capabilities: Option<String>,
+    ) -> Result<Self, EncodingError> {
+        let config_str = if let Some(capabilities) = protocol_capabilities {
+            capabilities
+        } else {
+            PROTOCOL_CAPABILITIES_JSON.to_string()
+        };
+        let mut config: HashMap<String, ProtocolCapabilities> = serde_json::from_str(&config_str)?;
+        let executors = swap_encoder_registry.executor_addresses();
+        let mut capabilities = HashMap::new();
+        for (protocol, executor) in &executors {
+            if executor
+                .as_ref()
+                .iter()
+                .all(|byte| *byte == 0)
+            {
+                return Err(EncodingError::FatalError(format!(
+                    "The executor of protocol {protocol} on chain {chain:?} is the zero address"
+                )));
+            }
+            let protocol_capabilities = config.remove(protocol).ok_or_else(|| {
+                EncodingError::FatalError(format!(
+                    "No capabilities configured for protocol {protocol} on chain {chain:?}"
+                ))
+            })?;
+            capabilities.insert(protocol.clone(), protocol_capabilities);
+        }
+        Ok(Self { chain, executors, capabilities })
+    }
+
+    /// Returns the protocols of the registry, with their capabilities in the default config
+    /// (none for the protocols it doesn't configure), sorted by protocol system.
+    pub fn registry_protocols(swap_encoder_registry: &SwapEncoderRegistry) -> Vec<ProtocolInfo> {
+        let mut config: HashMap<String, ProtocolCapabilities> =
+            serde_json::from_str(PROTOCOL_CAPABILITIES_JSON).unwrap_or_default();
+        let mut protocols: Vec<ProtocolInfo> = swap_encoder_registry
+            .executor_addresses()
+            .into_iter()
+            .map(|(system, executor_address)| ProtocolInfo {
+                capabilities: config
+                    .remove(&system)
+                    .unwrap_or_default(),
+                system,
+                executor_address,
+            })
+            .collect();
+        protocols.sort_by(|a, b| a.system.cmp(&b.system));
+        protocols
+    }
+
+    /// Returns the protocols deployed on the chain, sorted by protocol system.
+    pub fn protocols(&self) -> Vec<ProtocolInfo> {
+        let mut protocols: Vec<ProtocolInfo> = self
+            .executors
+            .iter()
+            .map(|(system, executor_address)| ProtocolInfo {
+                system: system.clone(),
+                executor_address: executor_address.clone(),
+                capabilities: self.capabilities[system],
+            })
+            .collect();
+        protocols.sort_by(|a, b| a.system.cmp(&b.system));
+        protocols
+    }
+
+    /// Returns the executor of the protocol on the chain.
+    ///
+    /// Returns an `EncodingError::InvalidInput` if the protocol isn't deployed on the chain.
+    pub fn executor_address(&self, protocol_system: &str) -> Result<&Bytes, EncodingError> {
+        self.executors
+            .get(protocol_system)
+            .ok_or_else(|| self.not_deployed(protocol_system))
+    }
+
+    /// Returns the capabilities of the protocol on the chain.
+    ///
+    /// Returns an `EncodingError::InvalidInput` if the protocol isn't deployed on the chain.
+    pub fn capabilities(
+        &self,
+        protocol_system: &str,
+    ) -> Result<&ProtocolCapabilities, EncodingError> {
+        self.capabilities
+            .get(protocol_system)
+            .ok_or_else(|| self.not_deployed(protocol_system))
+    }
+
+    /// Raises an `EncodingError::InvalidInput` if the solution can't be executed by the router of
+    /// the chain.
+    ///
+    /// A solution is supported if all the following conditions are met:
+    /// * The protocol of every swap is deployed on the chain.
+    /// * If the solution is exact out, the protocols of its swaps support exact out swaps.
+    /// * Only the protocols supporting the native token have swaps taking or outputting it.
+    pub fn validate_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
+        let native_address = ChainTokens::for_chain(self.chain)?.native;
+        for (i, swap) in solution.swaps.iter().enumerate() {
+            let protocol_system = &swap.component().protocol_system;
+            let capabilities = self.capabilities(protocol_system)?;
+            if solution.exact_out && !capabilities.exact_out {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Swap {i} is on protocol {protocol_system}, whose executor on chain {:?} \
+                     doesn't support exact out swaps",
+                    self.chain
+                )));
+            }
+            if !capabilities.native &&
+                (*swap.token_in() == native_address || *swap.token_out() == native_address)
+            {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Swap {i} on protocol {protocol_system} takes or outputs the native token, \
+                     which its executor on chain {:?} doesn't support. Use the wrapped token \
+                     instead.",
+                    self.chain
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn not_deployed(&self, protocol_system: &str) -> EncodingError {
+        let mut deployed: Vec<&str> = self
+            .executors
+            .keys()
+            .map(String::as_str)
+            .collect();
+        deployed.sort_unstable();
+        EncodingError::InvalidInput(format!(
+            "Protocol {protocol_system} is not deployed on chain {:?}. Deployed protocols: {}",
+            self.chain,
+            deployed.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use num_bigint::BigUint;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::{
+        evm::constants::{EXACT_OUT_PROTOCOLS, NATIVE_KEYED_PROTOCOLS},
+        models::Swap,
+    };
+
+    fn support_matrix(chain: Chain) -> SupportMatrix {
+        let executors_addresses = fs::read_to_string("config/executor_addresses.json").unwrap();
+        let registry = SwapEncoderRegistry::new(chain)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap();
+        SupportMatrix::new(chain, &registry, None).unwrap()
+    }
+
+    fn solution(protocol_system: &str, token_in: Bytes, exact_out: bool) -> Solution {
+        let dai = Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f");
+        let swap = Swap::new(
+            ProtocolComponent {
+                id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                protocol_system: protocol_system.to_string(),
+                ..Default::default()
+            },
+            token_in.clone(),
+            dai.clone(),
+        );
+        Solution {
+            exact_out,
+            given_token: token_in,
+            given_amount: BigUint::from(1000u32),
+            checked_token: dai,
+            swaps: vec![swap],
+            ..Default::default()
+        }
+    }
+
+    fn weth() -> Bytes {
+        Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+    }
+
+    #[test]
+    fn test_default_capabilities_match_the_encoders() {
+        let config: HashMap<String, ProtocolCapabilities> =
+            serde_json::from_str(PROTOCOL_CAPABILITIES_JSON).unwrap();
+        let executors_addresses = fs::read_to_string("config/executor_addresses.json").unwrap();
+        let registries: Vec<SwapEncoderRegistry> = [Chain::Ethereum, Chain::Base, Chain::Unichain]
+            .into_iter()
+            .map(|chain| {
+                SwapEncoderRegistry::new(chain)
+                    .add_default_encoders(Some(executors_addresses.clone()))
+                    .unwrap()
+            })
+            .collect();
+
+        for (protocol, capabilities) in &config {
+            assert_eq!(
+                capabilities.exact_out,
+                EXACT_OUT_PROTOCOLS.contains(protocol.as_str()),
+                "{protocol}"
+            );
+            assert_eq!(
+                capabilities.grouping,
+                registries
+                    .iter()
+                    .find_map(|registry| registry.capabilities(protocol))
+                    .is_some_and(|encoder_capabilities| encoder_capabilities.groupable),
+                "{protocol}"
+            );
+        }
+        for protocol in NATIVE_KEYED_PROTOCOLS.iter() {
+            assert!(config[*protocol].native, "{protocol}");
+        }
+    }
+
+    #[test]
+    fn test_support_matrix_of_every_chain() {
+        for chain in [Chain::Ethereum, Chain::Base, Chain::Unichain] {
+            support_matrix(chain);
+        }
+    }
+
+    #[test]
+    fn test_executor_address() {
+        let ethereum = support_matrix(Chain::Ethereum);
+        let base = support_matrix(Chain::Base);
+
+        assert!(ethereum
+            .executor_address("vm:balancer_v2")
+            .is_ok());
+        assert!(matches!(
+            base.executor_address("vm:balancer_v2"),
+            Err(EncodingError::InvalidInput(msg))
+                if msg.starts_with("Protocol vm:balancer_v2 is not deployed on chain Base")
+        ));
+    }
+
+    #[test]
+    fn test_validate_solution() {
+        let matrix = support_matrix(Chain::Ethereum);
+        let eth = Chain::Ethereum.native_token().address;
+
+        assert!(matrix
+            .validate_solution(&solution("uniswap_v2", weth(), false))
+            .is_ok());
+        assert!(matrix
+            .validate_solution(&solution("uniswap_v3", weth(), true))
+            .is_ok());
+        assert!(matrix
+            .validate_solution(&solution("uniswap_v4", eth.clone(), false))
+            .is_ok());
+        assert!(matches!(
+            matrix.validate_solution(&solution("vm:balancer_v2", weth(), true)),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("doesn't support exact out")
+        ));
+        assert!(matches!(
+            matrix.validate_solution(&solution("uniswap_v2", eth, false)),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("takes or outputs the native")
+        ));
+        assert!(matches!(
+            matrix.validate_solution(&solution("aerodrome_slipstreams", weth(), false)),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("is not deployed")
+        ));
+    }
+
+    #[test]
+    fn test_protocols() {
+        let matrix = support_matrix(Chain::Ethereum);
+        let protocols = matrix.protocols();
+
+        assert!(protocols
+            .windows(2)
+            .all(|pair| pair[0].system < pair[1].system));
+        let balancer_v2 = protocols
+            .iter()
+            .find(|protocol| protocol.system == "vm:balancer_v2")
+            .unwrap();
+        assert_eq!(
+            &balancer_v2.executor_address,
+            matrix
+                .executor_address("vm:balancer_v2")
+                .unwrap()
+        );
+        assert!(!balancer_v2.capabilities.exact_out);
+
+        let executors_addresses = fs::read_to_string("config/executor_addresses.json").unwrap();
+        let registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap();
+        assert_eq!(SupportMatrix::registry_protocols(&registry), protocols);
+    }
+
+    #[test]
+    fn test_missing_capabilities() {
+        let executors_addresses = fs::read_to_string("config/executor_addresses.json").unwrap();
+        let registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap();
+
+        let capabilities =
+            r#"{"uniswap_v2": {"exact_out": true, "grouping": false, "native": false}}"#;
+
+        let result = SupportMatrix::new(Chain::Ethereum, &registry, Some(capabilities.to_string()));
+
+        assert!(matches!(
+            result,
+            Err(EncodingError::FatalError(msg)) if msg.starts_with("No capabilities configured")
+        ));
+    }
+}
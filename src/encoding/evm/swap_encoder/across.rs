@@ -0,0 +1,251 @@
+use std::{collections::HashMap, str::FromStr};
+
+use alloy::{
+    primitives::{Address, U256},
+    sol_types::SolValue,
+};
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::utils::bytes_to_address,
+    models::{EncodingContext, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+/// Encodes an Across V3 deposit bridging the output of a solution to another chain.
+///
+/// The deposit must be the last swap of the solution (see `TychoRouterEncoder`). Its component id
+/// is the spoke pool of the chain, and its `user_data` is the ABI encoded
+/// `(uint256 destinationChainId, address recipient, uint16 relayFeeBps)`: the token out is
+/// delivered to the recipient on the destination chain, minus the relay fee. The receiver of the
+/// swap is the depositor, refunded on this chain if the deposit isn't filled.
+///
+/// Nothing is received on this chain, so the router checks the amount deposited into the spoke
+/// pool instead of the amount received by the receiver.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `spoke_pool_address` - The address of the Across spoke pool of the chain.
+/// * `native_token_address` - The address of the native token of the chain.
+#[derive(Clone)]
+pub struct BridgeSwapEncoder {
+    executor_address: Bytes,
+    spoke_pool_address: Bytes,
+    native_token_address: Bytes,
+}
+
+impl BridgeSwapEncoder {
+    /// Decodes the destination chain id, the recipient and the relay fee (in bps) of the deposit
+    /// from the swap's user data.
+    fn deposit_params(&self, swap: &Swap) -> Result<(U256, Address, u16), EncodingError> {
+        let Some(user_data) = swap.get_user_data() else {
+            return Err(EncodingError::InvalidInput(
+                "Across deposits need the destination chain id, recipient and relay fee in the \
+                 user data"
+                    .to_string(),
+            ));
+        };
+        let (destination_chain_id, recipient, relay_fee_bps) =
+            <(U256, Address, u16)>::abi_decode(user_data).map_err(|e| {
+                EncodingError::InvalidInput(format!(
+                    "Failed to decode Across deposit from user data: {e}"
+                ))
+            })?;
+        if recipient == Address::ZERO {
+            return Err(EncodingError::InvalidInput(
+                "Across deposit recipient can't be the zero address".to_string(),
+            ));
+        }
+        if relay_fee_bps >= 10_000 {
+            return Err(EncodingError::InvalidInput(format!(
+                "Across relay fee must be below 10000 bps. Found {relay_fee_bps}"
+            )));
+        }
+        Ok((destination_chain_id, recipient, relay_fee_bps))
+    }
+}
+
+impl SwapEncoder for BridgeSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        let config = config.ok_or(EncodingError::FatalError(
+            "Missing Across specific addresses in config".to_string(),
+        ))?;
+        let spoke_pool_address = config
+            .get("spoke_pool_address")
+            .map(|s| {
+                Bytes::from_str(s).map_err(|_| {
+                    EncodingError::FatalError("Invalid Across spoke pool address".to_string())
+                })
+            })
+            .ok_or(EncodingError::FatalError(
+                "Missing Across spoke pool address in config".to_string(),
+            ))
+            .flatten()?;
+        Ok(Self {
+            executor_address,
+            spoke_pool_address,
+            native_token_address: chain.native_token().address,
+        })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let spoke_pool = Bytes::from_str(&swap.component().id).map_err(|_| {
+            EncodingError::InvalidInput(format!(
+                "Invalid Across spoke pool id: {}",
+                swap.component().id
+            ))
+        })?;
+        if spoke_pool != self.spoke_pool_address {
+            return Err(EncodingError::InvalidInput(format!(
+                "Across deposits must go through the spoke pool {}. Found {}",
+                self.spoke_pool_address,
+                swap.component().id
+            )));
+        }
+        if *swap.token_in() == self.native_token_address {
+            return Err(EncodingError::InvalidInput(
+                "Across deposits of the native token are not supported. Deposit the wrapped \
+                 token instead"
+                    .to_string(),
+            ));
+        }
+        let (destination_chain_id, recipient, relay_fee_bps) = self.deposit_params(swap)?;
+
+        let args = (
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            bytes_to_address(swap.token_in())?,
+            bytes_to_address(swap.token_out())?,
+            destination_chain_id,
+            recipient,
+            bytes_to_address(&encoding_context.receiver)?,
+            relay_fee_bps.to_be_bytes(),
+        );
+
+        Ok(args.abi_encode_packed())
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::{ApprovalPolicy, TransferType};
+
+    fn across_encoder() -> BridgeSwapEncoder {
+        BridgeSwapEncoder::new(
+            Bytes::from("0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"),
+            Chain::Ethereum,
+            Some(HashMap::from([(
+                "spoke_pool_address".to_string(),
+                "0x5c7BCd6E7De5423a257D81B442095A1a6ced35C5".to_string(),
+            )])),
+        )
+        .unwrap()
+    }
+
+    fn deposit_swap(user_data: Option<Vec<u8>>) -> Swap {
+        let spoke_pool = ProtocolComponent {
+            id: String::from("0x5c7BCd6E7De5423a257D81B442095A1a6ced35C5"),
+            protocol_system: String::from("bridge:across"),
+            ..Default::default()
+        };
+        // USDC -> (across) -> USDC on Base
+        let swap = Swap::new(
+            spoke_pool,
+            Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            Bytes::from("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+        );
+        match user_data {
+            Some(user_data) => swap.user_data(Bytes::from(user_data)),
+            None => swap,
+        }
+    }
+
+    fn encoding_context() -> EncodingContext {
+        EncodingContext {
+            // The receiver was generated with `makeAddr("bob*") using forge`
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::default()),
+            group_token_in: Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            group_token_out: Bytes::from("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+            transfer_type: TransferType::None,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    #[test]
+    fn test_encode_across_deposit() {
+        let recipient = Address::from_str("0x1D96F2f6BeF1202E4Ce1Ff6Dad0c2CB002861d3e").unwrap();
+        let user_data = (U256::from(8453), recipient, 5u16).abi_encode();
+        let swap = deposit_swap(Some(user_data));
+
+        let encoded_swap = across_encoder()
+            .encode_swap(&swap, &encoding_context())
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // transfer type
+                "02",
+                // input token
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                // output token
+                "833589fcd6edb6e08f4c7c32d4f71b54bda02913",
+                // destination chain id
+                "0000000000000000000000000000000000000000000000000000000000002105",
+                // recipient
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // depositor
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // relay fee bps
+                "0005",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_across_deposit_without_user_data() {
+        let result = across_encoder().encode_swap(&deposit_swap(None), &encoding_context());
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_encode_across_deposit_invalid_relay_fee() {
+        let recipient = Address::from_str("0x1D96F2f6BeF1202E4Ce1Ff6Dad0c2CB002861d3e").unwrap();
+        let user_data = (U256::from(8453), recipient, 10_000u16).abi_encode();
+
+        let result =
+            across_encoder().encode_swap(&deposit_swap(Some(user_data)), &encoding_context());
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use alloy::{primitives::Address, sol_types::SolValue};
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        component_id::component_pool_address,
+        utils::{bytes_to_address, get_static_attribute},
+    },
+    models::{EncodingContext, Swap},
+    swap_encoder::SwapEncoder,
+};
+
+/// Encodes a swap on an Aerodrome V2 (or Velodrome V2) stable or volatile pool through the given
+/// executor address.
+///
+/// The curve of the pool is given by its `stable` static attribute. The executor checks the pool
+/// against the factory of the chain, which is encoded with the swap so the same executor contract
+/// can serve the Aerodrome and Velodrome deployments.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `factory_address` - The address of the pool factory of the protocol on the chain.
+#[derive(Clone)]
+pub struct AerodromeV2SwapEncoder {
+    executor_address: Bytes,
+    factory_address: Address,
+}
+
+impl AerodromeV2SwapEncoder {
+    fn get_zero_to_one(sell_token_address: Address, buy_token_address: Address) -> bool {
+        sell_token_address < buy_token_address
+    }
+
+    /// Returns whether the pool of the swap is a stable pool, from its `stable` static attribute.
+    fn is_stable(swap: &Swap) -> Result<bool, EncodingError> {
+        let stable = get_static_attribute(swap, "stable")?;
+        Ok(stable.iter().any(|byte| *byte != 0))
+    }
+}
+
+impl SwapEncoder for AerodromeV2SwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        _chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        let factory_address = config
+            .as_ref()
+            .and_then(|config| config.get("factory_address"))
+            .ok_or_else(|| {
+                EncodingError::FatalError(
+                    "Missing factory_address in Aerodrome V2 config".to_string(),
+                )
+            })?;
+        Ok(Self {
+            executor_address,
+            factory_address: bytes_to_address(&Bytes::from(factory_address.as_str()))?,
+        })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let token_in_address = bytes_to_address(swap.token_in())?;
+        let token_out_address = bytes_to_address(swap.token_out())?;
+
+        let zero_to_one = Self::get_zero_to_one(token_in_address, token_out_address);
+        let component_id = component_pool_address(swap.component())?;
+
+        let args = (
+            token_in_address,
+            component_id,
+            bytes_to_address(&encoding_context.receiver)?,
+            zero_to_one,
+            Self::is_stable(swap)?,
+            self.factory_address,
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+        );
+
+        Ok(args.abi_encode_packed())
+    }
+
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::{
+        evm::utils::write_calldata_to_file,
+        models::{ApprovalPolicy, TransferType},
+    };
+
+    fn encoder() -> AerodromeV2SwapEncoder {
+        AerodromeV2SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Base,
+            Some(HashMap::from([(
+                "factory_address".to_string(),
+                "0x420DD381b31aEf6683db6B902084cB0FFECe40Da".to_string(),
+            )])),
+        )
+        .unwrap()
+    }
+
+    fn swap(stable: bool) -> Swap {
+        let aerodrome_pool = ProtocolComponent {
+            id: String::from("0xcDAC0d6c6C59727a65F871236188350531885C43"),
+            protocol_system: String::from("aerodrome_v2"),
+            static_attributes: HashMap::from([(
+                "stable".to_string(),
+                Bytes::from(vec![u8::from(stable)]),
+            )]),
+            ..Default::default()
+        };
+        // WETH -> USDC
+        Swap::new(
+            aerodrome_pool,
+            Bytes::from("0x4200000000000000000000000000000000000006"),
+            Bytes::from("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+        )
+    }
+
+    fn encoding_context(swap: &Swap) -> EncodingContext {
+        EncodingContext {
+            // The receiver was generated with `makeAddr("bob*") using forge`
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: swap.token_in().clone(),
+            group_token_out: swap.token_out().clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    #[test]
+    fn test_encode_aerodrome_v2() {
+        let swap = swap(false);
+        let encoded_swap = encoder()
+            .encode_swap(&swap, &encoding_context(&swap))
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // in token
+                "4200000000000000000000000000000000000006",
+                // component id
+                "cdac0d6c6c59727a65f871236188350531885c43",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // zero for one
+                "01",
+                // stable
+                "00",
+                // factory
+                "420dd381b31aef6683db6b902084cb0ffece40da",
+                // transfer type Transfer
+                "01",
+            ))
+        );
+        write_calldata_to_file("test_encode_aerodrome_v2", hex_swap.as_str());
+    }
+
+    #[test]
+    fn test_encode_aerodrome_v2_stable() {
+        let swap = swap(true);
+        let encoded_swap = encoder()
+            .encode_swap(&swap, &encoding_context(&swap))
+            .unwrap();
+
+        // The stable flag follows the zero for one flag
+        assert_eq!(encoded_swap[61], 1);
+    }
+
+    #[test]
+    fn test_aerodrome_v2_missing_factory() {
+        let result = AerodromeV2SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Base,
+            None,
+        );
+
+        assert!(matches!(result, Err(EncodingError::FatalError(_))));
+    }
+}
@@ -0,0 +1,229 @@
+use std::{collections::HashMap, str::FromStr};
+
+use alloy::{primitives::U256, sol_types::SolValue};
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        approvals::protocol_approvals_manager::ProtocolApprovalsManager,
+        utils::{bytes_to_address, get_static_attribute, pad_or_truncate_to_size},
+    },
+    models::{EncodingContext, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+/// Encodes a swap on an Ambient (CrocSwap) pool through the given executor address.
+///
+/// All Ambient pools live in a single CrocSwapDex contract, which identifies a pool by its base and
+/// quote tokens and its pool index. The base token is the one with the lowest address, so that the
+/// native token (the zero address) is always the base token of its pools.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `croc_swap_dex_address` - The address of the CrocSwapDex contract holding the pools.
+#[derive(Clone)]
+pub struct AmbientSwapEncoder {
+    executor_address: Bytes,
+    croc_swap_dex_address: Bytes,
+}
+
+impl SwapEncoder for AmbientSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        _chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        let config = config.ok_or(EncodingError::FatalError(
+            "Missing ambient specific addresses in config".to_string(),
+        ))?;
+        let croc_swap_dex_address = config
+            .get("croc_swap_dex_address")
+            .map(|s| {
+                Bytes::from_str(s).map_err(|_| {
+                    EncodingError::FatalError("Invalid ambient CrocSwapDex address".to_string())
+                })
+            })
+            .ok_or(EncodingError::FatalError(
+                "Missing ambient CrocSwapDex address in config".to_string(),
+            ))
+            .flatten()?;
+        Ok(Self { executor_address, croc_swap_dex_address })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let token_in = bytes_to_address(swap.token_in())?;
+        let token_out = bytes_to_address(swap.token_out())?;
+        // Buying the quote token with the base token
+        let is_buy = token_in < token_out;
+        let (base, quote) = if is_buy { (token_in, token_out) } else { (token_out, token_in) };
+        let pool_index = U256::from_be_bytes(pad_or_truncate_to_size::<32>(
+            &get_static_attribute(swap, "pool_index")?,
+        )?);
+
+        // The native token is sent with the call, without approval
+        let mut approval_needed = !token_in.is_zero();
+        if approval_needed {
+            if let Some(router_address) = &encoding_context.router_address {
+                if let Some(fixed_approval) = encoding_context.fixed_approval() {
+                    approval_needed = fixed_approval;
+                } else {
+                    let token_approvals_manager = ProtocolApprovalsManager::new()?;
+                    approval_needed = token_approvals_manager.approval_needed(
+                        token_in,
+                        bytes_to_address(router_address)?,
+                        bytes_to_address(&self.croc_swap_dex_address)?,
+                    )?;
+                }
+            }
+        }
+
+        let args = (
+            base,
+            quote,
+            pool_index,
+            is_buy,
+            bytes_to_address(&self.croc_swap_dex_address)?,
+            bytes_to_address(&encoding_context.receiver)?,
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            approval_needed,
+        );
+        Ok(args.abi_encode_packed())
+    }
+
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use num_bigint::BigInt;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::{ApprovalPolicy, TransferType};
+
+    const ETH_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+    const USDC_ADDRESS: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+    fn encode_ambient_swap(
+        token_in: &str,
+        token_out: &str,
+        static_attributes: HashMap<String, Bytes>,
+    ) -> Result<String, EncodingError> {
+        let component = ProtocolComponent {
+            id: String::from("0xe6a7e0a6eb15a6fbcd7d4b3f4d6e1e9ed1bba0fd0df6e2d1c9fc4c8be1c1a0a0"),
+            protocol_system: String::from("ambient"),
+            static_attributes,
+            ..Default::default()
+        };
+        let token_in = Bytes::from(token_in);
+        let token_out = Bytes::from(token_out);
+        let swap = Swap::new(component, token_in.clone(), token_out.clone());
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0x1D96F2f6BeF1202E4Ce1Ff6Dad0c2CB002861d3e"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in,
+            group_token_out: token_out,
+            transfer_type: TransferType::None,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::AlwaysApprove,
+        };
+        let encoder = AmbientSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            Some(HashMap::from([(
+                "croc_swap_dex_address".to_string(),
+                "0xAaAaAAAaA24eEeb8d57D431224f73832bC34f688".to_string(),
+            )])),
+        )
+        .unwrap();
+        let encoded_swap = encoder.encode_swap(&swap, &encoding_context)?;
+        Ok(encode(&encoded_swap))
+    }
+
+    fn pool_index(index: u32) -> HashMap<String, Bytes> {
+        HashMap::from([(
+            "pool_index".to_string(),
+            Bytes::from(BigInt::from(index).to_signed_bytes_be()),
+        )])
+    }
+
+    #[test]
+    fn test_encode_ambient_sell_base() {
+        let hex_swap = encode_ambient_swap(ETH_ADDRESS, USDC_ADDRESS, pool_index(420)).unwrap();
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // base
+                "0000000000000000000000000000000000000000",
+                // quote
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                // pool index
+                "00000000000000000000000000000000000000000000000000000000000001a4",
+                // is buy
+                "01",
+                // CrocSwapDex
+                "aaaaaaaaa24eeeb8d57d431224f73832bc34f688",
+                // receiver
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // transfer type None
+                "02",
+                // approval needed (native token in)
+                "00",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_ambient_sell_quote() {
+        let hex_swap = encode_ambient_swap(USDC_ADDRESS, ETH_ADDRESS, pool_index(420)).unwrap();
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // base
+                "0000000000000000000000000000000000000000",
+                // quote
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                // pool index
+                "00000000000000000000000000000000000000000000000000000000000001a4",
+                // is buy
+                "00",
+                // CrocSwapDex
+                "aaaaaaaaa24eeeb8d57d431224f73832bc34f688",
+                // receiver
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // transfer type None
+                "02",
+                // approval needed
+                "01",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_ambient_missing_pool_index() {
+        let result = encode_ambient_swap(ETH_ADDRESS, USDC_ADDRESS, HashMap::new());
+
+        assert!(matches!(result, Err(EncodingError::FatalError(_))));
+    }
+}
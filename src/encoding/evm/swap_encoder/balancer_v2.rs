@@ -9,10 +9,11 @@ use tycho_common::{models::Chain, Bytes};
 use crate::encoding::{
     errors::EncodingError,
     evm::{
-        approvals::protocol_approvals_manager::ProtocolApprovalsManager, utils::bytes_to_address,
+        approvals::protocol_approvals_manager::ProtocolApprovalsManager, component_id::ComponentId,
+        utils::bytes_to_address,
     },
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 /// Encodes a swap on a Balancer V2 pool through the given executor address.
@@ -54,12 +55,14 @@ impl SwapEncoder for BalancerV2SwapEncoder {
         swap: &Swap,
         encoding_context: &EncodingContext,
     ) -> Result<Vec<u8>, EncodingError> {
-        let token_approvals_manager = ProtocolApprovalsManager::new()?;
         let token = bytes_to_address(swap.token_in())?;
         let mut approval_needed: bool = true;
 
         if let Some(router_address) = &encoding_context.router_address {
-            if !encoding_context.historical_trade {
+            if let Some(fixed_approval) = encoding_context.fixed_approval() {
+                approval_needed = fixed_approval;
+            } else {
+                let token_approvals_manager = ProtocolApprovalsManager::new()?;
                 let tycho_router_address = bytes_to_address(router_address)?;
                 approval_needed = token_approvals_manager.approval_needed(
                     token,
@@ -69,13 +72,12 @@ impl SwapEncoder for BalancerV2SwapEncoder {
             }
         };
 
-        let component_id = AlloyBytes::from_str(&swap.component().id)
-            .map_err(|_| EncodingError::FatalError("Invalid component ID".to_string()))?;
+        let component_id = ComponentId::parse(swap.component())?;
 
         let args = (
             bytes_to_address(swap.token_in())?,
             bytes_to_address(swap.token_out())?,
-            component_id,
+            AlloyBytes::copy_from_slice(component_id.as_slice()),
             bytes_to_address(&encoding_context.receiver)?,
             approval_needed,
             (encoding_context.transfer_type as u8).to_be_bytes(),
@@ -83,6 +85,14 @@ impl SwapEncoder for BalancerV2SwapEncoder {
         Ok(args.abi_encode_packed())
     }
 
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -99,7 +109,7 @@ mod tests {
     use super::*;
     use crate::encoding::{
         evm::{swap_encoder::balancer_v2::BalancerV2SwapEncoder, utils::write_calldata_to_file},
-        models::{Swap, TransferType},
+        models::{ApprovalPolicy, Swap, TransferType},
     };
     #[test]
     fn test_encode_balancer_v2() {
@@ -120,6 +130,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::None,
             historical_trade: true,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = BalancerV2SwapEncoder::new(
             Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
@@ -154,4 +166,47 @@ mod tests {
         );
         write_calldata_to_file("test_encode_balancer_v2", hex_swap.as_str());
     }
+
+    #[test]
+    fn test_encode_balancer_v2_approval_policy() {
+        let balancer_pool = ProtocolComponent {
+            id: String::from("0x5c6ee304399dbdb9c8ef030ab642b10820db8f56000200000000000000000014"),
+            protocol_system: String::from("vm:balancer_v2"),
+            ..Default::default()
+        };
+        let token_in = Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let token_out = Bytes::from("0xba100000625a3754423978a60c9317c58a424e3D");
+        let swap = Swap::new(balancer_pool, token_in.clone(), token_out.clone());
+        let encoding_context = |approval_policy: ApprovalPolicy| EncodingContext {
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::None,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy,
+        };
+        let encoder = BalancerV2SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            Some(HashMap::from([(
+                "vault_address".to_string(),
+                "0xba12222222228d8ba445958a75a0704d566bf2c8".to_string(),
+            )])),
+        )
+        .unwrap();
+
+        // The fixed policies don't look the router's allowance up, so the calldata is reproducible
+        for (approval_policy, approval_needed) in
+            [(ApprovalPolicy::AlwaysApprove, 1), (ApprovalPolicy::NeverApprove, 0)]
+        {
+            let encoded_swap = encoder
+                .encode_swap(&swap, &encoding_context(approval_policy))
+                .unwrap();
+            // The approval flag precedes the transfer type
+            assert_eq!(encoded_swap[encoded_swap.len() - 2], approval_needed);
+        }
+    }
 }
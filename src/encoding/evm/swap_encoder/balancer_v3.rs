@@ -1,17 +1,59 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
-use alloy::{primitives::Address, sol_types::SolValue};
+use alloy::{
+    primitives::{Address, Bytes as AlloyBytes, TxKind, U256},
+    providers::Provider,
+    rpc::types::{TransactionInput, TransactionRequest},
+    sol,
+    sol_types::SolValue,
+};
+use tokio::{
+    runtime::{Handle, Runtime},
+    task::block_in_place,
+};
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
-    evm::utils::bytes_to_address,
+    evm::{
+        component_id::component_pool_address,
+        encoding_utils::encode_input,
+        utils::{bytes_to_address, get_client, get_runtime, get_static_attribute, EVMProvider},
+    },
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
+sol! {
+    /// The swap parameters passed to the hook computing the dynamic swap fee of a Balancer V3
+    /// pool, `kind` being 0 for EXACT_IN.
+    struct PoolSwapParams {
+        uint8 kind;
+        uint256 amountGivenScaled18;
+        uint256[] balancesScaled18;
+        uint256 indexIn;
+        uint256 indexOut;
+        address router;
+        bytes userData;
+    }
+}
+
+/// The Balancer V3 Vault, deployed at the same address on all supported chains.
+pub const BALANCER_V3_VAULT_ADDRESS: &str = "0xbA1333333333a1BA1108E8412f11850A5C319bA9";
+
 /// Encodes a swap on a Balancer V3 pool through the given executor address.
 ///
+/// Pools without hooks are encoded with the plain 81 bytes layout. Pools with a hook (given by
+/// the `hooks` static attribute) get the hook data appended, which is forwarded by the executor to
+/// the Vault as `userData` and reaches the hook on every swap callback. The hook data is taken
+/// from the swap's `user_data`, the same way as for Uniswap V4 hooks.
+///
+/// Boosted pools hold ERC4626 tokens, swapped from and to their underlying token through the
+/// Vault's buffers. The `buffer_token_in` static attribute is the ERC4626 token the token in is
+/// wrapped into before the pool swap, and `buffer_token_out` the one the pool swap outputs,
+/// unwrapped into the token out. Swaps through a buffer get both buffer tokens (zero if missing)
+/// appended after the hook data length and hook data (empty without a hook).
+///
 /// # Fields
 /// * `executor_address` - The address of the executor contract that will perform the swap.
 #[derive(Clone)]
@@ -19,6 +61,36 @@ pub struct BalancerV3SwapEncoder {
     executor_address: Bytes,
 }
 
+impl BalancerV3SwapEncoder {
+    /// Returns the address of the given static attribute of the pool, or `None` if the attribute
+    /// is missing or the zero address.
+    fn address_attribute(
+        swap: &Swap,
+        attribute_name: &str,
+    ) -> Result<Option<Address>, EncodingError> {
+        match get_static_attribute(swap, attribute_name) {
+            Ok(address) => {
+                let address = bytes_to_address(&Bytes::from(address))?;
+                Ok((address != Address::ZERO).then_some(address))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Returns the hook address of the given pool, or `None` if the pool has no hook.
+    fn hook_address(swap: &Swap) -> Result<Option<Address>, EncodingError> {
+        Self::address_attribute(swap, "hooks")
+    }
+
+    /// Returns the buffer tokens the swap goes through, as `(buffer_token_in, buffer_token_out)`.
+    fn buffer_tokens(swap: &Swap) -> Result<(Option<Address>, Option<Address>), EncodingError> {
+        Ok((
+            Self::address_attribute(swap, "buffer_token_in")?,
+            Self::address_attribute(swap, "buffer_token_out")?,
+        ))
+    }
+}
+
 impl SwapEncoder for BalancerV3SwapEncoder {
     fn new(
         executor_address: Bytes,
@@ -33,9 +105,7 @@ impl SwapEncoder for BalancerV3SwapEncoder {
         swap: &Swap,
         encoding_context: &EncodingContext,
     ) -> Result<Vec<u8>, EncodingError> {
-        let pool = Address::from_str(&swap.component().id).map_err(|_| {
-            EncodingError::FatalError("Invalid pool address for Balancer v3".to_string())
-        })?;
+        let pool = component_pool_address(swap.component())?;
 
         let args = (
             bytes_to_address(swap.token_in())?,
@@ -44,7 +114,37 @@ impl SwapEncoder for BalancerV3SwapEncoder {
             (encoding_context.transfer_type as u8).to_be_bytes(),
             bytes_to_address(&encoding_context.receiver)?,
         );
-        Ok(args.abi_encode_packed())
+        let mut encoded = args.abi_encode_packed();
+
+        let (buffer_token_in, buffer_token_out) = Self::buffer_tokens(swap)?;
+        let through_buffer = buffer_token_in.is_some() || buffer_token_out.is_some();
+        let hooked = Self::hook_address(swap)?.is_some();
+        if hooked || through_buffer {
+            let hook_data = if hooked {
+                swap.get_user_data()
+                    .clone()
+                    .unwrap_or_default()
+                    .to_vec()
+            } else {
+                vec![]
+            };
+            let hook_data_length = u16::try_from(hook_data.len()).map_err(|_| {
+                EncodingError::InvalidInput("Balancer v3 hook data is too long".to_string())
+            })?;
+            encoded.extend(hook_data_length.to_be_bytes());
+            encoded.extend(hook_data);
+        }
+        if through_buffer {
+            encoded.extend(
+                (buffer_token_in.unwrap_or_default(), buffer_token_out.unwrap_or_default())
+                    .abi_encode_packed(),
+            );
+        }
+        Ok(encoded)
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { groupable: true, callback_constrained: true, ..Default::default() }
     }
 
     fn executor_address(&self) -> &Bytes {
@@ -56,6 +156,116 @@ impl SwapEncoder for BalancerV3SwapEncoder {
     }
 }
 
+/// Discovers the swap fee a Balancer V3 pool currently charges, by static-calling the Vault.
+///
+/// For pools whose hook computes a dynamic swap fee (`should_call_compute_dynamic_swap_fee`
+/// static attribute), the static swap fee stored in the Vault is not the fee that will be charged,
+/// so the fee is computed by the Vault for the given swap instead. This is meant to improve the
+/// expected amount analysis of hooked pools and is not needed for encoding.
+pub struct BalancerV3FeeFetcher {
+    client: EVMProvider,
+    vault_address: Address,
+    runtime_handle: Handle,
+    #[allow(dead_code)]
+    runtime: Option<Arc<Runtime>>,
+}
+
+impl BalancerV3FeeFetcher {
+    pub fn new() -> Result<Self, EncodingError> {
+        let (handle, runtime) = get_runtime()?;
+        let client = block_in_place(|| handle.block_on(get_client()))?;
+        let vault_address = Address::from_str(BALANCER_V3_VAULT_ADDRESS)
+            .map_err(|_| EncodingError::FatalError("Invalid Balancer v3 vault".to_string()))?;
+        Ok(Self { client, vault_address, runtime_handle: handle, runtime })
+    }
+
+    /// Returns the swap fee percentage (18 decimals fixed point, i.e. 1e18 = 100%) that the pool
+    /// of the given swap would charge for `amount_given_scaled18` of `token_in`.
+    pub fn swap_fee_percentage(
+        &self,
+        swap: &Swap,
+        amount_given_scaled18: U256,
+    ) -> Result<U256, EncodingError> {
+        let pool = component_pool_address(swap.component())?;
+        let is_dynamic = get_static_attribute(swap, "should_call_compute_dynamic_swap_fee")
+            .map(|flag| flag.iter().any(|byte| *byte != 0))
+            .unwrap_or(false);
+
+        if !is_dynamic {
+            let response =
+                self.call(encode_input("getStaticSwapFeePercentage(address)", pool.abi_encode()))?;
+            return U256::abi_decode(&response).map_err(|_| {
+                EncodingError::FatalError(
+                    "Failed to decode Balancer v3 static swap fee".to_string(),
+                )
+            });
+        }
+
+        let tokens_response =
+            self.call(encode_input("getPoolTokens(address)", pool.abi_encode()))?;
+        let tokens = Vec::<Address>::abi_decode(&tokens_response).map_err(|_| {
+            EncodingError::FatalError("Failed to decode Balancer v3 pool tokens".to_string())
+        })?;
+        let token_index = |token: &Bytes| -> Result<U256, EncodingError> {
+            let token = bytes_to_address(token)?;
+            tokens
+                .iter()
+                .position(|t| *t == token)
+                .map(U256::from)
+                .ok_or_else(|| {
+                    EncodingError::InvalidInput(format!(
+                        "Token {token} is not part of Balancer v3 pool {pool}"
+                    ))
+                })
+        };
+        let index_in = token_index(swap.token_in())?;
+        let index_out = token_index(swap.token_out())?;
+
+        let balances_response =
+            self.call(encode_input("getCurrentLiveBalances(address)", pool.abi_encode()))?;
+        let balances = Vec::<U256>::abi_decode(&balances_response).map_err(|_| {
+            EncodingError::FatalError("Failed to decode Balancer v3 pool balances".to_string())
+        })?;
+
+        let hook_data = swap
+            .get_user_data()
+            .clone()
+            .unwrap_or_default()
+            .to_vec();
+        let swap_params = PoolSwapParams {
+            kind: 0,
+            amountGivenScaled18: amount_given_scaled18,
+            balancesScaled18: balances,
+            indexIn: index_in,
+            indexOut: index_out,
+            router: Address::ZERO,
+            userData: AlloyBytes::from(hook_data),
+        };
+        let response = self.call(encode_input(
+            "computeDynamicSwapFeePercentage(address,(uint8,uint256,uint256[],uint256,uint256,address,bytes))",
+            (pool, swap_params).abi_encode(),
+        ))?;
+        U256::abi_decode(&response).map_err(|_| {
+            EncodingError::FatalError("Failed to decode Balancer v3 dynamic swap fee".to_string())
+        })
+    }
+
+    fn call(&self, data: Vec<u8>) -> Result<AlloyBytes, EncodingError> {
+        let tx = TransactionRequest {
+            to: Some(TxKind::from(self.vault_address)),
+            input: TransactionInput { input: Some(AlloyBytes::from(data)), data: None },
+            ..Default::default()
+        };
+        block_in_place(|| {
+            self.runtime_handle
+                .block_on(async { self.client.call(tx).await })
+        })
+        .map_err(|err| {
+            EncodingError::RecoverableError(format!("Balancer v3 vault call failed: {err}"))
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloy::hex::encode;
@@ -64,7 +274,7 @@ mod tests {
     use super::*;
     use crate::encoding::{
         evm::{swap_encoder::balancer_v3::BalancerV3SwapEncoder, utils::write_calldata_to_file},
-        models::TransferType,
+        models::{ApprovalPolicy, TransferType},
     };
 
     #[test]
@@ -86,6 +296,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = BalancerV3SwapEncoder::new(
             Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
@@ -115,4 +327,162 @@ mod tests {
         );
         write_calldata_to_file("test_encode_balancer_v3", hex_swap.as_str());
     }
+
+    #[test]
+    fn test_encode_balancer_v3_with_hook() {
+        let mut static_attributes: HashMap<String, Bytes> = HashMap::new();
+        static_attributes
+            .insert("hooks".into(), Bytes::from("0x1e6febd32976f663c09924a331230f3a799b093c"));
+        let balancer_pool = ProtocolComponent {
+            id: String::from("0x85b2b559bc2d21104c4defdd6efca8a20343361d"),
+            protocol_system: String::from("vm:balancer_v3"),
+            static_attributes,
+            ..Default::default()
+        };
+        let token_in = Bytes::from("0x7bc3485026ac48b6cf9baf0a377477fff5703af8");
+        let token_out = Bytes::from("0xc71ea051a5f82c67adcf634c36ffe6334793d24c");
+        let swap = Swap::new(balancer_pool, token_in.clone(), token_out.clone())
+            .user_data(Bytes::from("0xabcd"));
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = BalancerV3SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // token in
+                "7bc3485026ac48b6cf9baf0a377477fff5703af8",
+                // token out
+                "c71ea051a5f82c67adcf634c36ffe6334793d24c",
+                // pool id
+                "85b2b559bc2d21104c4defdd6efca8a20343361d",
+                // transfer type Transfer
+                "01",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // hook data length
+                "0002",
+                // hook data
+                "abcd",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_balancer_v3_with_zero_hook() {
+        let mut static_attributes: HashMap<String, Bytes> = HashMap::new();
+        static_attributes.insert("hooks".into(), Bytes::zero(20));
+        let balancer_pool = ProtocolComponent {
+            id: String::from("0x85b2b559bc2d21104c4defdd6efca8a20343361d"),
+            protocol_system: String::from("vm:balancer_v3"),
+            static_attributes,
+            ..Default::default()
+        };
+        let token_in = Bytes::from("0x7bc3485026ac48b6cf9baf0a377477fff5703af8");
+        let token_out = Bytes::from("0xc71ea051a5f82c67adcf634c36ffe6334793d24c");
+        let swap = Swap::new(balancer_pool, token_in.clone(), token_out.clone());
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = BalancerV3SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+
+        // Pools without a hook keep the plain layout
+        assert_eq!(encoded_swap.len(), 81);
+    }
+
+    #[test]
+    fn test_encode_balancer_v3_through_buffer() {
+        let mut static_attributes: HashMap<String, Bytes> = HashMap::new();
+        // USDT is wrapped into waEthUSDT by its buffer before the swap
+        static_attributes.insert(
+            "buffer_token_in".into(),
+            Bytes::from("0x7bc3485026ac48b6cf9baf0a377477fff5703af8"),
+        );
+        let balancer_pool = ProtocolComponent {
+            id: String::from("0x85b2b559bc2d21104c4defdd6efca8a20343361d"),
+            protocol_system: String::from("vm:balancer_v3"),
+            static_attributes,
+            ..Default::default()
+        };
+        let token_in = Bytes::from("0xdac17f958d2ee523a2206206994597c13d831ec7");
+        let token_out = Bytes::from("0xc71ea051a5f82c67adcf634c36ffe6334793d24c");
+        let swap = Swap::new(balancer_pool, token_in.clone(), token_out.clone());
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = BalancerV3SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // token in
+                "dac17f958d2ee523a2206206994597c13d831ec7",
+                // token out
+                "c71ea051a5f82c67adcf634c36ffe6334793d24c",
+                // pool id
+                "85b2b559bc2d21104c4defdd6efca8a20343361d",
+                // transfer type Transfer
+                "01",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // hook data length (no hook)
+                "0000",
+                // buffer token in
+                "7bc3485026ac48b6cf9baf0a377477fff5703af8",
+                // buffer token out (none)
+                "0000000000000000000000000000000000000000",
+            ))
+        );
+    }
 }
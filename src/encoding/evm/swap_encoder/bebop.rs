@@ -1,23 +1,35 @@
 use std::{collections::HashMap, str::FromStr, sync::Arc};
 
-use alloy::{primitives::Address, sol_types::SolValue};
+use alloy::{
+    primitives::{Address, U256},
+    sol_types::SolValue,
+};
+use async_trait::async_trait;
+use chrono::Utc;
 use tokio::{
     runtime::{Handle, Runtime},
     task::block_in_place,
 };
 use tycho_common::{
     models::{protocol::GetAmountOutParams, Chain},
+    simulation::indicatively_priced::SignedQuote,
     Bytes,
 };
 
+#[cfg(feature = "failure-injection")]
+use crate::encoding::evm::failure_injection::{inject, InjectionPoint};
 use crate::encoding::{
     errors::EncodingError,
     evm::{
         approvals::protocol_approvals_manager::ProtocolApprovalsManager,
-        utils::{biguint_to_u256, bytes_to_address, get_runtime},
+        encoding_utils::encode_input,
+        rfq_feedback::{notify_quote, BEBOP_SWAP_SINGLE},
+        utils::{
+            biguint_to_u256, bytes_to_address, copy_quote_params, get_runtime, recorded_quote,
+        },
     },
-    models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    models::{EncodingContext, RfqConfig, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 /// Encodes a swap on Bebop (PMM RFQ) through the given executor address.
@@ -25,9 +37,16 @@ use crate::encoding::{
 /// Bebop uses a Request-for-Quote model where quotes are obtained off-chain
 /// and settled on-chain. This encoder supports PMM RFQ execution.
 ///
+/// Historical trades are encoded offline: the quote is read from the swap's user data (see
+/// `recorded_quote`) and the settlement contract is always approved.
+///
+/// Quotes of a single maker expiring too soon are rejected (see `RfqConfig::check_quote_expiry`).
+/// The expiry of the other quotes is not checked.
+///
 /// # Fields
 /// * `executor_address` - The address of the executor contract that will perform the swap.
 /// * `settlement_address` - The address of the Bebop settlement contract.
+/// * `rfq_config` - The timeout and retry policy of the quote requests.
 #[derive(Clone)]
 pub struct BebopSwapEncoder {
     executor_address: Bytes,
@@ -37,8 +56,150 @@ pub struct BebopSwapEncoder {
     runtime_handle: Handle,
     #[allow(dead_code)]
     runtime: Option<Arc<Runtime>>,
+    rfq_config: RfqConfig,
+}
+
+impl BebopSwapEncoder {
+    /// Returns the token, owner (the router) and spender (the settlement contract) of the
+    /// allowance to check before settling the swap, or `None` if the swap sells the native token.
+    fn approval_check(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Option<(Address, Address, Address)>, EncodingError> {
+        let token_in = bytes_to_address(swap.token_in())?;
+        let sender = encoding_context
+            .router_address
+            .clone()
+            .ok_or(EncodingError::FatalError(
+                "The router address is needed to perform a Hashflow swap".to_string(),
+            ))?;
+        if *swap.token_in() == self.native_token_address {
+            return Ok(None);
+        }
+        let tycho_router_address = bytes_to_address(&sender)?;
+        let settlement_address =
+            Address::from_str(&self.settlement_address.to_string()).map_err(|_| {
+                EncodingError::FatalError("Invalid bebop settlement address".to_string())
+            })?;
+        Ok(Some((token_in, tycho_router_address, settlement_address)))
+    }
+
+    /// Returns the parameters of the signed quote to request for the swap.
+    fn quote_params(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<GetAmountOutParams, EncodingError> {
+        #[cfg(feature = "failure-injection")]
+        inject(InjectionPoint::RfqQuote)?;
+        let estimated_amount_in = swap
+            .get_estimated_amount_in()
+            .clone()
+            .ok_or(EncodingError::FatalError(
+                "Estimated amount in is mandatory for a Bebop swap".to_string(),
+            ))?;
+        // Bebop uses another address for the native token than the zero address
+        let mut token_in = swap.token_in().clone();
+        if *swap.token_in() == self.native_token_address {
+            token_in = self.native_token_bebop_address.clone()
+        }
+        let mut token_out = swap.token_out().clone();
+        if *swap.token_out() == self.native_token_address {
+            token_out = self.native_token_bebop_address.clone()
+        }
+
+        Ok(GetAmountOutParams {
+            amount_in: estimated_amount_in,
+            token_in,
+            token_out,
+            sender: encoding_context
+                .router_address
+                .clone()
+                .ok_or(EncodingError::FatalError(
+                    "The router address is needed to perform a Bebop swap".to_string(),
+                ))?,
+            receiver: encoding_context.receiver.clone(),
+        })
+    }
+
+    /// Returns the expiry (unix timestamp in seconds) of the quote, parsed from the order of its
+    /// `swapSingle` calldata, or `None` if the quote is settled with another function.
+    fn quote_expiry(signed_quote: &SignedQuote) -> Option<u64> {
+        let calldata = signed_quote
+            .quote_attributes
+            .get("calldata")?;
+        // swapSingle(Single order, ...): the static order starts right after the selector, with
+        // the expiry as its first word
+        if calldata.len() < 4 + 32 || calldata[..4] != encode_input(BEBOP_SWAP_SINGLE, vec![])[..] {
+            return None;
+        }
+        U256::from_be_slice(&calldata[4..4 + 32])
+            .try_into()
+            .ok()
+    }
+
+    /// Encodes the swap settling the signed quote, and reports the quote to the RFQ observers.
+    ///
+    /// Returns an `EncodingError::QuoteExpired` if the quote expires too soon, unless the trade is
+    /// historical.
+    fn encode_signed_quote(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+        approval_needed: bool,
+        signed_quote: SignedQuote,
+    ) -> Result<Vec<u8>, EncodingError> {
+        if !encoding_context.historical_trade {
+            if let Some(expiry) = Self::quote_expiry(&signed_quote) {
+                self.rfq_config.check_quote_expiry(
+                    expiry,
+                    encoding_context.execution_deadline,
+                    Utc::now().timestamp().max(0) as u64,
+                )?;
+            }
+        }
+        let token_in = bytes_to_address(swap.token_in())?;
+        let token_out = bytes_to_address(swap.token_out())?;
+        let bebop_calldata = signed_quote
+            .quote_attributes
+            .get("calldata")
+            .ok_or(EncodingError::FatalError(
+                "Bebop quote must have a calldata attribute".to_string(),
+            ))?;
+        let partial_fill_offset = signed_quote
+            .quote_attributes
+            .get("partial_fill_offset")
+            .ok_or(EncodingError::FatalError(
+                "Bebop quote must have a partial_fill_offset attribute".to_string(),
+            ))?;
+        // we are only interested in the last byte to get a u8
+        let partial_fill_offset = partial_fill_offset[partial_fill_offset.len() - 1];
+        let original_filled_taker_amount = biguint_to_u256(&signed_quote.amount_out);
+
+        let receiver = bytes_to_address(&encoding_context.receiver)?;
+
+        // Encode packed data for the executor
+        // Format: token_in | token_out | transfer_type | partial_fill_offset |
+        //         original_filled_taker_amount | approval_needed | receiver | bebop_calldata
+        let args = (
+            token_in,
+            token_out,
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            partial_fill_offset.to_be_bytes(),
+            original_filled_taker_amount.to_be_bytes::<32>(),
+            (approval_needed as u8).to_be_bytes(),
+            receiver,
+            &bebop_calldata[..],
+        );
+
+        let encoded = args.abi_encode_packed();
+        notify_quote(swap, &signed_quote);
+        Ok(encoded)
+    }
 }
 
+#[async_trait]
 impl SwapEncoder for BebopSwapEncoder {
     fn new(
         executor_address: Bytes,
@@ -76,6 +237,7 @@ impl SwapEncoder for BebopSwapEncoder {
             settlement_address,
             runtime_handle,
             runtime,
+            rfq_config: RfqConfig::default(),
             native_token_bebop_address,
             native_token_address: chain.native_token().address,
         })
@@ -86,114 +248,68 @@ impl SwapEncoder for BebopSwapEncoder {
         swap: &Swap,
         encoding_context: &EncodingContext,
     ) -> Result<Vec<u8>, EncodingError> {
-        let token_in = bytes_to_address(swap.token_in())?;
-        let token_out = bytes_to_address(swap.token_out())?;
-        let sender = encoding_context
-            .router_address
-            .clone()
-            .ok_or(EncodingError::FatalError(
-                "The router address is needed to perform a Hashflow swap".to_string(),
-            ))?;
-        let approval_needed = if *swap.token_in() == self.native_token_address {
-            false
-        } else {
-            let tycho_router_address = bytes_to_address(&sender)?;
-            let settlement_address = Address::from_str(&self.settlement_address.to_string())
-                .map_err(|_| {
-                    EncodingError::FatalError("Invalid bebop settlement address".to_string())
-                })?;
-            ProtocolApprovalsManager::new()?.approval_needed(
-                token_in,
-                tycho_router_address,
-                settlement_address,
-            )?
+        let approval_check = self.approval_check(swap, encoding_context)?;
+        let approval_needed = match (approval_check, encoding_context.fixed_approval()) {
+            (Some(_), Some(fixed_approval)) => fixed_approval,
+            (Some((token, owner, spender)), None) => {
+                ProtocolApprovalsManager::new()?.approval_needed(token, owner, spender)?
+            }
+            (None, _) => false,
         };
+        if encoding_context.historical_trade {
+            let signed_quote = recorded_quote(swap)?;
+            return self.encode_signed_quote(swap, encoding_context, approval_needed, signed_quote);
+        }
+        let indicatively_priced_state = swap.rfq_state()?;
+        let params = self.quote_params(swap, encoding_context)?;
+        let signed_quote = block_in_place(|| {
+            self.runtime_handle.block_on(
+                self.rfq_config
+                    .request_signed_quote(|| {
+                        indicatively_priced_state.request_signed_quote(copy_quote_params(&params))
+                    }),
+            )
+        })?;
+        self.encode_signed_quote(swap, encoding_context, approval_needed, signed_quote)
+    }
 
-        let protocol_state = swap
-            .get_protocol_state()
-            .as_ref()
-            .ok_or_else(|| {
-                EncodingError::FatalError("protocol_state is required for Bebop".to_string())
-            })?;
-        let (partial_fill_offset, original_filled_taker_amount, bebop_calldata) = {
-            let indicatively_priced_state = protocol_state
-                .as_indicatively_priced()
-                .map_err(|e| {
-                    EncodingError::FatalError(format!("State is not indicatively priced {e}"))
-                })?;
-            let estimated_amount_in = swap
-                .get_estimated_amount_in()
-                .clone()
-                .ok_or(EncodingError::FatalError(
-                    "Estimated amount in is mandatory for a Bebop swap".to_string(),
-                ))?;
-            // Bebop uses another address for the native token than the zero address
-            let mut token_in = swap.token_in().clone();
-            if *swap.token_in() == self.native_token_address {
-                token_in = self.native_token_bebop_address.clone()
-            }
-            let mut token_out = swap.token_out().clone();
-            if *swap.token_out() == self.native_token_address {
-                token_out = self.native_token_bebop_address.clone()
+    async fn encode_swap_async(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let approval_check = self.approval_check(swap, encoding_context)?;
+        let approval_needed = match (approval_check, encoding_context.fixed_approval()) {
+            (Some(_), Some(fixed_approval)) => fixed_approval,
+            (Some((token, owner, spender)), None) => {
+                ProtocolApprovalsManager::new_async()
+                    .await?
+                    .approval_needed_async(token, owner, spender)
+                    .await?
             }
-
-            let params = GetAmountOutParams {
-                amount_in: estimated_amount_in,
-                token_in,
-                token_out,
-                sender: encoding_context
-                    .router_address
-                    .clone()
-                    .ok_or(EncodingError::FatalError(
-                        "The router address is needed to perform a Bebop swap".to_string(),
-                    ))?,
-                receiver: encoding_context.receiver.clone(),
-            };
-            let signed_quote = block_in_place(|| {
-                self.runtime_handle.block_on(async {
-                    indicatively_priced_state
-                        .request_signed_quote(params)
-                        .await
-                })
-            })?;
-            let bebop_calldata = signed_quote
-                .quote_attributes
-                .get("calldata")
-                .ok_or(EncodingError::FatalError(
-                    "Bebop quote must have a calldata attribute".to_string(),
-                ))?;
-            let partial_fill_offset = signed_quote
-                .quote_attributes
-                .get("partial_fill_offset")
-                .ok_or(EncodingError::FatalError(
-                    "Bebop quote must have a partial_fill_offset attribute".to_string(),
-                ))?;
-            let original_filled_taker_amount = biguint_to_u256(&signed_quote.amount_out);
-            (
-                // we are only interested in the last byte to get a u8
-                partial_fill_offset[partial_fill_offset.len() - 1],
-                original_filled_taker_amount,
-                bebop_calldata.to_vec(),
-            )
+            (None, _) => false,
         };
+        if encoding_context.historical_trade {
+            let signed_quote = recorded_quote(swap)?;
+            return self.encode_signed_quote(swap, encoding_context, approval_needed, signed_quote);
+        }
+        let indicatively_priced_state = swap.rfq_state()?;
+        let params = self.quote_params(swap, encoding_context)?;
+        let signed_quote = self
+            .rfq_config
+            .request_signed_quote(|| {
+                indicatively_priced_state.request_signed_quote(copy_quote_params(&params))
+            })
+            .await?;
+        self.encode_signed_quote(swap, encoding_context, approval_needed, signed_quote)
+    }
 
-        let receiver = bytes_to_address(&encoding_context.receiver)?;
-
-        // Encode packed data for the executor
-        // Format: token_in | token_out | transfer_type | partial_fill_offset |
-        //         original_filled_taker_amount | approval_needed | receiver | bebop_calldata
-        let args = (
-            token_in,
-            token_out,
-            (encoding_context.transfer_type as u8).to_be_bytes(),
-            partial_fill_offset.to_be_bytes(),
-            original_filled_taker_amount.to_be_bytes::<32>(),
-            (approval_needed as u8).to_be_bytes(),
-            receiver,
-            &bebop_calldata[..],
-        );
+    fn with_rfq_config(&self, rfq_config: &RfqConfig) -> Option<Box<dyn SwapEncoder>> {
+        Some(Box::new(Self { rfq_config: *rfq_config, ..self.clone() }))
+    }
 
-        Ok(args.abi_encode_packed())
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
     }
 
     fn executor_address(&self) -> &Bytes {
@@ -207,14 +323,23 @@ impl SwapEncoder for BebopSwapEncoder {
 
 #[cfg(test)]
 mod tests {
-    use alloy::hex::encode;
+    use std::sync::Mutex;
+
+    use alloy::{
+        hex::encode,
+        primitives::{Bytes as AlloyBytes, U256},
+    };
     use num_bigint::BigUint;
     use tycho_common::models::protocol::ProtocolComponent;
 
     use super::*;
     use crate::encoding::{
-        evm::{swap_encoder::bebop::BebopSwapEncoder, testing_utils::MockRFQState},
-        models::TransferType,
+        evm::{
+            rfq_feedback::{register_rfq_observer, unregister_rfq_observer, RfqFeedbackEvent},
+            swap_encoder::bebop::BebopSwapEncoder,
+            testing_utils::MockRFQState,
+        },
+        models::{ApprovalPolicy, TransferType},
     };
 
     fn bebop_config() -> HashMap<String, String> {
@@ -272,6 +397,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
 
         let encoder = BebopSwapEncoder::new(
@@ -304,4 +431,213 @@ mod tests {
         ));
         assert_eq!(hex_swap, expected_swap + &bebop_calldata.to_string()[2..]);
     }
+
+    #[test]
+    fn test_encode_bebop_historical_trade() {
+        // 3000 USDC -> 1 WETH, settling the quote recorded in the user data without any RPC or
+        // API call
+        let bebop_component = ProtocolComponent {
+            id: String::from("bebop-rfq"),
+            protocol_system: String::from("rfq:bebop"),
+            ..Default::default()
+        };
+        let token_in = Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"); // USDC
+        let token_out = Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"); // WETH
+        let quote_data = (
+            U256::from(3000000000u64),
+            U256::from(1000000000000000000u64),
+            vec!["calldata".to_string(), "partial_fill_offset".to_string()],
+            vec![AlloyBytes::from(vec![0x12, 0x34, 0x56]), AlloyBytes::from(vec![12u8])],
+        )
+            .abi_encode();
+        let swap = Swap::new(bebop_component, token_in.clone(), token_out.clone())
+            .user_data(Bytes::from(quote_data));
+
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0xc5564C13A157E6240659fb81882A28091add8670"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: true,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+
+        let encoder = BebopSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            Some(bebop_config()),
+        )
+        .unwrap();
+
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+
+        let expected_swap = String::from(concat!(
+            // token in
+            "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            // token out
+            "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            // transfer type
+            "01",
+            // partiall filled offset
+            "0c",
+            //  original taker amount
+            "0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+            // approval needed
+            "01",
+            //receiver,
+            "c5564c13a157e6240659fb81882a28091add8670",
+            // bebop calldata
+            "123456",
+        ));
+        assert_eq!(encode(&encoded_swap), expected_swap);
+
+        // The quote can't be requested for a historical trade
+        let result = encoder.encode_swap(
+            &Swap::new(swap.component().clone(), token_in, token_out),
+            &encoding_context,
+        );
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_encode_bebop_reports_quote() {
+        let bebop_component = ProtocolComponent {
+            id: String::from("bebop-rfq-reported"),
+            protocol_system: String::from("rfq:bebop"),
+            ..Default::default()
+        };
+        let bebop_state = MockRFQState {
+            quote_amount_out: BigUint::from_str("3000000000").unwrap(),
+            quote_data: HashMap::from([
+                ("calldata".to_string(), Bytes::from_str("0x123456").unwrap()),
+                ("partial_fill_offset".to_string(), Bytes::from(vec![12u8])),
+                (
+                    "maker".to_string(),
+                    Bytes::from_str("0xce79b081c0c924cb67848723ed3057234d10fc6b").unwrap(),
+                ),
+            ]),
+        };
+        let token_in = Bytes::from("0x0000000000000000000000000000000000000000"); // ETH
+        let token_out = Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"); // USDC
+        let swap = Swap::new(bebop_component, token_in.clone(), token_out.clone())
+            .estimated_amount_in(BigUint::from_str("1000000000000000000").unwrap())
+            .protocol_state(Arc::new(bebop_state));
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0xc5564C13A157E6240659fb81882A28091add8670"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::None,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = BebopSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            Some(bebop_config()),
+        )
+        .unwrap();
+
+        let quotes = Arc::new(Mutex::new(vec![]));
+        let quotes_clone = quotes.clone();
+        let handle = register_rfq_observer(Arc::new(move |event: &RfqFeedbackEvent| {
+            if let RfqFeedbackEvent::Quoted(quote) = event {
+                if quote.component_id == "bebop-rfq-reported" {
+                    quotes_clone
+                        .lock()
+                        .unwrap()
+                        .push(quote.clone());
+                }
+            }
+        }));
+        encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+        unregister_rfq_observer(handle);
+
+        let quotes = quotes.lock().unwrap();
+        assert_eq!(quotes.len(), 1);
+        assert_eq!(quotes[0].amount_in, BigUint::from_str("1000000000000000000").unwrap());
+        assert_eq!(quotes[0].quoted_amount_out, BigUint::from_str("3000000000").unwrap());
+        assert_eq!(
+            quotes[0].maker.maker,
+            Some(Bytes::from_str("0xce79b081c0c924cb67848723ed3057234d10fc6b").unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encode_bebop_async_on_current_thread_runtime() {
+        // 1 ETH -> 3000 USDC, awaiting the quote on a single threaded runtime. Selling the native
+        // token needs no approval check.
+        let bebop_calldata = Bytes::from_str("0x123456").unwrap();
+        let bebop_component = ProtocolComponent {
+            id: String::from("bebop-rfq"),
+            protocol_system: String::from("rfq:bebop"),
+            ..Default::default()
+        };
+        let bebop_state = MockRFQState {
+            quote_amount_out: BigUint::from_str("3000000000").unwrap(),
+            quote_data: HashMap::from([
+                ("calldata".to_string(), bebop_calldata.clone()),
+                ("partial_fill_offset".to_string(), Bytes::from(vec![12u8])),
+            ]),
+        };
+
+        let token_in = Bytes::from("0x0000000000000000000000000000000000000000"); // ETH
+        let token_out = Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"); // USDC
+
+        let swap = Swap::new(bebop_component, token_in.clone(), token_out.clone())
+            .estimated_amount_in(BigUint::from_str("1000000000000000000").unwrap())
+            .protocol_state(Arc::new(bebop_state));
+
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0xc5564C13A157E6240659fb81882A28091add8670"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::None,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+
+        let encoder = BebopSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            Some(bebop_config()),
+        )
+        .unwrap();
+
+        let encoded_swap = encoder
+            .encode_swap_async(&swap, &encoding_context)
+            .await
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        let expected_swap = String::from(concat!(
+            // token in
+            "0000000000000000000000000000000000000000",
+            // token out
+            "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            // transfer type
+            "02",
+            // partiall filled offset
+            "0c",
+            //  original taker amount
+            "00000000000000000000000000000000000000000000000000000000b2d05e00",
+            // approval needed
+            "00",
+            //receiver,
+            "c5564c13a157e6240659fb81882a28091add8670",
+        ));
+        assert_eq!(hex_swap, expected_swap + &bebop_calldata.to_string()[2..]);
+    }
 }
@@ -1,77 +1,229 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
 use alloy::{
-    primitives::{Address, U8},
+    primitives::{Address, Bytes as AlloyBytes, TxKind, U256, U8},
+    providers::Provider,
+    rpc::types::{TransactionInput, TransactionRequest},
     sol_types::SolValue,
 };
 use serde_json::from_str;
+use tokio::task::block_in_place;
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
     evm::{
         approvals::protocol_approvals_manager::ProtocolApprovalsManager,
-        utils::{bytes_to_address, get_static_attribute},
+        component_id::component_pool_address,
+        encoding_utils::encode_input,
+        utils::{bytes_to_address, get_client, get_runtime, get_static_attribute},
     },
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
+/// Pool type of the StableSwap-NG pools swapped through `exchange_received`.
+const STABLE_NG_POOL_TYPE: u8 = 10;
+/// Pool type of the StableSwap-NG pools with oracle or rebasing coins, swapped through `exchange`.
+const STABLE_NG_NO_RECEIVED_POOL_TYPE: u8 = 11;
+
 /// Encodes a swap on a Curve pool through the given executor address.
 ///
 /// # Fields
 /// * `executor_address` - The address of the executor contract that will perform the swap.
-/// * `meta_registry_address` - The address of the Curve meta registry contract. Used to get coin
-///   indexes.
+/// * `meta_registry_address` - The address of the Curve meta registry contract. Used to resolve the
+///   type of pools that are not deployed by a known factory. Optional: without it, such pools need
+///   a `pool_type` static attribute.
 /// * `native_token_curve_address` - The address used as native token in curve pools.
 /// * `native_token_address` - The address of the native token.
+/// * `pool_types` - The pool types resolved through the meta registry, shared between clones.
 #[derive(Clone)]
 pub struct CurveSwapEncoder {
     executor_address: Bytes,
+    meta_registry_address: Option<Address>,
     native_token_curve_address: Bytes,
     native_token_address: Bytes,
     wrapped_native_token_address: Bytes,
+    pool_types: Arc<Mutex<HashMap<Address, U8>>>,
 }
 
 impl CurveSwapEncoder {
-    fn get_pool_type(&self, pool_id: &str, factory_address: &str) -> Result<U8, EncodingError> {
+    /// Returns the type of the pool of the given swap, which selects the `exchange` interface used
+    /// by the executor.
+    ///
+    /// The type is taken, in order, from the `pool_type` static attribute, the `pool_variant`
+    /// static attribute of StableSwap-NG pools, the known pools and factories, and finally the meta
+    /// registry (cached per pool). Historical trades are encoded offline, so the meta registry is
+    /// not queried for them.
+    fn get_pool_type(
+        &self,
+        swap: &Swap,
+        pool_address: Address,
+        historical_trade: bool,
+    ) -> Result<U8, EncodingError> {
+        if let Ok(pool_type) = get_static_attribute(swap, "pool_type") {
+            let pool_type = U256::try_from_be_slice(&pool_type)
+                .and_then(|pool_type| u8::try_from(pool_type).ok())
+                .ok_or(EncodingError::FatalError(
+                    "Invalid curve pool type static attribute".to_string(),
+                ))?;
+            return Ok(U8::from(pool_type));
+        }
+        if let Ok(pool_variant) = get_static_attribute(swap, "pool_variant") {
+            return Self::stable_ng_pool_type(std::str::from_utf8(&pool_variant)?);
+        }
+
+        let factory_address = match get_static_attribute(swap, "factory") {
+            Ok(factory_bytes) => {
+                // the conversion to Address is necessary to checksum the address
+                let factory_address =
+                    Address::from_str(std::str::from_utf8(&factory_bytes).map_err(|_| {
+                        EncodingError::FatalError(
+                            "Failed to convert curve factory address to string".to_string(),
+                        )
+                    })?)
+                    .map_err(|_| {
+                        EncodingError::FatalError("Invalid curve factory address".to_string())
+                    })?;
+                Some(factory_address.to_string())
+            }
+            Err(_) => None,
+        };
+        if let Some(pool_type) =
+            Self::known_pool_type(&pool_address.to_string(), factory_address.as_deref())
+        {
+            return Ok(pool_type);
+        }
+
+        if let Some(pool_type) = self
+            .pool_types
+            .lock()
+            .map_err(|_| EncodingError::FatalError("Curve pool types lock poisoned".to_string()))?
+            .get(&pool_address)
+        {
+            return Ok(*pool_type);
+        }
+        if historical_trade {
+            return Err(EncodingError::InvalidInput(format!(
+                "The type of curve pool {pool_address} is unknown. Set its pool_type static \
+                 attribute to encode historical trades"
+            )));
+        }
+
+        let meta_registry_address = self
+            .meta_registry_address
+            .ok_or_else(|| {
+                EncodingError::FatalError(format!(
+                    "Unsupported curve factory address: {}",
+                    factory_address.unwrap_or_else(|| "none".to_string())
+                ))
+            })?;
+        let coins_bytes = get_static_attribute(swap, "coins")?;
+        let coins: Vec<Address> = from_str(std::str::from_utf8(&coins_bytes)?)?;
+        let pool_type = Self::query_pool_type(meta_registry_address, pool_address, coins.len())?;
+        self.pool_types
+            .lock()
+            .map_err(|_| EncodingError::FatalError("Curve pool types lock poisoned".to_string()))?
+            .insert(pool_address, pool_type);
+        Ok(pool_type)
+    }
+
+    /// Returns the type of a StableSwap-NG pool with the given variant.
+    ///
+    /// Pools whose coins are plain ERC20 or ERC4626 tokens (`standard`, `erc4626`) swap the tokens
+    /// sent to them through `exchange_received`. Pools with oracle or rebasing coins (`oracle`,
+    /// `rebasing`) disable `exchange_received`, so they are swapped through `exchange` instead.
+    fn stable_ng_pool_type(pool_variant: &str) -> Result<U8, EncodingError> {
+        match pool_variant {
+            "standard" | "erc4626" => Ok(U8::from(STABLE_NG_POOL_TYPE)),
+            "oracle" | "rebasing" => Ok(U8::from(STABLE_NG_NO_RECEIVED_POOL_TYPE)),
+            _ => Err(EncodingError::FatalError(format!(
+                "Unsupported curve pool variant: {pool_variant}"
+            ))),
+        }
+    }
+
+    /// Returns the type of the known pools and of the pools deployed by known factories.
+    fn known_pool_type(pool_id: &str, factory_address: Option<&str>) -> Option<U8> {
         match pool_id {
             // TriPool
-            "0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7" => Ok(U8::from(1)),
+            "0xbEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7" => Some(U8::from(1)),
             // STETHPool
-            "0xDC24316b9AE028F1497c275EB9192a3Ea0f67022" => Ok(U8::from(1)),
+            "0xDC24316b9AE028F1497c275EB9192a3Ea0f67022" => Some(U8::from(1)),
             // TriCryptoPool
-            "0xD51a44d3FaE010294C616388b506AcdA1bfAAE46" => Ok(U8::from(3)),
+            "0xD51a44d3FaE010294C616388b506AcdA1bfAAE46" => Some(U8::from(3)),
             // SUSDPool
-            "0xA5407eAE9Ba41422680e2e00537571bcC53efBfD" => Ok(U8::from(1)),
+            "0xA5407eAE9Ba41422680e2e00537571bcC53efBfD" => Some(U8::from(1)),
             // FRAXUSDCPool
-            "0xDcEF968d416a41Cdac0ED8702fAC8128A64241A2" => Ok(U8::from(1)),
-            _ => match factory_address {
+            "0xDcEF968d416a41Cdac0ED8702fAC8128A64241A2" => Some(U8::from(1)),
+            _ => match factory_address? {
                 // CryptoSwapNG factory
-                "0x6A8cbed756804B16E05E741eDaBd5cB544AE21bf" => Ok(U8::from(1)),
+                "0x6A8cbed756804B16E05E741eDaBd5cB544AE21bf" => Some(U8::from(1)),
                 // Metapool factory
-                "0xB9fC157394Af804a3578134A6585C0dc9cc990d4" => Ok(U8::from(1)),
+                "0xB9fC157394Af804a3578134A6585C0dc9cc990d4" => Some(U8::from(1)),
                 // CryptoPool factory
-                "0xF18056Bbd320E96A48e3Fbf8bC061322531aac99" => Ok(U8::from(2)),
+                "0xF18056Bbd320E96A48e3Fbf8bC061322531aac99" => Some(U8::from(2)),
                 // Tricrypto factory
-                "0x0c0e5f2fF0ff18a3be9b835635039256dC4B4963" => Ok(U8::from(3)),
+                "0x0c0e5f2fF0ff18a3be9b835635039256dC4B4963" => Some(U8::from(3)),
                 // Twocrypto factory
-                "0x98EE851a00abeE0d95D08cF4CA2BdCE32aeaAF7F" => Ok(U8::from(2)),
+                "0x98EE851a00abeE0d95D08cF4CA2BdCE32aeaAF7F" => Some(U8::from(2)),
                 // StableSwap factory
-                "0x4F8846Ae9380B90d2E71D5e3D042dff3E7ebb40d" => Ok(U8::from(1)),
+                "0x4F8846Ae9380B90d2E71D5e3D042dff3E7ebb40d" => Some(U8::from(1)),
                 // Unichain Tricrypto factory
-                "0x5702BDB1Ec244704E3cBBaAE11a0275aE5b07499" => Ok(U8::from(3)),
+                "0x5702BDB1Ec244704E3cBBaAE11a0275aE5b07499" => Some(U8::from(3)),
                 // Unichain Twocrypto factory
-                "0xc9Fe0C63Af9A39402e8a5514f9c43Af0322b665F" => Ok(U8::from(2)),
+                "0xc9Fe0C63Af9A39402e8a5514f9c43Af0322b665F" => Some(U8::from(2)),
                 // Unichain Core StableSwap factory
-                "0x604388Bb1159AFd21eB5191cE22b4DeCdEE2Ae22" => Ok(U8::from(1)),
-                _ => Err(EncodingError::FatalError(format!(
-                    "Unsupported curve factory address: {factory_address}"
-                ))),
+                "0x604388Bb1159AFd21eB5191cE22b4DeCdEE2Ae22" => Some(U8::from(1)),
+                _ => None,
             },
         }
     }
 
+    /// Resolves the type of a pool through the meta registry.
+    ///
+    /// The registry handlers of stable pools only return the amplification coefficient as pool
+    /// parameters, while the ones of crypto pools also return `D`, `gamma` and the fee parameters.
+    /// Crypto pools with three coins are tricrypto pools.
+    fn query_pool_type(
+        meta_registry_address: Address,
+        pool_address: Address,
+        n_coins: usize,
+    ) -> Result<U8, EncodingError> {
+        let (handle, _runtime) = get_runtime()?;
+        let client = block_in_place(|| handle.block_on(get_client()))?;
+        let data = encode_input("get_pool_params(address)", pool_address.abi_encode());
+        let tx = TransactionRequest {
+            to: Some(TxKind::from(meta_registry_address)),
+            input: TransactionInput { input: Some(AlloyBytes::from(data)), data: None },
+            ..Default::default()
+        };
+        let response = block_in_place(|| handle.block_on(async { client.call(tx).await }))
+            .map_err(|err| {
+                EncodingError::RecoverableError(format!(
+                    "Curve meta registry call for pool {pool_address} failed: {err}"
+                ))
+            })?;
+        let params = <[U256; 20]>::abi_decode(&response).map_err(|_| {
+            EncodingError::FatalError("Failed to decode curve pool params".to_string())
+        })?;
+
+        if params[1..]
+            .iter()
+            .all(|param| param.is_zero())
+        {
+            Ok(U8::from(1))
+        } else if n_coins == 3 {
+            Ok(U8::from(3))
+        } else {
+            Ok(U8::from(2))
+        }
+    }
+
     // Some curve pools support both ETH and WETH as tokens.
     // They do the wrapping/unwrapping inside the pool
     fn normalize_token(&self, token: Address, coins: &[Address]) -> Result<Address, EncodingError> {
@@ -86,6 +238,36 @@ impl CurveSwapEncoder {
         }
     }
 
+    /// Returns whether the swap is called with `use_eth`, i.e. whether the pool wraps the native
+    /// token it receives and unwraps the native token it sends.
+    ///
+    /// This is taken from the `use_eth` static attribute if set. Otherwise, swaps of the native
+    /// token on crypto pools (pool types other than stable ones) whose coins hold the wrapped
+    /// native token use it, while pools holding the raw native token take it as `msg.value`
+    /// without the flag.
+    fn use_eth(
+        &self,
+        swap: &Swap,
+        pool_type: U8,
+        token_in: Address,
+        token_out: Address,
+    ) -> Result<bool, EncodingError> {
+        let native_token_address = Address::from_slice(&self.native_token_curve_address);
+        if token_in != native_token_address && token_out != native_token_address {
+            return Ok(false);
+        }
+        if let Ok(use_eth) = get_static_attribute(swap, "use_eth") {
+            return Ok(use_eth.iter().any(|byte| *byte != 0));
+        }
+        if [1, STABLE_NG_POOL_TYPE, STABLE_NG_NO_RECEIVED_POOL_TYPE].contains(&pool_type.to::<u8>())
+        {
+            return Ok(false);
+        }
+        let coins_bytes = get_static_attribute(swap, "coins")?;
+        let coins: Vec<Address> = from_str(std::str::from_utf8(&coins_bytes)?)?;
+        Ok(!coins.contains(&native_token_address))
+    }
+
     fn get_coin_indexes(
         &self,
         swap: &Swap,
@@ -114,6 +296,30 @@ impl CurveSwapEncoder {
     }
 }
 
+/// Returns true if the Curve pool of the given swap takes the raw native token, i.e. if its
+/// `coins` static attribute contains `native_token_curve_address` but not the wrapped native token,
+/// or if it holds the wrapped native token and its `use_eth` static attribute is set (the pool
+/// wraps the native token itself).
+///
+/// Swaps on these pools have to be funded with the native token already in the router (forwarded
+/// as `msg.value` for first hops) instead of the wrapped token.
+pub fn takes_raw_native_token(
+    swap: &Swap,
+    native_token_curve_address: &Bytes,
+    wrapped_native_token_address: &Bytes,
+) -> Result<bool, EncodingError> {
+    let coins_bytes = get_static_attribute(swap, "coins")?;
+    let coins: Vec<Address> = from_str(std::str::from_utf8(&coins_bytes)?)?;
+    let native_token = bytes_to_address(native_token_curve_address)?;
+    let wrapped_native_token = bytes_to_address(wrapped_native_token_address)?;
+    if coins.contains(&wrapped_native_token) {
+        return Ok(get_static_attribute(swap, "use_eth")
+            .map(|use_eth| use_eth.iter().any(|byte| *byte != 0))
+            .unwrap_or(false));
+    }
+    Ok(coins.contains(&native_token))
+}
+
 impl SwapEncoder for CurveSwapEncoder {
     fn new(
         executor_address: Bytes,
@@ -134,11 +340,21 @@ impl SwapEncoder for CurveSwapEncoder {
                 "Missing native token curve address in config".to_string(),
             ))
             .flatten()?;
+        let meta_registry_address = config
+            .get("meta_registry_address")
+            .map(|s| {
+                Address::from_str(s).map_err(|_| {
+                    EncodingError::FatalError("Invalid curve meta registry address".to_string())
+                })
+            })
+            .transpose()?;
         Ok(Self {
             executor_address,
+            meta_registry_address,
             native_token_address: chain.native_token().address,
             native_token_curve_address,
             wrapped_native_token_address: chain.wrapped_native_token().address,
+            pool_types: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
@@ -147,7 +363,6 @@ impl SwapEncoder for CurveSwapEncoder {
         swap: &Swap,
         encoding_context: &EncodingContext,
     ) -> Result<Vec<u8>, EncodingError> {
-        let token_approvals_manager = ProtocolApprovalsManager::new()?;
         let native_token_curve_address = Address::from_slice(&self.native_token_curve_address);
         let token_in = if *swap.token_in() == self.native_token_address {
             native_token_curve_address
@@ -161,38 +376,30 @@ impl SwapEncoder for CurveSwapEncoder {
         };
         let approval_needed: bool;
 
-        let component_address = Address::from_str(&swap.component().id)
-            .map_err(|_| EncodingError::FatalError("Invalid curve pool address".to_string()))?;
-        if let Some(router_address) = &encoding_context.router_address {
-            if token_in != native_token_curve_address {
+        let component_address = component_pool_address(swap.component())?;
+        let pool_type =
+            self.get_pool_type(swap, component_address, encoding_context.historical_trade)?;
+        if pool_type == U8::from(STABLE_NG_POOL_TYPE) {
+            // the tokens are sent to the pool before `exchange_received`
+            approval_needed = false;
+        } else if let Some(router_address) = &encoding_context.router_address {
+            if token_in == native_token_curve_address {
+                approval_needed = false;
+            } else if let Some(fixed_approval) = encoding_context.fixed_approval() {
+                approval_needed = fixed_approval;
+            } else {
+                let token_approvals_manager = ProtocolApprovalsManager::new()?;
                 let tycho_router_address = bytes_to_address(router_address)?;
                 approval_needed = token_approvals_manager.approval_needed(
                     token_in,
                     tycho_router_address,
                     component_address,
                 )?;
-            } else {
-                approval_needed = false;
             }
         } else {
             approval_needed = true;
         }
 
-        let factory_bytes = get_static_attribute(swap, "factory")?.to_vec();
-        // the conversion to Address is necessary to checksum the address
-        let factory_address =
-            Address::from_str(std::str::from_utf8(&factory_bytes).map_err(|_| {
-                EncodingError::FatalError(
-                    "Failed to convert curve factory address to string".to_string(),
-                )
-            })?)
-            .map_err(|_| EncodingError::FatalError("Invalid curve factory address".to_string()))?;
-
-        let pool_address = Address::from_str(&swap.component().id)
-            .map_err(|_| EncodingError::FatalError("Invalid curve pool address".to_string()))?;
-        let pool_type =
-            self.get_pool_type(&pool_address.to_string(), &factory_address.to_string())?;
-
         let (i, j) = self.get_coin_indexes(swap, token_in, token_out)?;
 
         let args = (
@@ -205,11 +412,20 @@ impl SwapEncoder for CurveSwapEncoder {
             approval_needed,
             (encoding_context.transfer_type as u8).to_be_bytes(),
             bytes_to_address(&encoding_context.receiver)?,
+            self.use_eth(swap, pool_type, token_in, token_out)?,
         );
 
         Ok(args.abi_encode_packed())
     }
 
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -225,7 +441,10 @@ mod tests {
     use tycho_common::models::protocol::ProtocolComponent;
 
     use super::*;
-    use crate::encoding::{evm::swap_encoder::curve::CurveSwapEncoder, models::TransferType};
+    use crate::encoding::{
+        evm::swap_encoder::curve::CurveSwapEncoder,
+        models::{ApprovalPolicy, TransferType},
+    };
 
     fn curve_config() -> Option<HashMap<String, String>> {
         Some(HashMap::from([
@@ -349,6 +568,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::None,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = CurveSwapEncoder::new(
             Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
@@ -382,6 +603,8 @@ mod tests {
                 "02",
                 // receiver,
                 "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // use eth
+                "00",
             ))
         );
     }
@@ -416,6 +639,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::None,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = CurveSwapEncoder::new(
             Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
@@ -449,6 +674,8 @@ mod tests {
                 "02",
                 // receiver
                 "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // use eth
+                "00",
             ))
         );
     }
@@ -484,6 +711,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::None,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = CurveSwapEncoder::new(
             Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
@@ -526,7 +755,304 @@ mod tests {
                 "02",
                 // receiver
                 "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // use eth
+                "00",
+            ))
+        );
+    }
+
+    fn unknown_factory_swap(extra_attributes: HashMap<String, Bytes>) -> Swap {
+        let mut static_attributes: HashMap<String, Bytes> = extra_attributes;
+        static_attributes.insert(
+            "factory".into(),
+            Bytes::from(
+                "0x1111111111111111111111111111111111111111"
+                    .as_bytes()
+                    .to_vec(),
+            ),
+        );
+        static_attributes.insert("coins".into(), Bytes::from_str("0x5b22307834633965646435383532636439303566303836633735396538333833653039626666316536386233222c22307861306238363939316336323138623336633164313964346132653965623063653336303665623438225d").unwrap());
+        let curve_pool = ProtocolComponent {
+            id: String::from("0x02950460E2b9529D0E00284A5fA2d7bDF3fA4d72"),
+            protocol_system: String::from("vm:curve"),
+            static_attributes,
+            ..Default::default()
+        };
+        Swap::new(
+            curve_pool,
+            Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            Bytes::from("0x4c9EDD5852cd905f086C759E8383e09bff1E68B3"),
+        )
+    }
+
+    fn encoding_context(swap: &Swap) -> EncodingContext {
+        EncodingContext {
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: None,
+            group_token_in: swap.token_in().clone(),
+            group_token_out: swap.token_out().clone(),
+            transfer_type: TransferType::None,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    #[test]
+    fn test_curve_pool_type_from_static_attribute() {
+        let swap = unknown_factory_swap(HashMap::from([(
+            "pool_type".to_string(),
+            Bytes::from(vec![2u8]),
+        )]));
+        let encoder = CurveSwapEncoder::new(
+            Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+            Chain::Ethereum,
+            curve_config(),
+        )
+        .unwrap();
+
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context(&swap))
+            .unwrap();
+
+        // the pool type follows the token in, token out and pool addresses
+        assert_eq!(encoded_swap[60], 2);
+    }
+
+    #[test]
+    fn test_curve_encode_stable_ng_rebasing() {
+        // stETH is a rebasing coin, so the pool is swapped through `exchange`
+        let mut static_attributes: HashMap<String, Bytes> = HashMap::new();
+        static_attributes
+            .insert("pool_variant".into(), Bytes::from("rebasing".as_bytes().to_vec()));
+        static_attributes.insert("coins".into(), Bytes::from_str("0x5b22307863303261616133396232323366653864306130653563346632376561643930383363373536636332222c22307861653761623936353230646533613138653565313131623565616162303935333132643766653834225d").unwrap());
+        let curve_pool = ProtocolComponent {
+            id: String::from("0x21E27a5E5513D6e65C4f830167390997aA84843a"),
+            protocol_system: String::from("vm:curve"),
+            static_attributes,
+            ..Default::default()
+        };
+        let swap = Swap::new(
+            curve_pool,
+            Bytes::from("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84"),
+            Bytes::from("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+        );
+        let encoder = CurveSwapEncoder::new(
+            Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+            Chain::Ethereum,
+            curve_config(),
+        )
+        .unwrap();
+
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context(&swap))
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // token in
+                "ae7ab96520de3a18e5e111b5eaab095312d7fe84",
+                // token out
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                // pool address
+                "21e27a5e5513d6e65c4f830167390997aa84843a",
+                // pool type 11
+                "0b",
+                // i index
+                "01",
+                // j index
+                "00",
+                // approval needed
+                "01",
+                // transfer type None
+                "02",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // use eth
+                "00",
             ))
         );
     }
+
+    #[test]
+    fn test_curve_encode_use_eth() {
+        // ETH -> USDC on a crypto pool holding WETH
+        let crypto_pool_swap = |extra_attributes: Vec<(&str, Bytes)>| {
+            let mut static_attributes: HashMap<String, Bytes> = extra_attributes
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect();
+            static_attributes.insert("pool_type".into(), Bytes::from(vec![2u8]));
+            static_attributes.insert(
+                "coins".into(),
+                Bytes::from(
+                    r#"["0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2","0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"]"#
+                        .as_bytes()
+                        .to_vec(),
+                ),
+            );
+            Swap::new(
+                ProtocolComponent {
+                    id: String::from("0x7F86Bf177Dd4F3494b841a37e810A34dD56c829B"),
+                    protocol_system: String::from("vm:curve"),
+                    static_attributes,
+                    ..Default::default()
+                },
+                Bytes::from("0x0000000000000000000000000000000000000000"),
+                Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            )
+        };
+        let encoder = CurveSwapEncoder::new(
+            Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+            Chain::Ethereum,
+            curve_config(),
+        )
+        .unwrap();
+
+        let swap = crypto_pool_swap(vec![]);
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context(&swap))
+            .unwrap();
+        // the pool wraps the ETH sent with the swap
+        assert_eq!(encoded_swap[85], 1);
+
+        let swap = crypto_pool_swap(vec![("use_eth", Bytes::from(vec![0u8]))]);
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context(&swap))
+            .unwrap();
+        assert_eq!(encoded_swap[85], 0);
+        assert!(!takes_raw_native_token(
+            &swap,
+            &Bytes::from("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE"),
+            &Chain::Ethereum
+                .wrapped_native_token()
+                .address
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_curve_encode_stable_ng_standard() {
+        let swap = unknown_factory_swap(HashMap::from([(
+            "pool_variant".to_string(),
+            Bytes::from("standard".as_bytes().to_vec()),
+        )]));
+        let encoder = CurveSwapEncoder::new(
+            Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+            Chain::Ethereum,
+            curve_config(),
+        )
+        .unwrap();
+
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context(&swap))
+            .unwrap();
+
+        assert_eq!(encoded_swap[60], 10);
+        // the tokens are sent to the pool, so it is never approved
+        assert_eq!(encoded_swap[63], 0);
+    }
+
+    #[test]
+    fn test_curve_encode_stable_ng_invalid_variant() {
+        let swap = unknown_factory_swap(HashMap::from([(
+            "pool_variant".to_string(),
+            Bytes::from("lending".as_bytes().to_vec()),
+        )]));
+        let encoder = CurveSwapEncoder::new(
+            Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+            Chain::Ethereum,
+            curve_config(),
+        )
+        .unwrap();
+
+        let result = encoder.encode_swap(&swap, &encoding_context(&swap));
+
+        assert!(matches!(
+            result,
+            Err(EncodingError::FatalError(msg)) if msg.starts_with("Unsupported curve pool variant")
+        ));
+    }
+
+    #[test]
+    fn test_curve_pool_type_from_cache() {
+        let swap = unknown_factory_swap(HashMap::new());
+        let encoder = CurveSwapEncoder::new(
+            Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+            Chain::Ethereum,
+            curve_config(),
+        )
+        .unwrap();
+        encoder
+            .pool_types
+            .lock()
+            .unwrap()
+            .insert(
+                Address::from_str("0x02950460E2b9529D0E00284A5fA2d7bDF3fA4d72").unwrap(),
+                U8::from(3),
+            );
+
+        let encoded_swap = encoder
+            .clone()
+            .encode_swap(&swap, &encoding_context(&swap))
+            .unwrap();
+
+        assert_eq!(encoded_swap[60], 3);
+    }
+
+    #[test]
+    fn test_curve_historical_trade() {
+        let encoder = CurveSwapEncoder::new(
+            Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+            Chain::Ethereum,
+            curve_config(),
+        )
+        .unwrap();
+        let historical_context = |swap: &Swap| EncodingContext {
+            router_address: Some(Bytes::from("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66")),
+            historical_trade: true,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+            ..encoding_context(swap)
+        };
+
+        // The pool is approved without looking up the router's allowance
+        let swap = unknown_factory_swap(HashMap::from([(
+            "pool_type".to_string(),
+            Bytes::from(vec![2u8]),
+        )]));
+        let encoded_swap = encoder
+            .encode_swap(&swap, &historical_context(&swap))
+            .unwrap();
+        assert_eq!(encoded_swap[63], 1);
+
+        // The meta registry isn't queried for the pool type
+        let swap = unknown_factory_swap(HashMap::new());
+        let result = encoder.encode_swap(&swap, &historical_context(&swap));
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_curve_unknown_factory_without_meta_registry() {
+        let swap = unknown_factory_swap(HashMap::new());
+        let encoder = CurveSwapEncoder::new(
+            Bytes::from("0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"),
+            Chain::Ethereum,
+            Some(HashMap::from([(
+                "native_token_address".to_string(),
+                "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE".to_string(),
+            )])),
+        )
+        .unwrap();
+
+        let result = encoder.encode_swap(&swap, &encoding_context(&swap));
+
+        assert!(matches!(
+            result,
+            Err(EncodingError::FatalError(msg)) if msg.starts_with("Unsupported curve factory address")
+        ));
+    }
 }
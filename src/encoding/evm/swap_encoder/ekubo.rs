@@ -8,7 +8,7 @@ use crate::encoding::{
     errors::EncodingError,
     evm::utils::{bytes_to_address, get_static_attribute},
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 /// Encodes a swap on an Ekubo pool through the given executor address.
@@ -71,6 +71,10 @@ impl SwapEncoder for EkuboSwapEncoder {
         Ok(encoded)
     }
 
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { groupable: true, callback_constrained: true, ..Default::default() }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -88,7 +92,7 @@ mod tests {
     use super::*;
     use crate::encoding::{
         evm::{swap_encoder::ekubo::EkuboSwapEncoder, utils::write_calldata_to_file},
-        models::TransferType,
+        models::{ApprovalPolicy, TransferType},
     };
 
     const RECEIVER: &str = "ca4f73fe97d0b987a0d12b39bbd562c779bab6f6"; // Random address
@@ -116,6 +120,8 @@ mod tests {
             router_address: Some(Bytes::default()),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
 
         let encoder = EkuboSwapEncoder::new(Bytes::default(), Chain::Ethereum, None).unwrap();
@@ -159,6 +165,8 @@ mod tests {
             router_address: Some(Bytes::default()),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
 
         let first_swap = Swap::new(
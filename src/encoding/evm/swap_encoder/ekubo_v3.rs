@@ -10,7 +10,7 @@ use crate::encoding::{
     errors::EncodingError,
     evm::utils::{bytes_to_address, get_static_attribute},
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 /// Encodes a swap on an Ekubo V3 pool through the given executor address.
@@ -70,6 +70,10 @@ impl SwapEncoder for EkuboV3SwapEncoder {
         Ok(encoded)
     }
 
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { groupable: true, callback_constrained: true, ..Default::default() }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -87,7 +91,10 @@ mod tests {
     use tycho_common::models::protocol::ProtocolComponent;
 
     use super::*;
-    use crate::encoding::{evm::utils::write_calldata_to_file, models::TransferType};
+    use crate::encoding::{
+        evm::utils::write_calldata_to_file,
+        models::{ApprovalPolicy, TransferType},
+    };
 
     const RECEIVER: &str = "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2"; // ALICE
 
@@ -114,6 +121,8 @@ mod tests {
             router_address: Some(Bytes::default()),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
 
         let encoder = EkuboV3SwapEncoder::new(Bytes::default(), Chain::Ethereum, None).unwrap();
@@ -157,6 +166,8 @@ mod tests {
             router_address: Some(Bytes::default()),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
 
         let first_swap = Swap::new(
@@ -1,18 +1,16 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
-use alloy::{
-    primitives::{Address, Bytes as AlloyBytes},
-    sol_types::SolValue,
-};
+use alloy::sol_types::SolValue;
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
     evm::{
-        approvals::protocol_approvals_manager::ProtocolApprovalsManager, utils::bytes_to_address,
+        approvals::protocol_approvals_manager::ProtocolApprovalsManager,
+        component_id::component_pool_address, utils::bytes_to_address,
     },
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 #[derive(Clone)]
@@ -34,12 +32,10 @@ impl SwapEncoder for ERC4626SwapEncoder {
         swap: &Swap,
         encoding_context: &EncodingContext,
     ) -> Result<Vec<u8>, EncodingError> {
-        let component_id = AlloyBytes::from_str(&swap.component().id)
-            .map_err(|_| EncodingError::FatalError("Invalid component ID".to_string()))?;
         let token_approvals_manager = ProtocolApprovalsManager::new()?;
         let token = bytes_to_address(swap.token_in())?;
         let token_out = bytes_to_address(swap.token_out())?;
-        let pool_address = Address::from_slice(&component_id);
+        let pool_address = component_pool_address(swap.component())?;
         let mut approval_needed: bool = false;
 
         if let Some(router_address) = &encoding_context.router_address {
@@ -55,7 +51,7 @@ impl SwapEncoder for ERC4626SwapEncoder {
         };
         let args = (
             bytes_to_address(swap.token_in())?,
-            component_id,
+            pool_address,
             bytes_to_address(&encoding_context.receiver)?,
             (encoding_context.transfer_type as u8).to_be_bytes(),
             approval_needed,
@@ -63,6 +59,14 @@ impl SwapEncoder for ERC4626SwapEncoder {
         Ok(args.abi_encode_packed())
     }
 
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -77,7 +81,7 @@ mod tests {
     use tycho_common::models::protocol::ProtocolComponent;
 
     use super::*;
-    use crate::encoding::models::TransferType;
+    use crate::encoding::models::{ApprovalPolicy, TransferType};
     #[test]
     fn test_encode_erc4626_deposit() {
         // WETH -> (spETH) -> spETH
@@ -98,6 +102,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::TransferFrom,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = ERC4626SwapEncoder::new(
             Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
@@ -149,6 +155,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::TransferFrom,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = ERC4626SwapEncoder::new(
             Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
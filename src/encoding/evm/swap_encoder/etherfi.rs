@@ -9,7 +9,7 @@ use crate::encoding::{
         approvals::protocol_approvals_manager::ProtocolApprovalsManager, utils::bytes_to_address,
     },
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 /// Encodes a swap on a Etherfi pool through the given executor address.
@@ -110,6 +110,14 @@ impl SwapEncoder for EtherfiSwapEncoder {
         Ok(args.abi_encode_packed())
     }
 
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -146,7 +154,7 @@ mod tests {
     use tycho_common::models::protocol::ProtocolComponent;
 
     use super::*;
-    use crate::encoding::models::TransferType;
+    use crate::encoding::models::{ApprovalPolicy, TransferType};
 
     const EETH_ADDRESS: &str = "0x35fA164735182de50811E8e2E824cFb9B6118ac2";
     const WEETH_ADDRESS: &str = "0xCd5fE23C85820F7B72D0926FC9b05b43E359b7ee";
@@ -169,6 +177,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::None,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         }
     }
 
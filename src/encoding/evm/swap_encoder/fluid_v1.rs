@@ -1,13 +1,13 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
-use alloy::{primitives::Address, sol_types::SolValue};
+use alloy::sol_types::SolValue;
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
-    evm::utils::bytes_to_address,
+    evm::{component_id::component_pool_address, utils::bytes_to_address},
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 #[derive(Clone)]
@@ -35,12 +35,7 @@ impl SwapEncoder for FluidV1SwapEncoder {
         swap: &Swap,
         encoding_context: &EncodingContext,
     ) -> Result<Vec<u8>, EncodingError> {
-        let dex_address = Address::from_str(&swap.component().id).map_err(|_| {
-            EncodingError::FatalError(format!(
-                "Failed parsing FluidV1 component id as ethereum address: {}",
-                &swap.component().id
-            ))
-        })?;
+        let dex_address = component_pool_address(swap.component())?;
 
         let args = (
             dex_address,
@@ -53,6 +48,10 @@ impl SwapEncoder for FluidV1SwapEncoder {
         Ok(args.abi_encode_packed())
     }
 
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { callback_constrained: true, ..Default::default() }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -78,7 +77,10 @@ mod tests {
     use tycho_common::models::protocol::ProtocolComponent;
 
     use super::*;
-    use crate::encoding::{evm::swap_encoder::fluid_v1::FluidV1SwapEncoder, models::TransferType};
+    use crate::encoding::{
+        evm::swap_encoder::fluid_v1::FluidV1SwapEncoder,
+        models::{ApprovalPolicy, TransferType},
+    };
     #[test]
     fn test_encode_fluid_v1() {
         // sUSDe -> (fluid_v1) -> USDT
@@ -99,6 +101,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::TransferFrom,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = FluidV1SwapEncoder::new(
             Bytes::from("0x212224D2F2d262cd093eE13240ca4873fcCBbA3C"),
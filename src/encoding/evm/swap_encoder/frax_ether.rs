@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use alloy::sol_types::SolValue;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        approvals::protocol_approvals_manager::ProtocolApprovalsManager, utils::bytes_to_address,
+    },
+    models::{EncodingContext, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+/// Encodes a Frax Ether staking swap through the given executor address: minting frxETH from ETH,
+/// or wrapping (unwrapping) frxETH into (from) sfrxETH.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `frxeth_address` - The address of the frxETH token.
+/// * `sfrxeth_address` - The address of the sfrxETH vault.
+/// * `eth_address` - The address of the native token (only ETH).
+#[derive(Clone)]
+pub struct FraxStakingSwapEncoder {
+    executor_address: Bytes,
+    frxeth_address: Bytes,
+    sfrxeth_address: Bytes,
+    eth_address: Bytes,
+}
+
+#[repr(u8)]
+#[allow(clippy::enum_variant_names)]
+enum FraxEtherDirection {
+    EthToFrxEth = 0,
+    FrxEthToSfrxEth = 1,
+    SfrxEthToFrxEth = 2,
+}
+
+impl SwapEncoder for FraxStakingSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        if chain != Chain::Ethereum {
+            return Err(EncodingError::FatalError(
+                "Frax Ether swaps are only supported on Ethereum".to_string(),
+            ));
+        }
+        let config = config
+            .ok_or_else(|| EncodingError::FatalError("Frax Ether config is empty".to_string()))?;
+
+        let frxeth_address = config
+            .get("frxeth_address")
+            .map(|a| Bytes::from(a.as_str()))
+            .ok_or_else(|| {
+                EncodingError::FatalError("Missing frxeth_address in frax_ether config".to_string())
+            })?;
+
+        let sfrxeth_address = config
+            .get("sfrxeth_address")
+            .map(|a| Bytes::from(a.as_str()))
+            .ok_or_else(|| {
+                EncodingError::FatalError(
+                    "Missing sfrxeth_address in frax_ether config".to_string(),
+                )
+            })?;
+
+        Ok(Self {
+            executor_address,
+            frxeth_address,
+            sfrxeth_address,
+            eth_address: chain.native_token().address,
+        })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let (direction, approval_needed) =
+            if *swap.token_in() == self.eth_address && *swap.token_out() == self.frxeth_address {
+                (FraxEtherDirection::EthToFrxEth, false)
+            } else if *swap.token_in() == self.frxeth_address &&
+                *swap.token_out() == self.sfrxeth_address
+            {
+                let approval_needed = self.approval_needed(
+                    encoding_context,
+                    &self.frxeth_address,
+                    &self.sfrxeth_address,
+                )?;
+                (FraxEtherDirection::FrxEthToSfrxEth, approval_needed)
+            } else if *swap.token_in() == self.sfrxeth_address &&
+                *swap.token_out() == self.frxeth_address
+            {
+                (FraxEtherDirection::SfrxEthToFrxEth, false)
+            } else {
+                return Err(EncodingError::InvalidInput("Combination not allowed".to_owned()))
+            };
+
+        let args = (
+            bytes_to_address(&encoding_context.receiver)?,
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            (direction as u8).to_be_bytes(),
+            approval_needed,
+        );
+
+        Ok(args.abi_encode_packed())
+    }
+
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+impl FraxStakingSwapEncoder {
+    fn approval_needed(
+        &self,
+        encoding_context: &EncodingContext,
+        token_address: &Bytes,
+        spender_address: &Bytes,
+    ) -> Result<bool, EncodingError> {
+        if let Some(router_address) = &encoding_context.router_address {
+            if !encoding_context.historical_trade {
+                let token_approvals_manager = ProtocolApprovalsManager::new()?;
+                return token_approvals_manager.approval_needed(
+                    bytes_to_address(token_address)?,
+                    bytes_to_address(router_address)?,
+                    bytes_to_address(spender_address)?,
+                );
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::{ApprovalPolicy, TransferType};
+
+    const FRXETH_ADDRESS: &str = "0x5E8422345238F34275888049021821E8E08CAa1f";
+    const SFRXETH_ADDRESS: &str = "0xac3E018457B222d93114458476f3E3416Abbe38F";
+
+    fn frax_ether_config() -> HashMap<String, String> {
+        HashMap::from([
+            ("frxeth_address".to_string(), FRXETH_ADDRESS.to_string()),
+            ("sfrxeth_address".to_string(), SFRXETH_ADDRESS.to_string()),
+        ])
+    }
+
+    fn encoding_context(token_in: &Bytes, token_out: &Bytes) -> EncodingContext {
+        EncodingContext {
+            receiver: Bytes::from("0x1D96F2f6BeF1202E4Ce1Ff6Dad0c2CB002861d3e"),
+            exact_out: false,
+            router_address: None,
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::None,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    fn encoder() -> FraxStakingSwapEncoder {
+        FraxStakingSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            Some(frax_ether_config()),
+        )
+        .unwrap()
+    }
+
+    fn encode_frax_ether_swap(token_in: &str, token_out: &str) -> Result<String, EncodingError> {
+        let component = ProtocolComponent {
+            id: String::from("0xbafa44efe7901e04e39dad13167d089c559c1138"),
+            protocol_system: String::from("frax_ether"),
+            ..Default::default()
+        };
+        let token_in = Bytes::from(token_in);
+        let token_out = Bytes::from(token_out);
+        let swap = Swap::new(component, token_in.clone(), token_out.clone());
+        let encoded_swap =
+            encoder().encode_swap(&swap, &encoding_context(&token_in, &token_out))?;
+        Ok(encode(&encoded_swap))
+    }
+
+    #[test]
+    fn test_encode_frax_ether_eth_to_frxeth() {
+        let hex_swap =
+            encode_frax_ether_swap("0x0000000000000000000000000000000000000000", FRXETH_ADDRESS)
+                .unwrap();
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // receiver
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // transfer type None
+                "02",
+                // direction EthToFrxEth
+                "00",
+                // approval_needed
+                "00",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_frax_ether_frxeth_to_sfrxeth() {
+        let hex_swap = encode_frax_ether_swap(FRXETH_ADDRESS, SFRXETH_ADDRESS).unwrap();
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // receiver
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // transfer type None
+                "02",
+                // direction FrxEthToSfrxEth
+                "01",
+                // approval_needed
+                "01",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_frax_ether_sfrxeth_to_frxeth() {
+        let hex_swap = encode_frax_ether_swap(SFRXETH_ADDRESS, FRXETH_ADDRESS).unwrap();
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // receiver
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // transfer type None
+                "02",
+                // direction SfrxEthToFrxEth
+                "02",
+                // approval_needed
+                "00",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_frax_ether_invalid_pair() {
+        // frxETH can't be redeemed for ETH through the minter
+        let result =
+            encode_frax_ether_swap(FRXETH_ADDRESS, "0x0000000000000000000000000000000000000000");
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -1,23 +1,32 @@
 use std::{collections::HashMap, str::FromStr, sync::Arc};
 
-use alloy::{primitives::Address, sol_types::SolValue};
+use alloy::{
+    primitives::{Address, U256},
+    sol_types::SolValue,
+};
+use async_trait::async_trait;
+use chrono::Utc;
 use tokio::{
     runtime::{Handle, Runtime},
     task::block_in_place,
 };
 use tycho_common::{
     models::{protocol::GetAmountOutParams, Chain},
+    simulation::indicatively_priced::SignedQuote,
     Bytes,
 };
 
+#[cfg(feature = "failure-injection")]
+use crate::encoding::evm::failure_injection::{inject, InjectionPoint};
 use crate::encoding::{
     errors::EncodingError,
     evm::{
         approvals::protocol_approvals_manager::ProtocolApprovalsManager,
-        utils::{bytes_to_address, get_runtime},
+        rfq_feedback::notify_quote,
+        utils::{bytes_to_address, copy_quote_params, get_runtime, recorded_quote},
     },
-    models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    models::{EncodingContext, RfqConfig, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 #[derive(Clone)]
@@ -25,48 +34,50 @@ pub struct HashflowSwapEncoder {
     executor_address: Bytes,
     hashflow_router_address: Bytes,
     native_token_address: Bytes,
+    wrapped_native_token_address: Bytes,
     runtime_handle: Handle,
     #[allow(dead_code)]
     runtime: Option<Arc<Runtime>>,
+    rfq_config: RfqConfig,
 }
 
-impl SwapEncoder for HashflowSwapEncoder {
-    fn new(
-        executor_address: Bytes,
-        chain: Chain,
-        config: Option<HashMap<String, String>>,
-    ) -> Result<Self, EncodingError> {
-        let config = config.ok_or(EncodingError::FatalError(
-            "Missing hashflow specific addresses in config".to_string(),
-        ))?;
-        let hashflow_router_address = config
-            .get("hashflow_router_address")
-            .map(|s| {
-                Bytes::from_str(s).map_err(|_| {
-                    EncodingError::FatalError("Invalid hashflow router address".to_string())
-                })
-            })
-            .ok_or(EncodingError::FatalError(
-                "Missing hashflow router address in config".to_string(),
-            ))
-            .flatten()?;
-        let native_token_address = chain.native_token().address;
-        let (runtime_handle, runtime) = get_runtime()?;
-        Ok(Self {
-            executor_address,
-            hashflow_router_address,
-            native_token_address,
-            runtime_handle,
-            runtime,
-        })
+/// Flag of the native byte of swaps selling the native token, wrapped by the executor.
+const NATIVE_IN_FLAG: u8 = 1;
+/// Flag of the native byte of swaps buying the native token, unwrapped by the executor.
+const NATIVE_OUT_FLAG: u8 = 2;
+
+impl HashflowSwapEncoder {
+    /// Returns the token the quote is requested for: the native token is quoted as its wrapped
+    /// version, which the executor wraps and unwraps internally.
+    fn quoted_token(&self, token: &Bytes) -> Bytes {
+        if *token == self.native_token_address {
+            self.wrapped_native_token_address
+                .clone()
+        } else {
+            token.clone()
+        }
     }
 
-    fn encode_swap(
+    /// Returns the native byte of the swap (see `NATIVE_IN_FLAG` and `NATIVE_OUT_FLAG`).
+    fn native_flags(&self, swap: &Swap) -> u8 {
+        let mut flags = 0;
+        if *swap.token_in() == self.native_token_address {
+            flags |= NATIVE_IN_FLAG;
+        }
+        if *swap.token_out() == self.native_token_address {
+            flags |= NATIVE_OUT_FLAG;
+        }
+        flags
+    }
+
+    /// Returns the token, owner (the router) and spender (the Hashflow router) of the allowance to
+    /// check before settling the swap. Swaps selling the native token check the allowance of the
+    /// wrapped token, which the executor settles the quote with.
+    fn approval_check(
         &self,
         swap: &Swap,
         encoding_context: &EncodingContext,
-    ) -> Result<Vec<u8>, EncodingError> {
-        // Native tokens doesn't need approval, only ERC20 tokens do
+    ) -> Result<(Address, Address, Address), EncodingError> {
         let sender = encoding_context
             .router_address
             .clone()
@@ -74,26 +85,23 @@ impl SwapEncoder for HashflowSwapEncoder {
                 "The router address is needed to perform a Hashflow swap".to_string(),
             ))?;
 
-        // Native ETH doesn't need approval, only ERC20 tokens do
-        let approval_needed = if *swap.token_in() == self.native_token_address {
-            false
-        } else {
-            let tycho_router_address = bytes_to_address(&sender)?;
-            let hashflow_router_address = Address::from_slice(&self.hashflow_router_address);
-            ProtocolApprovalsManager::new()?.approval_needed(
-                bytes_to_address(swap.token_in())?,
-                tycho_router_address,
-                hashflow_router_address,
-            )?
-        };
+        let tycho_router_address = bytes_to_address(&sender)?;
+        let hashflow_router_address = Address::from_slice(&self.hashflow_router_address);
+        Ok((
+            bytes_to_address(&self.quoted_token(swap.token_in()))?,
+            tycho_router_address,
+            hashflow_router_address,
+        ))
+    }
 
-        // Get quote
-        let protocol_state = swap
-            .get_protocol_state()
-            .as_ref()
-            .ok_or_else(|| {
-                EncodingError::FatalError("protocol_state is required for Hashflow".to_string())
-            })?;
+    /// Returns the parameters of the signed quote to request for the swap.
+    fn quote_params(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<GetAmountOutParams, EncodingError> {
+        #[cfg(feature = "failure-injection")]
+        inject(InjectionPoint::RfqQuote)?;
         let amount_in = swap
             .get_estimated_amount_in()
             .as_ref()
@@ -107,23 +115,53 @@ impl SwapEncoder for HashflowSwapEncoder {
             .ok_or(EncodingError::FatalError(
                 "The router address is needed to perform a Hashflow swap".to_string(),
             ))?;
-        let signed_quote = block_in_place(|| {
-            self.runtime_handle.block_on(async {
-                protocol_state
-                    .as_indicatively_priced()?
-                    .request_signed_quote(GetAmountOutParams {
-                        amount_in,
-                        token_in: swap.token_in().clone(),
-                        token_out: swap.token_out().clone(),
-                        sender,
-                        receiver: encoding_context.receiver.clone(),
-                    })
-                    .await
-            })
-        })?;
+        // The wrapped token bought for the native token is paid to the router to be unwrapped
+        let receiver = if self.native_flags(swap) & NATIVE_OUT_FLAG != 0 {
+            sender.clone()
+        } else {
+            encoding_context.receiver.clone()
+        };
+        Ok(GetAmountOutParams {
+            amount_in,
+            token_in: self.quoted_token(swap.token_in()),
+            token_out: self.quoted_token(swap.token_out()),
+            sender,
+            receiver,
+        })
+    }
 
+    /// Encodes the swap settling the signed quote, and reports the quote to the RFQ observers.
+    ///
+    /// Returns an `EncodingError::QuoteExpired` if the quote expires too soon (see
+    /// `RfqConfig::check_quote_expiry`), unless the trade is historical.
+    fn encode_signed_quote(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+        approval_needed: bool,
+        signed_quote: SignedQuote,
+    ) -> Result<Vec<u8>, EncodingError> {
+        if !encoding_context.historical_trade {
+            let quote_expiry = signed_quote
+                .quote_attributes
+                .get("quote_expiry")
+                .ok_or(EncodingError::FatalError(
+                    "Hashflow quote must have a quote_expiry attribute".to_string(),
+                ))?;
+            let expiry = U256::try_from_be_slice(quote_expiry)
+                .and_then(|expiry| u64::try_from(expiry).ok())
+                .ok_or(EncodingError::FatalError(format!(
+                    "Invalid Hashflow quote expiry {quote_expiry}"
+                )))?;
+            self.rfq_config.check_quote_expiry(
+                expiry,
+                encoding_context.execution_deadline,
+                Utc::now().timestamp().max(0) as u64,
+            )?;
+        }
         // Encode packed data for the executor
-        // Format: approval_needed | transfer_type | hashflow_calldata[..]
+        // Format: transfer_type | approval_needed | hashflow_calldata[..] |
+        // (native_flags | receiver), the last part only for swaps of the native token
         let hashflow_fields = [
             "pool",
             "external_account",
@@ -152,7 +190,117 @@ impl SwapEncoder for HashflowSwapEncoder {
             (approval_needed as u8).to_be_bytes(),
             &hashflow_calldata[..],
         );
-        Ok(args.abi_encode_packed())
+        let mut encoded = args.abi_encode_packed();
+        let native_flags = self.native_flags(swap);
+        if native_flags != 0 {
+            encoded.push(native_flags);
+            encoded.extend_from_slice(bytes_to_address(&encoding_context.receiver)?.as_slice());
+        }
+        notify_quote(swap, &signed_quote);
+        Ok(encoded)
+    }
+}
+
+#[async_trait]
+impl SwapEncoder for HashflowSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        let config = config.ok_or(EncodingError::FatalError(
+            "Missing hashflow specific addresses in config".to_string(),
+        ))?;
+        let hashflow_router_address = config
+            .get("hashflow_router_address")
+            .map(|s| {
+                Bytes::from_str(s).map_err(|_| {
+                    EncodingError::FatalError("Invalid hashflow router address".to_string())
+                })
+            })
+            .ok_or(EncodingError::FatalError(
+                "Missing hashflow router address in config".to_string(),
+            ))
+            .flatten()?;
+        let native_token_address = chain.native_token().address;
+        let wrapped_native_token_address = chain.wrapped_native_token().address;
+        let (runtime_handle, runtime) = get_runtime()?;
+        Ok(Self {
+            executor_address,
+            hashflow_router_address,
+            native_token_address,
+            wrapped_native_token_address,
+            runtime_handle,
+            runtime,
+            rfq_config: RfqConfig::default(),
+        })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let (token, owner, spender) = self.approval_check(swap, encoding_context)?;
+        let approval_needed = match encoding_context.fixed_approval() {
+            Some(fixed_approval) => fixed_approval,
+            None => ProtocolApprovalsManager::new()?.approval_needed(token, owner, spender)?,
+        };
+        if encoding_context.historical_trade {
+            // Historical trades are encoded offline, from the quote recorded in the user data
+            let signed_quote = recorded_quote(swap)?;
+            return self.encode_signed_quote(swap, encoding_context, approval_needed, signed_quote);
+        }
+
+        // Get quote
+        let rfq_state = swap.rfq_state()?;
+        let params = self.quote_params(swap, encoding_context)?;
+        let signed_quote = block_in_place(|| {
+            self.runtime_handle.block_on(
+                self.rfq_config
+                    .request_signed_quote(|| {
+                        rfq_state.request_signed_quote(copy_quote_params(&params))
+                    }),
+            )
+        })?;
+        self.encode_signed_quote(swap, encoding_context, approval_needed, signed_quote)
+    }
+
+    async fn encode_swap_async(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let (token, owner, spender) = self.approval_check(swap, encoding_context)?;
+        let approval_needed = match encoding_context.fixed_approval() {
+            Some(fixed_approval) => fixed_approval,
+            None => {
+                ProtocolApprovalsManager::new_async()
+                    .await?
+                    .approval_needed_async(token, owner, spender)
+                    .await?
+            }
+        };
+        if encoding_context.historical_trade {
+            let signed_quote = recorded_quote(swap)?;
+            return self.encode_signed_quote(swap, encoding_context, approval_needed, signed_quote);
+        }
+
+        let rfq_state = swap.rfq_state()?;
+        let params = self.quote_params(swap, encoding_context)?;
+        let signed_quote = self
+            .rfq_config
+            .request_signed_quote(|| rfq_state.request_signed_quote(copy_quote_params(&params)))
+            .await?;
+        self.encode_signed_quote(swap, encoding_context, approval_needed, signed_quote)
+    }
+
+    fn with_rfq_config(&self, rfq_config: &RfqConfig) -> Option<Box<dyn SwapEncoder>> {
+        Some(Box::new(Self { rfq_config: *rfq_config, ..self.clone() }))
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
     }
 
     fn executor_address(&self) -> &Bytes {
@@ -176,7 +324,7 @@ mod test {
             swap_encoder::hashflow::HashflowSwapEncoder, testing_utils::MockRFQState,
             utils::biguint_to_u256,
         },
-        models::{Swap, TransferType},
+        models::{ApprovalPolicy, Swap, TransferType},
     };
 
     fn hashflow_config() -> Option<HashMap<String, String>> {
@@ -209,6 +357,8 @@ mod test {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
 
         let encoder = HashflowSwapEncoder::new(
@@ -302,6 +452,9 @@ mod test {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            // The quote was executed right after being signed
+            execution_deadline: Some(1755610284),
+            approval_policy: ApprovalPolicy::Live,
         };
 
         let encoder = HashflowSwapEncoder::new(
@@ -322,4 +475,147 @@ mod test {
         ));
         assert_eq!(hex_swap, expected_swap + &hashflow_calldata.to_string()[2..]);
     }
+
+    #[test]
+    fn test_encode_hashflow_native_out() {
+        // 3000 USDC -> 1 ETH, quoted for WETH which the executor unwraps
+        let hashflow_component = ProtocolComponent {
+            id: String::from("hashflow-rfq"),
+            protocol_system: String::from("rfq:hashflow"),
+            ..Default::default()
+        };
+        let token_in = Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"); // USDC
+        let token_out = Bytes::from("0x0000000000000000000000000000000000000000"); // ETH
+        let weth = Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let router_address = Bytes::from("0x3Ede3eCa2a72B3aeCC820E955B36f38437D01395");
+        let receiver = Bytes::from("0xc5564C13A157E6240659fb81882A28091add8670");
+
+        let swap = Swap::new(hashflow_component, token_in.clone(), token_out.clone())
+            .estimated_amount_in(BigUint::from_str("3000000000").unwrap());
+        let encoding_context = EncodingContext {
+            receiver: receiver.clone(),
+            exact_out: false,
+            router_address: Some(router_address.clone()),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = HashflowSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            hashflow_config(),
+        )
+        .unwrap();
+
+        // The quote is requested for WETH, paid to the router to be unwrapped
+        let params = encoder
+            .quote_params(&swap, &encoding_context)
+            .unwrap();
+        assert_eq!(params.token_in, token_in);
+        assert_eq!(params.token_out, weth);
+        assert_eq!(params.receiver, router_address);
+
+        let quote_attributes: HashMap<String, Bytes> = [
+            "pool",
+            "external_account",
+            "trader",
+            "base_token",
+            "quote_token",
+            "base_token_amount",
+            "quote_token_amount",
+            "quote_expiry",
+            "nonce",
+            "tx_id",
+            "signature",
+        ]
+        .iter()
+        .map(|field| {
+            // The quote must not be expired
+            let value = if *field == "quote_expiry" { vec![0xff; 4] } else { vec![0xaa] };
+            (field.to_string(), Bytes::from(value))
+        })
+        .collect();
+        let signed_quote = SignedQuote {
+            base_token: params.token_in,
+            quote_token: params.token_out,
+            amount_in: params.amount_in,
+            amount_out: BigUint::from_str("1000000000000000000").unwrap(),
+            quote_attributes,
+        };
+        let encoded_swap = encoder
+            .encode_signed_quote(&swap, &encoding_context, false, signed_quote)
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        let expected_swap = String::from(concat!(
+            "01",                                       // transfer type
+            "00",                                       // approval needed
+            "aaaaaaaaaaaaaa",                           // hashflow calldata
+            "ffffffff",                                 // quote expiry
+            "aaaaaa",                                   // hashflow calldata
+            "02",                                       // native out
+            "c5564c13a157e6240659fb81882a28091add8670", // receiver
+        ));
+        assert_eq!(hex_swap, expected_swap);
+    }
+
+    #[test]
+    fn test_encode_hashflow_expired_quote() {
+        let hashflow_component = ProtocolComponent {
+            id: String::from("hashflow-rfq"),
+            protocol_system: String::from("rfq:hashflow"),
+            ..Default::default()
+        };
+        let token_in = Bytes::from("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"); // USDC
+        let token_out = Bytes::from("0x2260fac5e5542a773aa44fbcfedf7c193bc2c599"); // WBTC
+        let swap = Swap::new(hashflow_component, token_in.clone(), token_out.clone());
+        let encoder = HashflowSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            hashflow_config(),
+        )
+        .unwrap();
+        let signed_quote = |expiry: u64| SignedQuote {
+            base_token: token_in.clone(),
+            quote_token: token_out.clone(),
+            amount_in: BigUint::from(3000_u64),
+            amount_out: BigUint::from(1_u64),
+            quote_attributes: HashMap::from([(
+                "quote_expiry".to_string(),
+                Bytes::from(
+                    biguint_to_u256(&BigUint::from(expiry))
+                        .to_be_bytes::<32>()
+                        .to_vec(),
+                ),
+            )]),
+        };
+        let encoding_context = |execution_deadline: Option<u64>| EncodingContext {
+            receiver: Bytes::from("0xc5564C13A157E6240659fb81882A28091add8670"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline,
+            approval_policy: ApprovalPolicy::Live,
+        };
+
+        // The quote expires within the default buffer of 10s after the execution deadline
+        let result = encoder.encode_signed_quote(
+            &swap,
+            &encoding_context(Some(1755610320)),
+            false,
+            signed_quote(1755610328),
+        );
+        assert!(matches!(result, Err(EncodingError::QuoteExpired(_))));
+
+        // The quote expired long ago
+        let result =
+            encoder.encode_signed_quote(&swap, &encoding_context(None), false, signed_quote(1));
+        assert!(matches!(result, Err(EncodingError::QuoteExpired(_))));
+    }
 }
@@ -0,0 +1,226 @@
+use std::{collections::HashMap, str::FromStr};
+
+use alloy::sol_types::SolValue;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::utils::bytes_to_address,
+    models::{EncodingContext, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+/// Encodes a withdrawal request of stETH for ETH on the Lido withdrawal queue.
+///
+/// The request must be the last swap of the solution (see `TychoRouterEncoder`). Its component id
+/// is the withdrawal queue, its token in stETH and its token out ETH. The receiver of the swap is
+/// the owner of the withdrawal request NFTs (unstETH) minted by the queue, which claims the ETH
+/// once the requests are finalized.
+///
+/// Nothing is received by the end of the transaction, so the router checks the amount of stETH
+/// received by the withdrawal queue instead of the amount received by the receiver.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `withdrawal_queue_address` - The address of the Lido withdrawal queue.
+/// * `steth_address` - The address of the stETH token.
+/// * `eth_address` - The address of the native token (only ETH).
+#[derive(Clone)]
+pub struct LidoWithdrawalSwapEncoder {
+    executor_address: Bytes,
+    withdrawal_queue_address: Bytes,
+    steth_address: Bytes,
+    eth_address: Bytes,
+}
+
+impl SwapEncoder for LidoWithdrawalSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        if chain != Chain::Ethereum {
+            return Err(EncodingError::FatalError(
+                "Lido withdrawals are only supported on Ethereum".to_string(),
+            ));
+        }
+        let config = config.ok_or_else(|| {
+            EncodingError::FatalError("Lido withdrawal config is empty".to_string())
+        })?;
+
+        let withdrawal_queue_address = config
+            .get("withdrawal_queue_address")
+            .map(|a| {
+                Bytes::from_str(a).map_err(|_| {
+                    EncodingError::FatalError("Invalid Lido withdrawal queue address".to_string())
+                })
+            })
+            .ok_or_else(|| {
+                EncodingError::FatalError(
+                    "Missing withdrawal_queue_address in lido_withdrawal config".to_string(),
+                )
+            })
+            .flatten()?;
+
+        let steth_address = config
+            .get("steth_address")
+            .map(|a| {
+                Bytes::from_str(a)
+                    .map_err(|_| EncodingError::FatalError("Invalid stETH address".to_string()))
+            })
+            .ok_or_else(|| {
+                EncodingError::FatalError(
+                    "Missing steth_address in lido_withdrawal config".to_string(),
+                )
+            })
+            .flatten()?;
+
+        Ok(Self {
+            executor_address,
+            withdrawal_queue_address,
+            steth_address,
+            eth_address: chain.native_token().address,
+        })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let withdrawal_queue = Bytes::from_str(&swap.component().id).map_err(|_| {
+            EncodingError::InvalidInput(format!(
+                "Invalid Lido withdrawal queue id: {}",
+                swap.component().id
+            ))
+        })?;
+        if withdrawal_queue != self.withdrawal_queue_address {
+            return Err(EncodingError::InvalidInput(format!(
+                "Lido withdrawals must be requested on the withdrawal queue {}. Found {}",
+                self.withdrawal_queue_address,
+                swap.component().id
+            )));
+        }
+        if *swap.token_in() != self.steth_address || *swap.token_out() != self.eth_address {
+            return Err(EncodingError::InvalidInput(format!(
+                "Lido withdrawals only request ETH for stETH. Found {} -> {}",
+                swap.token_in(),
+                swap.token_out()
+            )));
+        }
+
+        let args = (
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            bytes_to_address(&encoding_context.receiver)?,
+        );
+
+        Ok(args.abi_encode_packed())
+    }
+
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::{
+        evm::utils::write_calldata_to_file,
+        models::{ApprovalPolicy, TransferType},
+    };
+
+    fn lido_withdrawal_encoder() -> LidoWithdrawalSwapEncoder {
+        LidoWithdrawalSwapEncoder::new(
+            Bytes::from("0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"),
+            Chain::Ethereum,
+            Some(HashMap::from([
+                (
+                    "withdrawal_queue_address".to_string(),
+                    "0x889edC2eDab5f40e902b864aD4d7AdE8E412F9B1".to_string(),
+                ),
+                (
+                    "steth_address".to_string(),
+                    "0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84".to_string(),
+                ),
+            ])),
+        )
+        .unwrap()
+    }
+
+    fn withdrawal_swap(token_in: &str) -> Swap {
+        let withdrawal_queue = ProtocolComponent {
+            id: String::from("0x889edC2eDab5f40e902b864aD4d7AdE8E412F9B1"),
+            protocol_system: String::from("lido_withdrawal"),
+            ..Default::default()
+        };
+        Swap::new(
+            withdrawal_queue,
+            Bytes::from(token_in),
+            Bytes::from("0x0000000000000000000000000000000000000000"),
+        )
+    }
+
+    fn encoding_context() -> EncodingContext {
+        EncodingContext {
+            // The receiver was generated with `makeAddr("bob*") using forge`
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::default()),
+            group_token_in: Bytes::from("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84"),
+            group_token_out: Bytes::from("0x0000000000000000000000000000000000000000"),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    #[test]
+    fn test_encode_lido_withdrawal() {
+        // stETH -> (lido withdrawal queue) -> ETH withdrawal request
+        let swap = withdrawal_swap("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84");
+
+        let encoded_swap = lido_withdrawal_encoder()
+            .encode_swap(&swap, &encoding_context())
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // transfer type
+                "01",
+                // receiver (owner of the withdrawal requests)
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+            ))
+        );
+
+        write_calldata_to_file("test_encode_lido_withdrawal", hex_swap.as_str());
+    }
+
+    #[test]
+    fn test_encode_lido_withdrawal_invalid_token() {
+        // wstETH can't be withdrawn directly
+        let swap = withdrawal_swap("0x7f39C581F595B53c5cb19bD0b3f8dA6c935E2Ca0");
+
+        let result = lido_withdrawal_encoder().encode_swap(&swap, &encoding_context());
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use alloy::sol_types::SolValue;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::utils::bytes_to_address,
+    models::{EncodingContext, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+/// Encodes a mint or a redeem of a liquid staking token (e.g. rETH, stETH or mETH) through the
+/// given executor address.
+///
+/// The functions called to mint and redeem are set in the deployment of the `LstExecutor` of every
+/// token, so adding a token only takes an executor deployment and its config:
+/// * `lst_address` - The address of the liquid staking token.
+/// * `deposit_token_address` - The address of the token deposited to mint it. Defaults to the
+///   native token of the chain.
+/// * `redeemable` - Whether the token can be redeemed back into the deposit token within the
+///   transaction (`"true"` or `"false"`, the default).
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `lst_address` - The address of the liquid staking token.
+/// * `deposit_token_address` - The address of the token deposited to mint it.
+/// * `redeemable` - Whether the token can be redeemed.
+#[derive(Clone)]
+pub struct LstSwapEncoder {
+    executor_address: Bytes,
+    lst_address: Bytes,
+    deposit_token_address: Bytes,
+    redeemable: bool,
+}
+
+impl SwapEncoder for LstSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        let config =
+            config.ok_or_else(|| EncodingError::FatalError("LST config is empty".to_string()))?;
+
+        let lst_address = config
+            .get("lst_address")
+            .map(|a| Bytes::from(a.as_str()))
+            .ok_or_else(|| {
+                EncodingError::FatalError("Missing lst_address in LST config".to_string())
+            })?;
+        let deposit_token_address = config
+            .get("deposit_token_address")
+            .map(|a| Bytes::from(a.as_str()))
+            .unwrap_or(chain.native_token().address);
+        let redeemable = match config.get("redeemable") {
+            Some(redeemable) => redeemable
+                .parse::<bool>()
+                .map_err(|_| {
+                    EncodingError::FatalError(format!(
+                        "Invalid redeemable in LST config: {redeemable}"
+                    ))
+                })?,
+            None => false,
+        };
+
+        Ok(Self { executor_address, lst_address, deposit_token_address, redeemable })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let (token_in, token_out) = (swap.token_in(), swap.token_out());
+        let is_mint = if *token_in == self.deposit_token_address && *token_out == self.lst_address {
+            true
+        } else if *token_in == self.lst_address && *token_out == self.deposit_token_address {
+            if !self.redeemable {
+                return Err(EncodingError::InvalidInput(format!(
+                    "{} can't be redeemed within a transaction",
+                    self.lst_address
+                )));
+            }
+            false
+        } else {
+            return Err(EncodingError::InvalidInput(format!(
+                "LST swaps must mint {} from {} or redeem it. Found {token_in} -> {token_out}",
+                self.lst_address, self.deposit_token_address
+            )));
+        };
+
+        let args = (
+            is_mint,
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            bytes_to_address(&encoding_context.receiver)?,
+        );
+
+        Ok(args.abi_encode_packed())
+    }
+
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::{
+        evm::utils::{append_user_data, write_calldata_to_file},
+        models::{ApprovalPolicy, TransferType},
+    };
+
+    const ETH: &str = "0x0000000000000000000000000000000000000000";
+    const RETH: &str = "0xae78736Cd615f374D3085123A210448E74Fc6393";
+    const METH: &str = "0xd5F7838F5C461fefF7FE49ea5ebaF7728bB0ADfa";
+
+    fn lst_encoder(lst_address: &str, redeemable: bool) -> LstSwapEncoder {
+        LstSwapEncoder::new(
+            Bytes::from("0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"),
+            Chain::Ethereum,
+            Some(HashMap::from([
+                ("lst_address".to_string(), lst_address.to_string()),
+                ("redeemable".to_string(), redeemable.to_string()),
+            ])),
+        )
+        .unwrap()
+    }
+
+    fn lst_swap(protocol_system: &str, token_in: &str, token_out: &str) -> Swap {
+        let component = ProtocolComponent {
+            id: String::from(RETH),
+            protocol_system: String::from(protocol_system),
+            ..Default::default()
+        };
+        Swap::new(component, Bytes::from(token_in), Bytes::from(token_out))
+    }
+
+    fn encoding_context(token_in: &str, token_out: &str) -> EncodingContext {
+        EncodingContext {
+            // The receiver was generated with `makeAddr("bob*") using forge`
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::default()),
+            group_token_in: Bytes::from(token_in),
+            group_token_out: Bytes::from(token_out),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    #[test]
+    fn test_encode_rocketpool_deposit() {
+        // ETH -> (rocketpool) -> rETH
+        let encoded_swap = lst_encoder(RETH, true)
+            .encode_swap(&lst_swap("rocketpool", ETH, RETH), &encoding_context(ETH, RETH))
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // is mint
+                "01",
+                // transfer type
+                "01",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+            ))
+        );
+
+        write_calldata_to_file("test_encode_rocketpool_deposit", hex_swap.as_str());
+    }
+
+    #[test]
+    fn test_encode_rocketpool_burn() {
+        // rETH -> (rocketpool) -> ETH
+        let encoded_swap = lst_encoder(RETH, true)
+            .encode_swap(&lst_swap("rocketpool", RETH, ETH), &encoding_context(RETH, ETH))
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // is mint
+                "00",
+                // transfer type
+                "01",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+            ))
+        );
+
+        write_calldata_to_file("test_encode_rocketpool_burn", hex_swap.as_str());
+    }
+
+    #[test]
+    fn test_encode_lst_with_user_data() {
+        // ETH -> (rocketpool) -> rETH, tagged with a referral
+        let encoder = lst_encoder(RETH, true);
+        let swap = lst_swap("rocketpool", ETH, RETH).user_data(Bytes::from("0xabcd"));
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context(ETH, RETH))
+            .unwrap();
+        let encoded_swap = append_user_data(&encoder, &swap, encoded_swap).unwrap();
+
+        assert_eq!(
+            encode(&encoded_swap),
+            String::from(concat!(
+                // is mint
+                "01",
+                // transfer type
+                "01",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // user data length
+                "0002",
+                // user data
+                "abcd",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_lst_not_redeemable() {
+        let encoder = lst_encoder(METH, false);
+
+        // ETH -> (mantle_meth) -> mETH
+        let encoded_swap = encoder
+            .encode_swap(&lst_swap("mantle_meth", ETH, METH), &encoding_context(ETH, METH))
+            .unwrap();
+        assert_eq!(encoded_swap[0], 1);
+
+        // mETH is unstaked asynchronously
+        let result =
+            encoder.encode_swap(&lst_swap("mantle_meth", METH, ETH), &encoding_context(METH, ETH));
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+        let result =
+            encoder.encode_swap(&lst_swap("mantle_meth", ETH, RETH), &encoding_context(ETH, RETH));
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
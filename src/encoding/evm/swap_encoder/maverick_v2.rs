@@ -1,11 +1,11 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
-use alloy::{primitives::Bytes as AlloyBytes, sol_types::SolValue};
+use alloy::sol_types::SolValue;
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
-    evm::utils::bytes_to_address,
+    evm::{component_id::component_pool_address, utils::bytes_to_address},
     models::{EncodingContext, Swap},
     swap_encoder::SwapEncoder,
 };
@@ -33,8 +33,7 @@ impl SwapEncoder for MaverickV2SwapEncoder {
         swap: &Swap,
         encoding_context: &EncodingContext,
     ) -> Result<Vec<u8>, EncodingError> {
-        let component_id = AlloyBytes::from_str(&swap.component().id)
-            .map_err(|_| EncodingError::FatalError("Invalid component ID".to_string()))?;
+        let component_id = component_pool_address(swap.component())?;
 
         let args = (
             bytes_to_address(swap.token_in())?,
@@ -45,6 +44,10 @@ impl SwapEncoder for MaverickV2SwapEncoder {
         Ok(args.abi_encode_packed())
     }
 
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -61,7 +64,7 @@ mod tests {
     use super::*;
     use crate::encoding::{
         evm::{swap_encoder::maverick_v2::MaverickV2SwapEncoder, utils::write_calldata_to_file},
-        models::TransferType,
+        models::{ApprovalPolicy, TransferType},
     };
     #[test]
     fn test_encode_maverick_v2() {
@@ -83,6 +86,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = MaverickV2SwapEncoder::new(
             Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
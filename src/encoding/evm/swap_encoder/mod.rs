@@ -1,15 +1,24 @@
+mod across;
+mod aerodrome_v2;
+mod ambient;
 mod balancer_v2;
-mod balancer_v3;
+pub mod balancer_v3;
 mod bebop;
-mod curve;
+pub mod curve;
 mod ekubo;
 mod ekubo_v3;
 mod erc_4626;
 mod etherfi;
 mod fluid_v1;
+mod frax_ether;
 mod hashflow;
+mod lido_withdrawal;
+mod lst;
 mod maverick_v2;
-mod rocketpool;
+mod oneinch_lop;
+mod pancakeswap_v3;
+mod pendle;
+mod sky_psm;
 mod slipstreams;
 pub mod swap_encoder_registry;
 mod uniswap_v2;
@@ -0,0 +1,539 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use alloy::{
+    core::sol,
+    primitives::{Address, Bytes as AlloyBytes, B256, U256},
+    sol_types::SolValue,
+};
+use async_trait::async_trait;
+use tokio::{
+    runtime::{Handle, Runtime},
+    task::block_in_place,
+};
+use tycho_common::{
+    models::{protocol::GetAmountOutParams, Chain},
+    simulation::indicatively_priced::SignedQuote,
+    Bytes,
+};
+
+#[cfg(feature = "failure-injection")]
+use crate::encoding::evm::failure_injection::{inject, InjectionPoint};
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        approvals::protocol_approvals_manager::ProtocolApprovalsManager,
+        utils::{bytes_to_address, copy_quote_params, get_runtime},
+    },
+    models::{EncodingContext, RfqConfig, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+sol! {
+    /// Maker order of the 1inch Limit Order Protocol (v4). Addresses are packed into the lower
+    /// 160 bits of their `uint256`.
+    #[derive(Debug)]
+    struct LimitOrder {
+        uint256 salt;
+        uint256 maker;
+        uint256 receiver;
+        uint256 makerAsset;
+        uint256 takerAsset;
+        uint256 makingAmount;
+        uint256 takingAmount;
+        uint256 makerTraits;
+    }
+}
+
+/// Encodes the fill of a resting maker order of the 1inch Limit Order Protocol through the given
+/// executor address.
+///
+/// The signed order is read from the swap's user data if it is set, as the ABI encoding of
+/// `(LimitOrder order, bytes32 r, bytes32 vs, bytes extension)`. Otherwise, it is requested from
+/// the swap's protocol state like an RFQ quote, whose attributes must contain the ABI encoded
+/// `order` and its `signature` (either compact or 65 bytes long), and optionally the order's
+/// `extension`.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `limit_order_protocol_address` - The address of the 1inch Limit Order Protocol contract.
+/// * `native_token_address` - The address of the native token of the chain. Limit orders are only
+///   filled with ERC20 tokens.
+/// * `rfq_config` - The timeout and retry policy of the quote requests.
+#[derive(Clone)]
+pub struct LimitOrderSwapEncoder {
+    executor_address: Bytes,
+    limit_order_protocol_address: Bytes,
+    native_token_address: Bytes,
+    runtime_handle: Handle,
+    #[allow(dead_code)]
+    runtime: Option<Arc<Runtime>>,
+    rfq_config: RfqConfig,
+}
+
+/// A maker order with its signature, ready to be filled.
+struct SignedLimitOrder {
+    order: LimitOrder,
+    r: B256,
+    vs: B256,
+    extension: Vec<u8>,
+}
+
+impl LimitOrderSwapEncoder {
+    /// Rejects swaps of the native token, which limit orders can't be filled with.
+    fn check_tokens(&self, swap: &Swap) -> Result<(), EncodingError> {
+        if *swap.token_in() == self.native_token_address ||
+            *swap.token_out() == self.native_token_address
+        {
+            return Err(EncodingError::InvalidInput(
+                "1inch limit orders can't be filled with the native token, use its wrapped \
+                 version instead"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the signed order of the swap's user data, if set.
+    fn user_data_order(&self, swap: &Swap) -> Result<Option<SignedLimitOrder>, EncodingError> {
+        let Some(user_data) = swap.get_user_data() else {
+            return Ok(None);
+        };
+        let (order, r, vs, extension) =
+            <(LimitOrder, B256, B256, AlloyBytes)>::abi_decode(user_data).map_err(|e| {
+                EncodingError::InvalidInput(format!(
+                    "Failed to decode 1inch limit order from user data: {e}"
+                ))
+            })?;
+        Ok(Some(SignedLimitOrder { order, r, vs, extension: extension.to_vec() }))
+    }
+
+    /// Returns the parameters of the signed order to request from the swap's protocol state.
+    fn quote_params(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<GetAmountOutParams, EncodingError> {
+        #[cfg(feature = "failure-injection")]
+        inject(InjectionPoint::RfqQuote)?;
+        let estimated_amount_in = swap
+            .get_estimated_amount_in()
+            .clone()
+            .ok_or(EncodingError::FatalError(
+                "Estimated amount in is mandatory for a 1inch limit order swap".to_string(),
+            ))?;
+        Ok(GetAmountOutParams {
+            amount_in: estimated_amount_in,
+            token_in: swap.token_in().clone(),
+            token_out: swap.token_out().clone(),
+            sender: encoding_context
+                .router_address
+                .clone()
+                .ok_or(EncodingError::FatalError(
+                    "The router address is needed to perform a 1inch limit order swap".to_string(),
+                ))?,
+            receiver: encoding_context.receiver.clone(),
+        })
+    }
+
+    /// Reads the signed order from the attributes of a signed quote.
+    fn quoted_order(signed_quote: SignedQuote) -> Result<SignedLimitOrder, EncodingError> {
+        let order = signed_quote
+            .quote_attributes
+            .get("order")
+            .ok_or(EncodingError::FatalError(
+                "1inch limit order quote must have an order attribute".to_string(),
+            ))?;
+        let order = LimitOrder::abi_decode(order).map_err(|e| {
+            EncodingError::FatalError(format!("Failed to decode 1inch limit order: {e}"))
+        })?;
+        let signature = signed_quote
+            .quote_attributes
+            .get("signature")
+            .ok_or(EncodingError::FatalError(
+                "1inch limit order quote must have a signature attribute".to_string(),
+            ))?;
+        let (r, vs) = compact_signature(signature)?;
+        let extension = signed_quote
+            .quote_attributes
+            .get("extension")
+            .map(|extension| extension.to_vec())
+            .unwrap_or_default();
+        Ok(SignedLimitOrder { order, r, vs, extension })
+    }
+
+    /// Checks that the order fills the swap and returns the token, owner (the router) and spender
+    /// (the Limit Order Protocol) of the allowance to check before filling it.
+    fn approval_check(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+        signed_order: &SignedLimitOrder,
+    ) -> Result<(Address, Address, Address), EncodingError> {
+        let token_in = bytes_to_address(swap.token_in())?;
+        let token_out = bytes_to_address(swap.token_out())?;
+        let sender = encoding_context
+            .router_address
+            .clone()
+            .ok_or(EncodingError::FatalError(
+                "The router address is needed to perform a 1inch limit order swap".to_string(),
+            ))?;
+        if packed_address(signed_order.order.takerAsset) != token_in ||
+            packed_address(signed_order.order.makerAsset) != token_out
+        {
+            return Err(EncodingError::InvalidInput(format!(
+                "1inch limit order assets don't match the swap {} -> {}",
+                swap.token_in(),
+                swap.token_out()
+            )));
+        }
+        Ok((
+            token_in,
+            bytes_to_address(&sender)?,
+            bytes_to_address(&self.limit_order_protocol_address)?,
+        ))
+    }
+
+    /// Encodes the fill of the signed order.
+    fn encode_signed_order(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+        approval_needed: bool,
+        signed_order: SignedLimitOrder,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let token_in = bytes_to_address(swap.token_in())?;
+        let token_out = bytes_to_address(swap.token_out())?;
+        let receiver = bytes_to_address(&encoding_context.receiver)?;
+
+        // Encode packed data for the executor
+        // Format: token_in | token_out | transfer_type | approval_needed | receiver | order | r |
+        //         vs | extension
+        let args = (
+            token_in,
+            token_out,
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            (approval_needed as u8).to_be_bytes(),
+            receiver,
+            signed_order.order.abi_encode(),
+            signed_order.r,
+            signed_order.vs,
+            &signed_order.extension[..],
+        );
+
+        Ok(args.abi_encode_packed())
+    }
+}
+
+/// Converts a signature to its compact form (EIP-2098), which the Limit Order Protocol expects.
+fn compact_signature(signature: &[u8]) -> Result<(B256, B256), EncodingError> {
+    match signature.len() {
+        64 => Ok((B256::from_slice(&signature[..32]), B256::from_slice(&signature[32..]))),
+        65 => {
+            let v = signature[64];
+            let y_parity = match v {
+                0 | 27 => 0u8,
+                1 | 28 => 1u8,
+                _ => {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Invalid 1inch limit order signature v value: {v}"
+                    )))
+                }
+            };
+            let mut vs = B256::from_slice(&signature[32..64]);
+            vs[0] |= y_parity << 7;
+            Ok((B256::from_slice(&signature[..32]), vs))
+        }
+        length => Err(EncodingError::InvalidInput(format!(
+            "Invalid 1inch limit order signature length: {length}"
+        ))),
+    }
+}
+
+/// Returns the address packed into the lower 160 bits of a `uint256`.
+fn packed_address(value: U256) -> Address {
+    Address::from_slice(&value.to_be_bytes::<32>()[12..])
+}
+
+#[async_trait]
+impl SwapEncoder for LimitOrderSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        let config = config.ok_or(EncodingError::FatalError(
+            "Missing 1inch limit order protocol specific addresses in config".to_string(),
+        ))?;
+        let limit_order_protocol_address = config
+            .get("limit_order_protocol_address")
+            .map(|s| {
+                Bytes::from_str(s).map_err(|_| {
+                    EncodingError::FatalError(
+                        "Invalid 1inch limit order protocol address".to_string(),
+                    )
+                })
+            })
+            .ok_or(EncodingError::FatalError(
+                "Missing 1inch limit order protocol address in config".to_string(),
+            ))
+            .flatten()?;
+        let (runtime_handle, runtime) = get_runtime()?;
+        Ok(Self {
+            executor_address,
+            limit_order_protocol_address,
+            native_token_address: chain.native_token().address,
+            runtime_handle,
+            runtime,
+            rfq_config: RfqConfig::default(),
+        })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        self.check_tokens(swap)?;
+        let signed_order = match self.user_data_order(swap)? {
+            Some(signed_order) => signed_order,
+            None => {
+                let indicatively_priced_state = swap.rfq_state()?;
+                let params = self.quote_params(swap, encoding_context)?;
+                let signed_quote = block_in_place(|| {
+                    self.runtime_handle.block_on(
+                        self.rfq_config
+                            .request_signed_quote(|| {
+                                indicatively_priced_state
+                                    .request_signed_quote(copy_quote_params(&params))
+                            }),
+                    )
+                })?;
+                Self::quoted_order(signed_quote)?
+            }
+        };
+        let (token, owner, spender) = self.approval_check(swap, encoding_context, &signed_order)?;
+        let approval_needed = encoding_context.historical_trade ||
+            ProtocolApprovalsManager::new()?.approval_needed(token, owner, spender)?;
+        self.encode_signed_order(swap, encoding_context, approval_needed, signed_order)
+    }
+
+    async fn encode_swap_async(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        self.check_tokens(swap)?;
+        let signed_order = match self.user_data_order(swap)? {
+            Some(signed_order) => signed_order,
+            None => {
+                let indicatively_priced_state = swap.rfq_state()?;
+                let params = self.quote_params(swap, encoding_context)?;
+                let signed_quote = self
+                    .rfq_config
+                    .request_signed_quote(|| {
+                        indicatively_priced_state.request_signed_quote(copy_quote_params(&params))
+                    })
+                    .await?;
+                Self::quoted_order(signed_quote)?
+            }
+        };
+        let (token, owner, spender) = self.approval_check(swap, encoding_context, &signed_order)?;
+        let approval_needed = encoding_context.historical_trade ||
+            ProtocolApprovalsManager::new_async()
+                .await?
+                .approval_needed_async(token, owner, spender)
+                .await?;
+        self.encode_signed_order(swap, encoding_context, approval_needed, signed_order)
+    }
+
+    fn with_rfq_config(&self, rfq_config: &RfqConfig) -> Option<Box<dyn SwapEncoder>> {
+        Some(Box::new(Self { rfq_config: *rfq_config, ..self.clone() }))
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use num_bigint::BigUint;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::{
+        evm::testing_utils::MockRFQState,
+        models::{ApprovalPolicy, TransferType},
+    };
+
+    const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+
+    fn limit_order_config() -> HashMap<String, String> {
+        HashMap::from([(
+            "limit_order_protocol_address".to_string(),
+            "0x111111125421cA6dc452d289314280a0f8842A65".to_string(),
+        )])
+    }
+
+    fn limit_order_encoder() -> LimitOrderSwapEncoder {
+        LimitOrderSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            Some(limit_order_config()),
+        )
+        .unwrap()
+    }
+
+    fn address_word(address: &str) -> U256 {
+        U256::from_be_slice(&Bytes::from(address))
+    }
+
+    /// Order selling 1 WETH for 3000 USDC.
+    fn order() -> LimitOrder {
+        LimitOrder {
+            salt: U256::from(1),
+            maker: address_word("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2"),
+            receiver: U256::ZERO,
+            makerAsset: address_word(WETH),
+            takerAsset: address_word(USDC),
+            makingAmount: U256::from(1_000_000_000_000_000_000u128),
+            takingAmount: U256::from(3_000_000_000u64),
+            makerTraits: U256::ZERO,
+        }
+    }
+
+    fn encoding_context() -> EncodingContext {
+        EncodingContext {
+            receiver: Bytes::from("0xc5564C13A157E6240659fb81882A28091add8670"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: Bytes::from(USDC),
+            group_token_out: Bytes::from(WETH),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    fn component() -> ProtocolComponent {
+        ProtocolComponent {
+            id: String::from("oneinch-lop"),
+            protocol_system: String::from("rfq:oneinch_lop"),
+            ..Default::default()
+        }
+    }
+
+    fn expected_swap(extension: &str) -> String {
+        String::from(concat!(
+            // token in
+            "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            // token out
+            "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            // transfer type
+            "01",
+            // approval needed
+            "01",
+            // receiver
+            "c5564c13a157e6240659fb81882a28091add8670",
+            // order: salt
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            // maker
+            "000000000000000000000000cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2",
+            // receiver
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            // maker asset
+            "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            // taker asset
+            "000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            // making amount
+            "0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+            // taking amount
+            "00000000000000000000000000000000000000000000000000000000b2d05e00",
+            // maker traits
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            // r
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            // vs
+            "a222222222222222222222222222222222222222222222222222222222222222",
+        )) + extension
+    }
+
+    #[test]
+    fn test_encode_limit_order_from_user_data() {
+        let user_data = (
+            order(),
+            B256::repeat_byte(0x11),
+            B256::from_str("0xa222222222222222222222222222222222222222222222222222222222222222")
+                .unwrap(),
+            AlloyBytes::from(vec![0xab, 0xcd]),
+        )
+            .abi_encode();
+        let swap = Swap::new(component(), Bytes::from(USDC), Bytes::from(WETH))
+            .user_data(Bytes::from(user_data));
+
+        let encoded_swap = limit_order_encoder()
+            .encode_swap(&swap, &encoding_context())
+            .unwrap();
+
+        assert_eq!(encode(&encoded_swap), expected_swap("abcd"));
+    }
+
+    #[test]
+    fn test_encode_limit_order_with_protocol_state() {
+        let mut signature = vec![0x11; 32];
+        signature.extend(vec![0x22; 32]);
+        // v = 28, i.e. an odd y parity
+        signature.push(28);
+        let state = MockRFQState {
+            quote_amount_out: BigUint::from_str("1000000000000000000").unwrap(),
+            quote_data: HashMap::from([
+                ("order".to_string(), Bytes::from(order().abi_encode())),
+                ("signature".to_string(), Bytes::from(signature)),
+            ]),
+        };
+        let swap = Swap::new(component(), Bytes::from(USDC), Bytes::from(WETH))
+            .estimated_amount_in(BigUint::from_str("3000000000").unwrap())
+            .protocol_state(Arc::new(state));
+
+        let encoded_swap = limit_order_encoder()
+            .encode_swap(&swap, &encoding_context())
+            .unwrap();
+
+        assert_eq!(encode(&encoded_swap), expected_swap(""));
+    }
+
+    #[test]
+    fn test_encode_limit_order_fails_for_other_assets() {
+        let user_data = (order(), B256::ZERO, B256::ZERO, AlloyBytes::new()).abi_encode();
+        // The order sells WETH, not USDC
+        let swap = Swap::new(component(), Bytes::from(WETH), Bytes::from(USDC))
+            .user_data(Bytes::from(user_data));
+
+        let result = limit_order_encoder().encode_swap(&swap, &encoding_context());
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_compact_signature() {
+        let mut signature = vec![0x11; 32];
+        signature.extend(vec![0x22; 32]);
+        signature.push(27);
+        let (r, vs) = compact_signature(&signature).unwrap();
+        assert_eq!(r, B256::repeat_byte(0x11));
+        assert_eq!(vs, B256::repeat_byte(0x22));
+
+        assert!(matches!(compact_signature(&[0; 10]), Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::HashMap, str::FromStr};
+
+use alloy::primitives::Address;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{swap_encoder::uniswap_v3::UniswapV3SwapEncoder, utils::get_static_attribute},
+    models::{EncodingContext, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+/// Maximum fee of a PancakeSwap V3 pool, in hundredths of a basis point (100%).
+const MAX_FEE: u32 = 1_000_000;
+
+/// Encodes a swap on a PancakeSwap V3 pool through the given executor address.
+///
+/// PancakeSwap V3 pools are swapped through a `UniswapV3Executor` (which implements the
+/// `pancakeV3SwapCallback`), so the swap is encoded like a Uniswap V3 one. The differences are in
+/// the static attributes of the pools:
+/// * The pool addresses are derived from the address of the pool deployer, not of the factory. If
+///   the component has a `deployer` attribute, it must match the deployer the executor was deployed
+///   with.
+/// * The `fee` attribute can be encoded on more bytes than the 3 of a `uint24`, and PancakeSwap has
+///   its own fee tiers (e.g. 2500). The fee is validated instead of being silently truncated.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `deployer_address` - The address of the PancakeSwap V3 pool deployer. Optional: without it,
+///   the `deployer` attribute of the pools is not checked.
+#[derive(Clone)]
+pub struct PancakeV3SwapEncoder {
+    executor_address: Bytes,
+    deployer_address: Option<Address>,
+    uniswap_v3_encoder: UniswapV3SwapEncoder,
+}
+
+impl PancakeV3SwapEncoder {
+    /// Raises an error if the pool of the swap was deployed by another deployer than the one the
+    /// executor derives the pool addresses from.
+    fn validate_deployer(&self, swap: &Swap) -> Result<(), EncodingError> {
+        let (Some(deployer_address), Some(pool_deployer)) = (
+            self.deployer_address,
+            swap.component()
+                .static_attributes
+                .get("deployer"),
+        ) else {
+            return Ok(());
+        };
+        if pool_deployer.as_ref() != deployer_address.as_slice() {
+            return Err(EncodingError::FatalError(format!(
+                "The pancakeswap_v3 pool {} was deployed by {pool_deployer}, but the executor \
+                 only supports pools of the deployer {deployer_address}",
+                swap.component().id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Raises an error if the `fee` attribute of the swap doesn't fit in a `uint24` or is not a
+    /// valid fee.
+    fn validate_fee(swap: &Swap) -> Result<(), EncodingError> {
+        let fee_bytes = get_static_attribute(swap, "fee")?;
+        let significant_bytes: Vec<u8> = fee_bytes
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        let fee = match significant_bytes.as_slice() {
+            bytes if bytes.len() <= 3 => bytes
+                .iter()
+                .fold(0u32, |fee, byte| (fee << 8) | u32::from(*byte)),
+            _ => u32::MAX,
+        };
+        if fee >= MAX_FEE {
+            return Err(EncodingError::FatalError(format!(
+                "Invalid fee for the pancakeswap_v3 pool {}: 0x{}",
+                swap.component().id,
+                alloy::hex::encode(significant_bytes)
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl SwapEncoder for PancakeV3SwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        let deployer_address = config
+            .as_ref()
+            .and_then(|config| config.get("deployer_address"))
+            .map(|address| {
+                Address::from_str(address).map_err(|_| {
+                    EncodingError::FatalError("Invalid pancakeswap v3 deployer address".to_string())
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            uniswap_v3_encoder: UniswapV3SwapEncoder::new(executor_address.clone(), chain, None)?,
+            executor_address,
+            deployer_address,
+        })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        self.validate_deployer(swap)?;
+        Self::validate_fee(swap)?;
+        self.uniswap_v3_encoder
+            .encode_swap(swap, encoding_context)
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { callback_constrained: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use num_bigint::BigInt;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::{ApprovalPolicy, TransferType};
+
+    const DEPLOYER: &str = "0x41ff9AA7e16B8B1a8a8dc4f0eFacd93D02d071c9";
+
+    fn encoder() -> PancakeV3SwapEncoder {
+        PancakeV3SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Base,
+            Some(HashMap::from([("deployer_address".to_string(), DEPLOYER.to_string())])),
+        )
+        .unwrap()
+    }
+
+    fn swap(static_attributes: HashMap<String, Bytes>) -> Swap {
+        let pool = ProtocolComponent {
+            id: String::from("0xb94b22332ABf5f89877A14Cc88f2aBC48c34B3Df"),
+            protocol_system: String::from("pancakeswap_v3"),
+            static_attributes,
+            ..Default::default()
+        };
+        Swap::new(
+            pool,
+            Bytes::from("0xcbB7C0000aB88B473b1f5aFd9ef808440eed33Bf"),
+            Bytes::from("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+        )
+    }
+
+    fn encoding_context() -> EncodingContext {
+        EncodingContext {
+            receiver: Bytes::from("0x0000000000000000000000000000000000000001"),
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: Bytes::from("0xcbB7C0000aB88B473b1f5aFd9ef808440eed33Bf"),
+            group_token_out: Bytes::from("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    #[test]
+    fn test_encode_pancakeswap_v3() {
+        // The fee is encoded on a full word, like some indexer attributes
+        let mut fee = [0u8; 32];
+        fee[29..].copy_from_slice(&[0x00, 0x09, 0xc4]);
+        let swap = swap(HashMap::from([
+            ("fee".to_string(), Bytes::from(fee.to_vec())),
+            ("deployer".to_string(), Bytes::from(DEPLOYER)),
+        ]));
+
+        let encoded_swap = encoder()
+            .encode_swap(&swap, &encoding_context())
+            .unwrap();
+
+        assert_eq!(
+            encode(&encoded_swap),
+            String::from(concat!(
+                // in token
+                "cbb7c0000ab88b473b1f5afd9ef808440eed33bf",
+                // out token
+                "833589fcd6edb6e08f4c7c32d4f71b54bda02913",
+                // fee (2500)
+                "0009c4",
+                // receiver
+                "0000000000000000000000000000000000000001",
+                // pool id
+                "b94b22332abf5f89877a14cc88f2abc48c34b3df",
+                // zero for one
+                "00",
+                // transfer type Transfer
+                "01",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_pancakeswap_v3_other_deployer() {
+        let swap = swap(HashMap::from([
+            ("fee".to_string(), Bytes::from(BigInt::from(500).to_signed_bytes_be())),
+            ("deployer".to_string(), Bytes::from("0x0BFbCF9fa4f9C56B0F40a671Ad40E0805A091865")),
+        ]));
+
+        let result = encoder().encode_swap(&swap, &encoding_context());
+
+        assert!(matches!(
+            result,
+            Err(EncodingError::FatalError(msg)) if msg.contains("only supports pools of the deployer")
+        ));
+    }
+
+    #[test]
+    fn test_encode_pancakeswap_v3_invalid_fee() {
+        for fee in [BigInt::from(1_000_000), BigInt::from(1u64 << 32)] {
+            let swap =
+                swap(HashMap::from([("fee".to_string(), Bytes::from(fee.to_signed_bytes_be()))]));
+
+            let result = encoder().encode_swap(&swap, &encoding_context());
+
+            assert!(matches!(
+                result,
+                Err(EncodingError::FatalError(msg)) if msg.starts_with("Invalid fee")
+            ));
+        }
+    }
+}
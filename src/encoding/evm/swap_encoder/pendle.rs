@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use alloy::sol_types::SolValue;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        component_id::component_pool_address,
+        utils::{bytes_to_address, get_static_attribute},
+    },
+    models::{EncodingContext, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+/// Encodes a swap on a Pendle V2 market through the given executor address.
+///
+/// The component id is the market, and its `sy` and `pt` static attributes are the addresses of
+/// its SY (standardized yield) and PT (principal) tokens. Supported swaps are:
+/// * SY -> PT and PT -> SY, on the market (through the Pendle router, which solves the PT amount
+///   bought for an exact SY amount in).
+/// * Underlying -> SY (minting SY from one of its input tokens) and SY -> underlying (redeeming SY
+///   into one of its output tokens), on the SY contract.
+///
+/// YT (yield) tokens can't be swapped.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `native_token_address` - The address of the native token of the chain.
+#[derive(Clone)]
+pub struct PendleSwapEncoder {
+    executor_address: Bytes,
+    native_token_address: Bytes,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PendleDirection {
+    SyToPt = 0,
+    PtToSy = 1,
+    MintSy = 2,
+    RedeemSy = 3,
+}
+
+impl PendleSwapEncoder {
+    /// Returns the direction of the swap, from its tokens and the SY and PT of its market.
+    fn direction(
+        &self,
+        swap: &Swap,
+        sy: &Bytes,
+        pt: &Bytes,
+    ) -> Result<PendleDirection, EncodingError> {
+        let (token_in, token_out) = (swap.token_in(), swap.token_out());
+        if *token_in == self.native_token_address || *token_out == self.native_token_address {
+            return Err(EncodingError::InvalidInput(
+                "Pendle swaps of the native token are not supported. Swap the wrapped token \
+                 instead"
+                    .to_string(),
+            ));
+        }
+        match (token_in == sy, token_out == sy) {
+            (true, false) if token_out == pt => Ok(PendleDirection::SyToPt),
+            (false, true) if token_in == pt => Ok(PendleDirection::PtToSy),
+            (false, true) => Ok(PendleDirection::MintSy),
+            (true, false) => Ok(PendleDirection::RedeemSy),
+            _ => Err(EncodingError::InvalidInput(format!(
+                "Pendle swaps must go from or to the SY of the market. Found {token_in} -> \
+                 {token_out}"
+            ))),
+        }
+    }
+}
+
+impl SwapEncoder for PendleSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        chain: Chain,
+        _config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        Ok(Self { executor_address, native_token_address: chain.native_token().address })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let market = component_pool_address(swap.component())?;
+        let sy = Bytes::from(get_static_attribute(swap, "sy")?);
+        let pt = Bytes::from(get_static_attribute(swap, "pt")?);
+        let direction = self.direction(swap, &sy, &pt)?;
+        // The token swapped against the SY: the PT of the market, or the underlying token minted
+        // from or redeemed into
+        let token = match direction {
+            PendleDirection::SyToPt | PendleDirection::PtToSy => &pt,
+            PendleDirection::MintSy => swap.token_in(),
+            PendleDirection::RedeemSy => swap.token_out(),
+        };
+
+        let args = (
+            (direction as u8).to_be_bytes(),
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            bytes_to_address(&encoding_context.receiver)?,
+            market,
+            bytes_to_address(&sy)?,
+            bytes_to_address(token)?,
+        );
+
+        Ok(args.abi_encode_packed())
+    }
+
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::{
+        evm::utils::write_calldata_to_file,
+        models::{ApprovalPolicy, TransferType},
+    };
+
+    // A market of PT-sUSDe, with sUSDe as the underlying of its SY
+    const MARKET: &str = "0xA36b60A14A1A5247912584768C6e53E1a269a9F7";
+    const SY: &str = "0xAbF8165dE2aE3FA5e5D2A0aEe5dDB3C9Ae7b9cA8";
+    const PT: &str = "0x9F56094C450763769BA0EA9Fe2876070c0fD5F77";
+    const SUSDE: &str = "0x9D39A5DE30e57443BfF2A8307A4256c8797A3497";
+
+    fn pendle_encoder() -> PendleSwapEncoder {
+        PendleSwapEncoder::new(
+            Bytes::from("0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn pendle_swap(token_in: &str, token_out: &str) -> Swap {
+        let market = ProtocolComponent {
+            id: String::from(MARKET),
+            protocol_system: String::from("pendle_v2"),
+            static_attributes: HashMap::from([
+                ("sy".to_string(), Bytes::from(SY)),
+                ("pt".to_string(), Bytes::from(PT)),
+            ]),
+            ..Default::default()
+        };
+        Swap::new(market, Bytes::from(token_in), Bytes::from(token_out))
+    }
+
+    fn encoding_context() -> EncodingContext {
+        EncodingContext {
+            // The receiver was generated with `makeAddr("bob*") using forge`
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"),
+            exact_out: false,
+            router_address: Some(Bytes::default()),
+            group_token_in: Bytes::from(SY),
+            group_token_out: Bytes::from(PT),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        }
+    }
+
+    #[test]
+    fn test_encode_pendle_sy_to_pt() {
+        let encoded_swap = pendle_encoder()
+            .encode_swap(&pendle_swap(SY, PT), &encoding_context())
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // direction (SY -> PT)
+                "00",
+                // transfer type
+                "01",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // market
+                "a36b60a14a1a5247912584768c6e53e1a269a9f7",
+                // SY
+                "abf8165de2ae3fa5e5d2a0aee5ddb3c9ae7b9ca8",
+                // PT
+                "9f56094c450763769ba0ea9fe2876070c0fd5f77",
+            ))
+        );
+
+        write_calldata_to_file("test_encode_pendle_sy_to_pt", hex_swap.as_str());
+    }
+
+    #[test]
+    fn test_encode_pendle_directions() {
+        let encoder = pendle_encoder();
+        let cases = [
+            (PT, SY, PendleDirection::PtToSy, PT),
+            (SUSDE, SY, PendleDirection::MintSy, SUSDE),
+            (SY, SUSDE, PendleDirection::RedeemSy, SUSDE),
+        ];
+        for (token_in, token_out, direction, token) in cases {
+            let encoded_swap = encoder
+                .encode_swap(&pendle_swap(token_in, token_out), &encoding_context())
+                .unwrap();
+
+            assert_eq!(encoded_swap[0], direction as u8);
+            assert_eq!(Bytes::from(encoded_swap[62..82].to_vec()), Bytes::from(token));
+        }
+    }
+
+    #[test]
+    fn test_encode_pendle_invalid_tokens() {
+        let encoder = pendle_encoder();
+
+        // The swap must go from or to the SY
+        let result = encoder.encode_swap(&pendle_swap(PT, SUSDE), &encoding_context());
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+        // The native token can't be minted into SY
+        let result = encoder.encode_swap(
+            &pendle_swap("0x0000000000000000000000000000000000000000", SY),
+            &encoding_context(),
+        );
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use alloy::sol_types::SolValue;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        approvals::protocol_approvals_manager::ProtocolApprovalsManager, utils::bytes_to_address,
+    },
+    models::{EncodingContext, Swap},
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
+};
+
+/// Encodes a swap through a Sky (formerly Maker) Peg Stability Module through the given executor
+/// address: selling the gem (USDC) for DAI or USDS, or buying it with them.
+///
+/// DAI is swapped on the Lite PSM and USDS on its USDS wrapper, which share the same interface.
+///
+/// # Fields
+/// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `gem_address` - The address of the gem token of the PSMs.
+/// * `gem_decimals` - The decimals of the gem token. Amounts of gems with less than 18 decimals are
+///   converted by the executor when buying gems.
+/// * `dai_address` - The address of the DAI token.
+/// * `psm_address` - The address of the DAI PSM (the Lite PSM).
+/// * `usds_address` - The address of the USDS token.
+/// * `usds_psm_address` - The address of the USDS PSM (the USDS wrapper of the Lite PSM).
+#[derive(Clone)]
+pub struct SkyPsmSwapEncoder {
+    executor_address: Bytes,
+    gem_address: Bytes,
+    gem_decimals: u8,
+    dai_address: Bytes,
+    psm_address: Bytes,
+    usds_address: Bytes,
+    usds_psm_address: Bytes,
+}
+
+#[repr(u8)]
+enum SkyPsmDirection {
+    SellGem = 0,
+    BuyGem = 1,
+}
+
+impl SwapEncoder for SkyPsmSwapEncoder {
+    fn new(
+        executor_address: Bytes,
+        _chain: Chain,
+        config: Option<HashMap<String, String>>,
+    ) -> Result<Self, EncodingError> {
+        let config = config
+            .ok_or_else(|| EncodingError::FatalError("Sky PSM config is empty".to_string()))?;
+        let address = |key: &str| {
+            config
+                .get(key)
+                .map(|a| Bytes::from(a.as_str()))
+                .ok_or_else(|| {
+                    EncodingError::FatalError(format!("Missing {key} in sky_psm config"))
+                })
+        };
+        let gem_decimals = config
+            .get("gem_decimals")
+            .ok_or_else(|| {
+                EncodingError::FatalError("Missing gem_decimals in sky_psm config".to_string())
+            })?
+            .parse::<u8>()
+            .map_err(|_| EncodingError::FatalError("Invalid sky_psm gem_decimals".to_string()))?;
+
+        Ok(Self {
+            executor_address,
+            gem_address: address("gem_address")?,
+            gem_decimals,
+            dai_address: address("dai_address")?,
+            psm_address: address("psm_address")?,
+            usds_address: address("usds_address")?,
+            usds_psm_address: address("usds_psm_address")?,
+        })
+    }
+
+    fn encode_swap(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        let (direction, stable_token) = if *swap.token_in() == self.gem_address {
+            (SkyPsmDirection::SellGem, swap.token_out())
+        } else if *swap.token_out() == self.gem_address {
+            (SkyPsmDirection::BuyGem, swap.token_in())
+        } else {
+            return Err(EncodingError::InvalidInput(format!(
+                "Sky PSM swaps must sell or buy the gem {}",
+                self.gem_address
+            )));
+        };
+        let psm_address = if *stable_token == self.dai_address {
+            &self.psm_address
+        } else if *stable_token == self.usds_address {
+            &self.usds_psm_address
+        } else {
+            return Err(EncodingError::InvalidInput(format!(
+                "Sky PSM swaps can only swap the gem for DAI or USDS. Found {stable_token}"
+            )));
+        };
+        let approval_needed =
+            self.approval_needed(encoding_context, swap.token_in(), psm_address)?;
+
+        let args = (
+            bytes_to_address(swap.token_in())?,
+            bytes_to_address(psm_address)?,
+            bytes_to_address(&encoding_context.receiver)?,
+            (encoding_context.transfer_type as u8).to_be_bytes(),
+            (direction as u8).to_be_bytes(),
+            self.gem_decimals != 18,
+            approval_needed,
+        );
+
+        Ok(args.abi_encode_packed())
+    }
+
+    fn accepts_user_data_segment(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { funds_in_router: true, ..Default::default() }
+    }
+
+    fn executor_address(&self) -> &Bytes {
+        &self.executor_address
+    }
+    fn clone_box(&self) -> Box<dyn SwapEncoder> {
+        Box::new(self.clone())
+    }
+}
+
+impl SkyPsmSwapEncoder {
+    fn approval_needed(
+        &self,
+        encoding_context: &EncodingContext,
+        token_address: &Bytes,
+        spender_address: &Bytes,
+    ) -> Result<bool, EncodingError> {
+        if let Some(router_address) = &encoding_context.router_address {
+            if !encoding_context.historical_trade {
+                let token_approvals_manager = ProtocolApprovalsManager::new()?;
+                return token_approvals_manager.approval_needed(
+                    bytes_to_address(token_address)?,
+                    bytes_to_address(router_address)?,
+                    bytes_to_address(spender_address)?,
+                );
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::hex::encode;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::{ApprovalPolicy, TransferType};
+
+    const USDC_ADDRESS: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    const DAI_ADDRESS: &str = "0x6B175474E89094C44Da98b954EedeAC495271d0F";
+    const USDS_ADDRESS: &str = "0xdC035D45d973E3EC169d2276DDab16f1e407384F";
+
+    fn sky_psm_config() -> HashMap<String, String> {
+        HashMap::from([
+            ("gem_address".to_string(), USDC_ADDRESS.to_string()),
+            ("gem_decimals".to_string(), "6".to_string()),
+            ("dai_address".to_string(), DAI_ADDRESS.to_string()),
+            ("psm_address".to_string(), "0xf6e72Db5454dd049d0788e411b06CfAF16853042".to_string()),
+            ("usds_address".to_string(), USDS_ADDRESS.to_string()),
+            (
+                "usds_psm_address".to_string(),
+                "0xA188EEC8F81263234dA3622A406892F3D630f98c".to_string(),
+            ),
+        ])
+    }
+
+    fn encode_sky_psm_swap(token_in: &str, token_out: &str) -> Result<String, EncodingError> {
+        let component = ProtocolComponent {
+            id: String::from("0xf6e72Db5454dd049d0788e411b06CfAF16853042"),
+            protocol_system: String::from("sky_psm"),
+            ..Default::default()
+        };
+        let token_in = Bytes::from(token_in);
+        let token_out = Bytes::from(token_out);
+        let swap = Swap::new(component, token_in.clone(), token_out.clone());
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0x1D96F2f6BeF1202E4Ce1Ff6Dad0c2CB002861d3e"),
+            exact_out: false,
+            router_address: None,
+            group_token_in: token_in,
+            group_token_out: token_out,
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = SkyPsmSwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            Some(sky_psm_config()),
+        )
+        .unwrap();
+        let encoded_swap = encoder.encode_swap(&swap, &encoding_context)?;
+        Ok(encode(&encoded_swap))
+    }
+
+    #[test]
+    fn test_encode_sky_psm_sell_gem() {
+        let hex_swap = encode_sky_psm_swap(USDC_ADDRESS, DAI_ADDRESS).unwrap();
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // token in
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                // psm
+                "f6e72db5454dd049d0788e411b06cfaf16853042",
+                // receiver
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // transfer type Transfer
+                "01",
+                // direction SellGem
+                "00",
+                // convert gem decimals
+                "01",
+                // approval_needed
+                "01",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_sky_psm_buy_gem_with_usds() {
+        let hex_swap = encode_sky_psm_swap(USDS_ADDRESS, USDC_ADDRESS).unwrap();
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // token in
+                "dc035d45d973e3ec169d2276ddab16f1e407384f",
+                // psm (USDS wrapper)
+                "a188eec8f81263234da3622a406892f3d630f98c",
+                // receiver
+                "1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e",
+                // transfer type Transfer
+                "01",
+                // direction BuyGem
+                "01",
+                // convert gem decimals
+                "01",
+                // approval_needed
+                "01",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_sky_psm_invalid_pair() {
+        // DAI and USDS are swapped on their own converter, not on the PSM
+        let result = encode_sky_psm_swap(DAI_ADDRESS, USDS_ADDRESS);
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -1,13 +1,16 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
 use alloy::{primitives::Address, sol_types::SolValue};
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
-    evm::utils::{bytes_to_address, get_static_attribute, pad_or_truncate_to_size},
+    evm::{
+        component_id::component_pool_address,
+        utils::{bytes_to_address, get_static_attribute, pad_or_truncate_to_size},
+    },
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 /// Encodes a swap on a Aerodrome Slipstreams pool through the given executor address.
@@ -43,9 +46,7 @@ impl SwapEncoder for SlipstreamsSwapEncoder {
         let token_out_address = bytes_to_address(swap.token_out())?;
 
         let zero_to_one = Self::get_zero_to_one(token_in_address, token_out_address);
-        let component_id = Address::from_str(&swap.component().id).map_err(|_| {
-            EncodingError::FatalError("Invalid Slipstreams component id".to_string())
-        })?;
+        let component_id = component_pool_address(swap.component())?;
         let tick_spacing_bytes = get_static_attribute(swap, "tick_spacing")?;
 
         let tick_spacing_bytes_u24 =
@@ -66,6 +67,10 @@ impl SwapEncoder for SlipstreamsSwapEncoder {
         Ok(args.abi_encode_packed())
     }
 
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { callback_constrained: true, ..Default::default() }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
@@ -1,36 +1,67 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::HashMap,
+    fs,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
 
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
     evm::{
-        constants::{DEFAULT_EXECUTORS_JSON, PROTOCOL_SPECIFIC_CONFIG},
+        constants::{
+            DEFAULT_EXECUTORS_JSON, DEFAULT_SWAP_GAS_ESTIMATE, PROTOCOL_SPECIFIC_CONFIG,
+            SWAP_GAS_ESTIMATES,
+        },
+        group_swaps::group_protocol_system,
         swap_encoder::{
-            balancer_v2::BalancerV2SwapEncoder, balancer_v3::BalancerV3SwapEncoder,
-            bebop::BebopSwapEncoder, curve::CurveSwapEncoder, ekubo::EkuboSwapEncoder,
-            ekubo_v3::EkuboV3SwapEncoder, erc_4626::ERC4626SwapEncoder,
+            across::BridgeSwapEncoder, aerodrome_v2::AerodromeV2SwapEncoder,
+            ambient::AmbientSwapEncoder, balancer_v2::BalancerV2SwapEncoder,
+            balancer_v3::BalancerV3SwapEncoder, bebop::BebopSwapEncoder, curve::CurveSwapEncoder,
+            ekubo::EkuboSwapEncoder, ekubo_v3::EkuboV3SwapEncoder, erc_4626::ERC4626SwapEncoder,
             etherfi::EtherfiSwapEncoder, fluid_v1::FluidV1SwapEncoder,
-            hashflow::HashflowSwapEncoder, maverick_v2::MaverickV2SwapEncoder,
-            rocketpool::RocketpoolSwapEncoder, slipstreams::SlipstreamsSwapEncoder,
+            frax_ether::FraxStakingSwapEncoder, hashflow::HashflowSwapEncoder,
+            lido_withdrawal::LidoWithdrawalSwapEncoder, lst::LstSwapEncoder,
+            maverick_v2::MaverickV2SwapEncoder, oneinch_lop::LimitOrderSwapEncoder,
+            pancakeswap_v3::PancakeV3SwapEncoder, pendle::PendleSwapEncoder,
+            sky_psm::SkyPsmSwapEncoder, slipstreams::SlipstreamsSwapEncoder,
             uniswap_v2::UniswapV2SwapEncoder, uniswap_v3::UniswapV3SwapEncoder,
             uniswap_v4::UniswapV4SwapEncoder,
         },
     },
-    swap_encoder::SwapEncoder,
+    models::RfqConfig,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
 /// Registry containing all supported `SwapEncoders`.
+///
+/// The encoders are shared between the clones of the registry, so cloning it (e.g. for every
+/// strategy or tenant encoder) is cheap.
 #[derive(Clone)]
 pub struct SwapEncoderRegistry {
     chain: Chain,
     /// A hashmap containing the protocol system as a key and the `SwapEncoder` as a value.
-    encoders: HashMap<String, Box<dyn SwapEncoder>>,
+    encoders: Arc<HashMap<String, Box<dyn SwapEncoder>>>,
+    /// The gas estimates overriding the static ones (see `SWAP_GAS_ESTIMATES`), by protocol
+    /// system.
+    gas_estimates: Arc<HashMap<String, u64>>,
+    /// The RFQ config applied to the RFQ encoders (see `with_rfq_config`), kept to apply it again
+    /// to the encoders of a reloaded registry.
+    rfq_config: Option<RfqConfig>,
+    /// The protocol systems encoded like another one (see `with_protocol_alias`), by alias.
+    protocol_aliases: Arc<HashMap<String, String>>,
 }
 
 impl SwapEncoderRegistry {
     pub fn new(chain: Chain) -> Self {
-        Self { chain, encoders: HashMap::new() }
+        Self {
+            chain,
+            encoders: Arc::new(HashMap::new()),
+            gas_estimates: Arc::new(HashMap::new()),
+            rfq_config: None,
+            protocol_aliases: Arc::new(HashMap::new()),
+        }
     }
 
     /// Populates the registry with the default `SwapEncoders` for the given blockchain by
@@ -48,7 +79,67 @@ impl SwapEncoderRegistry {
         let executors = config
             .get(&self.chain)
             .ok_or(EncodingError::FatalError("No executors found for chain".to_string()))?;
+        self.add_encoders(executors)?;
+        Ok(self)
+    }
+
+    /// Returns a registry for the same chain with the default `SwapEncoders` of the given
+    /// executors' addresses, e.g. after some executors were redeployed. The addresses are given
+    /// either as the content of an executors' addresses file (keyed by chain, like the default
+    /// one) or as the path of such a file.
+    ///
+    /// The gas estimate overrides, the RFQ config and the protocol aliases of this registry are
+    /// kept. The encoders
+    /// registered manually (see `register_encoder`) are not, unless their protocol is in the file.
+    pub fn reload(&self, executors_addresses: &str) -> Result<Self, EncodingError> {
+        let config_str = if executors_addresses
+            .trim_start()
+            .starts_with('{')
+        {
+            executors_addresses.to_string()
+        } else {
+            fs::read_to_string(executors_addresses).map_err(|err| {
+                EncodingError::FatalError(format!(
+                    "Failed to read the executors' addresses file {executors_addresses}: {err}"
+                ))
+            })?
+        };
+        let mut registry = Self {
+            chain: self.chain,
+            encoders: Arc::new(HashMap::new()),
+            gas_estimates: self.gas_estimates.clone(),
+            rfq_config: None,
+            protocol_aliases: self.protocol_aliases.clone(),
+        }
+        .add_default_encoders(Some(config_str))?;
+        if let Some(rfq_config) = &self.rfq_config {
+            registry = registry.with_rfq_config(rfq_config);
+        }
+        Ok(registry)
+    }
 
+    /// Builds one registry per chain of the executors' addresses file, populated with the default
+    /// `SwapEncoders` of that chain. The file is keyed by chain (`{"ethereum": {...}, "base":
+    /// {...}}`), like the default one.
+    pub fn new_multichain(
+        executors_addresses: Option<String>,
+    ) -> Result<HashMap<Chain, Self>, EncodingError> {
+        let config_str = if let Some(addresses) = executors_addresses {
+            addresses
+        } else {
+            DEFAULT_EXECUTORS_JSON.to_string()
+        };
+        let config: HashMap<Chain, HashMap<String, String>> = serde_json::from_str(&config_str)?;
+        let mut registries = HashMap::new();
+        for (chain, executors) in config {
+            let mut registry = Self::new(chain);
+            registry.add_encoders(&executors)?;
+            registries.insert(chain, registry);
+        }
+        Ok(registries)
+    }
+
+    fn add_encoders(&mut self, executors: &HashMap<String, String>) -> Result<(), EncodingError> {
         let protocol_specific_config: HashMap<Chain, HashMap<String, HashMap<String, String>>> =
             serde_json::from_str(PROTOCOL_SPECIFIC_CONFIG)?;
         let protocol_specific_config = protocol_specific_config
@@ -57,6 +148,18 @@ impl SwapEncoderRegistry {
                 "No protocol specific config found for chain".to_string(),
             ))?;
         for (protocol, executor_address) in executors {
+            // Aliases without a config of their own use the config of their protocol
+            let config = protocol_specific_config
+                .get(protocol)
+                .or_else(|| protocol_specific_config.get(self.resolve_alias(protocol)));
+            if let Some(gas_estimate) = config.and_then(|config| config.get("gas_estimate")) {
+                let gas_estimate = gas_estimate.parse().map_err(|_| {
+                    EncodingError::FatalError(format!(
+                        "Invalid gas estimate for protocol {protocol}"
+                    ))
+                })?;
+                Arc::make_mut(&mut self.gas_estimates).insert(protocol.to_string(), gas_estimate);
+            }
             let encoder = self.create_encoder(
                 protocol,
                 Bytes::from_str(executor_address).map_err(|_| {
@@ -65,23 +168,101 @@ impl SwapEncoderRegistry {
                         protocol
                     ))
                 })?,
-                protocol_specific_config
-                    .get(protocol)
-                    .cloned(),
+                config.cloned(),
             )?;
-            self.encoders
-                .insert(protocol.to_string(), encoder);
+            Arc::make_mut(&mut self.encoders).insert(protocol.to_string(), encoder);
         }
-        Ok(self)
+        Ok(())
     }
 
     // Adds an encoder to the registry
     pub fn register_encoder(mut self, protocol: &str, encoder: Box<dyn SwapEncoder>) -> Self {
-        self.encoders
-            .insert(protocol.to_string(), encoder);
+        Arc::make_mut(&mut self.encoders).insert(protocol.to_string(), encoder);
+        self
+    }
+
+    /// Applies the quote timeout and retry policy to the registered RFQ encoders. The other
+    /// encoders are left untouched.
+    pub fn with_rfq_config(mut self, rfq_config: &RfqConfig) -> Self {
+        self.rfq_config = Some(*rfq_config);
+        let rfq_encoders: Vec<(String, Box<dyn SwapEncoder>)> = self
+            .encoders
+            .iter()
+            .filter_map(|(protocol, encoder)| {
+                encoder
+                    .with_rfq_config(rfq_config)
+                    .map(|encoder| (protocol.clone(), encoder))
+            })
+            .collect();
+        if !rfq_encoders.is_empty() {
+            Arc::make_mut(&mut self.encoders).extend(rfq_encoders);
+        }
+        self
+    }
+
+    /// Encodes the swaps of the `alias` protocol system like those of `protocol_system`, e.g.
+    /// `sushiswap_v2` like `uniswap_v2` for a fork using the same executor format with its own
+    /// factory and init code hash. The alias then gets its own encoder and executor from the
+    /// executors' addresses (see `add_default_encoders`), so aliases must be set before adding
+    /// the encoders.
+    ///
+    /// Only the encoder is shared: the alias keeps its own protocol specific config and gas
+    /// estimate, falling back to those of `protocol_system` if it has none.
+    pub fn with_protocol_alias(mut self, alias: &str, protocol_system: &str) -> Self {
+        Arc::make_mut(&mut self.protocol_aliases)
+            .insert(alias.to_string(), protocol_system.to_string());
+        self
+    }
+
+    /// Returns the protocol system encoding the swaps of the given one: the protocol it is an alias
+    /// of, else itself.
+    fn resolve_alias<'a>(&'a self, protocol_system: &'a str) -> &'a str {
+        self.protocol_aliases
+            .get(protocol_system)
+            .map(String::as_str)
+            .unwrap_or(protocol_system)
+    }
+
+    /// Overrides the static gas estimate of a swap on the given protocol.
+    pub fn with_gas_estimate(mut self, protocol: &str, gas_estimate: u64) -> Self {
+        Arc::make_mut(&mut self.gas_estimates).insert(protocol.to_string(), gas_estimate);
         self
     }
 
+    /// Returns the gas estimate of a single swap on the given protocol: the configured override if
+    /// any, else its static estimate (see `SWAP_GAS_ESTIMATES`) or the one of the protocol it is an
+    /// alias of.
+    pub fn gas_estimate(&self, protocol_system: &str) -> u64 {
+        self.gas_estimates
+            .get(protocol_system)
+            .or_else(|| SWAP_GAS_ESTIMATES.get(protocol_system))
+            .or_else(|| SWAP_GAS_ESTIMATES.get(self.resolve_alias(protocol_system)))
+            .copied()
+            .unwrap_or(DEFAULT_SWAP_GAS_ESTIMATE)
+    }
+
+    /// Returns whether the two registries share the same encoders.
+    pub fn shares_encoders_with(&self, other: &SwapEncoderRegistry) -> bool {
+        Arc::ptr_eq(&self.encoders, &other.encoders)
+    }
+
+    /// Returns the executor address of every registered encoder, by protocol system.
+    pub fn executor_addresses(&self) -> HashMap<String, Bytes> {
+        self.encoders
+            .iter()
+            .map(|(protocol, encoder)| (protocol.clone(), encoder.executor_address().clone()))
+            .collect()
+    }
+
+    /// Returns the capabilities of the encoder of the given protocol system (see
+    /// `SwapEncoder::capabilities`), or `None` if no encoder is registered for it. Protocol
+    /// systems encoded by the encoder of another one (see `group_protocol_system`) take its
+    /// capabilities.
+    pub fn capabilities(&self, protocol_system: &str) -> Option<EncoderCapabilities> {
+        self.get_encoder(group_protocol_system(protocol_system))
+            .map(|encoder| encoder.capabilities())
+    }
+
     #[allow(clippy::borrowed_box)]
     pub fn get_encoder(&self, protocol_system: &str) -> Option<&Box<dyn SwapEncoder>> {
         self.encoders.get(protocol_system)
@@ -93,7 +274,7 @@ impl SwapEncoderRegistry {
         executor_address: Bytes,
         config: Option<HashMap<String, String>>,
     ) -> Result<Box<dyn SwapEncoder>, EncodingError> {
-        match protocol_system {
+        match self.resolve_alias(protocol_system) {
             "uniswap_v2" => {
                 Ok(Box::new(UniswapV2SwapEncoder::new(executor_address, self.chain, config)?))
             }
@@ -106,11 +287,14 @@ impl SwapEncoderRegistry {
             "vm:balancer_v2" => {
                 Ok(Box::new(BalancerV2SwapEncoder::new(executor_address, self.chain, config)?))
             }
+            "ambient" => {
+                Ok(Box::new(AmbientSwapEncoder::new(executor_address, self.chain, config)?))
+            }
             "uniswap_v3" => {
                 Ok(Box::new(UniswapV3SwapEncoder::new(executor_address, self.chain, config)?))
             }
             "pancakeswap_v3" => {
-                Ok(Box::new(UniswapV3SwapEncoder::new(executor_address, self.chain, config)?))
+                Ok(Box::new(PancakeV3SwapEncoder::new(executor_address, self.chain, config)?))
             }
             "uniswap_v4" => {
                 Ok(Box::new(UniswapV4SwapEncoder::new(executor_address, self.chain, config)?))
@@ -136,6 +320,9 @@ impl SwapEncoderRegistry {
             "rfq:hashflow" => {
                 Ok(Box::new(HashflowSwapEncoder::new(executor_address, self.chain, config)?))
             }
+            "rfq:oneinch_lop" => {
+                Ok(Box::new(LimitOrderSwapEncoder::new(executor_address, self.chain, config)?))
+            }
             "fluid_v1" => {
                 Ok(Box::new(FluidV1SwapEncoder::new(executor_address, self.chain, config)?))
             }
@@ -143,7 +330,11 @@ impl SwapEncoderRegistry {
                 Ok(Box::new(SlipstreamsSwapEncoder::new(executor_address, self.chain, config)?))
             }
             "rocketpool" => {
-                Ok(Box::new(RocketpoolSwapEncoder::new(executor_address, self.chain, config)?))
+                Ok(Box::new(LstSwapEncoder::new(executor_address, self.chain, config)?))
+            }
+            "lido" => Ok(Box::new(LstSwapEncoder::new(executor_address, self.chain, config)?)),
+            "mantle_meth" => {
+                Ok(Box::new(LstSwapEncoder::new(executor_address, self.chain, config)?))
             }
             "erc4626" => {
                 Ok(Box::new(ERC4626SwapEncoder::new(executor_address, self.chain, config)?))
@@ -151,9 +342,30 @@ impl SwapEncoderRegistry {
             "velodrome_slipstreams" => {
                 Ok(Box::new(SlipstreamsSwapEncoder::new(executor_address, self.chain, config)?))
             }
+            "aerodrome_v2" => {
+                Ok(Box::new(AerodromeV2SwapEncoder::new(executor_address, self.chain, config)?))
+            }
+            "velodrome_v2" => {
+                Ok(Box::new(AerodromeV2SwapEncoder::new(executor_address, self.chain, config)?))
+            }
             "etherfi" => {
                 Ok(Box::new(EtherfiSwapEncoder::new(executor_address, self.chain, config)?))
             }
+            "frax_ether" => {
+                Ok(Box::new(FraxStakingSwapEncoder::new(executor_address, self.chain, config)?))
+            }
+            "sky_psm" => {
+                Ok(Box::new(SkyPsmSwapEncoder::new(executor_address, self.chain, config)?))
+            }
+            "pendle_v2" => {
+                Ok(Box::new(PendleSwapEncoder::new(executor_address, self.chain, config)?))
+            }
+            "lido_withdrawal" => {
+                Ok(Box::new(LidoWithdrawalSwapEncoder::new(executor_address, self.chain, config)?))
+            }
+            "bridge:across" => {
+                Ok(Box::new(BridgeSwapEncoder::new(executor_address, self.chain, config)?))
+            }
             _ => Err(EncodingError::FatalError(format!(
                 "Unknown protocol system: {}",
                 protocol_system
@@ -161,3 +373,180 @@ impl SwapEncoderRegistry {
         }
     }
 }
+
+/// A `SwapEncoderRegistry` shared between encoders, so that its executors can be rotated at runtime
+/// (e.g. after a redeploy) without rebuilding the encoders.
+///
+/// The encoders given a shared registry (see `TychoRouterEncoder::with_shared_registry`) take its
+/// current registry at the start of every encoding, so a reload only affects the encodings started
+/// after it.
+#[derive(Clone)]
+pub struct SharedSwapEncoderRegistry {
+    registry: Arc<RwLock<SwapEncoderRegistry>>,
+}
+
+impl SharedSwapEncoderRegistry {
+    pub fn new(registry: SwapEncoderRegistry) -> Self {
+        Self { registry: Arc::new(RwLock::new(registry)) }
+    }
+
+    /// Returns the current registry.
+    pub fn current(&self) -> SwapEncoderRegistry {
+        self.registry
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Reloads the registry from the given executors' addresses (see
+    /// `SwapEncoderRegistry::reload`). The current registry is kept if they are invalid.
+    pub fn reload(&self, executors_addresses: &str) -> Result<(), EncodingError> {
+        let reloaded = self
+            .current()
+            .reload(executors_addresses)?;
+        self.replace(reloaded)
+    }
+
+    /// Replaces the current registry with the given one, which must be for the same chain.
+    pub fn replace(&self, registry: SwapEncoderRegistry) -> Result<(), EncodingError> {
+        let mut current = self
+            .registry
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if current.chain != registry.chain {
+            return Err(EncodingError::FatalError(format!(
+                "Can't replace the {:?} registry with a {:?} one",
+                current.chain, registry.chain
+            )));
+        }
+        *current = registry;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXECUTORS: &str =
+        r#"{"ethereum": {"uniswap_v2": "0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"}}"#;
+    const REDEPLOYED_EXECUTORS: &str = r#"{"ethereum": {"uniswap_v2": "0x1d1499e622D69689cdf9004d05Ec547d650Ff211", "uniswap_v3": "0x2e234DAe75C793f67A35089C9d99245E1C58470b"}}"#;
+
+    #[test]
+    fn test_reload() {
+        let registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(EXECUTORS.to_string()))
+            .unwrap()
+            .with_gas_estimate("uniswap_v2", 42);
+
+        let reloaded = registry
+            .reload(REDEPLOYED_EXECUTORS)
+            .unwrap();
+
+        assert_eq!(
+            reloaded.executor_addresses(),
+            HashMap::from([
+                (
+                    "uniswap_v2".to_string(),
+                    Bytes::from_str("0x1d1499e622D69689cdf9004d05Ec547d650Ff211").unwrap()
+                ),
+                (
+                    "uniswap_v3".to_string(),
+                    Bytes::from_str("0x2e234DAe75C793f67A35089C9d99245E1C58470b").unwrap()
+                ),
+            ])
+        );
+        assert_eq!(reloaded.gas_estimate("uniswap_v2"), 42);
+        assert!(!reloaded.shares_encoders_with(&registry));
+        assert!(registry
+            .reload("config/missing_executor_addresses.json")
+            .is_err());
+    }
+
+    #[test]
+    fn test_protocol_alias() {
+        let executors =
+            r#"{"ethereum": {"fork_v2": "0x1d1499e622D69689cdf9004d05Ec547d650Ff211"}}"#;
+        assert!(SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors.to_string()))
+            .is_err());
+
+        let registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .with_protocol_alias("fork_v2", "uniswap_v2")
+            .add_default_encoders(Some(executors.to_string()))
+            .unwrap();
+
+        assert_eq!(
+            registry
+                .get_encoder("fork_v2")
+                .unwrap()
+                .executor_address(),
+            &Bytes::from_str("0x1d1499e622D69689cdf9004d05Ec547d650Ff211").unwrap()
+        );
+        assert!(registry
+            .get_encoder("uniswap_v2")
+            .is_none());
+        assert_eq!(registry.gas_estimate("fork_v2"), registry.gas_estimate("uniswap_v2"));
+        assert!(registry
+            .reload(executors)
+            .unwrap()
+            .get_encoder("fork_v2")
+            .is_some());
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let executors = r#"{"ethereum": {
+            "uniswap_v4": "0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f",
+            "fork_v4": "0x1d1499e622D69689cdf9004d05Ec547d650Ff211"
+        }}"#;
+        let registry = SwapEncoderRegistry::new(Chain::Ethereum)
+            .with_protocol_alias("fork_v4", "uniswap_v4")
+            .add_default_encoders(Some(executors.to_string()))
+            .unwrap();
+
+        let capabilities = registry
+            .capabilities("uniswap_v4")
+            .unwrap();
+        assert!(capabilities.groupable && capabilities.callback_constrained);
+        // Aliases and Uniswap V4 hooks take the capabilities of their encoder
+        assert_eq!(registry.capabilities("fork_v4"), Some(capabilities));
+        assert_eq!(registry.capabilities("uniswap_v4_hooks"), Some(capabilities));
+        assert_eq!(registry.capabilities("uniswap_v2"), None);
+    }
+
+    #[test]
+    fn test_shared_registry() {
+        let shared = SharedSwapEncoderRegistry::new(
+            SwapEncoderRegistry::new(Chain::Ethereum)
+                .add_default_encoders(Some(EXECUTORS.to_string()))
+                .unwrap(),
+        );
+        let before = shared.current();
+
+        shared
+            .reload(REDEPLOYED_EXECUTORS)
+            .unwrap();
+
+        assert!(!shared
+            .current()
+            .shares_encoders_with(&before));
+        assert_eq!(
+            shared
+                .current()
+                .get_encoder("uniswap_v2")
+                .unwrap()
+                .executor_address(),
+            &Bytes::from_str("0x1d1499e622D69689cdf9004d05Ec547d650Ff211").unwrap()
+        );
+        // Invalid addresses keep the current registry
+        assert!(shared.reload("{}").is_err());
+        assert!(shared
+            .current()
+            .get_encoder("uniswap_v3")
+            .is_some());
+        assert!(shared
+            .replace(SwapEncoderRegistry::new(Chain::Base))
+            .is_err());
+    }
+}
@@ -1,17 +1,30 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
 use alloy::{primitives::Address, sol_types::SolValue};
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
-    evm::utils::bytes_to_address,
+    evm::{
+        component_id::component_pool_address,
+        utils::{bytes_to_address, supports_fee_on_transfer},
+    },
     models::{EncodingContext, Swap},
     swap_encoder::SwapEncoder,
 };
 
+/// Flag of the swaps' mode byte for exact out swaps.
+const EXACT_OUT_FLAG: u8 = 1;
+/// Flag of the swaps' mode byte for swaps involving fee-on-transfer tokens.
+const FEE_ON_TRANSFER_FLAG: u8 = 2;
+
 /// Encodes a swap on a Uniswap V2 pool through the given executor address.
 ///
+/// Swaps flagged as involving fee-on-transfer tokens (see `supports_fee_on_transfer`) are encoded
+/// with the semantics of `swapExactTokensForTokensSupportingFeeOnTransferTokens`: the executor
+/// measures the amount in from the balance of the pool and the amount out from the balance of the
+/// receiver. Exact out swaps can't support fee-on-transfer tokens.
+///
 /// # Fields
 /// * `executor_address` - The address of the executor contract that will perform the swap.
 #[derive(Clone)]
@@ -43,8 +56,7 @@ impl SwapEncoder for UniswapV2SwapEncoder {
         let token_out_address = bytes_to_address(swap.token_out())?;
 
         let zero_to_one = Self::get_zero_to_one(token_in_address, token_out_address);
-        let component_id = Address::from_str(&swap.component().id)
-            .map_err(|_| EncodingError::FatalError("Invalid USV2 component id".to_string()))?;
+        let component_id = component_pool_address(swap.component())?;
 
         let args = (
             token_in_address,
@@ -54,7 +66,24 @@ impl SwapEncoder for UniswapV2SwapEncoder {
             (encoding_context.transfer_type as u8).to_be_bytes(),
         );
 
-        Ok(args.abi_encode_packed())
+        let fee_on_transfer = supports_fee_on_transfer(swap);
+        if fee_on_transfer && encoding_context.exact_out {
+            return Err(EncodingError::InvalidInput(format!(
+                "Exact out swaps don't support fee-on-transfer tokens, but the swap on pool {} is \
+                 flagged as fee-on-transfer",
+                swap.component().id
+            )));
+        }
+
+        let mut encoded = args.abi_encode_packed();
+        // The mode byte is only appended to exact out and fee-on-transfer swaps, so the other swaps
+        // keep the layout older executors expect
+        if encoding_context.exact_out {
+            encoded.push(EXACT_OUT_FLAG);
+        } else if fee_on_transfer {
+            encoded.push(FEE_ON_TRANSFER_FLAG);
+        }
+        Ok(encoded)
     }
 
     fn executor_address(&self) -> &Bytes {
@@ -74,7 +103,7 @@ mod tests {
     use super::*;
     use crate::encoding::{
         evm::{swap_encoder::uniswap_v2::UniswapV2SwapEncoder, utils::write_calldata_to_file},
-        models::{Swap, TransferType},
+        models::{ApprovalPolicy, Swap, TransferType},
     };
     #[test]
     fn test_encode_uniswap_v2() {
@@ -94,6 +123,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = UniswapV2SwapEncoder::new(
             Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
@@ -122,4 +153,176 @@ mod tests {
         );
         write_calldata_to_file("test_encode_uniswap_v2", hex_swap.as_str());
     }
+
+    #[test]
+    fn test_encode_uniswap_v2_exact_out() {
+        let usv2_pool = ProtocolComponent {
+            id: String::from("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11"),
+            ..Default::default()
+        };
+
+        let token_in = Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let token_out = Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f");
+        let swap = Swap::new(usv2_pool, token_in.clone(), token_out.clone());
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"), // BOB*
+            exact_out: true,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::TransferFrom,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = UniswapV2SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // in token
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                // component id
+                "a478c2975ab1ea89e8196811f51a7b7ade33eb11",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // zero for one
+                "00",
+                // transfer type TransferFrom
+                "00",
+                // exact out
+                "01",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_uniswap_v2_hash_component_id() {
+        let usv2_pool = ProtocolComponent {
+            id: String::from("0x5c6ee304399dbdb9c8ef030ab642b10820db8f56000200000000000000000014"),
+            protocol_system: String::from("uniswap_v2"),
+            contract_addresses: vec![Bytes::from("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11")],
+            ..Default::default()
+        };
+
+        let token_in = Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let token_out = Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f");
+        let swap = Swap::new(usv2_pool, token_in.clone(), token_out.clone());
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"), // BOB*
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = UniswapV2SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // in token
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                // pool address, resolved from the contract addresses of the component
+                "a478c2975ab1ea89e8196811f51a7b7ade33eb11",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // zero for one
+                "00",
+                // transfer type Transfer
+                "01",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encode_uniswap_v2_fee_on_transfer() {
+        let usv2_pool = ProtocolComponent {
+            id: String::from("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11"),
+            static_attributes: HashMap::from([(
+                "supports_fot".to_string(),
+                Bytes::from(vec![1u8]),
+            )]),
+            ..Default::default()
+        };
+
+        let token_in = Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let token_out = Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f");
+        let swap = Swap::new(usv2_pool, token_in.clone(), token_out.clone());
+        let mut encoding_context = EncodingContext {
+            receiver: Bytes::from("0x9964bff29baa37b47604f3f3f51f3b3c5149d6de"), // BOB*
+            exact_out: false,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = UniswapV2SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+        assert_eq!(
+            hex_swap,
+            String::from(concat!(
+                // in token
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                // component id
+                "a478c2975ab1ea89e8196811f51a7b7ade33eb11",
+                // receiver
+                "9964bff29baa37b47604f3f3f51f3b3c5149d6de",
+                // zero for one
+                "00",
+                // transfer type Transfer
+                "01",
+                // fee-on-transfer
+                "02",
+            ))
+        );
+
+        // The user data of the swap doesn't set the flag
+        let tagged_swap = Swap::new(
+            ProtocolComponent {
+                id: String::from("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11"),
+                ..Default::default()
+            },
+            token_in,
+            token_out,
+        )
+        .user_data(Bytes::from(vec![1u8]));
+        assert!(!supports_fee_on_transfer(&tagged_swap));
+
+        encoding_context.exact_out = true;
+        assert!(matches!(
+            encoder.encode_swap(&swap, &encoding_context),
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("fee-on-transfer")
+        ));
+    }
 }
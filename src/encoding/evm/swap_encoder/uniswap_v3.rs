@@ -1,13 +1,16 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
 use alloy::{primitives::Address, sol_types::SolValue};
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
-    evm::utils::{bytes_to_address, get_static_attribute, pad_or_truncate_to_size},
+    evm::{
+        component_id::component_pool_address,
+        utils::{bytes_to_address, get_static_attribute, pad_or_truncate_to_size},
+    },
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 /// Encodes a swap on a Uniswap V3 pool through the given executor address.
 ///
@@ -42,8 +45,7 @@ impl SwapEncoder for UniswapV3SwapEncoder {
         let token_out_address = bytes_to_address(swap.token_out())?;
 
         let zero_to_one = Self::get_zero_to_one(token_in_address, token_out_address);
-        let component_id = Address::from_str(&swap.component().id)
-            .map_err(|_| EncodingError::FatalError("Invalid USV3 component id".to_string()))?;
+        let component_id = component_pool_address(swap.component())?;
         let pool_fee_bytes = get_static_attribute(swap, "fee")?;
 
         let pool_fee_u24 = pad_or_truncate_to_size::<3>(&pool_fee_bytes)
@@ -59,7 +61,17 @@ impl SwapEncoder for UniswapV3SwapEncoder {
             (encoding_context.transfer_type as u8).to_be_bytes(),
         );
 
-        Ok(args.abi_encode_packed())
+        let mut encoded = args.abi_encode_packed();
+        // The exact out flag is only appended for exact out swaps, so exact in swaps keep the
+        // layout older executors expect
+        if encoding_context.exact_out {
+            encoded.push(1);
+        }
+        Ok(encoded)
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities { callback_constrained: true, ..Default::default() }
     }
 
     fn executor_address(&self) -> &Bytes {
@@ -78,8 +90,8 @@ mod tests {
 
     use super::*;
     use crate::encoding::{
-        evm::swap_encoder::uniswap_v3::UniswapV3SwapEncoder,
-        models::{Swap, TransferType},
+        evm::{swap_encoder::uniswap_v3::UniswapV3SwapEncoder, utils::append_user_data},
+        models::{ApprovalPolicy, Swap, TransferType},
     };
     #[test]
     fn test_encode_uniswap_v3() {
@@ -104,6 +116,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = UniswapV3SwapEncoder::new(
             Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
@@ -135,4 +149,72 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_encode_uniswap_v3_exact_out() {
+        let mut static_attributes: HashMap<String, Bytes> = HashMap::new();
+        static_attributes.insert("fee".into(), Bytes::from(BigInt::from(500).to_signed_bytes_be()));
+        let usv3_pool = ProtocolComponent {
+            id: String::from("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640"),
+            static_attributes,
+            ..Default::default()
+        };
+        let token_in = Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let token_out = Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f");
+        let swap = Swap::new(usv3_pool, token_in.clone(), token_out.clone());
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0x0000000000000000000000000000000000000001"),
+            exact_out: true,
+            router_address: Some(Bytes::zero(20)),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::TransferFrom,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = UniswapV3SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let encoded_swap = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+        let hex_swap = encode(&encoded_swap);
+        // Same layout as exact in swaps, followed by the exact out flag
+        assert_eq!(encoded_swap.len(), 86);
+        assert!(hex_swap.ends_with(concat!(
+            // zero for one
+            "00", // transfer type TransferFrom
+            "00", // exact out
+            "01",
+        )));
+    }
+
+    #[test]
+    fn test_encode_uniswap_v3_ignores_user_data() {
+        // The executor checks the exact length of its data, so it can't carry the user data
+        let encoder = UniswapV3SwapEncoder::new(
+            Bytes::from("0x543778987b293C7E8Cf0722BB2e935ba6f4068D4"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let swap = Swap::new(
+            ProtocolComponent {
+                id: String::from("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640"),
+                protocol_system: String::from("uniswap_v3"),
+                ..Default::default()
+            },
+            Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f"),
+        )
+        .user_data(Bytes::from("0xabcd"));
+
+        let protocol_data = append_user_data(&encoder, &swap, vec![0x01, 0x02]).unwrap();
+
+        assert_eq!(protocol_data, vec![0x01, 0x02]);
+    }
 }
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 use alloy::{
     primitives::{Address, Bytes as AlloyBytes},
@@ -7,24 +10,50 @@ use alloy::{
 use serde::{Deserialize, Serialize};
 use tycho_common::{models::Chain, Bytes};
 
+#[cfg(feature = "failure-injection")]
+use crate::encoding::evm::failure_injection::{inject, InjectionPoint};
 use crate::encoding::{
     errors::EncodingError,
     evm::{
         constants::ANGSTROM_DEFAULT_BLOCKS_IN_FUTURE,
-        utils::{bytes_to_address, get_static_attribute, pad_or_truncate_to_size},
+        utils::{
+            bytes_to_address, get_static_attribute, pad_or_truncate_to_size, percentage_to_uint24,
+            ple_encode,
+        },
     },
     models::{EncodingContext, Swap},
-    swap_encoder::SwapEncoder,
+    swap_encoder::{EncoderCapabilities, SwapEncoder},
 };
 
+/// Length of the header of the protocol data of a group: token in, token out, zero for one,
+/// transfer type and receiver.
+const HEADER_LENGTH: usize = 62;
+/// Position of the zero for one byte in the header.
+const ZERO_FOR_ONE_OFFSET: usize = 40;
+/// Value of the zero for one byte of a group split over several branches.
+const SPLIT_BRANCHES: u8 = 2;
+
 /// Encodes a swap on a Uniswap V4 pool through the given executor address.
 ///
+/// Some hooks only allow specific senders to swap on their pools. Swaps on such pools are routed
+/// through a delegate executor allowlisted by the hook, set in the config:
+/// * `hook_delegate_executors` - JSON object of the delegate executor address of every gated hook
+///   address, e.g. `{"0xhook": "0xexecutor"}`.
+/// * `gated_hooks` - JSON array of other hook addresses known to only allow specific senders. Swaps
+///   on their pools are rejected until a delegate executor is configured, since they would revert.
+///
 /// # Fields
 /// * `executor_address` - The address of the executor contract that will perform the swap.
+/// * `angstrom_hook_address` - The address of the Angstrom hook, whose hook data is fetched from
+///   the Angstrom API.
+/// * `hook_delegate_executors` - The delegate executor of every gated hook with one.
+/// * `gated_hooks` - The hooks only allowing specific senders, with or without delegate executor.
 #[derive(Clone)]
 pub struct UniswapV4SwapEncoder {
     executor_address: Bytes,
     angstrom_hook_address: Bytes,
+    hook_delegate_executors: HashMap<Address, Bytes>,
+    gated_hooks: HashSet<Address>,
 }
 
 impl UniswapV4SwapEncoder {
@@ -32,8 +61,78 @@ impl UniswapV4SwapEncoder {
         sell_token_address < buy_token_address
     }
 
+    fn get_hook_address(swap: &Swap) -> Address {
+        match get_static_attribute(swap, "hooks") {
+            Ok(hook) => Address::from_slice(&hook),
+            Err(_) => Address::ZERO,
+        }
+    }
+
+    /// Returns the delegate executor of the hook, or `None` if the hook doesn't restrict its
+    /// senders.
+    ///
+    /// Returns an `EncodingError::InvalidInput` for a gated hook without delegate executor.
+    fn hook_executor(&self, hook_address: &Address) -> Result<Option<&Bytes>, EncodingError> {
+        if let Some(executor) = self
+            .hook_delegate_executors
+            .get(hook_address)
+        {
+            return Ok(Some(executor));
+        }
+        if self.gated_hooks.contains(hook_address) {
+            return Err(EncodingError::InvalidInput(format!(
+                "Uniswap V4 hook {hook_address} only allows specific senders and has no delegate \
+                 executor configured"
+            )));
+        }
+        Ok(None)
+    }
+
+    /// Parses the delegate executors and the gated hooks of the config.
+    fn parse_gated_hooks(
+        config: &HashMap<String, String>,
+    ) -> Result<(HashMap<Address, Bytes>, HashSet<Address>), EncodingError> {
+        let parse_address = |address: &str| {
+            Address::from_str(address).map_err(|_| {
+                EncodingError::FatalError(format!("Invalid Uniswap V4 hook address: {address}"))
+            })
+        };
+
+        let mut hook_delegate_executors = HashMap::new();
+        if let Some(delegates) = config.get("hook_delegate_executors") {
+            let delegates: HashMap<String, String> =
+                serde_json::from_str(delegates).map_err(|e| {
+                    EncodingError::FatalError(format!("Invalid hook delegate executors: {e}"))
+                })?;
+            for (hook, executor) in delegates {
+                let executor = Bytes::from_str(&executor).map_err(|_| {
+                    EncodingError::FatalError(format!(
+                        "Invalid delegate executor address of hook {hook}: {executor}"
+                    ))
+                })?;
+                hook_delegate_executors.insert(parse_address(&hook)?, executor);
+            }
+        }
+
+        let mut gated_hooks: HashSet<Address> = hook_delegate_executors
+            .keys()
+            .copied()
+            .collect();
+        if let Some(hooks) = config.get("gated_hooks") {
+            let hooks: Vec<String> = serde_json::from_str(hooks)
+                .map_err(|e| EncodingError::FatalError(format!("Invalid gated hooks: {e}")))?;
+            for hook in hooks {
+                gated_hooks.insert(parse_address(&hook)?);
+            }
+        }
+
+        Ok((hook_delegate_executors, gated_hooks))
+    }
+
     /// Fetches attestations from the Angstrom API (blocking)
     fn fetch_angstrom_attestations() -> Result<AttestationResponse, EncodingError> {
+        #[cfg(feature = "failure-injection")]
+        inject(InjectionPoint::AttestationFetch)?;
         let client = reqwest::blocking::Client::new();
 
         let api_url = std::env::var("ANGSTROM_API_URL")
@@ -123,18 +222,17 @@ impl SwapEncoder for UniswapV4SwapEncoder {
         _chain: Chain,
         config: Option<HashMap<String, String>>,
     ) -> Result<Self, EncodingError> {
-        let angstrom_hook_address = match config {
-            // Allow for no config, since Angstrom is not on every chain
-            None => Bytes::new(),
-            Some(cfg) => cfg
-                .get("angstrom_hook_address")
-                .map_or(Ok(Bytes::new()), |s| {
-                    Bytes::from_str(s).map_err(|_| {
-                        EncodingError::FatalError("Invalid Angstrom hook address".to_string())
-                    })
-                })?,
-        };
-        Ok(Self { executor_address, angstrom_hook_address })
+        // Allow for no config, since Angstrom is not on every chain
+        let config = config.unwrap_or_default();
+        let angstrom_hook_address = config
+            .get("angstrom_hook_address")
+            .map_or(Ok(Bytes::new()), |s| {
+                Bytes::from_str(s).map_err(|_| {
+                    EncodingError::FatalError("Invalid Angstrom hook address".to_string())
+                })
+            })?;
+        let (hook_delegate_executors, gated_hooks) = Self::parse_gated_hooks(&config)?;
+        Ok(Self { executor_address, angstrom_hook_address, hook_delegate_executors, gated_hooks })
     }
 
     fn encode_swap(
@@ -153,10 +251,9 @@ impl SwapEncoder for UniswapV4SwapEncoder {
             EncodingError::FatalError("Failed to pad tick spacing bytes".to_string())
         })?;
 
-        let hook_address = match get_static_attribute(swap, "hooks") {
-            Ok(hook) => Address::from_slice(&hook),
-            Err(_) => Address::ZERO,
-        };
+        let hook_address = Self::get_hook_address(swap);
+        // Fail early instead of encoding a swap that the hook would revert
+        self.hook_executor(&hook_address)?;
 
         let is_angstrom_hook = **hook_address == *self.angstrom_hook_address;
         let hook_data = if is_angstrom_hook {
@@ -217,10 +314,68 @@ impl SwapEncoder for UniswapV4SwapEncoder {
         Ok(args.abi_encode_packed())
     }
 
+    /// Keeps the header of the first branch, with its zero for one byte set to `SPLIT_BRANCHES`,
+    /// followed by the ple encoded branches: the split of each branch (as a `U24`) followed by its
+    /// pool params.
+    fn pack_split_branches(&self, branches: Vec<(f64, Vec<u8>)>) -> Result<Vec<u8>, EncodingError> {
+        let mut header = None;
+        let mut encoded_branches = Vec::with_capacity(branches.len());
+        for (split, protocol_data) in branches {
+            if protocol_data.len() < HEADER_LENGTH {
+                return Err(EncodingError::FatalError(
+                    "Invalid protocol data of a split branch".to_string(),
+                ));
+            }
+            let (branch_header, pool_params) = protocol_data.split_at(HEADER_LENGTH);
+            header.get_or_insert_with(|| branch_header.to_vec());
+            let mut encoded_branch = percentage_to_uint24(split).to_be_bytes_vec();
+            encoded_branch.extend_from_slice(pool_params);
+            encoded_branches.push(encoded_branch);
+        }
+        let mut encoded = header.ok_or_else(|| {
+            EncodingError::FatalError("No branches to pack into a split group".to_string())
+        })?;
+        encoded[ZERO_FOR_ONE_OFFSET] = SPLIT_BRANCHES;
+        encoded.extend(ple_encode(encoded_branches)?);
+        Ok(encoded)
+    }
+
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities {
+            groupable: true,
+            split_groupable: true,
+            callback_constrained: true,
+            ..Default::default()
+        }
+    }
+
     fn executor_address(&self) -> &Bytes {
         &self.executor_address
     }
 
+    /// Routes the group through the delegate executor of its gated hooks, if any. All the gated
+    /// hooks of the group must share the same delegate executor.
+    fn group_executor_address(&self, swaps: &[Swap]) -> Result<Bytes, EncodingError> {
+        let mut delegate: Option<&Bytes> = None;
+        for swap in swaps {
+            let Some(executor) = self.hook_executor(&Self::get_hook_address(swap))? else {
+                continue;
+            };
+            match delegate {
+                Some(delegate) if delegate != executor => {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Uniswap V4 swaps through hooks with different delegate executors \
+                         ({delegate} and {executor}) can't be grouped"
+                    )))
+                }
+                _ => delegate = Some(executor),
+            }
+        }
+        Ok(delegate
+            .unwrap_or(&self.executor_address)
+            .clone())
+    }
+
     fn clone_box(&self) -> Box<dyn SwapEncoder> {
         Box::new(self.clone())
     }
@@ -256,7 +411,7 @@ mod tests {
     use super::*;
     use crate::encoding::{
         evm::utils::{ple_encode, write_calldata_to_file},
-        models::{Swap, TransferType},
+        models::{ApprovalPolicy, Swap, TransferType},
     };
 
     #[test]
@@ -289,6 +444,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
         let encoder = UniswapV4SwapEncoder::new(
             Bytes::from("0xF62849F9A0B5Bf2913b396098F7c7019b51A820a"),
@@ -360,6 +517,8 @@ mod tests {
             group_token_out: token_out.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
 
         let encoder = UniswapV4SwapEncoder::new(
@@ -408,6 +567,8 @@ mod tests {
             group_token_out: wbtc_address.clone(),
             transfer_type: TransferType::Transfer,
             historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
         };
 
         // Setup - First sequence: USDE -> USDT
@@ -467,7 +628,7 @@ mod tests {
         let combined_hex = format!(
             "{}{}",
             encode(&initial_encoded_swap),
-            encode(ple_encode(vec![second_encoded_swap]))
+            encode(ple_encode(vec![second_encoded_swap]).unwrap())
         );
 
         assert_eq!(
@@ -512,6 +673,166 @@ mod tests {
         write_calldata_to_file("test_encode_uniswap_v4_sequential_swap", combined_hex.as_str());
     }
 
+    #[test]
+    fn test_pack_uniswap_v4_split_branches() {
+        // USDE ──(60%)──> USDT
+        //      └─(rest)──> USDT
+        let token_in = Bytes::from("0x4c9EDD5852cd905f086C759E8383e09bff1E68B3"); // USDE
+        let token_out = Bytes::from("0xdAC17F958D2ee523a2206206994597C13D831ec7"); // USDT
+
+        let mut static_attributes: HashMap<String, Bytes> = HashMap::new();
+        static_attributes
+            .insert("key_lp_fee".into(), Bytes::from(BigInt::from(100).to_signed_bytes_be()));
+        static_attributes
+            .insert("tick_spacing".into(), Bytes::from(BigInt::from(1).to_signed_bytes_be()));
+        let usv4_pool = ProtocolComponent {
+            id: String::from("0x000000000004444c5dc75cB358380D2e3dE08A90"),
+            static_attributes,
+            ..Default::default()
+        };
+        let swap = Swap::new(usv4_pool, token_in.clone(), token_out.clone());
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2"),
+            exact_out: false,
+            router_address: Some(Bytes::from("0x5615deb798bb3e4dfa0139dfa1b3d433cc23b72f")),
+            group_token_in: token_in,
+            group_token_out: token_out,
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let encoder = UniswapV4SwapEncoder::new(
+            Bytes::from("0xF62849F9A0B5Bf2913b396098F7c7019b51A820a"),
+            Chain::Ethereum,
+            None,
+        )
+        .unwrap();
+        let branch = encoder
+            .encode_swap(&swap, &encoding_context)
+            .unwrap();
+
+        let packed = encoder
+            .pack_split_branches(vec![(0.6, branch.clone()), (0f64, branch)])
+            .unwrap();
+
+        let pool_params = concat!(
+            // - intermediary token USDT
+            "dac17f958d2ee523a2206206994597c13d831ec7",
+            // - fee
+            "000064",
+            // - tick spacing
+            "000001",
+            // hook address (not set, so zero)
+            "0000000000000000000000000000000000000000",
+            // hook data length (0)
+            "0000"
+        );
+        let header = concat!(
+            // group token in
+            "4c9edd5852cd905f086c759e8383e09bff1e68b3",
+            // group token out
+            "dac17f958d2ee523a2206206994597c13d831ec7",
+            // split branches
+            "02",
+            // transfer type Transfer
+            "01",
+            // receiver
+            "cd09f75e2bf2a4d11f3ab23f1389fcc1621c0cc2",
+        );
+        assert_eq!(
+            encode(packed),
+            format!(
+                // Each branch: ple encoding, split (60% then the remainder) and pool params
+                "{header}0033999999{pool_params}0033000000{pool_params}"
+            )
+        );
+    }
+
+    #[test]
+    fn test_uniswap_v4_gated_hooks() {
+        let gated_hook = "0x1111111111111111111111111111111111111111";
+        let delegated_hook = "0x2222222222222222222222222222222222222222";
+        let other_delegated_hook = "0x3333333333333333333333333333333333333333";
+        let delegate = "0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF";
+        let other_delegate = "0xbaadf00dbaadf00dbaadf00dbaadf00dbaadf00d";
+
+        let token_in = Bytes::from("0x4c9EDD5852cd905f086C759E8383e09bff1E68B3"); // USDE
+        let token_out = Bytes::from("0xdAC17F958D2ee523a2206206994597C13D831ec7"); // USDT
+        let swap = |hook: &str| {
+            let mut static_attributes: HashMap<String, Bytes> = HashMap::new();
+            static_attributes
+                .insert("key_lp_fee".into(), Bytes::from(BigInt::from(100).to_signed_bytes_be()));
+            static_attributes
+                .insert("tick_spacing".into(), Bytes::from(BigInt::from(1).to_signed_bytes_be()));
+            static_attributes.insert("hooks".into(), Bytes::from(hook));
+            let usv4_pool = ProtocolComponent {
+                id: String::from("0x000000000004444c5dc75cB358380D2e3dE08A90"),
+                static_attributes,
+                ..Default::default()
+            };
+            Swap::new(usv4_pool, token_in.clone(), token_out.clone())
+        };
+        let encoding_context = EncodingContext {
+            receiver: Bytes::from("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2"),
+            exact_out: false,
+            router_address: Some(Bytes::from("0x5615deb798bb3e4dfa0139dfa1b3d433cc23b72f")),
+            group_token_in: token_in.clone(),
+            group_token_out: token_out.clone(),
+            transfer_type: TransferType::Transfer,
+            historical_trade: false,
+            execution_deadline: None,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let executor = Bytes::from("0xF62849F9A0B5Bf2913b396098F7c7019b51A820a");
+        let encoder = UniswapV4SwapEncoder::new(
+            executor.clone(),
+            Chain::Ethereum,
+            Some(HashMap::from([
+                (
+                    "hook_delegate_executors".to_string(),
+                    format!(
+                        r#"{{"{delegated_hook}": "{delegate}", "{other_delegated_hook}": "{other_delegate}"}}"#
+                    ),
+                ),
+                ("gated_hooks".to_string(), format!(r#"["{gated_hook}"]"#)),
+            ])),
+        )
+        .unwrap();
+
+        // Pools without gated hooks use the executor of the encoder
+        let ungated_swap = swap("0x0000000000000000000000000000000000000000");
+        assert_eq!(
+            encoder
+                .group_executor_address(std::slice::from_ref(&ungated_swap))
+                .unwrap(),
+            executor
+        );
+
+        // Groups with a delegated hook are routed through its delegate executor
+        let delegated_swap = swap(delegated_hook);
+        assert!(encoder
+            .encode_swap(&delegated_swap, &encoding_context)
+            .is_ok());
+        assert_eq!(
+            encoder
+                .group_executor_address(&[ungated_swap, delegated_swap.clone()])
+                .unwrap(),
+            Bytes::from(delegate)
+        );
+
+        // Gated hooks without delegate executor would revert
+        let result = encoder.encode_swap(&swap(gated_hook), &encoding_context);
+        assert!(matches!(
+            result,
+            Err(EncodingError::InvalidInput(msg)) if msg.contains("no delegate executor")
+        ));
+
+        // A group can only be routed through one delegate executor
+        let result = encoder.group_executor_address(&[delegated_swap, swap(other_delegated_hook)]);
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
     mod uniswap_v4_angstrom {
         use super::*;
         use crate::encoding::evm::{
@@ -584,6 +905,8 @@ mod tests {
                 group_token_out: usdt_address.clone(),
                 transfer_type: TransferType::Transfer,
                 historical_trade: false,
+                execution_deadline: None,
+                approval_policy: ApprovalPolicy::Live,
             };
 
             // Setup first pool: USDC -> WETH (use real tick spacing and fee from on-chain)
@@ -634,8 +957,11 @@ mod tests {
             let second_encoded = encoder
                 .encode_swap(&second_swap, &context)
                 .unwrap();
-            let combined_hex =
-                format!("{}{}", encode(&first_encoded), encode(ple_encode(vec![second_encoded])));
+            let combined_hex = format!(
+                "{}{}",
+                encode(&first_encoded),
+                encode(ple_encode(vec![second_encoded]).unwrap())
+            );
 
             write_calldata_to_file("test_encode_angstrom_grouped_swap", combined_hex.as_str());
             // Any different length could indicate we didn't encode attestation data
@@ -1,11 +1,14 @@
 // This module is used in integration tests as well
-use std::{any::Any, collections::HashMap};
+use std::{any::Any, collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
 use num_bigint::BigUint;
 use tycho_common::{
     dto::ProtocolStateDelta,
-    models::{protocol::GetAmountOutParams, token::Token},
+    models::{
+        protocol::{GetAmountOutParams, ProtocolComponent},
+        token::Token,
+    },
     simulation::{
         errors::{SimulationError, TransitionError},
         indicatively_priced::{IndicativelyPriced, SignedQuote},
@@ -14,6 +17,11 @@ use tycho_common::{
     Bytes,
 };
 
+use crate::encoding::{
+    errors::EncodingError,
+    models::{Solution, Swap},
+};
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct MockRFQState {
     pub quote_amount_out: BigUint,
@@ -91,3 +99,423 @@ impl IndicativelyPriced for MockRFQState {
         })
     }
 }
+
+/// The pricing function of a [`MockAmmState`].
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum MockAmmCurve {
+    /// Uniswap V2 style pool: `reserve_in * reserve_out` stays constant.
+    ConstantProduct,
+    /// Fixed exchange rate: `amount_out = amount_in * numerator / denominator`, for a swap from
+    /// the first to the second token of the pool (and the inverse rate the other way around),
+    /// limited by the reserves of the output token.
+    ConstantPrice { numerator: BigUint, denominator: BigUint },
+}
+
+/// A deterministic AMM state for offline tests.
+///
+/// All amounts are computed with integer math (rounding down), so the same inputs always yield
+/// the same amounts, and swapping never changes the state.
+///
+/// # Fields
+/// * `tokens`: The two tokens of the pool, in pool order.
+/// * `reserves`: Reserves of each token.
+/// * `fee_bps`: Fee (in basis points) taken from the amount in.
+/// * `curve`: Pricing function of the pool.
+/// * `gas`: Gas reported for every swap.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MockAmmState {
+    pub tokens: [Bytes; 2],
+    pub reserves: HashMap<Bytes, BigUint>,
+    pub fee_bps: u32,
+    pub curve: MockAmmCurve,
+    pub gas: BigUint,
+}
+
+impl MockAmmState {
+    /// Creates a constant product pool with the given reserves and no fee.
+    pub fn constant_product(
+        token_0: Bytes,
+        reserve_0: BigUint,
+        token_1: Bytes,
+        reserve_1: BigUint,
+    ) -> Self {
+        Self {
+            tokens: [token_0.clone(), token_1.clone()],
+            reserves: HashMap::from([(token_0, reserve_0), (token_1, reserve_1)]),
+            fee_bps: 0,
+            curve: MockAmmCurve::ConstantProduct,
+            gas: BigUint::from(100_000u32),
+        }
+    }
+
+    /// Creates a pool with a fixed exchange rate of `numerator / denominator` units of `token_1`
+    /// per unit of `token_0`, and no fee.
+    pub fn constant_price(
+        token_0: Bytes,
+        reserve_0: BigUint,
+        token_1: Bytes,
+        reserve_1: BigUint,
+        numerator: BigUint,
+        denominator: BigUint,
+    ) -> Self {
+        Self {
+            curve: MockAmmCurve::ConstantPrice { numerator, denominator },
+            ..Self::constant_product(token_0, reserve_0, token_1, reserve_1)
+        }
+    }
+
+    pub fn fee_bps(mut self, fee_bps: u32) -> Self {
+        self.fee_bps = fee_bps;
+        self
+    }
+
+    pub fn gas(mut self, gas: BigUint) -> Self {
+        self.gas = gas;
+        self
+    }
+
+    fn reserve(&self, token: &Bytes) -> Result<&BigUint, SimulationError> {
+        self.reserves.get(token).ok_or_else(|| {
+            SimulationError::InvalidInput(format!("Token {token} is not part of the pool"), None)
+        })
+    }
+
+    /// Computes the amount out of a swap of `amount_in` from `token_in` to `token_out`.
+    pub fn amount_out(
+        &self,
+        amount_in: &BigUint,
+        token_in: &Bytes,
+        token_out: &Bytes,
+    ) -> Result<BigUint, SimulationError> {
+        let reserve_in = self.reserve(token_in)?;
+        let reserve_out = self.reserve(token_out)?;
+        if token_in == token_out {
+            return Err(SimulationError::InvalidInput(
+                "Token in and token out must be different".to_string(),
+                None,
+            ));
+        }
+        if self.fee_bps > 10_000 {
+            return Err(SimulationError::FatalError(format!("Invalid fee of {} bps", self.fee_bps)));
+        }
+        let amount_in_after_fee = amount_in * BigUint::from(10_000 - self.fee_bps) / 10_000u32;
+
+        let amount_out = match &self.curve {
+            MockAmmCurve::ConstantProduct => {
+                let denominator = reserve_in + &amount_in_after_fee;
+                if denominator == BigUint::ZERO {
+                    BigUint::ZERO
+                } else {
+                    &amount_in_after_fee * reserve_out / denominator
+                }
+            }
+            MockAmmCurve::ConstantPrice { numerator, denominator } => {
+                let (numerator, denominator) = if *token_in == self.tokens[0] {
+                    (numerator, denominator)
+                } else {
+                    (denominator, numerator)
+                };
+                if *denominator == BigUint::ZERO {
+                    return Err(SimulationError::FatalError(
+                        "Invalid exchange rate with a zero denominator".to_string(),
+                    ));
+                }
+                &amount_in_after_fee * numerator / denominator
+            }
+        };
+
+        if amount_out > *reserve_out {
+            return Err(SimulationError::InvalidInput(
+                format!("Not enough {token_out} reserves for an amount out of {amount_out}"),
+                None,
+            ));
+        }
+        Ok(amount_out)
+    }
+}
+
+#[typetag::serde]
+impl ProtocolSim for MockAmmState {
+    fn fee(&self) -> f64 {
+        self.fee_bps as f64 / 10_000.0
+    }
+
+    fn spot_price(&self, base: &Token, quote: &Token) -> Result<f64, SimulationError> {
+        let reserve_base = self.reserve(&base.address)?;
+        let reserve_quote = self.reserve(&quote.address)?;
+        let to_f64 = |amount: &BigUint| {
+            amount
+                .to_string()
+                .parse::<f64>()
+                .unwrap_or_default()
+        };
+        let ratio =
+            |numerator: &BigUint, denominator: &BigUint| to_f64(numerator) / to_f64(denominator);
+        match &self.curve {
+            MockAmmCurve::ConstantProduct => Ok(ratio(reserve_quote, reserve_base)),
+            MockAmmCurve::ConstantPrice { numerator, denominator } => {
+                if base.address == self.tokens[0] {
+                    Ok(ratio(numerator, denominator))
+                } else {
+                    Ok(ratio(denominator, numerator))
+                }
+            }
+        }
+    }
+
+    fn get_amount_out(
+        &self,
+        amount_in: BigUint,
+        token_in: &Token,
+        token_out: &Token,
+    ) -> Result<GetAmountOutResult, SimulationError> {
+        let amount_out = self.amount_out(&amount_in, &token_in.address, &token_out.address)?;
+        Ok(GetAmountOutResult::new(amount_out, self.gas.clone(), self.clone_box()))
+    }
+
+    fn get_limits(
+        &self,
+        sell_token: Bytes,
+        buy_token: Bytes,
+    ) -> Result<(BigUint, BigUint), SimulationError> {
+        Ok((self.reserve(&sell_token)?.clone(), self.reserve(&buy_token)?.clone()))
+    }
+
+    fn delta_transition(
+        &mut self,
+        _delta: ProtocolStateDelta,
+        _tokens: &HashMap<Bytes, Token>,
+        _balances: &Balances,
+    ) -> Result<(), TransitionError> {
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn ProtocolSim> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn eq(&self, other: &dyn ProtocolSim) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<MockAmmState>()
+            .is_some_and(|other| self == other)
+    }
+}
+
+/// Builds sequential `Solution`s whose swaps carry [`MockAmmState`]s, for fully offline
+/// end-to-end tests of encoding pipelines.
+///
+/// The amount of every hop is computed with its mock state, and the checked amount of the solution
+/// is the final amount minus the configured slippage.
+///
+/// ```ignore
+/// let solution = ScenarioBuilder::new(weth(), BigUint::from(10u32).pow(18))
+///     .hop(uniswap_v2_component("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11"), dai(), weth_dai_state)
+///     .slippage_bps(50)
+///     .build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScenarioBuilder {
+    given_token: Bytes,
+    given_amount: BigUint,
+    sender: Bytes,
+    receiver: Bytes,
+    slippage_bps: u32,
+    hops: Vec<(ProtocolComponent, Bytes, MockAmmState)>,
+}
+
+impl ScenarioBuilder {
+    pub fn new(given_token: Bytes, given_amount: BigUint) -> Self {
+        Self {
+            given_token,
+            given_amount,
+            sender: Bytes::zero(20),
+            receiver: Bytes::zero(20),
+            slippage_bps: 0,
+            hops: Vec::new(),
+        }
+    }
+
+    pub fn sender(mut self, sender: Bytes) -> Self {
+        self.sender = sender;
+        self
+    }
+
+    pub fn receiver(mut self, receiver: Bytes) -> Self {
+        self.receiver = receiver;
+        self
+    }
+
+    /// Sets the slippage (in basis points) subtracted from the final amount to get the checked
+    /// amount of the solution.
+    pub fn slippage_bps(mut self, slippage_bps: u32) -> Self {
+        self.slippage_bps = slippage_bps;
+        self
+    }
+
+    /// Adds a swap from the output token of the previous hop (or the given token for the first
+    /// hop) to `token_out`, through the given component and priced with `state`.
+    pub fn hop(
+        mut self,
+        component: ProtocolComponent,
+        token_out: Bytes,
+        state: MockAmmState,
+    ) -> Self {
+        self.hops
+            .push((component, token_out, state));
+        self
+    }
+
+    /// Computes the amounts of all the hops and builds the solution.
+    pub fn build(self) -> Result<Solution, EncodingError> {
+        if self.hops.is_empty() {
+            return Err(EncodingError::InvalidInput(
+                "A scenario needs at least one hop".to_string(),
+            ));
+        }
+        if self.slippage_bps > 10_000 {
+            return Err(EncodingError::InvalidInput(format!(
+                "Invalid slippage of {} bps",
+                self.slippage_bps
+            )));
+        }
+
+        let mut token_in = self.given_token.clone();
+        let mut amount_in = self.given_amount.clone();
+        let mut swaps = Vec::with_capacity(self.hops.len());
+        for (i, (component, token_out, state)) in self.hops.into_iter().enumerate() {
+            let amount_out = state
+                .amount_out(&amount_in, &token_in, &token_out)
+                .map_err(|err| {
+                    EncodingError::InvalidInput(format!("Failed to simulate hop {i}: {err}"))
+                })?;
+            swaps.push(
+                Swap::new(component, token_in, token_out.clone())
+                    .estimated_amount_in(amount_in)
+                    .protocol_state(Arc::new(state)),
+            );
+            token_in = token_out;
+            amount_in = amount_out;
+        }
+
+        let checked_amount = &amount_in * BigUint::from(10_000 - self.slippage_bps) / 10_000u32;
+        Ok(Solution {
+            given_token: self.given_token,
+            given_amount: self.given_amount,
+            checked_token: token_in,
+            checked_amount,
+            sender: self.sender,
+            receiver: self.receiver,
+            swaps,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weth() -> Bytes {
+        Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+    }
+
+    fn dai() -> Bytes {
+        Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f")
+    }
+
+    fn usdc() -> Bytes {
+        Bytes::from("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+    }
+
+    #[test]
+    fn test_mock_amm_amount_out() {
+        let state = MockAmmState::constant_product(
+            weth(),
+            BigUint::from(1_000u32),
+            dai(),
+            BigUint::from(3_000_000u32),
+        )
+        .fee_bps(30);
+        // 100 * 0.997 = 99.7 -> 99, 99 * 3_000_000 / 1_099 = 270245
+        assert_eq!(
+            state
+                .amount_out(&BigUint::from(100u32), &weth(), &dai())
+                .unwrap(),
+            BigUint::from(270_245u32)
+        );
+
+        let state = MockAmmState::constant_price(
+            dai(),
+            BigUint::from(1_000_000u32),
+            usdc(),
+            BigUint::from(1_000u32),
+            BigUint::from(1u32),
+            BigUint::from(1_000u32),
+        );
+        assert_eq!(
+            state
+                .amount_out(&BigUint::from(5_000u32), &dai(), &usdc())
+                .unwrap(),
+            BigUint::from(5u32)
+        );
+        assert_eq!(
+            state
+                .amount_out(&BigUint::from(5u32), &usdc(), &dai())
+                .unwrap(),
+            BigUint::from(5_000u32)
+        );
+        // Not enough USDC reserves
+        assert!(state
+            .amount_out(&BigUint::from(2_000_000u32), &dai(), &usdc())
+            .is_err());
+    }
+
+    #[test]
+    fn test_scenario_builder() {
+        let component = |id: &str| ProtocolComponent {
+            id: id.to_string(),
+            protocol_system: "uniswap_v2".to_string(),
+            ..Default::default()
+        };
+        let weth_dai = MockAmmState::constant_product(
+            weth(),
+            BigUint::from(1_000u32),
+            dai(),
+            BigUint::from(3_000_000u32),
+        );
+        let dai_usdc = MockAmmState::constant_price(
+            dai(),
+            BigUint::from(3_000_000u32),
+            usdc(),
+            BigUint::from(3_000_000u32),
+            BigUint::from(1u32),
+            BigUint::from(1u32),
+        );
+
+        let solution = ScenarioBuilder::new(weth(), BigUint::from(1_000u32))
+            .hop(component("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11"), dai(), weth_dai)
+            .hop(component("0xAE461cA67B15dc8dc81CE7615e0320dA1A9aB8D5"), usdc(), dai_usdc)
+            .slippage_bps(100)
+            .build()
+            .unwrap();
+
+        assert_eq!(solution.checked_token, usdc());
+        // 1_000 * 3_000_000 / 2_000 = 1_500_000, minus 1% slippage
+        assert_eq!(solution.checked_amount, BigUint::from(1_485_000u32));
+        assert_eq!(solution.swaps.len(), 2);
+        assert_eq!(*solution.swaps[1].token_in(), dai());
+        assert_eq!(*solution.swaps[1].get_estimated_amount_in(), Some(BigUint::from(1_500_000u32)));
+        assert!(solution.swaps[1]
+            .get_protocol_state()
+            .is_some());
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::HashSet;
+
+use alloy::{
+    primitives::{Address, Bytes as AlloyBytes, TxKind, U256},
+    providers::Provider,
+    rpc::types::{BlockId, TransactionInput, TransactionRequest},
+    sol_types::SolValue,
+};
+use tokio::task::block_in_place;
+use tycho_common::{models::Chain, Bytes};
+
+use crate::encoding::{
+    errors::{EncodingError, UnsafeTokenReport},
+    evm::{
+        encoding_utils::encode_input,
+        strategy_encoder::wrap_adapter::ChainTokens,
+        utils::{bytes_to_address, get_client, get_runtime},
+    },
+    models::Solution,
+};
+
+/// Rejects solutions involving tokens that can't be transferred, before they are encoded.
+///
+/// Paused tokens (e.g. bridged tokens paused after an exploit) and blacklisted addresses (e.g. the
+/// USDT and USDC blacklists) otherwise only make the router revert late, after paying for the
+/// swaps before them. The checker rejects the solutions with an `EncodingError::UnsafeToken`:
+/// * If any token of the solution is in its denylist.
+/// * If transfer simulation is enabled, if a zero amount `transfer` of any token of the solution
+///   reverts (or returns `false`) when called with an `eth_call`: the given tokens are transferred
+///   from the sender to the router, the checked token from the router to the receiver and the
+///   intermediary tokens from the router to itself. Pause and blacklist checks don't depend on the
+///   amount, while the router holds no balance to transfer.
+///
+/// Transfer simulation requires the `RPC_URL` environment variable to be set (see
+/// `RpcConfig::from_env`).
+///
+/// # Fields
+/// * `chain`: Chain of the solutions
+/// * `denylist`: Tokens rejected without simulation
+/// * `simulate_transfers`: Whether the transfers of the tokens are simulated
+/// * `block`: Optional block to simulate the transfers at. Defaults to the latest block.
+#[derive(Clone, Debug)]
+pub struct TokenSafetyChecker {
+    chain: Chain,
+    denylist: HashSet<Bytes>,
+    simulate_transfers: bool,
+    block: Option<BlockId>,
+}
+
+/// A transfer of a token simulated by the `TokenSafetyChecker`.
+struct TokenTransfer {
+    token: Bytes,
+    from: Address,
+    to: Address,
+}
+
+impl TokenSafetyChecker {
+    pub fn new(chain: Chain) -> Self {
+        Self { chain, denylist: HashSet::new(), simulate_transfers: false, block: None }
+    }
+
+    /// Rejects the solutions involving any of the given tokens.
+    pub fn with_denylist(mut self, tokens: impl IntoIterator<Item = Bytes>) -> Self {
+        self.denylist.extend(tokens);
+        self
+    }
+
+    /// Simulates a transfer of every token of the solutions, rejecting the tokens whose transfer
+    /// reverts.
+    pub fn with_transfer_simulation(mut self) -> Self {
+        self.simulate_transfers = true;
+        self
+    }
+
+    /// Simulates the transfers at the given block instead of the latest one.
+    pub fn with_block_number(mut self, block_number: u64) -> Self {
+        self.block = Some(BlockId::number(block_number));
+        self
+    }
+
+    /// Raises an `EncodingError::UnsafeToken` if any token of the solution is denylisted or can't
+    /// be transferred, and an `EncodingError::RecoverableError` if a simulation request fails.
+    pub fn validate(
+        &self,
+        solution: &Solution,
+        router_address: &Bytes,
+    ) -> Result<(), EncodingError> {
+        let transfers = self.validate_denylist(solution, router_address)?;
+        if !self.simulate_transfers {
+            return Ok(());
+        }
+        let (handle, _runtime) = get_runtime()?;
+        block_in_place(|| handle.block_on(self.simulate_transfers(transfers)))
+    }
+
+    /// Validates the solution like `validate`, awaiting the simulation requests.
+    pub async fn validate_async(
+        &self,
+        solution: &Solution,
+        router_address: &Bytes,
+    ) -> Result<(), EncodingError> {
+        let transfers = self.validate_denylist(solution, router_address)?;
+        if !self.simulate_transfers {
+            return Ok(());
+        }
+        self.simulate_transfers(transfers).await
+    }
+
+    /// Checks the tokens of the solution against the denylist, and returns the transfers to
+    /// simulate for its ERC20 tokens.
+    fn validate_denylist(
+        &self,
+        solution: &Solution,
+        router_address: &Bytes,
+    ) -> Result<Vec<TokenTransfer>, EncodingError> {
+        let router = bytes_to_address(router_address)?;
+        let mut given_tokens = vec![&solution.given_token];
+        given_tokens.extend(
+            solution
+                .additional_given_tokens
+                .iter()
+                .map(|given_token| &given_token.token),
+        );
+
+        let mut candidates = Vec::new();
+        for token in &given_tokens {
+            candidates.push((*token, bytes_to_address(&solution.sender)?, router));
+        }
+        candidates.push((&solution.checked_token, router, bytes_to_address(&solution.receiver)?));
+        for swap in &solution.swaps {
+            for token in [swap.token_in(), swap.token_out()] {
+                candidates.push((token, router, router));
+            }
+        }
+
+        let native = ChainTokens::for_chain(self.chain)?.native;
+        let mut seen = HashSet::new();
+        let mut transfers = Vec::new();
+        for (token, from, to) in candidates {
+            if self.denylist.contains(token) {
+                return Err(unsafe_token(token, "The token is denylisted"));
+            }
+            if *token != native && seen.insert(token) {
+                transfers.push(TokenTransfer { token: token.clone(), from, to });
+            }
+        }
+        Ok(transfers)
+    }
+
+    async fn simulate_transfers(&self, transfers: Vec<TokenTransfer>) -> Result<(), EncodingError> {
+        if transfers.is_empty() {
+            return Ok(());
+        }
+        let client = get_client().await?;
+        for transfer in transfers {
+            let request = TransactionRequest {
+                from: Some(transfer.from),
+                to: Some(TxKind::from(bytes_to_address(&transfer.token)?)),
+                input: TransactionInput {
+                    input: Some(AlloyBytes::from(encode_input(
+                        "transfer(address,uint256)",
+                        (transfer.to, U256::ZERO).abi_encode(),
+                    ))),
+                    data: None,
+                },
+                ..Default::default()
+            };
+            let mut call = client.call(request);
+            if let Some(block) = self.block {
+                call = call.block(block);
+            }
+            match call.await {
+                Ok(response) => check_transfer_result(&transfer.token, &response)?,
+                Err(err) => match err.as_error_resp() {
+                    Some(payload) => {
+                        return Err(unsafe_token(
+                            &transfer.token,
+                            &format!(
+                                "The transfer from {} to {} reverted: {}",
+                                transfer.from, transfer.to, payload.message
+                            ),
+                        ))
+                    }
+                    None => {
+                        return Err(EncodingError::RecoverableError(format!(
+                            "Failed to simulate a transfer of {}: {err}",
+                            transfer.token
+                        )))
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tokens returning nothing from `transfer` (e.g. USDT) succeed if they don't revert, while the
+/// others must return `true`.
+fn check_transfer_result(token: &Bytes, response: &[u8]) -> Result<(), EncodingError> {
+    if response.is_empty() {
+        return Ok(());
+    }
+    match bool::abi_decode(response) {
+        Ok(true) => Ok(()),
+        _ => Err(unsafe_token(token, "The transfer returned false")),
+    }
+}
+
+fn unsafe_token(token: &Bytes, reason: &str) -> EncodingError {
+    EncodingError::UnsafeToken(Box::new(UnsafeTokenReport {
+        token: token.clone(),
+        reason: reason.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+    use tycho_common::models::protocol::ProtocolComponent;
+
+    use super::*;
+    use crate::encoding::models::Swap;
+
+    fn weth() -> Bytes {
+        Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")
+    }
+
+    fn usdt() -> Bytes {
+        Bytes::from("0xdac17f958d2ee523a2206206994597c13d831ec7")
+    }
+
+    fn router() -> Bytes {
+        Bytes::from("0xfD0b31d2E955fA55e3fa641Fe90e08b677188d35")
+    }
+
+    fn solution() -> Solution {
+        Solution {
+            sender: Bytes::from("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2"),
+            receiver: Bytes::from("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2"),
+            given_token: weth(),
+            given_amount: BigUint::from(1_000_000_000_000_000_000u64),
+            checked_token: usdt(),
+            checked_amount: BigUint::from(2_990_000_000u64),
+            swaps: vec![Swap::new(
+                ProtocolComponent {
+                    id: "0x0d4a11d5EEaaC28EC3F61d100daF4d40471f1852".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                usdt(),
+            )],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_denylist() {
+        let checker = TokenSafetyChecker::new(Chain::Ethereum).with_denylist([usdt()]);
+
+        let result = checker.validate(&solution(), &router());
+
+        let Err(EncodingError::UnsafeToken(report)) = result else {
+            panic!("Expected an unsafe token error, got {result:?}");
+        };
+        assert_eq!(report.token, usdt());
+        assert!(TokenSafetyChecker::new(Chain::Ethereum)
+            .with_denylist([Bytes::from("0x6b175474e89094c44da98b954eedeac495271d0f")])
+            .validate(&solution(), &router())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_transfer_result() {
+        // USDT doesn't return anything
+        assert!(check_transfer_result(&usdt(), &[]).is_ok());
+        assert!(check_transfer_result(&weth(), &true.abi_encode()).is_ok());
+        assert!(matches!(
+            check_transfer_result(&weth(), &false.abi_encode()),
+            Err(EncodingError::UnsafeToken(_))
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeSet;
+
+use alloy::{
+    primitives::{Address, Bytes as AlloyBytes, TxKind},
+    rpc::types::{AccessList, AccessListItem, TransactionInput, TransactionRequest},
+};
+use tycho_common::models::Chain;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        strategy_encoder::wrap_adapter::ChainTokens,
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+        utils::{biguint_to_u256, bytes_to_address},
+    },
+    models::{Solution, Transaction, TransactionOptions},
+};
+
+/// Type of the EIP-1559 transactions.
+const EIP1559_TRANSACTION_TYPE: u8 = 2;
+
+/// Builds the transaction request submitting the given transaction of the solution, sent by the
+/// solution's sender.
+///
+/// The gas fields and nonce are taken from the options. If the options ask for an access list, it
+/// holds the executors, pools and tokens the solution's swaps interact with (see `access_list`).
+///
+/// Returns an `EncodingError::InvalidInput` if the priority fee exceeds the max fee, or if the
+/// sender or the transaction's target is not a valid address.
+pub fn build_transaction_request(
+    chain: Chain,
+    swap_encoder_registry: &SwapEncoderRegistry,
+    solution: &Solution,
+    transaction: &Transaction,
+    options: &TransactionOptions,
+) -> Result<TransactionRequest, EncodingError> {
+    if let (Some(max_fee), Some(priority_fee)) =
+        (options.max_fee_per_gas, options.max_priority_fee_per_gas)
+    {
+        if priority_fee > max_fee {
+            return Err(EncodingError::InvalidInput(format!(
+                "The max priority fee per gas ({priority_fee}) exceeds the max fee per gas \
+                 ({max_fee})"
+            )));
+        }
+    }
+    let eip1559 = options.max_fee_per_gas.is_some() ||
+        options
+            .max_priority_fee_per_gas
+            .is_some();
+    let from = bytes_to_address(&solution.sender)?;
+    let to = bytes_to_address(&transaction.to)?;
+    let access_list = options
+        .access_list
+        .then(|| access_list(chain, swap_encoder_registry, solution, &[from, to]))
+        .transpose()?;
+    Ok(TransactionRequest {
+        from: Some(from),
+        to: Some(TxKind::from(to)),
+        value: Some(biguint_to_u256(&transaction.value)),
+        input: TransactionInput {
+            input: Some(AlloyBytes::from(transaction.data.clone())),
+            data: None,
+        },
+        chain_id: Some(chain.id()),
+        nonce: options.nonce,
+        gas: options.gas_limit,
+        max_fee_per_gas: options.max_fee_per_gas,
+        max_priority_fee_per_gas: options.max_priority_fee_per_gas,
+        transaction_type: eip1559.then_some(EIP1559_TRANSACTION_TYPE),
+        access_list,
+        ..Default::default()
+    })
+}
+
+/// Returns the access list of the solution: the executors of its protocols, its pools whose
+/// component id is an address and the tokens it swaps (except the native token), sorted by
+/// address.
+///
+/// The given addresses (the sender and the router) are left out, as they are warm from the start
+/// of the transaction anyway. No storage keys are listed, as the slots accessed by the protocols
+/// are not known.
+fn access_list(
+    chain: Chain,
+    swap_encoder_registry: &SwapEncoderRegistry,
+    solution: &Solution,
+    warm_addresses: &[Address],
+) -> Result<AccessList, EncodingError> {
+    let native_address = ChainTokens::for_chain(chain)?.native;
+    let mut addresses = BTreeSet::new();
+    for swap in &solution.swaps {
+        if let Some(encoder) = swap_encoder_registry.get_encoder(&swap.component().protocol_system)
+        {
+            let executor = encoder
+                .group_executor_address(std::slice::from_ref(swap))
+                .unwrap_or_else(|_| encoder.executor_address().clone());
+            addresses.extend(bytes_to_address(&executor).ok());
+        }
+        if let Ok(pool) = swap.component().id.parse::<Address>() {
+            addresses.insert(pool);
+        }
+        for token in [swap.token_in(), swap.token_out()] {
+            if *token != native_address {
+                addresses.extend(bytes_to_address(token).ok());
+            }
+        }
+    }
+    Ok(AccessList(
+        addresses
+            .into_iter()
+            .filter(|address| !warm_addresses.contains(address))
+            .map(|address| AccessListItem { address, storage_keys: vec![] })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, str::FromStr};
+
+    use alloy::primitives::U256;
+    use num_bigint::BigUint;
+    use tycho_common::{models::protocol::ProtocolComponent, Bytes};
+
+    use super::*;
+    use crate::encoding::models::Swap;
+
+    fn registry() -> SwapEncoderRegistry {
+        let executors_addresses =
+            fs::read_to_string("config/test_executor_addresses.json").unwrap();
+        SwapEncoderRegistry::new(Chain::Ethereum)
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap()
+    }
+
+    fn solution() -> Solution {
+        // WETH ──(USV2)──> DAI ──(USV4)──> ETH
+        let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        let dai = Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap();
+        let eth = Chain::Ethereum.native_token().address;
+        let swap_weth_dai = Swap::new(
+            ProtocolComponent {
+                id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                protocol_system: "uniswap_v2".to_string(),
+                ..Default::default()
+            },
+            weth.clone(),
+            dai.clone(),
+        );
+        let swap_dai_eth = Swap::new(
+            ProtocolComponent {
+                id: "0xdce6394339af00981949f5f3baf27e3610c76326a700af57e4b3e3ae4977f78d"
+                    .to_string(),
+                protocol_system: "uniswap_v4".to_string(),
+                ..Default::default()
+            },
+            dai,
+            eth.clone(),
+        );
+        Solution {
+            given_token: weth,
+            given_amount: BigUint::from(1000u64),
+            checked_token: eth,
+            sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+            receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+            swaps: vec![swap_weth_dai, swap_dai_eth],
+            ..Default::default()
+        }
+    }
+
+    fn transaction() -> Transaction {
+        Transaction {
+            to: Bytes::from_str("0x3Ede3eCa2a72B3aeCC820E955B36f38437D01395").unwrap(),
+            value: BigUint::from(5u64),
+            data: vec![0xde, 0xad],
+        }
+    }
+
+    #[test]
+    fn test_build_transaction_request() {
+        let options = TransactionOptions {
+            max_fee_per_gas: Some(30_000_000_000),
+            max_priority_fee_per_gas: Some(1_000_000_000),
+            nonce: Some(7),
+            gas_limit: Some(300_000),
+            access_list: true,
+        };
+
+        let request = build_transaction_request(
+            Chain::Ethereum,
+            &registry(),
+            &solution(),
+            &transaction(),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(
+            request.to,
+            Some(TxKind::from(
+                Address::from_str("0x3Ede3eCa2a72B3aeCC820E955B36f38437D01395").unwrap()
+            ))
+        );
+        assert_eq!(request.value, Some(U256::from(5)));
+        assert_eq!(request.input.input, Some(AlloyBytes::from(vec![0xde, 0xad])));
+        assert_eq!(request.chain_id, Some(1));
+        assert_eq!(request.nonce, Some(7));
+        assert_eq!(request.gas, Some(300_000));
+        assert_eq!(request.max_fee_per_gas, Some(30_000_000_000));
+        assert_eq!(request.max_priority_fee_per_gas, Some(1_000_000_000));
+        assert_eq!(request.transaction_type, Some(2));
+        // The executors, the USV2 pool, DAI and WETH, but not the USV4 pool id nor the native
+        // token
+        let addresses: Vec<Address> = request
+            .access_list
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|item| item.address)
+            .collect();
+        let expected: BTreeSet<Address> = [
+            "0x5615deb798bb3e4dfa0139dfa1b3d433cc23b72f",
+            "0xF62849F9A0B5Bf2913b396098F7c7019b51A820a",
+            "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11",
+            "0x6b175474e89094c44da98b954eedeac495271d0f",
+            "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+        ]
+        .into_iter()
+        .map(|address| Address::from_str(address).unwrap())
+        .collect();
+        assert_eq!(addresses, expected.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_build_transaction_request_without_options() {
+        let request = build_transaction_request(
+            Chain::Ethereum,
+            &registry(),
+            &solution(),
+            &transaction(),
+            &TransactionOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(request.nonce, None);
+        assert_eq!(request.gas, None);
+        assert_eq!(request.transaction_type, None);
+        assert_eq!(request.access_list, None);
+    }
+
+    #[test]
+    fn test_build_transaction_request_priority_fee_above_max_fee() {
+        let options = TransactionOptions {
+            max_fee_per_gas: Some(1_000_000_000),
+            max_priority_fee_per_gas: Some(2_000_000_000),
+            ..Default::default()
+        };
+
+        let result = build_transaction_request(
+            Chain::Ethereum,
+            &registry(),
+            &solution(),
+            &transaction(),
+            &options,
+        );
+
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+}
@@ -1,50 +1,132 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
-use alloy::signers::local::PrivateKeySigner;
+use alloy::{rpc::types::TransactionRequest, signers::local::PrivateKeySigner};
+use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use num_bigint::BigUint;
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
     evm::{
-        approvals::permit2::Permit2,
-        constants::{FUNDS_IN_ROUTER_PROTOCOLS, GROUPABLE_PROTOCOLS},
-        encoding_utils::encode_tycho_router_call,
+        approvals::{
+            permit2::Permit2,
+            permit_signer::{DetachedSignature, PermitSigner},
+        },
+        audit::AuditLog,
+        callback_allowlist::CallbackAllowlist,
+        calldata_budget::CalldataBudget,
+        checked_amount_guard::CheckedAmountGuard,
+        constants::{
+            BRIDGE_PROTOCOLS, EXACT_OUT_PROTOCOLS, NATIVE_KEYED_PROTOCOLS,
+            PROTOCOL_SPECIFIC_CONFIG, RFQ_PROTOCOLS, WITHDRAWAL_REQUEST_PROTOCOLS,
+        },
+        encoding_utils::{encode_tycho_router_call, permit_batch_typed_data, permit_typed_data},
         group_swaps::group_swaps,
-        strategy_encoder::strategy_encoders::{
-            SequentialSwapStrategyEncoder, SingleSwapStrategyEncoder, SplitSwapStrategyEncoder,
+        idempotency::IdempotencyCache,
+        rebasing::{
+            checked_amount_for_shares, is_rebasing_token, ShareRate, REBASING_TRANSFER_ROUNDING,
+        },
+        staleness::StalenessGuard,
+        strategy_encoder::{
+            strategy_encoders::{
+                MultiInputSplitSwapStrategyEncoder, SequentialSwapStrategyEncoder,
+                SingleSwapStrategyEncoder, SplitSwapStrategyEncoder,
+            },
+            wrap_adapter::{ChainTokens, WrapVariant},
+        },
+        support_matrix::{ProtocolInfo, SupportMatrix},
+        swap_encoder::{
+            curve::takes_raw_native_token,
+            swap_encoder_registry::{SharedSwapEncoderRegistry, SwapEncoderRegistry},
         },
-        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
-        utils::ple_encode,
+        token_safety::TokenSafetyChecker,
+        transaction::build_transaction_request,
+        utils::{append_user_data, ple_decode, ple_encode, summarize_encoded_swaps},
+    },
+    metrics::{
+        measure, measure_async, with_metrics, with_metrics_async, EncodingMetrics, EncodingStage,
     },
     models::{
-        EncodedSolution, EncodingContext, NativeAction, Solution, Transaction, TransferType,
+        ApprovalPolicy, EncodedSolution, EncodedSwaps, EncodingContext, Environment, NativeAction,
+        SlippageConfig, Solution, Swap, Transaction, TransactionOptions, TransferType,
         UserTransferType,
     },
     strategy_encoder::StrategyEncoder,
-    tycho_encoder::TychoEncoder,
+    tycho_encoder::{EncodingResultStream, TychoEncoder},
 };
 
 /// Encodes solutions to be used by the TychoRouter.
 ///
 /// # Fields
 /// * `chain`: Chain to be used
+/// * `chain_tokens`: Native and wrapped native tokens of the chain
 /// * `single_swap_strategy`: Encoder for single swaps
 /// * `sequential_swap_strategy`: Encoder for sequential swaps
 /// * `split_swap_strategy`: Encoder for split swaps
+/// * `multi_input_split_swap_strategy`: Encoder for split swaps with several given tokens
+/// * `swap_encoder_registry`: Registry of the swap encoders of the protocols deployed on the chain
 /// * `router_address`: Address of the Tycho router contract
 /// * `user_transfer_type`: Type of user transfer
 /// * `permit2`: Optional Permit2 instance for permit transfers
 /// * `signer`: Optional signer (used only for permit2 and full calldata encoding)
+/// * `audit_log`: Optional audit log that records every encoded solution
+/// * `idempotency_cache`: Optional cache of encoded solutions indexed by their idempotency key
+/// * `simulation_encoder`: Optional encoder for the simulation environment (with its own router and
+///   executors)
+/// * `permit_signer`: Optional signer of the permits, taking precedence over `signer` (e.g. an
+///   `Erc1271Signer` for smart contract wallet senders)
+/// * `cyclic_profit_check`: Whether cyclic solutions (given token equal to the checked token) must
+///   check an amount above the given amount, so the router reverts unprofitable arbitrages
+/// * `calldata_budget`: Optional maximum size of the encoded calldata. Defaults to the practical
+///   limit of the chain.
+/// * `default_slippage`: Optional slippage deriving the checked amount of solutions with an
+///   expected amount but no slippage of their own
+/// * `support_matrix`: Optional matrix of the protocols supported on the chain, rejecting the
+///   solutions its router can't execute
+/// * `metrics`: Optional callback receiving the timings and failure reasons of the encoding stages
+/// * `shared_registry`: Optional shared registry the swap encoders are taken from, so that
+///   executors can be reloaded at runtime (see `with_shared_registry`)
+/// * `native_action_inference`: Whether a wrap (or unwrap) is inserted for solutions giving (or
+///   checking) the native token whose first (or last) swaps take (or output) the wrapped token
+/// * `checked_amount_guard`: Optional guard rejecting the checked amounts off by orders of
+///   magnitude from the expected amount of the solution. Enabled by default.
+/// * `token_safety_checker`: Optional checker rejecting the solutions involving tokens that can't
+///   be transferred (e.g. paused or blacklisted), before they are encoded
 #[derive(Clone)]
 pub struct TychoRouterEncoder {
     chain: Chain,
+    chain_tokens: ChainTokens,
     single_swap_strategy: SingleSwapStrategyEncoder,
     sequential_swap_strategy: SequentialSwapStrategyEncoder,
     split_swap_strategy: SplitSwapStrategyEncoder,
+    multi_input_split_swap_strategy: MultiInputSplitSwapStrategyEncoder,
+    swap_encoder_registry: SwapEncoderRegistry,
     router_address: Bytes,
     user_transfer_type: UserTransferType,
     permit2: Option<Permit2>,
     signer: Option<PrivateKeySigner>,
+    audit_log: Option<AuditLog>,
+    idempotency_cache: Option<Arc<IdempotencyCache>>,
+    simulation_encoder: Option<Box<TychoRouterEncoder>>,
+    staleness_guard: Option<StalenessGuard>,
+    curve_native_token_address: Option<Bytes>,
+    callback_allowlist: Option<CallbackAllowlist>,
+    permit_signer: Option<Arc<dyn PermitSigner>>,
+    cyclic_profit_check: bool,
+    calldata_budget: Option<CalldataBudget>,
+    default_slippage: Option<SlippageConfig>,
+    support_matrix: Option<SupportMatrix>,
+    metrics: Option<Arc<dyn EncodingMetrics>>,
+    shared_registry: Option<SharedSwapEncoderRegistry>,
+    native_action_inference: bool,
+    checked_amount_guard: Option<CheckedAmountGuard>,
+    token_safety_checker: Option<TokenSafetyChecker>,
 }
 
 impl TychoRouterEncoder {
@@ -61,6 +143,46 @@ impl TychoRouterEncoder {
         } else {
             None
         };
+        Self::new_with_permit2(
+            chain,
+            swap_encoder_registry,
+            router_address,
+            user_transfer_type,
+            signer,
+            historical_trade,
+            permit2,
+        )
+    }
+
+    /// Creates the encoder like `new`, with an existing `Permit2` client (required for permit2
+    /// transfers) instead of connecting a new one.
+    pub(crate) fn new_with_permit2(
+        chain: Chain,
+        swap_encoder_registry: SwapEncoderRegistry,
+        router_address: Bytes,
+        user_transfer_type: UserTransferType,
+        signer: Option<PrivateKeySigner>,
+        historical_trade: bool,
+        permit2: Option<Permit2>,
+    ) -> Result<Self, EncodingError> {
+        if user_transfer_type == UserTransferType::TransferFromPermit2 && permit2.is_none() {
+            return Err(EncodingError::FatalError(
+                "A Permit2 client is required for permit2 transfers".to_string(),
+            ));
+        }
+        let protocol_specific_config: HashMap<Chain, HashMap<String, HashMap<String, String>>> =
+            serde_json::from_str(PROTOCOL_SPECIFIC_CONFIG)?;
+        let curve_native_token_address = protocol_specific_config
+            .get(&chain)
+            .and_then(|config| config.get("vm:curve"))
+            .and_then(|config| config.get("native_token_address"))
+            .map(|address| {
+                Bytes::from_str(address).map_err(|_| {
+                    EncodingError::FatalError("Invalid native token curve address".to_string())
+                })
+            })
+            .transpose()?;
+        let chain_tokens = ChainTokens::for_chain(chain)?;
         Ok(TychoRouterEncoder {
             single_swap_strategy: SingleSwapStrategyEncoder::new(
                 chain,
@@ -78,1002 +200,4346 @@ impl TychoRouterEncoder {
             )?,
             split_swap_strategy: SplitSwapStrategyEncoder::new(
                 chain,
-                swap_encoder_registry,
+                swap_encoder_registry.clone(),
+                user_transfer_type.clone(),
+                router_address.clone(),
+                historical_trade,
+            )?,
+            multi_input_split_swap_strategy: MultiInputSplitSwapStrategyEncoder::new(
+                chain,
+                swap_encoder_registry.clone(),
                 user_transfer_type.clone(),
                 router_address.clone(),
                 historical_trade,
             )?,
+            swap_encoder_registry,
             router_address,
             permit2,
             signer,
             chain,
+            chain_tokens,
             user_transfer_type,
+            audit_log: None,
+            idempotency_cache: None,
+            simulation_encoder: None,
+            staleness_guard: None,
+            curve_native_token_address,
+            callback_allowlist: None,
+            permit_signer: None,
+            cyclic_profit_check: false,
+            calldata_budget: CalldataBudget::for_chain(chain),
+            default_slippage: None,
+            support_matrix: None,
+            metrics: None,
+            shared_registry: None,
+            native_action_inference: true,
+            checked_amount_guard: Some(CheckedAmountGuard::default()),
+            token_safety_checker: None,
         })
     }
 
-    fn encode_solution(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
-        self.validate_solution(solution)?;
-        let protocols: HashSet<String> = solution
-            .swaps
-            .iter()
-            .map(|swap| swap.component().protocol_system.clone())
-            .collect();
-
-        let mut encoded_solution = if (solution.swaps.len() == 1) ||
-            ((protocols.len() == 1 &&
-                protocols
-                    .iter()
-                    .any(|p| GROUPABLE_PROTOCOLS.contains(&p.as_str()))) &&
-                solution
-                    .swaps
-                    .iter()
-                    .all(|swap| swap.get_split() == 0.0))
-        {
-            self.single_swap_strategy
-                .encode_strategy(solution)?
-        } else if solution
-            .swaps
-            .iter()
-            .all(|swap| swap.get_split() == 0.0)
-        {
-            self.sequential_swap_strategy
-                .encode_strategy(solution)?
-        } else {
-            self.split_swap_strategy
-                .encode_strategy(solution)?
-        };
+    /// Takes the swap encoders of all the strategies from the given shared registry, so that the
+    /// executors reloaded into it (see `SharedSwapEncoderRegistry::reload`) are used by the
+    /// following encodings without rebuilding the encoder.
+    ///
+    /// The callback allowlist and support matrix are not updated on reload.
+    pub fn with_shared_registry(mut self, shared_registry: SharedSwapEncoderRegistry) -> Self {
+        self.single_swap_strategy = self
+            .single_swap_strategy
+            .with_shared_registry(shared_registry.clone());
+        self.sequential_swap_strategy = self
+            .sequential_swap_strategy
+            .with_shared_registry(shared_registry.clone());
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_shared_registry(shared_registry.clone());
+        self.multi_input_split_swap_strategy = self
+            .multi_input_split_swap_strategy
+            .with_shared_registry(shared_registry.clone());
+        self.swap_encoder_registry = shared_registry.current();
+        self.shared_registry = Some(shared_registry);
+        self
+    }
 
-        if let Some(permit2) = &self.permit2 {
-            let permit = permit2.get_permit(
-                &self.router_address,
-                &solution.sender,
-                &solution.given_token,
-                &solution.given_amount,
-            )?;
-            encoded_solution.permit = Some(permit);
+    /// Returns the current registry of the shared registry if set, else the encoder's registry.
+    fn current_registry(&self) -> SwapEncoderRegistry {
+        match &self.shared_registry {
+            Some(shared_registry) => shared_registry.current(),
+            None => self.swap_encoder_registry.clone(),
         }
-        Ok(encoded_solution)
     }
-}
 
-impl TychoEncoder for TychoRouterEncoder {
-    fn encode_solutions(
+    /// Encodes the executors of sequential and split solutions in a shared table, with the swaps
+    /// referring to them by a 1-byte index instead of repeating their address. The solutions are
+    /// then encoded for the `Indexed` variants of the router functions.
+    pub fn with_executor_table(mut self) -> Self {
+        self.sequential_swap_strategy = self
+            .sequential_swap_strategy
+            .with_executor_table();
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_executor_table();
+        self
+    }
+
+    /// Encodes the token indices of split solutions on 2 bytes, so that they can have up to 65536
+    /// distinct tokens instead of 256 (see `SplitSwapStrategyEncoder::with_wide_token_indices`).
+    ///
+    /// Returns an error if the encoder uses an executor table or precise splits.
+    pub fn with_wide_split_indices(mut self) -> Result<Self, EncodingError> {
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_wide_token_indices()?;
+        Ok(self)
+    }
+
+    /// Encodes the splits of split solutions on 4 bytes with a 1e9 basis instead of 3 bytes, so
+    /// that they are rounded to 1e-9 of the amount (see
+    /// `SplitSwapStrategyEncoder::with_precise_splits`).
+    ///
+    /// Returns an error if the encoder uses an executor table or wide split indices.
+    pub fn with_precise_splits(mut self) -> Result<Self, EncodingError> {
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_precise_splits()?;
+        Ok(self)
+    }
+
+    /// Groups the Uniswap V4 branches splitting a token into the same output token into one swap
+    /// execution (see `SplitSwapStrategyEncoder::with_split_branch_groups`).
+    pub fn with_split_branch_groups(mut self) -> Self {
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_split_branch_groups();
+        self
+    }
+
+    /// Encodes the swaps of the groups failing to encode as one swap execution as individual swaps
+    /// in the sequential and split strategies, reporting the degraded groups in the encoded
+    /// solutions instead of failing them (see
+    /// `SequentialSwapStrategyEncoder::with_ungrouped_fallback`).
+    pub fn with_ungrouped_fallback(mut self) -> Self {
+        self.sequential_swap_strategy = self
+            .sequential_swap_strategy
+            .with_ungrouped_fallback();
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_ungrouped_fallback();
+        self
+    }
+
+    /// Normalizes the split percentages of split solutions within the given epsilon before
+    /// validating them (see `SplitSwapStrategyEncoder::with_split_normalization`).
+    pub fn with_split_normalization(mut self, epsilon: f64) -> Self {
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_split_normalization(epsilon);
+        self
+    }
+
+    /// Sets how the router wraps and unwraps the native token in all the strategies, instead of the
+    /// variant configured for the chain (see `WrapVariant::for_chain`).
+    pub fn with_wrap_variant(mut self, wrap_variant: WrapVariant) -> Self {
+        self.single_swap_strategy = self
+            .single_swap_strategy
+            .with_wrap_variant(wrap_variant);
+        self.sequential_swap_strategy = self
+            .sequential_swap_strategy
+            .with_wrap_variant(wrap_variant);
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_wrap_variant(wrap_variant);
+        self.multi_input_split_swap_strategy = self
+            .multi_input_split_swap_strategy
+            .with_wrap_variant(wrap_variant);
+        self
+    }
+
+    /// Sets how the swap encoders of all the strategies decide whether the router approves the
+    /// protocols, instead of looking its allowances up (see `ApprovalPolicy`). A fixed policy makes
+    /// the encoded calldata independent of the chain state.
+    pub fn with_approval_policy(mut self, approval_policy: ApprovalPolicy) -> Self {
+        self.single_swap_strategy = self
+            .single_swap_strategy
+            .with_approval_policy(approval_policy);
+        self.sequential_swap_strategy = self
+            .sequential_swap_strategy
+            .with_approval_policy(approval_policy);
+        self.split_swap_strategy = self
+            .split_swap_strategy
+            .with_approval_policy(approval_policy);
+        self.multi_input_split_swap_strategy = self
+            .multi_input_split_swap_strategy
+            .with_approval_policy(approval_policy);
+        self
+    }
+
+    /// Sets the metrics receiving the timings and failure reasons of the encoding stages of this
+    /// encoder, down to the RFQ quotes and approval checks of its swap encoders.
+    pub fn with_encoding_metrics(mut self, metrics: Arc<dyn EncodingMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Sets an audit log that records every solution encoded by this encoder.
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Sets a cache used to deduplicate solutions retried with the same idempotency key.
+    pub fn with_idempotency_cache(mut self, idempotency_cache: Arc<IdempotencyCache>) -> Self {
+        self.idempotency_cache = Some(idempotency_cache);
+        self
+    }
+
+    /// Sets the encoder used for solutions encoded for the `Simulation` environment.
+    pub fn with_simulation_encoder(mut self, simulation_encoder: TychoRouterEncoder) -> Self {
+        self.simulation_encoder = Some(Box::new(simulation_encoder));
+        self
+    }
+
+    /// Sets a guard that rejects solutions whose swaps were derived from outdated states.
+    pub fn with_staleness_guard(mut self, staleness_guard: StalenessGuard) -> Self {
+        self.staleness_guard = Some(staleness_guard);
+        self
+    }
+
+    /// Sets the collector of the callback allowlist attached to every encoded solution, for
+    /// off-chain checks only: the router doesn't enforce it (see `CallbackAllowlist`).
+    pub fn with_callback_allowlist(mut self, callback_allowlist: CallbackAllowlist) -> Self {
+        self.callback_allowlist = Some(callback_allowlist);
+        self
+    }
+
+    /// Sets the signer of the permits used when encoding full calldata, instead of the private key
+    /// signer.
+    pub fn with_permit_signer(mut self, permit_signer: Arc<dyn PermitSigner>) -> Self {
+        self.permit_signer = Some(permit_signer);
+        self
+    }
+
+    /// Disables the inference of the native action of solutions without one: a solution giving
+    /// the native token to swaps taking the wrapped token is then rejected instead of wrapped
+    /// (and symmetrically for unwraps). RFQ swaps and pools of protocols that also have
+    /// native-keyed pools still get their wrap or unwrap (see `resolve_rfq_native_boundaries`).
+    pub fn without_native_action_inference(mut self) -> Self {
+        self.native_action_inference = false;
+        self
+    }
+
+    /// Sets the `checked_amount_guard` rejecting the checked amounts off by more than its max
+    /// factor from the expected amount of the solution, instead of the default guard.
+    pub fn with_checked_amount_guard(mut self, checked_amount_guard: CheckedAmountGuard) -> Self {
+        self.checked_amount_guard = Some(checked_amount_guard);
+        self
+    }
+
+    /// Disables the checked amount guard, e.g. for solvers checking the magnitude of their amounts
+    /// themselves.
+    pub fn without_checked_amount_guard(mut self) -> Self {
+        self.checked_amount_guard = None;
+        self
+    }
+
+    /// Sets a `token_safety_checker` rejecting the solutions involving denylisted tokens or tokens
+    /// whose transfers revert, with an `EncodingError::UnsafeToken`.
+    pub fn with_token_safety_checker(mut self, token_safety_checker: TokenSafetyChecker) -> Self {
+        self.token_safety_checker = Some(token_safety_checker);
+        self
+    }
+
+    /// Requires cyclic solutions to check an amount above their given amount. The router's min
+    /// amount out check then doubles as a profit check.
+    pub fn with_cyclic_profit_check(mut self) -> Self {
+        self.cyclic_profit_check = true;
+        self
+    }
+
+    /// Sets the maximum size of the calldata encoded by `encode_full_calldata`, instead of the
+    /// default budget of the chain.
+    pub fn with_calldata_budget(mut self, calldata_budget: CalldataBudget) -> Self {
+        self.calldata_budget = Some(calldata_budget);
+        self
+    }
+
+    /// Sets the slippage deriving the checked amount of solutions that have an expected amount but
+    /// no slippage of their own.
+    pub fn with_default_slippage(mut self, default_slippage: SlippageConfig) -> Self {
+        self.default_slippage = Some(default_slippage);
+        self
+    }
+
+    /// Sets the `support_matrix` validating every solution against the protocols deployed on the
+    /// chain and the capabilities of their executors.
+    pub fn with_support_matrix(mut self, support_matrix: SupportMatrix) -> Self {
+        self.support_matrix = Some(support_matrix);
+        self
+    }
+
+    /// Returns the EIP-712 typed data of the permit of the encoded solution (single or batch), or
+    /// `None` if it has no permit.
+    ///
+    /// Meant for permits signed outside of this crate (e.g. by a KMS or a custody provider): the
+    /// typed data is signed by the owner of the tokens and the calldata assembled with the
+    /// signature (see `encode_calldata_with_signature`).
+    pub fn permit_typed_data(
         &self,
-        solutions: Vec<Solution>,
-    ) -> Result<Vec<EncodedSolution>, EncodingError> {
-        let mut result: Vec<EncodedSolution> = Vec::new();
-        for solution in solutions.iter() {
-            let encoded_solution = self.encode_solution(solution)?;
-            result.push(encoded_solution);
+        encoded_solution: &EncodedSolution,
+    ) -> Result<Option<serde_json::Value>, EncodingError> {
+        match (&encoded_solution.permit, &encoded_solution.permit_batch) {
+            (Some(permit), _) => Ok(Some(permit_typed_data(self.chain.id(), permit)?)),
+            (None, Some(permit_batch)) => {
+                Ok(Some(permit_batch_typed_data(self.chain.id(), permit_batch)?))
+            }
+            (None, None) => Ok(None),
         }
-        Ok(result)
     }
 
-    fn encode_full_calldata(
+    /// Encodes the router call of an encoded solution like `encode_full_calldata`, with the
+    /// signature of its permit produced from `permit_typed_data` instead of the encoder's signer.
+    pub fn encode_calldata_with_signature(
         &self,
-        solutions: Vec<Solution>,
-    ) -> Result<Vec<Transaction>, EncodingError> {
-        let mut transactions: Vec<Transaction> = Vec::new();
-        for solution in solutions.iter() {
-            let encoded_solution = self.encode_solution(solution)?;
-
-            let transaction = encode_tycho_router_call(
-                self.chain.id(),
-                encoded_solution,
-                solution,
-                &self.user_transfer_type,
-                &self.chain.native_token().address,
-                self.signer.clone(),
-            )?;
+        solution: &Solution,
+        encoded_solution: EncodedSolution,
+        signature: Vec<u8>,
+    ) -> Result<Transaction, EncodingError> {
+        self.assemble_router_call(
+            solution,
+            encoded_solution,
+            Some(&DetachedSignature::new(signature)),
+        )
+    }
 
-            transactions.push(transaction);
+    /// Encodes the router call of an encoded solution, with the permit signed by the given
+    /// signer.
+    fn assemble_router_call(
+        &self,
+        solution: &Solution,
+        encoded_solution: EncodedSolution,
+        permit_signer: Option<&dyn PermitSigner>,
+    ) -> Result<Transaction, EncodingError> {
+        let solution = self.resolve_receiver_override(&self.resolve_checked_amount(solution)?)?;
+        let solution = &self.resolve_bridge_settlement(&solution)?;
+
+        let transaction = encode_tycho_router_call(
+            self.chain.id(),
+            encoded_solution.clone(),
+            solution,
+            &self.user_transfer_type,
+            &self.chain_tokens.native,
+            permit_signer,
+        )?;
+        if let Some(calldata_budget) = &self.calldata_budget {
+            calldata_budget.check(&encoded_solution, transaction.data.len())?;
         }
-        Ok(transactions)
+        Ok(transaction)
     }
 
-    /// Raises an `EncodingError` if the solution is not considered valid.
+    /// Returns the protocols the encoder can encode swaps for, with their executor and
+    /// capabilities, sorted by protocol system.
     ///
-    /// A solution is considered valid if all the following conditions are met:
-    /// * The solution is not exact out.
-    /// * The solution has at least one swap.
-    /// * If the solution is wrapping, the given token is the chain's native token and the first
-    ///   swap's input is the chain's wrapped token.
-    /// * If the solution is unwrapping, the checked token is the chain's native token and the last
-    ///   swap's output is the chain's wrapped token.
-    /// * The token cannot appear more than once in the solution unless it is the first and last
-    ///   token (i.e. a true cyclical swap).
-    fn validate_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
-        if solution.exact_out {
-            return Err(EncodingError::FatalError(
-                "Currently only exact input solutions are supported".to_string(),
-            ));
+    /// Meant to filter the indexer components to the executable ones before building solutions.
+    /// The capabilities are those of the support matrix if set, else of the default config.
+    pub fn supported_protocols(&self) -> Vec<ProtocolInfo> {
+        match &self.support_matrix {
+            Some(support_matrix) => support_matrix.protocols(),
+            None => SupportMatrix::registry_protocols(&self.current_registry()),
         }
-        if solution.swaps.is_empty() {
-            return Err(EncodingError::FatalError("No swaps found in solution".to_string()));
+    }
+
+    /// Builds the transaction request submitting the router call of the solution, with the gas
+    /// fields, nonce and access list of the given options (see `build_transaction_request`).
+    pub fn build_transaction_request(
+        &self,
+        solution: &Solution,
+        transaction: &Transaction,
+        options: &TransactionOptions,
+    ) -> Result<TransactionRequest, EncodingError> {
+        build_transaction_request(
+            self.chain,
+            &self.current_registry(),
+            solution,
+            transaction,
+            options,
+        )
+    }
+
+    /// Encodes the swaps of the solution with the strategy the router would use, without wrapping
+    /// them in a router function call.
+    ///
+    /// Meant for callers building their own call around the swaps (e.g. another contract calling
+    /// the executors): no permit is requested and neither the idempotency cache nor the audit log
+    /// are used. Returns the encoded swaps, the encoded swap of every swap group and its executor.
+    pub fn encode_swaps_only(&self, solution: &Solution) -> Result<EncodedSwaps, EncodingError> {
+        let solution = &self.resolve_native_handling(solution);
+        self.validate_solution(solution)?;
+
+        let strategy = self.strategy_encoder(solution);
+        let encoded_solution = strategy.encode_strategy(solution)?;
+        let segments = if strategy.name() == self.single_swap_strategy.name() {
+            vec![encoded_solution.swaps.clone()]
+        } else {
+            let mut encoded_swaps = encoded_solution.swaps.as_slice();
+            // Indexed swaps are preceded by the executor table: its length and the executors
+            if encoded_solution
+                .function_signature
+                .contains("Indexed(")
+            {
+                let n_executors = *encoded_swaps.first().ok_or_else(|| {
+                    EncodingError::FatalError("The executor table is missing".to_string())
+                })? as usize;
+                encoded_swaps = encoded_swaps
+                    .get(1 + 20 * n_executors..)
+                    .ok_or_else(|| {
+                        EncodingError::FatalError("The executor table is truncated".to_string())
+                    })?;
+            }
+            ple_decode(encoded_swaps)?
+        };
+        let executors = encoded_solution
+            .debug_info
+            .iter()
+            .flat_map(|debug_info| debug_info.swap_groups.iter())
+            .map(|group| group.executor.clone())
+            .collect();
+        Ok(EncodedSwaps { swaps: encoded_solution.swaps, segments, executors })
+    }
+
+    /// Raises an `EncodingError` if the exact out solution is not considered valid.
+    ///
+    /// An exact out solution buys `given_amount` of the given token, selling at most
+    /// `checked_amount` of the checked token. It is considered valid if all the following
+    /// conditions are met:
+    /// * The solution has exactly one swap, on a protocol supporting exact out swaps (e.g. Uniswap
+    ///   V2 or V3).
+    /// * The swap sells the checked token and buys the given token (or the wrapped token if the
+    ///   solution is unwrapping).
+    /// * The solution doesn't wrap, as the router would keep the unused native token.
+    /// * If a staleness guard is set, the swap was not derived from an outdated state.
+    fn validate_exact_out_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
+        let [swap] = solution.swaps.as_slice() else {
+            return Err(EncodingError::InvalidInput(format!(
+                "Exact out solutions must have exactly one swap. Found {}",
+                solution.swaps.len()
+            )));
+        };
+        let protocol_system = &swap.component().protocol_system;
+        if !EXACT_OUT_PROTOCOLS.contains(protocol_system.as_str()) {
+            return Err(EncodingError::InvalidInput(format!(
+                "Exact out swaps are not supported for protocol {protocol_system}"
+            )));
         }
-        let native_address = self.chain.native_token().address;
-        let wrapped_address = self
-            .chain
-            .wrapped_native_token()
-            .address;
-        if let Some(native_action) = &solution.native_action {
-            if native_action == &NativeAction::Wrap {
-                if solution.given_token != native_address {
-                    return Err(EncodingError::FatalError(
-                        "Native token must be the input token in order to wrap".to_string(),
-                    ));
-                }
-                if let Some(first_swap) = solution.swaps.first() {
-                    if *first_swap.token_in() != wrapped_address {
-                        return Err(EncodingError::FatalError(
-                            "Wrapped token must be the first swap's input in order to wrap"
-                                .to_string(),
-                        ));
-                    }
-                }
-            } else if native_action == &NativeAction::Unwrap {
-                if solution.checked_token != native_address {
+        let bought_token = match solution.native_action {
+            Some(NativeAction::Wrap) => {
+                return Err(EncodingError::InvalidInput(
+                    "Exact out solutions do not support wrapping the native token".to_string(),
+                ));
+            }
+            Some(NativeAction::Unwrap) => {
+                if solution.given_token != self.chain_tokens.native {
                     return Err(EncodingError::FatalError(
                         "Native token must be the output token in order to unwrap".to_string(),
                     ));
                 }
-                if let Some(last_swap) = solution.swaps.last() {
-                    if *last_swap.token_out() != wrapped_address {
-                        return Err(EncodingError::FatalError(
-                            "Wrapped token must be the last swap's output in order to unwrap"
-                                .to_string(),
-                        ));
-                    }
-                }
+                self.chain_tokens.wrapped.clone()
             }
+            None => solution.given_token.clone(),
+        };
+        if *swap.token_in() != solution.checked_token || *swap.token_out() != bought_token {
+            return Err(EncodingError::InvalidInput(
+                "The swap of an exact out solution must sell the checked token and buy the given \
+                 token"
+                    .to_string(),
+            ));
+        }
+        if let Some(staleness_guard) = &self.staleness_guard {
+            staleness_guard.validate(solution)?;
         }
+        Ok(())
+    }
 
-        let mut solution_tokens = vec![];
-        let mut split_tokens_already_considered = HashSet::new();
-        for (i, swap) in solution.swaps.iter().enumerate() {
-            // so we don't count the split tokens more than once
-            if swap.get_split() != 0.0 {
-                if !split_tokens_already_considered.contains(swap.token_in()) {
-                    solution_tokens.push(swap.token_in());
-                    split_tokens_already_considered.insert(swap.token_in());
-                }
-            } else {
-                // it might be the last swap of the split or a regular swap
-                if !split_tokens_already_considered.contains(swap.token_in()) {
-                    solution_tokens.push(swap.token_in());
-                }
-            }
-            if i == solution.swaps.len() - 1 {
-                solution_tokens.push(swap.token_out());
-            }
+    /// Encodes the solution, or returns the cached encoded solution if it was already encoded with
+    /// the same idempotency key.
+    fn encode_solution_deduplicated(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        match &self.idempotency_cache {
+            Some(cache) => cache.get_or_encode(solution, |solution| self.encode_solution(solution)),
+            None => self.encode_solution(solution),
         }
+    }
 
-        if solution_tokens.len() !=
-            solution_tokens
-                .iter()
-                .cloned()
-                .collect::<HashSet<&Bytes>>()
-                .len()
-        {
-            if let Some(last_swap) = solution.swaps.last() {
-                if *solution.swaps[0].token_in() != *last_swap.token_out() {
-                    return Err(EncodingError::FatalError(
-                        "Cyclical swaps are only allowed if they are the first and last token of a solution".to_string(),
-                    ));
-                } else {
-                    // it is a valid cyclical swap
-                    // we don't support any wrapping or unwrapping in this case
-                    if let Some(_native_action) = &solution.native_action {
-                        return Err(EncodingError::FatalError(
-                            "Wrapping/Unwrapping is not available in cyclical swaps".to_string(),
-                        ));
-                    }
-                }
+    /// Encodes the solution like `encode_solution_deduplicated`, awaiting the encoding on a cache
+    /// miss.
+    async fn encode_solution_deduplicated_async(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        match &self.idempotency_cache {
+            Some(cache) => {
+                cache
+                    .get_or_encode_async(solution, self.encode_solution_async(solution))
+                    .await
+            }
+            None => {
+                self.encode_solution_async(solution)
+                    .await
             }
         }
-        Ok(())
     }
-}
 
-/// Represents an encoder for one swap to be executed directly against an Executor.
-///
-/// This is useful when you want to bypass the Tycho Router, use your own Router contract and
-/// just need the calldata for a particular swap.
-///
-/// # Fields
-/// * `swap_encoder_registry`: Registry of swap encoders
-#[derive(Clone)]
-pub struct TychoExecutorEncoder {
-    swap_encoder_registry: SwapEncoderRegistry,
-}
+    /// Aligns the native and wrapped tokens of RFQ swaps with the rest of the solution.
+    ///
+    /// RFQ quotes can be requested for the native token as well as for the wrapped one, so instead
+    /// of failing on a mismatch:
+    /// * If the solution has no native action, gives the native token and all its first swaps take
+    ///   the wrapped token, a wrap step is inserted. Symmetrically, an unwrap step is inserted if
+    ///   the solution checks the native token and all its last swaps output the wrapped token. If
+    ///   native action inference is disabled, this only applies if one of these swaps is an RFQ
+    ///   swap or a wrapped-keyed pool of a protocol that also has native-keyed pools, like Uniswap
+    ///   V4.
+    /// * Otherwise, the RFQ swap is quoted for the token that is actually available (input) or
+    ///   required (output) at its position in the solution.
+    fn resolve_rfq_native_boundaries(&self, solution: &Solution) -> Solution {
+        let ChainTokens { native, wrapped } = self.chain_tokens.clone();
+        let is_native_or_wrapped = |token: &Bytes| *token == native || *token == wrapped;
+        let is_rfq = |swap: &Swap| {
+            RFQ_PROTOCOLS.contains(
+                swap.component()
+                    .protocol_system
+                    .as_str(),
+            )
+        };
+        let infers_native_action = |swap: &Swap| {
+            self.native_action_inference ||
+                is_rfq(swap) ||
+                NATIVE_KEYED_PROTOCOLS.contains(
+                    swap.component()
+                        .protocol_system
+                        .as_str(),
+                )
+        };
 
-impl TychoExecutorEncoder {
-    pub fn new(swap_encoder_registry: SwapEncoderRegistry) -> Result<Self, EncodingError> {
-        Ok(TychoExecutorEncoder { swap_encoder_registry })
-    }
+        let mut solution = solution.clone();
+        let n_swaps = solution.swaps.len();
+        // RFQ swaps without an earlier swap producing the native/wrapped token consume the given
+        // token. RFQ swaps without a later swap consuming it produce the checked token.
+        let first_swaps: Vec<usize> = (0..n_swaps)
+            .filter(|&i| {
+                is_native_or_wrapped(solution.swaps[i].token_in()) &&
+                    !solution.swaps[..i]
+                        .iter()
+                        .any(|swap| is_native_or_wrapped(swap.token_out()))
+            })
+            .collect();
+        let last_swaps: Vec<usize> = (0..n_swaps)
+            .filter(|&i| {
+                is_native_or_wrapped(solution.swaps[i].token_out()) &&
+                    !solution.swaps[i + 1..]
+                        .iter()
+                        .any(|swap| is_native_or_wrapped(swap.token_in()))
+            })
+            .collect();
 
-    fn encode_executor_calldata(
-        &self,
-        solution: &Solution,
-    ) -> Result<EncodedSolution, EncodingError> {
-        let grouped_swaps = group_swaps(&solution.swaps);
-        let number_of_groups = grouped_swaps.len();
-        if number_of_groups > 1 {
-            return Err(EncodingError::InvalidInput(format!(
-                "Tycho executor encoder only supports one swap. Found {number_of_groups}"
-            )))
+        if solution.native_action.is_none() && solution.given_token == native {
+            let needs_wrap = !first_swaps.is_empty() &&
+                first_swaps
+                    .iter()
+                    .all(|&i| *solution.swaps[i].token_in() == wrapped) &&
+                first_swaps
+                    .iter()
+                    .any(|&i| infers_native_action(&solution.swaps[i]));
+            if needs_wrap {
+                solution.native_action = Some(NativeAction::Wrap);
+            }
+        }
+        if solution.native_action.is_none() && solution.checked_token == native {
+            let needs_unwrap = !last_swaps.is_empty() &&
+                last_swaps
+                    .iter()
+                    .all(|&i| *solution.swaps[i].token_out() == wrapped) &&
+                last_swaps
+                    .iter()
+                    .any(|&i| infers_native_action(&solution.swaps[i]));
+            if needs_unwrap {
+                solution.native_action = Some(NativeAction::Unwrap);
+            }
         }
 
-        let grouped_swap = grouped_swaps
-            .first()
-            .ok_or_else(|| EncodingError::FatalError("Swap grouping failed".to_string()))?;
-
-        let swap_encoder = self
-            .swap_encoder_registry
-            .get_encoder(&grouped_swap.protocol_system)
-            .ok_or_else(|| {
-                EncodingError::InvalidInput(format!(
-                    "Swap encoder not found for protocol: {}",
-                    grouped_swap.protocol_system
-                ))
-            })?;
-
-        let transfer = if !FUNDS_IN_ROUTER_PROTOCOLS.contains(
-            &grouped_swap.swaps[0]
-                .component()
-                .protocol_system
-                .as_str(),
-        ) {
-            TransferType::Transfer
+        let available_token = if solution.native_action == Some(NativeAction::Wrap) {
+            wrapped.clone()
         } else {
-            TransferType::None
+            solution.given_token.clone()
         };
-        let encoding_context = EncodingContext {
-            receiver: solution.receiver.clone(),
-            exact_out: solution.exact_out,
-            router_address: None,
-            group_token_in: grouped_swap.token_in.clone(),
-            group_token_out: grouped_swap.token_out.clone(),
-            transfer_type: transfer,
-            historical_trade: false,
+        let required_token = if solution.native_action == Some(NativeAction::Unwrap) {
+            wrapped.clone()
+        } else {
+            solution.checked_token.clone()
         };
-        let mut grouped_protocol_data: Vec<Vec<u8>> = vec![];
-        let mut initial_protocol_data: Vec<u8> = vec![];
-        for swap in grouped_swap.swaps.iter() {
-            let protocol_data = swap_encoder.encode_swap(swap, &encoding_context)?;
-            if encoding_context.group_token_in == *swap.token_in() {
-                initial_protocol_data = protocol_data;
-            } else {
-                grouped_protocol_data.push(protocol_data);
+
+        for i in 0..n_swaps {
+            let swap = &solution.swaps[i];
+            if !is_rfq(swap) {
+                continue;
             }
-        }
+            let mut token_in = swap.token_in().clone();
+            let mut token_out = swap.token_out().clone();
 
-        if !grouped_protocol_data.is_empty() {
-            initial_protocol_data.extend(ple_encode(grouped_protocol_data));
-        }
+            if is_native_or_wrapped(&token_in) {
+                if first_swaps.contains(&i) {
+                    if is_native_or_wrapped(&available_token) {
+                        token_in = available_token.clone();
+                    }
+                } else if let Some(produced) = solution.swaps[..i]
+                    .iter()
+                    .rev()
+                    .map(|swap| swap.token_out())
+                    .find(|token| is_native_or_wrapped(token))
+                {
+                    token_in = produced.clone();
+                }
+            }
+            if is_native_or_wrapped(&token_out) {
+                if last_swaps.contains(&i) {
+                    if is_native_or_wrapped(&required_token) {
+                        token_out = required_token.clone();
+                    }
+                } else if let Some(consumed) = solution.swaps[i + 1..]
+                    .iter()
+                    .map(|swap| swap.token_in())
+                    .find(|token| is_native_or_wrapped(token))
+                {
+                    token_out = consumed.clone();
+                }
+            }
 
-        Ok(EncodedSolution {
-            swaps: initial_protocol_data,
-            interacting_with: swap_encoder.executor_address().clone(),
-            permit: None,
-            function_signature: "".to_string(),
-            n_tokens: 0,
-        })
+            if token_in != *swap.token_in() || token_out != *swap.token_out() {
+                solution.swaps[i] = swap
+                    .clone()
+                    .with_tokens(token_in, token_out);
+            }
+        }
+        solution
     }
-}
-
-impl TychoEncoder for TychoExecutorEncoder {
-    fn encode_solutions(
-        &self,
-        solutions: Vec<Solution>,
-    ) -> Result<Vec<EncodedSolution>, EncodingError> {
-        let solution = solutions
-            .first()
-            .ok_or(EncodingError::FatalError("No solutions found".to_string()))?;
-        self.validate_solution(solution)?;
 
-        let encoded_solution = self.encode_executor_calldata(solution)?;
+    /// Makes Curve pools that take the raw native token receive it directly when they are first
+    /// hops.
+    ///
+    /// If the solution gives the native token and its first swaps are on such Curve pools, the
+    /// native token sent with the call (`msg.value`) is forwarded to the pools instead of being
+    /// wrapped: a wrap action is dropped (if no other first swap needs the wrapped token) and the
+    /// swaps are set to take the native token.
+    fn resolve_curve_native_first_hops(&self, solution: &Solution) -> Solution {
+        let Some(curve_native_token_address) = &self.curve_native_token_address else {
+            return solution.clone();
+        };
+        let ChainTokens { native, wrapped } = self.chain_tokens.clone();
+        if solution.given_token != native {
+            return solution.clone();
+        }
+        let takes_raw_native = |swap: &Swap| {
+            swap.component().protocol_system == "vm:curve" &&
+                takes_raw_native_token(swap, curve_native_token_address, &wrapped)
+                    .unwrap_or(false)
+        };
 
-        Ok(vec![encoded_solution])
-    }
+        let mut solution = solution.clone();
+        // Swaps taking the given token, before any other swap produced the native/wrapped token
+        let first_swaps: Vec<usize> = (0..solution.swaps.len())
+            .filter(|&i| {
+                let token_in = solution.swaps[i].token_in();
+                (*token_in == native || *token_in == wrapped) &&
+                    !solution.swaps[..i]
+                        .iter()
+                        .any(|swap| *swap.token_out() == native || *swap.token_out() == wrapped)
+            })
+            .collect();
+        let curve_first_swaps: Vec<usize> = first_swaps
+            .iter()
+            .copied()
+            .filter(|&i| takes_raw_native(&solution.swaps[i]))
+            .collect();
+        if curve_first_swaps.is_empty() {
+            return solution;
+        }
 
-    fn encode_full_calldata(
-        &self,
-        _solutions: Vec<Solution>,
-    ) -> Result<Vec<Transaction>, EncodingError> {
-        Err(EncodingError::NotImplementedError(
-            "Full calldata encoding is not supported for TychoExecutorEncoder".to_string(),
-        ))
+        let wrapping = solution.native_action == Some(NativeAction::Wrap);
+        if wrapping && curve_first_swaps.len() != first_swaps.len() {
+            // Other first swaps need the wrapped token, so the wrap has to stay
+            return solution;
+        }
+        if wrapping {
+            solution.native_action = None;
+        }
+        for i in curve_first_swaps {
+            let swap = &solution.swaps[i];
+            if *swap.token_in() == wrapped {
+                solution.swaps[i] = swap
+                    .clone()
+                    .with_tokens(native.clone(), swap.token_out().clone());
+            }
+        }
+        solution
     }
 
-    /// Raises an `EncodingError` if the solution is not considered valid.
+    /// Returns the solution with the checked amount to encode, derived from its min shares of a
+    /// rebasing checked token if they are set (see `CheckedShares`), or else from its expected
+    /// amount if a slippage applies to it (see `Solution::resolved_checked_amount`).
     ///
-    /// A solution is considered valid if all the following conditions are met:
-    /// * The solution is not exact out.
-    fn validate_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
-        if solution.exact_out {
-            return Err(EncodingError::FatalError(
-                "Currently only exact input solutions are supported".to_string(),
+    /// Returns an `EncodingError::InvalidInput` if min shares are set on an exact out solution, a
+    /// solution with a slippage or a checked token that doesn't rebase.
+    fn resolve_checked_amount(&self, solution: &Solution) -> Result<Solution, EncodingError> {
+        let mut solution = solution.clone();
+        let Some(checked_shares) = &solution.checked_shares else {
+            solution.checked_amount =
+                solution.resolved_checked_amount(self.default_slippage.as_ref())?;
+            return Ok(solution);
+        };
+        if solution.exact_out || solution.slippage.is_some() {
+            return Err(EncodingError::InvalidInput(
+                "Min shares can only be checked for exact in solutions without a slippage"
+                    .to_string(),
             ));
         }
-        Ok(())
+        if !is_rebasing_token(self.chain, &solution.checked_token) {
+            return Err(EncodingError::InvalidInput(format!(
+                "Min shares can only be checked for rebasing tokens. Found {}",
+                solution.checked_token
+            )));
+        }
+        let rate = ShareRate::new(
+            checked_shares.total_shares.clone(),
+            checked_shares.total_pooled.clone(),
+        )?;
+        solution.checked_amount = checked_amount_for_shares(
+            &checked_shares.min_shares,
+            &rate,
+            checked_shares.rebase_tolerance_bps,
+        )?;
+        Ok(solution)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use std::{collections::HashMap, fs, str::FromStr};
-
-    use num_bigint::{BigInt, BigUint};
-    use tycho_common::models::{protocol::ProtocolComponent, Chain};
 
-    use super::*;
-    use crate::encoding::models::Swap;
+    /// Returns the solution with the output checked by the router call: if the solution ends with
+    /// a bridge swap or a withdrawal request, nothing is received on this chain by the end of the
+    /// transaction, so the router checks the amount of the last swap's token in received by its
+    /// component (the bridge or the withdrawal queue) instead. For a rebasing token (e.g. stETH),
+    /// the checked amount is lowered by the rounding of its transfers.
+    fn resolve_bridge_settlement(&self, solution: &Solution) -> Result<Solution, EncodingError> {
+        let mut solution = solution.clone();
+        if let Some(last_swap) = solution.swaps.last().filter(|swap| {
+            let protocol_system = swap
+                .component()
+                .protocol_system
+                .as_str();
+            BRIDGE_PROTOCOLS.contains(protocol_system) ||
+                WITHDRAWAL_REQUEST_PROTOCOLS.contains(protocol_system)
+        }) {
+            let settlement = Bytes::from_str(&last_swap.component().id).map_err(|_| {
+                EncodingError::InvalidInput(format!(
+                    "Invalid {} component id: {}",
+                    last_swap.component().protocol_system,
+                    last_swap.component().id
+                ))
+            })?;
+            solution.checked_token = last_swap.token_in().clone();
+            solution.receiver = settlement;
+            // The settlement pulls a rebasing token with the rounding of its share math
+            if is_rebasing_token(self.chain, &solution.checked_token) {
+                let rounding = BigUint::from(REBASING_TRANSFER_ROUNDING);
+                solution.checked_amount = if solution.checked_amount > rounding {
+                    &solution.checked_amount - rounding
+                } else {
+                    BigUint::ZERO
+                };
+            }
+        }
+        Ok(solution)
+    }
 
-    fn dai() -> Bytes {
-        Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap()
+    /// Returns the solution with the receiver checked by the router call: if the terminal swaps
+    /// override their receiver, the router checks the amount received by their override instead.
+    ///
+    /// Returns an `EncodingError::InvalidInput` if only some terminal swaps override their
+    /// receiver, or not all to the same address, as the router can only check the amount out
+    /// received by a single receiver.
+    fn resolve_receiver_override(&self, solution: &Solution) -> Result<Solution, EncodingError> {
+        let overrides: Vec<&Option<Bytes>> = (0..solution.swaps.len())
+            .filter(|&i| solution.is_terminal_swap(i))
+            .map(|i| solution.swaps[i].get_receiver_override())
+            .collect();
+        let Some(receiver) = overrides
+            .iter()
+            .find_map(|receiver| receiver.as_ref())
+        else {
+            return Ok(solution.clone());
+        };
+        if overrides
+            .iter()
+            .any(|other| other.as_ref() != Some(receiver))
+        {
+            return Err(EncodingError::InvalidInput(
+                "The router checks the amount out received by a single receiver, so all terminal \
+                 swaps must override their receiver with the same address"
+                    .to_string(),
+            ));
+        }
+        let mut solution = solution.clone();
+        solution.receiver = receiver.clone();
+        Ok(solution)
     }
 
-    fn eth() -> Bytes {
-        Bytes::from_str("0x0000000000000000000000000000000000000000").unwrap()
+    /// Raises an `EncodingError::InvalidInput` if a swap overrides its receiver but isn't a
+    /// terminal swap (see `Solution::is_terminal_swap`), or if the solution unwraps its output,
+    /// which the router must receive to unwrap.
+    fn validate_receiver_overrides(&self, solution: &Solution) -> Result<(), EncodingError> {
+        for (i, swap) in solution.swaps.iter().enumerate() {
+            if swap.get_receiver_override().is_none() {
+                continue;
+            }
+            if solution.native_action == Some(NativeAction::Unwrap) {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Swap {i} overrides its receiver, but the solution unwraps its output, which \
+                     the router must receive"
+                )));
+            }
+            if !solution.is_terminal_swap(i) {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Swap {i} overrides its receiver, but only terminal swaps (whose output is \
+                     bought and not swapped again) can"
+                )));
+            }
+        }
+        Ok(())
     }
 
-    fn weth() -> Bytes {
-        Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap()
+    /// Raises an `EncodingError::InvalidInput` if the integrator fee of the solution is invalid
+    /// (see `Fee::validate`) or can't be taken by the router: on exact out, cyclical, bridge or
+    /// withdrawal request solutions, or on the output of solutions whose swaps override their
+    /// receiver.
+    fn validate_fee(&self, solution: &Solution) -> Result<(), EncodingError> {
+        let Some(fee) = &solution.fee else {
+            return Ok(());
+        };
+        fee.validate()?;
+        if solution.exact_out {
+            return Err(EncodingError::InvalidInput(
+                "Integrator fees are not supported for exact out solutions".to_string(),
+            ));
+        }
+        if solution.given_token == solution.checked_token {
+            return Err(EncodingError::InvalidInput(
+                "Integrator fees are not supported for cyclical solutions".to_string(),
+            ));
+        }
+        if solution.swaps.iter().any(|swap| {
+            let protocol_system = swap
+                .component()
+                .protocol_system
+                .as_str();
+            BRIDGE_PROTOCOLS.contains(protocol_system) ||
+                WITHDRAWAL_REQUEST_PROTOCOLS.contains(protocol_system)
+        }) {
+            return Err(EncodingError::InvalidInput(
+                "Integrator fees are not supported for bridge or withdrawal request solutions"
+                    .to_string(),
+            ));
+        }
+        if fee.on_input && solution.native_action == Some(NativeAction::Wrap) {
+            return Err(EncodingError::InvalidInput(
+                "Input fees are not supported when wrapping the native token, since the router \
+                 takes them before wrapping. Take the fee on the output"
+                    .to_string(),
+            ));
+        }
+        if !fee.on_input &&
+            solution
+                .swaps
+                .iter()
+                .any(|swap| swap.get_receiver_override().is_some())
+        {
+            return Err(EncodingError::InvalidInput(
+                "Output fees are taken by the router, which must receive the amount out instead of \
+                 the receiver overrides. Remove the overrides or take the fee on the input"
+                    .to_string(),
+            ));
+        }
+        Ok(())
     }
 
-    fn usdc() -> Bytes {
-        Bytes::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap()
+    /// Raises an `EncodingError::InvalidInput` if the receiver of the solution can't be called
+    /// back by the router: alongside a fee, on exact out, cyclical, bridge, withdrawal request or
+    /// multi input solutions, or on solutions whose swaps override their receiver.
+    fn validate_receiver_callback(&self, solution: &Solution) -> Result<(), EncodingError> {
+        if solution.receiver_callback.is_none() {
+            return Ok(());
+        }
+        if solution.fee.is_some() {
+            return Err(EncodingError::InvalidInput(
+                "Receiver callbacks are not supported alongside integrator fees".to_string(),
+            ));
+        }
+        if solution.exact_out {
+            return Err(EncodingError::InvalidInput(
+                "Receiver callbacks are not supported for exact out solutions".to_string(),
+            ));
+        }
+        if solution.given_token == solution.checked_token {
+            return Err(EncodingError::InvalidInput(
+                "Receiver callbacks are not supported for cyclical solutions".to_string(),
+            ));
+        }
+        if !solution
+            .additional_given_tokens
+            .is_empty()
+        {
+            return Err(EncodingError::InvalidInput(
+                "Receiver callbacks are not supported for solutions with additional given tokens"
+                    .to_string(),
+            ));
+        }
+        if solution.swaps.iter().any(|swap| {
+            let protocol_system = swap
+                .component()
+                .protocol_system
+                .as_str();
+            BRIDGE_PROTOCOLS.contains(protocol_system) ||
+                WITHDRAWAL_REQUEST_PROTOCOLS.contains(protocol_system)
+        }) {
+            return Err(EncodingError::InvalidInput(
+                "Receiver callbacks are not supported for bridge or withdrawal request solutions"
+                    .to_string(),
+            ));
+        }
+        if solution
+            .swaps
+            .iter()
+            .any(|swap| swap.get_receiver_override().is_some())
+        {
+            return Err(EncodingError::InvalidInput(
+                "The router calls back the receiver and must receive the amount out instead of \
+                 the receiver overrides. Remove the overrides to use a receiver callback"
+                    .to_string(),
+            ));
+        }
+        Ok(())
     }
 
-    fn wbtc() -> Bytes {
-        Bytes::from_str("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599").unwrap()
+    /// Raises an `EncodingError::InvalidInput` if the solution has additional given tokens that the
+    /// router can't sell together: on exact out or wrapping solutions, with an integrator fee or a
+    /// bridge swap, or if a given token is native, repeated or the checked token.
+    fn validate_multi_input_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
+        if solution
+            .additional_given_tokens
+            .is_empty()
+        {
+            return Ok(());
+        }
+        if solution.exact_out {
+            return Err(EncodingError::InvalidInput(
+                "Exact out solutions can't have additional given tokens".to_string(),
+            ));
+        }
+        if solution.fee.is_some() {
+            return Err(EncodingError::InvalidInput(
+                "Integrator fees are not supported for solutions with additional given tokens"
+                    .to_string(),
+            ));
+        }
+        if solution.native_action == Some(NativeAction::Wrap) {
+            return Err(EncodingError::InvalidInput(
+                "Solutions with additional given tokens do not support wrapping the native token"
+                    .to_string(),
+            ));
+        }
+        let native_address = self.chain_tokens.native.clone();
+        let mut given_tokens = HashSet::new();
+        for (token, _) in solution.given_tokens() {
+            if *token == native_address {
+                return Err(EncodingError::InvalidInput(
+                    "The native token can't be given alongside other tokens".to_string(),
+                ));
+            }
+            if *token == solution.checked_token {
+                return Err(EncodingError::InvalidInput(
+                    "The checked token can't be a given token of a multi input solution"
+                        .to_string(),
+                ));
+            }
+            if !given_tokens.insert(token) {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Token {token} is given more than once"
+                )));
+            }
+        }
+        if solution.swaps.iter().any(|swap| {
+            let protocol_system = swap
+                .component()
+                .protocol_system
+                .as_str();
+            BRIDGE_PROTOCOLS.contains(protocol_system) ||
+                WITHDRAWAL_REQUEST_PROTOCOLS.contains(protocol_system)
+        }) {
+            return Err(EncodingError::InvalidInput(
+                "Bridge and withdrawal request swaps are not supported for solutions with \
+                 additional given tokens"
+                    .to_string(),
+            ));
+        }
+        Ok(())
     }
 
-    fn pepe() -> Bytes {
-        Bytes::from_str("0x6982508145454Ce325dDbE47a25d4ec3d2311933").unwrap()
+    /// Adjusts the native token handling of the solution to what its protocols expect (see
+    /// `resolve_rfq_native_boundaries` and `resolve_curve_native_first_hops`).
+    fn resolve_native_handling(&self, solution: &Solution) -> Solution {
+        let solution = self.resolve_curve_native_first_hops(solution);
+        self.resolve_rfq_native_boundaries(&solution)
     }
 
-    // Fee and tick spacing information for this test is obtained by querying the
-    // USV4 Position Manager contract: 0xbd216513d74c8cf14cf4747e6aaa6420ff64ee9e
-    // Using the poolKeys function with the first 25 bytes of the pool id
-    fn swap_usdc_eth_univ4() -> Swap {
-        let pool_fee_usdc_eth = Bytes::from(BigInt::from(3000).to_signed_bytes_be());
-        let tick_spacing_usdc_eth = Bytes::from(BigInt::from(60).to_signed_bytes_be());
-        let mut static_attributes_usdc_eth: HashMap<String, Bytes> = HashMap::new();
-        static_attributes_usdc_eth.insert("key_lp_fee".into(), pool_fee_usdc_eth);
-        static_attributes_usdc_eth.insert("tick_spacing".into(), tick_spacing_usdc_eth);
-        Swap::new(
-            ProtocolComponent {
-                id: "0xdce6394339af00981949f5f3baf27e3610c76326a700af57e4b3e3ae4977f78d"
-                    .to_string(),
-                protocol_system: "uniswap_v4".to_string(),
-                static_attributes: static_attributes_usdc_eth,
-                ..Default::default()
-            },
-            usdc().clone(),
-            eth().clone(),
-        )
+    /// Returns the strategy encoding the solution: single swaps (or a group of swaps on a single
+    /// groupable protocol) use the single swap strategy, solutions without splits the sequential
+    /// one and the rest the split one. Solutions with additional given tokens always use the multi
+    /// input split strategy.
+    fn strategy_encoder(&self, solution: &Solution) -> &dyn StrategyEncoder {
+        if !solution
+            .additional_given_tokens
+            .is_empty()
+        {
+            return &self.multi_input_split_swap_strategy;
+        }
+        let protocols: HashSet<String> = solution
+            .swaps
+            .iter()
+            .map(|swap| swap.component().protocol_system.clone())
+            .collect();
+        let registry = self.current_registry();
+
+        if (solution.swaps.len() == 1) ||
+            ((protocols.len() == 1 &&
+                protocols.iter().any(|p| {
+                    registry
+                        .capabilities(p)
+                        .is_some_and(|capabilities| capabilities.groupable)
+                })) &&
+                solution
+                    .swaps
+                    .iter()
+                    .all(|swap| swap.get_split() == 0.0))
+        {
+            &self.single_swap_strategy
+        } else if solution
+            .swaps
+            .iter()
+            .all(|swap| swap.get_split() == 0.0)
+        {
+            &self.sequential_swap_strategy
+        } else {
+            &self.split_swap_strategy
+        }
     }
 
-    fn swap_eth_pepe_univ4() -> Swap {
-        let pool_fee_eth_pepe = Bytes::from(BigInt::from(25000).to_signed_bytes_be());
-        let tick_spacing_eth_pepe = Bytes::from(BigInt::from(500).to_signed_bytes_be());
-        let mut static_attributes_eth_pepe: HashMap<String, Bytes> = HashMap::new();
-        static_attributes_eth_pepe.insert("key_lp_fee".into(), pool_fee_eth_pepe);
-        static_attributes_eth_pepe.insert("tick_spacing".into(), tick_spacing_eth_pepe);
-        Swap::new(
-            ProtocolComponent {
-                id: "0xecd73ecbf77219f21f129c8836d5d686bbc27d264742ddad620500e3e548e2c9"
-                    .to_string(),
-                protocol_system: "uniswap_v4".to_string(),
-                static_attributes: static_attributes_eth_pepe,
-                ..Default::default()
-            },
-            eth().clone(),
-            pepe().clone(),
+    fn encode_solution(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError> {
+        let input_solution = solution;
+        let solution = &self.resolve_native_handling(solution);
+        self.validate_solution(solution)?;
+        if let Some(token_safety_checker) = &self.token_safety_checker {
+            token_safety_checker.validate(solution, &self.router_address)?;
+        }
+
+        let strategy = self.strategy_encoder(solution);
+        let mut encoded_solution =
+            measure(EncodingStage::EncodeStrategy, Some(strategy.name().to_string()), || {
+                strategy.encode_strategy(solution)
+            })?;
+
+        if let Some(permit2) = &self.permit2 {
+            let permit = permit2.get_permit(
+                &self.router_address,
+                &solution.sender,
+                &solution.given_token,
+                &solution.given_amount,
+            )?;
+            encoded_solution.permit = Some(permit);
+        }
+        self.finalize_encoded_solution(input_solution, solution, encoded_solution)
+    }
+
+    /// Encodes the solution like `encode_solution`, awaiting the RFQ quotes, allowance and permit
+    /// lookups instead of blocking on them.
+    async fn encode_solution_async(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let input_solution = solution;
+        let solution = &self.resolve_native_handling(solution);
+        self.validate_solution(solution)?;
+        if let Some(token_safety_checker) = &self.token_safety_checker {
+            token_safety_checker
+                .validate_async(solution, &self.router_address)
+                .await?;
+        }
+
+        let strategy = self.strategy_encoder(solution);
+        let mut encoded_solution = measure_async(
+            EncodingStage::EncodeStrategy,
+            Some(strategy.name().to_string()),
+            strategy.encode_strategy_async(solution),
         )
+        .await?;
+
+        if let Some(permit2) = &self.permit2 {
+            let permit = permit2
+                .get_permit_async(
+                    &self.router_address,
+                    &solution.sender,
+                    &solution.given_token,
+                    &solution.given_amount,
+                )
+                .await?;
+            encoded_solution.permit = Some(permit);
+        }
+        self.finalize_encoded_solution(input_solution, solution, encoded_solution)
     }
 
-    fn router_address() -> Bytes {
-        Bytes::from_str("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66").unwrap()
+    /// Encodes solutions of the same sender authorized by a single Permit2 batch permit, instead of
+    /// one permit (and one signature) per solution.
+    ///
+    /// All the solutions are encoded for `splitSwapPermit2Batch`. The first encoded solution
+    /// carries the batch permit, approving the router for the total given amount of every given
+    /// token of the solutions. The other ones carry no permit and spend the allowance granted by
+    /// the first one, so the transactions must be executed in order (e.g. in a bundle).
+    ///
+    /// Returns an `EncodingError::InvalidInput` if the encoder doesn't use permit2 transfers, if
+    /// the solutions have different senders, or if any of them is an exact out solution, and an
+    /// `EncodingError::FatalError` if the encoder uses an executor table.
+    pub fn encode_solutions_with_permit_batch(
+        &self,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        let permit2 = self.permit2.as_ref().ok_or_else(|| {
+            EncodingError::InvalidInput(
+                "Batch permits require the TransferFromPermit2 user transfer type".to_string(),
+            )
+        })?;
+        let Some(sender) = solutions
+            .first()
+            .map(|solution| solution.sender.clone())
+        else {
+            return Ok(vec![]);
+        };
+        let strategy = self
+            .split_swap_strategy
+            .clone()
+            .with_permit_batch()?;
+
+        let mut encoded = Vec::with_capacity(solutions.len());
+        let mut tokens: Vec<(Bytes, BigUint)> = Vec::new();
+        for input_solution in solutions.iter() {
+            if input_solution.sender != sender {
+                return Err(EncodingError::InvalidInput(
+                    "All the solutions of a batch permit must have the same sender".to_string(),
+                ));
+            }
+            if input_solution.exact_out {
+                return Err(EncodingError::InvalidInput(
+                    "Exact out solutions can't be authorized with a batch permit".to_string(),
+                ));
+            }
+            let solution = self.resolve_native_handling(input_solution);
+            self.validate_solution(&solution)?;
+            // Native tokens are sent with the call, so they aren't permitted
+            if solution.given_token != self.chain_tokens.native {
+                match tokens
+                    .iter_mut()
+                    .find(|(token, _)| *token == solution.given_token)
+                {
+                    Some((_, amount)) => *amount += &solution.given_amount,
+                    None => {
+                        tokens.push((solution.given_token.clone(), solution.given_amount.clone()))
+                    }
+                }
+            }
+            let encoded_solution = strategy.encode_strategy(&solution)?;
+            encoded.push((input_solution, solution, encoded_solution));
+        }
+
+        if !tokens.is_empty() {
+            let permit_batch = permit2.get_permit_batch(&self.router_address, &sender, &tokens)?;
+            encoded[0].2.permit_batch = Some(permit_batch);
+        }
+        encoded
+            .into_iter()
+            .map(|(input_solution, solution, encoded_solution)| {
+                self.finalize_encoded_solution(input_solution, &solution, encoded_solution)
+            })
+            .collect()
     }
 
-    fn eth_chain() -> Chain {
-        Chain::Ethereum
+    /// Attaches the callback allowlist to the encoded solution and records it in the audit log.
+    fn finalize_encoded_solution(
+        &self,
+        input_solution: &Solution,
+        solution: &Solution,
+        mut encoded_solution: EncodedSolution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        if let Some(callback_allowlist) = &self.callback_allowlist {
+            encoded_solution.callback_allowlist = Some(callback_allowlist.targets(solution)?);
+        }
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(input_solution, &encoded_solution)?;
+        }
+        Ok(encoded_solution)
     }
+}
 
-    fn get_swap_encoder_registry() -> SwapEncoderRegistry {
-        let executors_addresses =
-            fs::read_to_string("config/test_executor_addresses.json").unwrap();
-        SwapEncoderRegistry::new(eth_chain())
-            .add_default_encoders(Some(executors_addresses))
-            .unwrap()
+#[async_trait]
+impl TychoEncoder for TychoRouterEncoder {
+    fn encode_solutions(
+        &self,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        with_metrics(&self.metrics, || {
+            measure(EncodingStage::EncodeSolutions, None, || {
+                let mut result: Vec<EncodedSolution> = Vec::new();
+                for solution in solutions.iter() {
+                    let encoded_solution = self.encode_solution_deduplicated(solution)?;
+                    result.push(encoded_solution);
+                }
+                Ok(result)
+            })
+        })
     }
 
-    fn get_tycho_router_encoder(user_transfer_type: UserTransferType) -> TychoRouterEncoder {
-        TychoRouterEncoder::new(
-            eth_chain(),
-            get_swap_encoder_registry(),
-            router_address(),
-            user_transfer_type,
-            None,
-            false,
+    async fn encode_solutions_async(
+        &self,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        let encode = async {
+            let mut result: Vec<EncodedSolution> = Vec::new();
+            for solution in solutions.iter() {
+                let encoded_solution = self
+                    .encode_solution_deduplicated_async(solution)
+                    .await?;
+                result.push(encoded_solution);
+            }
+            Ok(result)
+        };
+        with_metrics_async(
+            &self.metrics,
+            measure_async(EncodingStage::EncodeSolutions, None, encode),
         )
-        .unwrap()
+        .await
+    }
+
+    /// Encodes the solutions like `encode_solutions_async`, awaiting up to `concurrency` of them
+    /// at once (at least one). The RFQ quotes, allowance and permit lookups of different solutions
+    /// are thus fetched concurrently, and every solution gets its own result.
+    async fn encode_solutions_concurrently(
+        &self,
+        solutions: Vec<Solution>,
+        concurrency: usize,
+    ) -> Vec<Result<EncodedSolution, EncodingError>> {
+        // Every future owns its solution, so that the stream doesn't borrow from the iterator
+        let encode = stream::iter(solutions)
+            .map(|solution| async move {
+                self.encode_solution_deduplicated_async(&solution)
+                    .await
+            })
+            .buffered(concurrency.max(1))
+            .collect::<Vec<_>>();
+        with_metrics_async(&self.metrics, encode).await
+    }
+
+    fn encode_solutions_for_environment(
+        &self,
+        solutions: Vec<Solution>,
+        environment: Environment,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        match environment {
+            Environment::Production => self.encode_solutions(solutions),
+            Environment::Simulation => self
+                .simulation_encoder
+                .as_ref()
+                .ok_or_else(|| {
+                    EncodingError::InvalidInput(
+                        "No simulation environment configured for this encoder".to_string(),
+                    )
+                })?
+                .encode_solutions(solutions),
+        }
+    }
+
+    fn encode_full_calldata(
+        &self,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<Transaction>, EncodingError> {
+        with_metrics(&self.metrics, || {
+            measure(EncodingStage::EncodeSolutions, None, || {
+                let permit_signer = match (&self.permit_signer, &self.signer) {
+                    (Some(permit_signer), _) => Some(permit_signer.as_ref()),
+                    (None, Some(signer)) => Some(signer as &dyn PermitSigner),
+                    (None, None) => None,
+                };
+                let mut transactions: Vec<Transaction> = Vec::new();
+                for solution in solutions.iter() {
+                    let encoded_solution = self.encode_solution_deduplicated(solution)?;
+                    transactions.push(self.assemble_router_call(
+                        solution,
+                        encoded_solution,
+                        permit_signer,
+                    )?);
+                }
+                Ok(transactions)
+            })
+        })
     }
 
-    mod router_encoder {
-        use super::*;
+    /// Raises an `EncodingError` if the solution is not considered valid.
+    ///
+    /// The native token handling of RFQ, Curve and native-keyed pool swaps is aligned with the
+    /// solution first (see `resolve_native_handling`).
+    ///
+    /// If a support matrix is set, the solution must be supported by the router of the chain (see
+    /// `SupportMatrix::validate_solution`). Exact out solutions are then validated separately (see
+    /// `validate_exact_out_solution`). In both cases, only terminal swaps can override their
+    /// receiver (see `validate_receiver_overrides`) and the integrator fee must be takeable by the
+    /// router (see `validate_fee`). Solutions with additional given tokens must be sellable
+    /// together by the router (see `validate_multi_input_solution`). If a checked amount guard is
+    /// set (the default), the checked amount must be of the magnitude of the expected amount (see
+    /// `CheckedAmountGuard`).
+    ///
+    /// A solution is considered valid if all the following conditions are met:
+    /// * The solution has at least one swap.
+    /// * If the solution is wrapping, the given token is the chain's native token and the first
+    ///   swap's input is the chain's wrapped token.
+    /// * If the solution is unwrapping, the checked token is the chain's native token and the last
+    ///   swap's output is the chain's wrapped token.
+    /// * If the solution gives (or checks) the wrapped token, no native-keyed pool (e.g. Uniswap V4
+    ///   or Ekubo) takes (or outputs) the native token without another swap producing (or
+    ///   consuming) it.
+    /// * Only RFQ registration swaps have a zero estimated amount in, and each one is followed by
+    ///   the swap filling its order on the same component.
+    /// * Bridge swaps (e.g. Across deposits) are only the last swap of a sequential solution, after
+    ///   at least one other swap.
+    /// * Withdrawal requests (e.g. Lido withdrawals) are only the last swap of a sequential
+    ///   solution.
+    /// * The token cannot appear more than once in the solution unless it is the first and last
+    ///   token (i.e. a true cyclical swap).
+    /// * If the cyclic profit check is enabled, cyclical solutions check more than they give.
+    /// * If a staleness guard is set, no swap was derived from an outdated state.
+    fn validate_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
+        let solution = &self.resolve_native_handling(&self.resolve_checked_amount(solution)?);
+        if let Some(support_matrix) = &self.support_matrix {
+            support_matrix.validate_solution(solution)?;
+        }
+        self.validate_receiver_overrides(solution)?;
+        self.validate_fee(solution)?;
+        self.validate_receiver_callback(solution)?;
+        self.validate_multi_input_solution(solution)?;
+        if let Some(checked_amount_guard) = &self.checked_amount_guard {
+            checked_amount_guard.validate(solution, self.chain)?;
+        }
+        if solution.exact_out {
+            return self.validate_exact_out_solution(solution);
+        }
+        if solution.swaps.is_empty() {
+            return Err(EncodingError::FatalError("No swaps found in solution".to_string()));
+        }
+        let ChainTokens { native: native_address, wrapped: wrapped_address } =
+            self.chain_tokens.clone();
+        if let Some(native_action) = &solution.native_action {
+            if native_action == &NativeAction::Wrap {
+                if solution.given_token != native_address {
+                    return Err(EncodingError::FatalError(
+                        "Native token must be the input token in order to wrap".to_string(),
+                    ));
+                }
+                if let Some(first_swap) = solution.swaps.first() {
+                    if *first_swap.token_in() != wrapped_address {
+                        return Err(EncodingError::FatalError(
+                            "Wrapped token must be the first swap's input in order to wrap"
+                                .to_string(),
+                        ));
+                    }
+                }
+            } else if native_action == &NativeAction::Unwrap {
+                if solution.checked_token != native_address {
+                    return Err(EncodingError::FatalError(
+                        "Native token must be the output token in order to unwrap".to_string(),
+                    ));
+                }
+                if let Some(last_swap) = solution.swaps.last() {
+                    if *last_swap.token_out() != wrapped_address {
+                        return Err(EncodingError::FatalError(
+                            "Wrapped token must be the last swap's output in order to unwrap"
+                                .to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // The router can only wrap the given token, so native-keyed pools can't be reached from
+        // the wrapped token (and vice versa for the checked token)
+        let is_native_keyed = |swap: &Swap| {
+            NATIVE_KEYED_PROTOCOLS.contains(
+                swap.component()
+                    .protocol_system
+                    .as_str(),
+            )
+        };
+        if solution.given_token == wrapped_address {
+            if let Some(swap) = solution.swaps.iter().find(|swap| {
+                is_native_keyed(swap) &&
+                    *swap.token_in() == native_address &&
+                    !solution
+                        .swaps
+                        .iter()
+                        .any(|other| *other.token_out() == native_address)
+            }) {
+                return Err(EncodingError::InvalidInput(format!(
+                    "The {} pool {} takes the native token, but the solution gives the wrapped \
+                     token, which the router can't unwrap before swapping. Give the native token \
+                     instead, or use a pool keyed on the wrapped token.",
+                    swap.component().protocol_system,
+                    swap.component().id
+                )));
+            }
+        }
+        if solution.checked_token == wrapped_address {
+            if let Some(swap) = solution.swaps.iter().find(|swap| {
+                is_native_keyed(swap) &&
+                    *swap.token_out() == native_address &&
+                    !solution
+                        .swaps
+                        .iter()
+                        .any(|other| *other.token_in() == native_address)
+            }) {
+                return Err(EncodingError::InvalidInput(format!(
+                    "The {} pool {} outputs the native token, but the solution checks the \
+                     wrapped token, which the router can't wrap after swapping. Check the native \
+                     token instead, or use a pool keyed on the wrapped token.",
+                    swap.component().protocol_system,
+                    swap.component().id
+                )));
+            }
+        }
+
+        for (i, swap) in solution.swaps.iter().enumerate() {
+            let protocol_system = &swap.component().protocol_system;
+            if BRIDGE_PROTOCOLS.contains(protocol_system.as_str()) {
+                if i != solution.swaps.len() - 1 {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Bridge swaps can only be the last swap of a solution. Swap {i} is on \
+                         {protocol_system}"
+                    )));
+                }
+                if i == 0 {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Bridge swaps must follow another swap. Deposit the given token into \
+                         {protocol_system} directly instead"
+                    )));
+                }
+                if solution
+                    .swaps
+                    .iter()
+                    .any(|swap| swap.get_split() != 0.0)
+                {
+                    return Err(EncodingError::InvalidInput(
+                        "Bridge swaps are only supported in sequential solutions".to_string(),
+                    ));
+                }
+            }
+            if WITHDRAWAL_REQUEST_PROTOCOLS.contains(protocol_system.as_str()) {
+                if i != solution.swaps.len() - 1 {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Withdrawal requests can only be the last swap of a solution, as their \
+                         output is claimed later. Swap {i} is on {protocol_system}"
+                    )));
+                }
+                if i == 0 {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Withdrawal requests must follow another swap. Request the withdrawal \
+                         from {protocol_system} directly instead"
+                    )));
+                }
+                if solution
+                    .swaps
+                    .iter()
+                    .any(|swap| swap.get_split() != 0.0)
+                {
+                    return Err(EncodingError::InvalidInput(
+                        "Withdrawal requests are only supported in sequential solutions"
+                            .to_string(),
+                    ));
+                }
+            }
+            let zero_amount = swap
+                .get_estimated_amount_in()
+                .as_ref()
+                .is_some_and(|amount| *amount == BigUint::ZERO);
+            if swap.is_registration() {
+                if !RFQ_PROTOCOLS.contains(protocol_system.as_str()) {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Registration swaps are only supported for RFQ protocols. Swap {i} is on \
+                         {protocol_system}"
+                    )));
+                }
+                if swap.get_estimated_amount_in().is_some() && !zero_amount {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Registration swap {i} must have a zero estimated amount in"
+                    )));
+                }
+                let is_filled = solution
+                    .swaps
+                    .get(i + 1)
+                    .is_some_and(|next| {
+                        !next.is_registration() &&
+                            next.component().id == swap.component().id &&
+                            next.token_in() == swap.token_in() &&
+                            next.token_out() == swap.token_out()
+                    });
+                if !is_filled {
+                    return Err(EncodingError::InvalidInput(format!(
+                        "Registration swap {i} must be followed by the swap filling the order on \
+                         the same component"
+                    )));
+                }
+            } else if zero_amount {
+                return Err(EncodingError::InvalidInput(format!(
+                    "Swap {i} has a zero estimated amount in. Flag it as a registration swap if \
+                     the venue expects a zero-amount settlement call"
+                )));
+            }
+        }
+
+        let mut solution_tokens = vec![];
+        let mut split_tokens_already_considered = HashSet::new();
+        for (i, swap) in solution.swaps.iter().enumerate() {
+            // registration swaps don't move any funds, so their tokens are counted by the swap
+            // filling the order
+            if swap.is_registration() {
+                continue;
+            }
+            // so we don't count the split tokens more than once
+            if swap.get_split() != 0.0 {
+                if !split_tokens_already_considered.contains(swap.token_in()) {
+                    solution_tokens.push(swap.token_in());
+                    split_tokens_already_considered.insert(swap.token_in());
+                }
+            } else {
+                // it might be the last swap of the split or a regular swap
+                if !split_tokens_already_considered.contains(swap.token_in()) {
+                    solution_tokens.push(swap.token_in());
+                }
+            }
+            if i == solution.swaps.len() - 1 {
+                solution_tokens.push(swap.token_out());
+            }
+        }
+
+        if solution_tokens.len() !=
+            solution_tokens
+                .iter()
+                .cloned()
+                .collect::<HashSet<&Bytes>>()
+                .len()
+        {
+            if let Some(last_swap) = solution.swaps.last() {
+                if *solution.swaps[0].token_in() != *last_swap.token_out() {
+                    return Err(EncodingError::FatalError(
+                        "Cyclical swaps are only allowed if they are the first and last token of a solution".to_string(),
+                    ));
+                } else {
+                    // it is a valid cyclical swap
+                    // we don't support any wrapping or unwrapping in this case
+                    if let Some(_native_action) = &solution.native_action {
+                        return Err(EncodingError::FatalError(
+                            "Wrapping/Unwrapping is not available in cyclical swaps".to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+        if self.cyclic_profit_check &&
+            solution.given_token == solution.checked_token &&
+            solution.checked_amount <= solution.given_amount
+        {
+            return Err(EncodingError::InvalidInput(format!(
+                "The checked amount {} of a cyclic solution must be above its given amount {}",
+                solution.checked_amount, solution.given_amount
+            )));
+        }
+        if let Some(staleness_guard) = &self.staleness_guard {
+            staleness_guard.validate(solution)?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes solutions to be used by the TychoRouter of several chains.
+///
+/// Cross-chain solvers hold one of these instead of one `TychoRouterEncoder` per chain, and pick
+/// the chain of every batch of solutions they encode.
+///
+/// # Fields
+/// * `encoders`: The router encoder of every supported chain
+#[derive(Clone, Default)]
+pub struct MultiChainTychoRouterEncoder {
+    encoders: HashMap<Chain, TychoRouterEncoder>,
+}
+
+impl MultiChainTychoRouterEncoder {
+    pub fn new(encoders: HashMap<Chain, TychoRouterEncoder>) -> Self {
+        MultiChainTychoRouterEncoder { encoders }
+    }
+
+    /// Adds the encoder of a chain, replacing its previous encoder if any.
+    pub fn with_encoder(mut self, encoder: TychoRouterEncoder) -> Self {
+        self.encoders
+            .insert(encoder.chain, encoder);
+        self
+    }
+
+    /// Returns the chains with an encoder.
+    pub fn chains(&self) -> Vec<Chain> {
+        self.encoders.keys().copied().collect()
+    }
+
+    /// Returns the encoder of the given chain.
+    pub fn encoder(&self, chain: Chain) -> Result<&TychoRouterEncoder, EncodingError> {
+        self.encoders
+            .get(&chain)
+            .ok_or_else(|| {
+                EncodingError::InvalidInput(format!("No encoder configured for chain {chain:?}"))
+            })
+    }
+
+    /// Encodes the solutions with the encoder of the given chain (see
+    /// [`TychoEncoder::encode_solutions`]).
+    pub fn encode_solutions(
+        &self,
+        chain: Chain,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        self.encoder(chain)?
+            .encode_solutions(solutions)
+    }
+
+    /// Encodes the solutions with the encoder of the given chain, awaiting the data fetched during
+    /// encoding (see [`TychoEncoder::encode_solutions_async`]).
+    pub async fn encode_solutions_async(
+        &self,
+        chain: Chain,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        self.encoder(chain)?
+            .encode_solutions_async(solutions)
+            .await
+    }
+
+    /// Encodes the solutions with the encoder of the given chain, up to `concurrency` at once and
+    /// with a result per solution (see [`TychoEncoder::encode_solutions_concurrently`]).
+    pub async fn encode_solutions_concurrently(
+        &self,
+        chain: Chain,
+        solutions: Vec<Solution>,
+        concurrency: usize,
+    ) -> Result<Vec<Result<EncodedSolution, EncodingError>>, EncodingError> {
+        Ok(self
+            .encoder(chain)?
+            .encode_solutions_concurrently(solutions, concurrency)
+            .await)
+    }
+
+    /// Encodes the solutions with the encoder of the given chain, yielding every result as soon as
+    /// it is ready (see [`TychoEncoder::encode_solutions_streaming`]).
+    pub fn encode_solutions_streaming(
+        &self,
+        chain: Chain,
+        solutions: Vec<Solution>,
+        deadline: Duration,
+    ) -> Result<EncodingResultStream<'_>, EncodingError> {
+        Ok(self
+            .encoder(chain)?
+            .encode_solutions_streaming(solutions, deadline))
+    }
+
+    /// Encodes the solutions with the encoder of the given chain, using the router and executors
+    /// of the given environment (see [`TychoEncoder::encode_solutions_for_environment`]).
+    pub fn encode_solutions_for_environment(
+        &self,
+        chain: Chain,
+        solutions: Vec<Solution>,
+        environment: Environment,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        self.encoder(chain)?
+            .encode_solutions_for_environment(solutions, environment)
+    }
+
+    /// Validates the solution with the encoder of the given chain (see
+    /// [`TychoEncoder::validate_solution`]).
+    pub fn validate_solution(
+        &self,
+        chain: Chain,
+        solution: &Solution,
+    ) -> Result<(), EncodingError> {
+        self.encoder(chain)?
+            .validate_solution(solution)
+    }
+}
+
+/// Represents an encoder for one swap to be executed directly against an Executor.
+///
+/// This is useful when you want to bypass the Tycho Router, use your own Router contract and
+/// just need the calldata for a particular swap.
+///
+/// # Fields
+/// * `swap_encoder_registry`: Registry of swap encoders
+#[derive(Clone)]
+pub struct TychoExecutorEncoder {
+    swap_encoder_registry: SwapEncoderRegistry,
+}
+
+impl TychoExecutorEncoder {
+    pub fn new(swap_encoder_registry: SwapEncoderRegistry) -> Result<Self, EncodingError> {
+        Ok(TychoExecutorEncoder { swap_encoder_registry })
+    }
+
+    fn encode_executor_calldata(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        let grouped_swaps = group_swaps(&solution.swaps, &self.swap_encoder_registry);
+        let number_of_groups = grouped_swaps.len();
+        if number_of_groups > 1 {
+            return Err(EncodingError::InvalidInput(format!(
+                "Tycho executor encoder only supports one swap. Found {number_of_groups}"
+            )))
+        }
+
+        let grouped_swap = grouped_swaps
+            .first()
+            .ok_or_else(|| EncodingError::FatalError("Swap grouping failed".to_string()))?;
+
+        let swap_encoder = self
+            .swap_encoder_registry
+            .get_encoder(&grouped_swap.protocol_system)
+            .ok_or_else(|| {
+                EncodingError::InvalidInput(format!(
+                    "Swap encoder not found for protocol: {}",
+                    grouped_swap.protocol_system
+                ))
+            })?;
+
+        let transfer = if !swap_encoder
+            .capabilities()
+            .funds_in_router
+        {
+            TransferType::Transfer
+        } else {
+            TransferType::None
+        };
+        let encoding_context = EncodingContext {
+            receiver: solution.receiver.clone(),
+            exact_out: solution.exact_out,
+            router_address: None,
+            group_token_in: grouped_swap.token_in.clone(),
+            group_token_out: grouped_swap.token_out.clone(),
+            transfer_type: transfer,
+            historical_trade: false,
+            execution_deadline: solution.execution_deadline,
+            approval_policy: ApprovalPolicy::Live,
+        };
+        let mut grouped_protocol_data: Vec<Vec<u8>> = vec![];
+        let mut initial_protocol_data: Vec<u8> = vec![];
+        for swap in grouped_swap.swaps.iter() {
+            let protocol_data = append_user_data(
+                swap_encoder.as_ref(),
+                swap,
+                swap_encoder.encode_swap(swap, &encoding_context)?,
+            )?;
+            if encoding_context.group_token_in == *swap.token_in() {
+                initial_protocol_data = protocol_data;
+            } else {
+                grouped_protocol_data.push(protocol_data);
+            }
+        }
+
+        if !grouped_protocol_data.is_empty() {
+            initial_protocol_data.extend(ple_encode(grouped_protocol_data)?);
+        }
+
+        let summary =
+            summarize_encoded_swaps("", &initial_protocol_data, solution.swaps.len(), &[transfer]);
+        // The executor is called directly, without the router overhead
+        let gas_estimate = grouped_swap
+            .swaps
+            .iter()
+            .map(|swap| {
+                self.swap_encoder_registry
+                    .gas_estimate(&swap.component().protocol_system)
+            })
+            .fold(0, u64::saturating_add);
+        Ok(EncodedSolution {
+            swaps: initial_protocol_data,
+            interacting_with: swap_encoder.group_executor_address(&grouped_swap.swaps)?,
+            permit: None,
+            permit_batch: None,
+            function_signature: "".to_string(),
+            n_tokens: 0,
+            native_action: None,
+            state_versions: solution.state_versions(),
+            callback_allowlist: None,
+            summary,
+            gas_estimate,
+            debug_info: None,
+            degraded_groups: vec![],
+        })
+    }
+}
+
+impl TychoEncoder for TychoExecutorEncoder {
+    fn encode_solutions(
+        &self,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        let solution = solutions
+            .first()
+            .ok_or(EncodingError::FatalError("No solutions found".to_string()))?;
+        self.validate_solution(solution)?;
+
+        let encoded_solution = self.encode_executor_calldata(solution)?;
+
+        Ok(vec![encoded_solution])
+    }
+
+    fn encode_full_calldata(
+        &self,
+        _solutions: Vec<Solution>,
+    ) -> Result<Vec<Transaction>, EncodingError> {
+        Err(EncodingError::NotImplementedError(
+            "Full calldata encoding is not supported for TychoExecutorEncoder".to_string(),
+        ))
+    }
+
+    /// Raises an `EncodingError` if the solution is not considered valid.
+    ///
+    /// A solution is considered valid if all the following conditions are met:
+    /// * The solution is not exact out.
+    fn validate_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
+        if solution.exact_out {
+            return Err(EncodingError::FatalError(
+                "Currently only exact input solutions are supported".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, str::FromStr};
+
+    use num_bigint::{BigInt, BigUint};
+    use tycho_common::models::{protocol::ProtocolComponent, Chain};
+
+    use super::*;
+
+    fn dai() -> Bytes {
+        Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap()
+    }
+
+    fn eth() -> Bytes {
+        Bytes::from_str("0x0000000000000000000000000000000000000000").unwrap()
+    }
+
+    fn weth() -> Bytes {
+        Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap()
+    }
+
+    fn usdc() -> Bytes {
+        Bytes::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap()
+    }
+
+    fn wbtc() -> Bytes {
+        Bytes::from_str("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599").unwrap()
+    }
+
+    fn pepe() -> Bytes {
+        Bytes::from_str("0x6982508145454Ce325dDbE47a25d4ec3d2311933").unwrap()
+    }
+
+    // Fee and tick spacing information for this test is obtained by querying the
+    // USV4 Position Manager contract: 0xbd216513d74c8cf14cf4747e6aaa6420ff64ee9e
+    // Using the poolKeys function with the first 25 bytes of the pool id
+    fn swap_usdc_eth_univ4() -> Swap {
+        let pool_fee_usdc_eth = Bytes::from(BigInt::from(3000).to_signed_bytes_be());
+        let tick_spacing_usdc_eth = Bytes::from(BigInt::from(60).to_signed_bytes_be());
+        let mut static_attributes_usdc_eth: HashMap<String, Bytes> = HashMap::new();
+        static_attributes_usdc_eth.insert("key_lp_fee".into(), pool_fee_usdc_eth);
+        static_attributes_usdc_eth.insert("tick_spacing".into(), tick_spacing_usdc_eth);
+        Swap::new(
+            ProtocolComponent {
+                id: "0xdce6394339af00981949f5f3baf27e3610c76326a700af57e4b3e3ae4977f78d"
+                    .to_string(),
+                protocol_system: "uniswap_v4".to_string(),
+                static_attributes: static_attributes_usdc_eth,
+                ..Default::default()
+            },
+            usdc().clone(),
+            eth().clone(),
+        )
+    }
+
+    fn swap_eth_pepe_univ4() -> Swap {
+        let pool_fee_eth_pepe = Bytes::from(BigInt::from(25000).to_signed_bytes_be());
+        let tick_spacing_eth_pepe = Bytes::from(BigInt::from(500).to_signed_bytes_be());
+        let mut static_attributes_eth_pepe: HashMap<String, Bytes> = HashMap::new();
+        static_attributes_eth_pepe.insert("key_lp_fee".into(), pool_fee_eth_pepe);
+        static_attributes_eth_pepe.insert("tick_spacing".into(), tick_spacing_eth_pepe);
+        Swap::new(
+            ProtocolComponent {
+                id: "0xecd73ecbf77219f21f129c8836d5d686bbc27d264742ddad620500e3e548e2c9"
+                    .to_string(),
+                protocol_system: "uniswap_v4".to_string(),
+                static_attributes: static_attributes_eth_pepe,
+                ..Default::default()
+            },
+            eth().clone(),
+            pepe().clone(),
+        )
+    }
+
+    fn router_address() -> Bytes {
+        Bytes::from_str("0x6bc529DC7B81A031828dDCE2BC419d01FF268C66").unwrap()
+    }
+
+    fn eth_chain() -> Chain {
+        Chain::Ethereum
+    }
+
+    fn get_swap_encoder_registry() -> SwapEncoderRegistry {
+        let executors_addresses =
+            fs::read_to_string("config/test_executor_addresses.json").unwrap();
+        SwapEncoderRegistry::new(eth_chain())
+            .add_default_encoders(Some(executors_addresses))
+            .unwrap()
+    }
+
+    fn get_tycho_router_encoder(user_transfer_type: UserTransferType) -> TychoRouterEncoder {
+        TychoRouterEncoder::new(
+            eth_chain(),
+            get_swap_encoder_registry(),
+            router_address(),
+            user_transfer_type,
+            None,
+            false,
+        )
+        .unwrap()
+    }
+
+    mod router_encoder {
+        use std::{
+            sync::{Arc, Mutex},
+            time::Duration,
+        };
+
+        use alloy::primitives::{keccak256, U256};
+
+        use super::*;
+        use crate::encoding::{
+            evm::{
+                audit::{verify_chain, AuditRecord, CallbackSink},
+                idempotency::IdempotencyCache,
+            },
+            metrics::EncodingMetric,
+            models::{CheckedShares, Fee, GivenToken, StateVersion},
+        };
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_encode_router_calldata_single_swap() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let eth_amount_in = BigUint::from(1000u32);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+
+            let solution = Solution {
+                exact_out: false,
+                given_amount: eth_amount_in.clone(),
+                given_token: eth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                native_action: Some(NativeAction::Wrap),
+                ..Default::default()
+            };
+
+            let transactions = encoder.encode_full_calldata(vec![solution]);
+            assert!(transactions.is_ok());
+            let transactions = transactions.unwrap();
+            assert_eq!(transactions.len(), 1);
+            assert_eq!(transactions[0].value, eth_amount_in);
+            assert_eq!(
+                transactions[0].to,
+                Bytes::from_str("0x6bc529dc7b81a031828ddce2bc419d01ff268c66").unwrap()
+            );
+            // single swap selector
+            assert_eq!(&hex::encode(transactions[0].clone().data)[..8], "5c4b639c");
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_encode_calldata_with_signature() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                checked_amount: BigUint::from(1u32),
+                swaps: vec![Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth(),
+                    dai(),
+                )],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+            let encoded_solution = encoder
+                .encode_solutions(vec![solution.clone()])
+                .unwrap()
+                .remove(0);
+
+            // Solutions without a permit have no typed data, and ignore the signature
+            assert!(encoder
+                .permit_typed_data(&encoded_solution)
+                .unwrap()
+                .is_none());
+            let transaction = encoder
+                .encode_calldata_with_signature(&solution, encoded_solution, vec![])
+                .unwrap();
+            let expected_transaction = encoder
+                .encode_full_calldata(vec![solution])
+                .unwrap()
+                .remove(0);
+            assert_eq!(transaction.to, expected_transaction.to);
+            assert_eq!(transaction.data, expected_transaction.data);
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_encode_router_calldata_single_swap_group() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                exact_out: false,
+                given_token: usdc(),
+                given_amount: BigUint::from_str("1000_000000").unwrap(),
+                checked_token: pepe(),
+                checked_amount: BigUint::from_str("105_152_000000000000000000").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap_usdc_eth_univ4(), swap_eth_pepe_univ4()],
+                ..Default::default()
+            };
+
+            let transactions = encoder.encode_full_calldata(vec![solution]);
+            assert!(transactions.is_ok());
+            let transactions = transactions.unwrap();
+            assert_eq!(transactions.len(), 1);
+            // single swap selector
+            assert_eq!(&hex::encode(transactions[0].clone().data)[..8], "5c4b639c");
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_encode_router_calldata_sequential_swap() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let eth_amount_in = BigUint::from(1000u32);
+            let swap_weth_dai = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+
+            let swap_dai_usdc = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                dai().clone(),
+                usdc().clone(),
+            );
+
+            let solution = Solution {
+                exact_out: false,
+                given_amount: eth_amount_in.clone(),
+                given_token: eth(),
+                checked_token: usdc(),
+                swaps: vec![swap_weth_dai, swap_dai_usdc],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                native_action: Some(NativeAction::Wrap),
+                checked_amount: BigUint::from(1000u32),
+                ..Default::default()
+            };
+
+            let transactions = encoder.encode_full_calldata(vec![solution]);
+            assert!(transactions.is_ok());
+            let transactions = transactions.unwrap();
+            assert_eq!(transactions.len(), 1);
+            assert_eq!(transactions[0].value, eth_amount_in);
+            // sequential swap selector
+            assert_eq!(&hex::encode(transactions[0].clone().data)[..8], "e21dd0d3");
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_encode_router_calldata_over_budget() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_calldata_budget(CalldataBudget::new(200));
+            let swap_weth_dai = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                dai(),
+            );
+            let swap_dai_usdc = Swap::new(
+                ProtocolComponent {
+                    id: "0xAE461cA67B15dc8dc81CE7615e0320dA1A9aB8D5".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                dai(),
+                usdc(),
+            );
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: usdc(),
+                swaps: vec![swap_weth_dai, swap_dai_usdc],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                checked_amount: BigUint::from(1000u32),
+                ..Default::default()
+            };
+
+            let result = encoder.encode_full_calldata(vec![solution]);
+
+            let Err(EncodingError::CalldataTooLarge(report)) = result else {
+                panic!("Expected the calldata to be over budget");
+            };
+            assert_eq!(report.max_bytes, 200);
+            assert!(report.calldata_bytes > 200);
+            assert_eq!(report.swap_bytes.len(), 2);
+            assert!(report.suggestions[0].starts_with("Use a route with fewer hops"));
+        }
+
+        #[test]
+        fn test_encode_solutions_with_audit_log() {
+            let records = Arc::new(Mutex::new(Vec::new()));
+            let records_clone = records.clone();
+            let audit_log =
+                AuditLog::new(Arc::new(CallbackSink::new(move |record: &AuditRecord| {
+                    records_clone
+                        .lock()
+                        .unwrap()
+                        .push(record.clone())
+                })));
+            let encoder =
+                get_tycho_router_encoder(UserTransferType::TransferFrom).with_audit_log(audit_log);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+
+            let encoded_solutions = encoder
+                .encode_solutions(vec![solution.clone(), solution])
+                .unwrap();
+
+            let records = records.lock().unwrap().clone();
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[0].interacting_with, router_address());
+            assert_eq!(
+                records[0].swaps_hash,
+                Bytes::from(keccak256(&encoded_solutions[0].swaps).to_vec())
+            );
+            assert!(verify_chain(&records).is_ok());
+        }
+
+        #[test]
+        fn test_encode_solutions_with_encoding_metrics() {
+            struct RecordingMetrics(Mutex<Vec<EncodingMetric>>);
+            impl EncodingMetrics for RecordingMetrics {
+                fn record(&self, metric: &EncodingMetric) {
+                    self.0
+                        .lock()
+                        .unwrap()
+                        .push(metric.clone());
+                }
+            }
+
+            let metrics = Arc::new(RecordingMetrics(Mutex::new(Vec::new())));
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_encoding_metrics(metrics.clone());
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+
+            encoder
+                .encode_solutions(vec![solution])
+                .unwrap();
+
+            let recorded = metrics.0.lock().unwrap().clone();
+            let stages: Vec<(EncodingStage, Option<String>)> = recorded
+                .iter()
+                .map(|metric| (metric.stage, metric.label.clone()))
+                .collect();
+            assert_eq!(
+                stages,
+                vec![
+                    (EncodingStage::EncodeStrategy, Some("single_swap".to_string())),
+                    (EncodingStage::EncodeSolutions, None),
+                ]
+            );
+            assert!(recorded
+                .iter()
+                .all(|metric| metric.error.is_none()));
+        }
+
+        #[tokio::test]
+        async fn test_encode_solutions_concurrently_isolates_failures() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+            let invalid_solution = Solution { swaps: vec![], ..solution.clone() };
+
+            let results = encoder
+                .encode_solutions_concurrently(
+                    vec![solution.clone(), invalid_solution, solution.clone()],
+                    2,
+                )
+                .await;
+
+            assert_eq!(results.len(), 3);
+            let expected = encoder
+                .encode_solutions(vec![solution])
+                .unwrap()
+                .remove(0);
+            assert_eq!(results[0].as_ref().unwrap().swaps, expected.swaps);
+            assert!(matches!(results[1], Err(EncodingError::FatalError(_))));
+            assert_eq!(results[2].as_ref().unwrap().swaps, expected.swaps);
+        }
+
+        /// Delays the async encoding of every solution by its given amount in milliseconds.
+        struct DelayedEncoder(TychoRouterEncoder);
+
+        #[async_trait]
+        impl TychoEncoder for DelayedEncoder {
+            fn encode_solutions(
+                &self,
+                solutions: Vec<Solution>,
+            ) -> Result<Vec<EncodedSolution>, EncodingError> {
+                self.0.encode_solutions(solutions)
+            }
+
+            async fn encode_solutions_async(
+                &self,
+                solutions: Vec<Solution>,
+            ) -> Result<Vec<EncodedSolution>, EncodingError> {
+                for solution in &solutions {
+                    let delay = u64::try_from(&solution.given_amount).unwrap();
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                }
+                self.0
+                    .encode_solutions_async(solutions)
+                    .await
+            }
+
+            #[allow(deprecated)]
+            fn encode_full_calldata(
+                &self,
+                solutions: Vec<Solution>,
+            ) -> Result<Vec<Transaction>, EncodingError> {
+                self.0.encode_full_calldata(solutions)
+            }
+
+            fn validate_solution(&self, solution: &Solution) -> Result<(), EncodingError> {
+                self.0.validate_solution(solution)
+            }
+        }
+
+        #[tokio::test]
+        async fn test_encode_solutions_streaming_yields_results_when_ready() {
+            let encoder = DelayedEncoder(get_tycho_router_encoder(UserTransferType::TransferFrom));
+            let solution = |delay_ms: u32| Solution {
+                given_amount: BigUint::from(delay_ms),
+                given_token: weth(),
+                checked_token: dai(),
+                checked_amount: BigUint::from(1u32),
+                swaps: vec![Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth(),
+                    dai(),
+                )],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+
+            let results: Vec<(usize, Result<EncodedSolution, EncodingError>)> = encoder
+                .encode_solutions_streaming(
+                    vec![solution(100), solution(1), solution(5000)],
+                    Duration::from_millis(500),
+                )
+                .collect()
+                .await;
+
+            let indices: Vec<usize> = results
+                .iter()
+                .map(|(index, _)| *index)
+                .collect();
+            assert_eq!(indices, vec![1, 0, 2]);
+            assert!(results[0].1.is_ok());
+            assert!(results[1].1.is_ok());
+            assert!(matches!(results[2].1, Err(EncodingError::Timeout(_))));
+        }
+
+        #[test]
+        fn test_encode_solutions_with_permit_batch_requires_permit2() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth(),
+                    dai(),
+                )],
+                ..Default::default()
+            };
+
+            let result = encoder.encode_solutions_with_permit_batch(vec![solution]);
+
+            assert!(matches!(
+                result,
+                Err(EncodingError::InvalidInput(msg)) if msg.starts_with("Batch permits require")
+            ));
+        }
+
+        #[test]
+        fn test_encode_solutions_with_idempotency_key() {
+            let records = Arc::new(Mutex::new(Vec::new()));
+            let records_clone = records.clone();
+            let audit_log =
+                AuditLog::new(Arc::new(CallbackSink::new(move |record: &AuditRecord| {
+                    records_clone
+                        .lock()
+                        .unwrap()
+                        .push(record.clone())
+                })));
+            let cache = Arc::new(IdempotencyCache::new(Duration::from_secs(60), 100));
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_audit_log(audit_log)
+                .with_idempotency_cache(cache.clone());
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                idempotency_key: Some("request-1".to_string()),
+                ..Default::default()
+            };
+
+            let encoded_solutions = encoder
+                .encode_solutions(vec![solution.clone(), solution])
+                .unwrap();
+
+            assert_eq!(encoded_solutions[0].swaps, encoded_solutions[1].swaps);
+            // The retried solution was served from the cache, so it was encoded only once
+            assert_eq!(records.lock().unwrap().len(), 1);
+            let stats = cache.stats().unwrap();
+            assert_eq!(stats.hits, 1);
+            assert_eq!(stats.misses, 1);
+        }
+
+        #[test]
+        fn test_encode_solutions_for_environment() {
+            let staging_router = Bytes::from("0xF62849F9A0B5Bf2913b396098F7c7019b51A820a");
+            let simulation_encoder = TychoRouterEncoder::new(
+                eth_chain(),
+                get_swap_encoder_registry(),
+                staging_router.clone(),
+                UserTransferType::TransferFrom,
+                None,
+                false,
+            )
+            .unwrap();
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+
+            // Without a simulation environment, only production is available
+            let result = encoder
+                .encode_solutions_for_environment(vec![solution.clone()], Environment::Simulation);
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            let encoder = encoder.with_simulation_encoder(simulation_encoder);
+            let production = encoder
+                .encode_solutions_for_environment(vec![solution.clone()], Environment::Production)
+                .unwrap();
+            let simulation = encoder
+                .encode_solutions_for_environment(vec![solution], Environment::Simulation)
+                .unwrap();
+
+            assert_eq!(production[0].interacting_with, router_address());
+            assert_eq!(simulation[0].interacting_with, staging_router);
+        }
+
+        #[test]
+        fn test_multichain_encoder() {
+            let executors_addresses =
+                fs::read_to_string("config/test_executor_addresses.json").unwrap();
+            let registries =
+                SwapEncoderRegistry::new_multichain(Some(executors_addresses)).unwrap();
+            assert!(registries[&Chain::Base]
+                .get_encoder("aerodrome_slipstreams")
+                .is_some());
+            let encoders = registries
+                .into_iter()
+                .map(|(chain, registry)| {
+                    let encoder = TychoRouterEncoder::new(
+                        chain,
+                        registry,
+                        router_address(),
+                        UserTransferType::TransferFrom,
+                        None,
+                        false,
+                    )
+                    .unwrap();
+                    (chain, encoder)
+                })
+                .collect();
+            let encoder = MultiChainTychoRouterEncoder::new(encoders);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+
+            let mut chains = encoder.chains();
+            chains.sort_by_key(|chain| chain.id());
+            assert_eq!(chains, vec![Chain::Ethereum, Chain::Base]);
+
+            let encoded = encoder
+                .encode_solutions(Chain::Ethereum, vec![solution.clone()])
+                .unwrap();
+            assert_eq!(encoded[0].interacting_with, router_address());
+
+            // No uniswap_v2 executor is configured on Base
+            assert!(encoder
+                .encode_solutions(Chain::Base, vec![solution.clone()])
+                .is_err());
+            let result = encoder.encode_solutions(Chain::Unichain, vec![solution]);
+            assert!(matches!(
+                result,
+                Err(EncodingError::InvalidInput(msg)) if msg.starts_with("No encoder configured")
+            ));
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_encode_router_calldata_with_default_slippage() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_default_slippage(SlippageConfig::new(30).unwrap());
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                usdc().clone(),
+            );
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: usdc(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                expected_amount: Some(BigUint::from(1_000_001u32)),
+                ..Default::default()
+            };
+
+            let transactions = encoder
+                .encode_full_calldata(vec![solution.clone()])
+                .unwrap();
+            // 1_000_001 * 0.997 = 997_000.997, rounded up so the slippage stays within 30 bps
+            let min_amount_out = U256::from_be_slice(&transactions[0].data[4 + 96..4 + 128]);
+            assert_eq!(min_amount_out, U256::from(997_001u32));
+
+            // The slippage of the solution takes precedence over the default one
+            let solution = Solution { slippage: Some(SlippageConfig::new(0).unwrap()), ..solution };
+            let transactions = encoder
+                .encode_full_calldata(vec![solution])
+                .unwrap();
+            let min_amount_out = U256::from_be_slice(&transactions[0].data[4 + 96..4 + 128]);
+            assert_eq!(min_amount_out, U256::from(1_000_001u32));
+        }
+
+        #[test]
+        fn test_checked_amount_guard() {
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                usdc().clone(),
+            );
+            // The checked amount was scaled with 18 decimals instead of the 6 of USDC
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: usdc(),
+                checked_amount: BigUint::from(997_000_000_000_000_000u64),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                expected_amount: Some(BigUint::from(1_000_000u32)),
+                ..Default::default()
+            };
+
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let result = encoder.encode_solutions(vec![solution.clone()]);
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .without_checked_amount_guard();
+            assert!(encoder
+                .encode_solutions(vec![solution])
+                .is_ok());
+        }
+
+        fn bebop_swap(token_in: Bytes, token_out: Bytes) -> Swap {
+            Swap::new(
+                ProtocolComponent {
+                    id: "bebop-rfq".to_string(),
+                    protocol_system: "rfq:bebop".to_string(),
+                    ..Default::default()
+                },
+                token_in,
+                token_out,
+            )
+        }
+
+        #[test]
+        fn test_resolve_rfq_native_boundaries_inserts_wrap() {
+            // ETH ──(bebop, quoted for WETH)──> USDC
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                given_token: eth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: usdc(),
+                swaps: vec![bebop_swap(weth(), usdc())],
+                ..Default::default()
+            };
+
+            let resolved = encoder.resolve_rfq_native_boundaries(&solution);
+
+            assert_eq!(resolved.native_action, Some(NativeAction::Wrap));
+            assert_eq!(*resolved.swaps[0].token_in(), weth());
+            assert!(encoder
+                .validate_solution(&solution)
+                .is_ok());
+        }
+
+        #[test]
+        fn test_resolve_rfq_native_boundaries_inserts_unwrap() {
+            // USDC ──(bebop, quoted for WETH)──> ETH
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                given_token: usdc(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: eth(),
+                swaps: vec![bebop_swap(usdc(), weth())],
+                ..Default::default()
+            };
+
+            let resolved = encoder.resolve_rfq_native_boundaries(&solution);
+
+            assert_eq!(resolved.native_action, Some(NativeAction::Unwrap));
+            assert_eq!(*resolved.swaps[0].token_out(), weth());
+        }
+
+        #[test]
+        fn test_resolve_rfq_native_boundaries_requotes_native() {
+            // WETH ──(bebop, quoted for ETH)──> USDC is not possible without unwrapping before the
+            // first swap (and USDC ──(bebop, quoted for ETH)──> WETH without wrapping after the
+            // last one), so the quotes are requested for WETH instead
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: usdc(),
+                swaps: vec![bebop_swap(eth(), usdc())],
+                ..Default::default()
+            };
+            let resolved = encoder.resolve_rfq_native_boundaries(&solution);
+            assert_eq!(resolved.native_action, None);
+            assert_eq!(*resolved.swaps[0].token_in(), weth());
+
+            let solution = Solution {
+                given_token: usdc(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: weth(),
+                swaps: vec![bebop_swap(usdc(), eth())],
+                ..Default::default()
+            };
+            let resolved = encoder.resolve_rfq_native_boundaries(&solution);
+            assert_eq!(resolved.native_action, None);
+            assert_eq!(*resolved.swaps[0].token_out(), weth());
+        }
+
+        #[test]
+        fn test_resolve_rfq_native_boundaries_intermediate_swap() {
+            // USDC ──(bebop, quoted for ETH)──> WETH ──(USV2)──> DAI
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swap_weth_dai = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                dai(),
+            );
+            let solution = Solution {
+                given_token: usdc(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: dai(),
+                swaps: vec![bebop_swap(usdc(), eth()), swap_weth_dai.clone()],
+                ..Default::default()
+            };
+
+            let resolved = encoder.resolve_rfq_native_boundaries(&solution);
+
+            assert_eq!(*resolved.swaps[0].token_out(), weth());
+            assert_eq!(resolved.swaps[1], swap_weth_dai);
+        }
+
+        fn curve_swap(coins: &str, token_in: Bytes, token_out: Bytes) -> Swap {
+            Swap::new(
+                ProtocolComponent {
+                    id: "0xDC24316b9AE028F1497c275EB9192a3Ea0f67022".to_string(),
+                    protocol_system: "vm:curve".to_string(),
+                    static_attributes: HashMap::from([(
+                        "coins".to_string(),
+                        Bytes::from(coins.as_bytes().to_vec()),
+                    )]),
+                    ..Default::default()
+                },
+                token_in,
+                token_out,
+            )
+        }
+
+        #[test]
+        fn test_resolve_curve_native_first_hops() {
+            // ETH ──(wrap)──> WETH ──(curve stETH pool, takes raw ETH)──> STETH
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let steth = Bytes::from("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84");
+            let raw_eth_coins = r#"["0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee","0xae7ab96520de3a18e5e111b5eaab095312d7fe84"]"#;
+            let solution = Solution {
+                given_token: eth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: steth.clone(),
+                swaps: vec![curve_swap(raw_eth_coins, weth(), steth.clone())],
+                native_action: Some(NativeAction::Wrap),
+                ..Default::default()
+            };
+
+            let resolved = encoder.resolve_curve_native_first_hops(&solution);
+
+            assert_eq!(resolved.native_action, None);
+            assert_eq!(*resolved.swaps[0].token_in(), eth());
+
+            // Pools holding WETH keep the wrap
+            let weth_coins = r#"["0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2","0xae7ab96520de3a18e5e111b5eaab095312d7fe84"]"#;
+            let solution =
+                Solution { swaps: vec![curve_swap(weth_coins, weth(), steth.clone())], ..solution };
+
+            let resolved = encoder.resolve_curve_native_first_hops(&solution);
+
+            assert_eq!(resolved.native_action, Some(NativeAction::Wrap));
+            assert_eq!(*resolved.swaps[0].token_in(), weth());
+
+            // unless they wrap the ETH themselves (`use_eth`)
+            let curve_pool = curve_swap(weth_coins, weth(), steth.clone())
+                .component()
+                .clone();
+            let mut static_attributes = curve_pool.static_attributes.clone();
+            static_attributes.insert("use_eth".to_string(), Bytes::from(vec![1u8]));
+            let use_eth_swap =
+                Swap::new(ProtocolComponent { static_attributes, ..curve_pool }, weth(), steth);
+            let solution = Solution { swaps: vec![use_eth_swap], ..solution };
+
+            let resolved = encoder.resolve_curve_native_first_hops(&solution);
+
+            assert_eq!(resolved.native_action, None);
+            assert_eq!(*resolved.swaps[0].token_in(), eth());
+        }
+
+        fn usv4_swap(token_in: Bytes, token_out: Bytes) -> Swap {
+            Swap::new(
+                ProtocolComponent {
+                    id: "0xdce6394339af00981949f5f3baf27e3610c76326a700af57e4b3e3ae4977f78d"
+                        .to_string(),
+                    protocol_system: "uniswap_v4".to_string(),
+                    ..Default::default()
+                },
+                token_in,
+                token_out,
+            )
+        }
+
+        #[test]
+        fn test_infer_wrap_for_wrapped_keyed_v4_pool() {
+            // ETH ──(wrap)──> WETH ──(USV4 pool keyed on WETH)──> USDC
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                given_token: eth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: usdc(),
+                swaps: vec![usv4_swap(weth(), usdc())],
+                ..Default::default()
+            };
+
+            let resolved = encoder.resolve_native_handling(&solution);
+
+            assert_eq!(resolved.native_action, Some(NativeAction::Wrap));
+            assert_eq!(*resolved.swaps[0].token_in(), weth());
+        }
+
+        #[test]
+        fn test_infer_native_action() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            // ETH ──(wrap)──> WETH ──(USV2)──> USDC
+            let wrap_solution = Solution {
+                given_token: eth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: usdc(),
+                swaps: vec![weth_usdc_univ2()],
+                ..Default::default()
+            };
+            let resolved = encoder.resolve_native_handling(&wrap_solution);
+            assert_eq!(resolved.native_action, Some(NativeAction::Wrap));
+
+            // USDC ──(USV2)──> DAI ──(USV2)──> WETH ──(unwrap)──> ETH
+            let dai_weth_univ2 = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                dai(),
+                weth(),
+            );
+            let unwrap_solution = Solution {
+                given_token: usdc(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: eth(),
+                checked_amount: BigUint::from(1u32),
+                receiver: Bytes::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
+                swaps: vec![usdc_dai_univ2(), dai_weth_univ2],
+                ..Default::default()
+            };
+            let encoded = encoder
+                .encode_solutions(vec![unwrap_solution.clone()])
+                .unwrap();
+            assert_eq!(encoded[0].native_action, Some(NativeAction::Unwrap));
+            assert!(encoded[0]
+                .function_signature
+                .starts_with("sequentialSwap"));
+
+            // Without inference, the native action must be set explicitly
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .without_native_action_inference();
+            let resolved = encoder.resolve_native_handling(&wrap_solution);
+            assert_eq!(resolved.native_action, None);
+            let resolved = encoder.resolve_native_handling(&unwrap_solution);
+            assert_eq!(resolved.native_action, None);
+        }
+
+        #[test]
+        fn test_validate_wrapped_given_into_native_keyed_v4_pool() {
+            // WETH ──(USV4 pool keyed on ETH)──> USDC
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: usdc(),
+                swaps: vec![usv4_swap(eth(), usdc())],
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        }
+
+        #[test]
+        fn test_encode_solutions_with_callback_allowlist() {
+            let registry = get_swap_encoder_registry();
+            let callback_allowlist = CallbackAllowlist::new(eth_chain(), &registry).unwrap();
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_callback_allowlist(callback_allowlist);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+            let solution = Solution {
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: dai(),
+                checked_amount: BigUint::from(1u32),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+
+            let encoded_solutions = encoder
+                .encode_solutions(vec![solution])
+                .unwrap();
+
+            let executor = registry
+                .get_encoder("uniswap_v2")
+                .unwrap()
+                .executor_address()
+                .clone();
+            assert_eq!(encoded_solutions[0].callback_allowlist, Some(vec![executor]));
+        }
+
+        #[test]
+        fn test_encode_solutions_with_state_versions() {
+            let guard = StalenessGuard::new(5);
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_staleness_guard(guard.clone());
+            let version = StateVersion::new(21_000_000).with_block_hash(Bytes::from(
+                "0x5d3bd0e2a7c9d3d6ab1e6a5d0f3f1b9f0c7e6a9e8d7c6b5a4f3e2d1c0b9a8f7e",
+            ));
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            )
+            .state_version(version.clone());
+            let solution = Solution {
+                exact_out: false,
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+
+            let encoded_solutions = encoder
+                .encode_solutions(vec![solution.clone()])
+                .unwrap();
+            assert_eq!(encoded_solutions[0].state_versions, vec![Some(version)]);
+
+            // The stream moved on, the state of the swap is now outdated
+            guard.update_latest_block(21_000_006);
+            let result = encoder.encode_solutions(vec![solution]);
+            assert!(matches!(result, Err(EncodingError::RecoverableError(_))));
+        }
+
+        #[test]
+        fn test_encode_solutions_with_token_safety_checker() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_token_safety_checker(
+                    TokenSafetyChecker::new(Chain::Ethereum).with_denylist([dai()]),
+                );
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+            let solution = Solution {
+                given_amount: BigUint::from(1000u32),
+                given_token: weth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            };
+
+            let result = encoder.encode_solutions(vec![solution]);
+
+            assert!(matches!(
+                result,
+                Err(EncodingError::UnsafeToken(report)) if report.token == dai()
+            ));
+        }
+
+        #[test]
+        fn test_encode_router_calldata_split_swap_group() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let mut swap_usdc_eth = swap_usdc_eth_univ4();
+            swap_usdc_eth = swap_usdc_eth.split(0.5); // Set split to 50%
+            let solution = Solution {
+                exact_out: false,
+                given_token: usdc(),
+                given_amount: BigUint::from_str("1000_000000").unwrap(),
+                checked_token: eth(),
+                checked_amount: BigUint::from_str("105_152_000000000000000000").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![swap_usdc_eth, swap_usdc_eth_univ4()],
+                ..Default::default()
+            };
+
+            let encoded_solution_res = encoder.encode_solution(&solution);
+            assert!(encoded_solution_res.is_ok());
+
+            let encoded_solution = encoded_solution_res.unwrap();
+            assert!(encoded_solution
+                .function_signature
+                .contains("splitSwap"));
+        }
+
+        #[test]
+        fn test_encode_solutions_is_deterministic() {
+            let uniswap_v2_swap = |pool: &str, token_in: Bytes, token_out: Bytes| {
+                Swap::new(
+                    ProtocolComponent {
+                        id: pool.to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    token_in,
+                    token_out,
+                )
+            };
+            // Split through several intermediary tokens, whose indices must not depend on the
+            // iteration order of any collection
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth(),
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: wbtc(),
+                checked_amount: BigUint::from_str("3_000000").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps: vec![
+                    uniswap_v2_swap("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11", weth(), dai())
+                        .split(0.3),
+                    uniswap_v2_swap("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc", weth(), usdc())
+                        .split(0.5),
+                    uniswap_v2_swap("0xA43fe16908251ee70EF74718545e4FE6C5cCEc9f", weth(), pepe()),
+                    uniswap_v2_swap("0x231B7589426Ffe1b75405526fC32aC09D44364c4", dai(), wbtc()),
+                    uniswap_v2_swap("0x004375Dff511095CC5A197A54140a24eFEF3A416", usdc(), wbtc()),
+                    uniswap_v2_swap("0xCb6e1EA4C2E2C35e3F5b03b8B4A8eDbA0B8D33dE", pepe(), wbtc()),
+                ],
+                ..Default::default()
+            };
+
+            let first = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .encode_solutions(vec![solution.clone()])
+                .unwrap()
+                .remove(0);
+            for _ in 0..100 {
+                // A new encoder every time, so no collection is reused between runs
+                let encoded = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                    .encode_solutions(vec![solution.clone()])
+                    .unwrap()
+                    .remove(0);
+                assert_eq!(encoded.swaps, first.swaps);
+                assert_eq!(encoded.n_tokens, first.n_tokens);
+                assert_eq!(encoded.function_signature, first.function_signature);
+            }
+        }
+
+        #[tokio::test]
+        async fn test_encode_solutions_async() {
+            let uniswap_v2_swap = |pool: &str, token_in: Bytes, token_out: Bytes| {
+                Swap::new(
+                    ProtocolComponent {
+                        id: pool.to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    token_in,
+                    token_out,
+                )
+            };
+            let solution = |swaps: Vec<Swap>| Solution {
+                exact_out: false,
+                given_token: weth(),
+                given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                checked_token: wbtc(),
+                checked_amount: BigUint::from_str("3_000000").unwrap(),
+                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                swaps,
+                ..Default::default()
+            };
+            let solutions = vec![
+                // Single
+                solution(vec![uniswap_v2_swap(
+                    "0xBb2b8038a1640196FbE3e38816F3e67Cba72D940",
+                    weth(),
+                    wbtc(),
+                )]),
+                // Sequential
+                solution(vec![
+                    uniswap_v2_swap("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11", weth(), dai()),
+                    uniswap_v2_swap("0x231B7589426Ffe1b75405526fC32aC09D44364c4", dai(), wbtc()),
+                ]),
+                // Split
+                solution(vec![
+                    uniswap_v2_swap("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11", weth(), dai())
+                        .split(0.5),
+                    uniswap_v2_swap("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc", weth(), usdc()),
+                    uniswap_v2_swap("0x231B7589426Ffe1b75405526fC32aC09D44364c4", dai(), wbtc()),
+                    uniswap_v2_swap("0x004375Dff511095CC5A197A54140a24eFEF3A416", usdc(), wbtc()),
+                ]),
+            ];
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+
+            // Runs on a single threaded runtime
+            let encoded_async = encoder
+                .encode_solutions_async(solutions.clone())
+                .await
+                .unwrap();
+            let encoded = encoder
+                .encode_solutions(solutions)
+                .unwrap();
+
+            assert_eq!(encoded_async.len(), 3);
+            for (encoded_async, encoded) in encoded_async.iter().zip(encoded.iter()) {
+                assert_eq!(encoded_async.function_signature, encoded.function_signature);
+                assert_eq!(encoded_async.swaps, encoded.swaps);
+                assert_eq!(encoded_async.n_tokens, encoded.n_tokens);
+            }
+        }
+
+        fn exact_out_solution(protocol_system: &str) -> Solution {
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: protocol_system.to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                dai(),
+            );
+            Solution {
+                exact_out: true,
+                given_token: dai(),
+                given_amount: BigUint::from_str("3000_000000000000000000").unwrap(),
+                checked_token: weth(),
+                checked_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+                swaps: vec![swap],
+                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn test_validate_passes_for_exact_out() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = exact_out_solution("uniswap_v2");
+
+            assert!(encoder
+                .validate_solution(&solution)
+                .is_ok());
+        }
+
+        #[test]
+        fn test_validate_fails_for_exact_out_unsupported_protocol() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = exact_out_solution("ekubo_v2");
+
+            let result = encoder.validate_solution(&solution);
+            assert_eq!(
+                result.err().unwrap(),
+                EncodingError::InvalidInput(
+                    "Exact out swaps are not supported for protocol ekubo_v2".to_string()
+                )
+            );
+        }
+
+        #[test]
+        fn test_validate_fails_for_exact_out_multiple_swaps() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let mut solution = exact_out_solution("uniswap_v2");
+            solution
+                .swaps
+                .push(solution.swaps[0].clone());
+
+            let result = encoder.validate_solution(&solution);
+            assert_eq!(
+                result.err().unwrap(),
+                EncodingError::InvalidInput(
+                    "Exact out solutions must have exactly one swap. Found 2".to_string()
+                )
+            );
+        }
+
+        #[test]
+        fn test_validate_fails_for_exact_out_wrong_tokens() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let mut solution = exact_out_solution("uniswap_v3");
+            // The given token of an exact out solution is the token bought
+            solution.given_token = weth();
+            solution.checked_token = dai();
+
+            assert!(matches!(
+                encoder.validate_solution(&solution),
+                Err(EncodingError::InvalidInput(_))
+            ));
+        }
+
+        #[test]
+        fn test_validate_fails_for_exact_out_wrap() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let mut solution = exact_out_solution("uniswap_v2");
+            solution.checked_token = eth();
+            solution.native_action = Some(NativeAction::Wrap);
+
+            let result = encoder.validate_solution(&solution);
+            assert_eq!(
+                result.err().unwrap(),
+                EncodingError::InvalidInput(
+                    "Exact out solutions do not support wrapping the native token".to_string()
+                )
+            );
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_encode_router_calldata_exact_out_single_swap() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = exact_out_solution("uniswap_v2");
+
+            let transactions = encoder
+                .encode_full_calldata(vec![solution])
+                .unwrap();
+            assert_eq!(transactions[0].value, BigUint::ZERO);
+            let expected_selector = &keccak256(
+                "exactOutSingleSwap(uint256,address,address,uint256,bool,address,bool,bytes)"
+                    .as_bytes(),
+            )[..4];
+            assert_eq!(&transactions[0].data[..4], expected_selector);
+        }
+
+        #[test]
+        fn test_validate_passes_for_wrap() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+
+            let solution = Solution {
+                exact_out: false,
+                given_token: eth(),
+                checked_token: dai(),
+                swaps: vec![swap],
+                native_action: Some(NativeAction::Wrap),
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn test_validate_fails_for_wrap_wrong_input() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth().clone(),
+                dai().clone(),
+            );
+
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth(),
+                swaps: vec![swap],
+                native_action: Some(NativeAction::Wrap),
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_err());
+            assert_eq!(
+                result.err().unwrap(),
+                EncodingError::FatalError(
+                    "Native token must be the input token in order to wrap".to_string()
+                )
+            );
+        }
+
+        #[test]
+        fn test_validate_fails_for_wrap_wrong_first_swap() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                eth().clone(),
+                dai().clone(),
+            );
+
+            let solution = Solution {
+                exact_out: false,
+                given_token: eth(),
+                swaps: vec![swap],
+                native_action: Some(NativeAction::Wrap),
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_err());
+            assert_eq!(
+                result.err().unwrap(),
+                EncodingError::FatalError(
+                    "Wrapped token must be the first swap's input in order to wrap".to_string()
+                )
+            );
+        }
+
+        #[test]
+        fn test_validate_fails_no_swaps() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = Solution {
+                exact_out: false,
+                given_token: eth(),
+                swaps: vec![],
+                native_action: Some(NativeAction::Wrap),
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_err());
+            assert_eq!(
+                result.err().unwrap(),
+                EncodingError::FatalError("No swaps found in solution".to_string())
+            );
+        }
+
+        #[test]
+        fn test_validate_passes_for_unwrap() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                dai().clone(),
+                weth().clone(),
+            );
+
+            let solution = Solution {
+                exact_out: false,
+                checked_token: eth(),
+                swaps: vec![swap],
+                native_action: Some(NativeAction::Unwrap),
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn test_validate_fails_for_unwrap_wrong_output() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swap = Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                dai().clone(),
+                weth().clone(),
+            );
+
+            let solution = Solution {
+                exact_out: false,
+                given_token: dai(),
+                checked_token: weth(),
+                swaps: vec![swap],
+                native_action: Some(NativeAction::Unwrap),
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_err());
+            assert_eq!(
+                result.err().unwrap(),
+                EncodingError::FatalError(
+                    "Native token must be the output token in order to unwrap".to_string()
+                )
+            );
+        }
 
         #[test]
-        #[allow(deprecated)]
-        fn test_encode_router_calldata_single_swap() {
+        fn test_validate_fails_for_unwrap_wrong_last_swap() {
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let eth_amount_in = BigUint::from(1000u32);
             let swap = Swap::new(
                 ProtocolComponent {
                     id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
                     protocol_system: "uniswap_v2".to_string(),
                     ..Default::default()
                 },
-                weth().clone(),
                 dai().clone(),
+                eth().clone(),
             );
 
             let solution = Solution {
                 exact_out: false,
-                given_amount: eth_amount_in.clone(),
-                given_token: eth(),
-                checked_token: dai(),
+                checked_token: eth(),
                 swaps: vec![swap],
-                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
-                native_action: Some(NativeAction::Wrap),
+                native_action: Some(NativeAction::Unwrap),
                 ..Default::default()
             };
 
-            let transactions = encoder.encode_full_calldata(vec![solution]);
-            assert!(transactions.is_ok());
-            let transactions = transactions.unwrap();
-            assert_eq!(transactions.len(), 1);
-            assert_eq!(transactions[0].value, eth_amount_in);
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_err());
             assert_eq!(
-                transactions[0].to,
-                Bytes::from_str("0x6bc529dc7b81a031828ddce2bc419d01ff268c66").unwrap()
+                result.err().unwrap(),
+                EncodingError::FatalError(
+                    "Wrapped token must be the last swap's output in order to unwrap".to_string()
+                )
             );
-            // single swap selector
-            assert_eq!(&hex::encode(transactions[0].clone().data)[..8], "5c4b639c");
         }
 
         #[test]
-        #[allow(deprecated)]
-        fn test_encode_router_calldata_single_swap_group() {
+        fn test_validate_cyclical_swap() {
+            // This validation passes because the cyclical swap is the first and last token
+            //      50% ->  WETH
+            // DAI -              -> DAI
+            //      50% -> WETH
+            // (some of the pool addresses in this test are fake)
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swaps = vec![
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    dai().clone(),
+                    weth().clone(),
+                ),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0x0000000000000000000000000000000000000000".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    dai().clone(),
+                    weth().clone(),
+                ),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0x0000000000000000000000000000000000000000".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth().clone(),
+                    dai().clone(),
+                ),
+            ];
+
             let solution = Solution {
                 exact_out: false,
-                given_token: usdc(),
-                given_amount: BigUint::from_str("1000_000000").unwrap(),
-                checked_token: pepe(),
-                checked_amount: BigUint::from_str("105_152_000000000000000000").unwrap(),
-                sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
-                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
-                swaps: vec![swap_usdc_eth_univ4(), swap_eth_pepe_univ4()],
+                given_token: dai(),
+                checked_token: dai(),
+                swaps,
                 ..Default::default()
             };
 
-            let transactions = encoder.encode_full_calldata(vec![solution]);
-            assert!(transactions.is_ok());
-            let transactions = transactions.unwrap();
-            assert_eq!(transactions.len(), 1);
-            // single swap selector
-            assert_eq!(&hex::encode(transactions[0].clone().data)[..8], "5c4b639c");
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_ok());
         }
 
         #[test]
-        #[allow(deprecated)]
-        fn test_encode_router_calldata_sequential_swap() {
+        fn test_validate_cyclical_swap_fail() {
+            // This test should fail because the cyclical swap is not the first and last token
+            // DAI -> WETH -> USDC -> DAI -> WBTC
+            // (some of the pool addresses in this test are fake)
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let eth_amount_in = BigUint::from(1000u32);
-            let swap_weth_dai = Swap::new(
-                ProtocolComponent {
-                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                    protocol_system: "uniswap_v2".to_string(),
-                    ..Default::default()
-                },
-                weth().clone(),
-                dai().clone(),
-            );
+            let swaps = vec![
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    dai().clone(),
+                    weth().clone(),
+                ),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth().clone(),
+                    usdc().clone(),
+                ),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0x0000000000000000000000000000000000000000".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    usdc().clone(),
+                    dai().clone(),
+                ),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0x0000000000000000000000000000000000000000".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    dai().clone(),
+                    wbtc().clone(),
+                ),
+            ];
 
-            let swap_dai_usdc = Swap::new(
-                ProtocolComponent {
-                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                    protocol_system: "uniswap_v2".to_string(),
-                    ..Default::default()
-                },
-                dai().clone(),
-                usdc().clone(),
-            );
+            let solution = Solution {
+                exact_out: false,
+                given_token: dai(),
+                checked_token: wbtc(),
+                swaps,
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_err());
+            assert_eq!(
+            result.err().unwrap(),
+            EncodingError::FatalError(
+                "Cyclical swaps are only allowed if they are the first and last token of a solution".to_string()
+            )
+        );
+        }
+        #[test]
+        fn test_validate_cyclical_swap_split_output() {
+            // This validation passes because it is a valid cyclical swap
+            //             -> WETH
+            // WETH -> DAI
+            //             -> WETH
+            // (some of the pool addresses in this test are fake)
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swaps = vec![
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth(),
+                    dai(),
+                ),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0x0000000000000000000000000000000000000000".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    dai(),
+                    weth(),
+                )
+                .split(0.5),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0x0000000000000000000000000000000000000000".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    dai(),
+                    weth(),
+                ),
+            ];
+
+            let solution = Solution {
+                exact_out: false,
+                given_token: weth(),
+                checked_token: weth(),
+                swaps,
+                ..Default::default()
+            };
+
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn test_validate_cyclical_swap_native_action_fail() {
+            // This validation fails because there is a native action with a valid cyclical swap
+            // ETH -> WETH -> DAI -> WETH
+            // (some of the pool addresses in this test are fake)
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let swaps = vec![
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    weth(),
+                    dai(),
+                ),
+                Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    dai(),
+                    weth(),
+                ),
+            ];
 
             let solution = Solution {
                 exact_out: false,
-                given_amount: eth_amount_in.clone(),
                 given_token: eth(),
-                checked_token: usdc(),
-                swaps: vec![swap_weth_dai, swap_dai_usdc],
-                receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+                checked_token: weth(),
+                swaps,
                 native_action: Some(NativeAction::Wrap),
-                checked_amount: BigUint::from(1000u32),
                 ..Default::default()
             };
 
-            let transactions = encoder.encode_full_calldata(vec![solution]);
-            assert!(transactions.is_ok());
-            let transactions = transactions.unwrap();
-            assert_eq!(transactions.len(), 1);
-            assert_eq!(transactions[0].value, eth_amount_in);
-            // sequential swap selector
-            assert_eq!(&hex::encode(transactions[0].clone().data)[..8], "e21dd0d3");
+            let result = encoder.validate_solution(&solution);
+
+            assert!(result.is_err());
+            assert_eq!(
+                result.err().unwrap(),
+                EncodingError::FatalError(
+                    "Wrapping/Unwrapping is not available in cyclical swaps"
+                        .to_string()
+                        .to_string()
+                )
+            );
         }
 
         #[test]
-        fn test_encode_router_calldata_split_swap_group() {
-            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let mut swap_usdc_eth = swap_usdc_eth_univ4();
-            swap_usdc_eth = swap_usdc_eth.split(0.5); // Set split to 50%
+        fn test_encode_cyclic_v4_group_with_single_strategy() {
+            // USDC ──(USV4)──> ETH ──(USV4)──> USDC, grouped as a single swap
+            let swap_usdc_eth = swap_usdc_eth_univ4();
+            let swap_eth_usdc = Swap::new(swap_usdc_eth.component().clone(), eth(), usdc());
             let solution = Solution {
-                exact_out: false,
                 given_token: usdc(),
-                given_amount: BigUint::from_str("1000_000000").unwrap(),
-                checked_token: eth(),
-                checked_amount: BigUint::from_str("105_152_000000000000000000").unwrap(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: usdc(),
+                checked_amount: BigUint::from(990u32),
                 sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
                 receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
-                swaps: vec![swap_usdc_eth, swap_usdc_eth_univ4()],
+                swaps: vec![swap_usdc_eth, swap_eth_usdc],
                 ..Default::default()
             };
 
-            let encoded_solution_res = encoder.encode_solution(&solution);
-            assert!(encoded_solution_res.is_ok());
-
-            let encoded_solution = encoded_solution_res.unwrap();
-            assert!(encoded_solution
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let encoded_solutions = encoder
+                .encode_solutions(vec![solution.clone()])
+                .unwrap();
+            assert!(encoded_solutions[0]
                 .function_signature
-                .contains("splitSwap"));
+                .starts_with("singleSwap("));
+
+            // With the profit check, the checked amount must be above the given amount
+            let encoder =
+                get_tycho_router_encoder(UserTransferType::TransferFrom).with_cyclic_profit_check();
+            assert!(matches!(
+                encoder.validate_solution(&solution),
+                Err(EncodingError::InvalidInput(_))
+            ));
+            let profitable_solution =
+                Solution { checked_amount: BigUint::from(1001u32), ..solution };
+            assert!(encoder
+                .validate_solution(&profitable_solution)
+                .is_ok());
         }
 
         #[test]
-        fn test_validate_fails_for_exact_out() {
-            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+        fn test_validate_solution_support_matrix() {
+            // The given ETH is swapped on a Uniswap V2 pool, whose executor only supports WETH
             let solution = Solution {
-                exact_out: true, // This should cause an error
+                given_token: eth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: dai(),
+                checked_amount: BigUint::from(1u32),
+                swaps: vec![Swap::new(
+                    ProtocolComponent {
+                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                        protocol_system: "uniswap_v2".to_string(),
+                        ..Default::default()
+                    },
+                    eth(),
+                    dai(),
+                )],
                 ..Default::default()
             };
-            let result = encoder.validate_solution(&solution);
+            let support_matrix =
+                SupportMatrix::new(eth_chain(), &get_swap_encoder_registry(), None).unwrap();
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_support_matrix(support_matrix);
 
-            assert!(result.is_err());
-            assert_eq!(
-                result.err().unwrap(),
-                EncodingError::FatalError(
-                    "Currently only exact input solutions are supported".to_string()
-                )
-            );
+            assert!(matches!(
+                encoder.validate_solution(&solution),
+                Err(EncodingError::InvalidInput(msg)) if msg.contains("takes or outputs the native")
+            ));
         }
 
         #[test]
-        fn test_validate_passes_for_wrap() {
+        fn test_supported_protocols() {
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swap = Swap::new(
-                ProtocolComponent {
-                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                    protocol_system: "uniswap_v2".to_string(),
-                    ..Default::default()
-                },
-                weth().clone(),
-                dai().clone(),
+            let protocols = encoder.supported_protocols();
+
+            let uniswap_v2 = protocols
+                .iter()
+                .find(|protocol| protocol.system == "uniswap_v2")
+                .unwrap();
+            assert_eq!(
+                uniswap_v2.executor_address,
+                Bytes::from_str("0x5615deb798bb3e4dfa0139dfa1b3d433cc23b72f").unwrap()
+            );
+            assert!(uniswap_v2.capabilities.exact_out);
+            assert!(!uniswap_v2.capabilities.grouping);
+            assert_eq!(
+                protocols.len(),
+                get_swap_encoder_registry()
+                    .executor_addresses()
+                    .len()
             );
+        }
 
+        #[test]
+        fn test_encode_with_reloaded_registry() {
+            let shared_registry = SharedSwapEncoderRegistry::new(get_swap_encoder_registry());
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_shared_registry(shared_registry.clone());
             let solution = Solution {
-                exact_out: false,
-                given_token: eth(),
-                checked_token: dai(),
-                swaps: vec![swap],
-                native_action: Some(NativeAction::Wrap),
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: usdc(),
+                checked_amount: BigUint::from(1u32),
+                receiver: Bytes::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
+                swaps: vec![weth_usdc_univ2()],
                 ..Default::default()
             };
+            let encoded = encoder
+                .encode_solutions(vec![solution.clone()])
+                .unwrap();
+            assert!(hex::encode(&encoded[0].swaps)
+                .starts_with("5615deb798bb3e4dfa0139dfa1b3d433cc23b72f"));
 
-            let result = encoder.validate_solution(&solution);
+            // The USV2 executor was redeployed
+            shared_registry
+                .reload(
+                    r#"{"ethereum": {"uniswap_v2": "0x1d1499e622D69689cdf9004d05Ec547d650Ff211"}}"#,
+                )
+                .unwrap();
 
-            assert!(result.is_ok());
+            let encoded = encoder
+                .encode_solutions(vec![solution])
+                .unwrap();
+            assert!(hex::encode(&encoded[0].swaps)
+                .starts_with("1d1499e622d69689cdf9004d05ec547d650ff211"));
+            assert_eq!(encoder.supported_protocols().len(), 1);
         }
 
         #[test]
-        fn test_validate_fails_for_wrap_wrong_input() {
+        fn test_encode_swaps_only() {
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swap = Swap::new(
+            let usv2_executor =
+                Bytes::from_str("0x5615deb798bb3e4dfa0139dfa1b3d433cc23b72f").unwrap();
+
+            let encoded = encoder
+                .encode_swaps_only(&weth_dai_solution(vec![weth_usdc_univ2(), usdc_dai_univ2()]))
+                .unwrap();
+            assert_eq!(encoded.swaps, ple_encode(encoded.segments.clone()).unwrap());
+            assert_eq!(encoded.segments.len(), 2);
+            assert!(encoded.segments[1].starts_with(&usv2_executor.to_vec()));
+            assert_eq!(encoded.executors, vec![usv2_executor.clone(), usv2_executor.clone()]);
+
+            // A single swap is a single segment
+            let encoded = encoder
+                .encode_swaps_only(&Solution {
+                    given_token: weth(),
+                    given_amount: BigUint::from(1000u32),
+                    checked_token: usdc(),
+                    checked_amount: BigUint::from(1u32),
+                    swaps: vec![weth_usdc_univ2()],
+                    receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2")
+                        .unwrap(),
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(encoded.segments, vec![encoded.swaps.clone()]);
+            assert_eq!(encoded.executors, vec![usv2_executor.clone()]);
+
+            // The executor table is not part of the segments
+            let encoded = get_tycho_router_encoder(UserTransferType::TransferFrom)
+                .with_executor_table()
+                .encode_swaps_only(&weth_dai_solution(vec![weth_usdc_univ2(), usdc_dai_univ2()]))
+                .unwrap();
+            assert_eq!(encoded.swaps[..21], [vec![1], usv2_executor.to_vec()].concat());
+            assert_eq!(encoded.swaps[21..], ple_encode(encoded.segments.clone()).unwrap());
+            assert_eq!(encoded.segments[1][0], 0);
+            assert_eq!(encoded.executors, vec![usv2_executor.clone(), usv2_executor]);
+        }
+
+        fn bebop_order_swap(registration: bool, estimated_amount_in: u32) -> Swap {
+            Swap::new(
                 ProtocolComponent {
-                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                    protocol_system: "uniswap_v2".to_string(),
+                    id: "bebop-rfq".to_string(),
+                    protocol_system: "rfq:bebop".to_string(),
                     ..Default::default()
                 },
-                weth().clone(),
-                dai().clone(),
-            );
+                usdc(),
+                weth(),
+            )
+            .estimated_amount_in(BigUint::from(estimated_amount_in))
+            .registration(registration)
+        }
 
-            let solution = Solution {
-                exact_out: false,
-                given_token: weth(),
-                swaps: vec![swap],
-                native_action: Some(NativeAction::Wrap),
+        #[test]
+        fn test_validate_registration_swaps() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = |swaps: Vec<Swap>| Solution {
+                given_token: usdc(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: weth(),
+                swaps,
                 ..Default::default()
             };
 
-            let result = encoder.validate_solution(&solution);
+            // The registration swap is followed by the swap filling the order
+            let result = encoder.validate_solution(&solution(vec![
+                bebop_order_swap(true, 0),
+                bebop_order_swap(false, 1000),
+            ]));
+            assert!(result.is_ok());
 
-            assert!(result.is_err());
-            assert_eq!(
-                result.err().unwrap(),
-                EncodingError::FatalError(
-                    "Native token must be the input token in order to wrap".to_string()
-                )
-            );
+            // Zero-amount swaps must be flagged as registration swaps
+            let result = encoder.validate_solution(&solution(vec![bebop_order_swap(false, 0)]));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            // Registration swaps must have a zero amount
+            let result = encoder.validate_solution(&solution(vec![
+                bebop_order_swap(true, 10),
+                bebop_order_swap(false, 1000),
+            ]));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            // Registration swaps must be followed by the swap filling the order
+            let result = encoder.validate_solution(&solution(vec![bebop_order_swap(true, 0)]));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
         }
 
-        #[test]
-        fn test_validate_fails_for_wrap_wrong_first_swap() {
-            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swap = Swap::new(
+        fn across_swap(token_in: Bytes) -> Swap {
+            Swap::new(
                 ProtocolComponent {
-                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    id: "0x5c7BCd6E7De5423a257D81B442095A1a6ced35C5".to_string(),
+                    protocol_system: "bridge:across".to_string(),
+                    ..Default::default()
+                },
+                token_in,
+                // USDC on Base
+                Bytes::from_str("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913").unwrap(),
+            )
+        }
+
+        fn weth_usdc_univ2() -> Swap {
+            Swap::new(
+                ProtocolComponent {
+                    id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
                     protocol_system: "uniswap_v2".to_string(),
                     ..Default::default()
                 },
-                eth().clone(),
-                dai().clone(),
-            );
+                weth(),
+                usdc(),
+            )
+        }
 
-            let solution = Solution {
-                exact_out: false,
-                given_token: eth(),
-                swaps: vec![swap],
-                native_action: Some(NativeAction::Wrap),
+        #[test]
+        fn test_validate_bridge_swaps() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let solution = |swaps: Vec<Swap>| Solution {
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: Bytes::from_str("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913")
+                    .unwrap(),
+                swaps,
                 ..Default::default()
             };
 
-            let result = encoder.validate_solution(&solution);
+            // The bridge swap is the last swap of a sequential solution
+            let result =
+                encoder.validate_solution(&solution(vec![weth_usdc_univ2(), across_swap(usdc())]));
+            assert!(result.is_ok());
 
-            assert!(result.is_err());
-            assert_eq!(
-                result.err().unwrap(),
-                EncodingError::FatalError(
-                    "Wrapped token must be the first swap's input in order to wrap".to_string()
-                )
-            );
+            // Bridge swaps can't be followed by another swap
+            let result =
+                encoder.validate_solution(&solution(vec![across_swap(weth()), weth_usdc_univ2()]));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            // Bridge swaps must follow another swap
+            let result = encoder.validate_solution(&solution(vec![across_swap(weth())]));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            // Bridge swaps can't be part of a split solution
+            let result = encoder.validate_solution(&solution(vec![
+                weth_usdc_univ2().split(0.5),
+                weth_usdc_univ2(),
+                across_swap(usdc()),
+            ]));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
         }
 
         #[test]
-        fn test_validate_fails_no_swaps() {
+        fn test_resolve_bridge_settlement() {
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
             let solution = Solution {
-                exact_out: false,
-                given_token: eth(),
-                swaps: vec![],
-                native_action: Some(NativeAction::Wrap),
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: Bytes::from_str("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913")
+                    .unwrap(),
+                receiver: Bytes::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
+                swaps: vec![weth_usdc_univ2(), across_swap(usdc())],
                 ..Default::default()
             };
 
-            let result = encoder.validate_solution(&solution);
+            let resolved = encoder
+                .resolve_bridge_settlement(&solution)
+                .unwrap();
 
-            assert!(result.is_err());
+            // The router checks the USDC deposited into the spoke pool
+            assert_eq!(resolved.checked_token, usdc());
             assert_eq!(
-                result.err().unwrap(),
-                EncodingError::FatalError("No swaps found in solution".to_string())
+                resolved.receiver,
+                Bytes::from_str("0x5c7BCd6E7De5423a257D81B442095A1a6ced35C5").unwrap()
             );
         }
 
-        #[test]
-        fn test_validate_passes_for_unwrap() {
-            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swap = Swap::new(
+        fn steth() -> Bytes {
+            Bytes::from_str("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84").unwrap()
+        }
+
+        fn lido_withdrawal_swap() -> Swap {
+            Swap::new(
                 ProtocolComponent {
-                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                    protocol_system: "uniswap_v2".to_string(),
+                    id: "0x889edC2eDab5f40e902b864aD4d7AdE8E412F9B1".to_string(),
+                    protocol_system: "lido_withdrawal".to_string(),
                     ..Default::default()
                 },
-                dai().clone(),
-                weth().clone(),
-            );
-
-            let solution = Solution {
-                exact_out: false,
-                checked_token: eth(),
-                swaps: vec![swap],
-                native_action: Some(NativeAction::Unwrap),
-                ..Default::default()
-            };
-
-            let result = encoder.validate_solution(&solution);
-
-            assert!(result.is_ok());
+                steth(),
+                eth(),
+            )
         }
 
-        #[test]
-        fn test_validate_fails_for_unwrap_wrong_output() {
-            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swap = Swap::new(
+        fn weth_steth_curve() -> Swap {
+            Swap::new(
                 ProtocolComponent {
-                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                    protocol_system: "uniswap_v2".to_string(),
+                    id: "0x828b154032950C8ff7CF8085D841723Db2696056".to_string(),
+                    protocol_system: "vm:curve".to_string(),
                     ..Default::default()
                 },
-                dai().clone(),
-                weth().clone(),
-            );
+                weth(),
+                steth(),
+            )
+        }
 
+        #[test]
+        fn test_resolve_checked_shares() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            // 9.5M shares backing 10M stETH
+            let checked_shares = CheckedShares {
+                min_shares: BigUint::from(950_000u32),
+                total_shares: BigUint::from(9_500_000u32),
+                total_pooled: BigUint::from(10_000_000u32),
+                rebase_tolerance_bps: 1,
+            };
             let solution = Solution {
-                exact_out: false,
-                given_token: dai(),
-                checked_token: weth(),
-                swaps: vec![swap],
-                native_action: Some(NativeAction::Unwrap),
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
+                checked_token: steth(),
+                checked_amount: BigUint::from(1_000_000u32),
+                swaps: vec![weth_steth_curve()],
+                checked_shares: Some(checked_shares.clone()),
                 ..Default::default()
             };
 
-            let result = encoder.validate_solution(&solution);
+            let resolved = encoder
+                .resolve_checked_amount(&solution)
+                .unwrap();
 
-            assert!(result.is_err());
-            assert_eq!(
-                result.err().unwrap(),
-                EncodingError::FatalError(
-                    "Native token must be the output token in order to unwrap".to_string()
-                )
-            );
+            // 1_000_000 stETH lowered by 1 bps and the 2 wei of transfer rounding
+            assert_eq!(resolved.checked_amount, BigUint::from(999_898u32));
+
+            // Only rebasing checked tokens have shares
+            let solution = Solution {
+                checked_token: usdc(),
+                checked_shares: Some(checked_shares),
+                ..weth_dai_solution(vec![weth_usdc_univ2()])
+            };
+            assert!(matches!(
+                encoder.resolve_checked_amount(&solution),
+                Err(EncodingError::InvalidInput(_))
+            ));
         }
 
         #[test]
-        fn test_validate_fails_for_unwrap_wrong_last_swap() {
+        fn test_validate_withdrawal_requests() {
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swap = Swap::new(
+            let solution = |given_token: Bytes, swaps: Vec<Swap>| Solution {
+                given_token,
+                given_amount: BigUint::from(1000u32),
+                checked_token: eth(),
+                swaps,
+                ..Default::default()
+            };
+
+            // The withdrawal request is the last swap of a sequential solution
+            let result = encoder.validate_solution(&solution(
+                weth(),
+                vec![weth_steth_curve(), lido_withdrawal_swap()],
+            ));
+            assert!(result.is_ok());
+
+            // A lone withdrawal request would check the stETH it is given as the amount out
+            let result =
+                encoder.validate_solution(&solution(steth(), vec![lido_withdrawal_swap()]));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            // Nothing can be swapped after a withdrawal request
+            let reth = Bytes::from_str("0xae78736Cd615f374D3085123A210448E74Fc6393").unwrap();
+            let eth_reth_rocketpool = Swap::new(
                 ProtocolComponent {
-                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                    protocol_system: "uniswap_v2".to_string(),
+                    id: "0xae78736Cd615f374D3085123A210448E74Fc6393".to_string(),
+                    protocol_system: "rocketpool".to_string(),
                     ..Default::default()
                 },
-                dai().clone(),
-                eth().clone(),
+                eth(),
+                reth.clone(),
             );
+            let result = encoder.validate_solution(&Solution {
+                checked_token: reth,
+                ..solution(
+                    weth(),
+                    vec![weth_steth_curve(), lido_withdrawal_swap(), eth_reth_rocketpool],
+                )
+            });
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+
+            // Withdrawal requests can't be part of a split solution
+            let result = encoder.validate_solution(&solution(
+                weth(),
+                vec![weth_steth_curve().split(0.5), weth_steth_curve(), lido_withdrawal_swap()],
+            ));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+        }
 
+        #[test]
+        fn test_resolve_withdrawal_request_settlement() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
             let solution = Solution {
-                exact_out: false,
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
                 checked_token: eth(),
-                swaps: vec![swap],
-                native_action: Some(NativeAction::Unwrap),
+                checked_amount: BigUint::from(990u32),
+                receiver: Bytes::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
+                swaps: vec![weth_steth_curve(), lido_withdrawal_swap()],
                 ..Default::default()
             };
 
-            let result = encoder.validate_solution(&solution);
+            let resolved = encoder
+                .resolve_bridge_settlement(&solution)
+                .unwrap();
 
-            assert!(result.is_err());
+            // The router checks the stETH received by the withdrawal queue
+            assert_eq!(resolved.checked_token, steth());
             assert_eq!(
-                result.err().unwrap(),
-                EncodingError::FatalError(
-                    "Wrapped token must be the last swap's output in order to unwrap".to_string()
-                )
+                resolved.receiver,
+                Bytes::from_str("0x889edC2eDab5f40e902b864aD4d7AdE8E412F9B1").unwrap()
             );
+            // The queue pulls the stETH with the rounding of its share math
+            assert_eq!(resolved.checked_amount, BigUint::from(988u32));
         }
 
-        #[test]
-        fn test_validate_cyclical_swap() {
-            // This validation passes because the cyclical swap is the first and last token
-            //      50% ->  WETH
-            // DAI -              -> DAI
-            //      50% -> WETH
-            // (some of the pool addresses in this test are fake)
-            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swaps = vec![
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    dai().clone(),
-                    weth().clone(),
-                ),
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0x0000000000000000000000000000000000000000".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    dai().clone(),
-                    weth().clone(),
-                ),
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0x0000000000000000000000000000000000000000".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    weth().clone(),
-                    dai().clone(),
-                ),
-            ];
+        fn usdc_dai_univ2() -> Swap {
+            Swap::new(
+                ProtocolComponent {
+                    id: "0xAE461cA67B15dc8dc81CE7615e0320dA1A9aB8D5".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                usdc(),
+                dai(),
+            )
+        }
 
-            let solution = Solution {
-                exact_out: false,
-                given_token: dai(),
+        fn treasury() -> Bytes {
+            Bytes::from_str("0x2222222222222222222222222222222222222222").unwrap()
+        }
+
+        fn weth_dai_solution(swaps: Vec<Swap>) -> Solution {
+            Solution {
+                given_token: weth(),
+                given_amount: BigUint::from(1000u32),
                 checked_token: dai(),
+                checked_amount: BigUint::from(990u32),
+                receiver: Bytes::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
                 swaps,
                 ..Default::default()
-            };
+            }
+        }
 
-            let result = encoder.validate_solution(&solution);
+        #[test]
+        fn test_validate_receiver_overrides() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
 
+            // The last swap of a sequence can override its receiver
+            let result = encoder.validate_solution(&weth_dai_solution(vec![
+                weth_usdc_univ2(),
+                usdc_dai_univ2().receiver_override(treasury()),
+            ]));
             assert!(result.is_ok());
+
+            // Intermediate swaps can't
+            let result = encoder.validate_solution(&weth_dai_solution(vec![
+                weth_usdc_univ2().receiver_override(treasury()),
+                usdc_dai_univ2(),
+            ]));
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
         }
 
         #[test]
-        fn test_validate_cyclical_swap_fail() {
-            // This test should fail because the cyclical swap is not the first and last token
-            // DAI -> WETH -> USDC -> DAI -> WBTC
-            // (some of the pool addresses in this test are fake)
+        fn test_resolve_receiver_override() {
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swaps = vec![
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    dai().clone(),
-                    weth().clone(),
-                ),
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    weth().clone(),
-                    usdc().clone(),
-                ),
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0x0000000000000000000000000000000000000000".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    usdc().clone(),
-                    dai().clone(),
-                ),
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0x0000000000000000000000000000000000000000".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    dai().clone(),
-                    wbtc().clone(),
-                ),
-            ];
 
-            let solution = Solution {
-                exact_out: false,
-                given_token: dai(),
-                checked_token: wbtc(),
-                swaps,
-                ..Default::default()
-            };
+            // The router checks the amount out received by the override
+            let solution = weth_dai_solution(vec![
+                weth_usdc_univ2(),
+                usdc_dai_univ2().receiver_override(treasury()),
+            ]);
+            let resolved = encoder
+                .resolve_receiver_override(&solution)
+                .unwrap();
+            assert_eq!(resolved.receiver, treasury());
 
-            let result = encoder.validate_solution(&solution);
+            // Without overrides, the solution receiver is kept
+            let solution = weth_dai_solution(vec![weth_usdc_univ2(), usdc_dai_univ2()]);
+            let resolved = encoder
+                .resolve_receiver_override(&solution)
+                .unwrap();
+            assert_eq!(resolved.receiver, solution.receiver);
 
-            assert!(result.is_err());
-            assert_eq!(
-            result.err().unwrap(),
-            EncodingError::FatalError(
-                "Cyclical swaps are only allowed if they are the first and last token of a solution".to_string()
-            )
-        );
+            // Terminal swaps sending to different receivers can't be checked by the router
+            let solution = weth_dai_solution(vec![
+                weth_usdc_univ2(),
+                usdc_dai_univ2()
+                    .split(0.5)
+                    .receiver_override(treasury()),
+                usdc_dai_univ2(),
+            ]);
+            let result = encoder.resolve_receiver_override(&solution);
+            assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
         }
+
         #[test]
-        fn test_validate_cyclical_swap_split_output() {
-            // This validation passes because it is a valid cyclical swap
-            //             -> WETH
-            // WETH -> DAI
-            //             -> WETH
-            // (some of the pool addresses in this test are fake)
+        fn test_validate_fee() {
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swaps = vec![
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    weth(),
-                    dai(),
-                ),
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0x0000000000000000000000000000000000000000".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    dai(),
-                    weth(),
-                )
-                .split(0.5),
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0x0000000000000000000000000000000000000000".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    dai(),
-                    weth(),
-                ),
-            ];
+            let fee = |bps: u16, on_input: bool| Fee { recipient: treasury(), bps, on_input };
+            let swaps = || vec![weth_usdc_univ2(), usdc_dai_univ2()];
+
+            let solution = Solution { fee: Some(fee(25, false)), ..weth_dai_solution(swaps()) };
+            assert!(encoder
+                .validate_solution(&solution)
+                .is_ok());
+
+            // Fees can't exceed 100%
+            let solution = Solution { fee: Some(fee(10_001, true)), ..weth_dai_solution(swaps()) };
+            assert!(matches!(
+                encoder.validate_solution(&solution),
+                Err(EncodingError::InvalidInput(_))
+            ));
 
+            // The router can't take the input fee in the wrapped token before wrapping
             let solution = Solution {
-                exact_out: false,
-                given_token: weth(),
-                checked_token: weth(),
-                swaps,
-                ..Default::default()
+                given_token: eth(),
+                fee: Some(fee(25, true)),
+                native_action: Some(NativeAction::Wrap),
+                ..weth_dai_solution(swaps())
             };
+            assert!(matches!(
+                encoder.validate_solution(&solution),
+                Err(EncodingError::InvalidInput(_))
+            ));
 
-            let result = encoder.validate_solution(&solution);
-
-            assert!(result.is_ok());
+            // The router must receive the amount out to take a fee on it
+            let solution = Solution {
+                fee: Some(fee(25, false)),
+                ..weth_dai_solution(vec![
+                    weth_usdc_univ2(),
+                    usdc_dai_univ2().receiver_override(treasury()),
+                ])
+            };
+            assert!(matches!(
+                encoder.validate_solution(&solution),
+                Err(EncodingError::InvalidInput(_))
+            ));
         }
 
         #[test]
-        fn test_validate_cyclical_swap_native_action_fail() {
-            // This validation fails because there is a native action with a valid cyclical swap
-            // ETH -> WETH -> DAI -> WETH
-            // (some of the pool addresses in this test are fake)
+        fn test_validate_receiver_callback() {
             let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
-            let swaps = vec![
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    weth(),
-                    dai(),
-                ),
-                Swap::new(
-                    ProtocolComponent {
-                        id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
-                        protocol_system: "uniswap_v2".to_string(),
-                        ..Default::default()
-                    },
-                    dai(),
-                    weth(),
-                ),
-            ];
+            let callback = || Some(Bytes::from(vec![0xbe, 0xef]));
+            let swaps = || vec![weth_usdc_univ2(), usdc_dai_univ2()];
 
+            let solution = Solution { receiver_callback: callback(), ..weth_dai_solution(swaps()) };
+            assert!(encoder
+                .validate_solution(&solution)
+                .is_ok());
+
+            // The router can't both take a fee and call back the receiver
             let solution = Solution {
-                exact_out: false,
-                given_token: eth(),
-                checked_token: weth(),
-                swaps,
-                native_action: Some(NativeAction::Wrap),
-                ..Default::default()
+                receiver_callback: callback(),
+                fee: Some(Fee { recipient: treasury(), bps: 25, on_input: true }),
+                ..weth_dai_solution(swaps())
             };
+            assert!(matches!(
+                encoder.validate_solution(&solution),
+                Err(EncodingError::InvalidInput(_))
+            ));
 
-            let result = encoder.validate_solution(&solution);
+            // The router must receive the amount out to call back the receiver
+            let solution = Solution {
+                receiver_callback: callback(),
+                ..weth_dai_solution(vec![
+                    weth_usdc_univ2(),
+                    usdc_dai_univ2().receiver_override(treasury()),
+                ])
+            };
+            assert!(matches!(
+                encoder.validate_solution(&solution),
+                Err(EncodingError::InvalidInput(_))
+            ));
+        }
 
-            assert!(result.is_err());
+        #[test]
+        fn test_validate_multi_input_solution() {
+            let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+            let wbtc_dai_univ2 = Swap::new(
+                ProtocolComponent {
+                    id: "0x231B7589426Ffe1b75405526fC32aC09D44364c4".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                wbtc(),
+                dai(),
+            );
+            let solution = |additional_token: Bytes| Solution {
+                additional_given_tokens: vec![GivenToken {
+                    token: additional_token,
+                    amount: BigUint::from(500u32),
+                }],
+                ..weth_dai_solution(vec![
+                    weth_usdc_univ2(),
+                    usdc_dai_univ2(),
+                    wbtc_dai_univ2.clone(),
+                ])
+            };
+
+            assert!(encoder
+                .validate_solution(&solution(wbtc()))
+                .is_ok());
             assert_eq!(
-                result.err().unwrap(),
-                EncodingError::FatalError(
-                    "Wrapping/Unwrapping is not available in cyclical swaps"
-                        .to_string()
-                        .to_string()
-                )
+                encoder
+                    .strategy_encoder(&solution(wbtc()))
+                    .name(),
+                "multi_input_split_swap"
             );
+
+            // Given tokens can't be repeated
+            assert!(matches!(
+                encoder.validate_solution(&solution(weth())),
+                Err(EncodingError::InvalidInput(_))
+            ));
+
+            // The checked token can't be given
+            assert!(matches!(
+                encoder.validate_solution(&solution(dai())),
+                Err(EncodingError::InvalidInput(_))
+            ));
+
+            // The router can't take a fee on several given tokens
+            let with_fee = Solution {
+                fee: Some(Fee { recipient: treasury(), bps: 25, on_input: true }),
+                ..solution(wbtc())
+            };
+            assert!(matches!(
+                encoder.validate_solution(&with_fee),
+                Err(EncodingError::InvalidInput(_))
+            ));
         }
     }
 
@@ -1116,6 +4582,7 @@ mod tests {
                 receiver: Bytes::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
                 swaps: vec![swap],
                 native_action: None,
+                ..Default::default()
             };
 
             let encoded_solutions = encoder
@@ -1174,6 +4641,7 @@ mod tests {
                 receiver: Bytes::from_str("0x1d96f2f6bef1202e4ce1ff6dad0c2cb002861d3e").unwrap(),
                 swaps: vec![swap.clone(), swap],
                 native_action: None,
+                ..Default::default()
             };
 
             let result = encoder.encode_solutions(vec![solution]);
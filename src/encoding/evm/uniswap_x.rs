@@ -0,0 +1,893 @@
+use alloy::{
+    core::sol,
+    primitives::{Address, Bytes as AlloyBytes, I256, U256},
+    sol_types::SolValue,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::encoding::{errors::EncodingError, evm::encoding_utils::encode_input};
+
+/// Signatures of the `UniswapXFiller` functions executing orders.
+const EXECUTE: &str = "execute((bytes,bytes),bytes)";
+const EXECUTE_BATCH: &str = "executeBatch((bytes,bytes)[],bytes)";
+const EXECUTE_WITH_REACTOR: &str = "executeWithReactor(address,(bytes,bytes),bytes)";
+const EXECUTE_BATCH_WITH_REACTOR: &str = "executeBatchWithReactor(address,(bytes,bytes)[],bytes)";
+
+/// Denominator of the exclusivity override of Dutch orders, in basis points.
+const BPS: u64 = 10_000;
+/// Denominator of the priority fee scaling of priority orders, in milli-basis points.
+const MPS: u64 = 10_000_000;
+/// Dutch V3 base fee adjustments are given per gwei of base fee change.
+const GWEI: u64 = 1_000_000_000;
+
+sol! {
+    /// A UniswapX order with the signature of its swapper, as received from the UniswapX API.
+    #[derive(Debug, PartialEq, Eq)]
+    struct SignedOrder {
+        bytes order;
+        bytes sig;
+    }
+
+    #[derive(Debug)]
+    struct OrderInfo {
+        address reactor;
+        address swapper;
+        uint256 nonce;
+        uint256 deadline;
+        address additionalValidationContract;
+        bytes additionalValidationData;
+    }
+
+    #[derive(Debug)]
+    struct DutchInput {
+        address token;
+        uint256 startAmount;
+        uint256 endAmount;
+    }
+
+    #[derive(Debug)]
+    struct DutchOutput {
+        address token;
+        uint256 startAmount;
+        uint256 endAmount;
+        address recipient;
+    }
+
+    #[derive(Debug)]
+    struct CosignerData {
+        uint256 decayStartTime;
+        uint256 decayEndTime;
+        address exclusiveFiller;
+        uint256 exclusivityOverrideBps;
+        uint256 inputAmount;
+        uint256[] outputAmounts;
+    }
+
+    #[derive(Debug)]
+    struct V2DutchOrder {
+        OrderInfo info;
+        address cosigner;
+        DutchInput baseInput;
+        DutchOutput[] baseOutputs;
+        CosignerData cosignerData;
+        bytes cosignature;
+    }
+
+    /// Decay curve of Dutch V3 orders: up to 16 points, whose block offsets from the decay start
+    /// block are packed as `uint16` into `relativeBlocks`, and whose amounts are relative to the
+    /// start amount.
+    #[derive(Debug)]
+    struct NonlinearDutchDecay {
+        uint256 relativeBlocks;
+        int256[] relativeAmounts;
+    }
+
+    #[derive(Debug)]
+    struct V3DutchInput {
+        address token;
+        uint256 startAmount;
+        NonlinearDutchDecay curve;
+        uint256 maxAmount;
+        uint256 adjustmentPerGweiBaseFee;
+    }
+
+    #[derive(Debug)]
+    struct V3DutchOutput {
+        address token;
+        uint256 startAmount;
+        NonlinearDutchDecay curve;
+        address recipient;
+        uint256 minAmount;
+        uint256 adjustmentPerGweiBaseFee;
+    }
+
+    #[derive(Debug)]
+    struct V3CosignerData {
+        uint256 decayStartBlock;
+        address exclusiveFiller;
+        uint256 exclusivityOverrideBps;
+        uint256 inputOverride;
+        uint256[] outputOverrides;
+    }
+
+    #[derive(Debug)]
+    struct V3DutchOrder {
+        OrderInfo info;
+        address cosigner;
+        uint256 startingBaseFee;
+        V3DutchInput baseInput;
+        V3DutchOutput[] baseOutputs;
+        V3CosignerData cosignerData;
+        bytes cosignature;
+    }
+
+    #[derive(Debug)]
+    struct PriorityInput {
+        address token;
+        uint256 amount;
+        uint256 mpsPerPriorityFeeWei;
+    }
+
+    #[derive(Debug)]
+    struct PriorityOutput {
+        address token;
+        uint256 amount;
+        uint256 mpsPerPriorityFeeWei;
+        address recipient;
+    }
+
+    #[derive(Debug)]
+    struct PriorityCosignerData {
+        uint256 auctionTargetBlock;
+    }
+
+    #[derive(Debug)]
+    struct PriorityOrder {
+        OrderInfo info;
+        address cosigner;
+        uint256 auctionStartBlock;
+        uint256 baselinePriorityFeeWei;
+        PriorityInput input;
+        PriorityOutput[] outputs;
+        PriorityCosignerData cosignerData;
+        bytes cosignature;
+    }
+}
+
+/// The UniswapX reactor version an order is executed on, which defines how its amounts resolve.
+///
+/// Variants:
+/// - `V2Dutch`: Dutch orders (v2), decaying linearly over time from their cosigned amounts.
+/// - `V3Dutch`: Dutch orders (v3), decaying over blocks along a piecewise linear curve, with their
+///   amounts adjusted to the base fee change since the order was created.
+/// - `Priority`: Priority orders, whose amounts scale with the priority fee of the fill
+///   transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReactorVersion {
+    V2Dutch,
+    V3Dutch,
+    Priority,
+}
+
+/// The fill transaction an order is resolved for.
+///
+/// # Fields
+/// * `filler` - The address filling the order (the `UniswapXFiller`), checked against the exclusive
+///   filler of Dutch orders.
+/// * `block_number` - The block the order is filled in.
+/// * `timestamp` - The timestamp of that block.
+/// * `base_fee_wei` - The base fee of that block.
+/// * `priority_fee_wei` - The priority fee of the fill transaction, i.e. its gas price minus the
+///   base fee.
+#[derive(Clone, Debug, Default)]
+pub struct FillContext {
+    pub filler: Address,
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub base_fee_wei: U256,
+    pub priority_fee_wei: U256,
+}
+
+/// An amount of a token of a resolved order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedAmount {
+    pub token: Address,
+    pub amount: U256,
+}
+
+/// The amounts an order settles with: the input taken from the swapper and the outputs that the
+/// filler has to provide (including the fee outputs).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedOrderAmounts {
+    pub input: ResolvedAmount,
+    pub outputs: Vec<ResolvedAmount>,
+}
+
+/// Resolves the amounts of an ABI encoded order the way its reactor does for the given fill.
+///
+/// The returned amounts size the Tycho solution filling the order: its given amount is the input
+/// and its checked amount has to cover the outputs. Signatures are not verified.
+pub fn resolve_order(
+    version: ReactorVersion,
+    order: &[u8],
+    context: &FillContext,
+) -> Result<ResolvedOrderAmounts, EncodingError> {
+    match version {
+        ReactorVersion::V2Dutch => resolve_v2_dutch_order(
+            V2DutchOrder::abi_decode(order).map_err(|_| invalid_order(version))?,
+            context,
+        ),
+        ReactorVersion::V3Dutch => resolve_v3_dutch_order(
+            V3DutchOrder::abi_decode(order).map_err(|_| invalid_order(version))?,
+            context,
+        ),
+        ReactorVersion::Priority => resolve_priority_order(
+            PriorityOrder::abi_decode(order).map_err(|_| invalid_order(version))?,
+            context,
+        ),
+    }
+}
+
+/// Returns the total input and output of a batch of orders filled with a single Tycho router call.
+///
+/// All the orders must sell the same token for the same token.
+pub fn batch_fill_amounts(
+    orders: &[ResolvedOrderAmounts],
+) -> Result<(ResolvedAmount, ResolvedAmount), EncodingError> {
+    let first = orders
+        .first()
+        .ok_or(EncodingError::InvalidInput("No UniswapX orders to fill".to_string()))?;
+    let output_token = first
+        .outputs
+        .first()
+        .ok_or(EncodingError::InvalidInput("UniswapX order without outputs".to_string()))?
+        .token;
+    let mut input = ResolvedAmount { token: first.input.token, amount: U256::ZERO };
+    let mut output = ResolvedAmount { token: output_token, amount: U256::ZERO };
+    for order in orders {
+        if order.input.token != input.token ||
+            order
+                .outputs
+                .iter()
+                .any(|o| o.token != output.token)
+        {
+            return Err(EncodingError::InvalidInput(
+                "All UniswapX orders filled with a single router call must trade the same tokens"
+                    .to_string(),
+            ));
+        }
+        input.amount = checked_add(input.amount, order.input.amount)?;
+        for o in &order.outputs {
+            output.amount = checked_add(output.amount, o.amount)?;
+        }
+    }
+    Ok((input, output))
+}
+
+/// Builds the callback data passed to the `UniswapXFiller`: the token approval flags followed by
+/// the calldata of the Tycho router call filling the orders.
+pub fn callback_data(
+    token_in_approval_needed: bool,
+    token_out_approval_needed: bool,
+    tycho_calldata: &[u8],
+) -> Vec<u8> {
+    (token_in_approval_needed, token_out_approval_needed, tycho_calldata.to_vec())
+        .abi_encode_packed()
+}
+
+/// Encodes the `UniswapXFiller` call executing the given orders. Several orders are executed as a
+/// batch, filled with the single router call of the callback data.
+///
+/// The orders are executed on the default reactor of the filler if `reactor` is `None`, or on the
+/// given reactor otherwise (which must be allowed to call back the filler).
+pub fn encode_filler_call(
+    reactor: Option<Address>,
+    mut orders: Vec<SignedOrder>,
+    callback_data: Vec<u8>,
+) -> Result<Vec<u8>, EncodingError> {
+    let callback_data = AlloyBytes::from(callback_data);
+    match (orders.len(), reactor) {
+        (0, _) => Err(EncodingError::InvalidInput("No UniswapX orders to execute".to_string())),
+        (1, None) => Ok(encode_input(EXECUTE, (orders.remove(0), callback_data).abi_encode())),
+        (1, Some(reactor)) => Ok(encode_input(
+            EXECUTE_WITH_REACTOR,
+            (reactor, orders.remove(0), callback_data).abi_encode(),
+        )),
+        (_, None) => Ok(encode_input(EXECUTE_BATCH, (orders, callback_data).abi_encode())),
+        (_, Some(reactor)) => Ok(encode_input(
+            EXECUTE_BATCH_WITH_REACTOR,
+            (reactor, orders, callback_data).abi_encode(),
+        )),
+    }
+}
+
+fn invalid_order(version: ReactorVersion) -> EncodingError {
+    EncodingError::InvalidInput(format!("Failed to decode UniswapX {version:?} order"))
+}
+
+fn resolve_v2_dutch_order(
+    mut order: V2DutchOrder,
+    context: &FillContext,
+) -> Result<ResolvedOrderAmounts, EncodingError> {
+    let cosigner_data = &order.cosignerData;
+    if !cosigner_data.inputAmount.is_zero() {
+        if cosigner_data.inputAmount > order.baseInput.startAmount {
+            return Err(EncodingError::InvalidInput("Invalid cosigner input".to_string()));
+        }
+        order.baseInput.startAmount = cosigner_data.inputAmount;
+    }
+    if cosigner_data.outputAmounts.len() != order.baseOutputs.len() {
+        return Err(EncodingError::InvalidInput("Invalid cosigner outputs".to_string()));
+    }
+    for (output, amount) in order
+        .baseOutputs
+        .iter_mut()
+        .zip(&cosigner_data.outputAmounts)
+    {
+        if !amount.is_zero() {
+            if *amount < output.startAmount {
+                return Err(EncodingError::InvalidInput("Invalid cosigner output".to_string()));
+            }
+            output.startAmount = *amount;
+        }
+    }
+
+    let start = to_u64(cosigner_data.decayStartTime)?;
+    let end = to_u64(cosigner_data.decayEndTime)?;
+    let input = ResolvedAmount {
+        token: order.baseInput.token,
+        amount: linear_decay(
+            order.baseInput.startAmount,
+            order.baseInput.endAmount,
+            start,
+            end,
+            context.timestamp,
+        )?,
+    };
+    let mut outputs = order
+        .baseOutputs
+        .iter()
+        .map(|output| {
+            Ok(ResolvedAmount {
+                token: output.token,
+                amount: linear_decay(
+                    output.startAmount,
+                    output.endAmount,
+                    start,
+                    end,
+                    context.timestamp,
+                )?,
+            })
+        })
+        .collect::<Result<Vec<_>, EncodingError>>()?;
+    apply_exclusivity(
+        &mut outputs,
+        cosigner_data.exclusiveFiller,
+        cosigner_data.exclusivityOverrideBps,
+        context.filler,
+        context.timestamp > start,
+    )?;
+    Ok(ResolvedOrderAmounts { input, outputs })
+}
+
+fn resolve_v3_dutch_order(
+    mut order: V3DutchOrder,
+    context: &FillContext,
+) -> Result<ResolvedOrderAmounts, EncodingError> {
+    let cosigner_data = &order.cosignerData;
+    if !cosigner_data.inputOverride.is_zero() {
+        if cosigner_data.inputOverride > order.baseInput.startAmount {
+            return Err(EncodingError::InvalidInput("Invalid cosigner input".to_string()));
+        }
+        order.baseInput.startAmount = cosigner_data.inputOverride;
+    }
+    if cosigner_data.outputOverrides.len() != order.baseOutputs.len() {
+        return Err(EncodingError::InvalidInput("Invalid cosigner outputs".to_string()));
+    }
+    for (output, amount) in order
+        .baseOutputs
+        .iter_mut()
+        .zip(&cosigner_data.outputOverrides)
+    {
+        if !amount.is_zero() {
+            if *amount < output.startAmount {
+                return Err(EncodingError::InvalidInput("Invalid cosigner output".to_string()));
+            }
+            output.startAmount = *amount;
+        }
+    }
+
+    // A base fee increase increases the input and decreases the outputs
+    let gas_delta_wei = to_i256(context.base_fee_wei)? - to_i256(order.startingBaseFee)?;
+    let gwei = I256::try_from(GWEI).map_err(|_| overflow())?;
+    if !order
+        .baseInput
+        .adjustmentPerGweiBaseFee
+        .is_zero()
+    {
+        let delta = gas_delta_wei * to_i256(order.baseInput.adjustmentPerGweiBaseFee)? / gwei;
+        order.baseInput.startAmount =
+            bounded_sub(order.baseInput.startAmount, -delta, U256::ZERO, order.baseInput.maxAmount);
+    }
+    for output in order.baseOutputs.iter_mut() {
+        if !output
+            .adjustmentPerGweiBaseFee
+            .is_zero()
+        {
+            let delta = gas_delta_wei * to_i256(output.adjustmentPerGweiBaseFee)? / gwei;
+            output.startAmount =
+                bounded_sub(output.startAmount, delta, output.minAmount, U256::MAX);
+        }
+    }
+
+    let decay_start_block = to_u64(cosigner_data.decayStartBlock)?;
+    let input = ResolvedAmount {
+        token: order.baseInput.token,
+        amount: curve_decay(
+            &order.baseInput.curve,
+            order.baseInput.startAmount,
+            decay_start_block,
+            context.block_number,
+            U256::ZERO,
+            order.baseInput.maxAmount,
+            true,
+        )?,
+    };
+    let mut outputs = order
+        .baseOutputs
+        .iter()
+        .map(|output| {
+            Ok(ResolvedAmount {
+                token: output.token,
+                amount: curve_decay(
+                    &output.curve,
+                    output.startAmount,
+                    decay_start_block,
+                    context.block_number,
+                    output.minAmount,
+                    U256::MAX,
+                    false,
+                )?,
+            })
+        })
+        .collect::<Result<Vec<_>, EncodingError>>()?;
+    apply_exclusivity(
+        &mut outputs,
+        cosigner_data.exclusiveFiller,
+        cosigner_data.exclusivityOverrideBps,
+        context.filler,
+        context.block_number > decay_start_block,
+    )?;
+    Ok(ResolvedOrderAmounts { input, outputs })
+}
+
+fn resolve_priority_order(
+    order: PriorityOrder,
+    context: &FillContext,
+) -> Result<ResolvedOrderAmounts, EncodingError> {
+    // The cosigner can only start the auction earlier
+    let target_block = order.cosignerData.auctionTargetBlock;
+    let auction_start_block = if order.cosigner != Address::ZERO &&
+        !target_block.is_zero() &&
+        target_block < order.auctionStartBlock
+    {
+        target_block
+    } else {
+        order.auctionStartBlock
+    };
+    if U256::from(context.block_number) < auction_start_block {
+        return Err(EncodingError::InvalidInput(format!(
+            "UniswapX priority order is not fillable before block {auction_start_block}"
+        )));
+    }
+
+    let priority_fee_wei = context
+        .priority_fee_wei
+        .saturating_sub(order.baselinePriorityFeeWei);
+    let mps = U256::from(MPS);
+
+    let input_scaling = priority_fee_wei
+        .checked_mul(order.input.mpsPerPriorityFeeWei)
+        .ok_or_else(overflow)?;
+    let input_amount = if input_scaling >= mps {
+        U256::ZERO
+    } else {
+        mul_div(order.input.amount, mps - input_scaling, mps, false)?
+    };
+    let outputs = order
+        .outputs
+        .iter()
+        .map(|output| {
+            let scaling = priority_fee_wei
+                .checked_mul(output.mpsPerPriorityFeeWei)
+                .ok_or_else(overflow)?;
+            Ok(ResolvedAmount {
+                token: output.token,
+                amount: mul_div(output.amount, checked_add(mps, scaling)?, mps, true)?,
+            })
+        })
+        .collect::<Result<Vec<_>, EncodingError>>()?;
+    Ok(ResolvedOrderAmounts {
+        input: ResolvedAmount { token: order.input.token, amount: input_amount },
+        outputs,
+    })
+}
+
+/// Decays an amount linearly over time, rounding in favour of the swapper: increasing amounts (the
+/// input) round up and decreasing amounts (the outputs) round down.
+fn linear_decay(
+    start_amount: U256,
+    end_amount: U256,
+    start: u64,
+    end: u64,
+    now: u64,
+) -> Result<U256, EncodingError> {
+    if end < start {
+        return Err(EncodingError::InvalidInput("Decay ends before it starts".to_string()));
+    }
+    if end <= now {
+        return Ok(end_amount);
+    }
+    if start >= now {
+        return Ok(start_amount);
+    }
+    let elapsed = U256::from(now - start);
+    let duration = U256::from(end - start);
+    if end_amount < start_amount {
+        Ok(start_amount - mul_div(start_amount - end_amount, elapsed, duration, false)?)
+    } else {
+        checked_add(start_amount, mul_div(end_amount - start_amount, elapsed, duration, true)?)
+    }
+}
+
+/// Decays an amount along a Dutch V3 curve and bounds it.
+///
+/// The amount relative to the start amount is interpolated linearly between the points of the
+/// curve surrounding the current block, and rounded in favour of the swapper.
+fn curve_decay(
+    curve: &NonlinearDutchDecay,
+    start_amount: U256,
+    decay_start_block: u64,
+    block_number: u64,
+    min_amount: U256,
+    max_amount: U256,
+    is_input: bool,
+) -> Result<U256, EncodingError> {
+    let points = curve.relativeAmounts.len();
+    if points > 16 {
+        return Err(EncodingError::InvalidInput("Invalid UniswapX decay curve".to_string()));
+    }
+    if decay_start_block >= block_number || points == 0 {
+        return Ok(start_amount.clamp(min_amount, max_amount));
+    }
+    let relative_block = |index: usize| -> u64 {
+        (curve.relativeBlocks >> (16 * index) & U256::from(u16::MAX)).to::<u64>()
+    };
+    let elapsed = block_number - decay_start_block;
+
+    let (start_point, end_point, start_relative, end_relative) = if relative_block(0) >= elapsed {
+        (0, relative_block(0), I256::ZERO, curve.relativeAmounts[0])
+    } else {
+        (1..points)
+            .find(|index| relative_block(*index) >= elapsed)
+            .map(|index| {
+                (
+                    relative_block(index - 1),
+                    relative_block(index),
+                    curve.relativeAmounts[index - 1],
+                    curve.relativeAmounts[index],
+                )
+            })
+            .unwrap_or((
+                relative_block(points - 1),
+                relative_block(points - 1),
+                curve.relativeAmounts[points - 1],
+                curve.relativeAmounts[points - 1],
+            ))
+    };
+
+    let relative_amount = if elapsed >= end_point {
+        end_relative
+    } else {
+        let numerator =
+            (end_relative - start_relative) * to_i256(U256::from(elapsed - start_point))?;
+        let denominator = to_i256(U256::from(end_point - start_point))?;
+        // a larger relative amount decreases the amount: round it down for inputs (rounding the
+        // input up) and up for outputs (rounding the outputs down)
+        let (quotient, remainder) = (numerator / denominator, numerator % denominator);
+        let adjustment = if remainder.is_zero() {
+            I256::ZERO
+        } else if is_input && numerator.is_negative() {
+            I256::MINUS_ONE
+        } else if !is_input && numerator.is_positive() {
+            I256::ONE
+        } else {
+            I256::ZERO
+        };
+        start_relative + quotient + adjustment
+    };
+    Ok(bounded_sub(start_amount, relative_amount, min_amount, max_amount))
+}
+
+/// Increases the outputs by the exclusivity override if the filler isn't the exclusive filler of
+/// an order that is still in its exclusivity period.
+fn apply_exclusivity(
+    outputs: &mut [ResolvedAmount],
+    exclusive_filler: Address,
+    override_bps: U256,
+    filler: Address,
+    exclusivity_ended: bool,
+) -> Result<(), EncodingError> {
+    if exclusive_filler == Address::ZERO || exclusivity_ended || exclusive_filler == filler {
+        return Ok(());
+    }
+    if override_bps.is_zero() {
+        return Err(EncodingError::InvalidInput(format!(
+            "UniswapX order can only be filled by {exclusive_filler} during its exclusivity period"
+        )));
+    }
+    let bps = U256::from(BPS);
+    for output in outputs.iter_mut() {
+        output.amount = mul_div(output.amount, checked_add(bps, override_bps)?, bps, true)?;
+    }
+    Ok(())
+}
+
+/// Subtracts a signed delta from the amount, bounding the result to `[min_amount, max_amount]`.
+fn bounded_sub(amount: U256, delta: I256, min_amount: U256, max_amount: U256) -> U256 {
+    let result = if delta.is_negative() {
+        amount.saturating_add(delta.unsigned_abs())
+    } else {
+        match amount.checked_sub(delta.unsigned_abs()) {
+            Some(result) => result,
+            None => return min_amount,
+        }
+    };
+    result.clamp(min_amount, max_amount)
+}
+
+fn mul_div(a: U256, b: U256, denominator: U256, round_up: bool) -> Result<U256, EncodingError> {
+    let product = a.checked_mul(b).ok_or_else(overflow)?;
+    let quotient = product / denominator;
+    if round_up && !(product % denominator).is_zero() {
+        checked_add(quotient, U256::from(1))
+    } else {
+        Ok(quotient)
+    }
+}
+
+fn checked_add(a: U256, b: U256) -> Result<U256, EncodingError> {
+    a.checked_add(b).ok_or_else(overflow)
+}
+
+fn to_u64(value: U256) -> Result<u64, EncodingError> {
+    u64::try_from(value).map_err(|_| overflow())
+}
+
+fn to_i256(value: U256) -> Result<I256, EncodingError> {
+    I256::try_from(value).map_err(|_| overflow())
+}
+
+fn overflow() -> EncodingError {
+    EncodingError::InvalidInput("Overflow while resolving UniswapX order amounts".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn token(byte: u8) -> Address {
+        Address::repeat_byte(byte)
+    }
+
+    fn order_info() -> OrderInfo {
+        OrderInfo {
+            reactor: token(0xaa),
+            swapper: token(0xbb),
+            nonce: U256::from(1),
+            deadline: U256::MAX,
+            additionalValidationContract: Address::ZERO,
+            additionalValidationData: AlloyBytes::new(),
+        }
+    }
+
+    fn context(block_number: u64, timestamp: u64) -> FillContext {
+        FillContext { filler: token(0xff), block_number, timestamp, ..Default::default() }
+    }
+
+    fn v2_order(exclusive_filler: Address, override_bps: u64) -> Vec<u8> {
+        V2DutchOrder {
+            info: order_info(),
+            cosigner: token(0xcc),
+            baseInput: DutchInput {
+                token: token(1),
+                startAmount: U256::from(1_000),
+                endAmount: U256::from(1_000),
+            },
+            baseOutputs: vec![DutchOutput {
+                token: token(2),
+                startAmount: U256::from(2_000),
+                endAmount: U256::from(1_000),
+                recipient: token(0xbb),
+            }],
+            cosignerData: CosignerData {
+                decayStartTime: U256::from(100),
+                decayEndTime: U256::from(200),
+                exclusiveFiller: exclusive_filler,
+                exclusivityOverrideBps: U256::from(override_bps),
+                inputAmount: U256::ZERO,
+                outputAmounts: vec![U256::from(2_100)],
+            },
+            cosignature: AlloyBytes::new(),
+        }
+        .abi_encode()
+    }
+
+    #[test]
+    fn test_resolve_v2_dutch_order() {
+        let order = v2_order(Address::ZERO, 0);
+
+        let before = resolve_order(ReactorVersion::V2Dutch, &order, &context(0, 50)).unwrap();
+        let halfway = resolve_order(ReactorVersion::V2Dutch, &order, &context(0, 150)).unwrap();
+        let after = resolve_order(ReactorVersion::V2Dutch, &order, &context(0, 300)).unwrap();
+
+        assert_eq!(before.input, ResolvedAmount { token: token(1), amount: U256::from(1_000) });
+        // the cosigned amount overrides the start amount
+        assert_eq!(before.outputs[0].amount, U256::from(2_100));
+        assert_eq!(halfway.outputs[0].amount, U256::from(1_550));
+        assert_eq!(after.outputs[0].amount, U256::from(1_000));
+    }
+
+    #[test]
+    fn test_resolve_v2_dutch_order_exclusivity() {
+        let exclusive = v2_order(token(0xee), 100);
+        let no_override = v2_order(token(0xee), 0);
+
+        let resolved = resolve_order(ReactorVersion::V2Dutch, &exclusive, &context(0, 50)).unwrap();
+        let result = resolve_order(ReactorVersion::V2Dutch, &no_override, &context(0, 50));
+
+        // 1% over the start amount, for not being the exclusive filler
+        assert_eq!(resolved.outputs[0].amount, U256::from(2_121));
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_resolve_v3_dutch_order() {
+        let curve = NonlinearDutchDecay {
+            // points at 2 and 4 blocks after the decay start block
+            relativeBlocks: U256::from(2) | (U256::from(4) << 16),
+            relativeAmounts: vec![I256::try_from(100).unwrap(), I256::try_from(201).unwrap()],
+        };
+        let order = V3DutchOrder {
+            info: order_info(),
+            cosigner: token(0xcc),
+            startingBaseFee: U256::from(GWEI),
+            baseInput: V3DutchInput {
+                token: token(1),
+                startAmount: U256::from(1_000),
+                curve: NonlinearDutchDecay { relativeBlocks: U256::ZERO, relativeAmounts: vec![] },
+                maxAmount: U256::from(1_100),
+                adjustmentPerGweiBaseFee: U256::from(10),
+            },
+            baseOutputs: vec![V3DutchOutput {
+                token: token(2),
+                startAmount: U256::from(1_000),
+                curve,
+                recipient: token(0xbb),
+                minAmount: U256::from(500),
+                adjustmentPerGweiBaseFee: U256::ZERO,
+            }],
+            cosignerData: V3CosignerData {
+                decayStartBlock: U256::from(10),
+                exclusiveFiller: Address::ZERO,
+                exclusivityOverrideBps: U256::ZERO,
+                inputOverride: U256::ZERO,
+                outputOverrides: vec![U256::ZERO],
+            },
+            cosignature: AlloyBytes::new(),
+        }
+        .abi_encode();
+
+        let amounts = |block_number: u64| {
+            resolve_order(ReactorVersion::V3Dutch, &order, &context(block_number, 0))
+                .unwrap()
+                .outputs[0]
+                .amount
+        };
+        assert_eq!(amounts(10), U256::from(1_000));
+        assert_eq!(amounts(11), U256::from(950));
+        assert_eq!(amounts(12), U256::from(900));
+        // 100 + 101 / 2 rounded up, rounding the output down
+        assert_eq!(amounts(13), U256::from(849));
+        assert_eq!(amounts(20), U256::from(799));
+
+        // the base fee doubled: the input increases by 10 per gwei, bounded by the max amount
+        let mut fill = context(10, 0);
+        fill.base_fee_wei = U256::from(2 * GWEI);
+        let resolved = resolve_order(ReactorVersion::V3Dutch, &order, &fill).unwrap();
+        assert_eq!(resolved.input.amount, U256::from(1_010));
+    }
+
+    #[test]
+    fn test_resolve_priority_order() {
+        let order = PriorityOrder {
+            info: order_info(),
+            cosigner: token(0xcc),
+            auctionStartBlock: U256::from(100),
+            baselinePriorityFeeWei: U256::from(1_000),
+            input: PriorityInput {
+                token: token(1),
+                amount: U256::from(1_000_000),
+                mpsPerPriorityFeeWei: U256::ZERO,
+            },
+            outputs: vec![PriorityOutput {
+                token: token(2),
+                amount: U256::from(1_000_000),
+                mpsPerPriorityFeeWei: U256::from(10),
+                recipient: token(0xbb),
+            }],
+            cosignerData: PriorityCosignerData { auctionTargetBlock: U256::from(90) },
+            cosignature: AlloyBytes::new(),
+        }
+        .abi_encode();
+
+        let mut fill = context(95, 0);
+        fill.priority_fee_wei = U256::from(11_000);
+        let resolved = resolve_order(ReactorVersion::Priority, &order, &fill).unwrap();
+        let too_early = resolve_order(ReactorVersion::Priority, &order, &context(89, 0));
+
+        assert_eq!(resolved.input.amount, U256::from(1_000_000));
+        // 10_000 wei over the baseline at 10 mps per wei: 1% more output
+        assert_eq!(resolved.outputs[0].amount, U256::from(1_010_000));
+        assert!(matches!(too_early, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_batch_fill_amounts() {
+        let order = |input: u64, output: u64, output_token: Address| ResolvedOrderAmounts {
+            input: ResolvedAmount { token: token(1), amount: U256::from(input) },
+            outputs: vec![ResolvedAmount { token: output_token, amount: U256::from(output) }],
+        };
+
+        let (input, output) =
+            batch_fill_amounts(&[order(10, 20, token(2)), order(5, 8, token(2))]).unwrap();
+        let result = batch_fill_amounts(&[order(10, 20, token(2)), order(5, 8, token(3))]);
+
+        assert_eq!(input, ResolvedAmount { token: token(1), amount: U256::from(15) });
+        assert_eq!(output, ResolvedAmount { token: token(2), amount: U256::from(28) });
+        assert!(matches!(result, Err(EncodingError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_encode_filler_call() {
+        let order = SignedOrder {
+            order: AlloyBytes::from_str("0x1234").unwrap(),
+            sig: AlloyBytes::from_str("0x5678").unwrap(),
+        };
+        let callback = callback_data(true, false, &[0xab, 0xcd]);
+        let reactor = token(0xaa);
+
+        let single = encode_filler_call(None, vec![order.clone()], callback.clone()).unwrap();
+        let batch =
+            encode_filler_call(Some(reactor), vec![order.clone(), order.clone()], callback.clone())
+                .unwrap();
+
+        assert_eq!(callback, vec![0x01, 0x00, 0xab, 0xcd]);
+        assert_eq!(single[..4], encode_input(EXECUTE, vec![])[..]);
+        let (decoded_order, decoded_callback) =
+            <(SignedOrder, AlloyBytes)>::abi_decode_params(&single[4..]).unwrap();
+        assert_eq!(decoded_order, order);
+        assert_eq!(decoded_callback.to_vec(), callback);
+
+        assert_eq!(batch[..4], encode_input(EXECUTE_BATCH_WITH_REACTOR, vec![])[..]);
+        let (decoded_reactor, decoded_orders, _) =
+            <(Address, Vec<SignedOrder>, AlloyBytes)>::abi_decode_params(&batch[4..]).unwrap();
+        assert_eq!(decoded_reactor, reactor);
+        assert_eq!(decoded_orders, vec![order.clone(), order]);
+
+        assert!(encode_filler_call(None, vec![], callback).is_err());
+    }
+}
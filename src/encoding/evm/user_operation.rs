@@ -0,0 +1,278 @@
+use alloy::{
+    core::sol,
+    primitives::{Bytes as AlloyBytes, U256},
+    sol_types::SolValue,
+};
+use tycho_common::models::Chain;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{
+        approvals::permit_signer::{DetachedSignature, PermitSigner},
+        encoding_utils::{encode_input, encode_tycho_router_call},
+        strategy_encoder::wrap_adapter::ChainTokens,
+        utils::{biguint_to_u256, bytes_to_address},
+    },
+    models::{EncodedSolution, Solution, UserTransferType},
+};
+
+/// Signature of the function of the smart account executing a call (e.g. `SimpleAccount`,
+/// `Kernel` and `LightAccount`).
+const EXECUTE: &str = "execute(address,uint256,bytes)";
+
+sol! {
+    /// An ERC-4337 user operation, as submitted by bundlers to the `EntryPoint` (v0.6).
+    #[derive(Debug, PartialEq, Eq)]
+    struct UserOperation {
+        address sender;
+        uint256 nonce;
+        bytes initCode;
+        bytes callData;
+        uint256 callGasLimit;
+        uint256 verificationGasLimit;
+        uint256 preVerificationGas;
+        uint256 maxFeePerGas;
+        uint256 maxPriorityFeePerGas;
+        bytes paymasterAndData;
+        bytes signature;
+    }
+}
+
+/// Converts an encoded solution into an ERC-4337 user operation of the smart account sending the
+/// solution.
+///
+/// The smart account is the sender of the solution, so it is the owner of the tokens in. The
+/// operation's call data makes the account call the Tycho router with `execute`, sending the
+/// native token in as the value of the call. The router call is encoded with
+/// `encode_tycho_router_call`, so the same responsibility applies to its checked amount.
+///
+/// For `TransferFromPermit2` solutions, the permit is signed by `permit_signer`, and Permit2
+/// validates the signature with the account's `isValidSignature` (see `Erc1271Signer`). Use
+/// `encode_user_operation_with_signature` for permits signed outside of this crate.
+///
+/// # Warning
+/// The nonce, the gas fields, the init code, the paymaster data and the signature of the operation
+/// are left empty: they are filled by the caller (usually with the bundler's estimates) before the
+/// operation is signed. The account must have approved the router (`TransferFrom`) or Permit2
+/// (`TransferFromPermit2`) for the token in beforehand.
+pub fn encode_user_operation(
+    chain: Chain,
+    encoded_solution: EncodedSolution,
+    solution: &Solution,
+    user_transfer_type: &UserTransferType,
+    permit_signer: Option<&dyn PermitSigner>,
+) -> Result<UserOperation, EncodingError> {
+    let native_address = ChainTokens::for_chain(chain)?.native;
+    let router = bytes_to_address(&encoded_solution.interacting_with)?;
+    let transaction = encode_tycho_router_call(
+        chain.id(),
+        encoded_solution,
+        solution,
+        user_transfer_type,
+        &native_address,
+        permit_signer,
+    )?;
+    let call_data = encode_input(
+        EXECUTE,
+        (router, biguint_to_u256(&transaction.value), AlloyBytes::from(transaction.data))
+            .abi_encode(),
+    );
+
+    Ok(UserOperation {
+        sender: bytes_to_address(&solution.sender)?,
+        nonce: U256::ZERO,
+        initCode: AlloyBytes::new(),
+        callData: call_data.into(),
+        callGasLimit: U256::ZERO,
+        verificationGasLimit: U256::ZERO,
+        preVerificationGas: U256::ZERO,
+        maxFeePerGas: U256::ZERO,
+        maxPriorityFeePerGas: U256::ZERO,
+        paymasterAndData: AlloyBytes::new(),
+        signature: AlloyBytes::new(),
+    })
+}
+
+/// Converts an encoded solution into a user operation like `encode_user_operation`, with the
+/// signature of its permit produced from `TychoRouterEncoder::permit_typed_data` (e.g. by the
+/// owner of the smart account) instead of a permit signer.
+pub fn encode_user_operation_with_signature(
+    chain: Chain,
+    encoded_solution: EncodedSolution,
+    solution: &Solution,
+    signature: Vec<u8>,
+) -> Result<UserOperation, EncodingError> {
+    encode_user_operation(
+        chain,
+        encoded_solution,
+        solution,
+        &UserTransferType::TransferFromPermit2,
+        Some(&DetachedSignature::new(signature)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use num_bigint::BigUint;
+    use tycho_common::{models::protocol::ProtocolComponent, Bytes};
+
+    use super::*;
+    use crate::encoding::models::{PermitDetails, PermitSingle, Swap};
+
+    fn router() -> Bytes {
+        Bytes::from_str("0xfD0b31d2E955fA55e3fa641Fe90e08b677188d35").unwrap()
+    }
+
+    fn account() -> Bytes {
+        Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap()
+    }
+
+    fn weth() -> Bytes {
+        Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap()
+    }
+
+    fn dai() -> Bytes {
+        Bytes::from_str("0x6b175474e89094c44da98b954eedeac495271d0f").unwrap()
+    }
+
+    fn encoded_solution(function_signature: &str) -> EncodedSolution {
+        EncodedSolution {
+            swaps: vec![0xab, 0xcd],
+            interacting_with: router(),
+            function_signature: function_signature.to_string(),
+            n_tokens: 0,
+            permit: None,
+            permit_batch: None,
+            native_action: None,
+            state_versions: vec![],
+            callback_allowlist: None,
+            summary: Default::default(),
+            gas_estimate: 0,
+            debug_info: None,
+            degraded_groups: vec![],
+        }
+    }
+
+    fn solution(given_token: Bytes) -> Solution {
+        Solution {
+            given_token,
+            given_amount: BigUint::from(1000u32),
+            checked_token: dai(),
+            checked_amount: BigUint::from(990u32),
+            sender: account(),
+            receiver: account(),
+            swaps: vec![Swap::new(
+                ProtocolComponent {
+                    id: "0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11".to_string(),
+                    protocol_system: "uniswap_v2".to_string(),
+                    ..Default::default()
+                },
+                weth(),
+                dai(),
+            )],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_encode_user_operation() {
+        let function_signature =
+            "singleSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)";
+        let solution = solution(Chain::Ethereum.native_token().address);
+
+        let user_operation = encode_user_operation(
+            Chain::Ethereum,
+            encoded_solution(function_signature),
+            &solution,
+            &UserTransferType::TransferFrom,
+            None,
+        )
+        .unwrap();
+
+        let transaction = encode_tycho_router_call(
+            1,
+            encoded_solution(function_signature),
+            &solution,
+            &UserTransferType::TransferFrom,
+            &Chain::Ethereum.native_token().address,
+            None,
+        )
+        .unwrap();
+        assert_eq!(user_operation.sender, bytes_to_address(&account()).unwrap());
+        assert_eq!(
+            user_operation.callData.to_vec(),
+            encode_input(
+                EXECUTE,
+                (
+                    bytes_to_address(&router()).unwrap(),
+                    U256::from(1000),
+                    AlloyBytes::from(transaction.data)
+                )
+                    .abi_encode()
+            )
+        );
+        assert_eq!(user_operation.nonce, U256::ZERO);
+        assert!(user_operation.signature.is_empty());
+    }
+
+    #[test]
+    fn test_encode_user_operation_with_signature() {
+        let function_signature = "singleSwapPermit2(uint256,address,address,uint256,bool,bool,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)";
+        let encoded_solution = EncodedSolution {
+            permit: Some(PermitSingle {
+                details: PermitDetails {
+                    token: weth(),
+                    amount: BigUint::from(1000u32),
+                    expiration: BigUint::from(1_800_000_000u64),
+                    nonce: BigUint::ZERO,
+                },
+                spender: router(),
+                sig_deadline: BigUint::from(1_800_000_000u64),
+            }),
+            ..encoded_solution(function_signature)
+        };
+        let signature = vec![0x11; 65];
+
+        let user_operation = encode_user_operation_with_signature(
+            Chain::Ethereum,
+            encoded_solution,
+            &solution(weth()),
+            signature.clone(),
+        )
+        .unwrap();
+
+        // The signature of the permit is part of the router call
+        let call_data = hex::encode(&user_operation.callData);
+        assert!(call_data.contains(&hex::encode(signature)));
+    }
+
+    #[test]
+    fn test_encode_user_operation_permit2_without_signer() {
+        let function_signature = "singleSwapPermit2(uint256,address,address,uint256,bool,bool,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)";
+        let encoded_solution = EncodedSolution {
+            permit: Some(PermitSingle {
+                details: PermitDetails {
+                    token: weth(),
+                    amount: BigUint::from(1000u32),
+                    expiration: BigUint::from(1_800_000_000u64),
+                    nonce: BigUint::ZERO,
+                },
+                spender: router(),
+                sig_deadline: BigUint::from(1_800_000_000u64),
+            }),
+            ..encoded_solution(function_signature)
+        };
+
+        let result = encode_user_operation(
+            Chain::Ethereum,
+            encoded_solution,
+            &solution(weth()),
+            &UserTransferType::TransferFromPermit2,
+            None,
+        );
+
+        assert!(matches!(result, Err(EncodingError::FatalError(_))));
+    }
+}
@@ -1,24 +1,31 @@
 use std::{
-    env,
+    collections::{BTreeMap, HashMap},
     fs::OpenOptions,
     io::{BufRead, BufReader, Write},
     sync::{Arc, Mutex},
 };
 
 use alloy::{
-    primitives::{aliases::U24, Address, U256, U8},
+    primitives::{aliases::U24, Address, Bytes as AlloyBytes, U256},
     providers::{
         fillers::{BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller},
-        ProviderBuilder, RootProvider,
+        RootProvider,
     },
     sol_types::SolValue,
 };
 use num_bigint::BigUint;
 use once_cell::sync::Lazy;
 use tokio::runtime::{Handle, Runtime};
-use tycho_common::Bytes;
+use tycho_common::{
+    models::protocol::GetAmountOutParams, simulation::indicatively_priced::SignedQuote, Bytes,
+};
 
-use crate::encoding::{errors::EncodingError, models::Swap};
+use crate::encoding::{
+    errors::EncodingError,
+    evm::rpc::RpcConfig,
+    models::{EncodedSolutionSummary, Swap, TransferType},
+    swap_encoder::SwapEncoder,
+};
 
 /// Safely converts a `Bytes` object to an `Address` object.
 ///
@@ -47,17 +54,78 @@ pub fn percentage_to_uint24(decimal: f64) -> U24 {
     U24::from(scaled.round())
 }
 
-/// Gets the position of a token in a list of tokens.
-pub fn get_token_position(tokens: &Vec<&Bytes>, token: &Bytes) -> Result<U8, EncodingError> {
-    let position = U8::from(
-        tokens
+/// Converts the splits of a list of swaps, given with their input token, to the `U24` values to
+/// encode. Remainder swaps (split of 0) are encoded as 0.
+///
+/// Every fraction is rounded to the nearest `U24`, so float drift can make the fractions of a token
+/// add up to 100% (e.g. 0.3333333333 and 0.6666666666), which would leave nothing for its remainder
+/// swap. The largest fraction of such a token is then lowered so the remainder gets at least one
+/// unit.
+pub fn splits_to_uint24(splits: &[(&Bytes, f64)]) -> Vec<U24> {
+    const MAX_UINT24: u64 = 16_777_215; // 2^24 - 1
+
+    scale_splits(splits, MAX_UINT24)
+        .into_iter()
+        .map(U24::from)
+        .collect()
+}
+
+/// Converts the splits of a list of swaps to `u32` values like `splits_to_uint24`, with 1e9
+/// corresponding to 100% (a granularity of 1e-9 instead of ~6e-8).
+pub fn splits_to_uint32(splits: &[(&Bytes, f64)]) -> Vec<u32> {
+    const PRECISE_SPLIT_BASE: u64 = 1_000_000_000;
+
+    scale_splits(splits, PRECISE_SPLIT_BASE)
+        .into_iter()
+        .map(|value| value as u32)
+        .collect()
+}
+
+/// Scales the splits to integers, with `base` corresponding to 100%, lowering the largest fraction
+/// of a token whose fractions add up to 100% or more.
+fn scale_splits(splits: &[(&Bytes, f64)], base: u64) -> Vec<u64> {
+    let mut encoded: Vec<u64> = splits
+        .iter()
+        .map(|(_, split)| (split * base as f64).round() as u64)
+        .collect();
+    let mut fractions_by_token: HashMap<&Bytes, Vec<usize>> = HashMap::new();
+    for (i, (token, split)) in splits.iter().enumerate() {
+        if *split != 0.0 {
+            fractions_by_token
+                .entry(*token)
+                .or_default()
+                .push(i);
+        }
+    }
+    for fractions in fractions_by_token.values() {
+        let total: u64 = fractions
             .iter()
-            .position(|t| *t == token)
-            .ok_or_else(|| {
-                EncodingError::InvalidInput(format!("Token {token} not found in tokens array"))
-            })?,
-    );
-    Ok(position)
+            .map(|i| encoded[*i])
+            .sum();
+        if total >= base {
+            if let Some(largest) = fractions
+                .iter()
+                .copied()
+                .max_by_key(|i| encoded[*i])
+            {
+                encoded[largest] = encoded[largest].saturating_sub(total - base + 1);
+            }
+        }
+    }
+    encoded
+        .into_iter()
+        .map(|value| value.min(base))
+        .collect()
+}
+
+/// Gets the position of a token in a list of tokens.
+pub fn get_token_position(tokens: &Vec<&Bytes>, token: &Bytes) -> Result<usize, EncodingError> {
+    tokens
+        .iter()
+        .position(|t| *t == token)
+        .ok_or_else(|| {
+            EncodingError::InvalidInput(format!("Token {token} not found in tokens array"))
+        })
 }
 
 /// Pads or truncates a byte slice to a fixed size array of N bytes.
@@ -89,6 +157,104 @@ pub fn get_static_attribute(swap: &Swap, attribute_name: &str) -> Result<Vec<u8>
         .to_vec())
 }
 
+/// Returns a copy of the given quote parameters, as `GetAmountOutParams` isn't `Clone` and every
+/// attempt of a quote request (see `RfqConfig::request_signed_quote`) takes its own.
+pub fn copy_quote_params(params: &GetAmountOutParams) -> GetAmountOutParams {
+    GetAmountOutParams {
+        amount_in: params.amount_in.clone(),
+        token_in: params.token_in.clone(),
+        token_out: params.token_out.clone(),
+        sender: params.sender.clone(),
+        receiver: params.receiver.clone(),
+    }
+}
+
+/// Decodes the signed quote recorded in the swap's user data, used instead of requesting a quote
+/// when encoding historical trades.
+///
+/// The user data must be the ABI encoding of `(uint256 amountIn, uint256 amountOut, string[]
+/// attributeNames, bytes[] attributeValues)`, the attributes being the `quote_attributes` of the
+/// recorded quote. Returns an `EncodingError::InvalidInput` if the user data is missing or can't be
+/// decoded.
+pub fn recorded_quote(swap: &Swap) -> Result<SignedQuote, EncodingError> {
+    let user_data = swap
+        .get_user_data()
+        .as_ref()
+        .ok_or_else(|| {
+            EncodingError::InvalidInput(format!(
+                "Historical trades can't request quotes. Record the quote of the {} swap on {} in \
+                 its user data",
+                swap.component().protocol_system,
+                swap.component().id
+            ))
+        })?;
+    let (amount_in, amount_out, names, values) =
+        <(U256, U256, Vec<String>, Vec<AlloyBytes>)>::abi_decode(user_data).map_err(|e| {
+            EncodingError::InvalidInput(format!("Failed to decode recorded quote: {e}"))
+        })?;
+    if names.len() != values.len() {
+        return Err(EncodingError::InvalidInput(
+            "The recorded quote must have a value for every attribute name".to_string(),
+        ));
+    }
+    Ok(SignedQuote {
+        base_token: swap.token_in().clone(),
+        quote_token: swap.token_out().clone(),
+        amount_in: BigUint::from_bytes_be(&amount_in.to_be_bytes::<32>()),
+        amount_out: BigUint::from_bytes_be(&amount_out.to_be_bytes::<32>()),
+        quote_attributes: names
+            .into_iter()
+            .zip(values)
+            .map(|(name, value)| (name, Bytes::from(value.to_vec())))
+            .collect(),
+    })
+}
+
+/// Returns whether the swap is flagged as involving a fee-on-transfer token, with a non-zero
+/// `supports_fot` static attribute of its component.
+///
+/// The flag isn't read from the user data of the swap, which integrators may set to arbitrary tags
+/// (see `append_user_data`).
+///
+/// The amounts of such swaps must be measured from balance differences instead of being computed
+/// from the given amount, since the token transfers deliver less than the transferred amount.
+pub fn supports_fee_on_transfer(swap: &Swap) -> bool {
+    swap.component()
+        .static_attributes
+        .get("supports_fot")
+        .is_some_and(|flag| {
+            flag.as_ref()
+                .iter()
+                .any(|byte| *byte != 0)
+        })
+}
+
+/// Appends the user data segment of the swap, `user_data_length (u16) | user_data`, to its
+/// protocol data if the swap has user data and the executor of the encoder ignores the segment
+/// (see [`SwapEncoder::accepts_user_data_segment`]).
+///
+/// The segment lets integrators tag swaps (e.g. with referral or tracking data) visible on-chain.
+/// Encoders interpreting the user data themselves (e.g. Uniswap V4 hook data) don't accept it.
+///
+/// The executors of Uniswap V2 and its forks, Uniswap V3, PancakeSwap V3, Slipstreams, Ekubo V2,
+/// Ekubo V3 and Fluid V1 check the exact length of their data, so they can't carry the segment
+/// either: the user data of their swaps is ignored.
+///
+/// # Errors
+/// Returns an `EncodingError::InvalidInput` if the user data is longer than `u16::MAX` bytes.
+pub fn append_user_data(
+    swap_encoder: &dyn SwapEncoder,
+    swap: &Swap,
+    mut protocol_data: Vec<u8>,
+) -> Result<Vec<u8>, EncodingError> {
+    if let Some(user_data) = swap.get_user_data() {
+        if swap_encoder.accepts_user_data_segment() {
+            protocol_data.extend(ple_encode(vec![user_data.to_vec()])?);
+        }
+    }
+    Ok(protocol_data)
+}
+
 /// Returns the current Tokio runtime handle, or creates a new one if it doesn't exist.
 /// It also returns the runtime to prevent it from being dropped before use.
 /// This is required since tycho-execution does not have a pre-existing runtime.
@@ -115,30 +281,171 @@ pub type EVMProvider = Arc<
 >;
 
 /// Gets the client used for interacting with the EVM-compatible network.
+///
+/// The RPC endpoints are read from the environment (see `RpcConfig::from_env`), and requests fail
+/// over between them.
 pub async fn get_client() -> Result<EVMProvider, EncodingError> {
     dotenv::dotenv().ok();
-    let eth_rpc_url = env::var("RPC_URL")
-        .map_err(|_| EncodingError::FatalError("Missing RPC_URL in environment".to_string()))?;
-    let client = ProviderBuilder::new()
-        .connect(&eth_rpc_url)
-        .await
-        .map_err(|_| EncodingError::FatalError("Failed to build provider".to_string()))?;
-    Ok(Arc::new(client))
+    RpcConfig::from_env()?.connect()
 }
 
 /// Uses prefix-length encoding to efficient encode action data.
 ///
 /// Prefix-length encoding is a data encoding method where the beginning of a data segment
 /// (the "prefix") contains information about the length of the following data.
-pub fn ple_encode(action_data_array: Vec<Vec<u8>>) -> Vec<u8> {
+///
+/// # Format
+/// Every segment is encoded as its length as a big endian `u16` (2 bytes) followed by the segment
+/// itself, and all encoded segments are concatenated:
+///
+/// ```text
+/// len_0 (2 bytes) | segment_0 | len_1 (2 bytes) | segment_1 | ...
+/// ```
+///
+/// This is the packing the Tycho Router expects for sequential and split swaps, as well as for
+/// grouped swaps of most groupable protocols (see `LibPrefixLengthEncodedByteArray.sol`).
+///
+/// # Errors
+/// Returns an `EncodingError::InvalidInput` if a segment is longer than `u16::MAX` bytes, since its
+/// length can't be represented in the prefix.
+pub fn ple_encode(action_data_array: Vec<Vec<u8>>) -> Result<Vec<u8>, EncodingError> {
     let mut encoded_action_data: Vec<u8> = Vec::new();
 
     for action_data in action_data_array {
-        let args = (encoded_action_data, action_data.len() as u16, action_data);
-        encoded_action_data = args.abi_encode_packed();
+        let length = u16::try_from(action_data.len()).map_err(|_| {
+            EncodingError::InvalidInput(format!(
+                "Segment of {} bytes is too long to be prefix-length encoded (max {} bytes)",
+                action_data.len(),
+                u16::MAX
+            ))
+        })?;
+        encoded_action_data.extend(length.to_be_bytes());
+        encoded_action_data.extend(action_data);
     }
 
-    encoded_action_data
+    Ok(encoded_action_data)
+}
+
+/// Decodes data encoded with [`ple_encode`] back into its segments.
+///
+/// # Errors
+/// Returns an `EncodingError::InvalidInput` if the data is truncated, i.e. if it ends in the
+/// middle of a length prefix or of a segment.
+pub fn ple_decode(encoded: &[u8]) -> Result<Vec<Vec<u8>>, EncodingError> {
+    let mut segments = Vec::new();
+    let mut offset = 0;
+    while offset < encoded.len() {
+        let prefix = encoded
+            .get(offset..offset + 2)
+            .ok_or_else(|| {
+                EncodingError::InvalidInput(format!(
+                    "Truncated length prefix at offset {offset} of prefix-length encoded data"
+                ))
+            })?;
+        let length = u16::from_be_bytes([prefix[0], prefix[1]]) as usize;
+        offset += 2;
+        let segment = encoded
+            .get(offset..offset + length)
+            .ok_or_else(|| {
+                EncodingError::InvalidInput(format!(
+                    "Segment of {length} bytes at offset {offset} exceeds the prefix-length \
+                     encoded data"
+                ))
+            })?;
+        segments.push(segment.to_vec());
+        offset += length;
+    }
+    Ok(segments)
+}
+
+/// Summarizes the encoded swaps of a solution (see `EncodedSolutionSummary`).
+///
+/// The calldata size is derived from the ABI layout of `function_signature`: the last `bytes`
+/// argument holds the encoded swaps, and any other `bytes` argument a 65-byte permit signature. An
+/// empty function signature means the encoded swaps are the whole calldata.
+///
+/// # Arguments
+/// * `function_signature`: Signature of the router function called with the encoded swaps
+/// * `swaps`: The encoded swaps
+/// * `n_hops`: Number of swaps in the solution
+/// * `transfer_types`: Transfer type of every encoded swap
+pub fn summarize_encoded_swaps(
+    function_signature: &str,
+    swaps: &[u8],
+    n_hops: usize,
+    transfer_types: &[TransferType],
+) -> EncodedSolutionSummary {
+    let swaps_gas: u64 = swaps
+        .iter()
+        .map(|byte| if *byte == 0 { 4 } else { 16 })
+        .sum();
+    let (calldata_bytes, calldata_gas) = match abi_overhead(function_signature, swaps.len()) {
+        Some((overhead, padding)) => (
+            overhead + swaps.len() + padding,
+            swaps_gas + 16 * overhead as u64 + 4 * padding as u64,
+        ),
+        None => (swaps.len(), swaps_gas),
+    };
+    let mut transfer_types_count: BTreeMap<TransferType, usize> = BTreeMap::new();
+    for transfer_type in transfer_types {
+        *transfer_types_count
+            .entry(*transfer_type)
+            .or_default() += 1;
+    }
+    EncodedSolutionSummary {
+        calldata_bytes,
+        swaps_bytes: swaps.len(),
+        n_hops,
+        n_groups: transfer_types.len(),
+        transfer_types: transfer_types_count,
+        calldata_gas,
+    }
+}
+
+/// Returns the number of calldata bytes of a call to `function_signature` besides the encoded
+/// swaps and their padding, and the size of that padding. Returns `None` if the signature has no
+/// argument list.
+fn abi_overhead(function_signature: &str, swaps_len: usize) -> Option<(usize, usize)> {
+    const WORD: usize = 32;
+    const SIGNATURE_LEN: usize = 65;
+    let start = function_signature.find('(')?;
+    let end = function_signature.rfind(')')?;
+    let mut params = Vec::new();
+    let mut depth = 0;
+    let mut param_start = start + 1;
+    for (i, c) in function_signature[..end]
+        .char_indices()
+        .skip(start + 1)
+    {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                params.push(&function_signature[param_start..i]);
+                param_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&function_signature[param_start..end]);
+    let n_bytes_params = params
+        .iter()
+        .filter(|param| **param == "bytes")
+        .count();
+    // Selector, then the head: one word per static value (tuples are static here) and one offset
+    // word per `bytes` argument
+    let mut overhead = 4;
+    for param in &params {
+        overhead +=
+            if param.starts_with('(') { WORD * (param.matches(',').count() + 1) } else { WORD };
+    }
+    // Tail: length word and padded content of every `bytes` argument
+    overhead += WORD * n_bytes_params;
+    if n_bytes_params > 1 {
+        overhead += (n_bytes_params - 1) * SIGNATURE_LEN.div_ceil(WORD) * WORD;
+    }
+    let padding = swaps_len.div_ceil(WORD) * WORD - swaps_len;
+    Some((overhead, padding))
 }
 
 static CALLDATA_WRITE_MUTEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
@@ -188,8 +495,169 @@ pub fn write_calldata_to_file(test_identifier: &str, hex_calldata: &str) {
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+    use tycho_common::models::protocol::ProtocolComponent;
+
     use super::*;
 
+    #[test]
+    fn test_ple_encode() {
+        let encoded = ple_encode(vec![vec![0xaa, 0xbb], vec![], vec![0xcc]]).unwrap();
+        assert_eq!(hex::encode(&encoded), "0002aabb00000001cc");
+        assert_eq!(ple_decode(&encoded).unwrap(), vec![vec![0xaa, 0xbb], vec![], vec![0xcc]]);
+    }
+
+    #[test]
+    fn test_ple_encode_segment_too_long() {
+        let too_long = vec![0u8; u16::MAX as usize + 1];
+        assert!(matches!(ple_encode(vec![too_long]), Err(EncodingError::InvalidInput(_))));
+        assert!(ple_encode(vec![vec![0u8; u16::MAX as usize]]).is_ok());
+    }
+
+    #[test]
+    fn test_ple_decode_truncated() {
+        // Length prefix cut in half
+        assert!(ple_decode(&hex::decode("0002aabb00").unwrap()).is_err());
+        // Segment shorter than its length prefix
+        assert!(ple_decode(&hex::decode("0003aabb").unwrap()).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_ple_roundtrip(
+            segments in prop::collection::vec(prop::collection::vec(any::<u8>(), 0..300), 0..20)
+        ) {
+            let encoded = ple_encode(segments.clone()).unwrap();
+            let total_length: usize = segments.iter().map(|s| s.len() + 2).sum();
+            prop_assert_eq!(encoded.len(), total_length);
+            prop_assert_eq!(ple_decode(&encoded).unwrap(), segments);
+        }
+
+        #[test]
+        fn test_ple_decode_never_panics(data in prop::collection::vec(any::<u8>(), 0..600)) {
+            if let Ok(segments) = ple_decode(&data) {
+                prop_assert_eq!(ple_encode(segments).unwrap(), data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_recorded_quote() {
+        let swap = Swap::new(
+            ProtocolComponent {
+                id: "bebop-rfq".to_string(),
+                protocol_system: "rfq:bebop".to_string(),
+                ..Default::default()
+            },
+            Bytes::from("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"),
+            Bytes::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+        );
+        assert!(matches!(recorded_quote(&swap), Err(EncodingError::InvalidInput(_))));
+
+        let user_data = (
+            U256::from(3000_000000u64),
+            U256::from(1_000000000000000000u64),
+            vec!["calldata".to_string()],
+            vec![AlloyBytes::from(vec![0xaa, 0xbb])],
+        )
+            .abi_encode();
+        let quote = recorded_quote(&swap.user_data(Bytes::from(user_data))).unwrap();
+
+        assert_eq!(quote.amount_in, BigUint::from(3000_000000u64));
+        assert_eq!(quote.amount_out, BigUint::from(1_000000000000000000u64));
+        assert_eq!(quote.quote_attributes["calldata"], Bytes::from(vec![0xaa, 0xbb]));
+    }
+
+    #[test]
+    fn test_summarize_encoded_swaps() {
+        let swaps = vec![0, 1, 2, 0, 3];
+        let summary = summarize_encoded_swaps(
+            "singleSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)",
+            &swaps,
+            2,
+            &[TransferType::TransferFrom],
+        );
+
+        // Selector, 9 head words, swaps length word, then the 5 swap bytes padded to a word
+        assert_eq!(summary.calldata_bytes, 4 + 9 * 32 + 32 + 32);
+        assert_eq!(summary.swaps_bytes, 5);
+        assert_eq!(summary.n_hops, 2);
+        assert_eq!(summary.n_groups, 1);
+        assert_eq!(summary.transfer_types, BTreeMap::from([(TransferType::TransferFrom, 1)]));
+        assert_eq!(summary.calldata_gas, (4 + 10 * 32) * 16 + 3 * 16 + 2 * 4 + 27 * 4);
+    }
+
+    #[test]
+    fn test_summarize_encoded_swaps_with_permit() {
+        let summary = summarize_encoded_swaps(
+            "singleSwapPermit2(uint256,address,address,uint256,bool,bool,address,((address,uint160,uint48,uint48),address,uint256),bytes,bytes)",
+            &[1; 64],
+            1,
+            &[TransferType::Transfer, TransferType::Transfer, TransferType::None],
+        );
+
+        // Selector, 7 static words, 6 permit words and 2 offsets, the signature length and its 65
+        // bytes padded to 3 words, then the swaps length and content
+        assert_eq!(summary.calldata_bytes, 4 + 15 * 32 + 4 * 32 + 32 + 64);
+        assert_eq!(
+            summary.transfer_types,
+            BTreeMap::from([(TransferType::Transfer, 2), (TransferType::None, 1)])
+        );
+    }
+
+    #[test]
+    fn test_summarize_encoded_swaps_without_signature() {
+        let summary = summarize_encoded_swaps("", &[1, 0, 1], 1, &[TransferType::Transfer]);
+        assert_eq!(summary.calldata_bytes, 3);
+        assert_eq!(summary.calldata_gas, 36);
+    }
+
+    #[test]
+    fn test_splits_to_uint24() {
+        let weth = Bytes::from("0x01");
+        let dai = Bytes::from("0x02");
+
+        let encoded = splits_to_uint24(&[(&weth, 0.5), (&weth, 0.0), (&dai, 0.0)]);
+
+        assert_eq!(encoded, vec![U24::from(8_388_608u32), U24::ZERO, U24::ZERO]);
+    }
+
+    #[test]
+    fn test_splits_to_uint24_renormalizes_drift() {
+        let weth = Bytes::from("0x01");
+        let dai = Bytes::from("0x02");
+
+        // 5_592_405 + 11_184_810 would be 100%, leaving nothing for the remainder of WETH
+        let encoded = splits_to_uint24(&[
+            (&weth, 0.3333333333),
+            (&weth, 0.6666666666),
+            (&dai, 0.6666666666),
+            (&weth, 0.0),
+            (&dai, 0.0),
+        ]);
+
+        assert_eq!(
+            encoded,
+            vec![
+                U24::from(5_592_405u32),
+                U24::from(11_184_809u32),
+                U24::from(11_184_810u32),
+                U24::ZERO,
+                U24::ZERO
+            ]
+        );
+    }
+
+    #[test]
+    fn test_splits_to_uint32() {
+        let weth = Bytes::from("0x01");
+
+        let encoded = splits_to_uint32(&[(&weth, 0.123456789), (&weth, 0.876543211), (&weth, 0.0)]);
+
+        // The fractions add up to 100%, so the largest is lowered for the remainder
+        assert_eq!(encoded, vec![123_456_789, 876_543_210, 0]);
+    }
+
     #[test]
     fn test_pad_or_truncate_to_size() {
         // Test padding
@@ -0,0 +1,237 @@
+use std::{
+    fmt,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tracing::Instrument;
+
+use crate::encoding::errors::EncodingError;
+
+tokio::task_local! {
+    /// The metrics of the encoder running the current encoding, if it has any.
+    static METRICS: Arc<dyn EncodingMetrics>;
+}
+
+/// A stage of the encoding pipeline, timed in a tracing span and reported to the
+/// [`EncodingMetrics`] of the encoder.
+///
+/// Variants:
+/// - `EncodeSolutions`: Encoding a batch of solutions, from validation to the final calldata.
+/// - `EncodeStrategy`: Encoding a single solution with its strategy (see `StrategyEncoder`).
+/// - `RfqQuote`: Requesting a signed quote from an RFQ maker, retries included (see `RfqConfig`).
+/// - `ApprovalCheck`: Checking the allowance of a token on-chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EncodingStage {
+    EncodeSolutions,
+    EncodeStrategy,
+    RfqQuote,
+    ApprovalCheck,
+}
+
+impl EncodingStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EncodingStage::EncodeSolutions => "encode_solutions",
+            EncodingStage::EncodeStrategy => "encode_strategy",
+            EncodingStage::RfqQuote => "rfq_quote",
+            EncodingStage::ApprovalCheck => "approval_check",
+        }
+    }
+}
+
+impl fmt::Display for EncodingStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The timing of one run of an encoding stage.
+///
+/// # Fields
+/// * `stage`: The stage that ran.
+/// * `label`: What the stage ran on, if relevant: the strategy of `EncodeStrategy` (e.g.
+///   `split_swap`) or the token of `ApprovalCheck`.
+/// * `duration`: How long the stage took.
+/// * `error`: Why the stage failed, if it did.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncodingMetric {
+    pub stage: EncodingStage,
+    pub label: Option<String>,
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+/// A callback receiving the timings and failure reasons of the encoding stages of an encoder
+/// (see `TychoRouterEncoderBuilder::encoding_metrics`).
+///
+/// The stages run deep in the swap encoders (RFQ quotes, approval checks) are reported to the
+/// metrics of the encoder they run for. The callback runs on the encoding thread, so it should
+/// return quickly.
+pub trait EncodingMetrics: Send + Sync {
+    fn record(&self, metric: &EncodingMetric);
+}
+
+/// Runs `f` reporting the stages it runs to the given metrics, if any.
+pub(crate) fn with_metrics<T>(
+    metrics: &Option<Arc<dyn EncodingMetrics>>,
+    f: impl FnOnce() -> T,
+) -> T {
+    match metrics {
+        Some(metrics) => METRICS.sync_scope(metrics.clone(), f),
+        None => f(),
+    }
+}
+
+/// Awaits `future` reporting the stages it runs to the given metrics, if any.
+pub(crate) async fn with_metrics_async<F: Future>(
+    metrics: &Option<Arc<dyn EncodingMetrics>>,
+    future: F,
+) -> F::Output {
+    match metrics {
+        Some(metrics) => {
+            METRICS
+                .scope(metrics.clone(), future)
+                .await
+        }
+        None => future.await,
+    }
+}
+
+/// Runs the stage `f` in a tracing span, and reports its timing to the current metrics.
+pub(crate) fn measure<T>(
+    stage: EncodingStage,
+    label: Option<String>,
+    f: impl FnOnce() -> Result<T, EncodingError>,
+) -> Result<T, EncodingError> {
+    let span = stage_span(stage, &label);
+    let _entered = span.enter();
+    let start = Instant::now();
+    let result = f();
+    report(stage, label, start.elapsed(), &result);
+    result
+}
+
+/// Awaits the stage `future` in a tracing span, and reports its timing to the current metrics.
+pub(crate) async fn measure_async<T, F>(
+    stage: EncodingStage,
+    label: Option<String>,
+    future: F,
+) -> Result<T, EncodingError>
+where
+    F: Future<Output = Result<T, EncodingError>>,
+{
+    let span = stage_span(stage, &label);
+    let start = Instant::now();
+    let result = future.instrument(span).await;
+    report(stage, label, start.elapsed(), &result);
+    result
+}
+
+fn stage_span(stage: EncodingStage, label: &Option<String>) -> tracing::Span {
+    tracing::debug_span!(
+        "encoding_stage",
+        stage = stage.as_str(),
+        label = label.as_deref().unwrap_or_default()
+    )
+}
+
+fn report<T>(
+    stage: EncodingStage,
+    label: Option<String>,
+    duration: Duration,
+    result: &Result<T, EncodingError>,
+) {
+    let error = result
+        .as_ref()
+        .err()
+        .map(|err| err.to_string());
+    match &error {
+        Some(error) => tracing::warn!(%stage, ?duration, %error, "Encoding stage failed"),
+        None => tracing::debug!(%stage, ?duration, "Encoding stage completed"),
+    }
+    let _ = METRICS.try_with(|metrics| {
+        metrics.record(&EncodingMetric { stage, label, duration, error });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingMetrics(Mutex<Vec<EncodingMetric>>);
+
+    impl EncodingMetrics for RecordingMetrics {
+        fn record(&self, metric: &EncodingMetric) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(metric.clone());
+        }
+    }
+
+    impl RecordingMetrics {
+        fn stages(&self) -> Vec<(EncodingStage, Option<String>, Option<String>)> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|metric| (metric.stage, metric.label.clone(), metric.error.clone()))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_measure_reports_to_current_metrics() {
+        let recording = Arc::new(RecordingMetrics::default());
+        let metrics: Option<Arc<dyn EncodingMetrics>> = Some(recording.clone());
+
+        let result = with_metrics(&metrics, || {
+            measure(EncodingStage::EncodeSolutions, None, || {
+                measure(EncodingStage::EncodeStrategy, Some("split_swap".to_string()), || {
+                    Err::<(), _>(EncodingError::FatalError("No swaps".to_string()))
+                })
+            })
+        });
+
+        assert!(result.is_err());
+        let error = Some("Fatal error: No swaps".to_string());
+        assert_eq!(
+            recording.stages(),
+            vec![
+                (EncodingStage::EncodeStrategy, Some("split_swap".to_string()), error.clone()),
+                (EncodingStage::EncodeSolutions, None, error),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_measure_without_metrics() {
+        let recording = Arc::new(RecordingMetrics::default());
+        let metrics: Option<Arc<dyn EncodingMetrics>> = Some(recording.clone());
+        with_metrics(&metrics, || {});
+
+        let result = measure(EncodingStage::ApprovalCheck, None, || Ok(true));
+
+        assert_eq!(result, Ok(true));
+        assert!(recording.stages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_measure_async_reports_to_current_metrics() {
+        let recording = Arc::new(RecordingMetrics::default());
+        let metrics: Option<Arc<dyn EncodingMetrics>> = Some(recording.clone());
+
+        let result = with_metrics_async(&metrics, async {
+            measure_async(EncodingStage::RfqQuote, None, async { Ok(1) }).await
+        })
+        .await;
+
+        assert_eq!(result, Ok(1));
+        assert_eq!(recording.stages(), vec![(EncodingStage::RfqQuote, None, None)]);
+    }
+}
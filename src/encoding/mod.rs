@@ -1,8 +1,11 @@
 pub mod errors;
 #[cfg(feature = "evm")]
 pub mod evm;
+pub mod metrics;
 pub mod models;
 pub mod serde_primitives;
+#[cfg(feature = "service")]
+pub mod service;
 pub mod strategy_encoder;
 mod swap_encoder;
 pub mod tycho_encoder;
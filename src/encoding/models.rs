@@ -1,13 +1,23 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, fmt, future::Future, sync::Arc, time::Duration};
 
 use clap::ValueEnum;
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 use tycho_common::{
-    models::protocol::ProtocolComponent, simulation::protocol_sim::ProtocolSim, Bytes,
+    models::protocol::ProtocolComponent,
+    simulation::{
+        errors::SimulationError,
+        indicatively_priced::{IndicativelyPriced, SignedQuote},
+        protocol_sim::ProtocolSim,
+    },
+    Bytes,
 };
 
-use crate::encoding::serde_primitives::biguint_string;
+use crate::encoding::{
+    errors::EncodingError,
+    metrics::{measure_async, EncodingStage},
+    serde_primitives::{biguint_string, biguint_string_option},
+};
 
 /// Specifies the method for transferring user funds into Tycho execution.
 ///
@@ -49,17 +59,348 @@ pub struct Solution {
     pub given_amount: BigUint,
     /// The token being bought (exact in) or sold (exact out).
     pub checked_token: Bytes,
-    /// False if the solution is an exact input solution. Currently only exact input solutions are
-    /// supported.
+    /// False if the solution is an exact input solution. Exact output solutions are only supported
+    /// for single swaps on Uniswap V2 and V3.
     #[serde(default)]
     pub exact_out: bool,
-    /// Minimum amount to be checked for the solution to be valid.
+    /// Minimum amount to be checked for the solution to be valid. For exact output solutions,
+    /// this is the maximum amount of the checked token to sell.
     #[serde(with = "biguint_string")]
     pub checked_amount: BigUint,
     /// List of swaps to fulfill the solution.
     pub swaps: Vec<Swap>,
     /// If set, the corresponding native action will be executed.
     pub native_action: Option<NativeAction>,
+    /// Optional key identifying this request. If the encoder has an idempotency cache, retrying a
+    /// solution with the same key returns the previously encoded solution instead of encoding it
+    /// (and requesting RFQ quotes) again.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Optional amount of the checked token expected at the current prices: the amount bought
+    /// (exact in) or sold (exact out). Along with a slippage, it is used to derive the checked
+    /// amount (see `Solution::resolved_checked_amount`).
+    #[serde(default, with = "biguint_string_option")]
+    pub expected_amount: Option<BigUint>,
+    /// Optional slippage tolerated on the expected amount. If set, the encoder derives the checked
+    /// amount from the expected amount instead of using `checked_amount`.
+    #[serde(default)]
+    pub slippage: Option<SlippageConfig>,
+    /// Optional integrator fee taken by the router (see `Fee`).
+    #[serde(default)]
+    pub fee: Option<Fee>,
+    /// Tokens sold along with the given token, each with its own amount. Solutions with additional
+    /// given tokens are encoded by the multi input split strategy (see
+    /// `MultiInputSplitSwapStrategyEncoder`).
+    #[serde(default)]
+    pub additional_given_tokens: Vec<GivenToken>,
+    /// Optional unix timestamp (in seconds) by which the solution is expected to be executed. RFQ
+    /// quotes expiring before it (plus the expiry buffer of the `RfqConfig`) are rejected.
+    #[serde(default)]
+    pub execution_deadline: Option<u64>,
+    /// Optional data to call back the receiver with, after the router sent it the output (see the
+    /// `IReceiverCallback` interface). The receiver must be a contract implementing it, e.g. to
+    /// stake the output or provide liquidity with it in the same transaction.
+    #[serde(default)]
+    pub receiver_callback: Option<Bytes>,
+    /// Optional min amount of shares of a rebasing checked token (e.g. stETH) to receive. If set,
+    /// the encoder derives the checked amount from it instead of using `checked_amount` (see
+    /// `CheckedShares`).
+    #[serde(default)]
+    pub checked_shares: Option<CheckedShares>,
+}
+
+impl Solution {
+    /// Returns the tokens sold by the solution with their amounts: the given token followed by the
+    /// additional given tokens.
+    pub fn given_tokens(&self) -> Vec<(&Bytes, &BigUint)> {
+        std::iter::once((&self.given_token, &self.given_amount))
+            .chain(
+                self.additional_given_tokens
+                    .iter()
+                    .map(|given| (&given.token, &given.amount)),
+            )
+            .collect()
+    }
+
+    /// Returns the state version of every swap of the solution, in the order of the swaps.
+    pub fn state_versions(&self) -> Vec<Option<StateVersion>> {
+        self.swaps
+            .iter()
+            .map(|swap| swap.get_state_version().clone())
+            .collect()
+    }
+
+    /// Returns whether the swap at the given index is terminal: it outputs the bought token (the
+    /// checked token for exact in solutions, the given token for exact out ones) and no later swap
+    /// takes it.
+    pub fn is_terminal_swap(&self, index: usize) -> bool {
+        let bought_token = if self.exact_out { &self.given_token } else { &self.checked_token };
+        let Some(swap) = self.swaps.get(index) else {
+            return false;
+        };
+        swap.token_out() == bought_token &&
+            !self.swaps[index + 1..]
+                .iter()
+                .any(|later| later.token_in() == bought_token)
+    }
+
+    /// Returns the checked amount to encode for the solution.
+    ///
+    /// If a slippage is set (on the solution, or else the given default one), the checked amount
+    /// is derived from the expected amount: the min amount bought for exact in solutions, or the
+    /// max amount sold for exact out ones (see `SlippageConfig`). Otherwise, `checked_amount` is
+    /// returned as is.
+    pub fn resolved_checked_amount(
+        &self,
+        default_slippage: Option<&SlippageConfig>,
+    ) -> Result<BigUint, EncodingError> {
+        let Some(slippage) = self
+            .slippage
+            .as_ref()
+            .or(default_slippage)
+        else {
+            return Ok(self.checked_amount.clone());
+        };
+        match &self.expected_amount {
+            Some(expected_amount) if self.exact_out => slippage.max_amount_in(expected_amount),
+            Some(expected_amount) => slippage.min_amount_out(expected_amount),
+            // A default slippage only applies to solutions with an expected amount
+            None if self.slippage.is_none() => Ok(self.checked_amount.clone()),
+            None => Err(EncodingError::InvalidInput(
+                "The expected amount must be set to derive the checked amount from a slippage"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+/// A token sold by a solution in addition to its given token.
+///
+/// # Fields
+/// * `token`: The token being sold
+/// * `amount`: Amount of the token being sold
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GivenToken {
+    pub token: Bytes,
+    #[serde(with = "biguint_string")]
+    pub amount: BigUint,
+}
+
+/// Slippage tolerated between the expected and the checked amount of a solution.
+///
+/// The derived amounts are rounded so the slippage never exceeds `bps`, which matters for tokens
+/// with few decimals (e.g. USDC or WBTC) where one unit is a significant fraction of the amount:
+/// the min amount out is rounded up and the max amount in is rounded down.
+///
+/// # Fields
+/// * `bps`: Tolerated slippage, in basis points (at most 10000)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SlippageConfig {
+    pub bps: u16,
+}
+
+impl SlippageConfig {
+    pub fn new(bps: u16) -> Result<Self, EncodingError> {
+        let slippage = SlippageConfig { bps };
+        slippage.validate()?;
+        Ok(slippage)
+    }
+
+    /// Returns the min amount to buy when `expected_amount` is expected.
+    pub fn min_amount_out(&self, expected_amount: &BigUint) -> Result<BigUint, EncodingError> {
+        self.validate()?;
+        let numerator = expected_amount * (10_000 - u32::from(self.bps));
+        Ok((numerator + 9_999u32) / 10_000u32)
+    }
+
+    /// Returns the max amount to sell when `expected_amount` is expected.
+    pub fn max_amount_in(&self, expected_amount: &BigUint) -> Result<BigUint, EncodingError> {
+        self.validate()?;
+        Ok(expected_amount * (10_000 + u32::from(self.bps)) / 10_000u32)
+    }
+
+    fn validate(&self) -> Result<(), EncodingError> {
+        if self.bps > 10_000 {
+            return Err(EncodingError::InvalidInput(format!(
+                "The slippage must be at most 10000 bps, got {}",
+                self.bps
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Min amount of shares of a rebasing checked token (e.g. stETH) to receive.
+///
+/// The balances of rebasing tokens move with every rebase, so a min balance computed at encoding
+/// time can revert after a tiny negative rebase. The encoder converts the min shares into the
+/// checked balance at the given share rate, lowered by `rebase_tolerance_bps` and the rounding of
+/// the share math (see `rebasing::checked_amount_for_shares`).
+///
+/// # Fields
+/// * `min_shares`: Min amount of shares of the checked token to receive
+/// * `total_shares`: Total amount of shares of the token, e.g. stETH's `getTotalShares()`
+/// * `total_pooled`: Total balance backing these shares, e.g. stETH's `getTotalPooledEther()`
+/// * `rebase_tolerance_bps`: Negative rebase tolerated between encoding and execution, in basis
+///   points (at most 10000)
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CheckedShares {
+    #[serde(with = "biguint_string")]
+    pub min_shares: BigUint,
+    #[serde(with = "biguint_string")]
+    pub total_shares: BigUint,
+    #[serde(with = "biguint_string")]
+    pub total_pooled: BigUint,
+    #[serde(default)]
+    pub rebase_tolerance_bps: u32,
+}
+
+/// An integrator fee, taken by the router on the amount in or out of a solution.
+///
+/// Fees on the input are sent to the recipient before swapping, so the swaps sell the rest of the
+/// given amount. Fees on the output are taken from the amount bought, so the swaps send it to the
+/// router instead of the receiver, and the checked amount applies to what is left after the fee.
+///
+/// # Fields
+/// * `recipient`: Address receiving the fee
+/// * `bps`: Fee in basis points of the amount it is taken on (at most 10000)
+/// * `on_input`: Whether the fee is taken on the given token, instead of the checked token
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fee {
+    pub recipient: Bytes,
+    pub bps: u16,
+    #[serde(default)]
+    pub on_input: bool,
+}
+
+impl Fee {
+    /// Returns an `EncodingError::InvalidInput` if the fee exceeds 10000 bps or has no recipient.
+    pub fn validate(&self) -> Result<(), EncodingError> {
+        if self.bps > 10_000 {
+            return Err(EncodingError::InvalidInput(format!(
+                "The fee must be at most 10000 bps, got {}",
+                self.bps
+            )));
+        }
+        if self
+            .recipient
+            .iter()
+            .all(|byte| *byte == 0)
+        {
+            return Err(EncodingError::InvalidInput(
+                "The fee recipient can't be the zero address".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Timeout and retry policy of the RFQ quote requests (e.g. Bebop or Hashflow signed quotes).
+///
+/// A request that fails with a recoverable error or doesn't answer within `timeout` is retried up
+/// to `retries` times. The first retry waits `backoff`, and every next one twice as long as the
+/// previous one. Invalid requests are not retried.
+///
+/// Quotes expiring within `expiry_buffer` of the current time (or of the execution deadline of the
+/// solution, if set) are rejected, as their settlement would revert on-chain.
+///
+/// # Fields
+/// * `timeout`: Maximum time to wait for a single quote request
+/// * `retries`: Number of times a failed or timed out request is retried
+/// * `backoff`: Delay before the first retry
+/// * `expiry_buffer`: Minimum time left before the expiry of a quote for it to be encoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RfqConfig {
+    pub timeout: Duration,
+    pub retries: u32,
+    pub backoff: Duration,
+    pub expiry_buffer: Duration,
+}
+
+impl Default for RfqConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            retries: 1,
+            backoff: Duration::from_millis(100),
+            expiry_buffer: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RfqConfig {
+    pub fn new(timeout: Duration, retries: u32, backoff: Duration) -> Self {
+        Self { timeout, retries, backoff, ..Default::default() }
+    }
+
+    /// Sets the minimum time left before the expiry of a quote for it to be encoded.
+    pub fn with_expiry_buffer(mut self, expiry_buffer: Duration) -> Self {
+        self.expiry_buffer = expiry_buffer;
+        self
+    }
+
+    /// Checks that a quote expiring at the given unix timestamp (in seconds) is still valid
+    /// `expiry_buffer` after the execution deadline, or after `now` if there is no deadline.
+    ///
+    /// Returns an `EncodingError::QuoteExpired` otherwise.
+    pub fn check_quote_expiry(
+        &self,
+        expiry: u64,
+        execution_deadline: Option<u64>,
+        now: u64,
+    ) -> Result<(), EncodingError> {
+        let reference = execution_deadline.unwrap_or(now);
+        let valid_until = reference.saturating_add(self.expiry_buffer.as_secs());
+        if expiry < valid_until {
+            return Err(EncodingError::QuoteExpired(format!(
+                "The quote expires at {expiry}, before {valid_until} ({}s after {})",
+                self.expiry_buffer.as_secs(),
+                if execution_deadline.is_some() { "the execution deadline" } else { "now" },
+            )));
+        }
+        Ok(())
+    }
+
+    /// Sends a quote request with the timeout and retry policy.
+    ///
+    /// Returns an `EncodingError::QuoteTimeout` if the last attempt timed out, and the error of the
+    /// last attempt otherwise.
+    pub async fn request_signed_quote<F, Fut>(
+        &self,
+        request: F,
+    ) -> Result<SignedQuote, EncodingError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<SignedQuote, SimulationError>>,
+    {
+        measure_async(EncodingStage::RfqQuote, None, async {
+            let mut attempt = 0;
+            loop {
+                let error = match tokio::time::timeout(self.timeout, request()).await {
+                    Ok(Ok(signed_quote)) => return Ok(signed_quote),
+                    Ok(Err(SimulationError::RecoverableError(msg))) => {
+                        EncodingError::RecoverableError(msg)
+                    }
+                    Ok(Err(err)) => return Err(err.into()),
+                    Err(_) => EncodingError::QuoteTimeout(format!(
+                        "No quote received within {:?} ({} attempts)",
+                        self.timeout,
+                        attempt + 1
+                    )),
+                };
+                if attempt >= self.retries {
+                    return Err(error);
+                }
+                tokio::time::sleep(
+                    self.backoff
+                        .saturating_mul(2u32.saturating_pow(attempt)),
+                )
+                .await;
+                attempt += 1;
+            }
+        })
+        .await
+    }
 }
 
 /// Represents an action to be performed on the native token either before or after the swap.
@@ -74,6 +415,18 @@ pub enum NativeAction {
     Unwrap,
 }
 
+/// The deployment a solution is encoded for.
+///
+/// `Production` uses the router and executors the encoder was built with. `Simulation` uses the
+/// separate set of router and executors configured for simulation or staging pipelines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Environment {
+    #[default]
+    Production,
+    Simulation,
+}
+
 /// Represents a swap operation to be performed on a pool.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Swap {
@@ -83,10 +436,14 @@ pub struct Swap {
     token_in: Bytes,
     /// Token being output from the pool.
     token_out: Bytes,
-    /// Decimal of the amount to be swapped in this operation (for example, 0.5 means 50%)
+    /// Decimal of the amount to be swapped in this operation (for example, 0.5 means 50%). A split
+    /// of 0 means that the swap takes the remainder (see [`SplitSpec`]).
     #[serde(default)]
     split: f64,
-    /// Optional user data to be passed to encoding.
+    /// Optional user data to be passed to encoding. Protocols interpret it (e.g. as Uniswap V4
+    /// hook data) or, for executors ignoring it, pass it through on-chain after the protocol
+    /// data (see `append_user_data`). It is ignored on the protocols whose executors can't carry
+    /// it.
     user_data: Option<Bytes>,
     /// Optional protocol state used to perform the swap.
     #[serde(skip)]
@@ -94,6 +451,79 @@ pub struct Swap {
     /// Optional estimated amount in for this Swap. This is necessary for RFQ protocols. This value
     /// is used to request the quote
     estimated_amount_in: Option<BigUint>,
+    /// Optional version of the indexer state this swap was derived from.
+    #[serde(default)]
+    state_version: Option<StateVersion>,
+    /// Whether this is a zero-amount registration swap, used by some RFQ venues to register or
+    /// validate a maker order before the swap that actually fills it.
+    #[serde(default)]
+    registration: bool,
+    /// Optional address receiving the output of this swap instead of the solution receiver. Only
+    /// terminal swaps (whose output is the checked token and isn't swapped again) can override it.
+    #[serde(default)]
+    receiver_override: Option<Bytes>,
+}
+
+/// How much of the amount of its input token a swap takes, when the token is split between several
+/// swaps (a split node).
+///
+/// Every split node must have exactly one `Remainder` swap, which must be the last swap of the node
+/// in the solution: the router sends it whatever the `Fraction` swaps of the node left, so the
+/// float fractions never need to add up to exactly 100%. Swaps of tokens that are not split are
+/// `Remainder` swaps too (they take 100% of the amount).
+///
+/// Variants:
+/// - `Fraction`: The swap takes the given fraction of the amount (for example, 0.5 means 50%).
+/// - `Remainder`: The swap takes what is left of the amount after the other swaps of its node. It
+///   is encoded as a split of 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitSpec {
+    Fraction(f64),
+    Remainder,
+}
+
+impl SplitSpec {
+    /// Returns the split value of the swap, 0 meaning the remainder.
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            SplitSpec::Fraction(fraction) => *fraction,
+            SplitSpec::Remainder => 0.0,
+        }
+    }
+}
+
+impl From<f64> for SplitSpec {
+    fn from(split: f64) -> Self {
+        if split == 0.0 {
+            SplitSpec::Remainder
+        } else {
+            SplitSpec::Fraction(split)
+        }
+    }
+}
+
+/// The version of the Tycho indexer state a swap was derived from.
+///
+/// # Fields
+/// * `block_number`: Number of the block of the state snapshot.
+/// * `block_hash`: Optional hash of the block of the state snapshot.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StateVersion {
+    pub block_number: u64,
+    #[serde(default)]
+    pub block_hash: Option<Bytes>,
+}
+
+impl StateVersion {
+    pub fn new(block_number: u64) -> Self {
+        Self { block_number, block_hash: None }
+    }
+
+    /// Sets the hash of the block of the state snapshot.
+    pub fn with_block_hash(mut self, block_hash: Bytes) -> Self {
+        self.block_hash = Some(block_hash);
+        self
+    }
 }
 
 impl Swap {
@@ -111,15 +541,31 @@ impl Swap {
             user_data: None,
             protocol_state: None,
             estimated_amount_in: None,
+            state_version: None,
+            registration: false,
+            receiver_override: None,
         }
     }
 
-    /// Sets the split value (percentage of the amount to be swapped)
+    /// Sets the split value (percentage of the amount to be swapped). A split of 0 means the
+    /// remainder, prefer [`Swap::remainder`] to make it explicit.
     pub fn split(mut self, split: f64) -> Self {
         self.split = split;
         self
     }
 
+    /// Sets the split of the swap (see [`SplitSpec`]).
+    pub fn split_spec(mut self, split: SplitSpec) -> Self {
+        self.split = split.as_f64();
+        self
+    }
+
+    /// Makes the swap take the remainder of its input token, after the other swaps of its split
+    /// node.
+    pub fn remainder(self) -> Self {
+        self.split_spec(SplitSpec::Remainder)
+    }
+
     /// Sets the user data to be passed to encoding
     pub fn user_data(mut self, user_data: Bytes) -> Self {
         self.user_data = Some(user_data);
@@ -138,6 +584,35 @@ impl Swap {
         self
     }
 
+    /// Sets the version of the indexer state this swap was derived from
+    pub fn state_version(mut self, state_version: StateVersion) -> Self {
+        self.state_version = Some(state_version);
+        self
+    }
+
+    /// Flags the swap as a zero-amount registration swap. Registration swaps have an estimated
+    /// amount in of zero (if any), skip all token transfers and must be followed by the swap that
+    /// fills the order on the same component.
+    pub fn registration(mut self, registration: bool) -> Self {
+        self.registration = registration;
+        self
+    }
+
+    /// Sends the output of the swap to the given address instead of the solution receiver. Only
+    /// allowed on terminal swaps.
+    pub fn receiver_override(mut self, receiver: Bytes) -> Self {
+        self.receiver_override = Some(receiver);
+        self
+    }
+
+    /// Replaces the input and output tokens of the swap. Used when the tokens of an RFQ swap need
+    /// to be aligned with the native or wrapped token used by the rest of the solution.
+    pub(crate) fn with_tokens(mut self, token_in: Bytes, token_out: Bytes) -> Self {
+        self.token_in = token_in;
+        self.token_out = token_out;
+        self
+    }
+
     // Getter methods for accessing private fields
     pub fn component(&self) -> &ProtocolComponent {
         &self.component
@@ -155,6 +630,10 @@ impl Swap {
         self.split
     }
 
+    pub fn get_split_spec(&self) -> SplitSpec {
+        SplitSpec::from(self.split)
+    }
+
     pub fn get_user_data(&self) -> &Option<Bytes> {
         &self.user_data
     }
@@ -166,6 +645,63 @@ impl Swap {
     pub fn get_estimated_amount_in(&self) -> &Option<BigUint> {
         &self.estimated_amount_in
     }
+
+    pub fn get_state_version(&self) -> &Option<StateVersion> {
+        &self.state_version
+    }
+
+    pub fn is_registration(&self) -> bool {
+        self.registration
+    }
+
+    pub fn get_receiver_override(&self) -> &Option<Bytes> {
+        &self.receiver_override
+    }
+
+    /// Returns the protocol state of the swap, or an `EncodingError::FatalError` if it's missing.
+    pub fn amm_state(&self) -> Result<&dyn ProtocolSim, EncodingError> {
+        self.protocol_state
+            .as_deref()
+            .ok_or_else(|| {
+                EncodingError::FatalError(format!(
+                    "protocol_state is required for the {} swap on {}",
+                    self.component.protocol_system, self.component.id
+                ))
+            })
+    }
+
+    /// Returns the protocol state of the swap as an indicatively priced (RFQ) state, or an
+    /// `EncodingError::FatalError` if it's missing or can't provide signed quotes.
+    pub fn rfq_state(&self) -> Result<&dyn IndicativelyPriced, EncodingError> {
+        self.amm_state()?
+            .as_indicatively_priced()
+            .map_err(|err| {
+                EncodingError::FatalError(format!(
+                    "The protocol_state of the {} swap on {} is not indicatively priced: {err}",
+                    self.component.protocol_system, self.component.id
+                ))
+            })
+    }
+
+    /// Returns what the protocol state of the swap supports, so callers can check it before
+    /// encoding.
+    pub fn capabilities(&self) -> StateCapabilities {
+        StateCapabilities {
+            has_state: self.protocol_state.is_some(),
+            indicatively_priced: self.rfq_state().is_ok(),
+        }
+    }
+}
+
+/// Capabilities of the protocol state attached to a swap.
+///
+/// # Fields
+/// * `has_state`: Whether the swap has a protocol state (see [`Swap::amm_state`])
+/// * `indicatively_priced`: Whether the state can provide signed quotes (see [`Swap::rfq_state`])
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StateCapabilities {
+    pub has_state: bool,
+    pub indicatively_priced: bool,
 }
 
 impl PartialEq for Swap {
@@ -175,38 +711,342 @@ impl PartialEq for Swap {
             self.token_out() == other.token_out() &&
             self.get_split() == other.get_split() &&
             self.get_user_data() == other.get_user_data() &&
-            self.get_estimated_amount_in() == other.get_estimated_amount_in()
+            self.get_estimated_amount_in() == other.get_estimated_amount_in() &&
+            self.get_state_version() == other.get_state_version() &&
+            self.is_registration() == other.is_registration() &&
+            self.get_receiver_override() == other.get_receiver_override()
     }
 }
 
 /// Represents a transaction to be executed.
 ///
+/// The calldata can contain signed RFQ quotes and permit signatures, which anyone could execute,
+/// so `Debug` and `Display` only show its function selector and length. Enable the
+/// `unsafe_debug` feature to show it in full.
+///
 /// # Fields
 /// * `to`: Address of the contract to call with the calldata
 /// * `value`: Native token value to be sent with the transaction.
 /// * `data`: Encoded calldata for the transaction.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Transaction {
     pub to: Bytes,
     pub value: BigUint,
     pub data: Vec<u8>,
 }
 
+impl fmt::Debug for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transaction")
+            .field("to", &self.to)
+            .field("value", &self.value)
+            .field("data", &DebugCalldata(&self.data))
+            .finish()
+    }
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "call to {} with value {} and data {:?}",
+            self.to,
+            self.value,
+            DebugCalldata(&self.data)
+        )
+    }
+}
+
+/// A call composed with others into a single multicall transaction (see
+/// `encoding_utils::compose_calls`), e.g. an aToken withdrawal before the Tycho router call.
+///
+/// Like for `Transaction`, `Debug` only shows the function selector and length of the calldata
+/// unless the `unsafe_debug` feature is enabled.
+///
+/// # Fields
+/// * `to`: Address of the contract to call.
+/// * `value`: Native token value to be sent with the call.
+/// * `data`: Calldata of the call.
+/// * `allow_failure`: Whether the multicall goes on if the call reverts. Only supported by
+///   `Multicall::Multicall3`.
+#[derive(Clone)]
+pub struct CallSpec {
+    pub to: Bytes,
+    pub value: BigUint,
+    pub data: Vec<u8>,
+    pub allow_failure: bool,
+}
+
+impl From<Transaction> for CallSpec {
+    fn from(transaction: Transaction) -> Self {
+        Self {
+            to: transaction.to,
+            value: transaction.value,
+            data: transaction.data,
+            allow_failure: false,
+        }
+    }
+}
+
+impl fmt::Debug for CallSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallSpec")
+            .field("to", &self.to)
+            .field("value", &self.value)
+            .field("data", &DebugCalldata(&self.data))
+            .field("allow_failure", &self.allow_failure)
+            .finish()
+    }
+}
+
+/// The multicall-capable contract executing composed calls, with the address it is called at.
+///
+/// # Variants
+/// * `Multicall3`: The Multicall3 contract, called with `aggregate3Value`. The value of the calls
+///   is sent with the multicall transaction. The calls are executed with Multicall3 as the sender,
+///   so they can't spend the tokens of the caller.
+/// * `SafeMultiSend`: The `MultiSendCallOnly` contract of Safe, called with `multiSend`. The
+///   transaction must be executed by the Safe with a delegate call, so that the calls are executed
+///   with the Safe as the sender and paid from its balance.
+/// * `AccountBatch`: A smart account called with `executeBatch((address,uint256,bytes)[])` (e.g.
+///   ERC-4337 accounts like the Coinbase Smart Wallet or the `SimpleAccount` v0.8), whose address
+///   is the account. The calls are executed with the account as the sender and paid from its
+///   balance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Multicall {
+    Multicall3(Bytes),
+    SafeMultiSend(Bytes),
+    AccountBatch(Bytes),
+}
+
+/// Options of the transaction request built for a `Transaction` (see
+/// `evm::transaction::build_transaction_request`).
+///
+/// The fields left unset are left to be filled by the provider or the wallet submitting the
+/// transaction.
+///
+/// # Fields
+/// * `max_fee_per_gas`: Maximum total fee per gas, in wei. If set, an EIP-1559 transaction is
+///   built.
+/// * `max_priority_fee_per_gas`: Maximum priority fee per gas, in wei. If set, an EIP-1559
+///   transaction is built. Can't exceed `max_fee_per_gas`.
+/// * `nonce`: Nonce of the sender.
+/// * `gas_limit`: Gas limit of the transaction.
+/// * `access_list`: Whether to add an access list with the executors, pools and tokens the solution
+///   interacts with, which makes their first access cheaper.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransactionOptions {
+    pub max_fee_per_gas: Option<u128>,
+    pub max_priority_fee_per_gas: Option<u128>,
+    pub nonce: Option<u64>,
+    pub gas_limit: Option<u64>,
+    pub access_list: bool,
+}
+
 /// Represents a solution that has been encoded for execution.
 ///
+/// The encoded swaps can contain signed RFQ quotes, so `Debug` only shows their length. Enable the
+/// `unsafe_debug` feature to show them in full.
+///
 /// # Fields
 /// * `swaps`: Encoded swaps to be executed.
 /// * `interacting_with`: Address of the contract to be called.
 /// * `function_signature`: The signature of the function to be called.
 /// * `n_tokens`: Number of tokens in the swap.
 /// * `permit`: Optional permit for the swap (if permit2 is enabled).
-#[derive(Clone, Debug)]
+/// * `permit_batch`: Optional batch permit authorizing several tokens at once (see
+///   `TychoRouterEncoder::encode_solutions_with_permit_batch`).
+/// * `native_action`: Native action the router call must perform. This can differ from the
+///   solution's native action if a wrap or unwrap step was inserted during encoding.
+/// * `state_versions`: Version of the indexer state each swap was derived from (in the order of the
+///   solution's swaps), to correlate the encoding with the state snapshot that produced it.
+/// * `callback_allowlist`: Contracts expected to call back into the router during the swap (the
+///   executors and settlement contracts involved in the solution). Advisory only: it isn't encoded
+///   in the calldata nor enforced on-chain (see `CallbackAllowlist`). Only set if the encoder was
+///   configured with a callback allowlist.
+/// * `summary`: Size and complexity of the encoded solution.
+/// * `gas_estimate`: Estimated gas of the call: the router overhead plus the static estimate of
+///   every swap, including the grouped ones (see `SwapEncoderRegistry::gas_estimate`). Meant to
+///   compare candidate solutions without simulating them, not as a gas limit.
+/// * `debug_info`: How the token transfers of every encoded swap were decided. Only set by the
+///   router strategy encoders.
+/// * `degraded_groups`: Groups of swaps which failed to encode as one swap execution and were
+///   encoded as individual swaps instead (see `TychoRouterEncoder::with_ungrouped_fallback`). Their
+///   extra transfers make the solution cost more gas than planned.
+#[derive(Clone)]
 pub struct EncodedSolution {
     pub swaps: Vec<u8>,
     pub interacting_with: Bytes,
     pub function_signature: String,
     pub n_tokens: usize,
     pub permit: Option<PermitSingle>,
+    pub permit_batch: Option<PermitBatch>,
+    pub native_action: Option<NativeAction>,
+    pub state_versions: Vec<Option<StateVersion>>,
+    pub callback_allowlist: Option<Vec<Bytes>>,
+    pub summary: EncodedSolutionSummary,
+    pub gas_estimate: u64,
+    pub debug_info: Option<EncodingDebugInfo>,
+    pub degraded_groups: Vec<DegradedSwapGroup>,
+}
+
+/// Summary of the size and complexity of an encoded solution, so that it can be logged and
+/// monitored without decoding the encoded swaps.
+///
+/// # Fields
+/// * `calldata_bytes`: Estimated size of the calldata of the router call, i.e. the function
+///   selector, the ABI encoded arguments and the encoded swaps. Permit signatures are assumed to be
+///   65 bytes long.
+/// * `swaps_bytes`: Size of the encoded swaps.
+/// * `n_hops`: Number of swaps in the solution.
+/// * `n_groups`: Number of encoded swaps, after consecutive swaps on the same protocol were grouped
+///   (e.g. Uniswap V4 swaps).
+/// * `transfer_types`: Number of encoded swaps per transfer type.
+/// * `calldata_gas`: Estimated intrinsic gas of the calldata (4 gas per zero byte and 16 per
+///   non-zero byte). The encoded swaps are counted exactly and the other bytes as non-zero, so this
+///   is an upper bound.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EncodedSolutionSummary {
+    pub calldata_bytes: usize,
+    pub swaps_bytes: usize,
+    pub n_hops: usize,
+    pub n_groups: usize,
+    pub transfer_types: BTreeMap<TransferType, usize>,
+    pub calldata_gas: u64,
+}
+
+/// A group of consecutive swaps on the same protocol (see `group_swaps`) which failed to encode as
+/// one swap execution, and whose swaps were encoded individually instead.
+///
+/// # Fields
+/// * `protocol_system`: Protocol of the swaps in the group.
+/// * `token_in`: Token sold by the group.
+/// * `token_out`: Token bought by the group.
+/// * `n_swaps`: Number of swaps in the group, each encoded as its own swap.
+/// * `reason`: The error the group failed to encode with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DegradedSwapGroup {
+    pub protocol_system: String,
+    pub token_in: Bytes,
+    pub token_out: Bytes,
+    pub n_swaps: usize,
+    pub reason: String,
+}
+
+/// The transfer decisions of an encoded solution, so that a reverted router call can be reviewed
+/// without decoding the encoded swaps.
+///
+/// # Fields
+/// * `swap_groups`: Decisions for every encoded swap, in the order of the encoded swaps.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EncodingDebugInfo {
+    pub swap_groups: Vec<SwapGroupDebugInfo>,
+}
+
+/// The transfer decisions of one encoded swap, i.e. of a group of consecutive swaps on the same
+/// protocol (see `group_swaps`).
+///
+/// # Fields
+/// * `protocol_system`: Protocol of the swaps in the group.
+/// * `executor`: Address of the executor performing the swaps of the group.
+/// * `token_in`: Token sold by the group.
+/// * `token_out`: Token bought by the group.
+/// * `transfer_type`: How the token in is transferred into the pool (or the router) before the
+///   swap.
+/// * `receiver`: Address receiving the token out: the solution's receiver (or the swap's receiver
+///   override), the router, or the pool of the next swap.
+/// * `in_between_optimization`: Whether the previous swap sent its token out directly to the pool
+///   of this swap, so that no transfer into the pool is needed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapGroupDebugInfo {
+    pub protocol_system: String,
+    pub executor: Bytes,
+    pub token_in: Bytes,
+    pub token_out: Bytes,
+    pub transfer_type: TransferType,
+    pub receiver: Bytes,
+    pub in_between_optimization: bool,
+}
+
+impl fmt::Debug for EncodedSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncodedSolution")
+            .field("swaps", &DebugSwaps(&self.swaps))
+            .field("interacting_with", &self.interacting_with)
+            .field("function_signature", &self.function_signature)
+            .field("n_tokens", &self.n_tokens)
+            .field("permit", &self.permit)
+            .field("permit_batch", &self.permit_batch)
+            .field("native_action", &self.native_action)
+            .field("state_versions", &self.state_versions)
+            .field("callback_allowlist", &self.callback_allowlist)
+            .field("summary", &self.summary)
+            .field("gas_estimate", &self.gas_estimate)
+            .field("debug_info", &self.debug_info)
+            .field("degraded_groups", &self.degraded_groups)
+            .finish()
+    }
+}
+
+/// The encoded swaps of a solution, without a router function to wrap them in (see
+/// `TychoRouterEncoder::encode_swaps_only`).
+///
+/// Like for `EncodedSolution`, `Debug` only shows the length of the encoded swaps unless the
+/// `unsafe_debug` feature is enabled.
+///
+/// # Fields
+/// * `swaps`: The encoded swaps, as passed to the router function. Sequential and split swaps are
+///   prefix-length encoded (preceded by the executor table if the encoder uses one).
+/// * `segments`: The encoded swap of every swap group, in order. A single swap has one segment, the
+///   whole encoded swaps.
+/// * `executors`: The executor of every swap group, in order.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncodedSwaps {
+    pub swaps: Vec<u8>,
+    pub segments: Vec<Vec<u8>>,
+    pub executors: Vec<Bytes>,
+}
+
+impl fmt::Debug for EncodedSwaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncodedSwaps")
+            .field("swaps", &DebugSwaps(&self.swaps))
+            .field(
+                "segments",
+                &self
+                    .segments
+                    .iter()
+                    .map(|segment| DebugSwaps(segment))
+                    .collect::<Vec<_>>(),
+            )
+            .field("executors", &self.executors)
+            .finish()
+    }
+}
+
+/// Formats calldata as its function selector and length, or in full with the `unsafe_debug`
+/// feature.
+struct DebugCalldata<'a>(&'a [u8]);
+
+impl fmt::Debug for DebugCalldata<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "unsafe_debug") || self.0.len() <= 4 {
+            return write!(f, "0x{}", hex::encode(self.0));
+        }
+        write!(f, "0x{}<{} bytes redacted>", hex::encode(&self.0[..4]), self.0.len() - 4)
+    }
+}
+
+/// Formats encoded swaps as their length, or in full with the `unsafe_debug` feature.
+struct DebugSwaps<'a>(&'a [u8]);
+
+impl fmt::Debug for DebugSwaps<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "unsafe_debug") {
+            return write!(f, "0x{}", hex::encode(self.0));
+        }
+        write!(f, "<{} bytes redacted>", self.0.len())
+    }
 }
 
 /// Represents a single permit for permit2.
@@ -222,6 +1062,19 @@ pub struct PermitSingle {
     pub sig_deadline: BigUint,
 }
 
+/// Represents a batch permit for permit2, approving several tokens with a single signature.
+///
+/// # Fields
+/// * `details`: The details of the permit of each token.
+/// * `spender`: The address authorized to spend the tokens.
+/// * `sig_deadline`: The deadline (as a timestamp) for the permit signature
+#[derive(Debug, Clone)]
+pub struct PermitBatch {
+    pub details: Vec<PermitDetails>,
+    pub spender: Bytes,
+    pub sig_deadline: BigUint,
+}
+
 /// Details of a permit.
 ///
 /// # Fields
@@ -244,6 +1097,13 @@ impl PartialEq for PermitSingle {
     }
 }
 
+impl PartialEq for PermitBatch {
+    fn eq(&self, other: &Self) -> bool {
+        self.details == other.details && self.spender == other.spender
+        // sig_deadline is intentionally ignored
+    }
+}
+
 impl PartialEq for PermitDetails {
     fn eq(&self, other: &Self) -> bool {
         self.token == other.token && self.amount == other.amount && self.nonce == other.nonce
@@ -263,7 +1123,12 @@ impl PartialEq for PermitDetails {
 /// * `group_token_out`: Token to be used as the output for the group swap.
 /// * `transfer`: Type of transfer to be performed. See `TransferType` for more details.
 /// * `historical_trade`: Whether the swap is to be done in the current block or in an historical
-///   one. This is relevant for checking token approvals in some protocols (like Balancer v2).
+///   one. Historical swaps are encoded offline: approvals are not looked up (the spender is always
+///   approved) and RFQ quotes are read from the swap's user data instead of being requested.
+/// * `execution_deadline`: Optional unix timestamp (in seconds) by which the solution is expected
+///   to be executed (see `Solution::execution_deadline`).
+/// * `approval_policy`: Whether the router's approvals of the protocols are looked up on chain or
+///   assumed (see `ApprovalPolicy`).
 #[derive(Clone, Debug)]
 pub struct EncodingContext {
     pub receiver: Bytes,
@@ -273,6 +1138,43 @@ pub struct EncodingContext {
     pub group_token_out: Bytes,
     pub transfer_type: TransferType,
     pub historical_trade: bool,
+    pub execution_deadline: Option<u64>,
+    pub approval_policy: ApprovalPolicy,
+}
+
+impl EncodingContext {
+    /// Returns whether the router must approve the spender of a swap, if it doesn't depend on the
+    /// router's current allowance: as set by the approval policy, or always for historical trades
+    /// under the live policy. Returns `None` if the allowance has to be looked up on chain.
+    pub fn fixed_approval(&self) -> Option<bool> {
+        match self.approval_policy {
+            ApprovalPolicy::AlwaysApprove => Some(true),
+            ApprovalPolicy::NeverApprove => Some(false),
+            ApprovalPolicy::Live => self.historical_trade.then_some(true),
+        }
+    }
+}
+
+/// How the swap encoders decide whether the router must approve the protocols it swaps through
+/// (e.g. the Balancer vault or a Curve pool).
+///
+/// Looking the router's allowances up makes the calldata depend on the chain state at encoding
+/// time. The fixed policies make it reproducible across runs (e.g. for calldata caching and test
+/// fixtures), at the cost of redundant approvals (`AlwaysApprove`) or of reverting swaps if the
+/// router isn't approved yet (`NeverApprove`).
+///
+/// # Variants
+///
+/// * `AlwaysApprove`: The router approves the spender on every swap.
+/// * `NeverApprove`: The router never approves the spender, which must already be approved.
+/// * `Live`: The router approves the spender if its current allowance is insufficient (the
+///   default). Historical trades always approve, as they are encoded offline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ApprovalPolicy {
+    AlwaysApprove,
+    NeverApprove,
+    #[default]
+    Live,
 }
 
 /// Represents the type of transfer to be performed into the pool.
@@ -283,7 +1185,7 @@ pub struct EncodingContext {
 /// * `Transfer`: Transfer the token from the router into the protocol.
 /// * `None`: No transfer is needed. Tokens are already in the pool.
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TransferType {
     TransferFrom = 0,
     Transfer = 1,
@@ -333,4 +1235,263 @@ mod tests {
         assert_eq!(swap.get_split(), 0.5);
         assert_eq!(swap.get_user_data(), &Some(user_data));
     }
+
+    #[test]
+    fn test_swap_split_spec() {
+        let swap =
+            Swap::new(ProtocolComponent::default(), Bytes::from("0x12"), Bytes::from("0x34"));
+        assert_eq!(swap.get_split_spec(), SplitSpec::Remainder);
+
+        let swap = swap.split_spec(SplitSpec::Fraction(0.25));
+        assert_eq!(swap.get_split(), 0.25);
+        assert_eq!(swap.get_split_spec(), SplitSpec::Fraction(0.25));
+
+        let swap = swap.remainder();
+        assert_eq!(swap.get_split(), 0.0);
+        assert_eq!(swap.get_split_spec(), SplitSpec::Remainder);
+    }
+
+    #[test]
+    fn test_swap_capabilities() {
+        use std::collections::HashMap;
+
+        use crate::encoding::evm::testing_utils::{MockAmmState, MockRFQState};
+
+        let component = || MockProtocolComponent {
+            id: "i-am-an-id".to_string(),
+            protocol_system: "rfq:bebop".to_string(),
+        };
+        let swap = Swap::new(component(), Bytes::from("0x12"), Bytes::from("0x34"));
+        assert_eq!(swap.capabilities(), StateCapabilities::default());
+        assert!(matches!(swap.amm_state(), Err(EncodingError::FatalError(_))));
+        assert!(matches!(swap.rfq_state(), Err(EncodingError::FatalError(_))));
+
+        let rfq_swap = swap
+            .clone()
+            .protocol_state(Arc::new(MockRFQState {
+                quote_amount_out: BigUint::from(1u32),
+                quote_data: HashMap::new(),
+            }));
+        assert_eq!(
+            rfq_swap.capabilities(),
+            StateCapabilities { has_state: true, indicatively_priced: true }
+        );
+
+        let amm_swap = swap.protocol_state(Arc::new(MockAmmState::constant_product(
+            Bytes::from("0x12"),
+            BigUint::from(1u32),
+            Bytes::from("0x34"),
+            BigUint::from(1u32),
+        )));
+        assert_eq!(
+            amm_swap.capabilities(),
+            StateCapabilities { has_state: true, indicatively_priced: false }
+        );
+        assert!(amm_swap.amm_state().is_ok());
+        assert!(amm_swap.rfq_state().is_err());
+    }
+
+    #[test]
+    fn test_slippage_rounding() {
+        let slippage = SlippageConfig::new(30).unwrap();
+        // 1.000001 USDC
+        let expected_amount = BigUint::from(1_000_001u32);
+
+        // 997_000.997 and 1_003_001.003 are rounded towards the expected amount
+        assert_eq!(
+            slippage
+                .min_amount_out(&expected_amount)
+                .unwrap(),
+            BigUint::from(997_001u32)
+        );
+        assert_eq!(
+            slippage
+                .max_amount_in(&expected_amount)
+                .unwrap(),
+            BigUint::from(1_003_001u32)
+        );
+        // Exact amounts are not rounded
+        assert_eq!(
+            slippage
+                .min_amount_out(&BigUint::from(10_000u32))
+                .unwrap(),
+            BigUint::from(9_970u32)
+        );
+        // 1 satoshi of WBTC can't lose 0.3%
+        assert_eq!(
+            slippage
+                .min_amount_out(&BigUint::from(1u32))
+                .unwrap(),
+            BigUint::from(1u32)
+        );
+        assert!(SlippageConfig::new(10_001).is_err());
+    }
+
+    #[test]
+    fn test_resolved_checked_amount() {
+        let solution = Solution {
+            checked_amount: BigUint::from(1u32),
+            expected_amount: Some(BigUint::from(10_000u32)),
+            ..Default::default()
+        };
+        let default_slippage = SlippageConfig::new(100).unwrap();
+
+        assert_eq!(
+            solution
+                .resolved_checked_amount(None)
+                .unwrap(),
+            BigUint::from(1u32)
+        );
+        assert_eq!(
+            solution
+                .resolved_checked_amount(Some(&default_slippage))
+                .unwrap(),
+            BigUint::from(9_900u32)
+        );
+
+        let exact_out =
+            Solution { exact_out: true, slippage: Some(SlippageConfig { bps: 50 }), ..solution };
+        assert_eq!(
+            exact_out
+                .resolved_checked_amount(Some(&default_slippage))
+                .unwrap(),
+            BigUint::from(10_050u32)
+        );
+
+        let missing_expected_amount = Solution { expected_amount: None, ..exact_out };
+        assert!(matches!(
+            missing_expected_amount.resolved_checked_amount(None),
+            Err(EncodingError::InvalidInput(_))
+        ));
+        let without_slippage = Solution { slippage: None, ..missing_expected_amount };
+        assert_eq!(
+            without_slippage
+                .resolved_checked_amount(Some(&default_slippage))
+                .unwrap(),
+            BigUint::from(1u32)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rfq_config_retries_recoverable_errors() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let attempts = AtomicU32::new(0);
+        let counter = &attempts;
+        let rfq_config = RfqConfig::new(Duration::from_secs(1), 2, Duration::from_millis(1));
+
+        let signed_quote = rfq_config
+            .request_signed_quote(move || async move {
+                if counter.fetch_add(1, Ordering::Relaxed) < 2 {
+                    return Err(SimulationError::RecoverableError("Maker unavailable".to_string()));
+                }
+                Ok(SignedQuote {
+                    base_token: Bytes::zero(20),
+                    quote_token: Bytes::zero(20),
+                    amount_in: BigUint::from(1u32),
+                    amount_out: BigUint::from(2u32),
+                    quote_attributes: Default::default(),
+                })
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(signed_quote.amount_out, BigUint::from(2u32));
+        assert_eq!(attempts.into_inner(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rfq_config_times_out() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let attempts = AtomicU32::new(0);
+        let counter = &attempts;
+        let rfq_config = RfqConfig::new(Duration::from_millis(10), 1, Duration::from_millis(1));
+
+        let result = rfq_config
+            .request_signed_quote(move || async move {
+                counter.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Err(SimulationError::FatalError("Unreachable".to_string()))
+            })
+            .await;
+
+        assert!(matches!(result, Err(EncodingError::QuoteTimeout(_))));
+        assert_eq!(attempts.into_inner(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rfq_config_does_not_retry_invalid_requests() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let attempts = AtomicU32::new(0);
+        let counter = &attempts;
+
+        let result = RfqConfig::default()
+            .request_signed_quote(move || async move {
+                counter.fetch_add(1, Ordering::Relaxed);
+                Err(SimulationError::FatalError("Unsupported token".to_string()))
+            })
+            .await;
+
+        assert!(matches!(result, Err(EncodingError::FatalError(_))));
+        assert_eq!(attempts.into_inner(), 1);
+    }
+
+    #[test]
+    fn test_rfq_config_check_quote_expiry() {
+        let rfq_config = RfqConfig::default().with_expiry_buffer(Duration::from_secs(30));
+        let now = 1_750_000_000;
+
+        assert!(rfq_config
+            .check_quote_expiry(now + 30, None, now)
+            .is_ok());
+        assert!(matches!(
+            rfq_config.check_quote_expiry(now + 29, None, now),
+            Err(EncodingError::QuoteExpired(_))
+        ));
+        // The buffer applies to the execution deadline instead of now
+        assert!(matches!(
+            rfq_config.check_quote_expiry(now + 60, Some(now + 45), now),
+            Err(EncodingError::QuoteExpired(_))
+        ));
+        assert!(rfq_config
+            .check_quote_expiry(now + 60, Some(now + 30), now)
+            .is_ok());
+    }
+
+    #[test]
+    #[cfg(not(feature = "unsafe_debug"))]
+    fn test_debug_redacts_calldata() {
+        let transaction = Transaction {
+            to: Bytes::from("0x12"),
+            value: BigUint::from(1u32),
+            data: vec![0xaa, 0xbb, 0xcc, 0xdd, 0x01, 0x02, 0x03],
+        };
+        let debug = format!("{transaction:?}");
+        assert!(debug.contains("0xaabbccdd<3 bytes redacted>"));
+        assert!(!debug.contains("010203"));
+        assert!(transaction
+            .to_string()
+            .contains("0xaabbccdd<3 bytes redacted>"));
+
+        let encoded_solution = EncodedSolution {
+            swaps: vec![0x01, 0x02, 0x03],
+            interacting_with: Bytes::from("0x12"),
+            function_signature: "singleSwap()".to_string(),
+            n_tokens: 0,
+            permit: None,
+            permit_batch: None,
+            native_action: None,
+            state_versions: vec![],
+            callback_allowlist: None,
+            summary: Default::default(),
+            gas_estimate: 0,
+            debug_info: None,
+            degraded_groups: vec![],
+        };
+        let debug = format!("{encoded_solution:?}");
+        assert!(debug.contains("<3 bytes redacted>"));
+        assert!(debug.contains("singleSwap()"));
+    }
 }
@@ -0,0 +1,430 @@
+// This module is only compiled with the `service` feature.
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use tycho_common::Bytes;
+
+use crate::encoding::{
+    errors::EncodingError,
+    evm::{approvals::permit_signer::PermitSigner, encoding_utils::encode_tycho_router_call},
+    models::{EncodedSolution, Solution, Transaction, UserTransferType},
+    tycho_encoder::TychoEncoder,
+};
+
+/// Configuration of an [`EncodingService`].
+///
+/// # Fields
+/// * `concurrency`: Number of solutions encoded in parallel (one worker thread each).
+/// * `max_retries`: Number of times a solution is encoded again after an
+///   `EncodingError::RecoverableError`, `EncodingError::QuoteTimeout` (e.g. a timed out RFQ quote
+///   request) or `EncodingError::QuoteExpired` (a new quote is requested on the retry).
+/// * `retry_backoff`: Delay before each retry.
+/// * `budget`: Optional time budget of each solution. No retry is started once it's spent, so stale
+///   solutions aren't encoded over and over.
+/// * `router_call`: Optional configuration of the router calls. If set, the service emits full
+///   router transactions (see [`RouterCallConfig`]) instead of encoded solutions.
+#[derive(Clone, Debug)]
+pub struct ServiceConfig {
+    pub concurrency: usize,
+    pub max_retries: u32,
+    pub retry_backoff: Duration,
+    pub budget: Option<Duration>,
+    pub router_call: Option<RouterCallConfig>,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(100),
+            budget: None,
+            router_call: None,
+        }
+    }
+}
+
+/// How the service builds a router transaction from every encoded solution, with
+/// [`encode_tycho_router_call`]. The same responsibility applies to the checked amounts of the
+/// solutions, which are used as they are.
+///
+/// # Fields
+/// * `chain_id`: Chain ID the permits are signed for.
+/// * `user_transfer_type`: How the router takes the given token from the user. It must match the
+///   transfer type the encoder was built with.
+/// * `native_address`: Address representing the native token of the chain.
+/// * `permit_signer`: Optional signer of the Permit2 permits, required with
+///   `UserTransferType::TransferFromPermit2`.
+#[derive(Clone)]
+pub struct RouterCallConfig {
+    pub chain_id: u64,
+    pub user_transfer_type: UserTransferType,
+    pub native_address: Bytes,
+    pub permit_signer: Option<Arc<dyn PermitSigner>>,
+}
+
+impl fmt::Debug for RouterCallConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouterCallConfig")
+            .field("chain_id", &self.chain_id)
+            .field("user_transfer_type", &self.user_transfer_type)
+            .field("native_address", &self.native_address)
+            .field("permit_signer", &self.permit_signer.is_some())
+            .finish()
+    }
+}
+
+/// What the service emits for a successfully encoded solution.
+#[derive(Clone, Debug)]
+pub enum ServiceOutput {
+    EncodedSolution(Box<EncodedSolution>),
+    Transaction(Transaction),
+}
+
+/// The outcome of encoding one solution received by the service.
+///
+/// # Fields
+/// * `sequence`: Position of the solution in the input stream, starting at 0. Results are emitted
+///   as soon as they are ready, so they can be out of order when `concurrency` is above 1.
+/// * `attempts`: Number of times the solution was encoded.
+/// * `latency`: Time spent encoding the solution, retries included.
+/// * `result`: The encoded output, or the error of the last attempt.
+#[derive(Debug)]
+pub struct ServiceResult {
+    pub sequence: u64,
+    pub attempts: u32,
+    pub latency: Duration,
+    pub result: Result<ServiceOutput, EncodingError>,
+}
+
+/// Counters of an [`EncodingService`].
+///
+/// # Fields
+/// * `received`: Number of solutions received.
+/// * `encoded`: Number of solutions encoded successfully.
+/// * `failed`: Number of solutions that could not be encoded.
+/// * `retries`: Number of retries over all solutions.
+/// * `budget_exceeded`: Number of solutions given up because their budget was spent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServiceMetrics {
+    pub received: u64,
+    pub encoded: u64,
+    pub failed: u64,
+    pub retries: u64,
+    pub budget_exceeded: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    received: AtomicU64,
+    encoded: AtomicU64,
+    failed: AtomicU64,
+    retries: AtomicU64,
+    budget_exceeded: AtomicU64,
+}
+
+impl Counters {
+    fn snapshot(&self) -> ServiceMetrics {
+        ServiceMetrics {
+            received: self.received.load(Ordering::Relaxed),
+            encoded: self.encoded.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            budget_exceeded: self
+                .budget_exceeded
+                .load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Encodes a stream of solutions in the background, with retries and metrics.
+///
+/// Solutions are read from a channel and every result is sent to another one, so the service can
+/// sit between a solver and a transaction submitter without each integration rebuilding the same
+/// orchestration around [`TychoEncoder::encode_solutions`]. The service stops once the solution
+/// channel is closed and all received solutions are encoded, or once the result channel is
+/// closed.
+pub struct EncodingService {
+    encoder: Arc<dyn TychoEncoder>,
+    config: ServiceConfig,
+}
+
+impl EncodingService {
+    pub fn new(encoder: Arc<dyn TychoEncoder>, config: ServiceConfig) -> Self {
+        Self { encoder, config }
+    }
+
+    /// Starts the worker threads of the service.
+    pub fn spawn(
+        self,
+        solutions: Receiver<Solution>,
+        results: Sender<ServiceResult>,
+    ) -> Result<ServiceHandle, EncodingError> {
+        if self.config.concurrency == 0 {
+            return Err(EncodingError::InvalidInput(
+                "The concurrency of the encoding service must be positive".to_string(),
+            ));
+        }
+        let solutions = Arc::new(Mutex::new(solutions));
+        let counters = Arc::new(Counters::default());
+        let workers = (0..self.config.concurrency)
+            .map(|_| {
+                let worker = Worker {
+                    encoder: self.encoder.clone(),
+                    config: self.config.clone(),
+                    counters: counters.clone(),
+                };
+                let solutions = solutions.clone();
+                let results = results.clone();
+                thread::spawn(move || worker.run(&solutions, &results))
+            })
+            .collect();
+        Ok(ServiceHandle { workers, counters })
+    }
+}
+
+/// Handle of a running [`EncodingService`].
+pub struct ServiceHandle {
+    workers: Vec<JoinHandle<()>>,
+    counters: Arc<Counters>,
+}
+
+impl ServiceHandle {
+    /// Returns the current metrics of the service.
+    pub fn metrics(&self) -> ServiceMetrics {
+        self.counters.snapshot()
+    }
+
+    /// Waits for the service to stop and returns its final metrics.
+    pub fn join(self) -> Result<ServiceMetrics, EncodingError> {
+        for worker in self.workers {
+            worker.join().map_err(|_| {
+                EncodingError::FatalError("An encoding service worker panicked".to_string())
+            })?;
+        }
+        Ok(self.counters.snapshot())
+    }
+}
+
+struct Worker {
+    encoder: Arc<dyn TychoEncoder>,
+    config: ServiceConfig,
+    counters: Arc<Counters>,
+}
+
+impl Worker {
+    fn run(&self, solutions: &Mutex<Receiver<Solution>>, results: &Sender<ServiceResult>) {
+        loop {
+            // The sequence is assigned while holding the lock so it follows the input order
+            let next = {
+                let Ok(solutions) = solutions.lock() else {
+                    return;
+                };
+                solutions.recv().map(|solution| {
+                    let sequence = self
+                        .counters
+                        .received
+                        .fetch_add(1, Ordering::Relaxed);
+                    (sequence, solution)
+                })
+            };
+            let Ok((sequence, solution)) = next else {
+                return;
+            };
+            let result = self.encode(sequence, solution);
+            if results.send(result).is_err() {
+                return;
+            }
+        }
+    }
+
+    fn encode(&self, sequence: u64, solution: Solution) -> ServiceResult {
+        let start = Instant::now();
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+            let result = self.encode_once(solution.clone());
+            let Err(
+                EncodingError::RecoverableError(_) |
+                EncodingError::QuoteTimeout(_) |
+                EncodingError::QuoteExpired(_),
+            ) = result
+            else {
+                break result;
+            };
+            if attempts > self.config.max_retries {
+                break result;
+            }
+            if self
+                .config
+                .budget
+                .is_some_and(|budget| start.elapsed() + self.config.retry_backoff >= budget)
+            {
+                self.counters
+                    .budget_exceeded
+                    .fetch_add(1, Ordering::Relaxed);
+                break result;
+            }
+            self.counters
+                .retries
+                .fetch_add(1, Ordering::Relaxed);
+            thread::sleep(self.config.retry_backoff);
+        };
+        let counter = if result.is_ok() { &self.counters.encoded } else { &self.counters.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+        ServiceResult { sequence, attempts, latency: start.elapsed(), result }
+    }
+
+    fn encode_once(&self, solution: Solution) -> Result<ServiceOutput, EncodingError> {
+        let encoded_solution = self
+            .encoder
+            .encode_solutions(vec![solution.clone()])?
+            .pop()
+            .ok_or_else(|| {
+                EncodingError::FatalError(
+                    "The encoder returned no output for the solution".to_string(),
+                )
+            })?;
+        let Some(router_call) = &self.config.router_call else {
+            return Ok(ServiceOutput::EncodedSolution(Box::new(encoded_solution)));
+        };
+        encode_tycho_router_call(
+            router_call.chain_id,
+            encoded_solution,
+            &solution,
+            &router_call.user_transfer_type,
+            &router_call.native_address,
+            router_call.permit_signer.as_deref(),
+        )
+        .map(ServiceOutput::Transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::AtomicUsize, mpsc};
+
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    /// Fails the first encoding of every solution with a recoverable error, and always fails
+    /// solutions without a given amount.
+    #[derive(Default)]
+    struct FlakyEncoder {
+        calls: AtomicUsize,
+    }
+
+    impl TychoEncoder for FlakyEncoder {
+        fn encode_solutions(
+            &self,
+            solutions: Vec<Solution>,
+        ) -> Result<Vec<EncodedSolution>, EncodingError> {
+            let solution = &solutions[0];
+            if solution.given_amount == BigUint::ZERO {
+                return Err(EncodingError::InvalidInput("Zero given amount".to_string()));
+            }
+            if self
+                .calls
+                .fetch_add(1, Ordering::Relaxed)
+                .is_multiple_of(2)
+            {
+                return Err(EncodingError::RecoverableError("Quote request timed out".to_string()));
+            }
+            Ok(vec![EncodedSolution {
+                swaps: vec![],
+                interacting_with: Bytes::zero(20),
+                function_signature: String::new(),
+                n_tokens: 0,
+                permit: None,
+                permit_batch: None,
+                native_action: None,
+                state_versions: vec![],
+                callback_allowlist: None,
+                summary: Default::default(),
+                gas_estimate: 0,
+                debug_info: None,
+                degraded_groups: vec![],
+            }])
+        }
+
+        fn encode_full_calldata(
+            &self,
+            _solutions: Vec<Solution>,
+        ) -> Result<Vec<Transaction>, EncodingError> {
+            Ok(vec![])
+        }
+
+        fn validate_solution(&self, _solution: &Solution) -> Result<(), EncodingError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_encoding_service() {
+        let config =
+            ServiceConfig { concurrency: 1, retry_backoff: Duration::ZERO, ..Default::default() };
+        let service = EncodingService::new(Arc::new(FlakyEncoder::default()), config);
+        let (solution_sender, solution_receiver) = mpsc::channel();
+        let (result_sender, result_receiver) = mpsc::channel();
+        let handle = service
+            .spawn(solution_receiver, result_sender)
+            .unwrap();
+
+        for given_amount in [1u32, 0, 2] {
+            solution_sender
+                .send(Solution { given_amount: BigUint::from(given_amount), ..Default::default() })
+                .unwrap();
+        }
+        drop(solution_sender);
+        let results: Vec<ServiceResult> = result_receiver.iter().collect();
+        let metrics = handle.join().unwrap();
+
+        assert_eq!(results.len(), 3);
+        // Recoverable errors are retried
+        assert_eq!(results[0].attempts, 2);
+        assert!(matches!(results[0].result, Ok(ServiceOutput::EncodedSolution(_))));
+        // Other errors are not
+        assert_eq!(results[1].attempts, 1);
+        assert!(matches!(results[1].result, Err(EncodingError::InvalidInput(_))));
+        assert_eq!(
+            metrics,
+            ServiceMetrics { received: 3, encoded: 2, failed: 1, retries: 2, budget_exceeded: 0 }
+        );
+    }
+
+    #[test]
+    fn test_encoding_service_budget() {
+        let config = ServiceConfig {
+            concurrency: 2,
+            retry_backoff: Duration::from_millis(50),
+            budget: Some(Duration::from_millis(10)),
+            ..Default::default()
+        };
+        let service = EncodingService::new(Arc::new(FlakyEncoder::default()), config);
+        let (solution_sender, solution_receiver) = mpsc::channel();
+        let (result_sender, result_receiver) = mpsc::channel();
+        let handle = service
+            .spawn(solution_receiver, result_sender)
+            .unwrap();
+
+        solution_sender
+            .send(Solution { given_amount: BigUint::from(1u32), ..Default::default() })
+            .unwrap();
+        drop(solution_sender);
+        let result = result_receiver.recv().unwrap();
+        let metrics = handle.join().unwrap();
+
+        assert!(matches!(result.result, Err(EncodingError::RecoverableError(_))));
+        assert_eq!(metrics.budget_exceeded, 1);
+        assert_eq!(metrics.retries, 0);
+    }
+}
@@ -1,3 +1,5 @@
+use async_trait::async_trait;
+
 use crate::encoding::{
     errors::EncodingError,
     models::{EncodedSolution, Solution},
@@ -5,7 +7,8 @@ use crate::encoding::{
 };
 
 /// A trait that defines how to encode a `Solution` for execution.
-pub trait StrategyEncoder {
+#[async_trait]
+pub trait StrategyEncoder: Send + Sync {
     /// `encode_strategy` takes a `Solution`, which contains all the necessary information about
     /// the swaps to be performed, and encodes it into a format that can be executed by the router
     /// or executor contracts.
@@ -18,6 +21,23 @@ pub trait StrategyEncoder {
     /// * `Result<EncodedSwaps, EncodingError>`
     fn encode_strategy(&self, solution: &Solution) -> Result<EncodedSolution, EncodingError>;
 
+    /// Returns the name of the strategy, labelling its encoding in the tracing spans and
+    /// `EncodingMetrics` of the encoder (e.g. `split_swap`).
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Encodes a `Solution` like [`StrategyEncoder::encode_strategy`], awaiting the swap encoders
+    /// (see `SwapEncoder::encode_swap_async`) instead of blocking on their data fetching.
+    ///
+    /// The default implementation calls `encode_strategy`.
+    async fn encode_strategy_async(
+        &self,
+        solution: &Solution,
+    ) -> Result<EncodedSolution, EncodingError> {
+        self.encode_strategy(solution)
+    }
+
     /// Retrieves the swap encoder for a specific protocol system.
     ///
     /// # Arguments
@@ -1,14 +1,37 @@
 use std::collections::HashMap;
 
+use async_trait::async_trait;
 use tycho_common::{models::Chain, Bytes};
 
 use crate::encoding::{
     errors::EncodingError,
-    models::{EncodingContext, Swap},
+    models::{EncodingContext, RfqConfig, Swap},
 };
 
+/// How the executor of a protocol takes its tokens and executes consecutive swaps, which drives
+/// the grouping of swaps (see `group_swaps`) and the transfer optimizations of the strategy
+/// encoders (see `TransferOptimization`).
+///
+/// # Fields
+/// * `groupable`: Consecutive swaps on the protocol can be encoded into one swap execution, e.g.
+///   because the protocol uses flash accounting (Uniswap V4, Balancer V3 or Ekubo).
+/// * `split_groupable`: The executor can also split a group's input token over several branches
+///   ending in the same token (see `group_split_branches`). Only relevant for groupable protocols.
+/// * `funds_in_router`: The executor expects the tokens in the router and transfers them to the
+///   pool itself, so they are never transferred to the pool beforehand.
+/// * `callback_constrained`: The tokens are transferred to the pool in a callback of the swap, so
+///   they can't be sent to it by the previous swap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncoderCapabilities {
+    pub groupable: bool,
+    pub split_groupable: bool,
+    pub funds_in_router: bool,
+    pub callback_constrained: bool,
+}
+
 /// A trait for protocol-specific swap encoding, where each implementation should handle the
 /// encoding logic for swaps on a specific protocol.
+#[async_trait]
 pub trait SwapEncoder: Sync + Send {
     /// Creates a new swap encoder for a specific protocol.
     ///
@@ -40,9 +63,69 @@ pub trait SwapEncoder: Sync + Send {
         encoding_context: &EncodingContext,
     ) -> Result<Vec<u8>, EncodingError>;
 
+    /// Encodes a swap like [`SwapEncoder::encode_swap`], awaiting any off-chain or on-chain data
+    /// the encoding depends on (e.g. RFQ quotes) instead of blocking the current thread.
+    ///
+    /// Encoders that don't fetch any data during encoding can rely on the default implementation,
+    /// which calls `encode_swap`.
+    async fn encode_swap_async(
+        &self,
+        swap: &Swap,
+        encoding_context: &EncodingContext,
+    ) -> Result<Vec<u8>, EncodingError> {
+        self.encode_swap(swap, encoding_context)
+    }
+
+    /// Returns a copy of the encoder requesting its RFQ quotes with the given timeout and retry
+    /// policy, or `None` if the encoder doesn't request any quote.
+    fn with_rfq_config(&self, _rfq_config: &RfqConfig) -> Option<Box<dyn SwapEncoder>> {
+        None
+    }
+
+    /// Packs the protocol data of a group splitting its input token over several branches into the
+    /// protocol data of one swap execution (see `group_split_branches`).
+    ///
+    /// Each branch is given with its split of the group's amount (0 for the remainder) and its
+    /// protocol data, packed like the one of a group of consecutive swaps. Only the encoders with
+    /// the `split_groupable` capability support split branches.
+    fn pack_split_branches(
+        &self,
+        _branches: Vec<(f64, Vec<u8>)>,
+    ) -> Result<Vec<u8>, EncodingError> {
+        Err(EncodingError::FatalError(
+            "Split branches within a swap group are not supported by this protocol".to_string(),
+        ))
+    }
+
+    /// Returns whether the executor ignores a user data segment (`user_data_length | user_data`)
+    /// appended to the protocol data, so that the user data of swaps can be passed through to the
+    /// chain (see `append_user_data`).
+    ///
+    /// Encoders of executors with a variable data length, or interpreting the swap's user data
+    /// themselves, don't accept it.
+    fn accepts_user_data_segment(&self) -> bool {
+        false
+    }
+
+    /// Returns the capabilities of the protocol's executor. Defaults to an executor taking the
+    /// tokens transferred to the pool before the swap, one swap at a time.
+    fn capabilities(&self) -> EncoderCapabilities {
+        EncoderCapabilities::default()
+    }
+
     /// Returns the address of the protocol-specific executor contract.
     fn executor_address(&self) -> &Bytes;
 
+    /// Returns the address of the executor contract performing the given group of consecutive
+    /// swaps (see `group_swaps`).
+    ///
+    /// Defaults to [`SwapEncoder::executor_address`]. Encoders routing some pools through another
+    /// executor (e.g. Uniswap V4 hooks only allowing specific senders) override it, and return an
+    /// `EncodingError::InvalidInput` if no single executor can perform all the swaps of the group.
+    fn group_executor_address(&self, _swaps: &[Swap]) -> Result<Bytes, EncodingError> {
+        Ok(self.executor_address().clone())
+    }
+
     /// Creates a cloned instance of the swap encoder.
     ///
     /// This allows the encoder to be cloned when it is being used as a `Box<dyn SwapEncoder>`.
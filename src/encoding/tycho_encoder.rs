@@ -1,8 +1,17 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::stream::{BoxStream, FuturesUnordered, StreamExt};
+
 use crate::encoding::{
     errors::EncodingError,
-    models::{EncodedSolution, Solution, Transaction},
+    models::{EncodedSolution, Environment, Solution, Transaction},
 };
 
+/// A stream of the results of encoding solutions, each with the index of its solution (see
+/// [`TychoEncoder::encode_solutions_streaming`]).
+pub type EncodingResultStream<'a> = BoxStream<'a, (usize, Result<EncodedSolution, EncodingError>)>;
+
 /// A high-level interface for encoding solutions into Tycho-compatible transactions or raw call
 /// data.
 ///
@@ -25,6 +34,7 @@ use crate::encoding::{
 /// Tycho is only responsible for generating the internal swap plan. **The user must encode the
 /// outer function call arguments themselves** and verify that they enforce correct and secure
 /// behavior.
+#[async_trait]
 pub trait TychoEncoder: Send + Sync {
     /// Encodes a list of [`Solution`]s into [`EncodedSolution`]s, which include the function
     /// signature and internal swap call data.
@@ -47,6 +57,110 @@ pub trait TychoEncoder: Send + Sync {
         solutions: Vec<Solution>,
     ) -> Result<Vec<EncodedSolution>, EncodingError>;
 
+    /// Encodes a list of [`Solution`]s like [`encode_solutions`], awaiting the data fetched during
+    /// encoding (e.g. RFQ quotes or allowances) instead of blocking the current thread.
+    ///
+    /// Prefer this method when encoding from an async context: swap encoders fetching data while
+    /// encoding (like the RFQ ones) await it, instead of blocking a runtime thread with
+    /// `block_in_place`, which panics on single-threaded Tokio runtimes.
+    ///
+    /// The default implementation calls `encode_solutions`.
+    async fn encode_solutions_async(
+        &self,
+        solutions: Vec<Solution>,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        self.encode_solutions(solutions)
+    }
+
+    /// Encodes a list of [`Solution`]s like [`encode_solutions_async`], encoding up to
+    /// `concurrency` solutions at once and isolating their failures.
+    ///
+    /// Use this method for large batches, where the data fetched while encoding (e.g. one RFQ
+    /// quote per swap) would otherwise be fetched one solution after the other.
+    ///
+    /// # Returns
+    /// The result of every solution, in the order of the given solutions: a failing solution
+    /// returns an error entry instead of failing the whole batch.
+    ///
+    /// The default implementation encodes the solutions one at a time with
+    /// `encode_solutions_async`.
+    async fn encode_solutions_concurrently(
+        &self,
+        solutions: Vec<Solution>,
+        concurrency: usize,
+    ) -> Vec<Result<EncodedSolution, EncodingError>> {
+        let _ = concurrency;
+        let mut results = Vec::with_capacity(solutions.len());
+        for solution in solutions {
+            let result = self
+                .encode_solutions_async(vec![solution])
+                .await
+                .and_then(|mut encoded| {
+                    encoded.pop().ok_or_else(|| {
+                        EncodingError::FatalError("No encoded solution returned".to_string())
+                    })
+                });
+            results.push(result);
+        }
+        results
+    }
+
+    /// Encodes a list of [`Solution`]s concurrently, yielding every result as soon as it is ready
+    /// instead of waiting for the whole batch, so that one slow solution (e.g. waiting for an RFQ
+    /// quote) doesn't delay the others.
+    ///
+    /// Each solution must be encoded within `deadline` of the call. A solution that isn't yields an
+    /// `EncodingError::Timeout`, and its encoding is dropped.
+    ///
+    /// # Returns
+    /// A stream of the index of a solution in the given solutions and its result, in the order the
+    /// solutions finish encoding. The stream ends once every solution yielded a result.
+    ///
+    /// The default implementation encodes every solution with `encode_solutions_async`.
+    fn encode_solutions_streaming(
+        &self,
+        solutions: Vec<Solution>,
+        deadline: Duration,
+    ) -> EncodingResultStream<'_> {
+        solutions
+            .into_iter()
+            .enumerate()
+            .map(|(index, solution)| async move {
+                let encode = self.encode_solutions_async(vec![solution]);
+                let result = match tokio::time::timeout(deadline, encode).await {
+                    Ok(result) => result.and_then(|mut encoded| {
+                        encoded.pop().ok_or_else(|| {
+                            EncodingError::FatalError("No encoded solution returned".to_string())
+                        })
+                    }),
+                    Err(_) => Err(EncodingError::Timeout(format!(
+                        "Solution {index} wasn't encoded within {deadline:?}"
+                    ))),
+                };
+                (index, result)
+            })
+            .collect::<FuturesUnordered<_>>()
+            .boxed()
+    }
+
+    /// Encodes a list of [`Solution`]s like [`encode_solutions`], using the router and executors
+    /// of the given [`Environment`].
+    ///
+    /// Encoders that only know a single environment treat it as `Production` and return an error
+    /// for any other environment.
+    fn encode_solutions_for_environment(
+        &self,
+        solutions: Vec<Solution>,
+        environment: Environment,
+    ) -> Result<Vec<EncodedSolution>, EncodingError> {
+        match environment {
+            Environment::Production => self.encode_solutions(solutions),
+            _ => Err(EncodingError::InvalidInput(format!(
+                "The {environment:?} environment is not configured for this encoder"
+            ))),
+        }
+    }
+
     /// Encodes a list of [`Solution`]s directly into executable transactions for the Tycho router.
     ///
     /// This method wraps around Tycho’s example encoding logic (see [`encode_tycho_router_call`])
@@ -1 +1,3 @@
 pub mod encoding;
+#[cfg(feature = "python")]
+pub mod python;
@@ -0,0 +1,344 @@
+// This module is only compiled with the `python` feature.
+//! Python bindings of the encoder API.
+//!
+//! Built into the `tycho_execution` Python module with maturin (see `pyproject.toml`):
+//!
+//! ```python
+//! from tycho_execution import Solution, Swap, TychoRouterEncoderBuilder
+//!
+//! encoder = TychoRouterEncoderBuilder().chain("ethereum").user_transfer_type("transfer-from").build()
+//! swap = Swap(pool_address, "uniswap_v2", weth, usdc)
+//! solution = Solution(sender, receiver, weth, 10**18, usdc, 3_000 * 10**6, [swap])
+//! encoded = encoder.encode_solutions([solution])[0]
+//! print(encoded.function_signature, encoded.swaps)
+//! ```
+//!
+//! Addresses and other bytes are given and returned as hex strings, and amounts as Python ints.
+
+// The code generated by pyo3 0.22's macros checks pyo3's `gil-refs` feature, unknown to this crate
+// (`create_exception!`), and converts the `PyErr` of every `PyResult` into itself (`#[pymethods]`).
+// pyo3 0.23 removed the `gil-refs` feature and the conversion. The lints are allowed in this
+// module only, until pyo3 can be bumped.
+#![allow(unexpected_cfgs, clippy::useless_conversion)]
+use std::{collections::HashMap, str::FromStr};
+
+use clap::ValueEnum;
+use num_bigint::BigUint;
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+};
+use tycho_common::{
+    models::{protocol::ProtocolComponent, Chain},
+    Bytes,
+};
+
+use crate::encoding::{
+    errors,
+    evm::{
+        encoder_builders::TychoRouterEncoderBuilder,
+        swap_encoder::swap_encoder_registry::SwapEncoderRegistry,
+    },
+    models::{EncodedSolution, Solution, Swap, UserTransferType},
+    tycho_encoder::TychoEncoder,
+};
+
+create_exception!(tycho_execution, EncodingError, PyException);
+
+fn to_py_err(err: errors::EncodingError) -> PyErr {
+    EncodingError::new_err(err.to_string())
+}
+
+fn parse_bytes(value: &str, name: &str) -> PyResult<Bytes> {
+    Bytes::from_str(value).map_err(|_| PyValueError::new_err(format!("Invalid {name}: {value}")))
+}
+
+/// A swap of a solution, see [`Swap`].
+#[pyclass(name = "Swap", module = "tycho_execution")]
+#[derive(Clone)]
+pub struct PySwap {
+    swap: Swap,
+}
+
+#[pymethods]
+impl PySwap {
+    #[new]
+    #[pyo3(signature = (
+        component_id,
+        protocol_system,
+        token_in,
+        token_out,
+        split=0.0,
+        static_attributes=None,
+        user_data=None,
+    ))]
+    fn new(
+        component_id: String,
+        protocol_system: String,
+        token_in: &str,
+        token_out: &str,
+        split: f64,
+        static_attributes: Option<HashMap<String, String>>,
+        user_data: Option<&str>,
+    ) -> PyResult<Self> {
+        let static_attributes = static_attributes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, value)| {
+                let value = parse_bytes(&value, "static attribute")?;
+                Ok((name, value))
+            })
+            .collect::<PyResult<HashMap<String, Bytes>>>()?;
+        let component = ProtocolComponent {
+            id: component_id,
+            protocol_system,
+            static_attributes,
+            ..Default::default()
+        };
+        let mut swap = Swap::new(
+            component,
+            parse_bytes(token_in, "token in")?,
+            parse_bytes(token_out, "token out")?,
+        )
+        .split(split);
+        if let Some(user_data) = user_data {
+            swap = swap.user_data(parse_bytes(user_data, "user data")?);
+        }
+        Ok(Self { swap })
+    }
+
+    /// Parses a swap from its JSON representation, the same as in a JSON solution.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let swap = serde_json::from_str(json)
+            .map_err(|err| PyValueError::new_err(format!("Invalid swap: {err}")))?;
+        Ok(Self { swap })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.swap)
+    }
+}
+
+/// A solution to encode, see [`Solution`].
+#[pyclass(name = "Solution", module = "tycho_execution")]
+#[derive(Clone)]
+pub struct PySolution {
+    solution: Solution,
+}
+
+#[pymethods]
+impl PySolution {
+    #[new]
+    #[pyo3(signature = (
+        sender,
+        receiver,
+        given_token,
+        given_amount,
+        checked_token,
+        checked_amount,
+        swaps,
+        exact_out=false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        sender: &str,
+        receiver: &str,
+        given_token: &str,
+        given_amount: BigUint,
+        checked_token: &str,
+        checked_amount: BigUint,
+        swaps: Vec<PySwap>,
+        exact_out: bool,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            solution: Solution {
+                sender: parse_bytes(sender, "sender")?,
+                receiver: parse_bytes(receiver, "receiver")?,
+                given_token: parse_bytes(given_token, "given token")?,
+                given_amount,
+                checked_token: parse_bytes(checked_token, "checked token")?,
+                checked_amount,
+                exact_out,
+                swaps: swaps
+                    .into_iter()
+                    .map(|swap| swap.swap)
+                    .collect(),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Parses a solution from its JSON representation, like the input of the `tycho-encode`
+    /// binary.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let solution = serde_json::from_str(json)
+            .map_err(|err| PyValueError::new_err(format!("Invalid solution: {err}")))?;
+        Ok(Self { solution })
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.solution)
+            .map_err(|err| PyValueError::new_err(format!("Failed to serialize solution: {err}")))
+    }
+}
+
+/// An encoded solution, see [`EncodedSolution`].
+#[pyclass(name = "EncodedSolution", module = "tycho_execution", frozen)]
+pub struct PyEncodedSolution {
+    encoded_solution: EncodedSolution,
+}
+
+#[pymethods]
+impl PyEncodedSolution {
+    /// The encoded swaps, as a hex string.
+    #[getter]
+    fn swaps(&self) -> String {
+        format!("0x{}", hex::encode(&self.encoded_solution.swaps))
+    }
+
+    /// The address of the contract to call, as a hex string.
+    #[getter]
+    fn interacting_with(&self) -> String {
+        self.encoded_solution
+            .interacting_with
+            .to_string()
+    }
+
+    #[getter]
+    fn function_signature(&self) -> &str {
+        &self.encoded_solution.function_signature
+    }
+
+    #[getter]
+    fn n_tokens(&self) -> usize {
+        self.encoded_solution.n_tokens
+    }
+
+    #[getter]
+    fn gas_estimate(&self) -> u64 {
+        self.encoded_solution.gas_estimate
+    }
+}
+
+/// Builds a [`PyTychoRouterEncoder`], see [`TychoRouterEncoderBuilder`].
+#[pyclass(name = "TychoRouterEncoderBuilder", module = "tycho_execution")]
+#[derive(Default)]
+pub struct PyTychoRouterEncoderBuilder {
+    chain: Option<Chain>,
+    user_transfer_type: Option<UserTransferType>,
+    router_address: Option<Bytes>,
+    executors_addresses: Option<String>,
+}
+
+#[pymethods]
+impl PyTychoRouterEncoderBuilder {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the chain, by its name (e.g. `ethereum`).
+    fn chain<'py>(mut slf: PyRefMut<'py, Self>, chain: &str) -> PyResult<PyRefMut<'py, Self>> {
+        let chain = serde_json::from_value(serde_json::Value::String(chain.to_string()))
+            .map_err(|_| PyValueError::new_err(format!("Invalid chain: {chain}")))?;
+        slf.chain = Some(chain);
+        Ok(slf)
+    }
+
+    /// Sets the user transfer type, by its name in the `tycho-encode` binary (`transfer-from`,
+    /// `transfer-from-permit2` or `none`).
+    fn user_transfer_type<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        user_transfer_type: &str,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let user_transfer_type = UserTransferType::from_str(user_transfer_type, true)
+            .map_err(|err| PyValueError::new_err(format!("Invalid user transfer type: {err}")))?;
+        slf.user_transfer_type = Some(user_transfer_type);
+        Ok(slf)
+    }
+
+    fn router_address<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        router_address: &str,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.router_address = Some(parse_bytes(router_address, "router address")?);
+        Ok(slf)
+    }
+
+    /// Sets the executors' addresses, as the JSON content of an executors' addresses file. The
+    /// default addresses are used if not set.
+    fn executors_addresses<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        executors_addresses: String,
+    ) -> PyRefMut<'py, Self> {
+        slf.executors_addresses = Some(executors_addresses);
+        slf
+    }
+
+    fn build(&self) -> PyResult<PyTychoRouterEncoder> {
+        let chain = self
+            .chain
+            .ok_or_else(|| EncodingError::new_err("Please set the chain before building"))?;
+        let swap_encoder_registry = SwapEncoderRegistry::new(chain)
+            .add_default_encoders(self.executors_addresses.clone())
+            .map_err(to_py_err)?;
+        let mut builder = TychoRouterEncoderBuilder::new()
+            .chain(chain)
+            .swap_encoder_registry(swap_encoder_registry);
+        if let Some(user_transfer_type) = &self.user_transfer_type {
+            builder = builder.user_transfer_type(user_transfer_type.clone());
+        }
+        if let Some(router_address) = &self.router_address {
+            builder = builder.router_address(router_address.clone());
+        }
+        Ok(PyTychoRouterEncoder { encoder: builder.build().map_err(to_py_err)? })
+    }
+}
+
+/// Encodes solutions for the Tycho router, see [`TychoEncoder`].
+#[pyclass(name = "TychoRouterEncoder", module = "tycho_execution", frozen)]
+pub struct PyTychoRouterEncoder {
+    encoder: Box<dyn TychoEncoder>,
+}
+
+#[pymethods]
+impl PyTychoRouterEncoder {
+    /// Encodes the solutions, releasing the GIL while encoding (RFQ quotes may be requested).
+    fn encode_solutions(
+        &self,
+        py: Python<'_>,
+        solutions: Vec<PySolution>,
+    ) -> PyResult<Vec<PyEncodedSolution>> {
+        let solutions = solutions
+            .into_iter()
+            .map(|solution| solution.solution)
+            .collect();
+        let encoded_solutions = py
+            .allow_threads(|| self.encoder.encode_solutions(solutions))
+            .map_err(to_py_err)?;
+        Ok(encoded_solutions
+            .into_iter()
+            .map(|encoded_solution| PyEncodedSolution { encoded_solution })
+            .collect())
+    }
+
+    fn validate_solution(&self, solution: &PySolution) -> PyResult<()> {
+        self.encoder
+            .validate_solution(&solution.solution)
+            .map_err(to_py_err)
+    }
+}
+
+#[pymodule]
+fn tycho_execution(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySwap>()?;
+    m.add_class::<PySolution>()?;
+    m.add_class::<PyEncodedSolution>()?;
+    m.add_class::<PyTychoRouterEncoderBuilder>()?;
+    m.add_class::<PyTychoRouterEncoder>()?;
+    m.add("EncodingError", m.py().get_type_bound::<EncodingError>())?;
+    Ok(())
+}
@@ -225,6 +225,31 @@ pub fn encode_tycho_router_call(
     Ok(Transaction { to: encoded_solution.interacting_with, value, data: contract_interaction })
 }
 
+/// Encodes a transaction for the Tycho Router like `encode_tycho_router_call`, wrapping or
+/// unwrapping according to the native action of the encoded solution instead of the solution's.
+///
+/// The encoded solution's native action includes the wrap or unwrap steps inserted while encoding,
+/// e.g. to align an RFQ quote in the wrapped token with a solution giving the native token.
+pub fn encode_tycho_router_call_with_encoded_native_action(
+    chain_id: u64,
+    encoded_solution: EncodedSolution,
+    solution: &Solution,
+    user_transfer_type: &UserTransferType,
+    native_address: &Bytes,
+    signer: Option<PrivateKeySigner>,
+) -> Result<Transaction, EncodingError> {
+    let solution =
+        Solution { native_action: encoded_solution.native_action.clone(), ..solution.clone() };
+    encode_tycho_router_call(
+        chain_id,
+        encoded_solution,
+        &solution,
+        user_transfer_type,
+        native_address,
+        signer,
+    )
+}
+
 /// Signs a Permit2 `PermitSingle` struct using the EIP-712 signing scheme.
 ///
 /// This function constructs an EIP-712 domain specific to the Permit2 contract and computes the
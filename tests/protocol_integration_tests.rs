@@ -9,7 +9,7 @@ use tycho_execution::encoding::{
         testing_utils::MockRFQState,
         utils::{biguint_to_u256, write_calldata_to_file},
     },
-    models::{Solution, Swap, UserTransferType},
+    models::{NativeAction, Solution, Swap, UserTransferType},
 };
 
 use crate::common::{
@@ -802,6 +802,74 @@ fn test_single_encoding_strategy_curve_st_eth() {
     write_calldata_to_file("test_single_encoding_strategy_curve_st_eth", hex_calldata.as_str());
 }
 
+#[test]
+fn test_single_encoding_strategy_curve_st_eth_with_wrap() {
+    //   ETH ──(wrap)──> WETH ──(curve stETH pool)──> STETH
+    //
+    // The stETH pool takes raw ETH, so the wrap is dropped and the ETH sent with the call is
+    // forwarded to the pool.
+
+    let token_out = Bytes::from("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84"); // STETH
+
+    let static_attributes = HashMap::from([(
+        "factory".to_string(),
+        Bytes::from(
+            "0x0000000000000000000000000000000000000000"
+                .as_bytes()
+                .to_vec(),
+        ),
+    ),
+        ("coins".to_string(), Bytes::from_str("0x5b22307865656565656565656565656565656565656565656565656565656565656565656565656565656565222c22307861653761623936353230646533613138653565313131623565616162303935333132643766653834225d").unwrap()),]);
+
+    let component = ProtocolComponent {
+        id: String::from("0xDC24316b9AE028F1497c275EB9192a3Ea0f67022"),
+        protocol_system: String::from("vm:curve"),
+        static_attributes,
+        ..Default::default()
+    };
+
+    let swap = Swap::new(component, weth(), token_out.clone());
+
+    let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+
+    let solution = Solution {
+        exact_out: false,
+        given_token: eth(),
+        given_amount: BigUint::from_str("1_000000000000000000").unwrap(),
+        checked_token: token_out,
+        checked_amount: BigUint::from_str("1").unwrap(),
+        // Alice
+        sender: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+        receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
+        swaps: vec![swap],
+        native_action: Some(NativeAction::Wrap),
+        ..Default::default()
+    };
+
+    let encoded_solution = encoder
+        .encode_solutions(vec![solution.clone()])
+        .unwrap()[0]
+        .clone();
+    assert_eq!(encoded_solution.native_action, None);
+
+    let transaction = encode_tycho_router_call(
+        eth_chain().id(),
+        encoded_solution,
+        &solution,
+        &UserTransferType::TransferFrom,
+        &eth(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(transaction.value, BigUint::from_str("1_000000000000000000").unwrap());
+
+    let hex_calldata = encode(&transaction.data);
+    write_calldata_to_file(
+        "test_single_encoding_strategy_curve_st_eth_with_wrap",
+        hex_calldata.as_str(),
+    );
+}
+
 #[test]
 fn test_single_encoding_strategy_balancer_v3() {
     // steakUSDTlite -> (balancer v3) -> steakUSDR
@@ -899,6 +967,9 @@ fn test_single_encoding_strategy_bebop() {
         sender: user.clone(),
         receiver: user,
         swaps: vec![swap],
+        // The quote expired since it was signed, so its expiry is checked against the time it was
+        // executed at instead
+        execution_deadline: Some(1769092400),
         ..Default::default()
     };
 
@@ -1075,6 +1146,9 @@ fn test_single_encoding_strategy_hashflow() {
         sender: alice_address(),
         receiver: alice_address(),
         swaps: vec![swap_usdc_wbtc],
+        // The quote expired since it was signed, so its expiry is checked against the time it was
+        // executed at instead
+        execution_deadline: Some(1769092600),
         ..Default::default()
     };
 
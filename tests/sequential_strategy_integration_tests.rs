@@ -5,7 +5,10 @@ use alloy::hex::encode;
 use num_bigint::{BigInt, BigUint};
 use tycho_common::{models::protocol::ProtocolComponent, Bytes};
 use tycho_execution::encoding::{
-    evm::utils::write_calldata_to_file,
+    evm::{
+        testing_utils::{MockAmmState, ScenarioBuilder},
+        utils::write_calldata_to_file,
+    },
     models::{NativeAction, Solution, Swap, UserTransferType},
 };
 
@@ -343,6 +346,7 @@ fn test_sequential_swap_strategy_encoder_unwrap() {
         receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
         swaps: vec![swap_usdc_wbtc, swap_wbtc_weth],
         native_action: Some(NativeAction::Unwrap),
+        ..Default::default()
     };
 
     let encoded_solution = encoder
@@ -364,3 +368,59 @@ fn test_sequential_swap_strategy_encoder_unwrap() {
     let hex_calldata = encode(&calldata);
     write_calldata_to_file("test_sequential_swap_strategy_encoder_unwrap", hex_calldata.as_str());
 }
+
+#[test]
+fn test_sequential_swap_strategy_encoder_mock_scenario() {
+    // Encodes a solution built offline from mock AMM states
+    //
+    //   WETH ───(USV2)──> WBTC ───(USV2)──> USDC
+
+    let weth_wbtc = MockAmmState::constant_product(
+        weth(),
+        BigUint::from_str("1_000_000000000000000000").unwrap(),
+        wbtc(),
+        BigUint::from_str("30_00000000").unwrap(),
+    )
+    .fee_bps(30);
+    let wbtc_usdc = MockAmmState::constant_product(
+        wbtc(),
+        BigUint::from_str("100_00000000").unwrap(),
+        usdc(),
+        BigUint::from_str("8_000_000_000000").unwrap(),
+    )
+    .fee_bps(30);
+    let component = |id: &str| ProtocolComponent {
+        id: id.to_string(),
+        protocol_system: "uniswap_v2".to_string(),
+        ..Default::default()
+    };
+
+    let solution = ScenarioBuilder::new(weth(), BigUint::from_str("1_000000000000000000").unwrap())
+        .hop(component("0xBb2b8038a1640196FbE3e38816F3e67Cba72D940"), wbtc(), weth_wbtc)
+        .hop(component("0x004375Dff511095CC5A197A54140a24eFEF3A416"), usdc(), wbtc_usdc)
+        .sender(Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap())
+        .receiver(Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap())
+        .slippage_bps(50)
+        .build()
+        .unwrap();
+
+    let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+    let encoded_solution = encoder
+        .encode_solutions(vec![solution.clone()])
+        .unwrap()[0]
+        .clone();
+
+    assert_eq!(
+        encoded_solution.function_signature,
+        "sequentialSwap(uint256,address,address,uint256,bool,bool,address,bool,bytes)"
+    );
+    assert!(encode_tycho_router_call(
+        eth_chain().id(),
+        encoded_solution,
+        &solution,
+        &UserTransferType::TransferFrom,
+        &eth(),
+        None,
+    )
+    .is_ok());
+}
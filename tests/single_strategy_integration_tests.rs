@@ -289,6 +289,7 @@ fn test_single_swap_strategy_encoder_wrap() {
         receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
         swaps: vec![swap],
         native_action: Some(NativeAction::Wrap),
+        ..Default::default()
     };
 
     let encoded_solution = encoder
@@ -339,6 +340,7 @@ fn test_single_swap_strategy_encoder_unwrap() {
         receiver: Bytes::from_str("0xcd09f75E2BF2A4d11F3AB23f1389FcC1621c0cc2").unwrap(),
         swaps: vec![swap],
         native_action: Some(NativeAction::Unwrap),
+        ..Default::default()
     };
 
     let encoded_solution = encoder
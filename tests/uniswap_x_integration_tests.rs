@@ -1,11 +1,19 @@
 use std::{collections::HashMap, str::FromStr};
 
-use alloy::{hex::encode, primitives::Address, sol_types::SolValue};
+use alloy::{
+    hex::encode,
+    primitives::{Address, Bytes as AlloyBytes, U256},
+    sol_types::SolValue,
+};
 use num_bigint::{BigInt, BigUint};
 use tycho_common::{models::protocol::ProtocolComponent, Bytes};
 use tycho_execution::encoding::{
     evm::{
         approvals::protocol_approvals_manager::ProtocolApprovalsManager,
+        uniswap_x::{
+            batch_fill_amounts, callback_data, encode_filler_call, resolve_order, FillContext,
+            ReactorVersion, SignedOrder,
+        },
         utils::{bytes_to_address, write_calldata_to_file},
     },
     models::{Solution, Swap, UserTransferType},
@@ -117,3 +125,95 @@ fn test_sequential_swap_usx() {
     let hex_calldata = encode(&full_calldata);
     write_calldata_to_file("test_sequential_swap_usx", hex_calldata.as_str());
 }
+
+fn usv3_swap(id: &str, token_in: &Bytes, token_out: &Bytes) -> Swap {
+    Swap::new(
+        ProtocolComponent {
+            id: id.to_string(),
+            protocol_system: "uniswap_v3".to_string(),
+            static_attributes: {
+                let mut attrs = HashMap::new();
+                attrs
+                    .insert("fee".to_string(), Bytes::from(BigInt::from(100).to_signed_bytes_be()));
+                attrs
+            },
+            ..Default::default()
+        },
+        token_in.clone(),
+        token_out.clone(),
+    )
+}
+
+#[test]
+fn test_batch_swap_usx() {
+    // Fills two DAI -> USDT V2 Dutch orders with a single router call, selling the inputs of both
+    // orders at once. The order is the one settled in tx:
+    // 0x005d7b150017ba1b59d2f99395ccae7bda9b739938ade4e509817e32760aaf9d
+    //
+    //   DAI ───(USV3)──> USDC ───(USV3)──> USDT
+    let filler = Bytes::from_str("0x1b435D322a41287C2e5b3b2E1469709e3ab13867").unwrap();
+    let order = SignedOrder {
+        order: AlloyBytes::from_str("0x000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000001000000000000000000000000004449cd34d1eb1fedcf02a1be3834ffde8e6a61800000000000000000000000006b175474e89094c44da98b954eedeac495271d0f00000000000000000000000000000000000000000000006c6b935b8bbd40000000000000000000000000000000000000000000000000006c6b935b8bbd40000000000000000000000000000000000000000000000000000000000000000001e00000000000000000000000000000000000000000000000000000000000000280000000000000000000000000000000000000000000000000000000000000038000000000000000000000000000000011f84b9aa48e5f8aa8b9897600006289be000000000000000000000000d213e6f6dcb2dbac03fa28b893f6da1bd822e8520468320351debb1ddbfb032a239d699e3d54e3ce2b6e1037cd836a784c80b60100000000000000000000000000000000000000000000000000000000686e2bf9000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000c000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001000000000000000000000000dac17f958d2ee523a2206206994597c13d831ec70000000000000000000000000000000000000000000000000000000076f9f4870000000000000000000000000000000000000000000000000000000076566300000000000000000000000000d213e6f6dcb2dbac03fa28b893f6da1bd822e85200000000000000000000000000000000000000000000000000000000686e2aee00000000000000000000000000000000000000000000000000000000686e2b2a000000000000000000000000ce79b081c0c924cb67848723ed3057234d10fc6b0000000000000000000000000000000000000000000000000000000000000064000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000c00000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000007727b5f40000000000000000000000000000000000000000000000000000000000000041a2d261cd4c8930428260f18b55e3036024bac68d58cb2ee6161e6395b0984b827104158713d44ddc4e14d852b48d93d95a4e60b8d5be1ef431c1e82d2f76a4111b00000000000000000000000000000000000000000000000000000000000000").unwrap(),
+        sig: AlloyBytes::from_str("0xf4cc5734820e4ee08519045c83a25b75687756053b3d6c0fda2141380dfa6ef17b40f64d9279f237e96982c6ba53a202e01a4358fd66e027c9bdf200d5626f441c").unwrap(),
+    };
+
+    // Resolved at the end of the exclusivity period of the order
+    let context = FillContext {
+        filler: bytes_to_address(&filler).unwrap(),
+        timestamp: 1752050415,
+        ..Default::default()
+    };
+    let resolved = resolve_order(ReactorVersion::V2Dutch, &order.order, &context).unwrap();
+    assert_eq!(resolved.input.amount, U256::from(2_000u64) * U256::from(10u64).pow(U256::from(18)));
+    assert_eq!(resolved.outputs[0].amount, U256::from(1_998_862_551u64));
+
+    let (total_in, total_out) = batch_fill_amounts(&[resolved.clone(), resolved]).unwrap();
+    assert_eq!(total_out.amount, U256::from(3_997_725_102u64));
+
+    let dai = dai();
+    let usdc = usdc();
+    let usdt = usdt();
+    let solution = Solution {
+        exact_out: false,
+        given_token: dai.clone(),
+        given_amount: BigUint::from_bytes_be(&total_in.amount.to_be_bytes::<32>()),
+        checked_token: usdt.clone(),
+        checked_amount: BigUint::from_bytes_be(&total_out.amount.to_be_bytes::<32>()),
+        sender: filler.clone(),
+        receiver: filler.clone(),
+        swaps: vec![
+            usv3_swap("0x5777d92f208679DB4b9778590Fa3CAB3aC9e2168", &dai, &usdc),
+            usv3_swap("0x3416cF6C708Da44DB2624D63ea0AAef7113527C6", &usdc, &usdt),
+        ],
+        ..Default::default()
+    };
+
+    let encoder = get_tycho_router_encoder(UserTransferType::TransferFrom);
+    let encoded_solution = encoder
+        .encode_solutions(vec![solution.clone()])
+        .unwrap()[0]
+        .clone();
+    let tycho_calldata = encode_tycho_router_call(
+        eth_chain().id(),
+        encoded_solution,
+        &solution,
+        &UserTransferType::TransferFrom,
+        &eth(),
+        None,
+    )
+    .unwrap()
+    .data;
+
+    let callback = callback_data(true, true, &tycho_calldata);
+    let filler_calldata = encode_filler_call(None, vec![order.clone(), order], callback).unwrap();
+
+    // executeBatch((bytes,bytes)[],bytes)
+    let (orders, decoded_callback) =
+        <(Vec<SignedOrder>, AlloyBytes)>::abi_decode_params(&filler_calldata[4..]).unwrap();
+    assert_eq!(orders.len(), 2);
+    assert_eq!(decoded_callback[..2], [1, 1]);
+    assert_eq!(decoded_callback[2..], tycho_calldata[..]);
+
+    let hex_calldata = encode(&filler_calldata);
+    write_calldata_to_file("test_batch_swap_usx", hex_calldata.as_str());
+}